@@ -1,10 +1,19 @@
+use super::non_empty;
 use crate::database;
 use crate::error::{ReaderError, Result};
+use crate::models::{Annotation, NewAnnotation, TextAnchor};
+use crate::parsers::{self, ExternalHighlight};
+use crate::search;
+use rusqlite::Connection;
+use std::collections::HashSet;
 use tauri::AppHandle;
 
+pub(super) const STYLE_HIGHLIGHT: &str = "highlight";
 const STYLE_SINGLE_UNDERLINE: &str = "single_underline";
 const STYLE_DOUBLE_UNDERLINE: &str = "double_underline";
 const STYLE_WAVY_STRIKETHROUGH: &str = "wavy_strikethrough";
+pub(super) const ANNOTATION_COLORS: &[&str] =
+    &["yellow", "green", "blue", "pink", "purple", "orange"];
 
 #[derive(Clone, serde::Serialize)]
 pub struct AnnotationOutput {
@@ -12,87 +21,422 @@ pub struct AnnotationOutput {
     pub paragraph_id: String,
     pub selected_text: String,
     pub style: String,
+    pub color: Option<String>,
     pub note: Option<String>,
+    pub tags: Vec<String>,
+    pub anchor: Option<TextAnchor>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
-#[tauri::command]
-pub async fn list_annotations(
-    app_handle: AppHandle,
-    paragraph_ids: Vec<String>,
-) -> Result<Vec<AnnotationOutput>> {
-    let conn = database::get_connection(&app_handle)?;
-    let annotations = database::list_annotations_by_paragraph_ids(&conn, &paragraph_ids)?;
-    Ok(annotations
-        .into_iter()
-        .map(|item| AnnotationOutput {
+impl From<Annotation> for AnnotationOutput {
+    fn from(item: Annotation) -> Self {
+        Self {
             id: item.id,
             paragraph_id: item.paragraph_id,
             selected_text: item.selected_text,
             style: item.style,
+            color: item.color,
             note: item.note,
+            tags: item.tags,
+            anchor: item.anchor,
             created_at: item.created_at,
             updated_at: item.updated_at,
-        })
+        }
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct CreateAnnotationRequest {
+    pub paragraph_id: String,
+    pub selected_text: String,
+    pub style: String,
+    pub color: Option<String>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Character offsets of the selection in the paragraph text, as seen by
+    /// the reader; the selection is looked up near them when they don't match
+    pub start_offset: Option<usize>,
+    pub end_offset: Option<usize>,
+}
+
+#[tauri::command]
+pub async fn list_annotations(
+    app_handle: AppHandle,
+    paragraph_ids: Vec<String>,
+) -> Result<Vec<AnnotationOutput>> {
+    let annotations = database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_annotations_by_paragraph_ids(
+            conn,
+            &paragraph_ids,
+        )?)
+    })
+    .await?;
+    Ok(annotations
+        .into_iter()
+        .map(AnnotationOutput::from)
         .collect())
 }
 
 #[tauri::command]
 pub async fn create_annotation(
     app_handle: AppHandle,
-    paragraph_id: String,
-    selected_text: String,
-    style: String,
-    note: Option<String>,
+    request: CreateAnnotationRequest,
 ) -> Result<AnnotationOutput> {
-    let text = selected_text.trim().to_string();
+    let text = request.selected_text.trim().to_string();
     if text.is_empty() {
         return Err(ReaderError::InvalidArgument(
             "Selected text cannot be empty".to_string(),
         ));
     }
-    if !matches!(
-        style.as_str(),
-        STYLE_SINGLE_UNDERLINE | STYLE_DOUBLE_UNDERLINE | STYLE_WAVY_STRIKETHROUGH
+    validate_style(&request.style)?;
+    let color = non_empty(request.color);
+    validate_color(color.as_deref())?;
+
+    database::run_blocking(&app_handle, move |conn| {
+        let paragraph = database::get_paragraph(conn, &request.paragraph_id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Paragraph {}", request.paragraph_id)))?;
+        let hint = match (request.start_offset, request.end_offset) {
+            (Some(start), Some(end)) => search::anchor_at(&paragraph.text, start, end),
+            _ => None,
+        };
+        let anchor = search::resolve_anchor(&paragraph.text, &text, hint.as_ref());
+        // The annotation and its tags are written together
+        let tx = conn.unchecked_transaction()?;
+        let created = database::insert_annotation(
+            &tx,
+            &NewAnnotation {
+                paragraph_id: paragraph.id,
+                selected_text: text,
+                style: request.style,
+                color,
+                note: non_empty(request.note),
+                tags: request.tags,
+                anchor,
+            },
+        )?;
+        tx.commit()?;
+        Ok(created.into())
+    })
+    .await
+}
+
+/// Changes an annotation's style, colour, note or tags
+///
+/// Omitted fields are kept; an empty colour or note clears it.
+#[tauri::command]
+pub async fn update_annotation(
+    app_handle: AppHandle,
+    id: String,
+    style: Option<String>,
+    color: Option<String>,
+    note: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<AnnotationOutput> {
+    if let Some(style) = style.as_deref() {
+        validate_style(style)?;
+    }
+    validate_color(color.as_deref().filter(|color| !color.trim().is_empty()))?;
+
+    database::run_blocking(&app_handle, move |conn| {
+        let current = database::get_annotation(conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Annotation {}", id)))?;
+        let style = style.unwrap_or(current.style);
+        let color = color.map_or(current.color, |color| non_empty(Some(color)));
+        let note = note.map_or(current.note, |note| non_empty(Some(note)));
+        let tx = conn.unchecked_transaction()?;
+        database::update_annotation(&tx, &id, &style, color.as_deref(), note.as_deref())?;
+        if let Some(tags) = tags {
+            database::set_annotation_tags(&tx, &id, &tags)?;
+        }
+        tx.commit()?;
+        database::get_annotation(conn, &id)?
+            .map(AnnotationOutput::from)
+            .ok_or_else(|| ReaderError::NotFound(format!("Annotation {}", id)))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_annotation(app_handle: AppHandle, id: String) -> Result<()> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::delete_annotation(conn, &id)?)
+    })
+    .await
+}
+
+pub(super) fn validate_style(style: &str) -> Result<()> {
+    if matches!(
+        style,
+        STYLE_HIGHLIGHT
+            | STYLE_SINGLE_UNDERLINE
+            | STYLE_DOUBLE_UNDERLINE
+            | STYLE_WAVY_STRIKETHROUGH
     ) {
-        return Err(ReaderError::InvalidArgument(format!(
+        Ok(())
+    } else {
+        Err(ReaderError::InvalidArgument(format!(
             "Unsupported annotation style: {}",
             style
-        )));
+        )))
+    }
+}
+
+fn validate_color(color: Option<&str>) -> Result<()> {
+    super::validate_color(color, ANNOTATION_COLORS, "annotation")
+}
+
+/// Minimum title score for a library document to count as the highlighted book
+pub(super) const MIN_TITLE_SIMILARITY: f32 = 0.75;
+/// Paragraph ids per `list_annotations_by_paragraph_ids` query
+const PARAGRAPH_ID_BATCH: usize = 500;
+
+#[derive(Clone, serde::Serialize)]
+pub struct UnmatchedHighlightOutput {
+    pub book_title: String,
+    pub text: String,
+    pub location: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct HighlightImportOutput {
+    pub total: usize,
+    pub imported: usize,
+    pub skipped_duplicates: usize,
+    /// Titles of the documents that received annotations
+    pub documents: Vec<String>,
+    pub unmatched: Vec<UnmatchedHighlightOutput>,
+}
+
+/// Imports Kindle `My Clippings.txt` or KOReader highlights as annotations
+///
+/// Each book is matched to a library document by title and author, unless
+/// `doc_id` names the document to use; each highlight is then located in the
+/// document's paragraphs. Highlights that can't be placed are reported back.
+#[tauri::command]
+pub async fn import_highlights(
+    app_handle: AppHandle,
+    file_path: String,
+    doc_id: Option<String>,
+) -> Result<HighlightImportOutput> {
+    let highlights = parsers::read_highlights(&file_path)?;
+    let output = database::run_blocking(&app_handle, move |conn| {
+        place_highlights(conn, &highlights, doc_id.as_deref())
+    })
+    .await?;
+
+    tracing::info!(
+        "Imported {} of {} highlights from {} ({} unmatched)",
+        output.imported,
+        output.total,
+        file_path,
+        output.unmatched.len()
+    );
+    Ok(output)
+}
+
+/// Matches highlights to documents and adds them as annotations, one
+/// transaction per document
+fn place_highlights(
+    conn: &Connection,
+    highlights: &[ExternalHighlight],
+    doc_id: Option<&str>,
+) -> Result<HighlightImportOutput> {
+    let documents = database::list_documents(conn)?;
+    if let Some(id) = doc_id {
+        if !documents.iter().any(|doc| doc.id == id) {
+            return Err(ReaderError::NotFound(format!("Document {}", id)));
+        }
     }
 
-    let cleaned_note = note.and_then(|v| {
-        let trimmed = v.trim().to_string();
-        if trimmed.is_empty() {
-            None
+    let mut output = HighlightImportOutput {
+        total: highlights.len(),
+        imported: 0,
+        skipped_duplicates: 0,
+        documents: Vec::new(),
+        unmatched: Vec::new(),
+    };
+    let unmatched = |highlight: &ExternalHighlight, reason: &str| UnmatchedHighlightOutput {
+        book_title: highlight.book_title.clone(),
+        text: if highlight.text.is_empty() {
+            highlight.note.clone().unwrap_or_default()
         } else {
-            Some(trimmed)
+            highlight.text.clone()
+        },
+        location: highlight.location.clone(),
+        reason: reason.to_string(),
+    };
+
+    // Group by book, keeping the order books first appear in
+    let mut books: Vec<(String, Option<String>, Vec<&ExternalHighlight>)> = Vec::new();
+    for highlight in highlights {
+        match books.iter_mut().find(|(title, author, _)| {
+            *title == highlight.book_title && *author == highlight.author
+        }) {
+            Some((_, _, items)) => items.push(highlight),
+            None => books.push((
+                highlight.book_title.clone(),
+                highlight.author.clone(),
+                vec![highlight],
+            )),
         }
-    });
-
-    let conn = database::get_connection(&app_handle)?;
-    let created = database::insert_annotation(
-        &conn,
-        &paragraph_id,
-        &text,
-        &style,
-        cleaned_note.as_deref(),
-    )?;
-    Ok(AnnotationOutput {
-        id: created.id,
-        paragraph_id: created.paragraph_id,
-        selected_text: created.selected_text,
-        style: created.style,
-        note: created.note,
-        created_at: created.created_at,
-        updated_at: created.updated_at,
-    })
+    }
+
+    for (title, author, items) in books {
+        let document = match doc_id {
+            Some(id) => documents.iter().find(|doc| doc.id == id),
+            None => documents
+                .iter()
+                .map(|doc| {
+                    let mut score = search::title_similarity(&title, &doc.title);
+                    if let (Some(author), Some(doc_author)) =
+                        (author.as_deref(), doc.author.as_deref())
+                    {
+                        if search::authors_overlap(author, doc_author) {
+                            score += 0.1;
+                        } else {
+                            score *= 0.5;
+                        }
+                    }
+                    (doc, score)
+                })
+                .filter(|(_, score)| *score >= MIN_TITLE_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(doc, _)| doc),
+        };
+        let Some(document) = document else {
+            output.unmatched.extend(
+                items
+                    .iter()
+                    .map(|highlight| unmatched(highlight, "No matching document in library")),
+            );
+            continue;
+        };
+
+        let paragraphs = database::list_paragraphs(conn, &document.id)?;
+        let paragraph_ids: Vec<String> = paragraphs.iter().map(|p| p.id.clone()).collect();
+        let mut existing = HashSet::new();
+        for batch in paragraph_ids.chunks(PARAGRAPH_ID_BATCH) {
+            for annotation in database::list_annotations_by_paragraph_ids(conn, batch)? {
+                existing.insert((annotation.paragraph_id, annotation.selected_text));
+            }
+        }
+        let matcher = search::ParagraphMatcher::new(
+            &paragraphs
+                .iter()
+                .map(|p| p.text.as_str())
+                .collect::<Vec<_>>(),
+        );
+
+        let tx = conn.unchecked_transaction()?;
+        let mut imported_any = false;
+        for highlight in items {
+            if highlight.text.trim().is_empty() {
+                output
+                    .unmatched
+                    .push(unmatched(highlight, "Note without highlighted text"));
+                continue;
+            }
+            let Some(found) = matcher.locate(&highlight.text) else {
+                output.unmatched.push(unmatched(
+                    highlight,
+                    "Highlighted text not found in document",
+                ));
+                continue;
+            };
+
+            // Store the paragraph's own wording so the reader can find it again
+            let paragraph = &paragraphs[found.paragraph_index];
+            let selected_text = paragraph.text[found.start..found.end].to_string();
+            if !existing.insert((paragraph.id.clone(), selected_text.clone())) {
+                output.skipped_duplicates += 1;
+                continue;
+            }
+            let note = highlight
+                .note
+                .as_deref()
+                .map(str::trim)
+                .filter(|note| !note.is_empty());
+            database::insert_annotation_at(
+                &tx,
+                &NewAnnotation {
+                    paragraph_id: paragraph.id.clone(),
+                    selected_text,
+                    style: STYLE_SINGLE_UNDERLINE.to_string(),
+                    note: note.map(str::to_string),
+                    anchor: search::anchor_at_bytes(&paragraph.text, found.start, found.end),
+                    ..Default::default()
+                },
+                highlight
+                    .created_at
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+            )?;
+            output.imported += 1;
+            imported_any = true;
+        }
+        tx.commit()?;
+        if imported_any && !output.documents.contains(&document.title) {
+            output.documents.push(document.title.clone());
+        }
+    }
+
+    Ok(output)
 }
 
-#[tauri::command]
-pub async fn delete_annotation(app_handle: AppHandle, id: String) -> Result<()> {
-    let conn = database::get_connection(&app_handle)?;
-    database::delete_annotation(&conn, &id)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::place_highlights;
+    use crate::commands::import::insert_document_content;
+    use crate::database;
+    use crate::parsers::{ExternalHighlight, ParseExtras};
+
+    fn highlight(book_title: &str, text: &str) -> ExternalHighlight {
+        ExternalHighlight {
+            book_title: book_title.to_string(),
+            author: None,
+            text: text.to_string(),
+            note: None,
+            location: None,
+            chapter: None,
+            created_at: Some(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn places_highlights_once_and_reports_the_rest() {
+        let conn = database::open_in_memory();
+        let doc_id = database::insert_test_document(&conn, "Dune");
+        let chapters = vec![(
+            "Chapter".to_string(),
+            0,
+            "chapter.xhtml".to_string(),
+            vec![
+                "A beginning is the time for taking the most delicate care.".to_string(),
+                "Fear is the mind-killer.".to_string(),
+            ],
+        )];
+        insert_document_content(&conn, &doc_id, chapters, &ParseExtras::default()).unwrap();
+
+        let highlights = vec![
+            highlight("Dune", "fear is the mind-killer"),
+            highlight("Dune", "Not in the book at all"),
+            highlight("Unknown Book", "Anything"),
+        ];
+        let output = place_highlights(&conn, &highlights, None).unwrap();
+        assert_eq!(output.imported, 1);
+        assert_eq!(output.documents, vec!["Dune"]);
+        assert_eq!(output.unmatched.len(), 2);
+
+        let paragraphs = database::list_paragraphs(&conn, &doc_id).unwrap();
+        let annotations =
+            database::list_annotations_by_paragraph_ids(&conn, &[paragraphs[1].id.clone()])
+                .unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].selected_text, "Fear is the mind-killer");
+
+        let again = place_highlights(&conn, &highlights[..1], None).unwrap();
+        assert_eq!((again.imported, again.skipped_duplicates), (0, 1));
+    }
 }
@@ -0,0 +1,177 @@
+//! Named bookmarks at paragraph locations, with optional notes and colours
+
+use super::non_empty;
+use crate::database::{self, BookmarkEntry};
+use crate::error::{ReaderError, Result};
+use tauri::AppHandle;
+
+/// Colours the reader can show bookmarks in
+const BOOKMARK_COLORS: &[&str] = &["red", "orange", "yellow", "green", "blue", "purple"];
+/// Characters of paragraph text used as the title of an untitled bookmark
+const DEFAULT_TITLE_CHARS: usize = 60;
+
+#[derive(Clone, serde::Serialize)]
+pub struct BookmarkOutput {
+    pub id: String,
+    pub doc_id: String,
+    pub document_title: String,
+    pub section_id: String,
+    pub section_title: String,
+    pub paragraph_id: String,
+    pub location: String,
+    pub title: String,
+    pub note: Option<String>,
+    pub color: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<BookmarkEntry> for BookmarkOutput {
+    fn from(entry: BookmarkEntry) -> Self {
+        let bookmark = entry.bookmark;
+        Self {
+            id: bookmark.id,
+            doc_id: bookmark.doc_id,
+            document_title: entry.document_title,
+            section_id: entry.section_id,
+            section_title: entry.section_title,
+            paragraph_id: bookmark.paragraph_id,
+            location: entry.location,
+            title: bookmark.title,
+            note: bookmark.note,
+            color: bookmark.color,
+            created_at: bookmark.created_at,
+            updated_at: bookmark.updated_at,
+        }
+    }
+}
+
+/// Lists a document's bookmarks, or every bookmark in the library
+#[tauri::command]
+pub async fn list_bookmarks(
+    app_handle: AppHandle,
+    doc_id: Option<String>,
+) -> Result<Vec<BookmarkOutput>> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_bookmarks(conn, doc_id.as_deref())?
+            .into_iter()
+            .map(BookmarkOutput::from)
+            .collect())
+    })
+    .await
+}
+
+/// Bookmarks a paragraph; without a title the start of its text is used
+#[tauri::command]
+pub async fn create_bookmark(
+    app_handle: AppHandle,
+    paragraph_id: String,
+    title: Option<String>,
+    note: Option<String>,
+    color: Option<String>,
+) -> Result<BookmarkOutput> {
+    let note = non_empty(note);
+    let color = non_empty(color);
+    validate_color(color.as_deref())?;
+
+    database::run_blocking(&app_handle, move |conn| {
+        let paragraph = database::get_paragraph(conn, &paragraph_id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Paragraph {}", paragraph_id)))?;
+        let title = non_empty(title).unwrap_or_else(|| default_title(&paragraph.text));
+        let bookmark = database::insert_bookmark(
+            conn,
+            &paragraph.doc_id,
+            &paragraph.id,
+            &title,
+            note.as_deref(),
+            color.as_deref(),
+        )?;
+        get_output(conn, &bookmark.id)
+    })
+    .await
+}
+
+/// Changes a bookmark's title, note or colour
+///
+/// Omitted fields are kept; an empty note or colour clears it.
+#[tauri::command]
+pub async fn update_bookmark(
+    app_handle: AppHandle,
+    id: String,
+    title: Option<String>,
+    note: Option<String>,
+    color: Option<String>,
+) -> Result<BookmarkOutput> {
+    if title
+        .as_deref()
+        .is_some_and(|title| title.trim().is_empty())
+    {
+        return Err(ReaderError::InvalidArgument(
+            "Bookmark title cannot be empty".to_string(),
+        ));
+    }
+    validate_color(color.as_deref().filter(|color| !color.trim().is_empty()))?;
+
+    database::run_blocking(&app_handle, move |conn| {
+        let current = get_output(conn, &id)?;
+        let title = title.map_or(current.title, |title| title.trim().to_string());
+        let note = note.map_or(current.note, |note| non_empty(Some(note)));
+        let color = color.map_or(current.color, |color| non_empty(Some(color)));
+        database::update_bookmark(conn, &id, &title, note.as_deref(), color.as_deref())?;
+        get_output(conn, &id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_bookmark(app_handle: AppHandle, id: String) -> Result<()> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::delete_bookmark(conn, &id)?)
+    })
+    .await
+}
+
+fn get_output(conn: &rusqlite::Connection, id: &str) -> Result<BookmarkOutput> {
+    database::get_bookmark(conn, id)?
+        .map(BookmarkOutput::from)
+        .ok_or_else(|| ReaderError::NotFound(format!("Bookmark {}", id)))
+}
+
+fn validate_color(color: Option<&str>) -> Result<()> {
+    super::validate_color(color, BOOKMARK_COLORS, "bookmark")
+}
+
+/// The start of a paragraph's text, cut at a word boundary
+fn default_title(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= DEFAULT_TITLE_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(DEFAULT_TITLE_CHARS).collect();
+    // Text without spaces (e.g. CJK) is cut mid-run instead
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_title, validate_color};
+
+    #[test]
+    fn titles_untitled_bookmarks_from_paragraph_text() {
+        assert_eq!(default_title("  Call me\n Ishmael. "), "Call me Ishmael.");
+        let long = "word ".repeat(30);
+        let title = default_title(&long);
+        assert!(title.ends_with("word…"));
+        assert!(title.chars().count() <= 61);
+        let cjk = "很".repeat(80);
+        assert_eq!(default_title(&cjk).chars().count(), 61);
+
+        assert!(validate_color(Some("green")).is_ok());
+        assert!(validate_color(None).is_ok());
+        assert!(validate_color(Some("#00ff00")).is_err());
+    }
+}
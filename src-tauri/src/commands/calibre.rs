@@ -0,0 +1,120 @@
+use super::import::{import_document_with_extras, parse_document_file};
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::parsers::CalibreLibrary;
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+#[derive(Clone, serde::Serialize)]
+pub struct CalibreImportFailure {
+    pub title: String,
+    pub error: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CalibreImportOutput {
+    pub total: usize,
+    /// Ids of the newly imported documents
+    pub imported: Vec<String>,
+    /// Books whose chosen file, or a copy of it, is already in the library
+    pub skipped_existing: usize,
+    /// Books without a format we can read
+    pub skipped_unsupported: Vec<String>,
+    pub failed: Vec<CalibreImportFailure>,
+}
+
+/// Imports every book of a Calibre library
+///
+/// Each book is imported once from its best format (EPUB, then AZW3, then
+/// PDF, ...) with Calibre's title, authors, language, series and tags.
+/// Books already imported from the same file are skipped, so the command can
+/// be re-run after the Calibre library grows.
+#[tauri::command]
+pub async fn import_calibre_library(
+    app_handle: AppHandle,
+    library_path: String,
+) -> Result<CalibreImportOutput> {
+    let calibre_path = library_path.clone();
+    let books = tokio::task::spawn_blocking(move || CalibreLibrary::open(&calibre_path)?.books())
+        .await
+        .map_err(|e| ReaderError::Internal(e.to_string()))??;
+    let existing_paths: HashSet<String> = database::run_blocking(&app_handle, |conn| {
+        Ok(database::list_documents(conn)?
+            .into_iter()
+            // Managed copies live elsewhere; the Calibre path is the original
+            .map(|doc| doc.original_path.unwrap_or(doc.file_path))
+            .collect())
+    })
+    .await?;
+
+    let mut output = CalibreImportOutput {
+        total: books.len(),
+        imported: Vec::new(),
+        skipped_existing: 0,
+        skipped_unsupported: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for book in books {
+        let Some((format, path)) = book.best_format() else {
+            output.skipped_unsupported.push(book.title.clone());
+            continue;
+        };
+        let file_path = path.to_string_lossy().to_string();
+        if existing_paths.contains(&file_path) {
+            output.skipped_existing += 1;
+            continue;
+        }
+
+        let imported = async {
+            let parse_path = file_path.clone();
+            let format = format.to_lowercase();
+            let (mut metadata, chapters, mut extras) =
+                tokio::task::spawn_blocking(move || parse_document_file(&parse_path, &format))
+                    .await
+                    .map_err(|e| ReaderError::Internal(e.to_string()))??;
+            // Calibre's metadata is usually curated, so it wins over the file's
+            metadata.title = book.title.clone();
+            if !book.authors.is_empty() {
+                metadata.author = Some(book.authors.join(", "));
+            }
+            if let Some(language) = book.language() {
+                metadata.language = Some(language);
+            }
+            // Stored in the import transaction, so a book is never left untagged
+            extras.tags = book.tags.clone();
+            extras.series = book.series.clone();
+            extras.series_index = book.series_index;
+            import_document_with_extras(app_handle.clone(), metadata, chapters, extras).await
+        }
+        .await;
+
+        match imported {
+            Ok(doc_id) => output.imported.push(doc_id),
+            // The same file was imported from outside Calibre
+            Err(ReaderError::DuplicateDocument(_)) => output.skipped_existing += 1,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to import Calibre book {} ({}): {}",
+                    book.id,
+                    file_path,
+                    e
+                );
+                output.failed.push(CalibreImportFailure {
+                    title: book.title.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    tracing::info!(
+        "Calibre import from {}: {} imported, {} already present, {} unsupported, {} failed",
+        library_path,
+        output.imported.len(),
+        output.skipped_existing,
+        output.skipped_unsupported.len(),
+        output.failed.len()
+    );
+    Ok(output)
+}
@@ -9,7 +9,7 @@ use crate::config::load_config;
 use crate::database::{self, get_connection};
 use crate::error::{ReaderError, Result};
 use crate::models::Paragraph;
-use crate::search::cosine_similarity;
+use crate::search::{cosine_similarity, resolve_scope};
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct EmbeddingProfile {
@@ -40,6 +40,12 @@ pub struct SearchByEmbeddingRequest {
     pub query_vector: Vec<f32>,
     pub top_k: usize,
     pub doc_id: Option<String>,
+    /// Restricts the search to a collection when no `doc_id` is given
+    #[serde(default)]
+    pub collection_id: Option<String>,
+    /// Restricts the search to documents with this tag when no `doc_id` is given
+    #[serde(default)]
+    pub tag: Option<String>,
     pub query_text: Option<String>,
 }
 
@@ -144,13 +150,34 @@ pub async fn search_by_embedding(
     }
 
     let conn = get_connection(&app_handle)?;
-    let embeddings = database::list_by_profile(
+    let scope = resolve_scope(
         &conn,
-        &profile.provider,
-        &profile.model,
-        profile.dimension,
         request.doc_id.as_deref(),
+        request.collection_id.as_deref(),
+        request.tag.as_deref(),
     )?;
+    let embeddings = match scope {
+        Some(doc_ids) => {
+            let mut embeddings = Vec::new();
+            for doc_id in &doc_ids {
+                embeddings.extend(database::list_by_profile(
+                    &conn,
+                    &profile.provider,
+                    &profile.model,
+                    profile.dimension,
+                    Some(doc_id.as_str()),
+                )?);
+            }
+            embeddings
+        }
+        None => database::list_by_profile(
+            &conn,
+            &profile.provider,
+            &profile.model,
+            profile.dimension,
+            None,
+        )?,
+    };
     if embeddings.is_empty() {
         return Ok(Vec::new());
     }
@@ -0,0 +1,572 @@
+use super::annotation::MIN_TITLE_SIMILARITY;
+use super::folder::collect_files;
+use super::web_annotation::{notes_page, restore_web_annotations};
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::export::{self, ArchivedDocument, DeepLinkTarget, DocumentNotes, NotesArchive};
+use crate::models::{Document, Paragraph};
+use crate::search;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+/// The deep link the app was launched with, held until the window has loaded
+#[derive(Default)]
+pub struct LaunchDeepLink(Mutex<Option<DeepLinkTarget>>);
+
+impl LaunchDeepLink {
+    pub fn new(target: Option<DeepLinkTarget>) -> Self {
+        Self(Mutex::new(target))
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotesFormat {
+    /// One Markdown file grouped by book and section
+    Markdown,
+    /// One note per book in an Obsidian vault folder
+    Obsidian,
+    /// Lossless archive for `import_notes`
+    Json,
+    /// W3C Web Annotation page of the annotations, without bookmarks
+    #[serde(rename = "w3c")]
+    WebAnnotation,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct NotesExportOutput {
+    pub documents: usize,
+    pub annotations: usize,
+    pub bookmarks: usize,
+    /// Files written, e.g. one per book for Obsidian
+    pub files: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct NotesImportOutput {
+    /// Titles of the documents that received notes
+    pub documents: Vec<String>,
+    pub annotations: usize,
+    pub bookmarks: usize,
+    /// Notes already in the library
+    pub skipped_duplicates: usize,
+    /// Titles of archived documents with no match in the library
+    pub unmatched_documents: Vec<String>,
+    /// Selected text of annotations, or titles of bookmarks, that couldn't be placed
+    pub unplaced: Vec<String>,
+}
+
+/// Exports annotations and bookmarks of one document, or of the whole library
+///
+/// `path` is the file to write for Markdown and JSON, and the vault folder
+/// for Obsidian. A book already exported to the vault is found by the id in
+/// its note's front matter and updated in place, even if it was renamed.
+#[tauri::command]
+pub async fn export_notes(
+    app_handle: AppHandle,
+    format: NotesFormat,
+    path: String,
+    doc_id: Option<String>,
+) -> Result<NotesExportOutput> {
+    let notes = database::run_blocking(&app_handle, move |conn| {
+        collect_notes(conn, doc_id.as_deref())
+    })
+    .await?;
+    let exported_at = chrono::Utc::now().timestamp();
+    let path = PathBuf::from(path);
+
+    let files = match format {
+        NotesFormat::Markdown => {
+            std::fs::write(&path, export::render_markdown(&notes))?;
+            vec![path]
+        }
+        NotesFormat::Json => {
+            let archive = NotesArchive::new(&notes, exported_at);
+            let json = serde_json::to_string_pretty(&archive)
+                .map_err(|e| ReaderError::Internal(e.to_string()))?;
+            std::fs::write(&path, json)?;
+            vec![path]
+        }
+        NotesFormat::WebAnnotation => {
+            let json = serde_json::to_string_pretty(&notes_page(&notes))
+                .map_err(|e| ReaderError::Internal(e.to_string()))?;
+            std::fs::write(&path, json)?;
+            vec![path]
+        }
+        NotesFormat::Obsidian => write_vault(&path, &notes, exported_at)?,
+    };
+
+    let output = NotesExportOutput {
+        documents: notes.len(),
+        annotations: notes.iter().map(DocumentNotes::annotation_count).sum(),
+        bookmarks: notes.iter().map(DocumentNotes::bookmark_count).sum(),
+        files: files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect(),
+    };
+    tracing::info!(
+        "Exported {} annotations and {} bookmarks from {} documents as {:?}",
+        output.annotations,
+        output.bookmarks,
+        output.documents,
+        format
+    );
+    Ok(output)
+}
+
+/// Imports a JSON archive written by `export_notes`, or W3C Web Annotations
+///
+/// Each archived document is matched by id, then file hash, ISBN and title.
+/// Notes keep their ids, so importing the same archive twice adds nothing.
+/// Notes whose paragraph is gone, e.g. because the book was imported again
+/// from another file, are placed by their text or location instead.
+#[tauri::command]
+pub async fn import_notes(app_handle: AppHandle, file_path: String) -> Result<NotesImportOutput> {
+    let text = std::fs::read_to_string(&file_path)?;
+    if let Ok(annotations) = export::parse_web_annotations(&text) {
+        let output = database::run_blocking(&app_handle, move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            let output = restore_web_annotations(&tx, &annotations, None)?;
+            tx.commit()?;
+            Ok(output)
+        })
+        .await?;
+        tracing::info!(
+            "Imported {} Web Annotations from {} ({} duplicates, {} unplaced)",
+            output.annotations,
+            file_path,
+            output.skipped_duplicates,
+            output.unplaced.len()
+        );
+        return Ok(output);
+    }
+    let archive: NotesArchive = serde_json::from_str(&text)
+        .map_err(|e| ReaderError::InvalidArgument(format!("Not a notes archive: {}", e)))?;
+    if !archive.is_supported() {
+        return Err(ReaderError::InvalidArgument(format!(
+            "Unsupported notes archive: {} version {}",
+            archive.format, archive.version
+        )));
+    }
+
+    let output = database::run_blocking(&app_handle, move |conn| {
+        let tx = conn.unchecked_transaction()?;
+        let output = restore_archive(&tx, archive)?;
+        tx.commit()?;
+        Ok(output)
+    })
+    .await?;
+    tracing::info!(
+        "Imported {} annotations and {} bookmarks from {} ({} duplicates, {} unplaced)",
+        output.annotations,
+        output.bookmarks,
+        file_path,
+        output.skipped_duplicates,
+        output.unplaced.len()
+    );
+    Ok(output)
+}
+
+/// Notes of one document, or of every document that has any
+/// Returns the deep link the app was launched with, the first time only
+///
+/// The link arrives before the window can listen for deep link events.
+#[tauri::command]
+pub async fn take_launch_deep_link(
+    state: State<'_, LaunchDeepLink>,
+) -> Result<Option<DeepLinkTarget>> {
+    Ok(state.0.lock().unwrap().take())
+}
+
+fn collect_notes(conn: &Connection, doc_id: Option<&str>) -> Result<Vec<DocumentNotes>> {
+    match doc_id {
+        Some(id) => {
+            let document = database::get_document(conn, id)?
+                .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?;
+            Ok(vec![export::collect(conn, document)?])
+        }
+        None => {
+            let mut notes = Vec::new();
+            for document in database::list_documents(conn)? {
+                let document_notes = export::collect(conn, document)?;
+                if !document_notes.is_empty() {
+                    notes.push(document_notes);
+                }
+            }
+            Ok(notes)
+        }
+    }
+}
+
+/// Writes one note per book into `vault`, updating notes exported before
+fn write_vault(vault: &Path, notes: &[DocumentNotes], exported_at: i64) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(vault)?;
+    let mut existing: HashMap<String, PathBuf> = HashMap::new();
+    let (files, _) = collect_files(vault, true);
+    for file in files {
+        if file.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        if let Some(id) = export::obsidian_note_id(&text) {
+            existing.insert(id, file);
+        }
+    }
+
+    let mut written = Vec::new();
+    for document_notes in notes {
+        let id = &document_notes.document.id;
+        let (file, text) = match existing.get(id) {
+            Some(file) => {
+                let current = std::fs::read_to_string(file)?;
+                let text =
+                    export::render_obsidian_note(document_notes, Some(&current), exported_at);
+                (file.clone(), text)
+            }
+            None => {
+                let file = free_file_name(vault, &document_notes.document.title);
+                let text = export::render_obsidian_note(document_notes, None, exported_at);
+                (file, text)
+            }
+        };
+        std::fs::write(&file, text)?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+/// A path in `folder` for a new note, numbered when the title is taken
+fn free_file_name(folder: &Path, title: &str) -> PathBuf {
+    let name = export::obsidian_file_name(title);
+    let stem = name.trim_end_matches(".md");
+    let mut path = folder.join(&name);
+    let mut counter = 2;
+    while path.exists() {
+        path = folder.join(format!("{} {}.md", stem, counter));
+        counter += 1;
+    }
+    path
+}
+
+fn restore_archive(conn: &Connection, archive: NotesArchive) -> Result<NotesImportOutput> {
+    let documents = database::list_documents(conn)?;
+    let mut output = NotesImportOutput {
+        documents: Vec::new(),
+        annotations: 0,
+        bookmarks: 0,
+        skipped_duplicates: 0,
+        unmatched_documents: Vec::new(),
+        unplaced: Vec::new(),
+    };
+
+    for archived in archive.documents {
+        let Some(document) = match_document(&documents, &archived.document) else {
+            output
+                .unmatched_documents
+                .push(archived.document.title.clone());
+            continue;
+        };
+        let (annotations, bookmarks) = (output.annotations, output.bookmarks);
+        restore_document_notes(conn, document, archived, &mut output)?;
+        let added = output.annotations > annotations || output.bookmarks > bookmarks;
+        if added && !output.documents.contains(&document.title) {
+            output.documents.push(document.title.clone());
+        }
+    }
+    Ok(output)
+}
+
+/// The library document an archived document was exported from
+fn match_document<'a>(documents: &'a [Document], archived: &Document) -> Option<&'a Document> {
+    let same = |value: &Option<String>, other: &Option<String>| {
+        value.is_some() && value.as_deref() == other.as_deref()
+    };
+    documents
+        .iter()
+        .find(|doc| doc.id == archived.id)
+        .or_else(|| {
+            documents
+                .iter()
+                .find(|doc| same(&doc.content_hash, &archived.content_hash))
+        })
+        .or_else(|| {
+            documents
+                .iter()
+                .find(|doc| same(&doc.metadata.isbn, &archived.metadata.isbn))
+        })
+        .or_else(|| {
+            documents
+                .iter()
+                .map(|doc| (doc, search::title_similarity(&archived.title, &doc.title)))
+                .filter(|(_, score)| *score >= MIN_TITLE_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(doc, _)| doc)
+        })
+}
+
+fn restore_document_notes(
+    conn: &Connection,
+    document: &Document,
+    archived: ArchivedDocument,
+    output: &mut NotesImportOutput,
+) -> Result<()> {
+    let paragraphs = database::list_paragraphs(conn, &document.id)?;
+    let sections = database::list_sections(conn, &document.id)?;
+    let index: HashMap<&str, &Paragraph> = paragraphs.iter().map(|p| (p.id.as_str(), p)).collect();
+    let matcher = search::ParagraphMatcher::new(
+        &paragraphs
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>(),
+    );
+
+    for item in archived.annotations {
+        let mut annotation = item.annotation;
+        if database::get_annotation(conn, &annotation.id)?.is_some() {
+            output.skipped_duplicates += 1;
+            continue;
+        }
+        let kept = index
+            .get(annotation.paragraph_id.as_str())
+            .and_then(|paragraph| {
+                search::resolve_anchor(
+                    &paragraph.text,
+                    &annotation.selected_text,
+                    annotation.anchor.as_ref(),
+                )
+            });
+        match kept {
+            Some(anchor) => annotation.anchor = Some(anchor),
+            None => {
+                let Some(found) = matcher.locate(&annotation.selected_text) else {
+                    output.unplaced.push(annotation.selected_text);
+                    continue;
+                };
+                let paragraph = &paragraphs[found.paragraph_index];
+                annotation.paragraph_id = paragraph.id.clone();
+                annotation.selected_text = paragraph.text[found.start..found.end].to_string();
+                annotation.anchor =
+                    search::anchor_at_bytes(&paragraph.text, found.start, found.end);
+            }
+        }
+        database::restore_annotation(conn, &annotation)?;
+        output.annotations += 1;
+    }
+
+    for item in archived.bookmarks {
+        let mut bookmark = item.bookmark;
+        if database::get_bookmark(conn, &bookmark.id)?.is_some() {
+            output.skipped_duplicates += 1;
+            continue;
+        }
+        let in_section = |paragraph: &&Paragraph| {
+            sections
+                .iter()
+                .any(|s| s.id == paragraph.section_id && s.title == item.section_title)
+        };
+        let target = index
+            .get(bookmark.paragraph_id.as_str())
+            .copied()
+            .or_else(|| {
+                let at_location =
+                    |p: &&Paragraph| !item.location.is_empty() && p.location == item.location;
+                paragraphs
+                    .iter()
+                    .filter(at_location)
+                    .find(in_section)
+                    .or_else(|| paragraphs.iter().find(at_location))
+            })
+            .or_else(|| paragraphs.iter().find(in_section));
+        let Some(paragraph) = target else {
+            output.unplaced.push(bookmark.title);
+            continue;
+        };
+        bookmark.doc_id = document.id.clone();
+        bookmark.paragraph_id = paragraph.id.clone();
+        database::restore_bookmark(conn, &bookmark)?;
+        output.bookmarks += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_notes, match_document, restore_archive};
+    use crate::commands::import::insert_document_content;
+    use crate::database;
+    use crate::export::NotesArchive;
+    use crate::models::{Document, NewAnnotation};
+    use crate::parsers::ParseExtras;
+    use rusqlite::Connection;
+
+    const PARAGRAPHS: &[&str] = &[
+        "It was a dark and stormy night.",
+        "The cat sat on the mat.",
+        "Nobody noticed.",
+    ];
+
+    /// A document titled "Notes" holding `paragraphs` in one chapter
+    fn add_document(conn: &Connection, paragraphs: &[&str]) -> String {
+        let doc_id = database::insert_test_document(conn, "Notes");
+        let chapters = vec![(
+            "Chapter".to_string(),
+            0,
+            "chapter.xhtml".to_string(),
+            paragraphs.iter().map(|p| p.to_string()).collect(),
+        )];
+        insert_document_content(conn, &doc_id, chapters, &ParseExtras::default()).unwrap();
+        doc_id
+    }
+
+    /// Annotates "sat" and bookmarks the paragraph after it
+    fn add_notes(conn: &Connection, doc_id: &str) {
+        let paragraphs = database::list_paragraphs(conn, doc_id).unwrap();
+        database::insert_annotation(
+            conn,
+            &NewAnnotation {
+                paragraph_id: paragraphs[1].id.clone(),
+                selected_text: "sat".to_string(),
+                style: "highlight".to_string(),
+                tags: vec!["cats".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        database::insert_bookmark(conn, doc_id, &paragraphs[2].id, "End", None, None).unwrap();
+    }
+
+    /// Exports every document's notes and reads the archive back as `import_notes` does
+    fn export_archive(conn: &Connection) -> NotesArchive {
+        let notes = collect_notes(conn, None).unwrap();
+        let json = serde_json::to_string(&NotesArchive::new(&notes, 0)).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn note_counts(conn: &Connection, doc_id: &str) -> (usize, usize) {
+        (
+            database::list_annotations_by_document(conn, doc_id)
+                .unwrap()
+                .len(),
+            database::list_bookmarks(conn, Some(doc_id)).unwrap().len(),
+        )
+    }
+
+    #[test]
+    fn importing_an_export_again_adds_nothing() {
+        let conn = database::open_in_memory();
+        let doc_id = add_document(&conn, PARAGRAPHS);
+        add_notes(&conn, &doc_id);
+
+        let output = restore_archive(&conn, export_archive(&conn)).unwrap();
+
+        assert_eq!((output.annotations, output.bookmarks), (0, 0));
+        assert_eq!(output.skipped_duplicates, 2);
+        assert!(output.documents.is_empty());
+        assert_eq!(note_counts(&conn, &doc_id), (1, 1));
+    }
+
+    #[test]
+    fn places_notes_in_a_book_imported_again() {
+        let exported = database::open_in_memory();
+        let old_id = add_document(&exported, PARAGRAPHS);
+        add_notes(&exported, &old_id);
+        let archive = export_archive(&exported);
+
+        // Same book with new paragraph ids and a paragraph added before the notes
+        let conn = database::open_in_memory();
+        let doc_id = add_document(
+            &conn,
+            &[
+                "Preface.",
+                "It was a dark and stormy night.",
+                "The cat sat on the mat.",
+                "Nobody noticed.",
+            ],
+        );
+
+        let output = restore_archive(&conn, archive.clone()).unwrap();
+        assert_eq!((output.annotations, output.bookmarks), (1, 1));
+        assert_eq!(output.documents, ["Notes"]);
+        assert!(output.unplaced.is_empty() && output.unmatched_documents.is_empty());
+        let annotation = &database::list_annotations_by_document(&conn, &doc_id).unwrap()[0];
+        let paragraph = database::get_paragraph(&conn, &annotation.paragraph_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(paragraph.text, "The cat sat on the mat.");
+        assert_eq!(annotation.selected_text, "sat");
+        assert_eq!(annotation.tags, ["cats"]);
+        // Bookmarks keep their location, e.g. a page, rather than their text
+        let bookmark = &database::list_bookmarks(&conn, Some(&doc_id)).unwrap()[0];
+        assert_eq!(bookmark.location, "chapter.xhtml#p2");
+
+        let again = restore_archive(&conn, archive).unwrap();
+        assert_eq!((again.annotations, again.bookmarks), (0, 0));
+        assert_eq!(again.skipped_duplicates, 2);
+        assert_eq!(note_counts(&conn, &doc_id), (1, 1));
+    }
+
+    #[test]
+    fn reports_notes_whose_text_is_gone() {
+        let exported = database::open_in_memory();
+        let old_id = add_document(&exported, PARAGRAPHS);
+        add_notes(&exported, &old_id);
+        let archive = export_archive(&exported);
+
+        let conn = database::open_in_memory();
+        let doc_id = add_document(&conn, &["An entirely different first chapter."]);
+
+        let output = restore_archive(&conn, archive).unwrap();
+        assert_eq!(output.annotations, 0);
+        assert_eq!(output.unplaced, ["sat"]);
+        // The bookmark falls back to its section
+        assert_eq!(output.bookmarks, 1);
+        assert_eq!(note_counts(&conn, &doc_id), (0, 1));
+    }
+
+    #[test]
+    fn matches_documents_by_id_hash_isbn_then_title() {
+        let conn = database::open_in_memory();
+        let base = database::get_document(&conn, &database::insert_test_document(&conn, "base"))
+            .unwrap()
+            .unwrap();
+        let document = |id: &str, title: &str| Document {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..base.clone()
+        };
+        let mut hashed = document("hashed", "Dune");
+        hashed.content_hash = Some("abc".to_string());
+        let mut isbn = document("isbn", "Emma");
+        isbn.metadata.isbn = Some("9780306406157".to_string());
+        let library = vec![
+            document("titled", "The Left Hand of Darkness"),
+            hashed,
+            isbn,
+        ];
+        let matched = |archived: &Document| match_document(&library, archived).map(|doc| &doc.id);
+
+        let mut archived = document("isbn", "Something else");
+        archived.content_hash = Some("abc".to_string());
+        assert_eq!(matched(&archived).unwrap(), "isbn");
+
+        archived.id = "gone".to_string();
+        assert_eq!(matched(&archived).unwrap(), "hashed");
+
+        archived.content_hash = None;
+        archived.metadata.isbn = Some("9780306406157".to_string());
+        assert_eq!(matched(&archived).unwrap(), "isbn");
+
+        archived.metadata.isbn = None;
+        archived.title = "The Left Hand of Darkness: A Novel".to_string();
+        assert_eq!(matched(&archived).unwrap(), "titled");
+
+        archived.title = "Something else".to_string();
+        assert_eq!(matched(&archived), None);
+    }
+}
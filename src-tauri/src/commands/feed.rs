@@ -0,0 +1,555 @@
+use super::import::{
+    article_markdown, article_to_markdown, build_http_client, fetch_article, find_article_document,
+    normalize_http_url, store_article, ReaderMetadata, MIN_LOCAL_ARTICLE_CHARS,
+};
+use super::library::app_data_dir;
+use crate::database::{self, DbPool};
+use crate::error::{ReaderError, Result};
+use crate::models::Feed;
+use crate::parsers::{decode_html, extract_article, parse_feed, FeedEntry};
+use reqwest::Url;
+use rusqlite::Connection;
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+const MAX_FEED_BYTES: usize = 5_000_000;
+/// New entries imported per poll; the rest wait for the next one
+const MAX_ENTRIES_PER_POLL: usize = 20;
+/// How often the scheduler checks for feeds that are due
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// Keeps manual and scheduled polls from importing the same entry twice
+static REFRESH_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Clone, serde::Serialize)]
+pub struct FeedEntryFailure {
+    pub title: String,
+    pub link: Option<String>,
+    pub error: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FeedRefreshOutput {
+    pub feed_id: String,
+    pub title: String,
+    /// Ids of the documents created for new entries
+    pub imported: Vec<String>,
+    /// Ids of library documents that new entries turned out to duplicate
+    pub existing: Vec<String>,
+    pub failed: Vec<FeedEntryFailure>,
+    /// Set when the feed itself could not be fetched or parsed
+    pub error: Option<String>,
+}
+
+/// Subscribes to a feed after checking that it can be fetched and parsed
+#[tauri::command]
+pub async fn add_feed(
+    app_handle: AppHandle,
+    url: String,
+    poll_interval_minutes: Option<i64>,
+) -> Result<Feed> {
+    let url = normalize_http_url(&url)?;
+    if poll_interval_minutes.is_some_and(|minutes| minutes < 5) {
+        return Err(ReaderError::InvalidArgument(
+            "Feeds can be polled at most every 5 minutes".to_string(),
+        ));
+    }
+    let feed_url = url.to_string();
+    let existing = database::run_blocking(&app_handle, move |conn| {
+        Ok(database::find_feed_by_url(conn, &feed_url)?)
+    })
+    .await?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    let parsed = fetch_feed(&build_http_client()?, &url).await?;
+    let title = parsed
+        .title
+        .clone()
+        .unwrap_or_else(|| url.host_str().unwrap_or("Feed").to_string());
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::insert_feed(
+            conn,
+            url.as_str(),
+            &title,
+            parsed.link.as_deref(),
+            poll_interval_minutes,
+        )?)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_feeds(app_handle: AppHandle) -> Result<Vec<Feed>> {
+    database::run_blocking(&app_handle, |conn| Ok(database::list_feeds(conn)?)).await
+}
+
+/// Unsubscribes from a feed; documents imported from it stay in the library
+#[tauri::command]
+pub async fn delete_feed(app_handle: AppHandle, id: String) -> Result<()> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::delete_feed(conn, &id)?)
+    })
+    .await
+}
+
+/// Polls one feed, or all of them, and imports entries not seen before
+#[tauri::command]
+pub async fn refresh_feeds(
+    app_handle: AppHandle,
+    feed_id: Option<String>,
+) -> Result<Vec<FeedRefreshOutput>> {
+    let feeds = database::run_blocking(&app_handle, move |conn| match feed_id {
+        Some(id) => Ok(vec![database::get_feed(conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Feed {}", id)))?]),
+        None => Ok(database::list_feeds(conn)?),
+    })
+    .await?;
+    poll_feeds(&app_handle, &feeds).await
+}
+
+/// Starts the background task that polls feeds with a poll interval
+pub fn start_feed_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULER_TICK).await;
+            let due = match due_feeds(&app_handle).await {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::warn!("Failed to check feeds for polling: {}", e);
+                    continue;
+                }
+            };
+            if due.is_empty() {
+                continue;
+            }
+            if let Err(e) = poll_feeds(&app_handle, &due).await {
+                tracing::warn!("Scheduled feed poll failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn due_feeds(app_handle: &AppHandle) -> Result<Vec<Feed>> {
+    let feeds = database::run_blocking(app_handle, |conn| Ok(database::list_feeds(conn)?)).await?;
+    let now = chrono::Utc::now().timestamp();
+    Ok(feeds
+        .into_iter()
+        .filter(
+            |feed| match (feed.poll_interval_minutes, feed.last_polled_at) {
+                (Some(_), None) => true,
+                (Some(minutes), Some(last)) => now - last >= minutes * 60,
+                (None, _) => false,
+            },
+        )
+        .collect())
+}
+
+async fn poll_feeds(app_handle: &AppHandle, feeds: &[Feed]) -> Result<Vec<FeedRefreshOutput>> {
+    let _guard = REFRESH_LOCK.lock().await;
+    let pool = database::get_pool(app_handle);
+    let app_data_dir = app_data_dir(app_handle)?;
+    let client = build_http_client()?;
+    let mut outputs = Vec::with_capacity(feeds.len());
+    for feed in feeds {
+        outputs.push(poll_feed(&pool, &app_data_dir, &client, feed).await?);
+    }
+    Ok(outputs)
+}
+
+/// Fetches one feed and imports its new entries
+///
+/// Fetch and parse failures are recorded on the feed and returned in the
+/// output rather than as an error, so one broken feed doesn't stop the rest.
+async fn poll_feed(
+    pool: &DbPool,
+    app_data_dir: &Path,
+    client: &reqwest::Client,
+    feed: &Feed,
+) -> Result<FeedRefreshOutput> {
+    let mut output = FeedRefreshOutput {
+        feed_id: feed.id.clone(),
+        title: feed.title.clone(),
+        imported: Vec::new(),
+        existing: Vec::new(),
+        failed: Vec::new(),
+        error: None,
+    };
+
+    let feed_url = normalize_http_url(&feed.url)?;
+    let parsed = match fetch_feed(client, &feed_url).await {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!("Failed to poll feed {}: {}", feed.url, e);
+            let (feed_id, error) = (feed.id.clone(), e.to_string());
+            database::run_blocking_on(pool, move |conn| {
+                Ok(database::mark_feed_polled(
+                    conn,
+                    &feed_id,
+                    None,
+                    Some(&error),
+                )?)
+            })
+            .await?;
+            output.error = Some(e.to_string());
+            return Ok(output);
+        }
+    };
+    if let Some(title) = &parsed.title {
+        output.title = title.clone();
+    }
+
+    let new_entries: Vec<FeedEntry> = {
+        let (feed_id, entries) = (feed.id.clone(), parsed.entries);
+        database::run_blocking_on(pool, move |conn| {
+            let mut new_entries = Vec::new();
+            for entry in entries {
+                if !database::feed_entry_exists(conn, &feed_id, &entry.guid)? {
+                    new_entries.push(entry);
+                }
+            }
+            Ok(new_entries)
+        })
+        .await?
+    };
+
+    for entry in new_entries.into_iter().take(MAX_ENTRIES_PER_POLL) {
+        let stored = match entry_article(client, &feed_url, &output.title, &entry).await {
+            Ok(article) => {
+                let (feed_id, app_data_dir, entry) =
+                    (feed.id.clone(), app_data_dir.to_path_buf(), entry.clone());
+                database::run_blocking_on(pool, move |conn| {
+                    store_entry(conn, &app_data_dir, &feed_id, &entry, article)
+                })
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        match stored {
+            Ok(StoredEntry::Imported(doc_id)) => output.imported.push(doc_id),
+            Ok(StoredEntry::Existing(doc_id)) => output.existing.push(doc_id),
+            Err(e) => {
+                tracing::warn!("Failed to import feed entry {}: {}", entry.guid, e);
+                output.failed.push(FeedEntryFailure {
+                    title: entry.title.clone().unwrap_or_else(|| entry.guid.clone()),
+                    link: entry.link.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    let (feed_id, title) = (feed.id.clone(), parsed.title);
+    database::run_blocking_on(pool, move |conn| {
+        Ok(database::mark_feed_polled(
+            conn,
+            &feed_id,
+            title.as_deref(),
+            None,
+        )?)
+    })
+    .await?;
+    tracing::info!(
+        "Polled feed {}: {} new entries imported, {} already in the library, {} failed",
+        feed.url,
+        output.imported.len(),
+        output.existing.len(),
+        output.failed.len()
+    );
+    Ok(output)
+}
+
+async fn fetch_feed(client: &reqwest::Client, url: &Url) -> Result<crate::parsers::Feed> {
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|e| ReaderError::ModelApi(format!("Failed to fetch feed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(ReaderError::ModelApi(format!(
+            "Feed fetch failed with status {}",
+            response.status()
+        )));
+    }
+    let charset = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| {
+            content_type
+                .split(';')
+                .find_map(|param| param.trim().strip_prefix("charset="))
+                .map(|charset| charset.trim_matches('"').to_string())
+        });
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| ReaderError::ModelApi(format!("Failed to read feed: {}", e)))?;
+    if bytes.len() > MAX_FEED_BYTES {
+        return Err(ReaderError::FeedParse(
+            "Feed is too large (over 5MB)".to_string(),
+        ));
+    }
+    parse_feed(&decode_html(&bytes, charset.as_deref()))
+}
+
+/// An entry's article, ready to be written to the library
+struct EntryArticle {
+    page_url: Url,
+    author: Option<String>,
+    markdown: String,
+}
+
+enum StoredEntry {
+    Imported(String),
+    Existing(String),
+}
+
+/// Builds the markdown document for one entry
+///
+/// Content carried in the feed is used when it is a full article; otherwise
+/// the linked page is fetched like `import_url` does.
+async fn entry_article(
+    client: &reqwest::Client,
+    feed_url: &Url,
+    feed_title: &str,
+    entry: &FeedEntry,
+) -> Result<EntryArticle> {
+    let link = entry
+        .link
+        .as_deref()
+        .and_then(|link| feed_url.join(link).ok());
+    let page_url = link.clone().unwrap_or_else(|| feed_url.clone());
+
+    let inline_body = entry_markdown(entry, &page_url);
+    let (fetched, body) = match (inline_body, &link) {
+        (Some((body, true)), _) => (ReaderMetadata::default(), body),
+        (inline, Some(link)) => match fetch_article(client, link).await {
+            Ok(fetched) => fetched,
+            // A short excerpt beats nothing when the page can't be read
+            Err(e) => match inline {
+                Some((body, _)) => {
+                    tracing::warn!("Using feed excerpt for {}: {}", link, e);
+                    (ReaderMetadata::default(), body)
+                }
+                None => return Err(e),
+            },
+        },
+        (Some((body, false)), None) => (ReaderMetadata::default(), body),
+        (None, None) => {
+            return Err(ReaderError::FeedParse(
+                "Entry has neither content nor a link".to_string(),
+            ))
+        }
+    };
+
+    let metadata = ReaderMetadata {
+        title: entry.title.clone().or(fetched.title),
+        author: entry.author.clone().or(fetched.author),
+        published_time: entry
+            .published_at
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|date| date.to_rfc3339())
+            .or(fetched.published_time),
+    };
+    Ok(EntryArticle {
+        markdown: article_markdown(&page_url, &metadata, &body, Some(feed_title)),
+        author: metadata.author,
+        page_url,
+    })
+}
+
+/// Imports an entry's article and records the entry against its document
+///
+/// An article already in the library, e.g. one whose entry changed its GUID,
+/// is recorded against the existing document instead of failing every poll.
+fn store_entry(
+    conn: &Connection,
+    app_data_dir: &Path,
+    feed_id: &str,
+    entry: &FeedEntry,
+    article: EntryArticle,
+) -> Result<StoredEntry> {
+    let stored = match find_article_document(conn, &article.markdown)? {
+        Some(existing) => StoredEntry::Existing(existing.id),
+        None => StoredEntry::Imported(store_article(
+            conn,
+            app_data_dir,
+            &article.page_url,
+            article.author,
+            &article.markdown,
+        )?),
+    };
+    let (StoredEntry::Imported(doc_id) | StoredEntry::Existing(doc_id)) = &stored;
+    database::insert_feed_entry(
+        conn,
+        feed_id,
+        &entry.guid,
+        doc_id,
+        entry.title.as_deref(),
+        entry.link.as_deref(),
+        entry.published_at,
+    )?;
+    Ok(stored)
+}
+
+/// Markdown of the content or summary carried in the feed entry
+///
+/// The flag tells whether it is long enough to count as the full article.
+fn entry_markdown(entry: &FeedEntry, page_url: &Url) -> Option<(String, bool)> {
+    let html = entry.content_html.as_ref().or(entry.summary.as_ref())?;
+    let article = extract_article(html);
+    let body = article_to_markdown(&article, page_url);
+    if body.trim().is_empty() {
+        return None;
+    }
+    Some((body, article.text_len() >= MIN_LOCAL_ARTICLE_CHARS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{entry_markdown, fetch_feed, poll_feed};
+    use crate::database::{self, DbPool};
+    use reqwest::Url;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves one HTTP response from a local port and returns the URL
+    fn serve_once(content_type: &'static str, body: String) -> Url {
+        serve(content_type, vec![body])
+    }
+
+    /// Serves `bodies` to successive requests from a local port
+    fn serve(content_type: &'static str, bodies: Vec<String>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    content_type,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        Url::parse(&format!("http://127.0.0.1:{}/feed.xml", port)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetches_feed_from_local_server() {
+        let article = "<p>".to_string() + &"Feed content sentence. ".repeat(20) + "</p>";
+        let rss = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+              <channel>
+                <title>Local Feed</title>
+                <item>
+                  <title>Entry One</title>
+                  <link>/posts/one</link>
+                  <guid>one</guid>
+                  <author>writer@example.com (Writer)</author>
+                  <description>Short teaser</description>
+                  <content:encoded><![CDATA[{}]]></content:encoded>
+                </item>
+                <item>
+                  <title>Entry Two</title>
+                  <link>/posts/two</link>
+                  <description>Only a teaser</description>
+                </item>
+              </channel>
+            </rss>"#,
+            article
+        );
+        let url = serve_once("application/rss+xml; charset=utf-8", rss);
+        let client = reqwest::Client::new();
+        let feed = fetch_feed(&client, &url).await.unwrap();
+
+        assert_eq!(feed.title.as_deref(), Some("Local Feed"));
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(feed.entries[1].guid, "/posts/two");
+
+        let page_url = url.join("/posts/one").unwrap();
+        let (body, complete) = entry_markdown(&feed.entries[0], &page_url).unwrap();
+        assert!(complete);
+        assert!(body.starts_with("Feed content sentence."));
+        let (_, complete) = entry_markdown(&feed.entries[1], &page_url).unwrap();
+        assert!(!complete);
+    }
+
+    #[tokio::test]
+    async fn polls_record_each_guid_once_and_link_duplicate_articles() {
+        let dir =
+            std::env::temp_dir().join(format!("reader_feed_poll_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = DbPool::new(dir.join("reader.db"));
+        database::migrate(&pool.get().unwrap(), None).unwrap();
+
+        // The second poll lists the same article again under a new GUID
+        let article = "<p>".to_string() + &"Feed content sentence. ".repeat(20) + "</p>";
+        let item = |guid: &str| {
+            format!(
+                "<item><title>Entry One</title><link>/posts/one</link><guid>{}</guid>\
+                 <content:encoded><![CDATA[{}]]></content:encoded></item>",
+                guid, article
+            )
+        };
+        let rss = |items: String| {
+            format!(
+                r#"<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+                <channel><title>Local Feed</title>{}</channel></rss>"#,
+                items
+            )
+        };
+        let url = serve(
+            "application/rss+xml",
+            vec![rss(item("one")), rss(item("one") + &item("one-moved"))],
+        );
+        let feed =
+            database::insert_feed(&pool.get().unwrap(), url.as_str(), "Local Feed", None, None)
+                .unwrap();
+        let client = reqwest::Client::new();
+
+        let first = poll_feed(&pool, &dir, &client, &feed).await.unwrap();
+        let second = poll_feed(&pool, &dir, &client, &feed).await.unwrap();
+
+        let conn = pool.get().unwrap();
+        let mut statement = conn
+            .prepare("SELECT guid, doc_id FROM feed_entries ORDER BY guid")
+            .unwrap();
+        let entries: Vec<(String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        let article_files = std::fs::read_dir(dir.join("imports").join("url"))
+            .unwrap()
+            .count();
+        drop(statement);
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(first.imported.len(), 1);
+        assert!(first.failed.is_empty());
+        assert!(second.imported.is_empty());
+        assert!(second.failed.is_empty());
+        assert_eq!(second.existing, first.imported);
+        let doc_id = first.imported[0].clone();
+        assert_eq!(
+            entries,
+            vec![
+                ("one".to_string(), doc_id.clone()),
+                ("one-moved".to_string(), doc_id)
+            ]
+        );
+        assert_eq!(article_files, 1);
+    }
+}
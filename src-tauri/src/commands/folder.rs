@@ -0,0 +1,610 @@
+use super::import::{file_content_hash, parse_document_file, store_document, ParsedDocument};
+use super::library::{app_data_dir, refresh_library_copy, store_images};
+use super::reimport::replace_document_content;
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::models::{Document, WatchedFolder};
+use crate::parsers::detect_format;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+
+/// Upper bound on files parsed at the same time
+const MAX_PARALLEL_PARSES: usize = 4;
+/// How often watched folders are scanned in the background
+const WATCH_TICK: Duration = Duration::from_secs(300);
+
+/// Keeps manual and background scans from importing the same file twice
+static SCAN_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Clone, serde::Serialize)]
+pub struct FolderImportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FolderImportOutput {
+    pub folder: String,
+    /// Files found in the folder
+    pub scanned: usize,
+    /// Ids of the newly imported documents
+    pub imported: Vec<String>,
+    /// Ids of documents imported again because their file changed
+    pub updated: Vec<String>,
+    /// Files already in the library (at this or another path) and unchanged
+    pub skipped_existing: usize,
+    /// Files no parser can read
+    pub unsupported: usize,
+    pub failed: Vec<FolderImportFailure>,
+}
+
+/// A file the scan found that needs (re)importing
+#[derive(Clone)]
+struct PendingFile {
+    path: String,
+    format: &'static str,
+    size: i64,
+    modified_at: i64,
+    /// Document imported from an earlier version of the file
+    replaces: Option<String>,
+}
+
+/// Imports every readable file in a folder and, by default, its subfolders
+///
+/// Each file is matched to a parser by its content and extension, and files
+/// are parsed in parallel. Files imported by an earlier scan are skipped
+/// unless their contents changed, in which case the document is re-imported
+/// with its annotations kept. Copies of documents already in the library are
+/// skipped too. Failures are listed in the report and don't stop the rest of
+/// the batch.
+#[tauri::command]
+pub async fn import_folder(
+    app_handle: AppHandle,
+    folder_path: String,
+    recursive: Option<bool>,
+) -> Result<FolderImportOutput> {
+    let root = PathBuf::from(&folder_path);
+    if !root.is_dir() {
+        return Err(ReaderError::NotFound(folder_path));
+    }
+    let _guard = SCAN_LOCK.lock().await;
+    scan_folder(&app_handle, &root, recursive.unwrap_or(true)).await
+}
+
+/// Watches a folder so new and changed files are imported automatically
+#[tauri::command]
+pub async fn add_watched_folder(
+    app_handle: AppHandle,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<WatchedFolder> {
+    let path = path.trim();
+    if !Path::new(path).is_dir() {
+        return Err(ReaderError::InvalidArgument(format!(
+            "{} is not a folder",
+            path
+        )));
+    }
+    let conn = database::get_connection(&app_handle)?;
+    let folder = database::insert_watched_folder(&conn, path, recursive.unwrap_or(true))?;
+    Ok(folder)
+}
+
+#[tauri::command]
+pub async fn list_watched_folders(app_handle: AppHandle) -> Result<Vec<WatchedFolder>> {
+    let conn = database::get_connection(&app_handle)?;
+    Ok(database::list_watched_folders(&conn)?)
+}
+
+/// Stops watching a folder; documents imported from it stay in the library
+#[tauri::command]
+pub async fn remove_watched_folder(app_handle: AppHandle, id: String) -> Result<()> {
+    let conn = database::get_connection(&app_handle)?;
+    database::delete_watched_folder(&conn, &id)?;
+    Ok(())
+}
+
+/// Scans one watched folder, or all of them, for new and changed files
+#[tauri::command]
+pub async fn scan_watched_folders(
+    app_handle: AppHandle,
+    folder_id: Option<String>,
+) -> Result<Vec<FolderImportOutput>> {
+    let folders = {
+        let conn = database::get_connection(&app_handle)?;
+        let folders = database::list_watched_folders(&conn)?;
+        match folder_id {
+            Some(id) => vec![folders
+                .into_iter()
+                .find(|folder| folder.id == id)
+                .ok_or_else(|| ReaderError::NotFound(format!("Watched folder {}", id)))?],
+            None => folders,
+        }
+    };
+    scan_folders(&app_handle, &folders).await
+}
+
+/// Starts the background task that scans watched folders
+pub fn start_folder_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCH_TICK).await;
+            let folders = match watched_folders(&app_handle) {
+                Ok(folders) => folders,
+                Err(e) => {
+                    tracing::warn!("Failed to list watched folders: {}", e);
+                    continue;
+                }
+            };
+            if folders.is_empty() {
+                continue;
+            }
+            if let Err(e) = scan_folders(&app_handle, &folders).await {
+                tracing::warn!("Watched folder scan failed: {}", e);
+            }
+        }
+    });
+}
+
+fn watched_folders(app_handle: &AppHandle) -> Result<Vec<WatchedFolder>> {
+    let conn = database::get_connection(app_handle)?;
+    Ok(database::list_watched_folders(&conn)?)
+}
+
+async fn scan_folders(
+    app_handle: &AppHandle,
+    folders: &[WatchedFolder],
+) -> Result<Vec<FolderImportOutput>> {
+    let _guard = SCAN_LOCK.lock().await;
+    let mut outputs = Vec::with_capacity(folders.len());
+    for folder in folders {
+        let root = Path::new(&folder.path);
+        // A missing folder (e.g. an unmounted drive) is reported, not fatal
+        let output = if root.is_dir() {
+            scan_folder(app_handle, root, folder.recursive).await?
+        } else {
+            FolderImportOutput {
+                failed: vec![FolderImportFailure {
+                    path: folder.path.clone(),
+                    error: "Folder not found".to_string(),
+                }],
+                ..empty_output(root)
+            }
+        };
+        let conn = database::get_connection(app_handle)?;
+        database::mark_folder_scanned(&conn, &folder.id)?;
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+fn empty_output(root: &Path) -> FolderImportOutput {
+    FolderImportOutput {
+        folder: root.display().to_string(),
+        scanned: 0,
+        imported: Vec::new(),
+        updated: Vec::new(),
+        skipped_existing: 0,
+        unsupported: 0,
+        failed: Vec::new(),
+    }
+}
+
+/// Imports the new and changed files under `root`; callers hold `SCAN_LOCK`
+async fn scan_folder(
+    app_handle: &AppHandle,
+    root: &Path,
+    recursive: bool,
+) -> Result<FolderImportOutput> {
+    let walk_root = root.to_path_buf();
+    let (files, walk_errors) =
+        tokio::task::spawn_blocking(move || collect_files(&walk_root, recursive))
+            .await
+            .map_err(|e| ReaderError::Internal(e.to_string()))?;
+
+    let mut output = empty_output(root);
+    output.scanned = files.len();
+    output.failed = walk_errors;
+
+    // Touched files are hashed, so they're sorted out off the async runtime
+    let (pending, mut output) = database::run_blocking(app_handle, move |conn| {
+        let pending = classify_files(conn, files, &mut output)?;
+        Ok((pending, output))
+    })
+    .await?;
+
+    // Parse in parallel; documents are written one at a time as parses finish
+    let semaphore = Arc::new(Semaphore::new(parse_parallelism()));
+    let mut parses = JoinSet::new();
+    for file in pending {
+        let semaphore = semaphore.clone();
+        parses.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let path = file.path.clone();
+            let format = file.format;
+            let parsed = tokio::task::spawn_blocking(move || parse_document_file(&path, format))
+                .await
+                .map_err(|e| ReaderError::Internal(e.to_string()))
+                .and_then(|parsed| parsed);
+            (file, parsed)
+        });
+    }
+
+    let pool = database::get_pool(app_handle);
+    let app_data_dir = app_data_dir(app_handle)?;
+    while let Some(joined) = parses.join_next().await {
+        let (file, parsed) = joined.map_err(|e| ReaderError::Internal(e.to_string()))?;
+        let stored = match parsed {
+            Ok(parsed) => {
+                let app_data_dir = app_data_dir.clone();
+                let pending = file.clone();
+                database::run_blocking_on(&pool, move |conn| {
+                    store_file(conn, &app_data_dir, &pending, parsed)
+                })
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        match stored {
+            Ok(doc_id) if file.replaces.is_some() => output.updated.push(doc_id),
+            Ok(doc_id) => output.imported.push(doc_id),
+            // A copy of a document imported from elsewhere
+            Err(ReaderError::DuplicateDocument(_)) => output.skipped_existing += 1,
+            Err(e) => {
+                tracing::warn!("Failed to import {}: {}", file.path, e);
+                output.failed.push(FolderImportFailure {
+                    path: file.path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    tracing::info!(
+        "Folder import from {}: {} scanned, {} imported, {} updated, {} unchanged, {} unsupported, {} failed",
+        output.folder,
+        output.scanned,
+        output.imported.len(),
+        output.updated.len(),
+        output.skipped_existing,
+        output.unsupported,
+        output.failed.len()
+    );
+    Ok(output)
+}
+
+/// Counts the unchanged files in `output` and returns the ones to (re)import
+///
+/// A file whose size or modification time changed is hashed, so one that
+/// was only touched isn't imported again.
+fn classify_files(
+    conn: &Connection,
+    files: Vec<PathBuf>,
+    output: &mut FolderImportOutput,
+) -> Result<Vec<PendingFile>> {
+    let existing: HashMap<String, Document> = database::list_documents(conn)?
+        .into_iter()
+        // Managed documents are matched by the file they were copied from
+        .map(|doc| {
+            let source = doc.original_path.as_ref().unwrap_or(&doc.file_path);
+            (source.clone(), doc)
+        })
+        .collect();
+
+    let mut pending = Vec::new();
+    for path in files {
+        let file_path = path.to_string_lossy().to_string();
+        let (size, modified_at) = match file_stamp(&path) {
+            Ok(stamp) => stamp,
+            Err(e) => {
+                output.failed.push(FolderImportFailure {
+                    path: file_path,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let document = existing.get(&file_path);
+        let record = database::get_imported_file(conn, &file_path)?
+            // A record whose document was deleted no longer counts
+            .filter(|record| document.is_some_and(|doc| doc.id == record.doc_id));
+        let replaces = match (record, document) {
+            (Some(record), _) if record.size == size && record.modified_at == modified_at => {
+                output.skipped_existing += 1;
+                continue;
+            }
+            (Some(_), Some(doc)) => {
+                // Touched but not edited
+                let content_hash = file_content_hash(&path).ok();
+                if content_hash.is_some() && content_hash == doc.content_hash {
+                    database::record_imported_file(conn, &file_path, &doc.id, size, modified_at)?;
+                    output.skipped_existing += 1;
+                    continue;
+                }
+                Some(doc.id.clone())
+            }
+            // Imported some other way; track changes from now on
+            (None, Some(doc)) => {
+                database::record_imported_file(conn, &file_path, &doc.id, size, modified_at)?;
+                output.skipped_existing += 1;
+                continue;
+            }
+            (_, None) => None,
+        };
+
+        let Some(format) = detect_format(&path) else {
+            output.unsupported += 1;
+            continue;
+        };
+        pending.push(PendingFile {
+            path: file_path,
+            format,
+            size,
+            modified_at,
+            replaces,
+        });
+    }
+    Ok(pending)
+}
+
+/// Adds a parsed file to the library, or updates the document of its previous version
+///
+/// A changed file is swapped into its existing document in place, so its
+/// annotations, bookmarks and reading position are carried over; if that
+/// fails the document is left as it was.
+fn store_file(
+    conn: &Connection,
+    app_data_dir: &Path,
+    file: &PendingFile,
+    parsed: ParsedDocument,
+) -> Result<String> {
+    let doc_id = match &file.replaces {
+        Some(doc_id) => {
+            let path = Path::new(&file.path);
+            let content_hash = file_content_hash(path).ok();
+            let doc = database::get_document(conn, doc_id)?;
+            let mut parsed = parsed;
+            if let (Some(content_hash), Some(doc)) = (&content_hash, &doc) {
+                if doc.original_path.is_some() {
+                    store_images(app_data_dir, content_hash, &mut parsed.1)?;
+                }
+            }
+            replace_document_content(conn, doc_id, parsed, content_hash.as_deref())?;
+            if let (Some(content_hash), Some(doc)) = (&content_hash, &doc) {
+                refresh_library_copy(conn, app_data_dir, doc, path, content_hash)?;
+            }
+            doc_id.clone()
+        }
+        None => {
+            let (metadata, chapters, extras) = parsed;
+            store_document(conn, app_data_dir, metadata, chapters, &extras)?
+        }
+    };
+
+    database::record_imported_file(conn, &file.path, &doc_id, file.size, file.modified_at)?;
+    Ok(doc_id)
+}
+
+fn parse_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(2)
+        .min(MAX_PARALLEL_PARSES)
+}
+
+/// Size and modification time (Unix seconds) of a file
+fn file_stamp(path: &Path) -> std::io::Result<(i64, i64)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified_at = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0);
+    Ok((metadata.len() as i64, modified_at))
+}
+
+/// Files under `root`, sorted, plus the folders that couldn't be read
+///
+/// Hidden entries and KOReader `.sdr` sidecar folders are skipped, and
+/// symlinked folders aren't followed.
+pub(super) fn collect_files(
+    root: &Path,
+    recursive: bool,
+) -> (Vec<PathBuf>, Vec<FolderImportFailure>) {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = match std::fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(e) => {
+                errors.push(FolderImportFailure {
+                    path: folder.display().to_string(),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || name.ends_with(".sdr") {
+                continue;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if recursive {
+                    folders.push(path);
+                }
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    (files, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_files, collect_files, empty_output, store_file, PendingFile};
+    use crate::commands::import::parse_document_file;
+    use crate::database;
+    use crate::error::ReaderError;
+    use rusqlite::Connection;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn scan(conn: &Connection, root: &Path, files: &[&str]) -> (Vec<PendingFile>, usize) {
+        let mut output = empty_output(root);
+        let files = files.iter().map(|file| root.join(file)).collect();
+        let pending = classify_files(conn, files, &mut output).unwrap();
+        (pending, output.skipped_existing)
+    }
+
+    fn store(conn: &Connection, app_data_dir: &Path, file: &PendingFile) -> super::Result<String> {
+        let parsed = parse_document_file(&file.path, file.format).unwrap();
+        store_file(conn, app_data_dir, file, parsed)
+    }
+
+    fn test_dirs(name: &str) -> (PathBuf, PathBuf) {
+        let base = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("folder")).unwrap();
+        fs::create_dir_all(base.join("app")).unwrap();
+        (base.join("folder"), base.join("app"))
+    }
+
+    #[test]
+    fn collects_files_skipping_hidden_and_sidecar_folders() {
+        let root = std::env::temp_dir().join(format!("reader_folder_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("nested/deeper")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::create_dir_all(root.join("book.sdr")).unwrap();
+        for file in [
+            "a.epub",
+            ".DS_Store",
+            "nested/b.md",
+            "nested/deeper/c.pdf",
+            ".hidden/d.txt",
+            "book.sdr/metadata.epub.lua",
+        ] {
+            fs::write(root.join(file), b"x").unwrap();
+        }
+
+        let (recursive, errors) = collect_files(&root, true);
+        let (flat, _) = collect_files(&root, false);
+        let _ = fs::remove_dir_all(&root);
+
+        let relative = |files: Vec<std::path::PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(&root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+        assert!(errors.is_empty());
+        assert_eq!(
+            relative(recursive),
+            vec!["a.epub", "nested/b.md", "nested/deeper/c.pdf"]
+        );
+        assert_eq!(relative(flat), vec!["a.epub"]);
+    }
+
+    #[test]
+    fn classifies_new_unchanged_touched_changed_and_duplicate_files() {
+        let (root, app_data_dir) = test_dirs("reader_folder_classify_test");
+        let conn = database::open_in_memory();
+        fs::write(root.join("a.md"), "# A\n\nFirst book.\n").unwrap();
+        fs::write(root.join("b.md"), "# B\n\nSecond book.\n").unwrap();
+        fs::write(root.join("notes.xyz"), [0u8, 1, 2]).unwrap();
+
+        let (pending, skipped) = scan(&conn, &root, &["a.md", "b.md", "notes.xyz"]);
+        assert_eq!(skipped, 0);
+        assert!(pending.iter().all(|file| file.replaces.is_none()));
+        let ids: Vec<String> = pending
+            .iter()
+            .map(|file| store(&conn, &app_data_dir, file).unwrap())
+            .collect();
+
+        // A copy of a file already in the library is refused when stored
+        fs::copy(root.join("a.md"), root.join("a copy.md")).unwrap();
+        let (pending, _) = scan(&conn, &root, &["a copy.md"]);
+        assert_eq!(pending.len(), 1);
+        assert!(matches!(
+            store(&conn, &app_data_dir, &pending[0]),
+            Err(ReaderError::DuplicateDocument(_))
+        ));
+
+        // Touched only: skipped, and the new time is recorded
+        let touched = SystemTime::now() + Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(root.join("a.md"))
+            .unwrap()
+            .set_modified(touched)
+            .unwrap();
+        // Edited: imported again into the same document
+        fs::write(root.join("b.md"), "# B\n\nSecond book, revised.\n").unwrap();
+
+        let (pending, skipped) = scan(&conn, &root, &["a.md", "b.md"]);
+        assert_eq!(skipped, 1);
+        assert_eq!(pending.len(), 1);
+        assert!(pending[0].path.ends_with("b.md"));
+        assert_eq!(pending[0].replaces.as_ref(), Some(&ids[1]));
+
+        let (_, skipped) = scan(&conn, &root, &["a.md"]);
+        assert_eq!(skipped, 1);
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+    }
+
+    #[test]
+    fn updates_a_changed_file_in_its_document() {
+        let (root, app_data_dir) = test_dirs("reader_folder_update_test");
+        let conn = database::open_in_memory();
+        let path = root.join("book.md");
+        fs::write(&path, "# Book\n\nOld text.\n").unwrap();
+        let (pending, _) = scan(&conn, &root, &["book.md"]);
+        let doc_id = store(&conn, &app_data_dir, &pending[0]).unwrap();
+        let old_hash = database::get_document(&conn, &doc_id)
+            .unwrap()
+            .unwrap()
+            .content_hash;
+
+        fs::write(&path, "# Book\n\nNew and longer text.\n").unwrap();
+        let (pending, _) = scan(&conn, &root, &["book.md"]);
+        assert_eq!(store(&conn, &app_data_dir, &pending[0]).unwrap(), doc_id);
+
+        assert_eq!(database::list_documents(&conn).unwrap().len(), 1);
+        let doc = database::get_document(&conn, &doc_id).unwrap().unwrap();
+        assert_ne!(doc.content_hash, old_hash);
+        let texts: Vec<String> = database::list_paragraphs(&conn, &doc_id)
+            .unwrap()
+            .into_iter()
+            .map(|paragraph| paragraph.text)
+            .collect();
+        assert!(texts.iter().any(|text| text == "New and longer text."));
+        assert!(!texts.iter().any(|text| text == "Old text."));
+        // Unchanged since the update
+        let (_, skipped) = scan(&conn, &root, &["book.md"]);
+        assert_eq!(skipped, 1);
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+    }
+}
@@ -1,12 +1,27 @@
+use super::library::{
+    app_data_dir, copy_into_library, managed_library_enabled, release_library_file, store_images,
+};
+use crate::config::load_config;
 use crate::database;
 use crate::error::{ReaderError, Result};
-use crate::parsers::{EpubParser, MarkdownParser, PdfParser};
+use crate::parsers::{
+    decode_html, extract_article, Article, ArticleBlock, AsciidocParser, DocxParser, EpubParser,
+    Fb2Parser, HtmlParser, MarkdownParser, MobiParser, NotebookParser, OcrOptions, ParseExtras,
+    PdfParser, RstParser, TextParser, PARAGRAPH_KIND_OCR, PARAGRAPH_KIND_TEXT,
+};
 use reqwest::Url;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 use tokio::time::Duration;
 
+const MAX_FETCHED_CONTENT_BYTES: usize = 2_000_000;
+/// Below this much extracted text the local extractor is assumed to have
+/// missed the article (e.g. client-rendered pages) and jina reader is tried
+pub(crate) const MIN_LOCAL_ARTICLE_CHARS: usize = 200;
+
 #[derive(Clone, serde::Serialize)]
 pub struct ImportProgress {
     pub current: usize,
@@ -18,12 +33,43 @@ pub struct ImportProgress {
 pub async fn import_epub(app_handle: AppHandle, file_path: String) -> Result<String> {
     let mut parser = EpubParser::new(&file_path)?;
     let (metadata, chapters) = parser.parse_all()?;
-    import_document_internal(app_handle, metadata, chapters).await
+    let extras = ParseExtras {
+        links: parser.take_internal_links(),
+        identifiers: parser.identifiers(),
+        ..Default::default()
+    };
+    import_document_with_extras(app_handle, metadata, chapters, extras).await
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct PdfImportOutput {
+    pub doc_id: String,
+    /// 1-based pages whose text was recognised by OCR
+    pub ocr_pages: Vec<usize>,
 }
 
 #[tauri::command]
-pub async fn import_pdf(app_handle: AppHandle, file_path: String) -> Result<String> {
-    let parser = PdfParser::new(&file_path)?;
+pub async fn import_pdf(app_handle: AppHandle, file_path: String) -> Result<PdfImportOutput> {
+    // An unreadable config should not block imports; fall back to defaults
+    let config = load_config().unwrap_or_default();
+    let mut parser = PdfParser::new(&file_path)?;
+    if config.ocr_enabled {
+        parser = parser.with_ocr(OcrOptions {
+            languages: config.ocr_languages,
+            tesseract_path: config.ocr_tesseract_path,
+        });
+    }
+    let (metadata, chapters) = parser.parse_all()?;
+    let extras = parser.take_extras();
+    let ocr_pages = parser.ocr_pages().to_vec();
+
+    let doc_id = import_document_with_extras(app_handle, metadata, chapters, extras).await?;
+    Ok(PdfImportOutput { doc_id, ocr_pages })
+}
+
+#[tauri::command]
+pub async fn import_docx(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let mut parser = DocxParser::new(&file_path)?;
     let (metadata, chapters) = parser.parse_all()?;
     import_document_internal(app_handle, metadata, chapters).await
 }
@@ -35,22 +81,342 @@ pub async fn import_markdown(app_handle: AppHandle, file_path: String) -> Result
     import_document_internal(app_handle, metadata, chapters).await
 }
 
+#[tauri::command]
+pub async fn import_notebook(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = NotebookParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_rst(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = RstParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_asciidoc(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = AsciidocParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_html(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = HtmlParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_text(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = TextParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_fb2(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = Fb2Parser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+#[tauri::command]
+pub async fn import_mobi(app_handle: AppHandle, file_path: String) -> Result<String> {
+    let parser = MobiParser::new(&file_path)?;
+    let (metadata, chapters) = parser.parse_all()?;
+    import_document_internal(app_handle, metadata, chapters).await
+}
+
+/// Document metadata, chapters and extras as returned by a parser
+pub(crate) type ParsedDocument = (
+    crate::models::NewDocument,
+    Vec<(String, i32, String, Vec<String>)>,
+    ParseExtras,
+);
+
+/// Parses a file with the parser for `extension` (lower case, without the dot)
+pub(crate) fn parse_document_file(file_path: &str, extension: &str) -> Result<ParsedDocument> {
+    let parsed = match extension {
+        "epub" => {
+            let mut parser = EpubParser::new(file_path)?;
+            let (metadata, chapters) = parser.parse_all()?;
+            let extras = ParseExtras {
+                links: parser.take_internal_links(),
+                identifiers: parser.identifiers(),
+                ..Default::default()
+            };
+            (metadata, chapters, extras)
+        }
+        "pdf" => {
+            let config = load_config().unwrap_or_default();
+            let mut parser = PdfParser::new(file_path)?;
+            if config.ocr_enabled {
+                parser = parser.with_ocr(OcrOptions {
+                    languages: config.ocr_languages,
+                    tesseract_path: config.ocr_tesseract_path,
+                });
+            }
+            let (metadata, chapters) = parser.parse_all()?;
+            (metadata, chapters, parser.take_extras())
+        }
+        "mobi" | "azw3" | "azw" => {
+            let (metadata, chapters) = MobiParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "fb2" => {
+            let (metadata, chapters) = Fb2Parser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "docx" => {
+            let (metadata, chapters) = DocxParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "txt" => {
+            let (metadata, chapters) = TextParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "md" | "markdown" => {
+            let (metadata, chapters) = MarkdownParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "html" | "htm" | "mhtml" | "mht" => {
+            let (metadata, chapters) = HtmlParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "ipynb" => {
+            let (metadata, chapters) = NotebookParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "rst" => {
+            let (metadata, chapters) = RstParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        "adoc" | "asciidoc" => {
+            let (metadata, chapters) = AsciidocParser::new(file_path)?.parse_all()?;
+            (metadata, chapters, ParseExtras::default())
+        }
+        other => {
+            return Err(ReaderError::InvalidArgument(format!(
+                "Unsupported file format: {}",
+                other
+            )))
+        }
+    };
+    Ok(parsed)
+}
+
 #[tauri::command]
 pub async fn import_url(app_handle: AppHandle, url: String) -> Result<String> {
     let normalized_url = normalize_http_url(&url)?;
-    let reader_url = format!("https://r.jina.ai/{}", normalized_url.as_str());
+    let client = build_http_client()?;
+    let (metadata, cleaned_body) = fetch_article(&client, &normalized_url).await?;
+    import_article_markdown(app_handle, &normalized_url, metadata, &cleaned_body).await
+}
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(20))
-        .user_agent("reader/0.3.5")
-        .build()
-        .map_err(|e| ReaderError::ModelApi(format!("Failed to create HTTP client: {}", e)))?;
+/// Fetches a web article as markdown
+///
+/// Extracts locally first; jina reader handles pages that need rendering.
+pub(crate) async fn fetch_article(
+    client: &reqwest::Client,
+    url: &Url,
+) -> Result<(ReaderMetadata, String)> {
+    match fetch_local_article(client, url).await {
+        Ok(Some(article)) => Ok(article),
+        Ok(None) => {
+            tracing::info!(
+                "Local extraction found too little text in {}, falling back to jina reader",
+                url
+            );
+            fetch_reader_article(client, url).await
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Failed to fetch {} directly, falling back to jina reader: {}",
+                url,
+                e
+            );
+            fetch_reader_article(client, url).await
+        }
+    }
+}
+
+/// Writes an article to a markdown file under app data and imports it
+pub(crate) async fn import_article_markdown(
+    app_handle: AppHandle,
+    url: &Url,
+    metadata: ReaderMetadata,
+    cleaned_body: &str,
+) -> Result<String> {
+    let markdown = article_markdown(url, &metadata, cleaned_body, None);
+    let app_data_dir = app_data_dir(&app_handle)?;
+    let url = url.clone();
+    database::run_blocking(&app_handle, move |conn| {
+        store_article(conn, &app_data_dir, &url, metadata.author, &markdown)
+    })
+    .await
+}
+
+/// Markdown for a web article
+///
+/// The file starts with a header of source, author and date (plus the feed
+/// name for feed entries), followed by summary, media links and content.
+pub(crate) fn article_markdown(
+    url: &Url,
+    metadata: &ReaderMetadata,
+    cleaned_body: &str,
+    feed_name: Option<&str>,
+) -> String {
+    let extracted_title = metadata
+        .title
+        .clone()
+        .unwrap_or_else(|| inferred_title_from_url(url));
+    let summary = build_body_summary(cleaned_body);
+    let media_links = extract_media_links(cleaned_body);
+    let media_section = if media_links.is_empty() {
+        "_No key image/video links detected._".to_string()
+    } else {
+        media_links
+            .iter()
+            .map(|link| format!("- {}", link))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let feed_line = feed_name
+        .map(|name| format!("> Feed: {}\n", name))
+        .unwrap_or_default();
+    let author = metadata.author.as_deref().unwrap_or("Unknown");
+    let published = metadata.published_time.as_deref().unwrap_or("Unknown");
+
+    format!(
+        "# {}\n\n\
+         > Source: {}\n\
+         {}\
+         > Author: {}\n\
+         > Published: {}\n\n\
+         ## Summary\n\n\
+         {}\n\n\
+         ## Media Links\n\n\
+         {}\n\n\
+         ## Content\n\n\
+         {}",
+        extracted_title, url, feed_line, author, published, summary, media_section, cleaned_body
+    )
+}
+
+/// Writes an article's markdown under app data and imports it
+///
+/// An article already in the library is refused before anything is written.
+pub(crate) fn store_article(
+    conn: &Connection,
+    app_data_dir: &Path,
+    url: &Url,
+    author: Option<String>,
+    markdown: &str,
+) -> Result<String> {
+    if let Some(existing) = find_article_document(conn, markdown)? {
+        return Err(ReaderError::DuplicateDocument(existing.title));
+    }
+    let markdown_path = build_import_markdown_path(app_data_dir, url);
+    if let Some(parent) = markdown_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&markdown_path, markdown)?;
+
+    let parser = MarkdownParser::new(&markdown_path.to_string_lossy())?;
+    let (mut document, chapters) = parser.parse_all()?;
+    if author.is_some() {
+        document.author = author;
+    }
+    store_document(
+        conn,
+        app_data_dir,
+        document,
+        chapters,
+        &ParseExtras::default(),
+    )
+}
+
+/// The library document whose file holds exactly this article markdown
+pub(crate) fn find_article_document(
+    conn: &Connection,
+    markdown: &str,
+) -> Result<Option<crate::models::Document>> {
+    let documents = database::list_documents(conn)?;
+    find_document_with_content(
+        conn,
+        &documents,
+        &content_hash(markdown.as_bytes()),
+        markdown.len() as u64,
+    )
+}
 
+/// Fetches the page itself and extracts the article as markdown
+///
+/// Returns None when the response is not HTML or too little text was found.
+async fn fetch_local_article(
+    client: &reqwest::Client,
+    url: &Url,
+) -> Result<Option<(ReaderMetadata, String)>> {
     let response = client
-        .get(&reader_url)
+        .get(url.clone())
         .send()
         .await
-        .map_err(|e| ReaderError::ModelApi(format!("Failed to fetch URL via jina reader: {}", e)))?;
+        .map_err(|e| ReaderError::ModelApi(format!("Failed to fetch URL: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(ReaderError::ModelApi(format!(
+            "URL fetch failed with status {}",
+            response.status()
+        )));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if !content_type.is_empty() && !content_type.contains("html") {
+        return Ok(None);
+    }
+    let charset = content_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("charset="))
+        .map(|charset| charset.trim_matches('"').to_string());
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| ReaderError::ModelApi(format!("Failed to read fetched HTML: {}", e)))?;
+    if bytes.len() > MAX_FETCHED_CONTENT_BYTES {
+        return Ok(None);
+    }
+
+    let article = extract_article(&decode_html(&bytes, charset.as_deref()));
+    if article.text_len() < MIN_LOCAL_ARTICLE_CHARS {
+        return Ok(None);
+    }
+
+    let body = article_to_markdown(&article, url);
+    let metadata = ReaderMetadata {
+        title: article.title,
+        author: article.metadata.author,
+        published_time: article.metadata.published_time,
+    };
+    Ok(Some((metadata, body)))
+}
+
+/// Fetches the page through jina reader and cleans up its markdown
+async fn fetch_reader_article(
+    client: &reqwest::Client,
+    url: &Url,
+) -> Result<(ReaderMetadata, String)> {
+    let reader_url = format!("https://r.jina.ai/{}", url.as_str());
+    let response = client.get(&reader_url).send().await.map_err(|e| {
+        ReaderError::ModelApi(format!("Failed to fetch URL via jina reader: {}", e))
+    })?;
 
     if !response.status().is_success() {
         return Err(ReaderError::ModelApi(format!(
@@ -69,17 +435,13 @@ pub async fn import_url(app_handle: AppHandle, url: String) -> Result<String> {
             "Fetched content is empty. The source site may block extraction.".to_string(),
         ));
     }
-    if trimmed.len() > 2_000_000 {
+    if trimmed.len() > MAX_FETCHED_CONTENT_BYTES {
         return Err(ReaderError::ModelApi(
             "Fetched content is too large (over 2MB)".to_string(),
         ));
     }
 
     let metadata = extract_reader_metadata(trimmed);
-    let extracted_title = metadata
-        .title
-        .clone()
-        .unwrap_or_else(|| inferred_title_from_url(&normalized_url));
     let cleaned_body = extract_and_clean_reader_markdown(trimmed);
     if cleaned_body.trim().is_empty() {
         return Err(ReaderError::ModelApi(
@@ -87,59 +449,21 @@ pub async fn import_url(app_handle: AppHandle, url: String) -> Result<String> {
                 .to_string(),
         ));
     }
-    let summary = build_body_summary(&cleaned_body);
-    let media_links = extract_media_links(&cleaned_body);
-    let media_section = if media_links.is_empty() {
-        "_No key image/video links detected._".to_string()
-    } else {
-        media_links
-            .iter()
-            .map(|link| format!("- {}", link))
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
-    let author = metadata.author.unwrap_or_else(|| "Unknown".to_string());
-    let published = metadata
-        .published_time
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    let markdown = format!(
-        "# {}\n\n\
-         > Source: {}\n\
-         > Author: {}\n\
-         > Published: {}\n\n\
-         ## Summary\n\n\
-         {}\n\n\
-         ## Media Links\n\n\
-         {}\n\n\
-         ## Content\n\n\
-         {}",
-        extracted_title,
-        normalized_url,
-        author,
-        published,
-        summary,
-        media_section,
-        cleaned_body
-    );
-
-    let markdown_path = build_import_markdown_path(&app_handle, &normalized_url)?;
-    if let Some(parent) = markdown_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    std::fs::write(&markdown_path, markdown)?;
+    Ok((metadata, cleaned_body))
+}
 
-    import_markdown(app_handle, markdown_path.to_string_lossy().to_string()).await
+pub(crate) fn build_http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .user_agent("reader/0.3.5")
+        .build()
+        .map_err(|e| ReaderError::ModelApi(format!("Failed to create HTTP client: {}", e)))
 }
 
 #[tauri::command]
 pub async fn fetch_url_html(url: String) -> Result<String> {
     let normalized_url = normalize_http_url(&url)?;
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(20))
-        .user_agent("reader/0.3.5")
-        .build()
-        .map_err(|e| ReaderError::ModelApi(format!("Failed to create HTTP client: {}", e)))?;
+    let client = build_http_client()?;
     let response = client
         .get(normalized_url.clone())
         .send()
@@ -182,9 +506,9 @@ pub async fn import_markdown_content(
     let markdown = format!("# {}\n\n{}{}", safe_title, source_block, content.trim());
 
     let markdown_path = build_import_markdown_path(
-        &app_handle,
+        &app_data_dir(&app_handle)?,
         &normalize_http_url(source_url_normalized.as_deref().unwrap_or("https://example.com"))?,
-    )?;
+    );
     if let Some(parent) = markdown_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -198,14 +522,77 @@ async fn import_document_internal(
     metadata: crate::models::NewDocument,
     chapters: Vec<(String, i32, String, Vec<String>)>,
 ) -> Result<String> {
-    // Get database connection
-    let conn = database::get_connection(&app_handle)?;
+    import_document_with_extras(app_handle, metadata, chapters, ParseExtras::default()).await
+}
+
+pub(crate) async fn import_document_with_extras(
+    app_handle: AppHandle,
+    metadata: crate::models::NewDocument,
+    chapters: Vec<(String, i32, String, Vec<String>)>,
+    extras: ParseExtras,
+) -> Result<String> {
+    let app_data_dir = app_data_dir(&app_handle)?;
+    database::run_blocking(&app_handle, move |conn| {
+        store_document(conn, &app_data_dir, metadata, chapters, &extras)
+    })
+    .await
+}
+
+/// Adds a parsed document to the library, returning its id
+///
+/// Refuses a second copy of a file already in the library. In managed mode
+/// files from outside app data are copied into the library first.
+pub(crate) fn store_document(
+    conn: &Connection,
+    app_data_dir: &Path,
+    metadata: crate::models::NewDocument,
+    chapters: Vec<(String, i32, String, Vec<String>)>,
+    extras: &ParseExtras,
+) -> Result<String> {
+    // Refuse a second copy of a file already in the library
+    let content_hash = file_content_hash(Path::new(&metadata.file_path)).ok();
+    if let Some(existing) =
+        find_duplicate_document(conn, &metadata.file_path, content_hash.as_deref())?
+    {
+        return Err(ReaderError::DuplicateDocument(existing.title));
+    }
+
+    // In managed mode the document reads from a copy the app owns
+    let mut metadata = metadata;
+    let mut chapters = chapters;
+    let mut original_path = None;
+    if let Some(content_hash) = content_hash.as_deref() {
+        let source = Path::new(&metadata.file_path);
+        if !source.starts_with(app_data_dir) && managed_library_enabled() {
+            if let Some(copy) = copy_into_library(app_data_dir, source, content_hash)? {
+                store_images(app_data_dir, content_hash, &mut chapters)?;
+                let copy = copy.to_string_lossy().to_string();
+                original_path = Some(std::mem::replace(&mut metadata.file_path, copy));
+            }
+        }
+    }
 
     // Start transaction for data integrity
     let tx = conn.unchecked_transaction()?;
 
     // Insert document
     let doc = database::insert_document(&tx, metadata)?;
+    if let Some(content_hash) = &content_hash {
+        database::set_document_content_hash(&tx, &doc.id, content_hash)?;
+    }
+    if let Some(original_path) = &original_path {
+        database::set_document_file_paths(&tx, &doc.id, &doc.file_path, Some(original_path))?;
+    }
+    database::fill_document_identifiers(
+        &tx,
+        &doc.id,
+        extras.identifiers.isbn.as_deref(),
+        extras.identifiers.doi.as_deref(),
+    )?;
+    if extras.series.is_some() {
+        database::set_document_series(&tx, &doc.id, extras.series.as_deref(), extras.series_index)?;
+    }
+    database::add_document_tags(&tx, &doc.id, &extras.tags)?;
 
     tracing::info!(
         "Importing document {} with {} chapters",
@@ -213,8 +600,29 @@ async fn import_document_internal(
         chapters.len()
     );
 
+    insert_document_content(&tx, &doc.id, chapters, extras)?;
+
+    // Commit transaction to save all changes atomically
+    tx.commit()?;
+
+    tracing::info!("Document import completed successfully");
+    Ok(doc.id)
+}
+
+/// Inserts a document's sections, paragraphs and internal links
+///
+/// Returns the inserted paragraphs in reading order.
+pub(crate) fn insert_document_content(
+    conn: &Connection,
+    doc_id: &str,
+    chapters: Vec<(String, i32, String, Vec<String>)>,
+    extras: &ParseExtras,
+) -> Result<Vec<crate::models::Paragraph>> {
+    // Inserted paragraphs per chapter, used to resolve parser-relative positions
+    let mut inserted: Vec<Vec<crate::models::Paragraph>> = Vec::with_capacity(chapters.len());
+
     // Insert sections and paragraphs
-    for (title, order_index, href, paragraphs) in chapters {
+    for (chapter_idx, (title, order_index, href, paragraphs)) in chapters.into_iter().enumerate() {
         tracing::info!(
             "Processing chapter {}: {} ({} paragraphs)",
             title,
@@ -222,18 +630,29 @@ async fn import_document_internal(
             paragraphs.len()
         );
 
-        let section = database::insert_section(&tx, &doc.id, &title, order_index, &href)?;
+        let section = database::insert_section(conn, doc_id, &title, order_index, &href)?;
+        let mut section_paragraphs = Vec::with_capacity(paragraphs.len());
 
         for (para_order, para_text) in paragraphs.iter().enumerate() {
-            let location = format!("{}#p{}", href, para_order);
-            database::insert_paragraph(
-                &tx,
-                &doc.id,
+            let location = extras
+                .locations
+                .get(&(chapter_idx, para_order))
+                .cloned()
+                .unwrap_or_else(|| format!("{}#p{}", href, para_order));
+            let kind = extras
+                .kinds
+                .get(&(chapter_idx, para_order))
+                .map(String::as_str)
+                .unwrap_or(PARAGRAPH_KIND_TEXT);
+            section_paragraphs.push(database::insert_paragraph(
+                conn,
+                doc_id,
                 &section.id,
                 para_order as i32,
                 para_text,
                 &location,
-            )?;
+                kind,
+            )?);
         }
 
         tracing::info!(
@@ -241,20 +660,116 @@ async fn import_document_internal(
             paragraphs.len(),
             section.id
         );
+        inserted.push(section_paragraphs);
     }
 
-    // Commit transaction to save all changes atomically
-    tx.commit()?;
+    // Resolve footnotes and internal links to the inserted paragraphs
+    let mut link_count = 0usize;
+    for link in &extras.links {
+        let source = inserted
+            .get(link.source.0)
+            .and_then(|paragraphs| paragraphs.get(link.source.1));
+        let target = inserted
+            .get(link.target.0)
+            .and_then(|paragraphs| paragraphs.get(link.target.1));
+        let (Some(source), Some(target)) = (source, target) else {
+            continue;
+        };
+        database::insert_paragraph_link(
+            conn,
+            doc_id,
+            &source.id,
+            &target.id,
+            &target.location,
+            &link.kind,
+            &link.label,
+        )?;
+        link_count += 1;
+    }
+    if link_count > 0 {
+        tracing::info!("Inserted {} paragraph links", link_count);
+    }
 
-    tracing::info!("Document import completed successfully");
-    Ok(doc.id)
+    Ok(inserted.into_iter().flatten().collect())
+}
+
+/// SHA-256 of a file's contents, hex encoded
+pub(crate) fn file_content_hash(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The hash `file_content_hash` gives a file holding `bytes`
+fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// The library document imported from the same path or with the same contents
+fn find_duplicate_document(
+    conn: &Connection,
+    file_path: &str,
+    content_hash: Option<&str>,
+) -> Result<Option<crate::models::Document>> {
+    let documents = database::list_documents(conn)?;
+    if let Some(doc) = documents
+        .iter()
+        .find(|doc| doc.file_path == file_path || doc.original_path.as_deref() == Some(file_path))
+    {
+        return Ok(Some(doc.clone()));
+    }
+    let Some(content_hash) = content_hash else {
+        return Ok(None);
+    };
+    let size = std::fs::metadata(file_path)?.len();
+    find_document_with_content(conn, &documents, content_hash, size)
+}
+
+/// The document among `documents` whose file has the given contents
+///
+/// Documents imported before content hashes were recorded get theirs
+/// computed here, but only when their file is `size` bytes long.
+fn find_document_with_content(
+    conn: &Connection,
+    documents: &[crate::models::Document],
+    content_hash: &str,
+    size: u64,
+) -> Result<Option<crate::models::Document>> {
+    if let Some(doc) = documents
+        .iter()
+        .find(|doc| doc.content_hash.as_deref() == Some(content_hash))
+    {
+        return Ok(Some(doc.clone()));
+    }
+
+    for doc in documents.iter().filter(|doc| doc.content_hash.is_none()) {
+        let same_size = std::fs::metadata(&doc.file_path).is_ok_and(|meta| meta.len() == size);
+        if !same_size {
+            continue;
+        }
+        let Ok(existing_hash) = file_content_hash(Path::new(&doc.file_path)) else {
+            continue;
+        };
+        database::set_document_content_hash(conn, &doc.id, &existing_hash)?;
+        if existing_hash == content_hash {
+            return Ok(Some(doc.clone()));
+        }
+    }
+    Ok(None)
 }
 
+/// Lists the library, optionally narrowed to a tag, collection or series
 #[tauri::command]
-pub async fn list_documents(app_handle: AppHandle) -> Result<Vec<crate::models::Document>> {
-    let conn = database::get_connection(&app_handle)?;
-    let docs = database::list_documents(&conn)?;
-    Ok(docs)
+pub async fn list_documents(
+    app_handle: AppHandle,
+    filter: Option<crate::models::DocumentFilter>,
+) -> Result<Vec<crate::models::Document>> {
+    let filter = filter.unwrap_or_default();
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_documents_filtered(conn, &filter)?)
+    })
+    .await
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -269,42 +784,43 @@ pub async fn get_document_previews(
     doc_ids: Vec<String>,
     max_chars: usize,
 ) -> Result<Vec<DocumentPreview>> {
-    let conn = database::get_connection(&app_handle)?;
     let char_limit = max_chars.clamp(160, 4000);
-    let mut previews = Vec::new();
-
-    let mut stmt = conn.prepare(
-        "SELECT p.text
-         FROM paragraphs p
-         JOIN sections s ON p.section_id = s.id
-         WHERE p.doc_id = ?1
-         ORDER BY s.order_index, p.order_index
-         LIMIT 12",
-    )?;
-
-    for doc_id in doc_ids {
-        let rows = stmt.query_map([&doc_id], |row| row.get::<_, String>(0))?;
-        let mut merged = String::new();
-        for row in rows {
-            let text = row.unwrap_or_default();
-            let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
-            if normalized.is_empty() {
-                continue;
-            }
-            if !merged.is_empty() {
-                merged.push(' ');
-            }
-            merged.push_str(&normalized);
-            if merged.chars().count() >= char_limit {
-                break;
+    database::run_blocking(&app_handle, move |conn| {
+        let mut previews = Vec::new();
+        let mut stmt = conn.prepare_cached(
+            "SELECT p.text
+             FROM paragraphs p
+             JOIN sections s ON p.section_id = s.id
+             WHERE p.doc_id = ?1
+             ORDER BY s.order_index, p.order_index
+             LIMIT 12",
+        )?;
+
+        for doc_id in doc_ids {
+            let rows = stmt.query_map([&doc_id], |row| row.get::<_, String>(0))?;
+            let mut merged = String::new();
+            for row in rows {
+                let text = row.unwrap_or_default();
+                let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if normalized.is_empty() {
+                    continue;
+                }
+                if !merged.is_empty() {
+                    merged.push(' ');
+                }
+                merged.push_str(&normalized);
+                if merged.chars().count() >= char_limit {
+                    break;
+                }
             }
-        }
 
-        let preview = merged.chars().take(char_limit).collect::<String>();
-        previews.push(DocumentPreview { doc_id, preview });
-    }
+            let preview = merged.chars().take(char_limit).collect::<String>();
+            previews.push(DocumentPreview { doc_id, preview });
+        }
 
-    Ok(previews)
+        Ok(previews)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -312,16 +828,24 @@ pub async fn get_document(
     app_handle: AppHandle,
     id: String,
 ) -> Result<Option<crate::models::Document>> {
-    let conn = database::get_connection(&app_handle)?;
-    let doc = database::get_document(&conn, &id)?;
-    Ok(doc)
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::get_document(conn, &id)?)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn delete_document(app_handle: AppHandle, id: String) -> Result<()> {
-    let conn = database::get_connection(&app_handle)?;
-    database::delete_document(&conn, &id)?;
-    Ok(())
+    let app_data_dir = app_data_dir(&app_handle)?;
+    database::run_blocking(&app_handle, move |conn| {
+        let doc = database::get_document(conn, &id)?;
+        database::delete_document(conn, &id)?;
+        if let Some(doc) = doc {
+            release_library_file(conn, &app_data_dir, &doc.file_path)?;
+        }
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -329,9 +853,10 @@ pub async fn get_document_sections(
     app_handle: AppHandle,
     doc_id: String,
 ) -> Result<Vec<crate::models::Section>> {
-    let conn = database::get_connection(&app_handle)?;
-    let sections = database::list_sections(&conn, &doc_id)?;
-    Ok(sections)
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_sections(conn, &doc_id)?)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -339,12 +864,40 @@ pub async fn get_section_paragraphs(
     app_handle: AppHandle,
     section_id: String,
 ) -> Result<Vec<crate::models::Paragraph>> {
-    let conn = database::get_connection(&app_handle)?;
-    let paragraphs = database::list_paragraphs_by_section(&conn, &section_id)?;
-    Ok(paragraphs)
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_paragraphs_by_section(conn, &section_id)?)
+    })
+    .await
 }
 
-fn normalize_http_url(input: &str) -> Result<Url> {
+/// Lists the 1-based PDF pages whose text was recognised by OCR
+#[tauri::command]
+pub async fn get_document_ocr_pages(app_handle: AppHandle, doc_id: String) -> Result<Vec<usize>> {
+    let paragraphs = database::run_blocking(&app_handle, move |conn| {
+        Ok(database::list_paragraphs(conn, &doc_id)?)
+    })
+    .await?;
+    let mut pages: Vec<usize> = paragraphs
+        .iter()
+        .filter(|p| p.kind == PARAGRAPH_KIND_OCR)
+        .filter_map(|p| page_from_location(&p.location))
+        .collect();
+    pages.sort_unstable();
+    pages.dedup();
+    Ok(pages)
+}
+
+/// First page of a PDF paragraph location such as `page12#p3` or `page12-13#p0`
+fn page_from_location(location: &str) -> Option<usize> {
+    let digits: String = location
+        .strip_prefix("page")?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+pub(crate) fn normalize_http_url(input: &str) -> Result<Url> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return Err(ReaderError::InvalidArgument("URL cannot be empty".to_string()));
@@ -378,10 +931,10 @@ fn inferred_title_from_url(url: &Url) -> String {
 }
 
 #[derive(Default)]
-struct ReaderMetadata {
-    title: Option<String>,
-    author: Option<String>,
-    published_time: Option<String>,
+pub(crate) struct ReaderMetadata {
+    pub(crate) title: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) published_time: Option<String>,
 }
 
 fn extract_reader_metadata(text: &str) -> ReaderMetadata {
@@ -707,6 +1260,40 @@ fn collapse_blank_lines(lines: &[String]) -> String {
     out.trim().to_string()
 }
 
+/// Renders an extracted article as markdown for the URL import template
+///
+/// Headings are demoted one level so they nest under "## Content", and
+/// relative image links are made absolute.
+pub(crate) fn article_to_markdown(article: &Article, page_url: &Url) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut skipped_title = false;
+    for block in &article.blocks {
+        match block {
+            ArticleBlock::Heading { level, text } => {
+                if !skipped_title && *level == 1 && article.title.as_deref() == Some(text.as_str())
+                {
+                    skipped_title = true;
+                    continue;
+                }
+                let depth = (*level as usize + 1).min(6);
+                blocks.push(format!("{} {}", "#".repeat(depth), text));
+            }
+            ArticleBlock::Paragraph(text) => blocks.push(text.clone()),
+            ArticleBlock::ListItem(text) => blocks.push(format!("- {}", text)),
+            ArticleBlock::Code(text) => blocks.push(format!("```\n{}\n```", text)),
+            ArticleBlock::Image { src, alt } => {
+                if src.starts_with("data:") {
+                    continue;
+                }
+                if let Ok(absolute) = page_url.join(src) {
+                    blocks.push(format!("![{}]({})", alt, absolute));
+                }
+            }
+        }
+    }
+    blocks.join("\n\n")
+}
+
 fn build_body_summary(body: &str) -> String {
     let mut paragraphs = Vec::new();
     for part in body.split("\n\n") {
@@ -778,12 +1365,7 @@ fn is_media_url(url: &str) -> bool {
         || lower.contains("vimeo.com/")
 }
 
-fn build_import_markdown_path(app_handle: &AppHandle, url: &Url) -> Result<PathBuf> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| ReaderError::Internal(format!("Failed to resolve app data dir: {}", e)))?;
-
+fn build_import_markdown_path(app_data_dir: &Path, url: &Url) -> PathBuf {
     let safe_host = url.host_str().unwrap_or("web").replace('.', "_");
     let safe_tail = url
         .path_segments()
@@ -799,10 +1381,10 @@ fn build_import_markdown_path(app_handle: &AppHandle, url: &Url) -> Result<PathB
             }
         })
         .collect::<String>();
-    let ts = chrono::Utc::now().timestamp();
+    let ts = chrono::Utc::now().timestamp_millis();
 
-    Ok(app_data_dir
+    app_data_dir
         .join("imports")
         .join("url")
-        .join(format!("{}_{}_{}.md", safe_host, safe_tail, ts)))
+        .join(format!("{}_{}_{}.md", safe_host, safe_tail, ts))
 }
@@ -0,0 +1,535 @@
+//! Managed library storage
+//!
+//! With `managed_library` enabled, imported files are copied into a
+//! content-addressed folder under app data, so moving or deleting the
+//! original no longer breaks re-parsing. The document's `file_path` then
+//! points at the copy and `original_path` at the file it came from. Images
+//! extracted from managed documents are kept in the library as well.
+
+use super::folder::collect_files;
+use super::import::{file_content_hash, parse_document_file};
+use super::reimport::replace_document_content;
+use crate::config::load_config;
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::models::Document;
+use crate::parsers::{detect_format, IMAGE_MARKER_PREFIX};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Folder under app data holding the managed copies
+const LIBRARY_DIR: &str = "library";
+/// Folder under the library holding the images extracted from managed copies
+const IMAGES_DIR: &str = "images";
+
+#[derive(Clone, serde::Serialize)]
+pub struct RepairOutput {
+    /// Ids of documents pointed at a found file
+    pub relocated: Vec<String>,
+    /// Titles of documents whose file is still missing
+    pub still_missing: Vec<String>,
+}
+
+/// Documents whose file is no longer where the library expects it
+#[tauri::command]
+pub async fn list_missing_documents(app_handle: AppHandle) -> Result<Vec<Document>> {
+    let conn = database::get_connection(&app_handle)?;
+    Ok(database::list_documents(&conn)?
+        .into_iter()
+        .filter(|doc| !Path::new(&doc.file_path).is_file())
+        .collect())
+}
+
+/// Points a document at a file chosen by the user
+///
+/// When the file's contents differ from what was imported, the document is
+/// re-imported from it, keeping annotations as `reimport_document` does.
+#[tauri::command]
+pub async fn relocate_document(
+    app_handle: AppHandle,
+    id: String,
+    file_path: String,
+) -> Result<Document> {
+    let doc = {
+        let conn = database::get_connection(&app_handle)?;
+        database::get_document(&conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?
+    };
+    let path = PathBuf::from(&file_path);
+    if !path.is_file() {
+        return Err(ReaderError::NotFound(file_path));
+    }
+
+    let content_hash = file_content_hash(&path)?;
+    let app_data_dir = app_data_dir(&app_handle)?;
+    if doc
+        .content_hash
+        .as_deref()
+        .is_some_and(|hash| hash != content_hash)
+    {
+        reparse_document(&app_handle, &app_data_dir, &doc, &path, &content_hash).await?;
+    }
+
+    let (doc, unstored_images) = {
+        let conn = database::get_connection(&app_handle)?;
+        point_document_at(&conn, &app_data_dir, &doc, &path, &content_hash)?;
+        let doc = database::get_document(&conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?;
+        let unstored_images = has_unstored_images(&conn, &app_data_dir, &doc)?;
+        (doc, unstored_images)
+    };
+    // Images of a document imported before it was managed are re-extracted
+    if unstored_images {
+        reparse_document(&app_handle, &app_data_dir, &doc, &path, &content_hash).await?;
+    }
+    Ok(doc)
+}
+
+/// Finds the files of missing documents again
+///
+/// A managed document whose copy is gone is restored from its original when
+/// that still exists. Others are looked for in `folder_path`, by content
+/// hash or, for documents imported before hashes were recorded, by name.
+#[tauri::command]
+pub async fn repair_missing_files(
+    app_handle: AppHandle,
+    folder_path: Option<String>,
+) -> Result<RepairOutput> {
+    let candidates = match folder_path {
+        Some(folder) => {
+            let root = PathBuf::from(folder);
+            if !root.is_dir() {
+                return Err(ReaderError::NotFound(root.display().to_string()));
+            }
+            tokio::task::spawn_blocking(move || collect_files(&root, true).0)
+                .await
+                .map_err(|e| ReaderError::Internal(e.to_string()))?
+        }
+        None => Vec::new(),
+    };
+    let mut finder = FileFinder::new(candidates);
+
+    let app_data_dir = app_data_dir(&app_handle)?;
+    let conn = database::get_connection(&app_handle)?;
+    let missing: Vec<Document> = database::list_documents(&conn)?
+        .into_iter()
+        .filter(|doc| !Path::new(&doc.file_path).is_file())
+        .collect();
+
+    let mut output = RepairOutput {
+        relocated: Vec::new(),
+        still_missing: Vec::new(),
+    };
+    for doc in missing {
+        let original = doc
+            .original_path
+            .as_deref()
+            .map(PathBuf::from)
+            .filter(|path| path.is_file());
+        let found = match original {
+            Some(path) => Some(path),
+            None => finder.find(&doc),
+        };
+        let Some(path) = found else {
+            output.still_missing.push(doc.title.clone());
+            continue;
+        };
+        let content_hash = file_content_hash(&path)?;
+        if doc
+            .content_hash
+            .as_deref()
+            .is_some_and(|hash| hash != content_hash)
+        {
+            // The original was edited since; re-import it instead
+            output.still_missing.push(doc.title.clone());
+            continue;
+        }
+        point_document_at(&conn, &app_data_dir, &doc, &path, &content_hash)?;
+        output.relocated.push(doc.id.clone());
+
+        // Images of a document imported before it was managed are re-extracted
+        let Some(doc) = database::get_document(&conn, &doc.id)? else {
+            continue;
+        };
+        if has_unstored_images(&conn, &app_data_dir, &doc)? {
+            if let Err(e) =
+                reparse_document(&app_handle, &app_data_dir, &doc, &path, &content_hash).await
+            {
+                tracing::warn!("Failed to re-extract images of {}: {}", doc.title, e);
+            }
+        }
+    }
+
+    tracing::info!(
+        "Repaired {} missing documents, {} still missing",
+        output.relocated.len(),
+        output.still_missing.len()
+    );
+    Ok(output)
+}
+
+/// Whether imports should be copied into the managed library
+pub(crate) fn managed_library_enabled() -> bool {
+    // An unreadable config should not block imports; fall back to defaults
+    load_config().unwrap_or_default().managed_library
+}
+
+/// Copies a file into the managed library unless it already lives under app data
+///
+/// Returns the path of the copy, or `None` when the file is kept where it is
+/// (e.g. articles the importer wrote itself).
+pub(crate) fn copy_into_library(
+    app_data_dir: &Path,
+    source: &Path,
+    content_hash: &str,
+) -> Result<Option<PathBuf>> {
+    if source.starts_with(app_data_dir) {
+        return Ok(None);
+    }
+    store_file(&app_data_dir.join(LIBRARY_DIR), source, content_hash).map(Some)
+}
+
+/// Copies the images a parse extracted into the managed library
+///
+/// Parsers write images to a temporary folder, which doesn't survive a
+/// reboot. The copies go to `<library>/images/<hash>/` and the image
+/// markers are pointed at them.
+pub(crate) fn store_images(
+    app_data_dir: &Path,
+    content_hash: &str,
+    chapters: &mut [(String, i32, String, Vec<String>)],
+) -> Result<()> {
+    let dir = images_dir(app_data_dir, content_hash);
+    let paragraphs = chapters
+        .iter_mut()
+        .flat_map(|(_, _, _, paragraphs)| paragraphs.iter_mut());
+    for (index, paragraph) in paragraphs.enumerate() {
+        let Some(source) = image_marker_path(paragraph).map(PathBuf::from) else {
+            continue;
+        };
+        let Some(file_name) = source.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if source.starts_with(&dir) || !source.is_file() {
+            continue;
+        }
+        std::fs::create_dir_all(&dir)?;
+        // Prefixed with the paragraph's position, as parsers may reuse names
+        let target = dir.join(format!("{:05}_{}", index, file_name));
+        std::fs::copy(&source, &target)?;
+        *paragraph = format!(
+            "{prefix}{path}]]",
+            prefix = IMAGE_MARKER_PREFIX,
+            path = target.to_string_lossy()
+        );
+    }
+    Ok(())
+}
+
+/// Updates a managed document's copy after its original changed and was re-imported
+pub(crate) fn refresh_library_copy(
+    conn: &Connection,
+    app_data_dir: &Path,
+    doc: &Document,
+    source: &Path,
+    content_hash: &str,
+) -> Result<()> {
+    if doc.original_path.is_none() || Path::new(&doc.file_path) == source {
+        return Ok(());
+    }
+    point_document_at(conn, app_data_dir, doc, source, content_hash)
+}
+
+/// Deletes a managed copy once no document uses it any more
+pub(crate) fn release_library_file(
+    conn: &Connection,
+    app_data_dir: &Path,
+    file_path: &str,
+) -> Result<()> {
+    let library_dir = app_data_dir.join(LIBRARY_DIR);
+    if !Path::new(file_path).starts_with(&library_dir) {
+        return Ok(());
+    }
+    let in_use = database::list_documents(conn)?
+        .iter()
+        .any(|doc| doc.file_path == file_path);
+    if in_use {
+        return Ok(());
+    }
+    // A copy is named by the hash its images are kept under
+    if let Some(content_hash) = Path::new(file_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+    {
+        let _ = std::fs::remove_dir_all(images_dir(app_data_dir, content_hash));
+    }
+    match std::fs::remove_file(file_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Parses a document's file again and swaps the result in, keeping annotations
+///
+/// Images are kept in the library when the document is or is about to be managed.
+async fn reparse_document(
+    app_handle: &AppHandle,
+    app_data_dir: &Path,
+    doc: &Document,
+    path: &Path,
+    content_hash: &str,
+) -> Result<()> {
+    let file_path = path.to_string_lossy().to_string();
+    let format = detect_format(path).ok_or_else(|| {
+        ReaderError::InvalidArgument(format!("Unsupported file format: {}", file_path))
+    })?;
+    let mut parsed = tokio::task::spawn_blocking(move || parse_document_file(&file_path, format))
+        .await
+        .map_err(|e| ReaderError::Internal(e.to_string()))??;
+    if doc.original_path.is_some() || managed_library_enabled() {
+        store_images(app_data_dir, content_hash, &mut parsed.1)?;
+    }
+    let conn = database::get_connection(app_handle)?;
+    replace_document_content(&conn, &doc.id, parsed, Some(content_hash))?;
+    Ok(())
+}
+
+/// Whether a managed document shows images kept outside the library
+fn has_unstored_images(conn: &Connection, app_data_dir: &Path, doc: &Document) -> Result<bool> {
+    let (Some(_), Some(content_hash)) = (&doc.original_path, &doc.content_hash) else {
+        return Ok(false);
+    };
+    let dir = images_dir(app_data_dir, content_hash);
+    Ok(database::list_paragraphs(conn, &doc.id)?
+        .iter()
+        .filter_map(|paragraph| image_marker_path(&paragraph.text))
+        .any(|path| !Path::new(path).starts_with(&dir)))
+}
+
+/// Records `path` as a document's file, copying it into the managed library
+/// when the document is managed or managed mode is on
+fn point_document_at(
+    conn: &Connection,
+    app_data_dir: &Path,
+    doc: &Document,
+    path: &Path,
+    content_hash: &str,
+) -> Result<()> {
+    let file_path = path.to_string_lossy().to_string();
+    let managed = doc.original_path.is_some() || managed_library_enabled();
+    let copy = if managed {
+        copy_into_library(app_data_dir, path, content_hash)?
+    } else {
+        None
+    };
+    match copy {
+        Some(copy) => database::set_document_file_paths(
+            conn,
+            &doc.id,
+            &copy.to_string_lossy(),
+            Some(&file_path),
+        )?,
+        None => database::set_document_file_paths(conn, &doc.id, &file_path, None)?,
+    }
+    database::set_document_content_hash(conn, &doc.id, content_hash)?;
+    release_library_file(conn, app_data_dir, &doc.file_path)?;
+    Ok(())
+}
+
+pub(crate) fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| ReaderError::Internal(format!("Failed to resolve app data dir: {}", e)))
+}
+
+/// Folder for the images extracted from the managed file with `content_hash`
+fn images_dir(app_data_dir: &Path, content_hash: &str) -> PathBuf {
+    app_data_dir
+        .join(LIBRARY_DIR)
+        .join(IMAGES_DIR)
+        .join(content_hash)
+}
+
+/// The image file a paragraph stands for, when it is an image marker
+fn image_marker_path(paragraph: &str) -> Option<&str> {
+    paragraph
+        .strip_prefix(IMAGE_MARKER_PREFIX)?
+        .strip_suffix("]]")
+}
+
+/// Copies `source` to `<library_dir>/<hash[..2]>/<hash>.<ext>`
+///
+/// The extension is kept so format detection still works on the copy. An
+/// existing copy is reused, since the same hash means the same contents.
+fn store_file(library_dir: &Path, source: &Path, content_hash: &str) -> Result<PathBuf> {
+    let file_name = match source.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}.{}", content_hash, ext.to_lowercase()),
+        None => content_hash.to_string(),
+    };
+    let dir = library_dir.join(content_hash.get(..2).unwrap_or(content_hash));
+    let target = dir.join(&file_name);
+    if target.is_file() {
+        return Ok(target);
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    // Copy under a temporary name so an interrupted copy is never reused
+    let partial = dir.join(format!("{}.partial", file_name));
+    std::fs::copy(source, &partial)?;
+    std::fs::rename(&partial, &target)?;
+    Ok(target)
+}
+
+/// Looks up missing documents among the files of a folder
+///
+/// Files are only hashed when a document needs it, and each at most once.
+struct FileFinder {
+    files: Vec<PathBuf>,
+    hashes: HashMap<PathBuf, Option<String>>,
+}
+
+impl FileFinder {
+    fn new(files: Vec<PathBuf>) -> Self {
+        Self {
+            files,
+            hashes: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, doc: &Document) -> Option<PathBuf> {
+        // A managed copy is named by hash; the original's name is the useful one
+        let known = Path::new(doc.original_path.as_deref().unwrap_or(&doc.file_path));
+        let name = known.file_name()?;
+        let extension = known.extension().map(|ext| ext.to_ascii_lowercase());
+
+        let Some(content_hash) = doc.content_hash.as_deref() else {
+            return self
+                .files
+                .iter()
+                .find(|file| file.file_name() == Some(name))
+                .cloned();
+        };
+        // Same-named files first, then anything with the same extension
+        let mut candidates: Vec<&PathBuf> = self
+            .files
+            .iter()
+            .filter(|file| file.extension().map(|ext| ext.to_ascii_lowercase()) == extension)
+            .collect();
+        candidates.sort_by_key(|file| file.file_name() != Some(name));
+        for file in candidates {
+            let hash = self
+                .hashes
+                .entry(file.clone())
+                .or_insert_with(|| file_content_hash(file).ok());
+            if hash.as_deref() == Some(content_hash) {
+                return Some(file.clone());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{release_library_file, store_file, store_images, FileFinder};
+    use crate::database;
+    use crate::models::Document;
+    use crate::parsers::IMAGE_MARKER_PREFIX;
+    use std::fs;
+
+    #[test]
+    fn stores_by_hash_and_finds_moved_files() {
+        let dir = std::env::temp_dir().join(format!("reader_library_test_{}", std::process::id()));
+        let library = dir.join("library");
+        let elsewhere = dir.join("elsewhere");
+        fs::create_dir_all(&elsewhere).unwrap();
+        let source = dir.join("Book.EPUB");
+        fs::write(&source, b"book contents").unwrap();
+
+        let stored = store_file(&library, &source, "abcdef").unwrap();
+        // The same contents are stored once
+        let again = store_file(&library, &source, "abcdef").unwrap();
+
+        let renamed = elsewhere.join("renamed.epub");
+        fs::write(&renamed, b"book contents").unwrap();
+        let same_name = elsewhere.join("Book.EPUB");
+        fs::write(&same_name, b"edited contents").unwrap();
+        let content_hash = super::file_content_hash(&renamed).unwrap();
+        let doc = |content_hash: Option<String>| Document {
+            id: "doc".to_string(),
+            title: "Book".to_string(),
+            author: None,
+            language: None,
+            file_path: stored.to_string_lossy().to_string(),
+            file_type: "epub".to_string(),
+            content_hash,
+            original_path: Some(source.to_string_lossy().to_string()),
+            series: None,
+            series_index: None,
+            tags: Vec::new(),
+            collection_ids: Vec::new(),
+            metadata: Default::default(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        let mut finder = FileFinder::new(vec![same_name.clone(), renamed.clone()]);
+        let by_hash = finder.find(&doc(Some(content_hash)));
+        let by_name = finder.find(&doc(None));
+        let stored_contents = fs::read(&stored).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(stored, library.join("ab").join("abcdef.epub"));
+        assert_eq!(again, stored);
+        assert_eq!(stored_contents, b"book contents");
+        assert_eq!(by_hash, Some(renamed));
+        assert_eq!(by_name, Some(same_name));
+    }
+
+    #[test]
+    fn keeps_extracted_images_until_the_copy_is_released() {
+        let dir = std::env::temp_dir().join(format!("reader_images_test_{}", std::process::id()));
+        let extracted = dir.join("tmp").join("image_001.png");
+        fs::create_dir_all(extracted.parent().unwrap()).unwrap();
+        fs::write(&extracted, b"png").unwrap();
+        let app_data_dir = dir.join("app");
+        let source = dir.join("book.pdf");
+        fs::write(&source, b"book contents").unwrap();
+        let copy = store_file(&app_data_dir.join("library"), &source, "abcdef").unwrap();
+
+        let marker = format!("{}{}]]", IMAGE_MARKER_PREFIX, extracted.to_string_lossy());
+        let mut chapters = vec![(
+            "Chapter".to_string(),
+            0,
+            "page-1".to_string(),
+            vec!["Text".to_string(), marker],
+        )];
+        store_images(&app_data_dir, "abcdef", &mut chapters).unwrap();
+        // The temporary folder may be cleared at any time
+        fs::remove_dir_all(dir.join("tmp")).unwrap();
+
+        let stored = app_data_dir
+            .join("library/images/abcdef")
+            .join("00001_image_001.png");
+        assert_eq!(chapters[0].3[0], "Text");
+        assert_eq!(
+            chapters[0].3[1],
+            format!("{}{}]]", IMAGE_MARKER_PREFIX, stored.to_string_lossy())
+        );
+        assert_eq!(fs::read(&stored).unwrap(), b"png");
+
+        // Storing again leaves markers that already point into the library
+        let stored_markers = chapters.clone();
+        store_images(&app_data_dir, "abcdef", &mut chapters).unwrap();
+        assert_eq!(chapters, stored_markers);
+
+        let conn = database::open_in_memory();
+        release_library_file(&conn, &app_data_dir, &copy.to_string_lossy()).unwrap();
+        let images_left = stored.exists();
+        let _ = fs::remove_dir_all(&dir);
+        assert!(!copy.exists());
+        assert!(!images_left);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::database;
+use crate::error::Result;
+use tauri::AppHandle;
+
+#[derive(Clone, serde::Serialize)]
+pub struct ParagraphLinkOutput {
+    pub id: String,
+    pub source_paragraph_id: String,
+    pub target_paragraph_id: String,
+    pub target_section_id: String,
+    pub target_location: String,
+    pub kind: String,
+    pub label: String,
+    /// Text of the linked paragraph, so footnotes can be shown inline
+    pub target_text: String,
+}
+
+/// Lists footnote references and internal links starting in the given paragraphs
+#[tauri::command]
+pub async fn list_paragraph_links(
+    app_handle: AppHandle,
+    paragraph_ids: Vec<String>,
+) -> Result<Vec<ParagraphLinkOutput>> {
+    let conn = database::get_connection(&app_handle)?;
+    let links = database::list_paragraph_links(&conn, &paragraph_ids)?;
+
+    let mut output = Vec::with_capacity(links.len());
+    for link in links {
+        let Some(target) = database::get_paragraph(&conn, &link.target_paragraph_id)? else {
+            continue;
+        };
+        output.push(ParagraphLinkOutput {
+            id: link.id,
+            source_paragraph_id: link.source_paragraph_id,
+            target_paragraph_id: link.target_paragraph_id,
+            target_section_id: target.section_id,
+            target_location: link.target_location,
+            kind: link.kind,
+            label: link.label,
+            target_text: target.text,
+        });
+    }
+
+    Ok(output)
+}
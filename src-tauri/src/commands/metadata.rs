@@ -0,0 +1,180 @@
+//! Editing document metadata after import
+
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::models::Document;
+use crate::parsers::{normalize_doi, normalize_isbn};
+use tauri::AppHandle;
+
+/// Highest star rating
+const MAX_RATING: u8 = 5;
+
+/// Changes to a document's metadata
+///
+/// Omitted fields are kept; an empty string clears an optional field and a
+/// rating of 0 clears the rating.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct DocumentUpdate {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub language: Option<String>,
+    pub subtitle: Option<String>,
+    pub publisher: Option<String>,
+    pub published_date: Option<String>,
+    pub isbn: Option<String>,
+    pub doi: Option<String>,
+    pub description: Option<String>,
+    pub rating: Option<u8>,
+}
+
+/// Edits a document's title, author, language or extended metadata
+#[tauri::command]
+pub async fn update_document(
+    app_handle: AppHandle,
+    id: String,
+    changes: DocumentUpdate,
+) -> Result<Document> {
+    database::run_blocking(&app_handle, move |conn| {
+        let current = database::get_document(conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?;
+        let updated = apply_update(current, changes)?;
+        database::update_document_details(
+            conn,
+            &id,
+            &updated.title,
+            updated.author.as_deref(),
+            updated.language.as_deref(),
+            &updated.metadata,
+        )?;
+        database::get_document(conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))
+    })
+    .await
+}
+
+/// Applies `changes` to a document, validating and normalising identifiers
+fn apply_update(mut doc: Document, changes: DocumentUpdate) -> Result<Document> {
+    if let Some(title) = changes.title {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(ReaderError::InvalidArgument(
+                "Document title cannot be empty".to_string(),
+            ));
+        }
+        doc.title = title.to_string();
+    }
+    merge(&mut doc.author, changes.author);
+    merge(&mut doc.language, changes.language);
+
+    let metadata = &mut doc.metadata;
+    merge(&mut metadata.subtitle, changes.subtitle);
+    merge(&mut metadata.publisher, changes.publisher);
+    merge(&mut metadata.published_date, changes.published_date);
+    merge(&mut metadata.description, changes.description);
+    if let Some(isbn) = changes.isbn {
+        metadata.isbn = validated(&isbn, normalize_isbn, "ISBN")?;
+    }
+    if let Some(doi) = changes.doi {
+        metadata.doi = validated(&doi, normalize_doi, "DOI")?;
+    }
+    match changes.rating {
+        Some(rating) if rating > MAX_RATING => {
+            return Err(ReaderError::InvalidArgument(format!(
+                "Rating must be between 1 and {}",
+                MAX_RATING
+            )))
+        }
+        Some(0) => metadata.rating = None,
+        Some(rating) => metadata.rating = Some(rating),
+        None => {}
+    }
+    Ok(doc)
+}
+
+/// Replaces `field` when a change was given; blank values clear it
+fn merge(field: &mut Option<String>, change: Option<String>) {
+    if let Some(value) = change {
+        let value = value.trim();
+        *field = (!value.is_empty()).then(|| value.to_string());
+    }
+}
+
+fn validated(
+    value: &str,
+    normalize: fn(&str) -> Option<String>,
+    what: &str,
+) -> Result<Option<String>> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    normalize(value)
+        .map(Some)
+        .ok_or_else(|| ReaderError::InvalidArgument(format!("Not a valid {}: {}", what, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_update, DocumentUpdate};
+    use crate::models::{Document, DocumentMetadata};
+
+    #[test]
+    fn merges_and_validates_metadata_changes() {
+        let doc = Document {
+            id: "doc".to_string(),
+            title: "scan_0001".to_string(),
+            author: Some("Someone".to_string()),
+            language: None,
+            file_path: "/books/scan_0001.pdf".to_string(),
+            file_type: "pdf".to_string(),
+            content_hash: None,
+            original_path: None,
+            series: None,
+            series_index: None,
+            tags: Vec::new(),
+            collection_ids: Vec::new(),
+            metadata: DocumentMetadata {
+                rating: Some(3),
+                ..Default::default()
+            },
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        let updated = apply_update(
+            doc.clone(),
+            DocumentUpdate {
+                title: Some("  A Real Title ".to_string()),
+                author: Some(String::new()),
+                isbn: Some("978-0-306-40615-7".to_string()),
+                doi: Some("https://doi.org/10.1000/ABC".to_string()),
+                rating: Some(0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(updated.title, "A Real Title");
+        assert_eq!(updated.author, None);
+        assert_eq!(updated.metadata.isbn.as_deref(), Some("9780306406157"));
+        assert_eq!(updated.metadata.doi.as_deref(), Some("10.1000/abc"));
+        assert_eq!(updated.metadata.rating, None);
+
+        let kept = apply_update(doc.clone(), DocumentUpdate::default()).unwrap();
+        assert_eq!(kept.author.as_deref(), Some("Someone"));
+        assert_eq!(kept.metadata.rating, Some(3));
+
+        let invalid = |changes: DocumentUpdate| apply_update(doc.clone(), changes).is_err();
+        assert!(invalid(DocumentUpdate {
+            title: Some(" ".to_string()),
+            ..Default::default()
+        }));
+        assert!(invalid(DocumentUpdate {
+            isbn: Some("978-0-306-40615-8".to_string()),
+            ..Default::default()
+        }));
+        assert!(invalid(DocumentUpdate {
+            rating: Some(6),
+            ..Default::default()
+        }));
+    }
+}
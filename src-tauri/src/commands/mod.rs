@@ -1,27 +1,84 @@
 mod annotation;
+mod bookmark;
+mod calibre;
 mod config;
 mod embedding;
+mod export;
+mod feed;
+mod folder;
 mod import;
 mod index;
+mod library;
+mod link;
 mod mcp;
+mod metadata;
+mod organize;
+mod progress;
+mod reimport;
 mod search;
 mod translate;
 mod tts;
+mod web_annotation;
 
-pub use annotation::{create_annotation, delete_annotation, list_annotations};
+pub use annotation::{
+    create_annotation, delete_annotation, import_highlights, list_annotations, update_annotation,
+};
+pub use bookmark::{
+    create_bookmark, delete_bookmark, list_bookmarks, update_bookmark, BookmarkOutput,
+};
+pub use calibre::import_calibre_library;
 pub use config::{get_config, update_config};
 pub use embedding::{
     clear_embeddings_by_profile, download_embedding_model_files, get_document_paragraphs,
     get_embedding_profile_status, search_by_embedding, upsert_embeddings_batch,
     validate_local_embedding_model_path, EmbeddingProfileStatus, SearchByEmbeddingResult,
 };
+pub use export::{export_notes, import_notes, take_launch_deep_link, LaunchDeepLink};
+pub use feed::{add_feed, delete_feed, list_feeds, refresh_feeds, start_feed_scheduler};
+pub use folder::{
+    add_watched_folder, import_folder, list_watched_folders, remove_watched_folder,
+    scan_watched_folders, start_folder_watcher,
+};
 pub use import::{
-    delete_document, fetch_url_html, get_document, get_document_sections, get_section_paragraphs,
-    get_document_previews, import_epub, import_markdown, import_markdown_content, import_pdf,
-    import_url, list_documents,
+    delete_document, fetch_url_html, get_document, get_document_ocr_pages, get_document_previews,
+    get_document_sections, get_section_paragraphs, import_asciidoc, import_docx, import_epub,
+    import_fb2, import_html, import_markdown, import_markdown_content, import_mobi,
+    import_notebook, import_pdf, import_rst, import_text, import_url, list_documents,
 };
 pub use index::index_document;
+pub use library::{list_missing_documents, relocate_document, repair_missing_files};
+pub use link::list_paragraph_links;
 pub use mcp::{mcp_request, McpState};
+pub use metadata::update_document;
+pub use organize::{
+    add_to_collection, create_collection, delete_collection, delete_tag, list_collections,
+    list_series, list_tags, remove_from_collection, rename_tag, set_document_series,
+    set_document_tags, update_collection, SeriesOutput,
+};
+pub use progress::{
+    get_reading_position, list_recently_read, save_reading_position, RecentlyReadOutput,
+};
+pub use reimport::reimport_document;
 pub use search::{get_paragraph_context, search, ParagraphContextOutput, SearchResultOutput};
 pub use translate::{chat_with_context, deep_analyze, get_summary_cache, summarize, translate};
 pub use tts::{list_tts_voices, tts_synthesize};
+pub use web_annotation::{get_web_annotation, get_web_annotation_page, import_web_annotations};
+
+use crate::error::{ReaderError, Result};
+
+/// Trims optional text from the UI, treating blank text as absent
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Checks a colour name against the palette `what` can be shown in
+fn validate_color(color: Option<&str>, palette: &[&str], what: &str) -> Result<()> {
+    match color {
+        Some(color) if !palette.contains(&color.trim()) => Err(ReaderError::InvalidArgument(
+            format!("Unsupported {} color: {}", what, color),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,196 @@
+//! Tags, collections and series for organising the library
+
+use super::non_empty;
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::models::{Collection, Document, Tag};
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+#[derive(Clone, serde::Serialize)]
+pub struct SeriesOutput {
+    pub name: String,
+    pub document_count: usize,
+}
+
+/// Lists every tag with the number of documents carrying it
+#[tauri::command]
+pub async fn list_tags(app_handle: AppHandle) -> Result<Vec<Tag>> {
+    database::run_blocking(&app_handle, |conn| Ok(database::list_tags(conn)?)).await
+}
+
+/// Renames a tag; renaming onto an existing tag merges the two
+#[tauri::command]
+pub async fn rename_tag(app_handle: AppHandle, id: String, name: String) -> Result<Tag> {
+    let name = required_name(&name, "Tag")?;
+    database::run_blocking(&app_handle, move |conn| {
+        let id = database::rename_tag(conn, &id, &name)?;
+        database::list_tags(conn)?
+            .into_iter()
+            .find(|tag| tag.id == id)
+            .ok_or_else(|| ReaderError::NotFound(format!("Tag {}", id)))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_tag(app_handle: AppHandle, id: String) -> Result<()> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::delete_tag(conn, &id)?)
+    })
+    .await
+}
+
+/// Replaces a document's tags, creating tags that don't exist yet
+#[tauri::command]
+pub async fn set_document_tags(
+    app_handle: AppHandle,
+    doc_id: String,
+    tags: Vec<String>,
+) -> Result<Document> {
+    database::run_blocking(&app_handle, move |conn| {
+        require_document(conn, &doc_id)?;
+        database::set_document_tags(conn, &doc_id, &tags)?;
+        require_document(conn, &doc_id)
+    })
+    .await
+}
+
+/// Lists every collection with the number of documents in it
+#[tauri::command]
+pub async fn list_collections(app_handle: AppHandle) -> Result<Vec<Collection>> {
+    database::run_blocking(&app_handle, |conn| Ok(database::list_collections(conn)?)).await
+}
+
+#[tauri::command]
+pub async fn create_collection(
+    app_handle: AppHandle,
+    name: String,
+    description: Option<String>,
+) -> Result<Collection> {
+    let name = required_name(&name, "Collection")?;
+    let description = non_empty(description);
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::insert_collection(
+            conn,
+            &name,
+            description.as_deref(),
+        )?)
+    })
+    .await
+}
+
+/// Renames a collection or changes its description
+///
+/// Omitted fields are kept; an empty description clears it.
+#[tauri::command]
+pub async fn update_collection(
+    app_handle: AppHandle,
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+) -> Result<Collection> {
+    let name = name
+        .map(|name| required_name(&name, "Collection"))
+        .transpose()?;
+    database::run_blocking(&app_handle, move |conn| {
+        let current = require_collection(conn, &id)?;
+        let name = name.unwrap_or(current.name);
+        let description = description.map_or(current.description, |d| non_empty(Some(d)));
+        database::update_collection(conn, &id, &name, description.as_deref())?;
+        require_collection(conn, &id)
+    })
+    .await
+}
+
+/// Deletes a collection; the documents in it stay in the library
+#[tauri::command]
+pub async fn delete_collection(app_handle: AppHandle, id: String) -> Result<()> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::delete_collection(conn, &id)?)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn add_to_collection(
+    app_handle: AppHandle,
+    collection_id: String,
+    doc_ids: Vec<String>,
+) -> Result<Collection> {
+    database::run_blocking(&app_handle, move |conn| {
+        for doc_id in &doc_ids {
+            require_document(conn, doc_id)?;
+        }
+        database::add_to_collection(conn, &collection_id, &doc_ids)?;
+        require_collection(conn, &collection_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_from_collection(
+    app_handle: AppHandle,
+    collection_id: String,
+    doc_ids: Vec<String>,
+) -> Result<Collection> {
+    database::run_blocking(&app_handle, move |conn| {
+        database::remove_from_collection(conn, &collection_id, &doc_ids)?;
+        require_collection(conn, &collection_id)
+    })
+    .await
+}
+
+/// Lists the series in the library with how many documents each has
+#[tauri::command]
+pub async fn list_series(app_handle: AppHandle) -> Result<Vec<SeriesOutput>> {
+    database::run_blocking(&app_handle, |conn| {
+        Ok(database::list_series(conn)?
+            .into_iter()
+            .map(|(name, document_count)| SeriesOutput {
+                name,
+                document_count,
+            })
+            .collect())
+    })
+    .await
+}
+
+/// Sets or clears the series a document belongs to and its number in it
+#[tauri::command]
+pub async fn set_document_series(
+    app_handle: AppHandle,
+    doc_id: String,
+    series: Option<String>,
+    series_index: Option<f64>,
+) -> Result<Document> {
+    let series = non_empty(series);
+    if series_index.is_some_and(|index| !index.is_finite() || index < 0.0) {
+        return Err(ReaderError::InvalidArgument(
+            "Series index must be a non-negative number".to_string(),
+        ));
+    }
+    // An index means nothing without a series
+    let series_index = series_index.filter(|_| series.is_some());
+    database::run_blocking(&app_handle, move |conn| {
+        require_document(conn, &doc_id)?;
+        database::set_document_series(conn, &doc_id, series.as_deref(), series_index)?;
+        require_document(conn, &doc_id)
+    })
+    .await
+}
+
+fn require_document(conn: &Connection, doc_id: &str) -> Result<Document> {
+    database::get_document(conn, doc_id)?
+        .ok_or_else(|| ReaderError::NotFound(format!("Document {}", doc_id)))
+}
+
+fn require_collection(conn: &Connection, id: &str) -> Result<Collection> {
+    database::get_collection(conn, id)?
+        .ok_or_else(|| ReaderError::NotFound(format!("Collection {}", id)))
+}
+
+fn required_name(name: &str, what: &str) -> Result<String> {
+    non_empty(Some(name.to_string()))
+        .ok_or_else(|| ReaderError::InvalidArgument(format!("{} name cannot be empty", what)))
+}
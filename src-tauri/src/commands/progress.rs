@@ -0,0 +1,84 @@
+//! Reading positions and the "continue reading" list
+
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::models::{Document, ReadingPosition};
+use tauri::AppHandle;
+
+/// Documents listed by `list_recently_read` when no limit is given
+const DEFAULT_RECENT_LIMIT: usize = 10;
+
+#[derive(Clone, serde::Serialize)]
+pub struct RecentlyReadOutput {
+    pub document: Document,
+    pub position: ReadingPosition,
+    /// Title of the section reading stopped in
+    pub section_title: Option<String>,
+}
+
+/// Records where reading stopped in a document
+#[tauri::command]
+pub async fn save_reading_position(
+    app_handle: AppHandle,
+    doc_id: String,
+    section_id: Option<String>,
+    paragraph_id: Option<String>,
+    location: Option<String>,
+    scroll_fraction: Option<f64>,
+) -> Result<ReadingPosition> {
+    database::run_blocking(&app_handle, move |conn| {
+        if database::get_document(conn, &doc_id)?.is_none() {
+            return Err(ReaderError::NotFound(format!("Document {}", doc_id)));
+        }
+        Ok(database::save_reading_position(
+            conn,
+            &doc_id,
+            section_id.as_deref(),
+            paragraph_id.as_deref(),
+            location.as_deref(),
+            scroll_fraction.unwrap_or(0.0),
+        )?)
+    })
+    .await
+}
+
+/// Where reading stopped in a document, if it was opened before
+#[tauri::command]
+pub async fn get_reading_position(
+    app_handle: AppHandle,
+    doc_id: String,
+) -> Result<Option<ReadingPosition>> {
+    database::run_blocking(&app_handle, move |conn| {
+        Ok(database::get_reading_position(conn, &doc_id)?)
+    })
+    .await
+}
+
+/// Recently read documents with their positions, most recent first
+#[tauri::command]
+pub async fn list_recently_read(
+    app_handle: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<RecentlyReadOutput>> {
+    database::run_blocking(&app_handle, move |conn| {
+        let positions =
+            database::list_recent_positions(conn, limit.unwrap_or(DEFAULT_RECENT_LIMIT))?;
+        let mut recent = Vec::with_capacity(positions.len());
+        for position in positions {
+            let Some(document) = database::get_document(conn, &position.doc_id)? else {
+                continue;
+            };
+            let section_title = match position.section_id.as_deref() {
+                Some(section_id) => database::get_section(conn, section_id)?.map(|s| s.title),
+                None => None,
+            };
+            recent.push(RecentlyReadOutput {
+                document,
+                position,
+                section_title,
+            });
+        }
+        Ok(recent)
+    })
+    .await
+}
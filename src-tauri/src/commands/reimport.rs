@@ -0,0 +1,361 @@
+use super::import::{
+    file_content_hash, insert_document_content, parse_document_file, ParsedDocument,
+};
+use super::library::{app_data_dir, refresh_library_copy, store_images};
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::parsers::detect_format;
+use crate::search::{align_paragraphs, anchor_at_bytes, resolve_anchor, ParagraphMatcher};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Similarity above which a paragraph keeps its translations and embedding
+///
+/// Lower than this the cached results describe text that has changed too much.
+const MIN_CACHE_SIMILARITY: f32 = 0.9;
+
+#[derive(Clone, serde::Serialize)]
+pub struct ReimportOutput {
+    pub doc_id: String,
+    pub paragraphs: usize,
+    /// New paragraphs paired with a paragraph of the previous version
+    pub paragraphs_matched: usize,
+    pub annotations_kept: usize,
+    /// Selected text of annotations whose passage is gone from the new version
+    pub annotations_dropped: Vec<String>,
+    pub bookmarks_kept: usize,
+    pub translations_kept: usize,
+    pub embeddings_kept: usize,
+}
+
+/// Re-reads a document's file and replaces its content with the new version
+///
+/// The document keeps its id, tags and series. Annotations, cached
+/// translations, embeddings, bookmarks and the reading position are moved
+/// to the matching paragraphs of the new version instead of being dropped
+/// with the old ones.
+#[tauri::command]
+pub async fn reimport_document(app_handle: AppHandle, id: String) -> Result<ReimportOutput> {
+    let doc = {
+        let conn = database::get_connection(&app_handle)?;
+        database::get_document(&conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?
+    };
+    // A managed copy never changes; edits are picked up from the original
+    let source = doc
+        .original_path
+        .as_deref()
+        .filter(|original| Path::new(original).is_file())
+        .unwrap_or(&doc.file_path)
+        .to_string();
+    let path = Path::new(&source);
+    if !path.is_file() {
+        return Err(ReaderError::NotFound(source.clone()));
+    }
+    let format = detect_format(path).ok_or_else(|| {
+        ReaderError::InvalidArgument(format!("Unsupported file format: {}", source))
+    })?;
+
+    let file_path = source.clone();
+    let mut parsed = tokio::task::spawn_blocking(move || parse_document_file(&file_path, format))
+        .await
+        .map_err(|e| ReaderError::Internal(e.to_string()))??;
+    let content_hash = file_content_hash(path).ok();
+    let app_data_dir = app_data_dir(&app_handle)?;
+    if let (Some(content_hash), Some(_)) = (&content_hash, &doc.original_path) {
+        store_images(&app_data_dir, content_hash, &mut parsed.1)?;
+    }
+    let conn = database::get_connection(&app_handle)?;
+    let output = replace_document_content(&conn, &id, parsed, content_hash.as_deref())?;
+    if let Some(content_hash) = &content_hash {
+        refresh_library_copy(&conn, &app_data_dir, &doc, path, content_hash)?;
+    }
+    Ok(output)
+}
+
+/// Swaps a document's sections and paragraphs for a freshly parsed version
+///
+/// Old and new paragraphs are aligned by text. An annotation follows its
+/// paragraph when the selected text is still there, re-anchored to the
+/// occurrence with the most similar surroundings, and is otherwise looked
+/// up in the whole new version; translations and embeddings follow only
+/// paragraphs that are unchanged or nearly so.
+pub(crate) fn replace_document_content(
+    conn: &Connection,
+    doc_id: &str,
+    parsed: ParsedDocument,
+    content_hash: Option<&str>,
+) -> Result<ReimportOutput> {
+    let (_, chapters, extras) = parsed;
+    let tx = conn.unchecked_transaction()?;
+
+    let old_paragraphs = database::list_paragraphs(&tx, doc_id)?;
+    let annotations = database::list_annotations_by_document(&tx, doc_id)?;
+    let bookmarks = database::list_bookmarks(&tx, Some(doc_id))?;
+    database::stash_document_sections(&tx, doc_id)?;
+    let new_paragraphs = insert_document_content(&tx, doc_id, chapters, &extras)?;
+    database::fill_document_identifiers(
+        &tx,
+        doc_id,
+        extras.identifiers.isbn.as_deref(),
+        extras.identifiers.doi.as_deref(),
+    )?;
+
+    let old_texts: Vec<&str> = old_paragraphs.iter().map(|p| p.text.as_str()).collect();
+    let new_texts: Vec<&str> = new_paragraphs.iter().map(|p| p.text.as_str()).collect();
+    let alignment = align_paragraphs(&old_texts, &new_texts);
+
+    let mut output = ReimportOutput {
+        doc_id: doc_id.to_string(),
+        paragraphs: new_paragraphs.len(),
+        paragraphs_matched: alignment.iter().flatten().count(),
+        annotations_kept: 0,
+        annotations_dropped: Vec::new(),
+        bookmarks_kept: 0,
+        translations_kept: 0,
+        embeddings_kept: 0,
+    };
+
+    for (old, aligned) in old_paragraphs.iter().zip(&alignment) {
+        let Some((new_idx, similarity)) = aligned else {
+            continue;
+        };
+        if *similarity < MIN_CACHE_SIMILARITY {
+            continue;
+        }
+        let new_id = &new_paragraphs[*new_idx].id;
+        output.translations_kept += database::move_translations(&tx, &old.id, new_id)?;
+        if database::move_embedding(&tx, &old.id, new_id)? {
+            output.embeddings_kept += 1;
+        }
+    }
+
+    let old_index: HashMap<&str, usize> = old_paragraphs
+        .iter()
+        .enumerate()
+        .map(|(idx, paragraph)| (paragraph.id.as_str(), idx))
+        .collect();
+    let matcher = ParagraphMatcher::new(&new_texts);
+    for annotation in &annotations {
+        let aligned = old_index
+            .get(annotation.paragraph_id.as_str())
+            .and_then(|idx| alignment[*idx])
+            .and_then(|(new_idx, _)| {
+                let anchor = resolve_anchor(
+                    new_texts[new_idx],
+                    &annotation.selected_text,
+                    annotation.anchor.as_ref(),
+                )?;
+                Some((new_idx, annotation.selected_text.clone(), Some(anchor)))
+            });
+        let target = aligned.or_else(|| {
+            matcher.locate(&annotation.selected_text).map(|found| {
+                let paragraph = new_texts[found.paragraph_index];
+                let anchor = anchor_at_bytes(paragraph, found.start, found.end);
+                let text = &paragraph[found.start..found.end];
+                (found.paragraph_index, text.to_string(), anchor)
+            })
+        });
+        match target {
+            Some((new_idx, selected_text, anchor)) => {
+                database::relocate_annotation(
+                    &tx,
+                    &annotation.id,
+                    &new_paragraphs[new_idx].id,
+                    &selected_text,
+                    anchor.as_ref(),
+                )?;
+                output.annotations_kept += 1;
+            }
+            None => output
+                .annotations_dropped
+                .push(annotation.selected_text.clone()),
+        }
+    }
+
+    // Bookmarks and the reading position fall back to the nearest earlier
+    // paragraph that survived
+    let surviving = |old_idx: usize| {
+        alignment[..=old_idx]
+            .iter()
+            .rev()
+            .find_map(|aligned| *aligned)
+            .map(|(new_idx, _)| &new_paragraphs[new_idx])
+    };
+    for entry in &bookmarks {
+        let moved = old_index
+            .get(entry.bookmark.paragraph_id.as_str())
+            .and_then(|idx| surviving(*idx))
+            .or(new_paragraphs.first());
+        if let Some(paragraph) = moved {
+            database::relocate_bookmark(&tx, &entry.bookmark.id, &paragraph.id)?;
+            output.bookmarks_kept += 1;
+        }
+    }
+
+    if let Some(position) = database::get_reading_position(&tx, doc_id)? {
+        let anchor = position
+            .paragraph_id
+            .as_deref()
+            .and_then(|id| old_index.get(id).copied())
+            .or_else(|| {
+                let section_id = position.section_id.as_deref()?;
+                old_paragraphs
+                    .iter()
+                    .position(|p| p.section_id == section_id)
+            });
+        let moved = anchor.and_then(surviving);
+        database::move_reading_position(
+            &tx,
+            doc_id,
+            moved.map(|p| p.section_id.as_str()),
+            moved.map(|p| p.id.as_str()),
+        )?;
+    }
+
+    database::delete_stashed_sections(&tx, doc_id)?;
+    // A summary of the whole document describes the old text
+    database::delete_summaries(&tx, doc_id)?;
+    if let Some(content_hash) = content_hash {
+        database::set_document_content_hash(&tx, doc_id, content_hash)?;
+    }
+    database::touch_document(&tx, doc_id)?;
+    tx.commit()?;
+
+    tracing::info!(
+        "Re-imported document {}: {} of {} paragraphs matched, {} annotations kept, {} dropped",
+        doc_id,
+        output.paragraphs_matched,
+        output.paragraphs,
+        output.annotations_kept,
+        output.annotations_dropped.len()
+    );
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{insert_document_content, replace_document_content, ParsedDocument};
+    use crate::database;
+    use crate::models::{NewAnnotation, NewDocument};
+    use crate::parsers::ParseExtras;
+    use rusqlite::Connection;
+
+    fn new_document(file_path: &str) -> NewDocument {
+        NewDocument {
+            title: "Notes".to_string(),
+            author: None,
+            language: None,
+            file_path: file_path.to_string(),
+            file_type: "md".to_string(),
+        }
+    }
+
+    fn parsed(paragraphs: &[&str]) -> ParsedDocument {
+        let chapters = vec![(
+            "Chapter".to_string(),
+            0,
+            "chapter.md".to_string(),
+            paragraphs.iter().map(|p| p.to_string()).collect(),
+        )];
+        (
+            new_document("/books/notes.md"),
+            chapters,
+            ParseExtras::default(),
+        )
+    }
+
+    /// A document with `paragraphs` and an annotation on the word "sat"
+    fn annotated_document(conn: &Connection, paragraphs: &[&str]) -> (String, String) {
+        let doc = database::insert_document(conn, new_document("/books/notes.md")).unwrap();
+        let (_, chapters, extras) = parsed(paragraphs);
+        let inserted = insert_document_content(conn, &doc.id, chapters, &extras).unwrap();
+        let paragraph = inserted.iter().find(|p| p.text.contains("sat")).unwrap();
+        let annotation = database::insert_annotation(
+            conn,
+            &NewAnnotation {
+                paragraph_id: paragraph.id.clone(),
+                selected_text: "sat".to_string(),
+                style: "highlight".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        (doc.id, annotation.id)
+    }
+
+    #[test]
+    fn changed_file_updates_document_in_place() {
+        let conn = database::open_in_memory();
+        let (doc_id, annotation_id) =
+            annotated_document(&conn, &["Opening line.", "The cat sat on the mat."]);
+
+        let output = replace_document_content(
+            &conn,
+            &doc_id,
+            parsed(&["A new preface.", "Opening line.", "The cat sat on the rug."]),
+            Some("hash-2"),
+        )
+        .unwrap();
+
+        assert_eq!(output.annotations_kept, 1);
+        assert!(output.annotations_dropped.is_empty());
+        let doc = database::get_document(&conn, &doc_id).unwrap().unwrap();
+        assert_eq!(doc.content_hash.as_deref(), Some("hash-2"));
+        let annotation = database::get_annotation(&conn, &annotation_id)
+            .unwrap()
+            .unwrap();
+        let paragraph = database::get_paragraph(&conn, &annotation.paragraph_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(paragraph.text, "The cat sat on the rug.");
+        assert_eq!(database::list_paragraphs(&conn, &doc_id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn reading_position_moves_to_nearest_surviving_paragraph() {
+        let conn = database::open_in_memory();
+        let (doc_id, _) = annotated_document(
+            &conn,
+            &[
+                "Opening line.",
+                "The cat sat on the mat.",
+                "Zebras graze quietly beyond the hills.",
+            ],
+        );
+        let old_paragraphs = database::list_paragraphs(&conn, &doc_id).unwrap();
+        let last = &old_paragraphs[2];
+        database::save_reading_position(
+            &conn,
+            &doc_id,
+            Some(&last.section_id),
+            Some(&last.id),
+            None,
+            0.8,
+        )
+        .unwrap();
+
+        replace_document_content(
+            &conn,
+            &doc_id,
+            parsed(&["Opening line.", "The cat sat on the mat.", "0123 4567 89"]),
+            Some("hash-2"),
+        )
+        .unwrap();
+
+        let position = database::get_reading_position(&conn, &doc_id)
+            .unwrap()
+            .unwrap();
+        let paragraph = database::get_paragraph(&conn, position.paragraph_id.as_deref().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(paragraph.text, "The cat sat on the mat.");
+        assert_eq!(
+            position.section_id.as_deref(),
+            Some(paragraph.section_id.as_str())
+        );
+        assert_eq!(position.scroll_fraction, 0.8);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::config::load_config;
-use crate::database::{embeddings, get_connection};
+use crate::database::get_connection;
 use crate::error::{ReaderError, Result};
 use crate::llm::create_client;
-use crate::search::{cosine_similarity, SearchOptions, SearchResult};
+use crate::search::{cosine_similarity, scoped_embeddings, SearchOptions, SearchResult};
 use rusqlite::params;
 use std::collections::HashMap;
 use tauri::AppHandle;
@@ -57,13 +57,14 @@ pub async fn search(
     }
     let top_k = options.top_k.max(1);
     let query_owned = query.to_string();
-    let doc_id = options.doc_id.clone();
+    // Documents to search in, from the document, collection or tag scope
+    let scope = options.scope_doc_ids(&*get_connection(&app_handle)?)?;
 
     if options.force_keyword {
         let fallback = keyword_search_with_timeout(
             app_handle.clone(),
             query_owned.clone(),
-            doc_id.clone(),
+            scope.clone(),
             top_k,
         )
         .await?;
@@ -76,7 +77,7 @@ pub async fn search(
         let fallback = keyword_search_with_timeout(
             app_handle.clone(),
             query_owned.clone(),
-            doc_id.clone(),
+            scope.clone(),
             top_k,
         )
         .await?;
@@ -92,7 +93,7 @@ pub async fn search(
             let fallback = keyword_search_with_timeout(
                 app_handle.clone(),
                 query_owned.clone(),
-                doc_id.clone(),
+                scope.clone(),
                 top_k,
             )
             .await?;
@@ -106,38 +107,24 @@ pub async fn search(
         let conn = get_connection(&app_handle)?;
 
         // Get embeddings based on scope
-        all_embeddings = if let Some(doc_id) = &options.doc_id {
-            embeddings::list_by_document(&conn, doc_id)?
-                .into_iter()
-                .filter_map(|emb| {
-                    if emb.vector.len() > 0 {
-                        Some((emb.paragraph_id, emb.vector))
-                    } else {
-                        tracing::warn!("Empty embedding for paragraph {}", emb.paragraph_id);
-                        None
-                    }
-                })
-                .collect()
-        } else {
-            embeddings::list_all_vectors(&conn)?
-                .into_iter()
-                .filter_map(|emb| {
-                    if emb.vector.len() > 0 {
-                        Some((emb.paragraph_id, emb.vector))
-                    } else {
-                        tracing::warn!("Empty embedding for paragraph {}", emb.paragraph_id);
-                        None
-                    }
-                })
-                .collect()
-        };
+        all_embeddings = scoped_embeddings(&conn, scope.as_deref())?
+            .into_iter()
+            .filter_map(|emb| {
+                if emb.vector.len() > 0 {
+                    Some((emb.paragraph_id, emb.vector))
+                } else {
+                    tracing::warn!("Empty embedding for paragraph {}", emb.paragraph_id);
+                    None
+                }
+            })
+            .collect();
 
         // Return early if no embeddings
         if all_embeddings.is_empty() {
             let fallback = keyword_search_with_timeout(
                 app_handle.clone(),
                 query_owned.clone(),
-                doc_id.clone(),
+                scope.clone(),
                 top_k,
             )
             .await?;
@@ -161,7 +148,7 @@ pub async fn search(
             let fallback = keyword_search_with_timeout(
                 app_handle.clone(),
                 query_owned.clone(),
-                doc_id.clone(),
+                scope.clone(),
                 top_k,
             )
             .await?;
@@ -175,7 +162,7 @@ pub async fn search(
             let fallback = keyword_search_with_timeout(
                 app_handle.clone(),
                 query_owned.clone(),
-                doc_id.clone(),
+                scope.clone(),
                 top_k,
             )
             .await?;
@@ -313,73 +300,62 @@ pub fn get_paragraph_context(
 fn keyword_search(
     app_handle: &AppHandle,
     query: &str,
-    doc_id: Option<&str>,
+    doc_ids: Option<&[String]>,
     top_k: usize,
 ) -> Result<Vec<SearchResult>> {
+    if doc_ids.is_some_and(|doc_ids| doc_ids.is_empty()) {
+        return Ok(Vec::new());
+    }
     let conn = get_connection(app_handle)?;
     let lowered = query.to_lowercase();
     let like_query = format!("%{}%", lowered);
+    let limit = top_k as i64;
+
+    let scope_clause = match doc_ids {
+        Some(doc_ids) => format!(
+            "doc_id IN ({}) AND ",
+            doc_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",")
+        ),
+        None => String::new(),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, text, location
+         FROM paragraphs
+         WHERE {}lower(text) LIKE ?
+         LIMIT ?",
+        scope_clause
+    ))?;
+    let mut values: Vec<&dyn rusqlite::ToSql> = doc_ids
+        .unwrap_or_default()
+        .iter()
+        .map(|id| id as &dyn rusqlite::ToSql)
+        .collect();
+    values.push(&like_query);
+    values.push(&limit);
 
-    let mut results = Vec::new();
+    let rows = stmt.query_map(values.as_slice(), |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
 
-    if let Some(doc_id) = doc_id {
-        let mut stmt = conn.prepare(
-            "SELECT id, text, location
-             FROM paragraphs
-             WHERE doc_id = ?1 AND lower(text) LIKE ?2
-             LIMIT ?3",
-        )?;
-        let rows = stmt.query_map(params![doc_id, like_query, top_k as i64], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-            ))
-        })?;
-        for row in rows {
-            let (paragraph_id, text, location) = row?;
-            let snippet = if text.len() > 200 {
-                format!("{}...", &text[..200])
-            } else {
-                text.clone()
-            };
-            let occurrences = text.to_lowercase().matches(&lowered).count().max(1) as f32;
-            results.push(SearchResult {
-                paragraph_id,
-                snippet,
-                score: occurrences.min(10.0) / 10.0,
-                location,
-            });
-        }
-    } else {
-        let mut stmt = conn.prepare(
-            "SELECT id, text, location
-             FROM paragraphs
-             WHERE lower(text) LIKE ?1
-             LIMIT ?2",
-        )?;
-        let rows = stmt.query_map(params![like_query, top_k as i64], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-            ))
-        })?;
-        for row in rows {
-            let (paragraph_id, text, location) = row?;
-            let snippet = if text.len() > 200 {
-                format!("{}...", &text[..200])
-            } else {
-                text.clone()
-            };
-            let occurrences = text.to_lowercase().matches(&lowered).count().max(1) as f32;
-            results.push(SearchResult {
-                paragraph_id,
-                snippet,
-                score: occurrences.min(10.0) / 10.0,
-                location,
-            });
-        }
+    let mut results = Vec::new();
+    for row in rows {
+        let (paragraph_id, text, location) = row?;
+        let snippet = if text.len() > 200 {
+            format!("{}...", &text[..200])
+        } else {
+            text.clone()
+        };
+        let occurrences = text.to_lowercase().matches(&lowered).count().max(1) as f32;
+        results.push(SearchResult {
+            paragraph_id,
+            snippet,
+            score: occurrences.min(10.0) / 10.0,
+            location,
+        });
     }
 
     Ok(results)
@@ -388,12 +364,12 @@ fn keyword_search(
 async fn keyword_search_with_timeout(
     app_handle: AppHandle,
     query: String,
-    doc_id: Option<String>,
+    doc_ids: Option<Vec<String>>,
     top_k: usize,
 ) -> Result<Vec<SearchResult>> {
     match timeout(
         Duration::from_secs(SEARCH_KEYWORD_TIMEOUT_SECS),
-        spawn_blocking(move || keyword_search(&app_handle, &query, doc_id.as_deref(), top_k)),
+        spawn_blocking(move || keyword_search(&app_handle, &query, doc_ids.as_deref(), top_k)),
     )
     .await
     {
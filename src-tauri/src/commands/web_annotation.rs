@@ -0,0 +1,242 @@
+use super::annotation::{validate_style, ANNOTATION_COLORS, STYLE_HIGHLIGHT};
+use super::export::NotesImportOutput;
+use crate::database;
+use crate::error::{ReaderError, Result};
+use crate::export::{
+    self, AnnotationPage, DocumentNotes, ImportedWebAnnotation, Note, WebAnnotation,
+};
+use crate::models::{Annotation, Document};
+use crate::search;
+use rusqlite::Connection;
+use std::collections::HashSet;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+/// Returns an annotation as a W3C Web Annotation
+#[tauri::command]
+pub async fn get_web_annotation(app_handle: AppHandle, id: String) -> Result<WebAnnotation> {
+    database::run_blocking(&app_handle, move |conn| {
+        let annotation = database::get_annotation(conn, &id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Annotation {}", id)))?;
+        let paragraph =
+            database::get_paragraph(conn, &annotation.paragraph_id)?.ok_or_else(|| {
+                ReaderError::NotFound(format!("Paragraph {}", annotation.paragraph_id))
+            })?;
+        let exported = export::web_annotation(&annotation, &paragraph.doc_id, &paragraph.location);
+        Ok(exported.standalone())
+    })
+    .await
+}
+
+/// Returns a document's annotations as a W3C AnnotationPage, in reading order
+#[tauri::command]
+pub async fn get_web_annotation_page(
+    app_handle: AppHandle,
+    doc_id: String,
+) -> Result<AnnotationPage> {
+    database::run_blocking(&app_handle, move |conn| {
+        let document = database::get_document(conn, &doc_id)?
+            .ok_or_else(|| ReaderError::NotFound(format!("Document {}", doc_id)))?;
+        let notes = export::collect(conn, document)?;
+        Ok(notes_page(std::slice::from_ref(&notes)))
+    })
+    .await
+}
+
+/// Imports W3C Web Annotations given as JSON-LD
+///
+/// Accepts a single annotation, an AnnotationPage, or a collection with its
+/// first page embedded. Annotations are attached to `doc_id` when given, and
+/// otherwise to the document their target names. Annotations exported by the
+/// reader keep their ids, so importing them twice adds nothing.
+#[tauri::command]
+pub async fn import_web_annotations(
+    app_handle: AppHandle,
+    content: String,
+    doc_id: Option<String>,
+) -> Result<NotesImportOutput> {
+    let annotations = export::parse_web_annotations(&content)
+        .map_err(|e| ReaderError::InvalidArgument(format!("Not a Web Annotation: {}", e)))?;
+    database::run_blocking(&app_handle, move |conn| {
+        let tx = conn.unchecked_transaction()?;
+        let output = restore_web_annotations(&tx, &annotations, doc_id.as_deref())?;
+        tx.commit()?;
+        Ok(output)
+    })
+    .await
+}
+
+/// All annotations in `notes` as one page
+pub(super) fn notes_page(notes: &[DocumentNotes]) -> AnnotationPage {
+    let mut items = Vec::new();
+    for document_notes in notes {
+        for entry in document_notes.sections.iter().flat_map(|s| &s.entries) {
+            if let Note::Annotation(annotation) = &entry.note {
+                items.push(export::web_annotation(
+                    annotation,
+                    &document_notes.document.id,
+                    &entry.location,
+                ));
+            }
+        }
+    }
+    export::annotation_page(items)
+}
+
+/// Adds Web Annotations to their documents, placing each by its selectors
+pub(super) fn restore_web_annotations(
+    conn: &Connection,
+    annotations: &[WebAnnotation],
+    doc_id: Option<&str>,
+) -> Result<NotesImportOutput> {
+    let documents = database::list_documents(conn)?;
+    let forced = match doc_id {
+        Some(id) => Some(
+            documents
+                .iter()
+                .find(|doc| doc.id == id)
+                .ok_or_else(|| ReaderError::NotFound(format!("Document {}", id)))?,
+        ),
+        None => None,
+    };
+    let mut output = NotesImportOutput {
+        documents: Vec::new(),
+        annotations: 0,
+        bookmarks: 0,
+        skipped_duplicates: 0,
+        unmatched_documents: Vec::new(),
+        unplaced: Vec::new(),
+    };
+
+    // Group by document, keeping the order documents first appear in
+    let mut groups: Vec<(&Document, Vec<ImportedWebAnnotation>)> = Vec::new();
+    for annotation in annotations {
+        let Some(imported) = annotation.to_imported() else {
+            output.unplaced.push(
+                annotation
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| "Annotation without quoted text".to_string()),
+            );
+            continue;
+        };
+        let document = forced.or_else(|| {
+            let source = imported.source.as_str();
+            let id = export::id_from_iri(source);
+            documents.iter().find(|doc| {
+                id == Some(doc.id.as_str())
+                    || doc.file_path == source
+                    || doc.original_path.as_deref() == Some(source)
+            })
+        });
+        let Some(document) = document else {
+            if !output.unmatched_documents.contains(&imported.source) {
+                output.unmatched_documents.push(imported.source);
+            }
+            continue;
+        };
+        match groups.iter_mut().find(|(doc, _)| doc.id == document.id) {
+            Some((_, items)) => items.push(imported),
+            None => groups.push((document, vec![imported])),
+        }
+    }
+
+    for (document, items) in groups {
+        let before = output.annotations;
+        place_in_document(conn, document, items, &mut output)?;
+        if output.annotations > before {
+            output.documents.push(document.title.clone());
+        }
+    }
+    Ok(output)
+}
+
+fn place_in_document(
+    conn: &Connection,
+    document: &Document,
+    items: Vec<ImportedWebAnnotation>,
+    output: &mut NotesImportOutput,
+) -> Result<()> {
+    let paragraphs = database::list_paragraphs(conn, &document.id)?;
+    let matcher = search::ParagraphMatcher::new(
+        &paragraphs
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>(),
+    );
+    let mut existing: HashSet<(String, String)> =
+        database::list_annotations_by_document(conn, &document.id)?
+            .into_iter()
+            .map(|annotation| (annotation.paragraph_id, annotation.selected_text))
+            .collect();
+    let now = chrono::Utc::now().timestamp();
+
+    for item in items {
+        if let Some(id) = item.id.as_deref() {
+            if database::get_annotation(conn, id)?.is_some() {
+                output.skipped_duplicates += 1;
+                continue;
+            }
+        }
+        let exact = item.exact.trim();
+        let hint = item.anchor.as_ref();
+        // The paragraph the fragment names, then any paragraph quoting the
+        // text, then the closest fuzzy match
+        let at_location = item
+            .location
+            .as_deref()
+            .and_then(|location| paragraphs.iter().find(|p| p.location == location))
+            .and_then(|p| Some((p, search::resolve_anchor(&p.text, exact, hint)?)));
+        let placed = at_location
+            .or_else(|| {
+                paragraphs
+                    .iter()
+                    .find_map(|p| Some((p, search::resolve_anchor(&p.text, exact, hint)?)))
+            })
+            .or_else(|| {
+                let found = matcher.locate(exact)?;
+                let p = &paragraphs[found.paragraph_index];
+                Some((p, search::anchor_at_bytes(&p.text, found.start, found.end)?))
+            });
+        let Some((paragraph, anchor)) = placed else {
+            output.unplaced.push(item.exact);
+            continue;
+        };
+        let selected_text: String = paragraph
+            .text
+            .chars()
+            .skip(anchor.start)
+            .take(anchor.end - anchor.start)
+            .collect();
+        if !existing.insert((paragraph.id.clone(), selected_text.clone())) {
+            output.skipped_duplicates += 1;
+            continue;
+        }
+
+        let created_at = item.created_at.unwrap_or(now);
+        let annotation = Annotation {
+            id: item.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+            paragraph_id: paragraph.id.clone(),
+            selected_text,
+            style: item
+                .classes
+                .iter()
+                .find(|class| validate_style(class).is_ok())
+                .cloned()
+                .unwrap_or_else(|| STYLE_HIGHLIGHT.to_string()),
+            color: item
+                .classes
+                .iter()
+                .find(|class| ANNOTATION_COLORS.contains(&class.as_str()))
+                .cloned(),
+            note: item.note,
+            tags: item.tags,
+            anchor: Some(anchor),
+            created_at,
+            updated_at: item.updated_at.unwrap_or(created_at),
+        };
+        database::restore_annotation(conn, &annotation)?;
+        output.annotations += 1;
+    }
+    Ok(())
+}
@@ -107,6 +107,15 @@ pub struct Config {
     pub reader_font_size: u32,
     #[serde(default)]
     pub keymap: KeymapConfig,
+    #[serde(default)]
+    pub ocr_enabled: bool,
+    #[serde(default = "default_ocr_languages")]
+    pub ocr_languages: String,
+    #[serde(default)]
+    pub ocr_tesseract_path: Option<String>,
+    /// Copy imported files into the app's own library folder
+    #[serde(default)]
+    pub managed_library: bool,
 }
 
 fn default_reader_background_color() -> String {
@@ -163,6 +172,10 @@ fn default_reader_font_size() -> u32 {
     18
 }
 
+fn default_ocr_languages() -> String {
+    "eng".to_string()
+}
+
 fn default_keymap_next_page() -> Vec<String> {
     vec!["PageDown".to_string(), "Space".to_string(), "J".to_string()]
 }
@@ -243,6 +256,10 @@ impl Default for Config {
             reader_background_color: default_reader_background_color(),
             reader_font_size: default_reader_font_size(),
             keymap: KeymapConfig::default(),
+            ocr_enabled: false,
+            ocr_languages: default_ocr_languages(),
+            ocr_tesseract_path: None,
+            managed_library: false,
         }
     }
 }
@@ -1,39 +1,158 @@
-use crate::models::Annotation;
-use rusqlite::{params, Connection, Result};
+use super::split_list;
+use crate::models::{Annotation, NewAnnotation, TextAnchor};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use thiserror::Error;
 use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum AnnotationError {
+    #[error("Annotation not found")]
+    NotFound,
     #[error("Database error: {0}")]
     DatabaseError(#[from] rusqlite::Error),
 }
 
+const ANNOTATION_COLUMNS: &str = "a.id, a.paragraph_id, a.selected_text, a.style, a.note,
+     a.created_at, a.updated_at, a.color, a.start_offset, a.end_offset, a.prefix, a.suffix,
+     (SELECT group_concat(t.name, char(31)) FROM annotation_tags t
+      WHERE t.annotation_id = a.id)";
+
+fn annotation_from_row(row: &Row) -> Result<Annotation> {
+    let start: Option<i64> = row.get(8)?;
+    let end: Option<i64> = row.get(9)?;
+    let anchor = match (start, end) {
+        (Some(start), Some(end)) => Some(TextAnchor {
+            start: start as usize,
+            end: end as usize,
+            prefix: row.get::<_, Option<String>>(10)?.unwrap_or_default(),
+            suffix: row.get::<_, Option<String>>(11)?.unwrap_or_default(),
+        }),
+        _ => None,
+    };
+    let mut tags = split_list(row.get(12)?);
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    Ok(Annotation {
+        id: row.get(0)?,
+        paragraph_id: row.get(1)?,
+        selected_text: row.get(2)?,
+        style: row.get(3)?,
+        note: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        color: row.get(7)?,
+        anchor,
+        tags,
+    })
+}
+
 pub fn insert(
     conn: &Connection,
-    paragraph_id: &str,
-    selected_text: &str,
-    style: &str,
-    note: Option<&str>,
+    annotation: &NewAnnotation,
 ) -> Result<Annotation, AnnotationError> {
-    let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp();
+    insert_at(conn, annotation, now)
+}
+
+/// Inserts an annotation made at `created_at`, e.g. one imported from another reader
+pub fn insert_at(
+    conn: &Connection,
+    annotation: &NewAnnotation,
+    created_at: i64,
+) -> Result<Annotation, AnnotationError> {
+    let annotation = Annotation {
+        id: Uuid::new_v4().to_string(),
+        paragraph_id: annotation.paragraph_id.clone(),
+        selected_text: annotation.selected_text.clone(),
+        style: annotation.style.clone(),
+        color: annotation.color.clone(),
+        note: annotation.note.clone(),
+        tags: annotation.tags.clone(),
+        anchor: annotation.anchor.clone(),
+        created_at,
+        updated_at: created_at,
+    };
+    restore(conn, &annotation)?;
+
+    get(conn, &annotation.id)?.ok_or(AnnotationError::NotFound)
+}
+
+/// Inserts an annotation as it was exported, keeping its id and timestamps
+pub fn restore(conn: &Connection, annotation: &Annotation) -> Result<(), AnnotationError> {
+    let anchor = annotation.anchor.as_ref();
 
     conn.execute(
-        "INSERT INTO annotations (id, paragraph_id, selected_text, style, note, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![&id, paragraph_id, selected_text, style, note, now, now],
+        "INSERT INTO annotations (id, paragraph_id, selected_text, style, note, color,
+             start_offset, end_offset, prefix, suffix, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            annotation.id,
+            annotation.paragraph_id,
+            annotation.selected_text,
+            annotation.style,
+            annotation.note,
+            annotation.color,
+            anchor.map(|a| a.start as i64),
+            anchor.map(|a| a.end as i64),
+            anchor.map(|a| a.prefix.as_str()),
+            anchor.map(|a| a.suffix.as_str()),
+            annotation.created_at,
+            annotation.updated_at,
+        ],
     )?;
+    set_tags(conn, &annotation.id, &annotation.tags)
+}
 
-    Ok(Annotation {
-        id,
-        paragraph_id: paragraph_id.to_string(),
-        selected_text: selected_text.to_string(),
-        style: style.to_string(),
-        note: note.map(|v| v.to_string()),
-        created_at: now,
-        updated_at: now,
-    })
+pub fn get(conn: &Connection, id: &str) -> Result<Option<Annotation>, AnnotationError> {
+    let annotation = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM annotations a WHERE a.id = ?1",
+                ANNOTATION_COLUMNS
+            ),
+            params![id],
+            annotation_from_row,
+        )
+        .optional()?;
+    Ok(annotation)
+}
+
+/// Replaces an annotation's style, colour and note
+pub fn update(
+    conn: &Connection,
+    id: &str,
+    style: &str,
+    color: Option<&str>,
+    note: Option<&str>,
+) -> Result<(), AnnotationError> {
+    let rows_affected = conn.execute(
+        "UPDATE annotations SET style = ?1, color = ?2, note = ?3, updated_at = ?4 WHERE id = ?5",
+        params![style, color, note, chrono::Utc::now().timestamp(), id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(AnnotationError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Replaces an annotation's tags; blank and repeated names are dropped
+pub fn set_tags(conn: &Connection, id: &str, tags: &[String]) -> Result<(), AnnotationError> {
+    conn.execute(
+        "DELETE FROM annotation_tags WHERE annotation_id = ?1",
+        params![id],
+    )?;
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO annotation_tags (annotation_id, name) VALUES (?1, ?2)",
+    )?;
+    for tag in tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        stmt.execute(params![id, tag])?;
+    }
+    Ok(())
 }
 
 pub fn delete(conn: &Connection, id: &str) -> Result<(), AnnotationError> {
@@ -56,11 +175,11 @@ pub fn list_by_paragraph_ids(
         .join(",");
 
     let sql = format!(
-        "SELECT id, paragraph_id, selected_text, style, note, created_at, updated_at
-         FROM annotations
-         WHERE paragraph_id IN ({})
-         ORDER BY created_at DESC",
-        placeholders
+        "SELECT {}
+         FROM annotations a
+         WHERE a.paragraph_id IN ({})
+         ORDER BY a.created_at DESC",
+        ANNOTATION_COLUMNS, placeholders
     );
 
     let mut stmt = conn.prepare(&sql)?;
@@ -70,19 +189,54 @@ pub fn list_by_paragraph_ids(
             .map(|id| id as &dyn rusqlite::ToSql)
             .collect::<Vec<_>>()
             .as_slice(),
-        |row| {
-            Ok(Annotation {
-                id: row.get(0)?,
-                paragraph_id: row.get(1)?,
-                selected_text: row.get(2)?,
-                style: row.get(3)?,
-                note: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
-            })
-        },
+        annotation_from_row,
     )?;
 
     let annotations = rows.collect::<Result<Vec<_>, _>>()?;
     Ok(annotations)
 }
+
+/// Lists all annotations on a document's paragraphs
+pub fn list_by_document(
+    conn: &Connection,
+    doc_id: &str,
+) -> Result<Vec<Annotation>, AnnotationError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM annotations a
+         JOIN paragraphs p ON p.id = a.paragraph_id
+         WHERE p.doc_id = ?1
+         ORDER BY a.created_at",
+        ANNOTATION_COLUMNS
+    ))?;
+    let annotations = stmt
+        .query_map(params![doc_id], annotation_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(annotations)
+}
+
+/// Moves an annotation to another paragraph or position, e.g. after a re-import
+pub fn relocate(
+    conn: &Connection,
+    id: &str,
+    paragraph_id: &str,
+    selected_text: &str,
+    anchor: Option<&TextAnchor>,
+) -> Result<(), AnnotationError> {
+    conn.execute(
+        "UPDATE annotations
+         SET paragraph_id = ?1, selected_text = ?2, start_offset = ?3, end_offset = ?4,
+             prefix = ?5, suffix = ?6
+         WHERE id = ?7",
+        params![
+            paragraph_id,
+            selected_text,
+            anchor.map(|a| a.start as i64),
+            anchor.map(|a| a.end as i64),
+            anchor.map(|a| a.prefix.as_str()),
+            anchor.map(|a| a.suffix.as_str()),
+            id
+        ],
+    )?;
+    Ok(())
+}
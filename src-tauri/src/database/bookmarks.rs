@@ -0,0 +1,163 @@
+use crate::models::Bookmark;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum BookmarkError {
+    #[error("Bookmark not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+/// A bookmark with where it points, for listings across documents
+#[derive(Debug, Clone)]
+pub struct BookmarkEntry {
+    pub bookmark: Bookmark,
+    pub document_title: String,
+    pub section_id: String,
+    pub section_title: String,
+    /// Location of the bookmarked paragraph
+    pub location: String,
+}
+
+const ENTRY_COLUMNS: &str = "b.id, b.doc_id, b.paragraph_id, b.title, b.note, b.color,
+     b.created_at, b.updated_at, d.title, s.id, s.title, p.location";
+
+const ENTRY_JOINS: &str = "FROM bookmarks b
+     JOIN documents d ON d.id = b.doc_id
+     JOIN paragraphs p ON p.id = b.paragraph_id
+     JOIN sections s ON s.id = p.section_id";
+
+fn entry_from_row(row: &Row) -> Result<BookmarkEntry> {
+    Ok(BookmarkEntry {
+        bookmark: Bookmark {
+            id: row.get(0)?,
+            doc_id: row.get(1)?,
+            paragraph_id: row.get(2)?,
+            title: row.get(3)?,
+            note: row.get(4)?,
+            color: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        },
+        document_title: row.get(8)?,
+        section_id: row.get(9)?,
+        section_title: row.get(10)?,
+        location: row.get(11)?,
+    })
+}
+
+pub fn insert(
+    conn: &Connection,
+    doc_id: &str,
+    paragraph_id: &str,
+    title: &str,
+    note: Option<&str>,
+    color: Option<&str>,
+) -> Result<Bookmark, BookmarkError> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO bookmarks (id, doc_id, paragraph_id, title, note, color, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![&id, doc_id, paragraph_id, title, note, color, now, now],
+    )?;
+
+    Ok(Bookmark {
+        id,
+        doc_id: doc_id.to_string(),
+        paragraph_id: paragraph_id.to_string(),
+        title: title.to_string(),
+        note: note.map(str::to_string),
+        color: color.map(str::to_string),
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Inserts a bookmark as it was exported, keeping its id and timestamps
+pub fn restore(conn: &Connection, bookmark: &Bookmark) -> Result<(), BookmarkError> {
+    conn.execute(
+        "INSERT INTO bookmarks (id, doc_id, paragraph_id, title, note, color, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            bookmark.id,
+            bookmark.doc_id,
+            bookmark.paragraph_id,
+            bookmark.title,
+            bookmark.note,
+            bookmark.color,
+            bookmark.created_at,
+            bookmark.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<BookmarkEntry>, BookmarkError> {
+    let entry = conn
+        .query_row(
+            &format!("SELECT {} {} WHERE b.id = ?1", ENTRY_COLUMNS, ENTRY_JOINS),
+            params![id],
+            entry_from_row,
+        )
+        .optional()?;
+    Ok(entry)
+}
+
+/// Lists a document's bookmarks in reading order, or all bookmarks by
+/// document title when `doc_id` is `None`
+pub fn list(conn: &Connection, doc_id: Option<&str>) -> Result<Vec<BookmarkEntry>, BookmarkError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} {}
+         WHERE ?1 IS NULL OR b.doc_id = ?1
+         ORDER BY d.title COLLATE NOCASE, b.doc_id, s.order_index, p.order_index, b.created_at",
+        ENTRY_COLUMNS, ENTRY_JOINS
+    ))?;
+    let entries = stmt
+        .query_map(params![doc_id], entry_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Replaces a bookmark's title, note and colour
+pub fn update(
+    conn: &Connection,
+    id: &str,
+    title: &str,
+    note: Option<&str>,
+    color: Option<&str>,
+) -> Result<(), BookmarkError> {
+    let rows_affected = conn.execute(
+        "UPDATE bookmarks SET title = ?1, note = ?2, color = ?3, updated_at = ?4 WHERE id = ?5",
+        params![title, note, color, Utc::now().timestamp(), id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(BookmarkError::NotFound);
+    }
+
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<(), BookmarkError> {
+    let rows_affected = conn.execute("DELETE FROM bookmarks WHERE id = ?1", params![id])?;
+
+    if rows_affected == 0 {
+        return Err(BookmarkError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Moves a bookmark to another paragraph, e.g. after a re-import
+pub fn relocate(conn: &Connection, id: &str, paragraph_id: &str) -> Result<(), BookmarkError> {
+    conn.execute(
+        "UPDATE bookmarks SET paragraph_id = ?1 WHERE id = ?2",
+        params![paragraph_id, id],
+    )?;
+    Ok(())
+}
@@ -76,7 +76,7 @@ pub fn get_translation(
     paragraph_id: &str,
     target_lang: &str,
 ) -> Result<Option<Translation>, CacheError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, paragraph_id, target_lang, translation, created_at
          FROM cache_translations
          WHERE paragraph_id = ?1 AND target_lang = ?2",
@@ -133,7 +133,7 @@ pub fn get_text_translation(
     text_hash: &str,
     target_lang: &str,
 ) -> Result<Option<TextTranslation>, CacheError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, text_hash, target_lang, translation, created_at
          FROM cache_text_translations
          WHERE text_hash = ?1 AND target_lang = ?2",
@@ -193,7 +193,7 @@ pub fn get_summary(
     target_type: &str,
     style: &str,
 ) -> Result<Option<Summary>, CacheError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, target_id, target_type, style, summary, created_at
          FROM cache_summaries
          WHERE target_id = ?1 AND target_type = ?2 AND style = ?3",
@@ -214,3 +214,25 @@ pub fn get_summary(
 
     Ok(summaries.into_iter().next())
 }
+
+/// Moves cached translations from one paragraph to another; returns how many moved
+pub fn move_translations(
+    conn: &Connection,
+    from_paragraph_id: &str,
+    to_paragraph_id: &str,
+) -> Result<usize, CacheError> {
+    let moved = conn.execute(
+        "UPDATE OR IGNORE cache_translations SET paragraph_id = ?1 WHERE paragraph_id = ?2",
+        params![to_paragraph_id, from_paragraph_id],
+    )?;
+    Ok(moved)
+}
+
+/// Deletes all cached summaries of a target (document, section or paragraph)
+pub fn delete_summaries(conn: &Connection, target_id: &str) -> Result<(), CacheError> {
+    conn.execute(
+        "DELETE FROM cache_summaries WHERE target_id = ?1",
+        params![target_id],
+    )?;
+    Ok(())
+}
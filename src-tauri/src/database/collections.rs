@@ -0,0 +1,182 @@
+use crate::models::Collection;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum CollectionError {
+    #[error("Collection not found")]
+    NotFound,
+    #[error("A collection named \"{0}\" already exists")]
+    DuplicateName(String),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+const COLLECTION_COLUMNS: &str = "c.id, c.name, c.description, c.created_at, c.updated_at,
+     (SELECT COUNT(*) FROM document_collections dc WHERE dc.collection_id = c.id)";
+
+fn collection_from_row(row: &Row) -> Result<Collection> {
+    Ok(Collection {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        document_count: row.get::<_, i64>(5)? as usize,
+    })
+}
+
+/// Fails when another collection already uses `name`, ignoring case
+fn check_name_free(
+    conn: &Connection,
+    name: &str,
+    except_id: Option<&str>,
+) -> Result<(), CollectionError> {
+    let taken = conn
+        .query_row(
+            "SELECT id FROM collections WHERE name = ?1",
+            params![name],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    match taken {
+        Some(id) if Some(id.as_str()) != except_id => {
+            Err(CollectionError::DuplicateName(name.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn insert(
+    conn: &Connection,
+    name: &str,
+    description: Option<&str>,
+) -> Result<Collection, CollectionError> {
+    check_name_free(conn, name, None)?;
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO collections (id, name, description, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![&id, name, description, now, now],
+    )?;
+
+    Ok(Collection {
+        id,
+        name: name.to_string(),
+        description: description.map(str::to_string),
+        document_count: 0,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Lists all collections by name
+pub fn list(conn: &Connection) -> Result<Vec<Collection>, CollectionError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM collections c ORDER BY c.name",
+        COLLECTION_COLUMNS
+    ))?;
+    let collections = stmt
+        .query_map([], collection_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(collections)
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<Collection>, CollectionError> {
+    let collection = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM collections c WHERE c.id = ?1",
+                COLLECTION_COLUMNS
+            ),
+            params![id],
+            collection_from_row,
+        )
+        .optional()?;
+    Ok(collection)
+}
+
+/// Replaces a collection's name and description
+pub fn update(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    description: Option<&str>,
+) -> Result<(), CollectionError> {
+    check_name_free(conn, name, Some(id))?;
+    let rows_affected = conn.execute(
+        "UPDATE collections SET name = ?1, description = ?2, updated_at = ?3 WHERE id = ?4",
+        params![name, description, Utc::now().timestamp(), id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(CollectionError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Deletes a collection; its documents stay in the library
+pub fn delete(conn: &Connection, id: &str) -> Result<(), CollectionError> {
+    conn.execute(
+        "DELETE FROM document_collections WHERE collection_id = ?1",
+        params![id],
+    )?;
+    let rows_affected = conn.execute("DELETE FROM collections WHERE id = ?1", params![id])?;
+
+    if rows_affected == 0 {
+        return Err(CollectionError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Adds documents to a collection; ones already in it are left alone
+pub fn add_documents(
+    conn: &Connection,
+    id: &str,
+    doc_ids: &[String],
+) -> Result<(), CollectionError> {
+    touch(conn, id)?;
+    let now = Utc::now().timestamp();
+    for doc_id in doc_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO document_collections (doc_id, collection_id, added_at)
+             VALUES (?1, ?2, ?3)",
+            params![doc_id, id, now],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn remove_documents(
+    conn: &Connection,
+    id: &str,
+    doc_ids: &[String],
+) -> Result<(), CollectionError> {
+    touch(conn, id)?;
+    for doc_id in doc_ids {
+        conn.execute(
+            "DELETE FROM document_collections WHERE doc_id = ?1 AND collection_id = ?2",
+            params![doc_id, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Bumps a collection's `updated_at`, failing when it doesn't exist
+fn touch(conn: &Connection, id: &str) -> Result<(), CollectionError> {
+    let rows_affected = conn.execute(
+        "UPDATE collections SET updated_at = ?1 WHERE id = ?2",
+        params![Utc::now().timestamp(), id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(CollectionError::NotFound);
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
-use crate::models::{Document, NewDocument};
+use super::split_list;
+use crate::models::{Document, DocumentFilter, DocumentMetadata, NewDocument};
 use chrono::Utc;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, Result, Row};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -42,36 +43,101 @@ pub fn insert(conn: &Connection, new_doc: NewDocument) -> Result<Document, Docum
         language: new_doc.language,
         file_path: new_doc.file_path,
         file_type: new_doc.file_type,
+        content_hash: None,
+        original_path: None,
+        series: None,
+        series_index: None,
+        tags: Vec::new(),
+        collection_ids: Vec::new(),
+        metadata: DocumentMetadata::default(),
         created_at: now,
         updated_at: now,
     })
 }
 
+/// Document columns, with tag names and collection ids joined for `split_list`
+const DOCUMENT_COLUMNS: &str = "d.id, d.title, d.author, d.language, d.file_path, d.file_type,
+     d.created_at, d.updated_at, d.content_hash, d.original_path, d.series, d.series_index,
+     d.subtitle, d.publisher, d.published_date, d.isbn, d.doi, d.description, d.rating,
+     (SELECT group_concat(t.name, char(31)) FROM document_tags dt
+      JOIN tags t ON t.id = dt.tag_id WHERE dt.doc_id = d.id),
+     (SELECT group_concat(dc.collection_id, char(31)) FROM document_collections dc
+      WHERE dc.doc_id = d.id)";
+
+fn document_from_row(row: &Row) -> Result<Document> {
+    let mut tags = split_list(row.get(19)?);
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    let mut collection_ids = split_list(row.get(20)?);
+    collection_ids.sort();
+    Ok(Document {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        author: row.get(2)?,
+        language: row.get(3)?,
+        file_path: row.get(4)?,
+        file_type: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        content_hash: row.get(8)?,
+        original_path: row.get(9)?,
+        series: row.get(10)?,
+        series_index: row.get(11)?,
+        tags,
+        collection_ids,
+        metadata: DocumentMetadata {
+            subtitle: row.get(12)?,
+            publisher: row.get(13)?,
+            published_date: row.get(14)?,
+            isbn: row.get(15)?,
+            doi: row.get(16)?,
+            description: row.get(17)?,
+            rating: row.get(18)?,
+        },
+    })
+}
+
 /// Lists all documents in the database
 ///
 /// Returns documents ordered by created_at in descending order (newest first).
 pub fn list(conn: &Connection) -> Result<Vec<Document>, DocumentError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, title, author, language, file_path, file_type, created_at, updated_at
-         FROM documents
-         ORDER BY created_at DESC",
-    )?;
+    list_filtered(conn, &DocumentFilter::default())
+}
 
-    let documents = stmt
-        .query_map([], |row| {
-            Ok(Document {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                author: row.get(2)?,
-                language: row.get(3)?,
-                file_path: row.get(4)?,
-                file_type: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
-        })?
+/// Lists the documents matching every field set in `filter`
+///
+/// Documents of one series are ordered by series index, others newest first.
+pub fn list_filtered(
+    conn: &Connection,
+    filter: &DocumentFilter,
+) -> Result<Vec<Document>, DocumentError> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {}
+         FROM documents d
+         WHERE (?1 IS NULL OR EXISTS (
+                SELECT 1 FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+                WHERE dt.doc_id = d.id AND t.name = ?1))
+           AND (?2 IS NULL OR EXISTS (
+                SELECT 1 FROM document_collections dc
+                WHERE dc.doc_id = d.id AND dc.collection_id = ?2))
+           AND (?3 IS NULL OR d.series = ?3 COLLATE NOCASE)
+         ORDER BY d.created_at DESC",
+        DOCUMENT_COLUMNS
+    ))?;
+
+    let mut documents = stmt
+        .query_map(
+            params![filter.tag, filter.collection_id, filter.series],
+            document_from_row,
+        )?
         .collect::<Result<Vec<_>, _>>()?;
 
+    if filter.series.is_some() {
+        // Unnumbered volumes go last
+        documents.sort_by(|a, b| match (a.series_index, b.series_index) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
     Ok(documents)
 }
 
@@ -79,25 +145,13 @@ pub fn list(conn: &Connection) -> Result<Vec<Document>, DocumentError> {
 ///
 /// Returns None if the document doesn't exist.
 pub fn get(conn: &Connection, id: &str) -> Result<Option<Document>, DocumentError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, title, author, language, file_path, file_type, created_at, updated_at
-         FROM documents
-         WHERE id = ?1",
-    )?;
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM documents d WHERE d.id = ?1",
+        DOCUMENT_COLUMNS
+    ))?;
 
     let documents = stmt
-        .query_map(params![id], |row| {
-            Ok(Document {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                author: row.get(2)?,
-                language: row.get(3)?,
-                file_path: row.get(4)?,
-                file_type: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
-        })?
+        .query_map(params![id], document_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(documents.into_iter().next())
@@ -116,3 +170,213 @@ pub fn delete(conn: &Connection, id: &str) -> Result<(), DocumentError> {
 
     Ok(())
 }
+
+/// Sets the series a document belongs to and its position in it
+pub fn set_series(
+    conn: &Connection,
+    id: &str,
+    series: Option<&str>,
+    series_index: Option<f64>,
+) -> Result<(), DocumentError> {
+    let rows_affected = conn.execute(
+        "UPDATE documents SET series = ?1, series_index = ?2, updated_at = ?3 WHERE id = ?4",
+        params![series, series_index, Utc::now().timestamp(), id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(DocumentError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Replaces a document's title, author, language and extended metadata
+///
+/// `updated_at` only moves when the title, author or language change, so
+/// rating or describing a book doesn't bring it to the top of "recent".
+pub fn update_details(
+    conn: &Connection,
+    id: &str,
+    title: &str,
+    author: Option<&str>,
+    language: Option<&str>,
+    metadata: &DocumentMetadata,
+) -> Result<(), DocumentError> {
+    let rows_affected = conn.execute(
+        "UPDATE documents SET
+            updated_at = CASE WHEN title IS ?2 AND author IS ?3 AND language IS ?4
+                              THEN updated_at ELSE ?12 END,
+            title = ?2, author = ?3, language = ?4, subtitle = ?5, publisher = ?6,
+            published_date = ?7, isbn = ?8, doi = ?9, description = ?10, rating = ?11
+         WHERE id = ?1",
+        params![
+            id,
+            title,
+            author,
+            language,
+            metadata.subtitle,
+            metadata.publisher,
+            metadata.published_date,
+            metadata.isbn,
+            metadata.doi,
+            metadata.description,
+            metadata.rating,
+            Utc::now().timestamp(),
+        ],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(DocumentError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Records an ISBN and DOI found in the file, keeping ones already set
+pub fn fill_identifiers(
+    conn: &Connection,
+    id: &str,
+    isbn: Option<&str>,
+    doi: Option<&str>,
+) -> Result<(), DocumentError> {
+    conn.execute(
+        "UPDATE documents SET isbn = COALESCE(isbn, ?1), doi = COALESCE(doi, ?2) WHERE id = ?3",
+        params![isbn, doi, id],
+    )?;
+    Ok(())
+}
+
+/// Lists series names with the number of documents in each
+pub fn list_series(conn: &Connection) -> Result<Vec<(String, usize)>, DocumentError> {
+    let mut stmt = conn.prepare(
+        "SELECT series, COUNT(*) FROM documents
+         WHERE series IS NOT NULL AND series <> ''
+         GROUP BY series COLLATE NOCASE
+         ORDER BY series COLLATE NOCASE",
+    )?;
+    let series = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(series)
+}
+
+/// Records the SHA-256 of the file a document was imported from
+pub fn set_content_hash(
+    conn: &Connection,
+    id: &str,
+    content_hash: &str,
+) -> Result<(), DocumentError> {
+    conn.execute(
+        "UPDATE documents SET content_hash = ?1 WHERE id = ?2",
+        params![content_hash, id],
+    )?;
+    Ok(())
+}
+
+/// Points a document at a new file, e.g. a managed library copy or a moved original
+pub fn set_file_paths(
+    conn: &Connection,
+    id: &str,
+    file_path: &str,
+    original_path: Option<&str>,
+) -> Result<(), DocumentError> {
+    let rows_affected = conn.execute(
+        "UPDATE documents SET file_path = ?1, original_path = ?2 WHERE id = ?3",
+        params![file_path, original_path, id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(DocumentError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Bumps a document's `updated_at`, e.g. after its content was re-imported
+pub fn touch(conn: &Connection, id: &str) -> Result<(), DocumentError> {
+    conn.execute(
+        "UPDATE documents SET updated_at = ?1 WHERE id = ?2",
+        params![Utc::now().timestamp(), id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_filtered, set_series};
+    use crate::database::{self, add_document_tags, add_to_collection, insert_collection};
+    use crate::models::DocumentFilter;
+
+    fn titles(documents: Vec<crate::models::Document>) -> Vec<String> {
+        let mut titles: Vec<String> = documents.into_iter().map(|doc| doc.title).collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn filters_by_tag_collection_and_series() {
+        let conn = database::open_in_memory();
+        let dune = database::insert_test_document(&conn, "dune");
+        let emma = database::insert_test_document(&conn, "emma");
+        let ubik = database::insert_test_document(&conn, "ubik");
+        add_document_tags(&conn, &dune, &["Science Fiction".to_string()]).unwrap();
+        add_document_tags(&conn, &ubik, &["science fiction".to_string()]).unwrap();
+        let shelf = insert_collection(&conn, "Shelf", None).unwrap();
+        add_to_collection(&conn, &shelf.id, &[dune.clone(), emma.clone()]).unwrap();
+
+        let by_tag = DocumentFilter {
+            tag: Some("SCIENCE FICTION".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            titles(list_filtered(&conn, &by_tag).unwrap()),
+            ["dune", "ubik"]
+        );
+
+        let by_collection = DocumentFilter {
+            collection_id: Some(shelf.id.clone()),
+            ..Default::default()
+        };
+        assert_eq!(
+            titles(list_filtered(&conn, &by_collection).unwrap()),
+            ["dune", "emma"]
+        );
+
+        let both = DocumentFilter {
+            tag: Some("science fiction".to_string()),
+            collection_id: Some(shelf.id),
+            series: None,
+        };
+        assert_eq!(titles(list_filtered(&conn, &both).unwrap()), ["dune"]);
+
+        assert_eq!(
+            list_filtered(&conn, &DocumentFilter::default())
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn orders_series_by_index_with_unnumbered_last() {
+        let conn = database::open_in_memory();
+        let extra = database::insert_test_document(&conn, "extra");
+        let second = database::insert_test_document(&conn, "second");
+        let first = database::insert_test_document(&conn, "first");
+        database::insert_test_document(&conn, "other");
+        set_series(&conn, &extra, Some("Foundation"), None).unwrap();
+        set_series(&conn, &second, Some("Foundation"), Some(2.0)).unwrap();
+        set_series(&conn, &first, Some("Foundation"), Some(1.0)).unwrap();
+
+        let filter = DocumentFilter {
+            series: Some("foundation".to_string()),
+            ..Default::default()
+        };
+        let ordered: Vec<String> = list_filtered(&conn, &filter)
+            .unwrap()
+            .into_iter()
+            .map(|doc| doc.title)
+            .collect();
+        assert_eq!(ordered, ["first", "second", "extra"]);
+    }
+}
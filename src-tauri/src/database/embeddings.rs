@@ -144,7 +144,7 @@ pub fn clear_by_profile(
 ///
 /// Returns None if the embedding doesn't exist.
 pub fn get(conn: &Connection, paragraph_id: &str) -> Result<Option<Embedding>, EmbeddingError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, paragraph_id, vector, dim, provider, model, created_at, updated_at
          FROM embeddings
          WHERE paragraph_id = ?1",
@@ -175,7 +175,7 @@ pub fn get(conn: &Connection, paragraph_id: &str) -> Result<Option<Embedding>, E
 ///
 /// Returns all embeddings ordered by created_at in descending order (newest first).
 pub fn list_all_vectors(conn: &Connection) -> Result<Vec<Embedding>, EmbeddingError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, paragraph_id, vector, dim, provider, model, created_at, updated_at
          FROM embeddings
          ORDER BY created_at DESC",
@@ -207,7 +207,7 @@ pub fn list_all_vectors(conn: &Connection) -> Result<Vec<Embedding>, EmbeddingEr
 /// Returns embeddings for paragraphs belonging to the specified document,
 /// ordered by created_at in descending order (newest first).
 pub fn list_by_document(conn: &Connection, doc_id: &str) -> Result<Vec<Embedding>, EmbeddingError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT e.id, e.paragraph_id, e.vector, e.dim, e.provider, e.model, e.created_at, e.updated_at
          FROM embeddings e
          JOIN paragraphs p ON e.paragraph_id = p.id
@@ -244,7 +244,7 @@ pub fn list_by_profile(
     doc_id: Option<&str>,
 ) -> Result<Vec<Embedding>, EmbeddingError> {
     if let Some(doc_id) = doc_id {
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             "SELECT e.id, e.paragraph_id, e.vector, e.dim, e.provider, e.model, e.created_at, e.updated_at
              FROM embeddings e
              JOIN paragraphs p ON e.paragraph_id = p.id
@@ -269,7 +269,7 @@ pub fn list_by_profile(
         return Ok(rows.collect::<Result<Vec<_>, _>>()?);
     }
 
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, paragraph_id, vector, dim, provider, model, created_at, updated_at
          FROM embeddings
          WHERE provider = ?1 AND model = ?2 AND dim = ?3
@@ -292,3 +292,16 @@ pub fn list_by_profile(
     })?;
     Ok(rows.collect::<Result<Vec<_>, _>>()?)
 }
+
+/// Moves the embedding of one paragraph to another; returns whether one moved
+pub fn move_to_paragraph(
+    conn: &Connection,
+    from_paragraph_id: &str,
+    to_paragraph_id: &str,
+) -> Result<bool, EmbeddingError> {
+    let moved = conn.execute(
+        "UPDATE OR IGNORE embeddings SET paragraph_id = ?1 WHERE paragraph_id = ?2",
+        params![to_paragraph_id, from_paragraph_id],
+    )?;
+    Ok(moved > 0)
+}
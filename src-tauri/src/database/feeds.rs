@@ -0,0 +1,160 @@
+use crate::models::Feed;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum FeedError {
+    #[error("Feed not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+const FEED_COLUMNS: &str = "id, url, title, site_url, poll_interval_minutes, last_polled_at, last_error, created_at, updated_at";
+
+fn feed_from_row(row: &Row) -> Result<Feed> {
+    Ok(Feed {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        site_url: row.get(3)?,
+        poll_interval_minutes: row.get(4)?,
+        last_polled_at: row.get(5)?,
+        last_error: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+/// Inserts a new feed subscription
+pub fn insert(
+    conn: &Connection,
+    url: &str,
+    title: &str,
+    site_url: Option<&str>,
+    poll_interval_minutes: Option<i64>,
+) -> Result<Feed, FeedError> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO feeds (id, url, title, site_url, poll_interval_minutes, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![&id, url, title, site_url, poll_interval_minutes, now, now],
+    )?;
+
+    Ok(Feed {
+        id,
+        url: url.to_string(),
+        title: title.to_string(),
+        site_url: site_url.map(str::to_string),
+        poll_interval_minutes,
+        last_polled_at: None,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Lists all feeds ordered by title
+pub fn list(conn: &Connection) -> Result<Vec<Feed>, FeedError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM feeds ORDER BY title COLLATE NOCASE",
+        FEED_COLUMNS
+    ))?;
+    let feeds = stmt
+        .query_map([], feed_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(feeds)
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<Feed>, FeedError> {
+    let feed = conn
+        .query_row(
+            &format!("SELECT {} FROM feeds WHERE id = ?1", FEED_COLUMNS),
+            params![id],
+            feed_from_row,
+        )
+        .optional()?;
+    Ok(feed)
+}
+
+pub fn find_by_url(conn: &Connection, url: &str) -> Result<Option<Feed>, FeedError> {
+    let feed = conn
+        .query_row(
+            &format!("SELECT {} FROM feeds WHERE url = ?1", FEED_COLUMNS),
+            params![url],
+            feed_from_row,
+        )
+        .optional()?;
+    Ok(feed)
+}
+
+/// Deletes a feed and its entry records; imported documents are kept
+pub fn delete(conn: &Connection, id: &str) -> Result<(), FeedError> {
+    conn.execute("DELETE FROM feed_entries WHERE feed_id = ?1", params![id])?;
+    let rows_affected = conn.execute("DELETE FROM feeds WHERE id = ?1", params![id])?;
+
+    if rows_affected == 0 {
+        return Err(FeedError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Records the outcome of a poll; a fetched title replaces the stored one
+pub fn mark_polled(
+    conn: &Connection,
+    id: &str,
+    title: Option<&str>,
+    error: Option<&str>,
+) -> Result<(), FeedError> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "UPDATE feeds
+         SET title = COALESCE(?1, title), last_error = ?2, last_polled_at = ?3, updated_at = ?3
+         WHERE id = ?4",
+        params![title, error, now, id],
+    )?;
+    Ok(())
+}
+
+/// Whether an entry with this GUID was already imported from the feed
+pub fn entry_exists(conn: &Connection, feed_id: &str, guid: &str) -> Result<bool, FeedError> {
+    let exists = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM feed_entries WHERE feed_id = ?1 AND guid = ?2)",
+        params![feed_id, guid],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Records an imported entry and the document created for it
+pub fn insert_entry(
+    conn: &Connection,
+    feed_id: &str,
+    guid: &str,
+    doc_id: &str,
+    title: Option<&str>,
+    link: Option<&str>,
+    published_at: Option<i64>,
+) -> Result<(), FeedError> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT OR IGNORE INTO feed_entries (id, feed_id, guid, doc_id, title, link, published_at, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            &id,
+            feed_id,
+            guid,
+            doc_id,
+            title,
+            link,
+            published_at,
+            Utc::now().timestamp()
+        ],
+    )?;
+    Ok(())
+}
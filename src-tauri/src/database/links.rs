@@ -0,0 +1,102 @@
+use crate::models::ParagraphLink;
+use rusqlite::{params, Connection, Result};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum LinkError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+/// Inserts a link between two paragraphs of the same document
+///
+/// Generates a UUID v4 for the link ID.
+pub fn insert(
+    conn: &Connection,
+    doc_id: &str,
+    source_paragraph_id: &str,
+    target_paragraph_id: &str,
+    target_location: &str,
+    kind: &str,
+    label: &str,
+) -> Result<ParagraphLink, LinkError> {
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO paragraph_links (id, doc_id, source_paragraph_id, target_paragraph_id, target_location, kind, label, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            &id,
+            doc_id,
+            source_paragraph_id,
+            target_paragraph_id,
+            target_location,
+            kind,
+            label,
+            now
+        ],
+    )?;
+
+    Ok(ParagraphLink {
+        id,
+        doc_id: doc_id.to_string(),
+        source_paragraph_id: source_paragraph_id.to_string(),
+        target_paragraph_id: target_paragraph_id.to_string(),
+        target_location: target_location.to_string(),
+        kind: kind.to_string(),
+        label: label.to_string(),
+        created_at: now,
+    })
+}
+
+/// Lists links whose source is one of the given paragraphs
+///
+/// Returns links ordered by creation (import) order.
+pub fn list_by_source_paragraph_ids(
+    conn: &Connection,
+    paragraph_ids: &[String],
+) -> Result<Vec<ParagraphLink>, LinkError> {
+    if paragraph_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders = paragraph_ids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let sql = format!(
+        "SELECT id, doc_id, source_paragraph_id, target_paragraph_id, target_location, kind, label, created_at
+         FROM paragraph_links
+         WHERE source_paragraph_id IN ({})
+         ORDER BY created_at, rowid",
+        placeholders
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        paragraph_ids
+            .iter()
+            .map(|id| id as &dyn rusqlite::ToSql)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        |row| {
+            Ok(ParagraphLink {
+                id: row.get(0)?,
+                doc_id: row.get(1)?,
+                source_paragraph_id: row.get(2)?,
+                target_paragraph_id: row.get(3)?,
+                target_location: row.get(4)?,
+                kind: row.get(5)?,
+                label: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        },
+    )?;
+
+    let links = rows.collect::<Result<Vec<_>, _>>()?;
+    Ok(links)
+}
@@ -1,29 +1,42 @@
 mod annotations;
+mod bookmarks;
 mod cache;
+mod collections;
 mod documents;
 pub mod embeddings;
+mod feeds;
+mod links;
 pub mod paragraphs;
+mod pool;
+mod reading_positions;
 mod schema;
 mod sections;
+mod tags;
+mod watched_folders;
 
 use rusqlite::{Connection, Result};
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 use tracing::{error, info};
 
-pub use schema::create_tables;
+pub use pool::{DbPool, PooledConnection};
+pub use schema::{migrate, MigrationError, LATEST_VERSION};
 
 // Document operations
 pub use documents::DocumentError;
 pub use documents::{
-    delete as delete_document, get as get_document, insert as insert_document,
-    list as list_documents,
+    delete as delete_document, fill_identifiers as fill_document_identifiers, get as get_document,
+    insert as insert_document, list as list_documents, list_filtered as list_documents_filtered,
+    list_series, set_content_hash as set_document_content_hash,
+    set_file_paths as set_document_file_paths, set_series as set_document_series,
+    touch as touch_document, update_details as update_document_details,
 };
 
 // Section operations
 pub use sections::SectionError;
 pub use sections::{
-    get as get_section, insert as insert_section, list_by_document as list_sections,
+    delete_stashed as delete_stashed_sections, get as get_section, insert as insert_section,
+    list_by_document as list_sections, stash_for_document as stash_document_sections,
 };
 
 // Paragraph operations
@@ -38,22 +51,75 @@ pub use embeddings::{bytes_to_vec_f32, vec_f32_to_bytes};
 pub use embeddings::{
     clear_by_profile as clear_embeddings_by_profile, get as get_embedding,
     insert as insert_embedding, list_all_vectors, list_by_document, list_by_profile,
-    upsert_batch as upsert_embeddings_batch,
+    move_to_paragraph as move_embedding, upsert_batch as upsert_embeddings_batch,
 };
 pub use embeddings::{Embedding, EmbeddingError};
 
 // Cache operations
 pub use cache::{
-    get_summary, get_text_translation, get_translation, save_summary, save_text_translation,
-    save_translation,
+    delete_summaries, get_summary, get_text_translation, get_translation, move_translations,
+    save_summary, save_text_translation, save_translation,
 };
 pub use cache::{CacheError, Summary, Translation};
 
 // Annotation operations
 pub use annotations::AnnotationError;
 pub use annotations::{
-    delete as delete_annotation, insert as insert_annotation,
-    list_by_paragraph_ids as list_annotations_by_paragraph_ids,
+    delete as delete_annotation, get as get_annotation, insert as insert_annotation,
+    insert_at as insert_annotation_at, list_by_document as list_annotations_by_document,
+    list_by_paragraph_ids as list_annotations_by_paragraph_ids, relocate as relocate_annotation,
+    restore as restore_annotation, set_tags as set_annotation_tags, update as update_annotation,
+};
+
+// Bookmark operations
+pub use bookmarks::{
+    delete as delete_bookmark, get as get_bookmark, insert as insert_bookmark,
+    list as list_bookmarks, relocate as relocate_bookmark, restore as restore_bookmark,
+    update as update_bookmark,
+};
+pub use bookmarks::{BookmarkEntry, BookmarkError};
+
+// Feed operations
+pub use feeds::FeedError;
+pub use feeds::{
+    delete as delete_feed, entry_exists as feed_entry_exists, find_by_url as find_feed_by_url,
+    get as get_feed, insert as insert_feed, insert_entry as insert_feed_entry, list as list_feeds,
+    mark_polled as mark_feed_polled,
+};
+
+// Tag operations
+pub use tags::TagError;
+pub use tags::{
+    add_to_document as add_document_tags, delete as delete_tag, list as list_tags,
+    rename as rename_tag, set_for_document as set_document_tags,
+};
+
+// Collection operations
+pub use collections::CollectionError;
+pub use collections::{
+    add_documents as add_to_collection, delete as delete_collection, get as get_collection,
+    insert as insert_collection, list as list_collections,
+    remove_documents as remove_from_collection, update as update_collection,
+};
+
+// Watched folder operations
+pub use watched_folders::{
+    delete as delete_watched_folder, get_imported_file, insert as insert_watched_folder,
+    list as list_watched_folders, mark_scanned as mark_folder_scanned, record_imported_file,
+};
+pub use watched_folders::{ImportedFile, WatchedFolderError};
+
+// Reading position operations
+pub use reading_positions::ReadingPositionError;
+pub use reading_positions::{
+    get as get_reading_position, list_recent as list_recent_positions,
+    move_to_paragraph as move_reading_position, save as save_reading_position,
+};
+
+// Paragraph link operations
+pub use links::LinkError;
+pub use links::{
+    insert as insert_paragraph_link, list_by_source_paragraph_ids as list_paragraph_links,
 };
 
 // Convert EmbeddingError to ReaderError
@@ -84,6 +150,55 @@ impl From<AnnotationError> for crate::ReaderError {
     }
 }
 
+// Convert BookmarkError to ReaderError
+impl From<BookmarkError> for crate::ReaderError {
+    fn from(err: BookmarkError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert LinkError to ReaderError
+impl From<LinkError> for crate::ReaderError {
+    fn from(err: LinkError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert FeedError to ReaderError
+impl From<FeedError> for crate::ReaderError {
+    fn from(err: FeedError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert TagError to ReaderError
+impl From<TagError> for crate::ReaderError {
+    fn from(err: TagError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert CollectionError to ReaderError
+impl From<CollectionError> for crate::ReaderError {
+    fn from(err: CollectionError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert WatchedFolderError to ReaderError
+impl From<WatchedFolderError> for crate::ReaderError {
+    fn from(err: WatchedFolderError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
+// Convert ReadingPositionError to ReaderError
+impl From<ReadingPositionError> for crate::ReaderError {
+    fn from(err: ReadingPositionError) -> Self {
+        crate::ReaderError::Internal(err.to_string())
+    }
+}
+
 // Convert DocumentError to ReaderError
 impl From<DocumentError> for crate::ReaderError {
     fn from(err: DocumentError) -> Self {
@@ -98,6 +213,17 @@ impl From<SectionError> for crate::ReaderError {
     }
 }
 
+/// ASCII unit separator, used by `group_concat(.., char(31))` columns since it
+/// can't appear in names typed by users
+const LIST_SEPARATOR: char = '\u{1f}';
+
+/// Splits a `group_concat` column joined by `LIST_SEPARATOR`
+fn split_list(value: Option<String>) -> Vec<String> {
+    value
+        .map(|value| value.split(LIST_SEPARATOR).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
 /// Gets the path to the SQLite database file
 ///
 /// Returns the path to reader.db in the application's data directory
@@ -113,41 +239,86 @@ pub fn get_db_path(handle: &AppHandle) -> PathBuf {
     app_data_dir.join("reader.db")
 }
 
-/// Opens a connection to the SQLite database
+/// Takes a connection from the shared pool
 ///
-/// Enables WAL mode for better concurrency and performance
-pub fn get_connection(handle: &AppHandle) -> Result<Connection> {
-    let db_path = get_db_path(handle);
-    info!("Opening database connection: {:?}", db_path);
-
-    let mut conn = Connection::open(db_path)?;
+/// Connections come with WAL, foreign keys and a busy timeout set, and go
+/// back to the pool when dropped.
+pub fn get_connection(handle: &AppHandle) -> Result<PooledConnection> {
+    handle.state::<DbPool>().get()
+}
 
-    // Enable WAL mode for better concurrency
-    // Note: journal_mode returns a value, so we use query_row
-    let _journal_mode = conn.query_row("PRAGMA journal_mode = WAL", [], |row| {
-        row.get::<_, String>(0)
-    })?;
+/// Runs blocking database work on a pooled connection off the async runtime
+pub async fn run_blocking<T, F>(handle: &AppHandle, work: F) -> crate::error::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> crate::error::Result<T> + Send + 'static,
+{
+    run_blocking_on(&get_pool(handle), work).await
+}
 
-    // Set busy timeout to 5 seconds
-    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+/// The shared pool, for work that outlives a single command
+pub fn get_pool(handle: &AppHandle) -> DbPool {
+    handle.state::<DbPool>().inner().clone()
+}
 
-    info!("Database connection opened successfully");
-    Ok(conn)
+/// Like `run_blocking`, on a pool the caller already holds
+pub async fn run_blocking_on<T, F>(pool: &DbPool, work: F) -> crate::error::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> crate::error::Result<T> + Send + 'static,
+{
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get()?;
+        work(&conn)
+    })
+    .await
+    .map_err(|e| crate::ReaderError::Internal(e.to_string()))?
 }
 
-/// Initializes the database schema
+/// Initializes the connection pool and the database schema
 ///
-/// Creates all tables and indexes if they don't exist
-pub fn init_db(handle: &AppHandle) -> Result<()> {
+/// Runs pending migrations, backing the database up to `backups/` first
+pub fn init_db(handle: &AppHandle) -> std::result::Result<(), MigrationError> {
     info!("Initializing database");
 
+    let db_path = get_db_path(handle);
+    let backup_dir = db_path.with_file_name("backups");
+    handle.manage(DbPool::new(db_path));
     let conn = get_connection(handle)?;
 
-    create_tables(&conn).map_err(|e| {
-        error!("Failed to create database tables: {}", e);
+    migrate(&conn, Some(&backup_dir)).map_err(|e| {
+        error!("Failed to migrate database: {}", e);
         e
     })?;
 
     info!("Database initialized successfully");
     Ok(())
 }
+
+/// A migrated in-memory database with foreign keys on, for tests
+#[cfg(test)]
+pub fn open_in_memory() -> Connection {
+    let conn = Connection::open_in_memory().expect("open in-memory database");
+    conn.pragma_update(None, "foreign_keys", "ON")
+        .expect("enable foreign keys");
+    migrate(&conn, None).expect("migrate in-memory database");
+    conn
+}
+
+/// Inserts an empty EPUB document titled `title`, for tests
+#[cfg(test)]
+pub fn insert_test_document(conn: &Connection, title: &str) -> String {
+    insert_document(
+        conn,
+        crate::models::NewDocument {
+            title: title.to_string(),
+            author: None,
+            language: None,
+            file_path: format!("/books/{}.epub", title),
+            file_type: "epub".to_string(),
+        },
+    )
+    .expect("insert test document")
+    .id
+}
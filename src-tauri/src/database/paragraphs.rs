@@ -21,14 +21,24 @@ pub fn insert(
     order_index: i32,
     text: &str,
     location: &str,
+    kind: &str,
 ) -> Result<Paragraph, ParagraphError> {
     let id = Uuid::new_v4().to_string();
 
-    conn.execute(
-        "INSERT INTO paragraphs (id, doc_id, section_id, order_index, text, location)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![&id, doc_id, section_id, order_index, text, location],
-    )?;
+    // Imports insert paragraphs by the thousand; reuse the statement
+    conn.prepare_cached(
+        "INSERT INTO paragraphs (id, doc_id, section_id, order_index, text, location, kind)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?
+    .execute(params![
+        &id,
+        doc_id,
+        section_id,
+        order_index,
+        text,
+        location,
+        kind
+    ])?;
 
     Ok(Paragraph {
         id,
@@ -37,6 +47,7 @@ pub fn insert(
         order_index,
         text: text.to_string(),
         location: location.to_string(),
+        kind: kind.to_string(),
     })
 }
 
@@ -47,8 +58,8 @@ pub fn list_by_section(
     conn: &Connection,
     section_id: &str,
 ) -> Result<Vec<Paragraph>, ParagraphError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, doc_id, section_id, order_index, text, location
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, doc_id, section_id, order_index, text, location, kind
          FROM paragraphs
          WHERE section_id = ?1
          ORDER BY order_index",
@@ -63,6 +74,7 @@ pub fn list_by_section(
                 order_index: row.get(3)?,
                 text: row.get(4)?,
                 location: row.get(5)?,
+                kind: row.get(6)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -74,8 +86,8 @@ pub fn list_by_section(
 ///
 /// Returns None if the paragraph doesn't exist.
 pub fn get(conn: &Connection, id: &str) -> Result<Option<Paragraph>, ParagraphError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, doc_id, section_id, order_index, text, location
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, doc_id, section_id, order_index, text, location, kind
          FROM paragraphs
          WHERE id = ?1",
     )?;
@@ -89,6 +101,7 @@ pub fn get(conn: &Connection, id: &str) -> Result<Option<Paragraph>, ParagraphEr
                 order_index: row.get(3)?,
                 text: row.get(4)?,
                 location: row.get(5)?,
+                kind: row.get(6)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -100,8 +113,8 @@ pub fn get(conn: &Connection, id: &str) -> Result<Option<Paragraph>, ParagraphEr
 ///
 /// Returns paragraphs ordered by section_id and order_index in ascending order.
 pub fn list_by_document(conn: &Connection, doc_id: &str) -> Result<Vec<Paragraph>, ParagraphError> {
-    let mut stmt = conn.prepare(
-        "SELECT p.id, p.doc_id, p.section_id, p.order_index, p.text, p.location
+    let mut stmt = conn.prepare_cached(
+        "SELECT p.id, p.doc_id, p.section_id, p.order_index, p.text, p.location, p.kind
          FROM paragraphs p
          JOIN sections s ON p.section_id = s.id
          WHERE p.doc_id = ?1
@@ -117,6 +130,7 @@ pub fn list_by_document(conn: &Connection, doc_id: &str) -> Result<Vec<Paragraph
                 order_index: row.get(3)?,
                 text: row.get(4)?,
                 location: row.get(5)?,
+                kind: row.get(6)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -0,0 +1,161 @@
+//! Shared SQLite connection pool
+//!
+//! Connections are opened once with the pragmas every caller relies on and
+//! returned to the pool when dropped, so their prepared statement caches
+//! survive between commands.
+
+use rusqlite::{Connection, Result};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Idle connections kept open; extra ones are closed when returned
+const MAX_IDLE: usize = 8;
+/// Prepared statements cached per connection
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Pool of connections to the reader database, kept in Tauri state
+#[derive(Clone)]
+pub struct DbPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl DbPool {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                path,
+                idle: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Takes an idle connection, or opens one when none is free
+    pub fn get(&self) -> Result<PooledConnection> {
+        let idle = self
+            .inner
+            .idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => open_connection(&self.inner.path)?,
+        };
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self.inner.clone(),
+        })
+    }
+}
+
+/// A pooled connection, returned to the pool on drop
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<PoolInner>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("pooled connection already returned")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn
+            .as_mut()
+            .expect("pooled connection already returned")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        // A connection left inside a transaction can't be handed out again
+        if !conn.is_autocommit() {
+            return;
+        }
+        let mut idle = self
+            .pool
+            .idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if idle.len() < MAX_IDLE {
+            idle.push(conn);
+        }
+    }
+}
+
+fn open_connection(path: &std::path::Path) -> Result<Connection> {
+    debug!("Opening database connection: {:?}", path);
+    let conn = Connection::open(path)?;
+    configure(&conn)?;
+    Ok(conn)
+}
+
+/// Per-connection settings; none of these persist in the database file
+/// except `journal_mode`
+fn configure(conn: &Connection) -> Result<()> {
+    // journal_mode returns a value, so it has to be queried
+    let _journal_mode: String =
+        conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    // Safe with WAL; only the last transactions can be lost on power failure
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DbPool;
+
+    #[test]
+    fn reuses_connections_with_pragmas_applied() {
+        let dir = std::env::temp_dir().join(format!("reader_pool_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pool = DbPool::new(dir.join("reader.db"));
+
+        let first = pool.get().unwrap();
+        first
+            .execute("CREATE TEMP TABLE marker (id INTEGER)", [])
+            .unwrap();
+        let foreign_keys: i64 = first
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        drop(first);
+
+        // The temp table only exists on the connection that created it
+        let second = pool.get().unwrap();
+        let reused = second.prepare("SELECT id FROM temp.marker").is_ok();
+        let other = pool.get().unwrap();
+        let fresh = other.prepare("SELECT id FROM temp.marker").is_err();
+
+        // A connection dropped mid-transaction is discarded
+        other.execute_batch("BEGIN").unwrap();
+        drop(other);
+        drop(second);
+        let idle = pool.inner.idle.lock().unwrap().len();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(foreign_keys, 1);
+        assert!(reused);
+        assert!(fresh);
+        assert_eq!(idle, 1);
+    }
+}
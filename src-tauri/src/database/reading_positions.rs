@@ -0,0 +1,167 @@
+use crate::models::ReadingPosition;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReadingPositionError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+fn position_from_row(row: &Row) -> Result<ReadingPosition> {
+    Ok(ReadingPosition {
+        doc_id: row.get(0)?,
+        section_id: row.get(1)?,
+        paragraph_id: row.get(2)?,
+        location: row.get(3)?,
+        scroll_fraction: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// Records where reading stopped in a document, replacing the previous position
+pub fn save(
+    conn: &Connection,
+    doc_id: &str,
+    section_id: Option<&str>,
+    paragraph_id: Option<&str>,
+    location: Option<&str>,
+    scroll_fraction: f64,
+) -> Result<ReadingPosition, ReadingPositionError> {
+    let position = ReadingPosition {
+        doc_id: doc_id.to_string(),
+        section_id: section_id.map(str::to_string),
+        paragraph_id: paragraph_id.map(str::to_string),
+        location: location.map(str::to_string),
+        scroll_fraction: scroll_fraction.clamp(0.0, 1.0),
+        updated_at: Utc::now().timestamp(),
+    };
+    // Saved on every scroll pause; reuse the statement
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO reading_positions
+         (doc_id, section_id, paragraph_id, location, scroll_fraction, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?
+    .execute(params![
+        &position.doc_id,
+        &position.section_id,
+        &position.paragraph_id,
+        &position.location,
+        position.scroll_fraction,
+        position.updated_at
+    ])?;
+    Ok(position)
+}
+
+pub fn get(
+    conn: &Connection,
+    doc_id: &str,
+) -> Result<Option<ReadingPosition>, ReadingPositionError> {
+    let position = conn
+        .prepare_cached(
+            "SELECT doc_id, section_id, paragraph_id, location, scroll_fraction, updated_at
+             FROM reading_positions WHERE doc_id = ?1",
+        )?
+        .query_row(params![doc_id], position_from_row)
+        .optional()?;
+    Ok(position)
+}
+
+/// Lists positions by last activity, most recent first
+pub fn list_recent(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<ReadingPosition>, ReadingPositionError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT doc_id, section_id, paragraph_id, location, scroll_fraction, updated_at
+         FROM reading_positions ORDER BY updated_at DESC, rowid DESC LIMIT ?1",
+    )?;
+    let positions = stmt
+        .query_map(params![limit as i64], position_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(positions)
+}
+
+/// Points a position at another paragraph without counting it as reading
+///
+/// Used when a re-import replaces the paragraph the position was on.
+pub fn move_to_paragraph(
+    conn: &Connection,
+    doc_id: &str,
+    section_id: Option<&str>,
+    paragraph_id: Option<&str>,
+) -> Result<(), ReadingPositionError> {
+    conn.execute(
+        "UPDATE reading_positions SET section_id = ?1, paragraph_id = ?2 WHERE doc_id = ?3",
+        params![section_id, paragraph_id, doc_id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, list_recent, save};
+    use crate::database;
+
+    #[test]
+    fn saves_latest_position_per_document() {
+        let conn = database::open_in_memory();
+        let doc_id = database::insert_test_document(&conn, "first");
+        assert!(get(&conn, &doc_id).unwrap().is_none());
+
+        save(&conn, &doc_id, Some("s1"), Some("p1"), None, 0.25).unwrap();
+        save(
+            &conn,
+            &doc_id,
+            Some("s2"),
+            Some("p7"),
+            Some("epubcfi(/6/4)"),
+            0.5,
+        )
+        .unwrap();
+
+        let position = get(&conn, &doc_id).unwrap().unwrap();
+        assert_eq!(position.section_id.as_deref(), Some("s2"));
+        assert_eq!(position.paragraph_id.as_deref(), Some("p7"));
+        assert_eq!(position.location.as_deref(), Some("epubcfi(/6/4)"));
+        assert_eq!(position.scroll_fraction, 0.5);
+        assert_eq!(list_recent(&conn, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn clamps_scroll_fraction() {
+        let conn = database::open_in_memory();
+        let doc_id = database::insert_test_document(&conn, "first");
+
+        let saved = save(&conn, &doc_id, None, None, None, 1.5).unwrap();
+        assert_eq!(saved.scroll_fraction, 1.0);
+        assert_eq!(get(&conn, &doc_id).unwrap().unwrap().scroll_fraction, 1.0);
+
+        save(&conn, &doc_id, None, None, None, -0.2).unwrap();
+        assert_eq!(get(&conn, &doc_id).unwrap().unwrap().scroll_fraction, 0.0);
+    }
+
+    #[test]
+    fn lists_most_recently_read_first() {
+        let conn = database::open_in_memory();
+        let first = database::insert_test_document(&conn, "first");
+        let second = database::insert_test_document(&conn, "second");
+        let third = database::insert_test_document(&conn, "third");
+
+        save(&conn, &first, None, None, None, 0.1).unwrap();
+        save(&conn, &second, None, None, None, 0.2).unwrap();
+        save(&conn, &third, None, None, None, 0.3).unwrap();
+        save(&conn, &first, None, None, None, 0.4).unwrap();
+
+        let order: Vec<String> = list_recent(&conn, 10)
+            .unwrap()
+            .into_iter()
+            .map(|position| position.doc_id)
+            .collect();
+        assert_eq!(order, vec![first.clone(), third, second]);
+        let limited = list_recent(&conn, 1).unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].doc_id, first);
+    }
+}
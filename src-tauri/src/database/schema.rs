@@ -1,19 +1,226 @@
+//! Versioned schema migrations
+//!
+//! The schema version is kept in `PRAGMA user_version`. Each migration runs
+//! in its own transaction together with the version bump, so a failure
+//! leaves the database at the last version that applied cleanly. New schema
+//! changes are appended to [`MIGRATIONS`]; released entries never change.
+//!
+//! Databases created before versioning report version 0 but may already
+//! hold any part of the schema, so migrations only add what is missing.
+
+use chrono::Utc;
 use rusqlite::{Connection, Result};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 use tracing::info;
 
-/// Creates all tables and indexes for the reader database
+/// Pre-migration backups kept in the backup folder
+const MAX_BACKUPS: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("Database schema version {found} is newer than this app supports ({supported})")]
+    TooNew { found: usize, supported: usize },
+    #[error("Migration {version} ({description}) failed: {source}")]
+    Failed {
+        version: usize,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+    #[error("Failed to back up database: {0}")]
+    BackupFailed(#[from] std::io::Error),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+struct Migration {
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Every schema change in order; migration `n` upgrades version `n - 1` to `n`
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "initial schema",
+        apply: initial_schema,
+    },
+    Migration {
+        description: "paragraph links",
+        apply: paragraph_links,
+    },
+    Migration {
+        description: "paragraph kinds",
+        apply: paragraph_kinds,
+    },
+    Migration {
+        description: "tags and series",
+        apply: tags_and_series,
+    },
+    Migration {
+        description: "feeds",
+        apply: feeds,
+    },
+    Migration {
+        description: "watched folders",
+        apply: watched_folders,
+    },
+    Migration {
+        description: "document content hashes",
+        apply: content_hashes,
+    },
+    Migration {
+        description: "managed library paths",
+        apply: original_paths,
+    },
+    Migration {
+        description: "reading positions",
+        apply: reading_positions,
+    },
+    Migration {
+        description: "bookmarks",
+        apply: bookmarks,
+    },
+    Migration {
+        description: "collections",
+        apply: collections,
+    },
+    Migration {
+        description: "extended document metadata",
+        apply: extended_metadata,
+    },
+    Migration {
+        description: "annotation anchors, colours and tags",
+        apply: annotation_anchors,
+    },
+    Migration {
+        description: "rows orphaned while foreign keys were off",
+        apply: orphaned_rows,
+    },
+];
+
+/// Schema version of a fully migrated database
+pub const LATEST_VERSION: usize = MIGRATIONS.len();
+
+/// Brings the database schema up to [`LATEST_VERSION`]
 ///
-/// This function sets up the complete database schema including:
-/// - 6 tables: documents, sections, paragraphs, embeddings, cache_summaries, cache_translations
-/// - 3 indexes for performance optimization
-/// - Foreign key constraints with CASCADE deletes
-pub fn create_tables(conn: &Connection) -> Result<()> {
-    info!("Creating database schema");
+/// When migrations are pending on an existing database and `backup_dir` is
+/// given, a copy of the database is written there first.
+pub fn migrate(conn: &Connection, backup_dir: Option<&Path>) -> Result<(), MigrationError> {
+    run_migrations(conn, MIGRATIONS, backup_dir)
+}
+
+fn run_migrations(
+    conn: &Connection,
+    migrations: &[Migration],
+    backup_dir: Option<&Path>,
+) -> Result<(), MigrationError> {
+    let current = schema_version(conn)?;
+    if current > migrations.len() {
+        return Err(MigrationError::TooNew {
+            found: current,
+            supported: migrations.len(),
+        });
+    }
+    if current == migrations.len() {
+        return Ok(());
+    }
+
+    if let Some(backup_dir) = backup_dir {
+        if has_tables(conn)? {
+            let backup = back_up(conn, backup_dir, current)?;
+            info!("Backed up database to {:?} before migrating", backup);
+        }
+    }
+
+    for (idx, migration) in migrations.iter().enumerate().skip(current) {
+        let version = idx + 1;
+        info!(
+            "Migrating database to version {}: {}",
+            version, migration.description
+        );
+        let failed = |source| MigrationError::Failed {
+            version,
+            description: migration.description,
+            source,
+        };
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx).map_err(failed)?;
+        tx.pragma_update(None, "user_version", version as i64)
+            .map_err(failed)?;
+        tx.commit().map_err(failed)?;
+    }
+
+    info!("Database schema is at version {}", migrations.len());
+    Ok(())
+}
+
+fn schema_version(conn: &Connection) -> Result<usize> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+        .map(|version| version.max(0) as usize)
+}
+
+fn has_tables(conn: &Connection) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Writes a consistent copy of the database and prunes the oldest copies
+fn back_up(
+    conn: &Connection,
+    backup_dir: &Path,
+    version: usize,
+) -> Result<PathBuf, MigrationError> {
+    std::fs::create_dir_all(backup_dir)?;
+    let path = backup_dir.join(format!(
+        "reader-v{}-{}.db",
+        version,
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| {
+            file.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("reader-v") && name.ends_with(".db"))
+        })
+        .collect();
+    backups.sort_by_key(|file| {
+        std::fs::metadata(file)
+            .and_then(|meta| meta.modified())
+            .ok()
+    });
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in &backups[..excess] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(path)
+}
 
-    // Enable foreign keys
-    conn.execute("PRAGMA foreign_keys = ON", [])?;
+/// Adds a column unless a pre-versioning build already added it
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<bool> {
+    let exists = {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?;
+        columns.iter().any(|c| c == column)
+    };
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(!exists)
+}
 
-    // Create documents table
+/// Documents, their content, embeddings, caches and annotations
+fn initial_schema(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS documents (
             id TEXT PRIMARY KEY,
@@ -28,7 +235,6 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create sections table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS sections (
             id TEXT PRIMARY KEY,
@@ -41,7 +247,6 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create paragraphs table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS paragraphs (
             id TEXT PRIMARY KEY,
@@ -55,7 +260,6 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create embeddings table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS embeddings (
             id TEXT PRIMARY KEY,
@@ -70,32 +274,20 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Backward-compatible migrations for existing embeddings table
-    let mut columns = Vec::new();
-    {
-        let mut stmt = conn.prepare("PRAGMA table_info(embeddings)")?;
-        let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
-        for row in rows {
-            columns.push(row?);
-        }
-    }
-    if !columns.iter().any(|c| c == "provider") {
-        conn.execute(
-            "ALTER TABLE embeddings ADD COLUMN provider TEXT NOT NULL DEFAULT 'unknown'",
-            [],
-        )?;
-    }
-    if !columns.iter().any(|c| c == "model") {
-        conn.execute(
-            "ALTER TABLE embeddings ADD COLUMN model TEXT NOT NULL DEFAULT ''",
-            [],
-        )?;
-    }
-    if !columns.iter().any(|c| c == "updated_at") {
-        conn.execute(
-            "ALTER TABLE embeddings ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0",
-            [],
-        )?;
+    // Embeddings tables from before embedding profiles were recorded
+    add_column(
+        conn,
+        "embeddings",
+        "provider",
+        "TEXT NOT NULL DEFAULT 'unknown'",
+    )?;
+    add_column(conn, "embeddings", "model", "TEXT NOT NULL DEFAULT ''")?;
+    if add_column(
+        conn,
+        "embeddings",
+        "updated_at",
+        "INTEGER NOT NULL DEFAULT 0",
+    )? {
         conn.execute(
             "UPDATE embeddings SET updated_at = created_at WHERE updated_at = 0",
             [],
@@ -120,7 +312,6 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create cache_summaries table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS cache_summaries (
             id TEXT PRIMARY KEY,
@@ -134,7 +325,7 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create cache_translations table (by paragraph_id)
+    // Translations by paragraph_id
     conn.execute(
         "CREATE TABLE IF NOT EXISTS cache_translations (
             id TEXT PRIMARY KEY,
@@ -147,7 +338,7 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create cache_text_translations table (by text hash)
+    // Translations by text hash
     conn.execute(
         "CREATE TABLE IF NOT EXISTS cache_text_translations (
             id TEXT PRIMARY KEY,
@@ -160,7 +351,6 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create annotations table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS annotations (
             id TEXT PRIMARY KEY,
@@ -174,42 +364,531 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    // Create indexes for performance (only 3 indexes as per spec)
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_sections_doc_id ON sections(doc_id);
+         CREATE INDEX IF NOT EXISTS idx_paragraphs_doc_id ON paragraphs(doc_id);
+         CREATE INDEX IF NOT EXISTS idx_paragraphs_section_id ON paragraphs(section_id);
+         CREATE UNIQUE INDEX IF NOT EXISTS idx_embeddings_paragraph_id_unique ON embeddings(paragraph_id);
+         CREATE INDEX IF NOT EXISTS idx_embeddings_profile ON embeddings(provider, model, dim);
+         CREATE INDEX IF NOT EXISTS idx_embeddings_paragraph_id ON embeddings(paragraph_id);
+         CREATE INDEX IF NOT EXISTS idx_annotations_paragraph_id ON annotations(paragraph_id);",
+    )
+}
+
+/// Footnotes and in-document cross references
+fn paragraph_links(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS paragraph_links (
+            id TEXT PRIMARY KEY,
+            doc_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            source_paragraph_id TEXT NOT NULL REFERENCES paragraphs(id) ON DELETE CASCADE,
+            target_paragraph_id TEXT NOT NULL REFERENCES paragraphs(id) ON DELETE CASCADE,
+            target_location TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            label TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_sections_doc_id ON sections(doc_id)",
+        "CREATE INDEX IF NOT EXISTS idx_paragraph_links_source ON paragraph_links(source_paragraph_id)",
         [],
     )?;
+    Ok(())
+}
 
+/// Marks paragraphs recognised by OCR
+fn paragraph_kinds(conn: &Connection) -> Result<()> {
+    add_column(conn, "paragraphs", "kind", "TEXT NOT NULL DEFAULT 'text'")?;
+    Ok(())
+}
+
+/// Tags (many-to-many between documents and tag names) and series
+fn tags_and_series(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS document_tags (
+            doc_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (doc_id, tag_id)
+        )",
+        [],
+    )?;
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_paragraphs_doc_id ON paragraphs(doc_id)",
+        "CREATE INDEX IF NOT EXISTS idx_document_tags_tag_id ON document_tags(tag_id)",
         [],
     )?;
+    add_column(conn, "documents", "series", "TEXT")?;
+    add_column(conn, "documents", "series_index", "REAL")?;
+    Ok(())
+}
 
+/// RSS/Atom subscriptions and the entries imported from them
+fn feeds(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_paragraphs_section_id ON paragraphs(section_id)",
+        "CREATE TABLE IF NOT EXISTS feeds (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL UNIQUE,
+            title TEXT NOT NULL,
+            site_url TEXT,
+            poll_interval_minutes INTEGER,
+            last_polled_at INTEGER,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
         [],
     )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS feed_entries (
+            id TEXT PRIMARY KEY,
+            feed_id TEXT NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+            guid TEXT NOT NULL,
+            doc_id TEXT REFERENCES documents(id) ON DELETE SET NULL,
+            title TEXT,
+            link TEXT,
+            published_at INTEGER,
+            imported_at INTEGER NOT NULL,
+            UNIQUE(feed_id, guid)
+        )",
+        [],
+    )?;
+    Ok(())
+}
 
+/// Library folders scanned for new or changed files, and the size and mtime
+/// of the files imported from them
+fn watched_folders(conn: &Connection) -> Result<()> {
     conn.execute(
-        "CREATE UNIQUE INDEX IF NOT EXISTS idx_embeddings_paragraph_id_unique ON embeddings(paragraph_id)",
+        "CREATE TABLE IF NOT EXISTS watched_folders (
+            id TEXT PRIMARY KEY,
+            path TEXT NOT NULL UNIQUE,
+            recursive INTEGER NOT NULL DEFAULT 1,
+            last_scanned_at INTEGER,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS imported_files (
+            file_path TEXT PRIMARY KEY,
+            doc_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            size INTEGER NOT NULL,
+            modified_at INTEGER NOT NULL,
+            imported_at INTEGER NOT NULL
+        )",
         [],
     )?;
+    Ok(())
+}
 
+/// SHA-256 of imported files, for duplicate detection
+fn content_hashes(conn: &Connection) -> Result<()> {
+    add_column(conn, "documents", "content_hash", "TEXT")?;
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_embeddings_profile ON embeddings(provider, model, dim)",
+        "CREATE INDEX IF NOT EXISTS idx_documents_content_hash ON documents(content_hash)",
         [],
     )?;
+    Ok(())
+}
+
+/// Where managed library copies were imported from
+fn original_paths(conn: &Connection) -> Result<()> {
+    add_column(conn, "documents", "original_path", "TEXT")?;
+    Ok(())
+}
 
+/// Where reading stopped in each document; paragraph and section ids are
+/// not foreign keys since re-imports replace them
+fn reading_positions(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reading_positions (
+            doc_id TEXT PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+            section_id TEXT,
+            paragraph_id TEXT,
+            location TEXT,
+            scroll_fraction REAL NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_embeddings_paragraph_id ON embeddings(paragraph_id)",
+        "CREATE INDEX IF NOT EXISTS idx_reading_positions_updated_at
+         ON reading_positions(updated_at)",
         [],
     )?;
+    Ok(())
+}
 
+/// Named bookmarks at a paragraph, kept apart from text annotations
+fn bookmarks(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+            id TEXT PRIMARY KEY,
+            doc_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            paragraph_id TEXT NOT NULL REFERENCES paragraphs(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            note TEXT,
+            color TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_annotations_paragraph_id ON annotations(paragraph_id)",
+        "CREATE INDEX IF NOT EXISTS idx_bookmarks_doc_id ON bookmarks(doc_id)",
         [],
     )?;
+    Ok(())
+}
 
-    info!("Database schema created successfully");
+/// User collections of documents; a document can be in any number of them
+fn collections(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS collections (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            description TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS document_collections (
+            doc_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            collection_id TEXT NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+            added_at INTEGER NOT NULL,
+            PRIMARY KEY (doc_id, collection_id)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_document_collections_collection_id
+         ON document_collections(collection_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_documents_series ON documents(series)",
+        [],
+    )?;
     Ok(())
 }
+
+/// Editable bibliographic fields beyond title, author and language
+fn extended_metadata(conn: &Connection) -> Result<()> {
+    add_column(conn, "documents", "subtitle", "TEXT")?;
+    add_column(conn, "documents", "publisher", "TEXT")?;
+    add_column(conn, "documents", "published_date", "TEXT")?;
+    add_column(conn, "documents", "isbn", "TEXT")?;
+    add_column(conn, "documents", "doi", "TEXT")?;
+    add_column(conn, "documents", "description", "TEXT")?;
+    add_column(conn, "documents", "rating", "INTEGER")?;
+    Ok(())
+}
+
+/// Character ranges with surrounding text, colours and tags for annotations
+fn annotation_anchors(conn: &Connection) -> Result<()> {
+    add_column(conn, "annotations", "start_offset", "INTEGER")?;
+    add_column(conn, "annotations", "end_offset", "INTEGER")?;
+    add_column(conn, "annotations", "prefix", "TEXT")?;
+    add_column(conn, "annotations", "suffix", "TEXT")?;
+    add_column(conn, "annotations", "color", "TEXT")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS annotation_tags (
+            annotation_id TEXT NOT NULL REFERENCES annotations(id) ON DELETE CASCADE,
+            name TEXT NOT NULL COLLATE NOCASE,
+            PRIMARY KEY (annotation_id, name)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_annotation_tags_name ON annotation_tags(name)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Clears references to rows deleted while foreign keys weren't enforced
+///
+/// Older builds never turned `foreign_keys` on, so deleting a document left
+/// its sections, paragraphs and the like behind. Each orphan gets what its
+/// `ON DELETE` action would have done: it is deleted, or its reference is
+/// set to NULL.
+fn orphaned_rows(conn: &Connection) -> Result<()> {
+    // Deleting an orphan can orphan the rows referencing it in turn
+    loop {
+        let orphans = conn
+            .prepare(
+                "SELECT c.\"table\", c.rowid, f.\"from\", f.on_delete
+                 FROM pragma_foreign_key_check c
+                 JOIN pragma_foreign_key_list(c.\"table\") f ON f.id = c.fkid
+                 WHERE c.rowid IS NOT NULL",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        if orphans.is_empty() {
+            return Ok(());
+        }
+        for (table, rowid, column, on_delete) in orphans {
+            let sql = if on_delete == "SET NULL" {
+                format!(
+                    "UPDATE \"{}\" SET \"{}\" = NULL WHERE rowid = ?1",
+                    table, column
+                )
+            } else {
+                format!("DELETE FROM \"{}\" WHERE rowid = ?1", table)
+            };
+            conn.execute(&sql, [rowid])?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_migrations, schema_version, Migration, MigrationError, MIGRATIONS};
+    use rusqlite::{Connection, Result};
+
+    /// Columns of every table and the names of all indexes
+    fn snapshot(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT m.type, m.name, p.name, p.type, p.\"notnull\", p.dflt_value, p.pk
+                 FROM sqlite_master m
+                 LEFT JOIN pragma_table_info(m.name) p ON m.type = 'table'
+                 WHERE m.name NOT LIKE 'sqlite_%'
+                 ORDER BY m.type, m.name, p.name",
+            )
+            .unwrap();
+        stmt.query_map([], |row| {
+            Ok(format!(
+                "{} {} {:?} {:?} {:?} {:?} {:?}",
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+            ))
+        })
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+    }
+
+    #[test]
+    fn upgrades_every_historical_version_to_latest() {
+        let latest = Connection::open_in_memory().unwrap();
+        run_migrations(&latest, MIGRATIONS, None).unwrap();
+        let expected = snapshot(&latest);
+
+        for level in 0..MIGRATIONS.len() {
+            let conn = Connection::open_in_memory().unwrap();
+            run_migrations(&conn, &MIGRATIONS[..level], None).unwrap();
+            assert_eq!(schema_version(&conn).unwrap(), level);
+            if level > 0 {
+                conn.execute(
+                    "INSERT INTO documents (id, title, file_path, file_type, created_at, updated_at)
+                     VALUES ('doc', 'Old', '/books/old.epub', 'epub', 1, 1)",
+                    [],
+                )
+                .unwrap();
+            }
+
+            run_migrations(&conn, MIGRATIONS, None).unwrap();
+            assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+            assert_eq!(
+                snapshot(&conn),
+                expected,
+                "upgrading from version {}",
+                level
+            );
+            let documents: i64 = conn
+                .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(documents, i64::from(level > 0));
+        }
+    }
+
+    #[test]
+    fn upgrades_unversioned_database_with_partial_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        // As left by a build from before versioning: some later columns,
+        // old-style embeddings and duplicate embedding rows
+        conn.execute_batch(
+            "CREATE TABLE documents (
+                id TEXT PRIMARY KEY, title TEXT NOT NULL, author TEXT, language TEXT,
+                file_path TEXT NOT NULL UNIQUE, file_type TEXT NOT NULL,
+                created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL,
+                series TEXT, series_index REAL
+            );
+            CREATE TABLE embeddings (
+                id TEXT PRIMARY KEY, paragraph_id TEXT NOT NULL, vector BLOB NOT NULL,
+                dim INTEGER NOT NULL, created_at INTEGER NOT NULL
+            );
+            INSERT INTO embeddings VALUES ('a', 'p1', x'00', 1, 1), ('b', 'p1', x'00', 1, 2);",
+        )
+        .unwrap();
+
+        run_migrations(&conn, MIGRATIONS, None).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+        let kept: String = conn
+            .query_row("SELECT id FROM embeddings", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(kept, "b");
+        let columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('documents')
+                 WHERE name IN ('series', 'content_hash', 'original_path')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(columns, 3);
+    }
+
+    #[test]
+    fn failed_migration_leaves_previous_version() {
+        fn create_first(conn: &Connection) -> Result<()> {
+            conn.execute("CREATE TABLE first (id TEXT)", []).map(|_| ())
+        }
+        fn create_then_fail(conn: &Connection) -> Result<()> {
+            conn.execute("CREATE TABLE second (id TEXT)", [])?;
+            conn.execute("INSERT INTO missing VALUES (1)", [])
+                .map(|_| ())
+        }
+        let migrations = [
+            Migration {
+                description: "first",
+                apply: create_first,
+            },
+            Migration {
+                description: "broken",
+                apply: create_then_fail,
+            },
+        ];
+        let conn = Connection::open_in_memory().unwrap();
+
+        let result = run_migrations(&conn, &migrations, None);
+
+        assert!(matches!(
+            result,
+            Err(MigrationError::Failed { version: 2, .. })
+        ));
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        let second_exists: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'second')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!second_exists);
+        conn.pragma_update(None, "user_version", 5).unwrap();
+        assert!(matches!(
+            run_migrations(&conn, &migrations, None),
+            Err(MigrationError::TooNew {
+                found: 5,
+                supported: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn backs_up_before_migrating_existing_database() {
+        let dir =
+            std::env::temp_dir().join(format!("reader_migration_test_{}", std::process::id()));
+        let backups = dir.join("backups");
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open(dir.join("reader.db")).unwrap();
+        run_migrations(&conn, &MIGRATIONS[..2], Some(&backups)).unwrap();
+        // A new database has nothing worth backing up
+        let after_create = std::fs::read_dir(&backups).map(|d| d.count()).unwrap_or(0);
+
+        run_migrations(&conn, MIGRATIONS, Some(&backups)).unwrap();
+        let backup_files: Vec<_> = std::fs::read_dir(&backups)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        let backup_version = schema_version(&Connection::open(&backup_files[0]).unwrap()).unwrap();
+        // Up to date: no further backups
+        run_migrations(&conn, MIGRATIONS, Some(&backups)).unwrap();
+        let later = std::fs::read_dir(&backups).unwrap().count();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(after_create, 0);
+        assert_eq!(backup_files.len(), 1);
+        assert_eq!(backup_version, 2);
+        assert_eq!(later, 1);
+    }
+
+    #[test]
+    fn removes_rows_orphaned_while_foreign_keys_were_off() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn, &MIGRATIONS[..MIGRATIONS.len() - 1], None).unwrap();
+        // As older builds ran, which never turned foreign keys on
+        conn.pragma_update(None, "foreign_keys", "OFF").unwrap();
+        conn.execute_batch(
+            "INSERT INTO documents (id, title, file_path, file_type, created_at, updated_at)
+             VALUES ('doc', 'Kept', '/books/kept.epub', 'epub', 1, 1);
+             INSERT INTO sections (id, doc_id, title, order_index, href)
+             VALUES ('kept', 'doc', 'One', 0, ''), ('orphan', 'deleted', 'One', 0, '');
+             INSERT INTO paragraphs (id, doc_id, section_id, order_index, text, location)
+             VALUES ('kept', 'doc', 'kept', 0, 'Kept', 'p0'),
+                    ('orphan', 'doc', 'orphan', 0, 'Orphan', 'p0');
+             INSERT INTO annotations (id, paragraph_id, selected_text, style, created_at, updated_at)
+             VALUES ('orphan', 'orphan', 'Orphan', 'highlight', 1, 1);
+             INSERT INTO feeds (id, url, title, created_at, updated_at)
+             VALUES ('feed', 'https://example.com/feed', 'Feed', 1, 1);
+             INSERT INTO feed_entries (id, feed_id, guid, doc_id, imported_at)
+             VALUES ('entry', 'feed', 'guid', 'deleted', 1);",
+        )
+        .unwrap();
+
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        run_migrations(&conn, MIGRATIONS, None).unwrap();
+
+        let ids = |table: &str| -> Vec<String> {
+            conn.prepare(&format!("SELECT id FROM {} ORDER BY id", table))
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(ids("sections"), vec!["kept"]);
+        assert_eq!(ids("paragraphs"), vec!["kept"]);
+        assert!(ids("annotations").is_empty());
+        // The entry is still remembered as seen
+        let entry_doc: Option<String> = conn
+            .query_row(
+                "SELECT doc_id FROM feed_entries WHERE id = 'entry'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(entry_doc, None);
+        let violations: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(violations, 0);
+    }
+}
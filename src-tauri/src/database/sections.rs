@@ -23,11 +23,11 @@ pub fn insert(
 ) -> Result<Section, SectionError> {
     let id = Uuid::new_v4().to_string();
 
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO sections (id, doc_id, title, order_index, href)
          VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![&id, doc_id, title, order_index, href],
-    )?;
+    )?
+    .execute(params![&id, doc_id, title, order_index, href])?;
 
     Ok(Section {
         id,
@@ -42,7 +42,7 @@ pub fn insert(
 ///
 /// Returns sections ordered by order_index in ascending order.
 pub fn list_by_document(conn: &Connection, doc_id: &str) -> Result<Vec<Section>, SectionError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, doc_id, title, order_index, href
          FROM sections
          WHERE doc_id = ?1
@@ -68,7 +68,7 @@ pub fn list_by_document(conn: &Connection, doc_id: &str) -> Result<Vec<Section>,
 ///
 /// Returns None if the section doesn't exist.
 pub fn get(conn: &Connection, id: &str) -> Result<Option<Section>, SectionError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, doc_id, title, order_index, href
          FROM sections
          WHERE id = ?1",
@@ -88,3 +88,44 @@ pub fn get(conn: &Connection, id: &str) -> Result<Option<Section>, SectionError>
 
     Ok(sections.into_iter().next())
 }
+
+/// Moves a document's sections to negative order indexes
+///
+/// Used while re-importing: the new version's sections are inserted next to
+/// the stashed ones, and [`delete_stashed`] removes the old ones afterwards.
+pub fn stash_for_document(conn: &Connection, doc_id: &str) -> Result<usize, SectionError> {
+    let stashed = conn.execute(
+        "UPDATE sections SET order_index = -order_index - 1 WHERE doc_id = ?1 AND order_index >= 0",
+        params![doc_id],
+    )?;
+    Ok(stashed)
+}
+
+/// Deletes stashed sections along with their paragraphs and everything attached to them
+///
+/// Rows are removed explicitly rather than through `ON DELETE CASCADE`, which
+/// only fires on connections with foreign keys enabled.
+pub fn delete_stashed(conn: &Connection, doc_id: &str) -> Result<(), SectionError> {
+    let stashed_paragraphs = "SELECT p.id FROM paragraphs p
+         JOIN sections s ON s.id = p.section_id
+         WHERE s.doc_id = ?1 AND s.order_index < 0";
+    for sql in [
+        format!("DELETE FROM annotations WHERE paragraph_id IN ({stashed_paragraphs})"),
+        format!("DELETE FROM bookmarks WHERE paragraph_id IN ({stashed_paragraphs})"),
+        format!("DELETE FROM cache_translations WHERE paragraph_id IN ({stashed_paragraphs})"),
+        format!("DELETE FROM embeddings WHERE paragraph_id IN ({stashed_paragraphs})"),
+        format!(
+            "DELETE FROM paragraph_links WHERE source_paragraph_id IN ({stashed_paragraphs})
+             OR target_paragraph_id IN ({stashed_paragraphs})"
+        ),
+        format!("DELETE FROM cache_summaries WHERE target_id IN ({stashed_paragraphs})"),
+        "DELETE FROM cache_summaries WHERE target_id IN
+         (SELECT id FROM sections WHERE doc_id = ?1 AND order_index < 0)"
+            .to_string(),
+        format!("DELETE FROM paragraphs WHERE id IN ({stashed_paragraphs})"),
+        "DELETE FROM sections WHERE doc_id = ?1 AND order_index < 0".to_string(),
+    ] {
+        conn.execute(&sql, params![doc_id])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use crate::models::Tag;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum TagError {
+    #[error("Tag not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+/// Returns the id of the tag named `name`, creating the tag if needed
+///
+/// Tag names are matched case-insensitively.
+pub fn get_or_create(conn: &Connection, name: &str) -> Result<String, TagError> {
+    let existing = conn
+        .query_row(
+            "SELECT id FROM tags WHERE name = ?1",
+            params![name],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
+        params![&id, name, Utc::now().timestamp()],
+    )?;
+    Ok(id)
+}
+
+/// Adds tags to a document, creating missing tags; blank names are ignored
+pub fn add_to_document(conn: &Connection, doc_id: &str, names: &[String]) -> Result<(), TagError> {
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let tag_id = get_or_create(conn, name)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO document_tags (doc_id, tag_id) VALUES (?1, ?2)",
+            params![doc_id, &tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Replaces a document's tags with `names`
+pub fn set_for_document(conn: &Connection, doc_id: &str, names: &[String]) -> Result<(), TagError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM document_tags WHERE doc_id = ?1",
+        params![doc_id],
+    )?;
+    add_to_document(&tx, doc_id, names)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Lists all tags by name, with how many documents carry each
+pub fn list(conn: &Connection) -> Result<Vec<Tag>, TagError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.created_at, COUNT(dt.doc_id)
+         FROM tags t
+         LEFT JOIN document_tags dt ON dt.tag_id = t.id
+         GROUP BY t.id
+         ORDER BY t.name",
+    )?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                created_at: row.get(2)?,
+                document_count: row.get::<_, i64>(3)? as usize,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+/// Renames a tag, merging it into the tag that already has the new name
+///
+/// Returns the id of the renamed or merged tag.
+pub fn rename(conn: &Connection, id: &str, name: &str) -> Result<String, TagError> {
+    let tx = conn.unchecked_transaction()?;
+    let existing = tx
+        .query_row(
+            "SELECT id FROM tags WHERE name = ?1",
+            params![name],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    match existing {
+        // Only the case changes
+        Some(existing) if existing == id => {}
+        Some(existing) => {
+            tx.execute(
+                "INSERT OR IGNORE INTO document_tags (doc_id, tag_id)
+                 SELECT doc_id, ?1 FROM document_tags WHERE tag_id = ?2",
+                params![&existing, id],
+            )?;
+            delete(&tx, id)?;
+            tx.commit()?;
+            return Ok(existing);
+        }
+        None => {}
+    }
+
+    let rows_affected = tx.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+    if rows_affected == 0 {
+        return Err(TagError::NotFound);
+    }
+    tx.commit()?;
+    Ok(id.to_string())
+}
+
+/// Deletes a tag and removes it from all documents
+pub fn delete(conn: &Connection, id: &str) -> Result<(), TagError> {
+    conn.execute("DELETE FROM document_tags WHERE tag_id = ?1", params![id])?;
+    let rows_affected = conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+
+    if rows_affected == 0 {
+        return Err(TagError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_to_document, list, rename, set_for_document};
+    use crate::database;
+
+    #[test]
+    fn renaming_onto_existing_tag_merges_them() {
+        let conn = database::open_in_memory();
+        let dune = database::insert_test_document(&conn, "dune");
+        let ubik = database::insert_test_document(&conn, "ubik");
+        add_to_document(&conn, &dune, &["sf".to_string(), "Classics".to_string()]).unwrap();
+        add_to_document(&conn, &ubik, &["Science Fiction".to_string()]).unwrap();
+        let tags = list(&conn).unwrap();
+        let sf = tags.iter().find(|tag| tag.name == "sf").unwrap();
+        let science_fiction = tags
+            .iter()
+            .find(|tag| tag.name == "Science Fiction")
+            .unwrap();
+
+        let merged = rename(&conn, &sf.id, "science fiction").unwrap();
+
+        assert_eq!(merged, science_fiction.id);
+        let tags = list(&conn).unwrap();
+        let names: Vec<(&str, usize)> = tags
+            .iter()
+            .map(|tag| (tag.name.as_str(), tag.document_count))
+            .collect();
+        assert_eq!(names, [("Classics", 1), ("Science Fiction", 2)]);
+    }
+
+    #[test]
+    fn renaming_only_the_case_keeps_the_tag() {
+        let conn = database::open_in_memory();
+        let dune = database::insert_test_document(&conn, "dune");
+        add_to_document(&conn, &dune, &["classics".to_string()]).unwrap();
+        let id = list(&conn).unwrap()[0].id.clone();
+
+        assert_eq!(rename(&conn, &id, "Classics").unwrap(), id);
+        assert_eq!(list(&conn).unwrap()[0].name, "Classics");
+    }
+
+    #[test]
+    fn setting_tags_replaces_the_previous_ones() {
+        let conn = database::open_in_memory();
+        let dune = database::insert_test_document(&conn, "dune");
+        add_to_document(&conn, &dune, &["classics".to_string()]).unwrap();
+
+        set_for_document(&conn, &dune, &["sf".to_string(), " ".to_string()]).unwrap();
+
+        let doc = database::get_document(&conn, &dune).unwrap().unwrap();
+        assert_eq!(doc.tags, ["sf"]);
+    }
+}
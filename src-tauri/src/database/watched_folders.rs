@@ -0,0 +1,122 @@
+use crate::models::WatchedFolder;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum WatchedFolderError {
+    #[error("Watched folder not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+}
+
+/// Size and modification time of a file when it was last imported
+#[derive(Debug, Clone)]
+pub struct ImportedFile {
+    pub doc_id: String,
+    pub size: i64,
+    pub modified_at: i64,
+}
+
+fn folder_from_row(row: &Row) -> Result<WatchedFolder> {
+    Ok(WatchedFolder {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        recursive: row.get(2)?,
+        last_scanned_at: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// Adds a watched folder, or returns the existing one for the same path
+pub fn insert(
+    conn: &Connection,
+    path: &str,
+    recursive: bool,
+) -> Result<WatchedFolder, WatchedFolderError> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT OR IGNORE INTO watched_folders (id, path, recursive, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![&id, path, recursive, now],
+    )?;
+
+    let folder = conn.query_row(
+        "SELECT id, path, recursive, last_scanned_at, created_at
+         FROM watched_folders WHERE path = ?1",
+        params![path],
+        folder_from_row,
+    )?;
+    Ok(folder)
+}
+
+/// Lists all watched folders ordered by path
+pub fn list(conn: &Connection) -> Result<Vec<WatchedFolder>, WatchedFolderError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, path, recursive, last_scanned_at, created_at
+         FROM watched_folders ORDER BY path",
+    )?;
+    let folders = stmt
+        .query_map([], folder_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(folders)
+}
+
+/// Stops watching a folder; documents imported from it are kept
+pub fn delete(conn: &Connection, id: &str) -> Result<(), WatchedFolderError> {
+    let rows_affected = conn.execute("DELETE FROM watched_folders WHERE id = ?1", params![id])?;
+
+    if rows_affected == 0 {
+        return Err(WatchedFolderError::NotFound);
+    }
+
+    Ok(())
+}
+
+pub fn mark_scanned(conn: &Connection, id: &str) -> Result<(), WatchedFolderError> {
+    conn.execute(
+        "UPDATE watched_folders SET last_scanned_at = ?1 WHERE id = ?2",
+        params![Utc::now().timestamp(), id],
+    )?;
+    Ok(())
+}
+
+/// The import record of a file, if it was imported by a folder scan
+pub fn get_imported_file(
+    conn: &Connection,
+    file_path: &str,
+) -> Result<Option<ImportedFile>, WatchedFolderError> {
+    let file = conn
+        .query_row(
+            "SELECT doc_id, size, modified_at FROM imported_files WHERE file_path = ?1",
+            params![file_path],
+            |row| {
+                Ok(ImportedFile {
+                    doc_id: row.get(0)?,
+                    size: row.get(1)?,
+                    modified_at: row.get(2)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(file)
+}
+
+/// Records the document a file was imported as, replacing any older record
+pub fn record_imported_file(
+    conn: &Connection,
+    file_path: &str,
+    doc_id: &str,
+    size: i64,
+    modified_at: i64,
+) -> Result<(), WatchedFolderError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO imported_files (file_path, doc_id, size, modified_at, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![file_path, doc_id, size, modified_at, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
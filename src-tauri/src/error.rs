@@ -14,9 +14,36 @@ pub enum ReaderError {
     #[error("PDF parsing error: {0}")]
     PdfParse(String),
 
+    #[error("DOCX parsing error: {0}")]
+    DocxParse(String),
+
+    #[error("HTML parsing error: {0}")]
+    HtmlParse(String),
+
+    #[error("FB2 parsing error: {0}")]
+    Fb2Parse(String),
+
+    #[error("MOBI parsing error: {0}")]
+    MobiParse(String),
+
+    #[error("DRM-protected book: {0}")]
+    DrmProtected(String),
+
+    #[error("Notebook parsing error: {0}")]
+    NotebookParse(String),
+
+    #[error("Highlight import error: {0}")]
+    HighlightParse(String),
+
+    #[error("Feed parsing error: {0}")]
+    FeedParse(String),
+
     #[error("Model API error: {0}")]
     ModelApi(String),
 
+    #[error("Already in the library: {0}")]
+    DuplicateDocument(String),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
@@ -0,0 +1,153 @@
+//! Lossless JSON archive of annotations and bookmarks that can be imported back
+
+use super::{DocumentNotes, Note};
+use crate::models::{Annotation, Bookmark, Document};
+use serde::{Deserialize, Serialize};
+
+/// Value of [`NotesArchive::format`], to recognise the reader's archives
+pub const ARCHIVE_FORMAT: &str = "reader-notes";
+/// Version of the archive layout, bumped on incompatible changes
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotesArchive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    pub documents: Vec<ArchivedDocument>,
+}
+
+/// A document with its notes, in reading order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedDocument {
+    pub document: Document,
+    #[serde(default)]
+    pub annotations: Vec<ArchivedAnnotation>,
+    #[serde(default)]
+    pub bookmarks: Vec<ArchivedBookmark>,
+}
+
+/// An annotation with where it was, to place it again if paragraph ids changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAnnotation {
+    #[serde(flatten)]
+    pub annotation: Annotation,
+    #[serde(default)]
+    pub section_title: String,
+    #[serde(default)]
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBookmark {
+    #[serde(flatten)]
+    pub bookmark: Bookmark,
+    #[serde(default)]
+    pub section_title: String,
+    #[serde(default)]
+    pub location: String,
+}
+
+impl NotesArchive {
+    pub fn new(documents: &[DocumentNotes], exported_at: i64) -> Self {
+        Self {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            exported_at,
+            documents: documents.iter().map(ArchivedDocument::from).collect(),
+        }
+    }
+
+    /// Whether the archive was written by a reader this version can read
+    pub fn is_supported(&self) -> bool {
+        self.format == ARCHIVE_FORMAT && self.version <= ARCHIVE_VERSION
+    }
+}
+
+impl From<&DocumentNotes> for ArchivedDocument {
+    fn from(notes: &DocumentNotes) -> Self {
+        let mut annotations = Vec::new();
+        let mut bookmarks = Vec::new();
+        for section in &notes.sections {
+            for entry in &section.entries {
+                let section_title = section.title.clone();
+                let location = entry.location.clone();
+                match &entry.note {
+                    Note::Annotation(annotation) => annotations.push(ArchivedAnnotation {
+                        annotation: annotation.clone(),
+                        section_title,
+                        location,
+                    }),
+                    Note::Bookmark(bookmark) => bookmarks.push(ArchivedBookmark {
+                        bookmark: bookmark.clone(),
+                        section_title,
+                        location,
+                    }),
+                }
+            }
+        }
+        Self {
+            document: notes.document.clone(),
+            annotations,
+            bookmarks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotesArchive;
+
+    #[test]
+    fn archives_round_trip_without_loss() {
+        let text = r#"{
+            "format": "reader-notes",
+            "version": 1,
+            "exported_at": 1700000000,
+            "documents": [{
+                "document": {
+                    "id": "doc-1", "title": "Book", "author": null, "language": "en",
+                    "file_path": "/books/book.epub", "file_type": "epub",
+                    "content_hash": "abc", "original_path": null,
+                    "series": null, "series_index": null,
+                    "tags": ["fiction"], "collection_ids": [],
+                    "subtitle": null, "publisher": null, "published_date": "2019",
+                    "isbn": "9780306406157", "doi": null, "description": null, "rating": 4,
+                    "created_at": 1, "updated_at": 2
+                },
+                "annotations": [{
+                    "id": "a1", "paragraph_id": "p1", "selected_text": "cat",
+                    "style": "highlight", "color": "green", "note": "Note",
+                    "tags": ["animals"],
+                    "anchor": {"start": 4, "end": 7, "prefix": "The ", "suffix": " sat."},
+                    "created_at": 3, "updated_at": 4,
+                    "section_title": "Chapter 1", "location": "p. 2"
+                }],
+                "bookmarks": [{
+                    "id": "b1", "doc_id": "doc-1", "paragraph_id": "p0", "title": "Start",
+                    "note": null, "color": "red", "created_at": 5, "updated_at": 6,
+                    "section_title": "Chapter 1", "location": "p. 1"
+                }]
+            }]
+        }"#;
+        let archive: NotesArchive = serde_json::from_str(text).unwrap();
+        assert!(archive.is_supported());
+        let document = &archive.documents[0];
+        assert_eq!(document.document.metadata.rating, Some(4));
+        let annotation = &document.annotations[0];
+        assert_eq!(
+            annotation.annotation.anchor.as_ref().map(|a| a.end),
+            Some(7)
+        );
+        assert_eq!(annotation.location, "p. 2");
+        assert_eq!(document.bookmarks[0].bookmark.title, "Start");
+
+        let written = serde_json::to_value(&archive).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(written, expected);
+
+        let mut future = archive;
+        future.version += 1;
+        assert!(!future.is_supported());
+    }
+}
@@ -0,0 +1,442 @@
+//! Markdown notes, as one file or as Obsidian notes with YAML front matter
+//!
+//! An Obsidian note keeps the exported notes between two HTML comments. When
+//! a book is exported again into the same vault, only that block and the
+//! front matter keys written by the reader are replaced, so properties and
+//! text the user added to the note survive. Tags added in Obsidian are kept
+//! alongside the document's tags.
+
+use super::{deep_link, DocumentNotes, Note, NoteEntry};
+use chrono::{TimeZone, Utc};
+
+const BLOCK_BEGIN: &str = "<!-- reader:begin -->";
+const BLOCK_END: &str = "<!-- reader:end -->";
+/// Front matter key holding the document id, used to find the note again
+const ID_KEY: &str = "reader_id";
+/// Front matter key holding the document's tags and any the user added
+const TAGS_KEY: &str = "tags";
+/// Front matter keys written by the reader; any others belong to the user
+const OWNED_KEYS: &[&str] = &[
+    ID_KEY,
+    "title",
+    "subtitle",
+    "author",
+    "publisher",
+    "published",
+    "isbn",
+    "doi",
+    "series",
+    TAGS_KEY,
+    "annotations",
+    "bookmarks",
+    "exported",
+];
+/// Longest file name stem for a note
+const MAX_FILE_STEM_CHARS: usize = 120;
+
+/// Renders the notes of several documents as one Markdown file
+pub fn render_markdown(documents: &[DocumentNotes]) -> String {
+    let mut out = String::new();
+    for notes in documents {
+        if !out.is_empty() {
+            out.push_str("\n---\n\n");
+        }
+        write_document(&mut out, notes);
+    }
+    out
+}
+
+/// Renders a document's Obsidian note, merged into the note's current text
+pub fn render_obsidian_note(
+    notes: &DocumentNotes,
+    existing: Option<&str>,
+    exported_at: i64,
+) -> String {
+    // Notes saved on Windows use CRLF line endings
+    let existing = existing.map(|text| text.replace("\r\n", "\n"));
+    let mut user_tags = Vec::new();
+    let (user_properties, before, after) = match existing.as_deref() {
+        Some(text) => {
+            let (front_matter, body) = split_front_matter(text);
+            let mut user_properties = Vec::new();
+            for (key, entry) in front_matter.map(properties).unwrap_or_default() {
+                if key == TAGS_KEY {
+                    user_tags = list_values(entry);
+                } else if !OWNED_KEYS.contains(&key) {
+                    user_properties.push(entry);
+                }
+            }
+            match (body.find(BLOCK_BEGIN), body.find(BLOCK_END)) {
+                (Some(begin), Some(end)) if begin < end => (
+                    user_properties,
+                    &body[..begin],
+                    &body[end + BLOCK_END.len()..],
+                ),
+                // Markers removed by hand: keep the whole body below the notes
+                _ => (user_properties, "", body),
+            }
+        }
+        None => (Vec::new(), "", "\n"),
+    };
+
+    let mut out = String::from("---\n");
+    out.push_str(&front_matter(notes, &user_tags, exported_at));
+    for entry in user_properties {
+        out.push_str(entry);
+    }
+    out.push_str("---\n");
+    out.push_str(before);
+    out.push_str(BLOCK_BEGIN);
+    out.push('\n');
+    write_document(&mut out, notes);
+    out.push_str(BLOCK_END);
+    if !after.starts_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(after);
+    out
+}
+
+/// The document id recorded in an Obsidian note's front matter
+pub fn obsidian_note_id(text: &str) -> Option<String> {
+    let text = text.replace("\r\n", "\n");
+    let (front_matter, _) = split_front_matter(&text);
+    let (_, entry) = properties(front_matter?)
+        .into_iter()
+        .find(|(key, _)| *key == ID_KEY)?;
+    scalar(entry.split_once(':')?.1)
+}
+
+/// A file name for a book's note that is valid on every platform
+pub fn obsidian_file_name(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let stem: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches('.')
+        .chars()
+        .take(MAX_FILE_STEM_CHARS)
+        .collect();
+    let stem = stem.trim();
+    format!("{}.md", if stem.is_empty() { "Untitled" } else { stem })
+}
+
+/// Writes the reader's front matter keys; `user_tags` are added after the document's tags
+fn front_matter(notes: &DocumentNotes, user_tags: &[String], exported_at: i64) -> String {
+    let document = &notes.document;
+    let metadata = &document.metadata;
+    let mut out = String::new();
+    let mut text = |key: &str, value: Option<&str>| {
+        if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+            // JSON strings are valid YAML scalars and escape everything needed
+            out.push_str(&format!("{}: {}\n", key, serde_json::json!(value)));
+        }
+    };
+    text(ID_KEY, Some(&document.id));
+    text("title", Some(&document.title));
+    text("subtitle", metadata.subtitle.as_deref());
+    text("author", document.author.as_deref());
+    text("publisher", metadata.publisher.as_deref());
+    text("published", metadata.published_date.as_deref());
+    text("isbn", metadata.isbn.as_deref());
+    text("doi", metadata.doi.as_deref());
+    text("series", document.series.as_deref());
+    let mut tags: Vec<String> = document.tags.iter().map(|tag| tag_name(tag)).collect();
+    for tag in user_tags {
+        let tag = tag_name(tag.trim_start_matches('#'));
+        if !tag.is_empty() && !tags.iter().any(|known| known.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+    if !tags.is_empty() {
+        out.push_str(&format!("{}: {}\n", TAGS_KEY, serde_json::json!(tags)));
+    }
+    out.push_str(&format!("annotations: {}\n", notes.annotation_count()));
+    out.push_str(&format!("bookmarks: {}\n", notes.bookmark_count()));
+    if let Some(date) = Utc.timestamp_opt(exported_at, 0).single() {
+        out.push_str(&format!("exported: {}\n", date.format("%Y-%m-%d")));
+    }
+    out
+}
+
+/// Splits off the YAML front matter, returning it without its `---` fences
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return (None, text);
+    };
+    if let Some(body) = rest.strip_prefix("---\n") {
+        return (Some(""), body);
+    }
+    match rest.find("\n---\n") {
+        Some(end) => (Some(&rest[..end + 1]), &rest[end + 5..]),
+        None => match rest.strip_suffix("\n---") {
+            Some(front_matter) => (Some(front_matter), ""),
+            None => (None, text),
+        },
+    }
+}
+
+/// Top-level front matter entries as (key, full text including nested lines)
+fn properties(front_matter: &str) -> Vec<(&str, &str)> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    for line in front_matter.split_inclusive('\n') {
+        let nested = line.starts_with([' ', '\t', '-']) || line.trim().is_empty();
+        if !nested || starts.is_empty() {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(idx, &start)| {
+            let end = starts.get(idx + 1).copied().unwrap_or(front_matter.len());
+            let entry = &front_matter[start..end];
+            let key = entry.split_once(':').map_or("", |(key, _)| key.trim());
+            (key, entry)
+        })
+        .collect()
+}
+
+/// A YAML scalar, unquoted
+fn scalar(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.starts_with('"') {
+        serde_json::from_str(value).ok()
+    } else {
+        Some(value.trim_matches('\'').to_string())
+    }
+}
+
+/// The items of a front matter list, written inline (`[a, b]`), as `- a`
+/// lines or as one comma-separated value
+fn list_values(entry: &str) -> Vec<String> {
+    let mut lines = entry.lines();
+    let value = lines
+        .next()
+        .and_then(|line| line.split_once(':'))
+        .map_or("", |(_, value)| value.trim());
+    let items: Vec<&str> = if value.is_empty() {
+        lines
+            .filter_map(|line| line.trim().strip_prefix('-'))
+            .collect()
+    } else {
+        value
+            .strip_prefix('[')
+            .and_then(|value| value.strip_suffix(']'))
+            .unwrap_or(value)
+            .split(',')
+            .collect()
+    };
+    items
+        .into_iter()
+        .filter_map(scalar)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn write_document(out: &mut String, notes: &DocumentNotes) {
+    let document = &notes.document;
+    out.push_str(&format!("# {}\n\n", document.title));
+    if let Some(subtitle) = document.metadata.subtitle.as_deref() {
+        out.push_str(&format!("*{}*\n\n", subtitle));
+    }
+    if let Some(author) = document.author.as_deref() {
+        out.push_str(&format!("By {}\n\n", author));
+    }
+    if notes.is_empty() {
+        out.push_str("_No annotations or bookmarks yet._\n\n");
+    }
+    for section in &notes.sections {
+        let title = if section.title.trim().is_empty() {
+            "Untitled section"
+        } else {
+            section.title.trim()
+        };
+        out.push_str(&format!("## {}\n\n", title));
+        for entry in &section.entries {
+            write_entry(out, &document.id, &section.id, entry);
+        }
+    }
+}
+
+fn write_entry(out: &mut String, doc_id: &str, section_id: &str, entry: &NoteEntry) {
+    let link = format!(
+        "[{}]({})",
+        if entry.location.trim().is_empty() {
+            "Open"
+        } else {
+            entry.location.trim()
+        },
+        deep_link(doc_id, section_id, &entry.paragraph_id)
+    );
+    match &entry.note {
+        Note::Annotation(annotation) => {
+            for line in annotation.selected_text.lines() {
+                out.push_str(&format!("> {}\n", line));
+            }
+            out.push('\n');
+            if let Some(note) = annotation.note.as_deref() {
+                out.push_str(note.trim());
+                out.push_str("\n\n");
+            }
+            let tags: Vec<String> = annotation
+                .tags
+                .iter()
+                .map(|tag| format!("#{}", tag_name(tag)))
+                .collect();
+            if tags.is_empty() {
+                out.push_str(&format!("— {}\n\n", link));
+            } else {
+                out.push_str(&format!("— {} · {}\n\n", link, tags.join(" ")));
+            }
+        }
+        Note::Bookmark(bookmark) => {
+            out.push_str(&format!("🔖 **{}** — {}\n", bookmark.title, link));
+            if let Some(note) = bookmark.note.as_deref() {
+                out.push('\n');
+                out.push_str(note.trim());
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+    }
+}
+
+/// A tag as Obsidian accepts it: no spaces
+fn tag_name(tag: &str) -> String {
+    tag.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{obsidian_file_name, obsidian_note_id, render_markdown, render_obsidian_note};
+    use crate::export::{DocumentNotes, Note, NoteEntry, SectionNotes};
+    use crate::models::{Annotation, Bookmark, Document};
+
+    fn sample() -> DocumentNotes {
+        let document = Document {
+            id: "doc-1".to_string(),
+            title: "On \"Quotes\": A Study".to_string(),
+            author: Some("Someone".to_string()),
+            language: None,
+            file_path: "/books/quotes.epub".to_string(),
+            file_type: "epub".to_string(),
+            content_hash: None,
+            original_path: None,
+            series: None,
+            series_index: None,
+            tags: vec!["to read".to_string()],
+            collection_ids: Vec::new(),
+            metadata: Default::default(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        let annotation = Annotation {
+            id: "a1".to_string(),
+            paragraph_id: "p1".to_string(),
+            selected_text: "first line\nsecond line".to_string(),
+            style: "highlight".to_string(),
+            color: Some("yellow".to_string()),
+            note: Some("Worth rereading".to_string()),
+            tags: vec!["key idea".to_string()],
+            anchor: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let bookmark = Bookmark {
+            id: "b1".to_string(),
+            doc_id: "doc-1".to_string(),
+            paragraph_id: "p0".to_string(),
+            title: "Start here".to_string(),
+            note: None,
+            color: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        DocumentNotes {
+            document,
+            sections: vec![SectionNotes {
+                id: "s1".to_string(),
+                title: "Chapter 1".to_string(),
+                entries: vec![
+                    NoteEntry {
+                        paragraph_id: "p0".to_string(),
+                        location: "p. 3".to_string(),
+                        note: Note::Bookmark(bookmark),
+                    },
+                    NoteEntry {
+                        paragraph_id: "p1".to_string(),
+                        location: "p. 4".to_string(),
+                        note: Note::Annotation(annotation),
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn renders_notes_and_updates_obsidian_notes_in_place() {
+        let notes = sample();
+        let markdown = render_markdown(std::slice::from_ref(&notes));
+        assert!(markdown.starts_with("# On \"Quotes\": A Study\n\nBy Someone\n\n## Chapter 1\n\n"));
+        assert!(markdown.contains(
+            "🔖 **Start here** — [p. 3](com.mac.reader://open?doc=doc-1&section=s1&paragraph=p0)"
+        ));
+        assert!(markdown.contains("> first line\n> second line\n\nWorth rereading\n\n"));
+        assert!(markdown.contains("paragraph=p1) · #key-idea"));
+
+        let first = render_obsidian_note(&notes, None, 0);
+        assert!(
+            first.starts_with("---\nreader_id: \"doc-1\"\ntitle: \"On \\\"Quotes\\\": A Study\"\n")
+        );
+        assert!(first.contains(
+            "tags: [\"to-read\"]\nannotations: 1\nbookmarks: 1\nexported: 1970-01-01\n---\n"
+        ));
+        assert_eq!(obsidian_note_id(&first).as_deref(), Some("doc-1"));
+
+        // The user adds a property and their own text, then the book is exported again
+        let edited = first
+            .replacen("---\n", "---\nrating: 5\naliases:\n  - Quotes\n", 1)
+            .replace("Worth rereading", "stale")
+            + "\nMy own thoughts.\n";
+        let mut renamed = notes.clone();
+        renamed.document.title = "Quotes".to_string();
+        let second = render_obsidian_note(&renamed, Some(&edited), 0);
+        assert!(second.contains("title: \"Quotes\"\n"));
+        assert!(second.contains("rating: 5\naliases:\n  - Quotes\n---\n"));
+        assert!(second.contains("Worth rereading") && !second.contains("stale"));
+        assert!(second.ends_with("<!-- reader:end -->\n\nMy own thoughts.\n"));
+        assert_eq!(second.matches("<!-- reader:begin -->").count(), 1);
+        assert_eq!(render_obsidian_note(&renamed, Some(&second), 0), second);
+
+        // Tags added in Obsidian, inline or as a list, are kept
+        let tagged = second.replace("tags: [\"to-read\"]\n", "tags: [\"to-read\", fiction]\n");
+        let third = render_obsidian_note(&renamed, Some(&tagged), 0);
+        assert!(third.contains("tags: [\"to-read\",\"fiction\"]\n"));
+        let listed = second.replace("tags: [\"to-read\"]\n", "tags:\n  - To-Read\n  - \"#sf\"\n");
+        assert!(render_obsidian_note(&renamed, Some(&listed), 0)
+            .contains("tags: [\"to-read\",\"sf\"]\n"));
+
+        // A note saved with CRLF line endings is updated, not duplicated
+        let crlf = second.replace('\n', "\r\n");
+        assert_eq!(obsidian_note_id(&crlf).as_deref(), Some("doc-1"));
+        assert_eq!(render_obsidian_note(&renamed, Some(&crlf), 0), second);
+
+        assert_eq!(
+            obsidian_file_name("On \"Quotes\": A/B?"),
+            "On Quotes A B.md"
+        );
+        assert_eq!(obsidian_file_name(" ... "), "Untitled.md");
+        assert_eq!(obsidian_note_id("# No front matter"), None);
+    }
+}
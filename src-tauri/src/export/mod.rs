@@ -0,0 +1,206 @@
+//! Exporting annotations and bookmarks to Markdown, an Obsidian vault, JSON or
+//! W3C Web Annotations
+
+mod json;
+mod markdown;
+mod web_annotation;
+
+pub use json::{ArchivedAnnotation, ArchivedBookmark, ArchivedDocument, NotesArchive};
+pub use markdown::{obsidian_file_name, obsidian_note_id, render_markdown, render_obsidian_note};
+pub use web_annotation::{
+    annotation_page, id_from_iri, parse_web_annotations, uuid_iri, web_annotation, AnnotationPage,
+    ImportedWebAnnotation, WebAnnotation,
+};
+
+use crate::database;
+use crate::error::Result;
+use crate::models::{Annotation, Bookmark, Document};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// URL scheme the reader registers for links back into a document
+///
+/// The bundle identifier, so it can't clash with another app's scheme.
+pub const DEEP_LINK_SCHEME: &str = "com.mac.reader";
+/// Event sent to the window when a deep link is opened
+pub const DEEP_LINK_EVENT: &str = "deep-link-open";
+
+/// Link that opens the reader at a paragraph
+pub fn deep_link(doc_id: &str, section_id: &str, paragraph_id: &str) -> String {
+    format!(
+        "{}://open?doc={}&section={}&paragraph={}",
+        DEEP_LINK_SCHEME, doc_id, section_id, paragraph_id
+    )
+}
+
+/// Where a deep link points
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DeepLinkTarget {
+    pub doc_id: String,
+    pub section_id: Option<String>,
+    pub paragraph_id: Option<String>,
+}
+
+/// Reads a link made by [`deep_link`]; the section and paragraph may be left out
+pub fn parse_deep_link(link: &str) -> Option<DeepLinkTarget> {
+    let url = url::Url::parse(link).ok()?;
+    if url.scheme() != DEEP_LINK_SCHEME || url.host_str() != Some("open") {
+        return None;
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, value)| key == name && !value.is_empty())
+            .map(|(_, value)| value.into_owned())
+    };
+    Some(DeepLinkTarget {
+        doc_id: param("doc")?,
+        section_id: param("section"),
+        paragraph_id: param("paragraph"),
+    })
+}
+
+/// A document's annotations and bookmarks in reading order
+#[derive(Debug, Clone)]
+pub struct DocumentNotes {
+    pub document: Document,
+    /// Sections holding at least one note, in document order
+    pub sections: Vec<SectionNotes>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SectionNotes {
+    pub id: String,
+    pub title: String,
+    pub entries: Vec<NoteEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NoteEntry {
+    pub paragraph_id: String,
+    /// Location of the paragraph, e.g. a page number
+    pub location: String,
+    pub note: Note,
+}
+
+#[derive(Debug, Clone)]
+pub enum Note {
+    Annotation(Annotation),
+    Bookmark(Bookmark),
+}
+
+impl DocumentNotes {
+    pub fn annotation_count(&self) -> usize {
+        self.entries()
+            .filter(|entry| matches!(entry.note, Note::Annotation(_)))
+            .count()
+    }
+
+    pub fn bookmark_count(&self) -> usize {
+        self.entries()
+            .filter(|entry| matches!(entry.note, Note::Bookmark(_)))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &NoteEntry> {
+        self.sections.iter().flat_map(|section| &section.entries)
+    }
+}
+
+/// Gathers a document's annotations and bookmarks, grouped by section
+pub fn collect(conn: &Connection, document: Document) -> Result<DocumentNotes> {
+    let sections = database::list_sections(conn, &document.id)?;
+    let paragraphs = database::list_paragraphs(conn, &document.id)?;
+    let positions: HashMap<&str, usize> = paragraphs
+        .iter()
+        .enumerate()
+        .map(|(idx, paragraph)| (paragraph.id.as_str(), idx))
+        .collect();
+
+    // Paragraph, then bookmarks before annotations, then position in the
+    // paragraph and creation time
+    let mut placed: Vec<((usize, u8, usize, i64), NoteEntry)> = Vec::new();
+    let mut place = |paragraph_id: String, offset: usize, created_at: i64, note: Note| {
+        if let Some(&idx) = positions.get(paragraph_id.as_str()) {
+            let rank = match note {
+                Note::Bookmark(_) => 0,
+                Note::Annotation(_) => 1,
+            };
+            let entry = NoteEntry {
+                paragraph_id,
+                location: paragraphs[idx].location.clone(),
+                note,
+            };
+            placed.push(((idx, rank, offset, created_at), entry));
+        }
+    };
+    for annotation in database::list_annotations_by_document(conn, &document.id)? {
+        let offset = annotation.anchor.as_ref().map_or(0, |anchor| anchor.start);
+        let created_at = annotation.created_at;
+        place(
+            annotation.paragraph_id.clone(),
+            offset,
+            created_at,
+            Note::Annotation(annotation),
+        );
+    }
+    for entry in database::list_bookmarks(conn, Some(&document.id))? {
+        let bookmark = entry.bookmark;
+        let created_at = bookmark.created_at;
+        place(
+            bookmark.paragraph_id.clone(),
+            0,
+            created_at,
+            Note::Bookmark(bookmark),
+        );
+    }
+    placed.sort_by_key(|(key, _)| *key);
+
+    let mut grouped: Vec<SectionNotes> = Vec::new();
+    for (_, entry) in placed {
+        let section_id = &paragraphs[positions[entry.paragraph_id.as_str()]].section_id;
+        match grouped.last_mut() {
+            Some(section) if section.id == *section_id => section.entries.push(entry),
+            _ => grouped.push(SectionNotes {
+                id: section_id.clone(),
+                title: sections
+                    .iter()
+                    .find(|section| section.id == *section_id)
+                    .map(|section| section.title.clone())
+                    .unwrap_or_default(),
+                entries: vec![entry],
+            }),
+        }
+    }
+
+    Ok(DocumentNotes {
+        document,
+        sections: grouped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deep_link, parse_deep_link, DeepLinkTarget};
+
+    #[test]
+    fn parses_deep_links() {
+        assert_eq!(
+            parse_deep_link(&deep_link("doc-1", "s1", "p1")),
+            Some(DeepLinkTarget {
+                doc_id: "doc-1".to_string(),
+                section_id: Some("s1".to_string()),
+                paragraph_id: Some("p1".to_string()),
+            })
+        );
+        let document = parse_deep_link("com.mac.reader://open?doc=doc-1&section=").unwrap();
+        assert_eq!((document.section_id, document.paragraph_id), (None, None));
+        assert_eq!(parse_deep_link("com.mac.reader://open?section=s1"), None);
+        assert_eq!(parse_deep_link("https://open?doc=doc-1"), None);
+        assert_eq!(parse_deep_link("reader://open?doc=doc-1"), None);
+        assert_eq!(parse_deep_link("not a link"), None);
+    }
+}
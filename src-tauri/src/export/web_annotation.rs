@@ -0,0 +1,542 @@
+//! W3C Web Annotation Data Model (JSON-LD) for interchange with other tools
+//!
+//! Each annotation targets its document, identified as `urn:uuid:<doc id>`,
+//! with two alternative selectors: a TextQuoteSelector that finds the text
+//! anywhere in the document, and a FragmentSelector naming the paragraph
+//! `location`, refined by a TextPositionSelector within that paragraph.
+//! Style and colour travel as the target's `styleClass`, the note and tags
+//! as textual bodies.
+
+use crate::models::{Annotation, TextAnchor};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// JSON-LD context of the Web Annotation vocabulary
+pub const ANNOTATION_CONTEXT: &str = "http://www.w3.org/ns/anno.jsonld";
+const URN_UUID: &str = "urn:uuid:";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebAnnotation {
+    /// Set on annotations that stand alone, left out inside a page
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "type", default = "annotation_type")]
+    pub kind: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motivation: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub body: Vec<Body>,
+    /// Shorthand for a single plain-text body
+    #[serde(rename = "bodyValue", default, skip_serializing_if = "Option::is_none")]
+    pub body_value: Option<String>,
+    #[serde(deserialize_with = "one_or_many", serialize_with = "single_or_many")]
+    pub target: Vec<Target>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Body {
+    Textual(TextualBody),
+    /// A body given by IRI only, e.g. a linked web page
+    External(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextualBody {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// `commenting` for the note, `tagging` for each tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Target {
+    Resource(SpecificResource),
+    /// The whole resource, without selectors
+    Iri(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpecificResource {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub source: String,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub selector: Vec<Selector>,
+    #[serde(
+        rename = "styleClass",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub style_class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Selector {
+    #[serde(rename = "TextQuoteSelector")]
+    TextQuote {
+        exact: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suffix: Option<String>,
+    },
+    /// Offsets in Unicode characters
+    #[serde(rename = "TextPositionSelector")]
+    TextPosition { start: usize, end: usize },
+    #[serde(rename = "FragmentSelector")]
+    Fragment {
+        value: String,
+        #[serde(
+            rename = "conformsTo",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        conforms_to: Option<String>,
+        #[serde(
+            rename = "refinedBy",
+            default,
+            deserialize_with = "one_or_many",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        refined_by: Vec<Selector>,
+    },
+    /// Selectors the reader can't use, e.g. XPath ranges
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationPage {
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "startIndex", default)]
+    pub start_index: usize,
+    pub items: Vec<WebAnnotation>,
+}
+
+/// What the reader takes from a Web Annotation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedWebAnnotation {
+    /// The reader's own annotation id, when the annotation came from a reader
+    pub id: Option<String>,
+    pub source: String,
+    /// Paragraph location named by a FragmentSelector
+    pub location: Option<String>,
+    pub exact: String,
+    /// Position and context within the paragraph, as far as known
+    pub anchor: Option<TextAnchor>,
+    /// `styleClass` split into class names
+    pub classes: Vec<String>,
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+}
+
+/// IRI of a reader document or annotation, e.g. a target's source
+pub fn uuid_iri(id: &str) -> String {
+    format!("{}{}", URN_UUID, id)
+}
+
+/// The reader id in a `urn:uuid:` IRI
+pub fn id_from_iri(iri: &str) -> Option<&str> {
+    iri.strip_prefix(URN_UUID).filter(|id| !id.is_empty())
+}
+
+/// An annotation on the paragraph at `location` of document `doc_id`
+pub fn web_annotation(annotation: &Annotation, doc_id: &str, location: &str) -> WebAnnotation {
+    let mut body: Vec<Body> = Vec::new();
+    if let Some(note) = annotation.note.as_deref() {
+        body.push(textual_body(note, "commenting", Some("text/plain")));
+    }
+    body.extend(
+        annotation
+            .tags
+            .iter()
+            .map(|tag| textual_body(tag, "tagging", None)),
+    );
+
+    let anchor = annotation.anchor.as_ref();
+    let non_empty = |text: &str| Some(text.to_string()).filter(|text| !text.is_empty());
+    let mut selector = vec![Selector::TextQuote {
+        exact: annotation.selected_text.clone(),
+        prefix: anchor.and_then(|anchor| non_empty(&anchor.prefix)),
+        suffix: anchor.and_then(|anchor| non_empty(&anchor.suffix)),
+    }];
+    if !location.is_empty() {
+        selector.push(Selector::Fragment {
+            value: location.to_string(),
+            conforms_to: None,
+            refined_by: anchor
+                .map(|anchor| Selector::TextPosition {
+                    start: anchor.start,
+                    end: anchor.end,
+                })
+                .into_iter()
+                .collect(),
+        });
+    }
+    let style_class = match annotation.color.as_deref() {
+        Some(color) => format!("{} {}", annotation.style, color),
+        None => annotation.style.clone(),
+    };
+
+    WebAnnotation {
+        context: None,
+        id: Some(uuid_iri(&annotation.id)),
+        kind: Value::from("Annotation"),
+        motivation: Some(Value::from(if annotation.note.is_some() {
+            "commenting"
+        } else {
+            "highlighting"
+        })),
+        created: timestamp(annotation.created_at),
+        modified: timestamp(annotation.updated_at),
+        body,
+        body_value: None,
+        target: vec![Target::Resource(SpecificResource {
+            kind: Some("SpecificResource".to_string()),
+            source: uuid_iri(doc_id),
+            selector,
+            style_class: Some(style_class),
+        })],
+    }
+}
+
+/// Collects annotations into a page, e.g. all of a document's
+pub fn annotation_page(items: Vec<WebAnnotation>) -> AnnotationPage {
+    AnnotationPage {
+        context: Some(Value::from(ANNOTATION_CONTEXT)),
+        id: None,
+        kind: "AnnotationPage".to_string(),
+        start_index: 0,
+        items: items
+            .into_iter()
+            .map(|item| WebAnnotation {
+                context: None,
+                ..item
+            })
+            .collect(),
+    }
+}
+
+/// Reads one annotation, an AnnotationPage, a collection with its first page
+/// embedded, or a plain array of annotations
+pub fn parse_web_annotations(text: &str) -> Result<Vec<WebAnnotation>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let items = match value {
+        Value::Array(items) => items,
+        value if has_type(&value, "Annotation") => vec![value],
+        value if has_type(&value, "AnnotationPage") => items_of(value),
+        mut value if has_type(&value, "AnnotationCollection") => match value["first"].take() {
+            page @ Value::Object(_) => items_of(page),
+            _ => return Err("Collection without an embedded first page".to_string()),
+        },
+        _ => return Err("Not a Web Annotation, AnnotationPage or collection".to_string()),
+    };
+    items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|e| e.to_string()))
+        .collect()
+}
+
+impl WebAnnotation {
+    /// Adds the JSON-LD context so the annotation can stand alone
+    pub fn standalone(self) -> Self {
+        Self {
+            context: Some(Value::from(ANNOTATION_CONTEXT)),
+            ..self
+        }
+    }
+
+    /// The parts the reader keeps, from the first target that quotes text
+    pub fn to_imported(&self) -> Option<ImportedWebAnnotation> {
+        self.target.iter().find_map(|target| {
+            let Target::Resource(resource) = target else {
+                return None;
+            };
+            let mut imported = ImportedWebAnnotation {
+                id: self.id.as_deref().and_then(id_from_iri).map(str::to_string),
+                source: resource.source.clone(),
+                classes: resource
+                    .style_class
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+                created_at: self.created.as_deref().and_then(parse_timestamp),
+                updated_at: self.modified.as_deref().and_then(parse_timestamp),
+                ..Default::default()
+            };
+            let mut quote = None;
+            let mut position = None;
+            for selector in &resource.selector {
+                match selector {
+                    Selector::TextQuote { .. } => quote = quote.or(Some(selector)),
+                    Selector::Fragment {
+                        value, refined_by, ..
+                    } => {
+                        imported.location = Some(value.clone());
+                        for refined in refined_by {
+                            match refined {
+                                Selector::TextPosition { start, end } => {
+                                    position = Some((*start, *end))
+                                }
+                                Selector::TextQuote { .. } => quote = quote.or(Some(refined)),
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let Some(Selector::TextQuote {
+                exact,
+                prefix,
+                suffix,
+            }) = quote
+            else {
+                return None;
+            };
+            imported.exact = exact.clone();
+            let (start, end) = position.unwrap_or((0, 0));
+            if position.is_some() || prefix.is_some() || suffix.is_some() {
+                imported.anchor = Some(TextAnchor {
+                    start,
+                    end,
+                    prefix: prefix.clone().unwrap_or_default(),
+                    suffix: suffix.clone().unwrap_or_default(),
+                });
+            }
+
+            let bodies = self.body.iter().filter_map(|body| match body {
+                Body::Textual(body) => Some(body),
+                Body::External(_) => None,
+            });
+            let mut notes: Vec<&str> = self.body_value.as_deref().into_iter().collect();
+            for body in bodies {
+                let Some(value) = body.value.as_deref().map(str::trim) else {
+                    continue;
+                };
+                match body.purpose.as_deref() {
+                    Some("tagging") => imported.tags.push(value.to_string()),
+                    _ => notes.push(value),
+                }
+            }
+            let note = notes.join("\n\n");
+            imported.note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+            Some(imported)
+        })
+    }
+}
+
+fn textual_body(value: &str, purpose: &str, format: Option<&str>) -> Body {
+    Body::Textual(TextualBody {
+        kind: Some("TextualBody".to_string()),
+        value: Some(value.to_string()),
+        purpose: Some(purpose.to_string()),
+        format: format.map(str::to_string),
+    })
+}
+
+fn timestamp(seconds: i64) -> Option<String> {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn parse_timestamp(text: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|date| date.timestamp())
+}
+
+fn annotation_type() -> Value {
+    Value::from("Annotation")
+}
+
+/// Whether a JSON-LD node's `type` is or includes `kind`
+fn has_type(value: &Value, kind: &str) -> bool {
+    match &value["type"] {
+        Value::String(value) => value == kind,
+        Value::Array(values) => values.iter().any(|value| value == kind),
+        _ => false,
+    }
+}
+
+fn items_of(mut page: Value) -> Vec<Value> {
+    match page["items"].take() {
+        Value::Array(items) => items,
+        _ => Vec::new(),
+    }
+}
+
+/// JSON-LD allows a single value wherever a list is expected
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(items) => items,
+        OneOrMany::One(item) => vec![item],
+    })
+}
+
+fn single_or_many<S, T>(items: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    match items {
+        [item] => item.serialize(serializer),
+        items => items.serialize(serializer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotation_page, parse_web_annotations, web_annotation, ImportedWebAnnotation};
+    use crate::models::{Annotation, TextAnchor};
+    use serde_json::json;
+
+    #[test]
+    fn converts_annotations_to_and_from_json_ld() {
+        let anchor = TextAnchor {
+            start: 4,
+            end: 7,
+            prefix: "The ".to_string(),
+            suffix: " sat.".to_string(),
+        };
+        let annotation = Annotation {
+            id: "a1".to_string(),
+            paragraph_id: "p1".to_string(),
+            selected_text: "cat".to_string(),
+            style: "highlight".to_string(),
+            color: Some("green".to_string()),
+            note: Some("A note".to_string()),
+            tags: vec!["animals".to_string()],
+            anchor: Some(anchor.clone()),
+            created_at: 0,
+            updated_at: 60,
+        };
+        let exported = web_annotation(&annotation, "doc-1", "ch1.xhtml#p3").standalone();
+        let value = serde_json::to_value(&exported).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "@context": "http://www.w3.org/ns/anno.jsonld",
+                "id": "urn:uuid:a1",
+                "type": "Annotation",
+                "motivation": "commenting",
+                "created": "1970-01-01T00:00:00Z",
+                "modified": "1970-01-01T00:01:00Z",
+                "body": [
+                    {"type": "TextualBody", "value": "A note", "purpose": "commenting", "format": "text/plain"},
+                    {"type": "TextualBody", "value": "animals", "purpose": "tagging"}
+                ],
+                "target": {
+                    "type": "SpecificResource",
+                    "source": "urn:uuid:doc-1",
+                    "selector": [
+                        {"type": "TextQuoteSelector", "exact": "cat", "prefix": "The ", "suffix": " sat."},
+                        {
+                            "type": "FragmentSelector",
+                            "value": "ch1.xhtml#p3",
+                            "refinedBy": [{"type": "TextPositionSelector", "start": 4, "end": 7}]
+                        }
+                    ],
+                    "styleClass": "highlight green"
+                }
+            })
+        );
+
+        let expected = ImportedWebAnnotation {
+            id: Some("a1".to_string()),
+            source: "urn:uuid:doc-1".to_string(),
+            location: Some("ch1.xhtml#p3".to_string()),
+            exact: "cat".to_string(),
+            anchor: Some(anchor),
+            classes: vec!["highlight".to_string(), "green".to_string()],
+            note: Some("A note".to_string()),
+            tags: vec!["animals".to_string()],
+            created_at: Some(0),
+            updated_at: Some(60),
+        };
+        let single = parse_web_annotations(&value.to_string()).unwrap();
+        assert_eq!(single[0].to_imported(), Some(expected.clone()));
+
+        let page = annotation_page(vec![exported]);
+        assert!(page.context.is_some() && page.items[0].context.is_none());
+        let page_text = serde_json::to_string(&page).unwrap();
+        let items = parse_web_annotations(&page_text).unwrap();
+        assert_eq!(items[0].to_imported(), Some(expected));
+
+        // Hypothesis-style: a collection, a page URL as source, XPath ranges and bodyValue
+        let foreign = json!({
+            "@context": "http://www.w3.org/ns/anno.jsonld",
+            "type": "AnnotationCollection",
+            "first": {
+                "type": "AnnotationPage",
+                "items": [{
+                    "id": "https://hypothes.is/a/xyz",
+                    "type": "Annotation",
+                    "bodyValue": "Interesting",
+                    "target": [{
+                        "source": "https://example.com/article",
+                        "selector": [
+                            {"type": "RangeSelector", "startSelector": {"type": "XPathSelector", "value": "/p[1]"}},
+                            {"type": "TextQuoteSelector", "exact": "quoted words"}
+                        ]
+                    }]
+                }, {
+                    "type": "Annotation",
+                    "target": "https://example.com/article"
+                }]
+            }
+        });
+        let items = parse_web_annotations(&foreign.to_string()).unwrap();
+        let imported = items[0].to_imported().unwrap();
+        assert_eq!(imported.id, None);
+        assert_eq!(imported.source, "https://example.com/article");
+        assert_eq!(imported.exact, "quoted words");
+        assert_eq!((imported.anchor, imported.location), (None, None));
+        assert_eq!(imported.note.as_deref(), Some("Interesting"));
+        assert_eq!(items[1].to_imported(), None);
+        assert!(parse_web_annotations("{\"type\": \"Person\"}").is_err());
+    }
+}
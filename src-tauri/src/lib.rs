@@ -2,6 +2,7 @@ mod commands;
 mod config;
 mod database;
 mod error;
+mod export;
 mod llm;
 mod logger;
 mod mcp;
@@ -12,53 +13,172 @@ mod search;
 pub use error::{ReaderError, Result};
 
 use commands::{
-    chat_with_context,
-    clear_embeddings_by_profile, create_annotation, delete_annotation, delete_document,
-    deep_analyze, download_embedding_model_files, fetch_url_html, get_config, get_document,
-    get_document_paragraphs, get_document_sections, get_embedding_profile_status,
-    get_document_previews, get_paragraph_context, get_section_paragraphs, get_summary_cache,
-    import_epub, import_markdown, import_markdown_content, import_pdf, import_url,
-    index_document, list_annotations, list_documents, list_tts_voices, mcp_request, search,
-    search_by_embedding, summarize, translate, tts_synthesize, update_config,
-    upsert_embeddings_batch, validate_local_embedding_model_path,
+    add_feed, add_to_collection, add_watched_folder, chat_with_context,
+    clear_embeddings_by_profile, create_annotation, create_bookmark, create_collection,
+    deep_analyze, delete_annotation, delete_bookmark, delete_collection, delete_document,
+    delete_feed, delete_tag, download_embedding_model_files, export_notes, fetch_url_html,
+    get_config, get_document, get_document_ocr_pages, get_document_paragraphs,
+    get_document_previews, get_document_sections, get_embedding_profile_status,
+    get_paragraph_context, get_reading_position, get_section_paragraphs, get_summary_cache,
+    get_web_annotation, get_web_annotation_page, import_asciidoc, import_calibre_library,
+    import_docx, import_epub, import_fb2, import_folder, import_highlights, import_html,
+    import_markdown, import_markdown_content, import_mobi, import_notebook, import_notes,
+    import_pdf, import_rst, import_text, import_url, import_web_annotations, index_document,
+    list_annotations, list_bookmarks, list_collections, list_documents, list_feeds,
+    list_missing_documents, list_paragraph_links, list_recently_read, list_series, list_tags,
+    list_tts_voices, list_watched_folders, mcp_request, refresh_feeds, reimport_document,
+    relocate_document, remove_from_collection, remove_watched_folder, rename_tag,
+    repair_missing_files, save_reading_position, scan_watched_folders, search, search_by_embedding,
+    set_document_series, set_document_tags, summarize, take_launch_deep_link, translate,
+    tts_synthesize, update_annotation, update_bookmark, update_collection, update_config,
+    update_document, upsert_embeddings_batch, validate_local_embedding_model_path,
 };
-use tauri::{menu::Menu, Manager};
+use tauri::{menu::Menu, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
 
 fn build_app_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<Menu<R>> {
     Menu::default(app)
 }
 
+/// Forwards deep links, e.g. from exported notes, to the window
+///
+/// A link the app was launched with is kept for `take_launch_deep_link`.
+/// Links opened while it runs reach this instance through the single
+/// instance plugin.
+fn forward_deep_links(app: &tauri::AppHandle) {
+    // Bundles register the scheme when installed; AppImages and dev builds
+    // aren't installed
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        tracing::warn!("Failed to register deep link scheme: {}", e);
+    }
+    let launch_target = match app.deep_link().get_current() {
+        Ok(urls) => urls
+            .unwrap_or_default()
+            .iter()
+            .find_map(|url| export::parse_deep_link(url.as_str())),
+        Err(e) => {
+            tracing::warn!("Failed to read launch deep link: {}", e);
+            None
+        }
+    };
+    app.manage(commands::LaunchDeepLink::new(launch_target));
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            match export::parse_deep_link(url.as_str()) {
+                Some(target) => {
+                    if let Err(e) = handle.emit(export::DEEP_LINK_EVENT, target) {
+                        tracing::warn!("Failed to forward deep link {}: {}", url, e);
+                    }
+                }
+                None => tracing::warn!("Ignoring unrecognised deep link {}", url),
+            }
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    #[allow(unused_mut)]
+    let mut builder = tauri::Builder::default();
+    // Registered first so a second launch, e.g. from a clicked link, hands its
+    // link to the running app and quits before other plugins start
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }));
+    }
+    builder
         .menu(build_app_menu)
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             logger::init_logging();
             database::init_db(app.handle())?;
             app.manage(commands::McpState::default());
+            commands::start_feed_scheduler(app.handle().clone());
+            commands::start_folder_watcher(app.handle().clone());
+            forward_deep_links(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             import_epub,
             import_pdf,
+            import_docx,
+            import_html,
+            import_text,
+            import_fb2,
+            import_mobi,
             import_markdown,
+            import_notebook,
+            import_rst,
+            import_asciidoc,
+            import_calibre_library,
+            import_folder,
+            add_watched_folder,
+            list_watched_folders,
+            remove_watched_folder,
+            scan_watched_folders,
             import_url,
             fetch_url_html,
             import_markdown_content,
+            add_feed,
+            list_feeds,
+            delete_feed,
+            refresh_feeds,
             list_documents,
             get_document_previews,
             get_document,
             delete_document,
+            reimport_document,
+            list_missing_documents,
+            relocate_document,
+            repair_missing_files,
+            save_reading_position,
+            get_reading_position,
+            list_recently_read,
             get_document_sections,
             get_section_paragraphs,
+            get_document_ocr_pages,
             index_document,
             search,
             get_paragraph_context,
             get_document_paragraphs,
             list_annotations,
+            list_paragraph_links,
             create_annotation,
+            update_annotation,
             delete_annotation,
+            list_bookmarks,
+            create_bookmark,
+            update_bookmark,
+            delete_bookmark,
+            list_tags,
+            rename_tag,
+            delete_tag,
+            set_document_tags,
+            list_collections,
+            create_collection,
+            update_collection,
+            delete_collection,
+            add_to_collection,
+            remove_from_collection,
+            list_series,
+            set_document_series,
+            update_document,
+            import_highlights,
+            export_notes,
+            import_notes,
+            take_launch_deep_link,
+            get_web_annotation,
+            get_web_annotation_page,
+            import_web_annotations,
             upsert_embeddings_batch,
             search_by_embedding,
             get_embedding_profile_status,
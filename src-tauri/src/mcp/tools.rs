@@ -5,6 +5,7 @@ use crate::llm::LmStudioClient;
 use crate::search::{SearchOptions, SearchResult};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use tauri::AppHandle;
 
 // MCP Tool Schemas (from mcp_schemas/reader-tools.schema.json)
@@ -32,7 +33,17 @@ const TOOLS: &[(&str, &str, &str)] = &[
     ),
     (
         "reader.open_location",
-        "Open reader at a specific location",
+        "Open reader at a specific location, or get the current reading position",
+        "Navigate",
+    ),
+    (
+        "reader.list_bookmarks",
+        "List bookmarks of a document or the whole library",
+        "Navigate",
+    ),
+    (
+        "reader.add_bookmark",
+        "Bookmark a paragraph with an optional title and note",
         "Navigate",
     ),
 ];
@@ -61,6 +72,10 @@ struct SearchArgs {
     doc_id: Option<String>,
     #[serde(rename = "section_id", default)]
     section_id: Option<String>,
+    #[serde(default)]
+    collection_id: Option<String>,
+    #[serde(default)]
+    tag: Option<String>,
 }
 
 fn default_top_k() -> usize {
@@ -80,12 +95,14 @@ pub async fn handle_search(app_handle: &AppHandle, args: Value) -> Result<Value>
         config.chat_model,
     )?;
 
-    // Note: Current implementation only supports doc_id filtering
+    // Note: Current implementation supports doc_id, collection_id and tag filtering
     // section_id is accepted for future compatibility but not used
     let options = SearchOptions {
         query: args.query.clone(),
         top_k: args.top_k,
         doc_id: args.doc_id,
+        collection_id: args.collection_id,
+        tag: args.tag,
         force_keyword: false,
     };
 
@@ -124,13 +141,30 @@ pub async fn handle_get_section(app_handle: &AppHandle, args: Value) -> Result<V
 
     let paragraphs = database::list_paragraphs_by_section(&conn, &args.section_id)?;
 
+    // Footnote references and internal links, grouped by source paragraph
+    let paragraph_ids: Vec<String> = paragraphs.iter().map(|p| p.id.clone()).collect();
+    let mut links_by_paragraph: HashMap<String, Vec<Value>> = HashMap::new();
+    for link in database::list_paragraph_links(&conn, &paragraph_ids)? {
+        links_by_paragraph
+            .entry(link.source_paragraph_id)
+            .or_default()
+            .push(serde_json::json!({
+                "kind": link.kind,
+                "label": link.label,
+                "target_paragraph_id": link.target_paragraph_id,
+                "target_location": link.target_location,
+            }));
+    }
+
     let paragraphs_json: Vec<Value> = paragraphs
         .into_iter()
         .map(|p| {
+            let links = links_by_paragraph.remove(&p.id).unwrap_or_default();
             serde_json::json!({
                 "paragraph_id": p.id,
                 "text": p.text,
                 "location": p.location,
+                "links": links,
             })
         })
         .collect();
@@ -284,16 +318,69 @@ pub async fn handle_bilingual_view(app_handle: &AppHandle, args: Value) -> Resul
 struct OpenLocationArgs {
     #[serde(rename = "doc_id")]
     doc_id: String,
-    location: String,
+    /// Omitted to only read where reading stopped
+    location: Option<String>,
 }
 
-pub async fn handle_open_location(_app_handle: &AppHandle, args: Value) -> Result<Value> {
-    let _args: OpenLocationArgs = serde_json::from_value(args)
+pub async fn handle_open_location(app_handle: &AppHandle, args: Value) -> Result<Value> {
+    let args: OpenLocationArgs = serde_json::from_value(args)
         .map_err(|e| ReaderError::InvalidArgument(format!("Invalid open_location args: {}", e)))?;
 
-    // TODO: Implement jumping to location in UI
-    // For now, just return success
-    Ok(serde_json::json!({ "ok": true }))
+    let doc_id = args.doc_id;
+    let position = database::run_blocking(app_handle, move |conn| {
+        if database::get_document(conn, &doc_id)?.is_none() {
+            return Err(ReaderError::NotFound(format!("Document {}", doc_id)));
+        }
+        Ok(database::get_reading_position(conn, &doc_id)?)
+    })
+    .await?;
+
+    // TODO: Implement jumping to `args.location` in UI
+    Ok(serde_json::json!({
+        "ok": true,
+        "position": position,
+    }))
+}
+
+#[derive(Deserialize)]
+struct ListBookmarksArgs {
+    #[serde(rename = "doc_id", default)]
+    doc_id: Option<String>,
+}
+
+pub async fn handle_list_bookmarks(app_handle: &AppHandle, args: Value) -> Result<Value> {
+    let args: ListBookmarksArgs = serde_json::from_value(args)
+        .map_err(|e| ReaderError::InvalidArgument(format!("Invalid list_bookmarks args: {}", e)))?;
+
+    let bookmarks = crate::commands::list_bookmarks(app_handle.clone(), args.doc_id).await?;
+    Ok(serde_json::json!({ "bookmarks": bookmarks }))
+}
+
+#[derive(Deserialize)]
+struct AddBookmarkArgs {
+    #[serde(rename = "paragraph_id")]
+    paragraph_id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+pub async fn handle_add_bookmark(app_handle: &AppHandle, args: Value) -> Result<Value> {
+    let args: AddBookmarkArgs = serde_json::from_value(args)
+        .map_err(|e| ReaderError::InvalidArgument(format!("Invalid add_bookmark args: {}", e)))?;
+
+    let bookmark = crate::commands::create_bookmark(
+        app_handle.clone(),
+        args.paragraph_id,
+        args.title,
+        args.note,
+        args.color,
+    )
+    .await?;
+    Ok(serde_json::json!({ "bookmark": bookmark }))
 }
 
 pub async fn handle_tool_call(
@@ -308,6 +395,8 @@ pub async fn handle_tool_call(
         "reader.translate" => handle_translate(app_handle, arguments).await,
         "reader.bilingual_view" => handle_bilingual_view(app_handle, arguments).await,
         "reader.open_location" => handle_open_location(app_handle, arguments).await,
+        "reader.list_bookmarks" => handle_list_bookmarks(app_handle, arguments).await,
+        "reader.add_bookmark" => handle_add_bookmark(app_handle, arguments).await,
         _ => Err(ReaderError::InvalidArgument(format!(
             "Unknown tool: {}",
             tool_name
@@ -6,7 +6,42 @@ pub struct Annotation {
     pub paragraph_id: String,
     pub selected_text: String,
     pub style: String,
+    /// Highlight colour name; the style's default colour when unset
+    pub color: Option<String>,
     pub note: Option<String>,
+    /// Tag names, sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Position of `selected_text` in the paragraph; unset for annotations
+    /// whose text couldn't be placed, e.g. ones made before it was recorded
+    pub anchor: Option<TextAnchor>,
     pub created_at: i64,
     pub updated_at: i64,
 }
+
+/// Where an annotation's text sits in its paragraph
+///
+/// Offsets count Unicode characters, not bytes. The surrounding text tells
+/// repeated phrases apart and finds the selection again once the paragraph
+/// has been edited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextAnchor {
+    pub start: usize,
+    pub end: usize,
+    /// Text just before the selection
+    pub prefix: String,
+    /// Text just after the selection
+    pub suffix: String,
+}
+
+/// Represents a new annotation to be inserted
+#[derive(Debug, Clone, Default)]
+pub struct NewAnnotation {
+    pub paragraph_id: String,
+    pub selected_text: String,
+    pub style: String,
+    pub color: Option<String>,
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    pub anchor: Option<TextAnchor>,
+}
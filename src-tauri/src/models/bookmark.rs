@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// A named bookmark at a paragraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: String,
+    pub doc_id: String,
+    pub paragraph_id: String,
+    pub title: String,
+    pub note: Option<String>,
+    /// One of the reader's bookmark colour names
+    pub color: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A user-made collection of documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub document_count: usize,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
@@ -9,10 +9,40 @@ pub struct Document {
     pub language: Option<String>,
     pub file_path: String,
     pub file_type: String,
+    /// SHA-256 of the imported file; unset for documents imported before it was recorded
+    pub content_hash: Option<String>,
+    /// Where the file was imported from when `file_path` is a copy in the managed library
+    pub original_path: Option<String>,
+    pub series: Option<String>,
+    /// Position in the series; fractional for novellas between volumes
+    pub series_index: Option<f64>,
+    /// Tag names, sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Ids of the collections the document is in
+    #[serde(default)]
+    pub collection_ids: Vec<String>,
+    #[serde(flatten)]
+    pub metadata: DocumentMetadata,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
+/// Bibliographic details the user can edit besides title, author and language
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub subtitle: Option<String>,
+    pub publisher: Option<String>,
+    /// As written on the title page, e.g. `2019` or `2019-03-01`
+    pub published_date: Option<String>,
+    /// ISBN-10 or ISBN-13, digits only
+    pub isbn: Option<String>,
+    pub doi: Option<String>,
+    pub description: Option<String>,
+    /// 1 to 5 stars
+    pub rating: Option<u8>,
+}
+
 /// Represents a new document to be inserted
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewDocument {
@@ -22,3 +52,13 @@ pub struct NewDocument {
     pub file_path: String,
     pub file_type: String,
 }
+
+/// Restricts `list_documents` to part of the library; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentFilter {
+    /// Tag name, matched case-insensitively
+    pub tag: Option<String>,
+    pub collection_id: Option<String>,
+    /// Series name; matching documents are ordered by series index
+    pub series: Option<String>,
+}
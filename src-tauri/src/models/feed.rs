@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// An RSS/Atom subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub site_url: Option<String>,
+    /// Minutes between automatic polls; `None` polls only on request
+    pub poll_interval_minutes: Option<i64>,
+    pub last_polled_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// A footnote reference or in-document link between two paragraphs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParagraphLink {
+    pub id: String,
+    pub doc_id: String,
+    pub source_paragraph_id: String,
+    pub target_paragraph_id: String,
+    pub target_location: String,
+    pub kind: String,
+    pub label: String,
+    pub created_at: i64,
+}
@@ -1,9 +1,23 @@
 mod annotation;
+mod bookmark;
+mod collection;
 mod document;
+mod feed;
+mod link;
 mod paragraph;
+mod reading_position;
 mod section;
+mod tag;
+mod watched_folder;
 
-pub use annotation::Annotation;
-pub use document::{Document, NewDocument};
+pub use annotation::{Annotation, NewAnnotation, TextAnchor};
+pub use bookmark::Bookmark;
+pub use collection::Collection;
+pub use document::{Document, DocumentFilter, DocumentMetadata, NewDocument};
+pub use feed::Feed;
+pub use link::ParagraphLink;
 pub use paragraph::Paragraph;
+pub use reading_position::ReadingPosition;
 pub use section::Section;
+pub use tag::Tag;
+pub use watched_folder::WatchedFolder;
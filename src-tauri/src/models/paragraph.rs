@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+fn default_paragraph_kind() -> String {
+    "text".to_string()
+}
+
 /// Represents a paragraph within a document
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Paragraph {
@@ -9,4 +13,7 @@ pub struct Paragraph {
     pub order_index: i32,
     pub text: String,
     pub location: String,
+    /// `text`, or `ocr` when recognised from a scanned page
+    #[serde(default = "default_paragraph_kind")]
+    pub kind: String,
 }
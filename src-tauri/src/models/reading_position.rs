@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Where reading last stopped in a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    pub doc_id: String,
+    pub section_id: Option<String>,
+    /// Paragraph at the top of the view
+    pub paragraph_id: Option<String>,
+    /// Format-specific location, e.g. a page number or EPUB href
+    pub location: Option<String>,
+    /// How far the section was scrolled, from 0 to 1
+    pub scroll_fraction: f64,
+    pub updated_at: i64,
+}
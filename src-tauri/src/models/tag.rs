@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// A tag with the number of documents carrying it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub document_count: usize,
+    pub created_at: i64,
+}
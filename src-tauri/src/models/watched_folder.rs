@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// A library folder scanned for new or changed documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedFolder {
+    pub id: String,
+    pub path: String,
+    /// Whether subfolders are scanned too
+    pub recursive: bool,
+    pub last_scanned_at: Option<i64>,
+    pub created_at: i64,
+}
@@ -0,0 +1,785 @@
+//! AsciiDoc (`.adoc` / `.asciidoc`) documents
+//!
+//! The source is converted to Markdown and sectioned like any Markdown file.
+//! `=` titles map to `#` headings of the same depth, listing and literal
+//! blocks become fenced code, admonitions become quotes, and the document
+//! header supplies the title, author and language.
+
+use super::markdown::build_markdown_sections;
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+const ADMONITIONS: [&str; 5] = ["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+/// Placeholders for inline code while the rest of a paragraph is rewritten
+const PROTECTED_START: char = '\u{E010}';
+const PROTECTED_END: char = '\u{E011}';
+
+/// A `:name: value` or `:name!:` attribute entry
+static ATTRIBUTE_ENTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:(!?[\w-]+!?):\s*(.*)$").unwrap());
+/// Opening or closing line of a delimited block
+static DELIMITER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(-{4,}|\.{4,}|={4,}|\*{4,}|_{4,}|\+{4,}|/{4,}|\|={3,}|--|```.*)\s*$").unwrap()
+});
+static BLOCK_ATTRIBUTES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[([^\[\]]*)\]\s*$").unwrap());
+static BLOCK_ANCHOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[\[[^\]]*\]\]\s*$").unwrap());
+static BLOCK_TITLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\.([^.\s].*)$").unwrap());
+static SECTION_TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(={1,6})\s+(.+?)(?:\s+=+)?\s*$").unwrap());
+static ADMONITION_PARAGRAPH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(NOTE|TIP|IMPORTANT|WARNING|CAUTION):\s+(.*)$").unwrap());
+static UNORDERED_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\*{1,5}|-)\s+(.*)$").unwrap());
+static ORDERED_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\.{1,5}|\d+\.)\s+(.*)$").unwrap());
+static DESCRIPTION_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)(:{2,4}|;;)(?:\s+(.*))?$").unwrap());
+static BLOCK_IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^image::([^\[]+)\[([^\]]*)\]\s*$").unwrap());
+/// Preprocessor lines and macros that produce no text
+static PREPROCESSOR_DIRECTIVE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:include|toc|ifdef|ifndef|ifeval|endif)::").unwrap());
+static COLS_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"cols\s*=\s*"?([^"]*)"?"#).unwrap());
+static MONOSPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`\+(.+?)\+`|`([^`]+)`").unwrap());
+static PASSTHROUGH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\+\+\+(.+?)\+\+\+|\+\+(.+?)\+\+|pass:\[([^\]]*)\]").unwrap());
+static ATTRIBUTE_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([\w-]+)\}").unwrap());
+static KBD_MACRO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"kbd:\[([^\]]*)\]").unwrap());
+static BUTTON_MACRO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"btn:\[([^\]]*)\]").unwrap());
+static MENU_MACRO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"menu:([^\[]+)\[([^\]]*)\]").unwrap());
+static FOOTNOTE_MACRO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"footnote(?::[\w-]*)?:\[([^\]]*)\]").unwrap());
+static INLINE_IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"image:([^\s\[]+)\[([^\]]*)\]").unwrap());
+static LINK_MACRO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:link:)?((?:https?|ftp|mailto|file):[^\s\[]*|link:[^\s\[]+)\[([^\]]*)\]")
+        .unwrap()
+});
+static XREF_MACRO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"xref:([^\s\[]+)\[([^\]]*)\]").unwrap());
+static CROSS_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<<([^,>]+)(?:,\s*([^>]+))?>>").unwrap());
+/// Constrained `*strong*` text, not touching word characters
+static STRONG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w*])\*([^\s*](?:[^*]*[^\s*])?)\*([^\w*]|$)").unwrap());
+static HIGHLIGHT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[[^\]]*\]#([^#]+)#|(^|\s)#([^#\s][^#]*)#").unwrap());
+/// A protected span left by `inline`
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("\u{E010}(\\d+)\u{E011}").unwrap());
+
+pub struct AsciidocParser {
+    file_path: String,
+}
+
+/// Result of converting an AsciiDoc document
+struct AsciidocDocument {
+    markdown: String,
+    title: Option<String>,
+    author: Option<String>,
+    language: Option<String>,
+}
+
+impl AsciidocParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let content = fs::read_to_string(&self.file_path)?;
+        let document = asciidoc_to_markdown(&content);
+
+        let fallback_title = Path::new(&self.file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled");
+        let (heading_title, sections) = build_markdown_sections(&document.markdown, fallback_title);
+
+        let metadata = NewDocument {
+            title: document.title.unwrap_or(heading_title),
+            author: document.author,
+            language: document.language,
+            file_path: self.file_path.clone(),
+            file_type: "markdown".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+}
+
+fn asciidoc_to_markdown(content: &str) -> AsciidocDocument {
+    let content = content.replace("\r\n", "\n");
+    let lines: Vec<&str> = content.lines().collect();
+    let mut converter = AsciidocConverter::default();
+
+    // Document header: `= Title`, author and revision lines, attribute entries
+    let mut idx = 0;
+    while idx < lines.len() && (lines[idx].trim().is_empty() || is_comment(lines[idx])) {
+        idx += 1;
+    }
+    let mut header_title = None;
+    let mut author = None;
+    if let Some(title) = lines.get(idx).and_then(|line| line.strip_prefix("= ")) {
+        header_title = Some(converter.inline(title.trim()));
+        idx += 1;
+        let mut header_line = 0;
+        while let Some(line) = lines.get(idx) {
+            if line.trim().is_empty() {
+                break;
+            }
+            if !converter.attribute_entry(line) && !is_comment(line) {
+                // The author line comes first, then the revision line
+                if header_line == 0 {
+                    author = Some(
+                        line.split(';')
+                            .map(|name| {
+                                // Drop the `<email>` part
+                                name.split('<').next().unwrap_or("").trim().to_string()
+                            })
+                            .filter(|name| !name.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    )
+                    .filter(|names| !names.is_empty());
+                }
+                header_line += 1;
+            }
+            idx += 1;
+        }
+    }
+
+    let mut blocks = Vec::new();
+    if let Some(title) = &header_title {
+        blocks.push(format!("# {}", title));
+    }
+    blocks.extend(converter.convert(&lines[idx..]));
+
+    let author = author.or_else(|| {
+        converter
+            .attributes
+            .get("author")
+            .or_else(|| converter.attributes.get("authors"))
+            .cloned()
+    });
+    AsciidocDocument {
+        markdown: blocks.join("\n\n"),
+        title: header_title,
+        author,
+        language: converter.attributes.get("lang").cloned(),
+    }
+}
+
+#[derive(Default)]
+struct AsciidocConverter {
+    attributes: HashMap<String, String>,
+}
+
+/// Attributes and title set by the lines just before a block
+#[derive(Default)]
+struct PendingBlock {
+    attributes: Vec<String>,
+    title: Option<String>,
+}
+
+impl AsciidocConverter {
+    /// Records `:name: value` lines, returning whether the line was one
+    fn attribute_entry(&mut self, line: &str) -> bool {
+        let Some(caps) = ATTRIBUTE_ENTRY.captures(line.trim_end()) else {
+            return false;
+        };
+        let name = caps[1].trim_matches('!').to_ascii_lowercase();
+        if caps[1].contains('!') {
+            self.attributes.remove(&name);
+        } else {
+            self.attributes.insert(name, caps[2].trim().to_string());
+        }
+        true
+    }
+
+    fn convert(&mut self, lines: &[&str]) -> Vec<String> {
+        let mut blocks: Vec<String> = Vec::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut admonition: Option<String> = None;
+        let mut list: Vec<String> = Vec::new();
+        let mut pending = PendingBlock::default();
+
+        let mut idx = 0;
+        while idx < lines.len() {
+            let line = lines[idx].trim_end();
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                self.flush_paragraph(&mut paragraph, &mut admonition, &mut blocks);
+                idx += 1;
+                continue;
+            }
+            if is_comment(line) {
+                idx += 1;
+                continue;
+            }
+            if !paragraph.is_empty() && DELIMITER.find(line).is_none() {
+                paragraph.push(trimmed);
+                idx += 1;
+                continue;
+            }
+            self.flush_paragraph(&mut paragraph, &mut admonition, &mut blocks);
+
+            if let Some(found) = DELIMITER.find(line) {
+                flush_list(&mut list, &mut blocks);
+                let delimiter = found.as_str().trim();
+                let closing = if delimiter.starts_with("```") {
+                    "```"
+                } else {
+                    delimiter
+                };
+                let end = lines[idx + 1..]
+                    .iter()
+                    .position(|l| l.trim_end() == closing)
+                    .map(|offset| idx + 1 + offset)
+                    .unwrap_or(lines.len());
+                let body = &lines[idx + 1..end];
+                let block = std::mem::take(&mut pending);
+                blocks.extend(self.delimited_block(delimiter, body, block));
+                idx = end + 1;
+                continue;
+            }
+            if BLOCK_ANCHOR.is_match(line)
+                || PREPROCESSOR_DIRECTIVE.is_match(line)
+                || trimmed == "<<<"
+            {
+                idx += 1;
+                continue;
+            }
+            if let Some(caps) = BLOCK_ATTRIBUTES.captures(line) {
+                pending.attributes = caps[1].split(',').map(|a| a.trim().to_string()).collect();
+                idx += 1;
+                continue;
+            }
+            if let Some(caps) = BLOCK_TITLE.captures(line) {
+                pending.title = Some(self.inline(caps[1].trim()));
+                idx += 1;
+                continue;
+            }
+            if self.attribute_entry(line) {
+                idx += 1;
+                continue;
+            }
+            if let Some(caps) = SECTION_TITLE.captures(line) {
+                flush_list(&mut list, &mut blocks);
+                pending = PendingBlock::default();
+                blocks.push(format!(
+                    "{} {}",
+                    caps[1].replace('=', "#"),
+                    self.inline(&caps[2])
+                ));
+                idx += 1;
+                continue;
+            }
+            if trimmed == "'''" || trimmed == "---" || trimmed == "***" {
+                flush_list(&mut list, &mut blocks);
+                blocks.push("---".to_string());
+                idx += 1;
+                continue;
+            }
+            if let Some(caps) = BLOCK_IMAGE.captures(line) {
+                flush_list(&mut list, &mut blocks);
+                let block = std::mem::take(&mut pending);
+                if let Some(title) = block.title {
+                    blocks.push(format!("**{}**", title));
+                }
+                let alt = caps[2].split(',').next().unwrap_or("").trim();
+                blocks.push(format!("![{}]({})", alt, caps[1].trim()));
+                idx += 1;
+                continue;
+            }
+
+            // Literal paragraph: indented lines
+            if line.starts_with([' ', '\t']) && list.is_empty() {
+                let end = lines[idx..]
+                    .iter()
+                    .position(|l| l.trim().is_empty())
+                    .map(|offset| idx + offset)
+                    .unwrap_or(lines.len());
+                let body: Vec<&str> = lines[idx..end].iter().map(|l| l.trim_end()).collect();
+                blocks.push(fenced("", &dedent(&body)));
+                idx = end;
+                continue;
+            }
+
+            let item = if let Some(caps) = UNORDERED_ITEM.captures(trimmed) {
+                let depth = if &caps[1] == "-" { 1 } else { caps[1].len() };
+                Some((depth, "-".to_string(), caps[2].to_string()))
+            } else if let Some(caps) = ORDERED_ITEM.captures(trimmed) {
+                let depth = if caps[1].ends_with('.') && caps[1].starts_with(char::is_numeric) {
+                    1
+                } else {
+                    caps[1].len()
+                };
+                Some((depth, "1.".to_string(), caps[2].to_string()))
+            } else if let Some(caps) = DESCRIPTION_ITEM
+                .captures(trimmed)
+                .filter(|caps| !caps[1].contains("://") && !caps[1].ends_with(':'))
+            {
+                let term = format!("**{}**", caps[1].trim());
+                match caps.get(3).map(|m| m.as_str().trim()) {
+                    Some(text) if !text.is_empty() => {
+                        Some((1, "-".to_string(), format!("{}: {}", term, text)))
+                    }
+                    _ => Some((1, "-".to_string(), term)),
+                }
+            } else {
+                None
+            };
+            if let Some((depth, marker, mut text)) = item {
+                idx += 1;
+                // Item text continues on following non-blank lines until the next item
+                while let Some(more) = lines.get(idx) {
+                    let more = more.trim();
+                    if more.is_empty()
+                        || UNORDERED_ITEM.is_match(more)
+                        || ORDERED_ITEM.is_match(more)
+                        || DELIMITER.is_match(more)
+                    {
+                        break;
+                    }
+                    if more != "+" {
+                        text.push(' ');
+                        text.push_str(more);
+                    }
+                    idx += 1;
+                }
+                list.push(format!(
+                    "{}{} {}",
+                    "  ".repeat(depth - 1),
+                    marker,
+                    self.inline(text.trim())
+                ));
+                continue;
+            }
+
+            flush_list(&mut list, &mut blocks);
+            if let Some(title) = pending.title.take() {
+                blocks.push(format!("**{}**", title));
+            }
+            if let Some(caps) = ADMONITION_PARAGRAPH.captures(trimmed) {
+                admonition = Some(caps[1].to_string());
+                paragraph.push(caps.get(2).map_or("", |m| m.as_str().trim()));
+            } else {
+                if let Some(kind) = pending
+                    .attributes
+                    .first()
+                    .filter(|kind| ADMONITIONS.contains(&kind.as_str()))
+                {
+                    admonition = Some(kind.clone());
+                }
+                paragraph.push(trimmed);
+            }
+            pending = PendingBlock::default();
+            idx += 1;
+        }
+
+        self.flush_paragraph(&mut paragraph, &mut admonition, &mut blocks);
+        flush_list(&mut list, &mut blocks);
+        blocks
+    }
+
+    fn delimited_block(
+        &mut self,
+        delimiter: &str,
+        body: &[&str],
+        block: PendingBlock,
+    ) -> Vec<String> {
+        let mut blocks = Vec::new();
+        if let Some(title) = &block.title {
+            blocks.push(format!("**{}**", title));
+        }
+        let style = block.attributes.first().map(String::as_str).unwrap_or("");
+        let text = body.join("\n");
+
+        match delimiter.chars().next().unwrap_or('-') {
+            '`' => blocks.push(fenced(delimiter.trim_start_matches('`').trim(), &text)),
+            '/' | '+' => {}
+            '-' if delimiter.len() >= 4 => {
+                let language = match style {
+                    "source" | "" => block.attributes.get(1).map(String::as_str).unwrap_or(""),
+                    _ => "",
+                };
+                blocks.push(fenced(language, &text));
+            }
+            '.' => blocks.push(fenced("", &text)),
+            '|' => blocks.push(self.table(body, &block.attributes)),
+            '_' => {
+                let mut inner = if style == "verse" {
+                    vec![body
+                        .iter()
+                        .map(|l| format!("{}  ", l.trim_end()))
+                        .collect::<Vec<_>>()
+                        .join("\n")]
+                } else {
+                    self.convert(body)
+                };
+                if let Some(attribution) = block.attributes.get(1).filter(|a| !a.is_empty()) {
+                    inner.push(format!("— {}", self.inline(attribution)));
+                }
+                blocks.push(quote(&inner));
+            }
+            _ => {
+                // Example, sidebar and open blocks hold regular content
+                let mut inner = self.convert(body);
+                if ADMONITIONS.contains(&style) {
+                    let label = admonition_label(style);
+                    match inner.first_mut() {
+                        Some(first) => *first = format!("**{}:** {}", label, first),
+                        None => inner.push(format!("**{}**", label)),
+                    }
+                    blocks.push(quote(&inner));
+                } else {
+                    blocks.extend(inner);
+                }
+            }
+        }
+        blocks
+    }
+
+    /// Renders a `|===` table as a Markdown table
+    fn table(&self, body: &[&str], attributes: &[String]) -> String {
+        let mut cells: Vec<String> = Vec::new();
+        let mut first_row_len = 0;
+        for line in body {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(row) = line.strip_prefix('|') {
+                let row_cells: Vec<String> = row
+                    .split('|')
+                    .map(|cell| self.inline(cell.trim()).replace('|', "\\|"))
+                    .collect();
+                if first_row_len == 0 {
+                    first_row_len = row_cells.len();
+                }
+                cells.extend(row_cells);
+            } else if let Some(last) = cells.last_mut() {
+                last.push(' ');
+                last.push_str(&self.inline(line));
+            }
+        }
+
+        let columns = attributes
+            .iter()
+            .find_map(|attr| COLS_ATTRIBUTE.captures(attr))
+            .map(|caps| {
+                let spec = caps[1].trim();
+                // `cols="3*"` or `cols="1,2,1"`
+                match spec.split_once('*') {
+                    Some((count, _)) if !spec.contains(',') => count.trim().parse().unwrap_or(1),
+                    _ => spec.split(',').count(),
+                }
+            })
+            .unwrap_or(first_row_len)
+            .max(1);
+
+        let rows: Vec<String> = cells
+            .chunks(columns)
+            .map(|row| format!("| {} |", row.join(" | ")))
+            .collect();
+        match rows.split_first() {
+            Some((header, rest)) => {
+                let separator = format!("|{}", " --- |".repeat(columns));
+                std::iter::once(header.clone())
+                    .chain(std::iter::once(separator))
+                    .chain(rest.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            None => String::new(),
+        }
+    }
+
+    fn flush_paragraph(
+        &self,
+        paragraph: &mut Vec<&str>,
+        admonition: &mut Option<String>,
+        blocks: &mut Vec<String>,
+    ) {
+        if paragraph.is_empty() {
+            return;
+        }
+        let text = paragraph
+            .iter()
+            .map(|line| match line.strip_suffix(" +") {
+                // Hard line break
+                Some(line) => format!("{}  ", line),
+                None => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        paragraph.clear();
+
+        let text = self.inline(&text);
+        match admonition.take() {
+            Some(kind) => blocks.push(quote(&[format!(
+                "**{}:** {}",
+                admonition_label(&kind),
+                text
+            )])),
+            None => blocks.push(text),
+        }
+    }
+
+    /// Rewrites inline markup: monospace, strong, links, macros and attribute references
+    fn inline(&self, text: &str) -> String {
+        let mut protected: Vec<String> = Vec::new();
+        let mut protect = |markdown: String| {
+            protected.push(markdown);
+            format!(
+                "{}{}{}",
+                PROTECTED_START,
+                protected.len() - 1,
+                PROTECTED_END
+            )
+        };
+
+        let text = MONOSPACE
+            .replace_all(text, |caps: &Captures| {
+                let code = caps.get(1).or(caps.get(2)).map_or("", |m| m.as_str());
+                protect(format!("`{}`", code))
+            })
+            .into_owned();
+        let text = PASSTHROUGH
+            .replace_all(&text, |caps: &Captures| {
+                let raw = caps
+                    .get(1)
+                    .or(caps.get(2))
+                    .or(caps.get(3))
+                    .map_or("", |m| m.as_str());
+                protect(raw.to_string())
+            })
+            .into_owned();
+        let text = KBD_MACRO
+            .replace_all(&text, |caps: &Captures| protect(format!("`{}`", &caps[1])))
+            .into_owned();
+        let text = ATTRIBUTE_REFERENCE.replace_all(&text, |caps: &Captures| {
+            let name = caps[1].to_ascii_lowercase();
+            match name.as_str() {
+                "nbsp" => "\u{00A0}".to_string(),
+                "sp" | "space" => " ".to_string(),
+                "empty" => String::new(),
+                "amp" => "&".to_string(),
+                "lt" => "<".to_string(),
+                "gt" => ">".to_string(),
+                "startsb" => "[".to_string(),
+                "endsb" => "]".to_string(),
+                "vbar" => "|".to_string(),
+                _ => self
+                    .attributes
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string()),
+            }
+        });
+        let text = BUTTON_MACRO.replace_all(&text, "**$1**");
+        let text = MENU_MACRO.replace_all(&text, |caps: &Captures| {
+            std::iter::once(caps[1].trim())
+                .chain(caps[2].split('>').map(str::trim))
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" > ")
+        });
+        let text = FOOTNOTE_MACRO.replace_all(&text, " ($1)");
+        let text = INLINE_IMAGE
+            .replace_all(&text, |caps: &Captures| {
+                let alt = caps[2].split(',').next().unwrap_or("").trim();
+                protect(format!("![{}]({})", alt, &caps[1]))
+            })
+            .into_owned();
+        let text = LINK_MACRO
+            .replace_all(&text, |caps: &Captures| {
+                let url = caps[1].trim_start_matches("link:");
+                let label = caps[2]
+                    .split(',')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .trim_matches('"');
+                if label.is_empty() {
+                    protect(format!("<{}>", url))
+                } else {
+                    protect(format!("[{}]({})", label, url))
+                }
+            })
+            .into_owned();
+        let text = XREF_MACRO.replace_all(&text, |caps: &Captures| {
+            if caps[2].trim().is_empty() {
+                caps[1].to_string()
+            } else {
+                caps[2].trim().to_string()
+            }
+        });
+        let text = CROSS_REFERENCE.replace_all(&text, |caps: &Captures| {
+            caps.get(2)
+                .map(|label| label.as_str().trim().to_string())
+                .unwrap_or_else(|| caps[1].trim().to_string())
+        });
+        let text = STRONG.replace_all(&text, "$1**$2**$3");
+        let text = HIGHLIGHT.replace_all(&text, |caps: &Captures| match caps.get(1) {
+            Some(role_text) => role_text.as_str().to_string(),
+            None => format!("{}{}", &caps[2], &caps[3]),
+        });
+
+        PLACEHOLDER
+            .replace_all(&text, |caps: &Captures| {
+                caps[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|idx| protected.get(idx).cloned())
+                    .unwrap_or_default()
+            })
+            .into_owned()
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//") && !line.starts_with("////")
+}
+
+fn admonition_label(kind: &str) -> String {
+    let mut chars = kind.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn flush_list(list: &mut Vec<String>, blocks: &mut Vec<String>) {
+    if !list.is_empty() {
+        blocks.push(list.join("\n"));
+        list.clear();
+    }
+}
+
+fn quote(blocks: &[String]) -> String {
+    blocks
+        .iter()
+        .map(|block| {
+            block
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n>\n")
+}
+
+/// Wraps code in a fence longer than any backtick run inside it
+fn fenced(language: &str, code: &str) -> String {
+    let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{code}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::asciidoc_to_markdown;
+    use crate::parsers::markdown::build_markdown_sections;
+
+    #[test]
+    fn converts_header_sections_and_blocks() {
+        let adoc = "\
+= Field Guide
+Ann Lee <ann@example.com>
+v1.0, 2024-01-01
+:lang: en
+:product: Thing
+
+// a comment
+The {product} runs *fast*, see https://example.com[the site].
+
+== Setup
+
+[source,rust]
+----
+fn main() {}
+----
+
+NOTE: Needs Rust 1.70.
+
+* one
+** nested
+
+=== Options
+
+|===
+|Name |Default
+
+|depth
+|3
+|===
+";
+        let document = asciidoc_to_markdown(adoc);
+        assert_eq!(document.title.as_deref(), Some("Field Guide"));
+        assert_eq!(document.author.as_deref(), Some("Ann Lee"));
+        assert_eq!(document.language.as_deref(), Some("en"));
+
+        let (_, sections) = build_markdown_sections(&document.markdown, "fallback");
+        let titles: Vec<&str> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(titles, vec!["Field Guide", "Setup", "Options"]);
+        assert_eq!(
+            sections[0].3,
+            vec![
+                "# Field Guide",
+                "The Thing runs **fast**, see [the site](https://example.com).",
+            ]
+        );
+        assert_eq!(
+            sections[1].3,
+            vec![
+                "## Setup",
+                "```rust\nfn main() {}\n```",
+                "> **Note:** Needs Rust 1.70.",
+                "- one\n  - nested",
+            ]
+        );
+        assert_eq!(
+            sections[2].3,
+            vec![
+                "### Options",
+                "| Name | Default |\n| --- | --- |\n| depth | 3 |"
+            ]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Calibre libraries (`metadata.db` plus one folder per book)
+//!
+//! Only the metadata database is read here; the book files themselves go
+//! through the regular parsers once [`CalibreBook::best_format`] has picked one.
+
+use crate::error::{ReaderError, Result};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Formats we can import, most preferred first
+const FORMAT_PREFERENCE: &[&str] = &["EPUB", "AZW3", "PDF", "MOBI", "AZW", "FB2", "DOCX", "TXT"];
+
+#[derive(Debug, Clone)]
+pub struct CalibreBook {
+    pub id: i64,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub series: Option<String>,
+    pub series_index: Option<f64>,
+    pub tags: Vec<String>,
+    /// Language codes as Calibre stores them (ISO 639-2, e.g. "eng")
+    pub languages: Vec<String>,
+    /// Format name (upper case) and file path of every stored format
+    pub formats: Vec<(String, PathBuf)>,
+}
+
+impl CalibreBook {
+    /// The format to import: EPUB, then AZW3, then PDF, then anything else we read
+    ///
+    /// Formats whose file is missing on disk are passed over.
+    pub fn best_format(&self) -> Option<(&str, &Path)> {
+        FORMAT_PREFERENCE.iter().find_map(|wanted| {
+            self.formats
+                .iter()
+                .find(|(format, path)| format == wanted && path.is_file())
+                .map(|(format, path)| (format.as_str(), path.as_path()))
+        })
+    }
+
+    /// First language as a two-letter code where one exists
+    pub fn language(&self) -> Option<String> {
+        self.languages.first().map(|code| {
+            let two_letter = match code.as_str() {
+                "eng" => "en",
+                "zho" | "chi" => "zh",
+                "fra" | "fre" => "fr",
+                "deu" | "ger" => "de",
+                "spa" => "es",
+                "ita" => "it",
+                "jpn" => "ja",
+                "kor" => "ko",
+                "rus" => "ru",
+                "por" => "pt",
+                "nld" | "dut" => "nl",
+                "pol" => "pl",
+                "swe" => "sv",
+                "ara" => "ar",
+                "tur" => "tr",
+                other => other,
+            };
+            two_letter.to_string()
+        })
+    }
+}
+
+pub struct CalibreLibrary {
+    root: PathBuf,
+    conn: Connection,
+}
+
+impl CalibreLibrary {
+    /// Opens the library at `path`, either its folder or its `metadata.db`
+    pub fn open(path: &str) -> Result<Self> {
+        let path = Path::new(path);
+        let (root, db_path) = if path.is_dir() {
+            (path.to_path_buf(), path.join("metadata.db"))
+        } else {
+            let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            (root, path.to_path_buf())
+        };
+        if !db_path.is_file() {
+            return Err(ReaderError::NotFound(db_path.display().to_string()));
+        }
+
+        // Calibre may be running; never write to its database
+        let conn = Connection::open_with_flags(
+            &db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let has_books: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'books')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_books {
+            return Err(ReaderError::InvalidArgument(format!(
+                "{} is not a Calibre library database",
+                db_path.display()
+            )));
+        }
+        Ok(Self { root, conn })
+    }
+
+    /// All books in the library, ordered by Calibre id
+    pub fn books(&self) -> Result<Vec<CalibreBook>> {
+        let authors = self.linked_names(
+            "SELECT l.book, a.name FROM books_authors_link l
+             JOIN authors a ON a.id = l.author ORDER BY l.book, l.id",
+        )?;
+        let tags = self.linked_names(
+            "SELECT l.book, t.name FROM books_tags_link l
+             JOIN tags t ON t.id = l.tag ORDER BY l.book, t.name",
+        )?;
+        let series = self.linked_names(
+            "SELECT l.book, s.name FROM books_series_link l
+             JOIN series s ON s.id = l.series",
+        )?;
+        let languages = self.linked_names(
+            "SELECT l.book, g.lang_code FROM books_languages_link l
+             JOIN languages g ON g.id = l.lang_code ORDER BY l.book, l.item_order",
+        )?;
+
+        let mut formats: HashMap<i64, Vec<(String, String)>> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare("SELECT book, format, name FROM data")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (book, format, name) = row?;
+                formats.entry(book).or_default().push((format, name));
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, title, path, series_index FROM books ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+            ))
+        })?;
+
+        let mut books = Vec::new();
+        for row in rows {
+            let (id, title, folder, series_index) = row?;
+            let series = series.get(&id).and_then(|names| names.first()).cloned();
+            let formats = formats
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .map(|(format, name)| {
+                    let format = format.to_uppercase();
+                    let file_name = format!("{}.{}", name, format.to_lowercase());
+                    (format, self.root.join(&folder).join(file_name))
+                })
+                .collect();
+            books.push(CalibreBook {
+                id,
+                title,
+                // Calibre swaps commas in author names for '|'
+                authors: authors
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .map(|name| name.replace('|', ","))
+                    .collect(),
+                series_index: series.as_ref().and(series_index),
+                series,
+                tags: tags.get(&id).cloned().unwrap_or_default(),
+                languages: languages.get(&id).cloned().unwrap_or_default(),
+                formats,
+            });
+        }
+        Ok(books)
+    }
+
+    /// Runs a `(book id, name)` query and groups the names by book
+    fn linked_names(&self, sql: &str) -> Result<HashMap<i64, Vec<String>>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut grouped: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (book, name) = row?;
+            grouped.entry(book).or_default().push(name);
+        }
+        Ok(grouped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CalibreLibrary;
+    use rusqlite::Connection;
+    use std::fs;
+
+    #[test]
+    fn reads_books_and_picks_best_format() {
+        let root = std::env::temp_dir().join(format!("reader_calibre_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let book_dir = root.join("Jane Austen").join("Emma (1)");
+        fs::create_dir_all(&book_dir).unwrap();
+        fs::write(book_dir.join("Emma - Jane Austen.epub"), b"epub").unwrap();
+        fs::write(book_dir.join("Emma - Jane Austen.pdf"), b"pdf").unwrap();
+
+        let conn = Connection::open(root.join("metadata.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE books (id INTEGER PRIMARY KEY, title TEXT, path TEXT, series_index REAL);
+             CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE books_authors_link (id INTEGER PRIMARY KEY, book INTEGER, author INTEGER);
+             CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE books_tags_link (id INTEGER PRIMARY KEY, book INTEGER, tag INTEGER);
+             CREATE TABLE series (id INTEGER PRIMARY KEY, name TEXT);
+             CREATE TABLE books_series_link (id INTEGER PRIMARY KEY, book INTEGER, series INTEGER);
+             CREATE TABLE languages (id INTEGER PRIMARY KEY, lang_code TEXT);
+             CREATE TABLE books_languages_link (id INTEGER PRIMARY KEY, book INTEGER, lang_code INTEGER, item_order INTEGER);
+             CREATE TABLE data (id INTEGER PRIMARY KEY, book INTEGER, format TEXT, name TEXT);
+             INSERT INTO books VALUES (1, 'Emma', 'Jane Austen/Emma (1)', 4.0), (2, 'Notes', 'X/Notes (2)', 1.0);
+             INSERT INTO authors VALUES (1, 'Jane Austen');
+             INSERT INTO books_authors_link VALUES (1, 1, 1);
+             INSERT INTO tags VALUES (1, 'Fiction'), (2, 'Classics');
+             INSERT INTO books_tags_link VALUES (1, 1, 1), (2, 1, 2);
+             INSERT INTO series VALUES (1, 'Austen Novels');
+             INSERT INTO books_series_link VALUES (1, 1, 1);
+             INSERT INTO languages VALUES (1, 'eng');
+             INSERT INTO books_languages_link VALUES (1, 1, 1, 0);
+             INSERT INTO data VALUES (1, 1, 'PDF', 'Emma - Jane Austen'), (2, 1, 'EPUB', 'Emma - Jane Austen'),
+                                     (3, 2, 'EPUB', 'Notes');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let library = CalibreLibrary::open(root.to_str().unwrap()).unwrap();
+        let books = library.books().unwrap();
+        let best = books[0].best_format().map(|(format, _)| format.to_string());
+        let missing = books[1].best_format().map(|(format, _)| format.to_string());
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(books.len(), 2);
+        let emma = &books[0];
+        assert_eq!(emma.authors, vec!["Jane Austen"]);
+        assert_eq!(emma.tags, vec!["Classics", "Fiction"]);
+        assert_eq!(emma.series.as_deref(), Some("Austen Novels"));
+        assert_eq!(emma.series_index, Some(4.0));
+        assert_eq!(emma.language().as_deref(), Some("en"));
+        assert_eq!(best.as_deref(), Some("EPUB"));
+        // Listed in the database but not on disk
+        assert_eq!(missing, None);
+        // No series link, so the default series_index is dropped
+        assert_eq!(books[1].series, None);
+        assert_eq!(books[1].series_index, None);
+    }
+}
@@ -0,0 +1,159 @@
+//! Picks the parser for a file from its extension and leading bytes
+//!
+//! Binary formats are recognised by content, so a mislabelled or
+//! extensionless EPUB, PDF or MOBI still finds its parser. Text formats can
+//! only be told apart by extension.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file for sniffing
+const SNIFF_LEN: usize = 1024;
+
+/// Format key for [`detect_format`], as understood by the importer
+/// (`epub`, `pdf`, `mobi`, `docx`, `fb2`, `html`, `md`, `txt`, ...)
+pub fn detect_format(path: &Path) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)
+        .ok()?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    if let Some(format) = sniff_binary(path, &head) {
+        return Some(format);
+    }
+
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".fb2.zip") {
+        return Some("fb2");
+    }
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+    match extension {
+        "md" | "markdown" => Some("md"),
+        "txt" => Some("txt"),
+        "rst" => Some("rst"),
+        "adoc" | "asciidoc" => Some("adoc"),
+        "ipynb" => Some("ipynb"),
+        "fb2" => Some("fb2"),
+        "html" | "htm" | "xhtml" => Some("html"),
+        "mhtml" | "mht" => Some("mhtml"),
+        // Binary formats whose content didn't match are not worth parsing
+        "epub" | "pdf" | "mobi" | "azw" | "azw3" | "docx" => None,
+        _ => sniff_text(&head),
+    }
+}
+
+fn sniff_binary(path: &Path, head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        return Some("pdf");
+    }
+    if head.len() >= 68 && &head[60..68] == b"BOOKMOBI" {
+        return Some("mobi");
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        // EPUB stores an uncompressed `mimetype` entry first
+        if head.len() >= 58 && &head[30..58] == b"mimetypeapplication/epub+zip" {
+            return Some("epub");
+        }
+        return sniff_zip(path);
+    }
+    None
+}
+
+fn sniff_zip(path: &Path) -> Option<&'static str> {
+    let archive = zip::ZipArchive::new(File::open(path).ok()?).ok()?;
+    let names: Vec<&str> = archive.file_names().collect();
+    if names.contains(&"word/document.xml") {
+        Some("docx")
+    } else if names.contains(&"META-INF/container.xml") {
+        Some("epub")
+    } else if names
+        .iter()
+        .any(|name| name.to_lowercase().ends_with(".fb2"))
+    {
+        Some("fb2")
+    } else {
+        None
+    }
+}
+
+/// Formats recognisable from text content alone
+fn sniff_text(head: &[u8]) -> Option<&'static str> {
+    let text = String::from_utf8_lossy(head);
+    let start = text.trim_start_matches('\u{feff}').trim_start();
+    let lower = start
+        .get(..start.len().min(512))
+        .unwrap_or(start)
+        .to_lowercase();
+    if lower.contains("<fictionbook") {
+        Some("fb2")
+    } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        Some("html")
+    } else if lower.starts_with("mime-version:") || lower.starts_with("from: <saved by") {
+        Some("mhtml")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect_format;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn detects_formats_by_content_and_extension() {
+        let dir = std::env::temp_dir().join(format!("reader_detect_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.join(name);
+            fs::write(&path, bytes).unwrap();
+            path
+        };
+
+        // A PDF with the wrong extension is still a PDF
+        let pdf = write("scan.txt", b"%PDF-1.7\n%....");
+        let mut mobi_bytes = vec![0u8; 60];
+        mobi_bytes.extend_from_slice(b"BOOKMOBI");
+        mobi_bytes.extend_from_slice(&[0u8; 16]);
+        let mobi = write("book", &mobi_bytes);
+        let markdown = write("notes.MD", b"# Notes");
+        let fb2 = write("tale", b"<?xml version=\"1.0\"?><FictionBook xmlns=\"x\">");
+        let fake_epub = write("broken.epub", b"not a zip");
+        let unknown = write("data.bin", &[0u8, 1, 2, 3]);
+
+        let docx = dir.join("letter");
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&docx).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            writer.start_file("[Content_Types].xml", options).unwrap();
+            writer.write_all(b"<Types/>").unwrap();
+            writer.start_file("word/document.xml", options).unwrap();
+            writer.write_all(b"<w:document/>").unwrap();
+            writer.finish().unwrap();
+        }
+
+        let detected: Vec<Option<&str>> =
+            [&pdf, &mobi, &markdown, &fb2, &fake_epub, &unknown, &docx]
+                .iter()
+                .map(|path| detect_format(path))
+                .collect();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            detected,
+            vec![
+                Some("pdf"),
+                Some("mobi"),
+                Some("md"),
+                Some("fb2"),
+                None,
+                None,
+                Some("docx")
+            ]
+        );
+    }
+}
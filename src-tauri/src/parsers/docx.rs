@@ -0,0 +1,1057 @@
+//! Word (DOCX) documents
+//!
+//! Reads the body paragraphs and tables of `word/document.xml`, splits the
+//! document into sections at Heading 1/Heading 2 paragraphs (resolved through
+//! `word/styles.xml`), takes title and author from `docProps/core.xml` and
+//! extracts embedded images next to the other parser image output. List items
+//! get `- ` or `1. ` markers from `word/numbering.xml`, and footnotes are
+//! collected into a closing "Notes" section as `[n] text`.
+
+use super::xml_text::{attribute, push_entity};
+use super::{image_output_dir, sanitize_filename, IMAGE_MARKER_PREFIX};
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+const DOCUMENT_PART: &str = "word/document.xml";
+const STYLES_PART: &str = "word/styles.xml";
+const RELATIONSHIPS_PART: &str = "word/_rels/document.xml.rels";
+const CORE_PROPERTIES_PART: &str = "docProps/core.xml";
+const NUMBERING_PART: &str = "word/numbering.xml";
+const FOOTNOTES_PART: &str = "word/footnotes.xml";
+const EMPTY_DOCX_PLACEHOLDER: &str = "No readable content extracted from DOCX file.";
+const LEADING_SECTION_TITLE: &str = "Content";
+const NOTES_SECTION_TITLE: &str = "Notes";
+/// Outline levels below this (Heading 1 and Heading 2) start a new section
+const SECTION_OUTLINE_LEVELS: u8 = 2;
+const MAX_STYLE_INHERITANCE: usize = 16;
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg"];
+
+pub struct DocxParser {
+    file_path: String,
+    archive: ZipArchive<File>,
+}
+
+/// How a paragraph style affects the document structure
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StyleRole {
+    Title,
+    /// Zero-based outline level, 0 for Heading 1
+    Heading(u8),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DocxBlock {
+    Paragraph {
+        text: String,
+        role: Option<StyleRole>,
+    },
+    /// Relationship id of an embedded image
+    Image(String),
+}
+
+/// How one level of a numbering definition marks its items
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ListLevel {
+    bullet: bool,
+    start: usize,
+}
+
+/// List levels keyed by numbering instance id (`w:numId`) and level (`w:ilvl`)
+type Numbering = HashMap<String, HashMap<u8, ListLevel>>;
+
+#[derive(Debug, Default)]
+struct CoreProperties {
+    title: Option<String>,
+    creator: Option<String>,
+    language: Option<String>,
+}
+
+impl DocxParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+
+        let file = File::open(path)?;
+        let archive = ZipArchive::new(file)
+            .map_err(|e| ReaderError::DocxParse(format!("Failed to open DOCX: {}", e)))?;
+
+        Ok(Self {
+            file_path: file_path.to_string(),
+            archive,
+        })
+    }
+
+    fn read_part(&mut self, name: &str) -> Result<Option<String>> {
+        let mut entry = match self.archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => {
+                return Err(ReaderError::DocxParse(format!(
+                    "Failed to read {}: {}",
+                    name, e
+                )))
+            }
+        };
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        Ok(Some(content))
+    }
+
+    pub fn parse_all(&mut self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let document = self.read_part(DOCUMENT_PART)?.ok_or_else(|| {
+            ReaderError::DocxParse(format!("{} is missing, not a Word document", DOCUMENT_PART))
+        })?;
+        let styles = self
+            .read_part(STYLES_PART)?
+            .map(|xml| parse_styles(&xml))
+            .unwrap_or_default();
+        let relationships = self
+            .read_part(RELATIONSHIPS_PART)?
+            .map(|xml| parse_relationships(&xml))
+            .unwrap_or_default();
+        let core = self
+            .read_part(CORE_PROPERTIES_PART)?
+            .map(|xml| parse_core_properties(&xml))
+            .unwrap_or_default();
+        let numbering = self
+            .read_part(NUMBERING_PART)?
+            .map(|xml| parse_numbering(&xml))
+            .unwrap_or_default();
+        let footnotes = self
+            .read_part(FOOTNOTES_PART)?
+            .map(|xml| parse_footnotes(&xml))
+            .unwrap_or_default();
+
+        let blocks = parse_document(&document, &styles, &numbering)
+            .map_err(|e| ReaderError::DocxParse(format!("Invalid {}: {}", DOCUMENT_PART, e)))?;
+
+        let image_output_dir = image_output_dir(&self.file_path, "docx");
+        let _ = fs::remove_dir_all(&image_output_dir);
+        let mut image_markers: HashMap<String, Option<String>> = HashMap::new();
+        for block in &blocks {
+            if let DocxBlock::Image(rel_id) = block {
+                if image_markers.contains_key(rel_id) {
+                    continue;
+                }
+                let marker = relationships
+                    .get(rel_id)
+                    .and_then(|target| self.extract_image(target, &image_output_dir));
+                image_markers.insert(rel_id.clone(), marker);
+            }
+        }
+
+        let title = core
+            .title
+            .clone()
+            .or_else(|| {
+                blocks.iter().find_map(|block| match block {
+                    DocxBlock::Paragraph {
+                        text,
+                        role: Some(StyleRole::Title),
+                    } => Some(text.clone()),
+                    _ => None,
+                })
+            })
+            .unwrap_or_else(|| {
+                Path::new(&self.file_path)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Untitled")
+                    .to_string()
+            });
+
+        let metadata = NewDocument {
+            title,
+            author: core.creator,
+            language: core.language,
+            file_path: self.file_path.clone(),
+            file_type: "docx".to_string(),
+        };
+
+        let mut sections = build_sections(&blocks, &image_markers);
+        if !footnotes.is_empty() {
+            let order = sections.len() as i32;
+            sections.push((
+                NOTES_SECTION_TITLE.to_string(),
+                order,
+                format!("section{}", order + 1),
+                footnotes
+                    .into_iter()
+                    .map(|(id, text)| format!("[{}] {}", id, text))
+                    .collect(),
+            ));
+        }
+        Ok((metadata, sections))
+    }
+
+    /// Copies an image part out of the archive and returns its marker line
+    fn extract_image(&mut self, target: &str, output_dir: &Path) -> Option<String> {
+        let extension = Path::new(target)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())?;
+        if !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            tracing::debug!("Skipping unsupported DOCX image format: {}", target);
+            return None;
+        }
+
+        let mut bytes = Vec::new();
+        match self.archive.by_name(target) {
+            Ok(mut entry) => entry.read_to_end(&mut bytes).ok()?,
+            Err(e) => {
+                tracing::warn!("DOCX image {} not found: {}", target, e);
+                return None;
+            }
+        };
+
+        let file_name = Path::new(target).file_name()?.to_string_lossy().to_string();
+        fs::create_dir_all(output_dir).ok()?;
+        let image_path = output_dir.join(sanitize_filename(&file_name));
+        fs::write(&image_path, bytes).ok()?;
+
+        Some(format!(
+            "{prefix}{path}]]",
+            prefix = IMAGE_MARKER_PREFIX,
+            path = image_path.to_string_lossy()
+        ))
+    }
+}
+
+/// Groups blocks into sections starting at Heading 1/Heading 2 paragraphs
+///
+/// The heading stays in its section's body, like the Markdown importer does.
+/// Content before the first heading goes into a leading "Content" section.
+fn build_sections(
+    blocks: &[DocxBlock],
+    image_markers: &HashMap<String, Option<String>>,
+) -> Vec<(String, i32, String, Vec<String>)> {
+    let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+    let mut current_title = LEADING_SECTION_TITLE.to_string();
+    let mut current: Vec<String> = Vec::new();
+
+    let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
+                        title: &str,
+                        paragraphs: Vec<String>| {
+        let order = sections.len() as i32;
+        sections.push((
+            title.to_string(),
+            order,
+            format!("section{}", order + 1),
+            paragraphs,
+        ));
+    };
+
+    for block in blocks {
+        match block {
+            DocxBlock::Paragraph { text, role } => {
+                let starts_section = matches!(
+                    role,
+                    Some(StyleRole::Heading(level)) if *level < SECTION_OUTLINE_LEVELS
+                );
+                if starts_section {
+                    if !current.is_empty() {
+                        push_section(&mut sections, &current_title, std::mem::take(&mut current));
+                    }
+                    current_title = text.clone();
+                }
+                current.push(text.clone());
+            }
+            DocxBlock::Image(rel_id) => {
+                if let Some(Some(marker)) = image_markers.get(rel_id) {
+                    current.push(marker.clone());
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() || sections.is_empty() {
+        if current.is_empty() {
+            current.push(EMPTY_DOCX_PLACEHOLDER.to_string());
+        }
+        push_section(&mut sections, &current_title, current);
+    }
+
+    sections
+}
+
+/// Walks the document body into paragraphs, table rows and images
+///
+/// Table rows become one paragraph with the cell texts joined by ` | `.
+/// List items are prefixed with their marker, indented two spaces per level,
+/// and footnote references become `[n]`. Deleted revisions and field
+/// instructions are skipped.
+fn parse_document(
+    xml: &str,
+    styles: &HashMap<String, StyleRole>,
+    numbering: &Numbering,
+) -> std::result::Result<Vec<DocxBlock>, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut blocks = Vec::new();
+
+    let mut text = String::new();
+    let mut style_id: Option<String> = None;
+    let mut outline_level: Option<u8> = None;
+    let mut num_id: Option<String> = None;
+    let mut list_level = 0u8;
+    let mut images: Vec<String> = Vec::new();
+    let mut in_text = false;
+    // Next number of each (numId, level), reset when a shallower item appears
+    let mut counters: HashMap<(String, u8), usize> = HashMap::new();
+
+    let mut table_depth = 0usize;
+    let mut row_cells: Vec<String> = Vec::new();
+    let mut cell_text = String::new();
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"p" => {
+                        text.clear();
+                        style_id = None;
+                        outline_level = None;
+                        num_id = None;
+                        list_level = 0;
+                        images.clear();
+                    }
+                    b"pStyle" => style_id = attribute(e, b"val"),
+                    b"numId" => num_id = attribute(e, b"val").filter(|id| id != "0"),
+                    b"ilvl" => {
+                        list_level = attribute(e, b"val")
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(0)
+                    }
+                    b"footnoteReference" => {
+                        if let Some(id) = attribute(e, b"id") {
+                            text.push_str(&format!("[{}]", id));
+                        }
+                    }
+                    b"outlineLvl" => {
+                        outline_level = attribute(e, b"val").and_then(|v| v.parse().ok())
+                    }
+                    b"t" if !is_empty => in_text = true,
+                    b"tab" => text.push(' '),
+                    b"br" | b"cr" => text.push('\n'),
+                    b"noBreakHyphen" => text.push('-'),
+                    b"blip" => images.extend(attribute(e, b"embed")),
+                    b"imagedata" => images.extend(attribute(e, b"id")),
+                    b"del" | b"instrText" | b"delInstrText" if !is_empty => {
+                        reader.read_to_end(e.name())?;
+                    }
+                    b"tbl" if !is_empty => table_depth += 1,
+                    b"tr" if table_depth == 1 && !is_empty => row_cells.clear(),
+                    b"tc" if table_depth == 1 && !is_empty => cell_text.clear(),
+                    _ => {}
+                }
+            }
+            Event::Text(e) if in_text => text.push_str(&e.decode()?),
+            Event::GeneralRef(e) if in_text => push_entity(&mut text, e)?,
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let paragraph = normalize_paragraph(&text);
+                    if table_depth > 0 {
+                        if !paragraph.is_empty() {
+                            if !cell_text.is_empty() {
+                                cell_text.push(' ');
+                            }
+                            cell_text.push_str(&paragraph);
+                        }
+                    } else if !paragraph.is_empty() {
+                        let role = outline_level
+                            .filter(|level| *level < 9)
+                            .map(StyleRole::Heading)
+                            .or_else(|| style_id.as_ref().and_then(|id| style_role(id, styles)));
+                        let text = match (&num_id, role) {
+                            (Some(id), None) => {
+                                let marker = list_marker(numbering, &mut counters, id, list_level);
+                                format!(
+                                    "{}{} {}",
+                                    "  ".repeat(list_level as usize),
+                                    marker,
+                                    paragraph
+                                )
+                            }
+                            _ => paragraph,
+                        };
+                        blocks.push(DocxBlock::Paragraph { text, role });
+                    }
+                    blocks.extend(images.drain(..).map(DocxBlock::Image));
+                }
+                b"tc" if table_depth == 1 => row_cells.push(std::mem::take(&mut cell_text)),
+                b"tr" if table_depth == 1 => {
+                    if row_cells.iter().any(|cell| !cell.is_empty()) {
+                        blocks.push(DocxBlock::Paragraph {
+                            text: row_cells.join(" | "),
+                            role: None,
+                        });
+                    }
+                    row_cells.clear();
+                }
+                b"tbl" => table_depth = table_depth.saturating_sub(1),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Marker of the next item at `level` of list `num_id`: `-` or `n.`
+///
+/// Lists missing from numbering.xml are treated as bulleted.
+fn list_marker(
+    numbering: &Numbering,
+    counters: &mut HashMap<(String, u8), usize>,
+    num_id: &str,
+    level: u8,
+) -> String {
+    let definition = numbering
+        .get(num_id)
+        .and_then(|levels| levels.get(&level))
+        .copied()
+        .unwrap_or(ListLevel {
+            bullet: true,
+            start: 1,
+        });
+    counters.retain(|(id, counted), _| id != num_id || *counted <= level);
+    if definition.bullet {
+        return "-".to_string();
+    }
+    let next = counters
+        .entry((num_id.to_string(), level))
+        .or_insert(definition.start);
+    let marker = format!("{}.", next);
+    *next += 1;
+    marker
+}
+
+fn normalize_paragraph(text: &str) -> String {
+    text.split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Maps paragraph style ids to their structural role
+///
+/// A style is a heading if it declares an outline level or is named
+/// "heading N"; otherwise it inherits the role of its `basedOn` style.
+fn parse_styles(xml: &str) -> HashMap<String, StyleRole> {
+    struct StyleDef {
+        name: Option<String>,
+        based_on: Option<String>,
+        outline_level: Option<u8>,
+    }
+
+    let mut reader = Reader::from_str(xml);
+    let mut defs: HashMap<String, StyleDef> = HashMap::new();
+    let mut current: Option<(String, StyleDef)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"style" => {
+                let is_paragraph = attribute(&e, b"type").is_none_or(|t| t == "paragraph");
+                current = attribute(&e, b"styleId")
+                    .filter(|_| is_paragraph)
+                    .map(|id| {
+                        (
+                            id,
+                            StyleDef {
+                                name: None,
+                                based_on: None,
+                                outline_level: None,
+                            },
+                        )
+                    });
+            }
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                if let Some((_, def)) = current.as_mut() {
+                    match e.local_name().as_ref() {
+                        b"name" => def.name = attribute(&e, b"val"),
+                        b"basedOn" => def.based_on = attribute(&e, b"val"),
+                        b"outlineLvl" => {
+                            def.outline_level = attribute(&e, b"val").and_then(|v| v.parse().ok())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"style" => {
+                if let Some((id, def)) = current.take() {
+                    defs.insert(id, def);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let mut roles = HashMap::new();
+    for id in defs.keys() {
+        let mut style_id = id.as_str();
+        for _ in 0..MAX_STYLE_INHERITANCE {
+            let Some(def) = defs.get(style_id) else {
+                break;
+            };
+            let role = def
+                .outline_level
+                .filter(|level| *level < 9)
+                .map(StyleRole::Heading)
+                .or_else(|| def.name.as_deref().and_then(role_from_style_name));
+            if let Some(role) = role {
+                roles.insert(id.clone(), role);
+                break;
+            }
+            match def.based_on.as_deref() {
+                Some(parent) => style_id = parent,
+                None => break,
+            }
+        }
+    }
+    roles
+}
+
+/// Role of a style id, falling back to built-in names when styles.xml lacks it
+fn style_role(style_id: &str, styles: &HashMap<String, StyleRole>) -> Option<StyleRole> {
+    styles
+        .get(style_id)
+        .copied()
+        .or_else(|| role_from_style_name(style_id))
+}
+
+/// Recognises "Title", "heading 2" and style ids like "Heading2"
+fn role_from_style_name(name: &str) -> Option<StyleRole> {
+    let lower = name.trim().to_ascii_lowercase();
+    if lower == "title" {
+        return Some(StyleRole::Title);
+    }
+    let level: u8 = lower.strip_prefix("heading")?.trim().parse().ok()?;
+    (1..=9)
+        .contains(&level)
+        .then(|| StyleRole::Heading(level - 1))
+}
+
+/// Reads the list levels of each numbering instance
+///
+/// Instances (`w:num`) point at an abstract definition holding the levels;
+/// level overrides are not applied.
+fn parse_numbering(xml: &str) -> Numbering {
+    let mut reader = Reader::from_str(xml);
+    let mut abstract_levels: HashMap<String, HashMap<u8, ListLevel>> = HashMap::new();
+    let mut instances: Vec<(String, String)> = Vec::new();
+    let mut abstract_id: Option<String> = None;
+    let mut level: Option<(u8, ListLevel)> = None;
+    let mut num_id: Option<String> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"abstractNum" => abstract_id = attribute(&e, b"abstractNumId"),
+                b"lvl" if abstract_id.is_some() => {
+                    level = attribute(&e, b"ilvl")
+                        .and_then(|v| v.parse().ok())
+                        .map(|ilvl| {
+                            (
+                                ilvl,
+                                ListLevel {
+                                    bullet: false,
+                                    start: 1,
+                                },
+                            )
+                        });
+                }
+                b"numFmt" => {
+                    if let Some((_, def)) = level.as_mut() {
+                        def.bullet = attribute(&e, b"val").is_some_and(|fmt| fmt == "bullet");
+                    }
+                }
+                b"start" => {
+                    if let Some((_, def)) = level.as_mut() {
+                        def.start = attribute(&e, b"val")
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(1);
+                    }
+                }
+                b"num" => num_id = attribute(&e, b"numId"),
+                b"abstractNumId" => {
+                    if let (Some(num), Some(target)) = (num_id.as_ref(), attribute(&e, b"val")) {
+                        instances.push((num.clone(), target));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"lvl" => {
+                    if let (Some(id), Some((ilvl, def))) = (abstract_id.as_ref(), level.take()) {
+                        abstract_levels
+                            .entry(id.clone())
+                            .or_default()
+                            .insert(ilvl, def);
+                    }
+                }
+                b"abstractNum" => abstract_id = None,
+                b"num" => num_id = None,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    instances
+        .into_iter()
+        .filter_map(|(num, target)| Some((num, abstract_levels.get(&target)?.clone())))
+        .collect()
+}
+
+/// Reads the footnotes as (id, text), skipping the separator entries
+fn parse_footnotes(xml: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_str(xml);
+    let mut footnotes = Vec::new();
+    let mut current: Option<String> = None;
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"t" => in_text = true,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"footnote" => {
+                    let is_note = attribute(&e, b"type").is_none_or(|t| t == "normal");
+                    current = attribute(&e, b"id").filter(|_| is_note);
+                    paragraphs.clear();
+                }
+                b"p" => text.clear(),
+                b"tab" | b"br" | b"cr" => text.push(' '),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_text => {
+                if let Ok(decoded) = e.decode() {
+                    text.push_str(&decoded);
+                }
+            }
+            Ok(Event::GeneralRef(e)) if in_text => {
+                let _ = push_entity(&mut text, &e);
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let paragraph = normalize_paragraph(&text);
+                    if !paragraph.is_empty() {
+                        paragraphs.push(paragraph);
+                    }
+                }
+                b"footnote" => {
+                    if let Some(id) = current.take() {
+                        if !paragraphs.is_empty() {
+                            footnotes.push((id, paragraphs.join(" ")));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    footnotes
+}
+
+/// Maps relationship ids to archive paths of internal targets
+fn parse_relationships(xml: &str) -> HashMap<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut relationships = HashMap::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"Relationship" =>
+            {
+                if attribute(&e, b"TargetMode").is_some_and(|mode| mode == "External") {
+                    continue;
+                }
+                if let (Some(id), Some(target)) = (attribute(&e, b"Id"), attribute(&e, b"Target")) {
+                    relationships.insert(id, resolve_part_path("word", &target));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    relationships
+}
+
+/// Resolves a relationship target against the directory of its source part
+fn resolve_part_path(base_dir: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    } else {
+        base_dir.split('/').filter(|p| !p.is_empty()).collect()
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            other => parts.push(other),
+        }
+    }
+    parts.join("/")
+}
+
+fn parse_core_properties(xml: &str) -> CoreProperties {
+    let mut reader = Reader::from_str(xml);
+    let mut properties = CoreProperties::default();
+    let mut field: Option<Vec<u8>> = None;
+    let mut value = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                field = Some(e.local_name().as_ref().to_vec());
+                value.clear();
+            }
+            Ok(Event::Text(e)) if field.is_some() => {
+                if let Ok(text) = e.decode() {
+                    value.push_str(&text);
+                }
+            }
+            Ok(Event::GeneralRef(e)) if field.is_some() => {
+                let _ = push_entity(&mut value, &e);
+            }
+            Ok(Event::End(_)) => {
+                let trimmed = value.trim();
+                let text = (!trimmed.is_empty()).then(|| trimmed.to_string());
+                match field.take().as_deref() {
+                    Some(b"title") => properties.title = properties.title.take().or(text),
+                    Some(b"creator") => properties.creator = properties.creator.take().or(text),
+                    Some(b"language") => properties.language = properties.language.take().or(text),
+                    _ => {}
+                }
+                value.clear();
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_document, parse_footnotes, parse_numbering, parse_relationships, DocxBlock,
+        DocxParser,
+    };
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    const DOCUMENT_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+  xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+  xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<w:body>
+  <w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Quarterly Notes</w:t></w:r></w:p>
+  <w:p><w:r><w:t xml:space="preserve">Read this </w:t></w:r><w:r><w:t>first &amp; foremost.</w:t></w:r></w:p>
+  <w:p><w:pPr><w:pStyle w:val="ChapterHeading"/></w:pPr><w:r><w:t>Results</w:t></w:r></w:p>
+  <w:p><w:r><w:t>Revenue grew.</w:t></w:r><w:del><w:r><w:delText>Old claim.</w:delText></w:r></w:del></w:p>
+  <w:p><w:pPr><w:pStyle w:val="Heading3"/></w:pPr><w:r><w:t>Details</w:t></w:r></w:p>
+  <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Q1</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>12</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+  <w:p><w:r><w:drawing><a:graphic><a:graphicData><a:blip r:embed="rId7"/></a:graphicData></a:graphic></w:drawing></w:r></w:p>
+  <w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Outlook</w:t></w:r></w:p>
+  <w:p><w:r><w:t>Line one</w:t><w:br/><w:t>line two</w:t></w:r></w:p>
+</w:body>
+</w:document>"#;
+
+    const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:pPr><w:outlineLvl w:val="0"/></w:pPr></w:style>
+  <w:style w:type="paragraph" w:styleId="ChapterHeading"><w:name w:val="Chapter Heading"/><w:basedOn w:val="Heading1"/></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/></w:style>
+</w:styles>"#;
+
+    const RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/>
+  <Relationship Id="rId8" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/>
+</Relationships>"#;
+
+    const CORE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties"
+  xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <dc:title>Q3 Report</dc:title><dc:creator>Ada Lovelace</dc:creator><dc:language>en-GB</dc:language>
+</cp:coreProperties>"#;
+
+    const NUMBERING_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/></w:lvl>
+    <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/></w:lvl>
+  </w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+</w:numbering>"#;
+
+    const FOOTNOTES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:footnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+  <w:footnote w:type="continuationSeparator" w:id="0"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>
+  <w:footnote w:id="1"><w:p><w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> Smith, 2019.</w:t></w:r></w:p></w:footnote>
+  <w:footnote w:id="2"><w:p><w:r><w:t>See above.</w:t></w:r></w:p><w:p><w:r><w:t>Also &amp; below.</w:t></w:r></w:p></w:footnote>
+</w:footnotes>"#;
+
+    fn body(content: &str) -> String {
+        format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+  xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+  xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+  xmlns:v="urn:schemas-microsoft-com:vml"><w:body>{}</w:body></w:document>"#,
+            content
+        )
+    }
+
+    fn list_item(num_id: &str, level: u8, text: &str) -> String {
+        format!(
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr></w:pPr><w:r><w:t>{}</w:t></w:r></w:p>"#,
+            level, num_id, text
+        )
+    }
+
+    fn paragraphs(blocks: &[DocxBlock]) -> Vec<&str> {
+        blocks
+            .iter()
+            .filter_map(|block| match block {
+                DocxBlock::Paragraph { text, .. } => Some(text.as_str()),
+                DocxBlock::Image(_) => None,
+            })
+            .collect()
+    }
+
+    fn write_docx(file_name: &str, parts: &[(&str, &[u8])]) -> PathBuf {
+        let path = std::env::temp_dir().join(file_name);
+        let mut zip = ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, content) in parts {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    fn write_fixture_docx() -> PathBuf {
+        write_docx(
+            "reader_docx_fixture_test.docx",
+            &[
+                ("word/document.xml", DOCUMENT_XML.as_bytes()),
+                ("word/styles.xml", STYLES_XML.as_bytes()),
+                ("word/_rels/document.xml.rels", RELS_XML.as_bytes()),
+                ("docProps/core.xml", CORE_XML.as_bytes()),
+                ("word/media/image1.png", b"\x89PNG\r\n\x1a\n".as_slice()),
+            ],
+        )
+    }
+
+    #[test]
+    fn parses_sections_metadata_and_images() {
+        let path = write_fixture_docx();
+        let mut parser = DocxParser::new(path.to_str().unwrap()).unwrap();
+        let (metadata, sections) = parser.parse_all().unwrap();
+
+        assert_eq!(metadata.title, "Q3 Report");
+        assert_eq!(metadata.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(metadata.language.as_deref(), Some("en-GB"));
+        assert_eq!(metadata.file_type, "docx");
+
+        let titles: Vec<_> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(titles, vec!["Content", "Results", "Outlook"]);
+        assert_eq!(
+            sections[0].3,
+            vec!["Quarterly Notes", "Read this first & foremost."]
+        );
+        assert_eq!(
+            &sections[1].3[..4],
+            &["Results", "Revenue grew.", "Details", "Q1 | 12"]
+        );
+        assert!(sections[1].3[4].starts_with("[[PDF_IMAGE:"));
+        assert!(sections[1].3[4].ends_with("image1.png]]"));
+        assert_eq!(sections[2].3, vec!["Outlook", "Line one\nline two"]);
+        assert_eq!(sections[2].2, "section3");
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn numbers_list_items_by_level() {
+        let numbering = parse_numbering(NUMBERING_XML);
+        let xml = body(
+            &[
+                list_item("1", 0, "First"),
+                list_item("1", 1, "Detail a"),
+                list_item("1", 1, "Detail b"),
+                list_item("1", 0, "Second"),
+                list_item("1", 1, "Detail again"),
+                list_item("2", 0, "Bullet"),
+                list_item("9", 0, "Unknown list"),
+                list_item("0", 0, "Not a list"),
+            ]
+            .concat(),
+        );
+        let blocks = parse_document(&xml, &HashMap::new(), &numbering).unwrap();
+
+        assert_eq!(
+            paragraphs(&blocks),
+            vec![
+                "1. First",
+                "  1. Detail a",
+                "  2. Detail b",
+                "2. Second",
+                "  1. Detail again",
+                "- Bullet",
+                "- Unknown list",
+                "Not a list",
+            ]
+        );
+    }
+
+    #[test]
+    fn joins_table_cells_into_rows() {
+        let xml = body(
+            r#"<w:tbl>
+  <w:tr><w:tc><w:p><w:r><w:t>Name</w:t></w:r></w:p><w:p><w:r><w:t>(full)</w:t></w:r></w:p></w:tc>
+        <w:tc><w:tbl><w:tr><w:tc><w:p><w:r><w:t>nested</w:t></w:r></w:p></w:tc></w:tr></w:tbl></w:tc></w:tr>
+  <w:tr><w:tc><w:p/></w:tc><w:tc><w:p/></w:tc></w:tr>
+  <w:tr><w:tc><w:p><w:r><w:t>Ada</w:t></w:r></w:p></w:tc><w:tc><w:p/></w:tc></w:tr>
+</w:tbl>
+<w:p><w:r><w:t>After the table.</w:t></w:r></w:p>"#,
+        );
+        let blocks = parse_document(&xml, &HashMap::new(), &HashMap::new()).unwrap();
+
+        assert_eq!(
+            paragraphs(&blocks),
+            vec!["Name (full) | nested", "Ada | ", "After the table."]
+        );
+    }
+
+    #[test]
+    fn reads_footnotes_and_their_references() {
+        assert_eq!(
+            parse_footnotes(FOOTNOTES_XML),
+            vec![
+                ("1".to_string(), "Smith, 2019.".to_string()),
+                ("2".to_string(), "See above. Also & below.".to_string()),
+            ]
+        );
+
+        let xml = body(
+            r#"<w:p><w:r><w:t>Claim</w:t></w:r><w:r><w:footnoteReference w:id="1"/></w:r><w:r><w:t xml:space="preserve"> holds.</w:t></w:r></w:p>"#,
+        );
+        let blocks = parse_document(&xml, &HashMap::new(), &HashMap::new()).unwrap();
+        assert_eq!(paragraphs(&blocks), vec!["Claim[1] holds."]);
+    }
+
+    #[test]
+    fn places_images_after_their_paragraph() {
+        let xml = body(
+            r#"<w:p><w:r><w:t>Figure</w:t></w:r><w:r><w:drawing><a:blip r:embed="rId1"/></w:drawing></w:r></w:p>
+<w:p><w:r><w:pict><v:imagedata r:id="rId2"/></w:pict></w:r></w:p>"#,
+        );
+        let blocks = parse_document(&xml, &HashMap::new(), &HashMap::new()).unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                DocxBlock::Paragraph {
+                    text: "Figure".to_string(),
+                    role: None,
+                },
+                DocxBlock::Image("rId1".to_string()),
+                DocxBlock::Image("rId2".to_string()),
+            ]
+        );
+
+        let relationships = parse_relationships(
+            r#"<Relationships>
+  <Relationship Id="rId1" Target="media/one.png"/>
+  <Relationship Id="rId2" Target="../customXml/two.jpeg"/>
+  <Relationship Id="rId3" Target="https://example.com/x.png" TargetMode="External"/>
+</Relationships>"#,
+        );
+        assert_eq!(relationships["rId1"], "word/media/one.png");
+        assert_eq!(relationships["rId2"], "customXml/two.jpeg");
+        assert!(!relationships.contains_key("rId3"));
+    }
+
+    #[test]
+    fn parses_lists_footnotes_and_images_from_archive() {
+        let document = body(
+            &[
+                r#"<w:p><w:r><w:t>Intro</w:t></w:r><w:r><w:footnoteReference w:id="2"/></w:r></w:p>"#,
+                &list_item("1", 0, "Step"),
+                r#"<w:p><w:r><w:drawing><a:blip r:embed="rId1"/></w:drawing></w:r></w:p>"#,
+                r#"<w:p><w:r><w:drawing><a:blip r:embed="rId2"/></w:drawing></w:r></w:p>"#,
+            ]
+            .concat(),
+        );
+        let rels = r#"<Relationships>
+  <Relationship Id="rId1" Target="media/chart.png"/>
+  <Relationship Id="rId2" Target="media/legacy.emf"/>
+</Relationships>"#;
+        let path = write_docx(
+            "reader_docx_lists_test.docx",
+            &[
+                ("word/document.xml", document.as_bytes()),
+                ("word/numbering.xml", NUMBERING_XML.as_bytes()),
+                ("word/footnotes.xml", FOOTNOTES_XML.as_bytes()),
+                ("word/_rels/document.xml.rels", rels.as_bytes()),
+                ("word/media/chart.png", b"\x89PNG\r\n\x1a\n".as_slice()),
+                ("word/media/legacy.emf", b"EMF".as_slice()),
+            ],
+        );
+        let mut parser = DocxParser::new(path.to_str().unwrap()).unwrap();
+        let (metadata, sections) = parser.parse_all().unwrap();
+
+        assert_eq!(metadata.title, "reader_docx_lists_test");
+        let titles: Vec<_> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(titles, vec!["Content", "Notes"]);
+        // The unsupported EMF image is left out
+        assert_eq!(sections[0].3.len(), 3);
+        assert_eq!(&sections[0].3[..2], &["Intro[2]", "1. Step"]);
+        assert!(sections[0].3[2].ends_with("chart.png]]"));
+        assert!(std::path::Path::new(
+            sections[0].3[2]
+                .trim_start_matches("[[PDF_IMAGE:")
+                .trim_end_matches("]]")
+        )
+        .exists());
+        assert_eq!(
+            sections[1].3,
+            vec!["[1] Smith, 2019.", "[2] See above. Also & below."]
+        );
+        assert_eq!(sections[1].2, "section2");
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,11 +1,15 @@
+use super::html_text::{self, HtmlParagraphs};
+use super::{Identifiers, InternalLink, LINK_KIND_FOOTNOTE, LINK_KIND_INTERNAL};
 use crate::error::{ReaderError, Result};
 use crate::models::NewDocument;
 use epub::doc::EpubDoc;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct EpubParser {
     doc: EpubDoc<std::io::BufReader<std::fs::File>>,
     file_path: String,
+    internal_links: Vec<InternalLink>,
 }
 
 impl EpubParser {
@@ -21,6 +25,7 @@ impl EpubParser {
         Ok(Self {
             doc,
             file_path: file_path.to_string(),
+            internal_links: Vec::new(),
         })
     }
 
@@ -56,6 +61,17 @@ impl EpubParser {
         })
     }
 
+    /// ISBN and DOI from the package's `dc:identifier` entries
+    pub fn identifiers(&self) -> Identifiers {
+        Identifiers::from_values(
+            self.doc
+                .metadata
+                .iter()
+                .filter(|item| item.property == "identifier" || item.property == "dc:identifier")
+                .map(|item| item.value.as_str()),
+        )
+    }
+
     pub fn get_table_of_contents(&self) -> Result<Vec<(String, i32, String)>> {
         let mut chapters = Vec::new();
         let mut order = 0;
@@ -135,10 +151,14 @@ impl EpubParser {
             .trim_start_matches("./")
             .replace('\\', "/");
 
-        percent_decode(&base)
+        html_text::percent_decode(&base)
     }
 
-    pub fn get_chapter_content(&mut self, href: &str) -> Result<Vec<String>> {
+    /// Loads a chapter's XHTML and splits it into paragraphs
+    ///
+    /// Returns the resolved resource path alongside the paragraphs so that
+    /// in-book links pointing at that file can be matched later.
+    fn load_chapter(&mut self, href: &str) -> Result<Option<(String, HtmlParagraphs)>> {
         let base_href = Self::normalize_href(href);
 
         // Build a map from path to resource_id
@@ -169,9 +189,9 @@ impl EpubParser {
                 );
                 if let Some((content, _mime_type)) = self.doc.get_resource(resource_id) {
                     tracing::info!("Successfully retrieved content, {} bytes", content.len());
-                    let text = self.extract_text_from_html(&content);
-                    tracing::info!("Extracted {} paragraphs", text.len());
-                    return Ok(text);
+                    let parsed = html_text::extract_paragraphs(&content);
+                    tracing::info!("Extracted {} paragraphs", parsed.paragraphs.len());
+                    return Ok(Some((normalized_path, parsed)));
                 } else {
                     tracing::warn!("get_resource returned None for id='{}'", resource_id);
                 }
@@ -195,9 +215,9 @@ impl EpubParser {
                                 "Successfully retrieved content, {} bytes",
                                 content.len()
                             );
-                            let text = self.extract_text_from_html(&content);
-                            tracing::info!("Extracted {} paragraphs", text.len());
-                            return Ok(text);
+                            let parsed = html_text::extract_paragraphs(&content);
+                            tracing::info!("Extracted {} paragraphs", parsed.paragraphs.len());
+                            return Ok(Some((normalized_path, parsed)));
                         }
                     }
                 }
@@ -211,31 +231,13 @@ impl EpubParser {
             path_to_id.keys().take(5).cloned().collect::<Vec<_>>()
         );
 
-        Ok(Vec::new())
+        Ok(None)
     }
 
-    fn extract_text_from_html(&self, html: &[u8]) -> Vec<String> {
-        let html_str = String::from_utf8_lossy(html);
-
-        // Simple HTML tag removal
-        let text = html_str
-            .replace("<p>", "\n")
-            .replace("</p>", "\n")
-            .replace("<br>", "\n")
-            .replace("<br/>", "\n")
-            .replace("<div>", "\n")
-            .replace("</div>", "\n");
-
-        // Remove all other HTML tags
-        let re = regex::Regex::new(r"<[^>]+>").unwrap();
-        let text = re.replace_all(&text, "");
-
-        // Split into paragraphs and filter empty
-        text.split('\n')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect()
+    /// Footnote references and in-book links found by the last `parse_all`,
+    /// resolved to (chapter index, paragraph index) pairs.
+    pub fn take_internal_links(&mut self) -> Vec<InternalLink> {
+        std::mem::take(&mut self.internal_links)
     }
 
     pub fn parse_all(&mut self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
@@ -249,37 +251,90 @@ impl EpubParser {
         }
 
         let mut chapters = Vec::new();
+        let mut loaded: Vec<Option<(String, HtmlParagraphs)>> = Vec::new();
 
         for (title, order_index, href) in &toc {
             tracing::info!("Attempting to load chapter: {} href={}", title, href);
-            let paragraphs = self.get_chapter_content(href)?;
+            let chapter = self.load_chapter(href)?;
+            let paragraphs = chapter
+                .as_ref()
+                .map(|(_, parsed)| parsed.paragraphs.clone())
+                .unwrap_or_default();
             tracing::info!(
                 "Chapter {} loaded with {} paragraphs",
                 title,
                 paragraphs.len()
             );
             chapters.push((title.clone(), *order_index, href.clone(), paragraphs));
+            loaded.push(chapter);
         }
 
+        self.internal_links = resolve_internal_links(&loaded);
+        tracing::info!("Resolved {} internal links", self.internal_links.len());
+
         Ok((metadata, chapters))
     }
 }
 
-fn percent_decode(input: &str) -> String {
-    let bytes = input.as_bytes();
-    let mut out = Vec::with_capacity(bytes.len());
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i] == b'%' && i + 2 < bytes.len() {
-            let hex = &input[i + 1..i + 3];
-            if let Ok(value) = u8::from_str_radix(hex, 16) {
-                out.push(value);
-                i += 3;
+/// Matches every in-book `<a href>` against the anchors of the loaded chapters
+///
+/// When the same file backs several TOC entries, the first chapter wins.
+/// Links whose target cannot be found (or points outside the book) are dropped.
+fn resolve_internal_links(chapters: &[Option<(String, HtmlParagraphs)>]) -> Vec<InternalLink> {
+    let mut first_chapter_by_path: HashMap<&str, usize> = HashMap::new();
+    for (chapter_idx, chapter) in chapters.iter().enumerate() {
+        if let Some((path, _)) = chapter {
+            first_chapter_by_path
+                .entry(path.as_str())
+                .or_insert(chapter_idx);
+        }
+    }
+
+    let mut links = Vec::new();
+    for (source_chapter, chapter) in chapters.iter().enumerate() {
+        let Some((source_path, parsed)) = chapter else {
+            continue;
+        };
+        for link in &parsed.links {
+            let Some((target_path, fragment)) =
+                html_text::resolve_relative_href(source_path, &link.href)
+            else {
+                continue;
+            };
+            let Some(&target_chapter) = first_chapter_by_path.get(target_path.as_str()) else {
+                tracing::debug!("Unresolved internal link target: {}", link.href);
+                continue;
+            };
+            let Some((_, target)) = &chapters[target_chapter] else {
+                continue;
+            };
+            let target_paragraph = match &fragment {
+                Some(id) => match target.anchors.get(id) {
+                    Some(&idx) => idx,
+                    None => {
+                        tracing::debug!("Unresolved internal link anchor: {}", link.href);
+                        continue;
+                    }
+                },
+                None if !target.paragraphs.is_empty() => 0,
+                None => continue,
+            };
+            if source_chapter == target_chapter && link.paragraph_index == target_paragraph {
                 continue;
             }
+
+            links.push(InternalLink {
+                source: (source_chapter, link.paragraph_index),
+                target: (target_chapter, target_paragraph),
+                kind: if link.is_noteref {
+                    LINK_KIND_FOOTNOTE.to_string()
+                } else {
+                    LINK_KIND_INTERNAL.to_string()
+                },
+                label: link.label.clone(),
+            });
         }
-        out.push(bytes[i]);
-        i += 1;
     }
-    String::from_utf8_lossy(&out).to_string()
+
+    links
 }
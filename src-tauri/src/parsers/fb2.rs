@@ -0,0 +1,455 @@
+//! FictionBook (`.fb2`, `.fb2.zip`) books
+//!
+//! Every `<section>` with a `<title>` starts a new section, nested sections
+//! included. Title, authors and language come from
+//! `<description><title-info>`; `<binary>` images referenced by `<image>`
+//! are written out next to the other parser image output.
+
+use super::text::decode_text;
+use super::xml_text::{attribute, push_entity};
+use super::{image_output_dir, sanitize_filename, IMAGE_MARKER_PREFIX};
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use base64::Engine;
+use encoding_rs::Encoding;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+const EMPTY_FB2_PLACEHOLDER: &str = "No readable content extracted from FB2 file.";
+const LEADING_SECTION_TITLE: &str = "Content";
+/// Elements whose text becomes one paragraph
+const PARAGRAPH_ELEMENTS: &[&[u8]] = &[b"p", b"v", b"subtitle", b"text-author"];
+
+pub struct Fb2Parser {
+    file_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Fb2Block {
+    /// Joined `<title>` paragraphs of a section
+    Heading(String),
+    Paragraph(String),
+    /// Id of a `<binary>` image
+    Image(String),
+}
+
+#[derive(Debug, Default)]
+struct Fb2Book {
+    title: Option<String>,
+    authors: Vec<String>,
+    language: Option<String>,
+    blocks: Vec<Fb2Block>,
+    /// Binary id mapped to (content type, base64 data)
+    binaries: HashMap<String, (String, String)>,
+}
+
+impl Fb2Parser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let bytes = self.read_book_bytes()?;
+        let xml = decode_xml(&bytes);
+        let book = parse_book(&xml)
+            .map_err(|e| ReaderError::Fb2Parse(format!("Invalid FictionBook XML: {}", e)))?;
+
+        let image_output_dir = image_output_dir(&self.file_path, "fb2");
+        let _ = fs::remove_dir_all(&image_output_dir);
+        let sections = build_sections(&book.blocks, |id| {
+            extract_image(&book.binaries, id, &image_output_dir)
+        });
+
+        let title = book.title.unwrap_or_else(|| {
+            Path::new(&self.file_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|stem| stem.trim_end_matches(".fb2"))
+                .unwrap_or("Untitled")
+                .to_string()
+        });
+
+        let metadata = NewDocument {
+            title,
+            author: (!book.authors.is_empty()).then(|| book.authors.join(", ")),
+            language: book.language,
+            file_path: self.file_path.clone(),
+            file_type: "fb2".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+
+    /// Reads the book, unpacking the first `.fb2` entry of a zipped book
+    fn read_book_bytes(&self) -> Result<Vec<u8>> {
+        let bytes = fs::read(&self.file_path)?;
+        if !bytes.starts_with(b"PK\x03\x04") {
+            return Ok(bytes);
+        }
+
+        let mut archive = ZipArchive::new(File::open(&self.file_path)?)
+            .map_err(|e| ReaderError::Fb2Parse(format!("Failed to open zipped FB2: {}", e)))?;
+        let name = archive
+            .file_names()
+            .find(|name| name.to_ascii_lowercase().ends_with(".fb2"))
+            .map(str::to_string)
+            .ok_or_else(|| ReaderError::Fb2Parse("Archive contains no .fb2 file".to_string()))?;
+        let mut entry = archive
+            .by_name(&name)
+            .map_err(|e| ReaderError::Fb2Parse(format!("Failed to read {}: {}", name, e)))?;
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        Ok(content)
+    }
+}
+
+/// Decodes the XML using its BOM or `encoding` declaration
+///
+/// Many FB2 files are Windows-1251; undeclared files fall back to the plain
+/// text detection.
+fn decode_xml(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return text.into_owned();
+    }
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).to_string();
+    let declared = head
+        .split("?>")
+        .next()
+        .filter(|decl| decl.trim_start().starts_with("<?xml"))
+        .and_then(|decl| decl.split_once("encoding="))
+        .and_then(|(_, rest)| {
+            let quote = rest.chars().next()?;
+            rest[1..].split(quote).next()
+        })
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()));
+    match declared {
+        Some(encoding) => {
+            let (text, _) = encoding.decode_without_bom_handling(bytes);
+            text.into_owned()
+        }
+        None => decode_text(bytes),
+    }
+}
+
+/// Walks the FictionBook document into metadata, blocks and binaries
+fn parse_book(xml: &str) -> std::result::Result<Fb2Book, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut book = Fb2Book::default();
+    let mut path: Vec<Vec<u8>> = Vec::new();
+
+    let mut text = String::new();
+    let mut in_paragraph = false;
+    let mut title_lines: Option<Vec<String>> = None;
+    let mut row_cells: Option<Vec<String>> = None;
+    let mut author_parts: Option<Vec<String>> = None;
+    let mut binary: Option<(String, String)> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                let in_body = path.iter().any(|n| n == b"body");
+                let in_title_info = path.iter().any(|n| n == b"title-info");
+
+                match name.as_slice() {
+                    b"binary" => {
+                        binary = attribute(e, b"id").map(|id| {
+                            let content_type = attribute(e, b"content-type").unwrap_or_default();
+                            (id, content_type)
+                        });
+                        text.clear();
+                    }
+                    b"author" if in_title_info => author_parts = Some(Vec::new()),
+                    b"book-title" | b"lang" | b"first-name" | b"middle-name" | b"last-name"
+                    | b"nickname"
+                        if in_title_info =>
+                    {
+                        text.clear()
+                    }
+                    b"annotation" if in_title_info => {
+                        reader.read_to_end(e.name())?;
+                        continue;
+                    }
+                    // Only section titles start sections; the body's own title
+                    // repeats author and book title
+                    b"title" if in_body && path.last().is_some_and(|n| n == b"section") => {
+                        title_lines = Some(Vec::new());
+                    }
+                    b"tr" if in_body => row_cells = Some(Vec::new()),
+                    b"td" | b"th" if in_body => text.clear(),
+                    _ if in_body && PARAGRAPH_ELEMENTS.contains(&name.as_slice()) => {
+                        in_paragraph = true;
+                        text.clear();
+                    }
+                    b"image" if in_body => push_image(&mut book.blocks, e),
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Empty(e)
+                if e.local_name().as_ref() == b"image" && path.iter().any(|n| n == b"body") =>
+            {
+                push_image(&mut book.blocks, e);
+            }
+            Event::Text(e) => text.push_str(&e.decode()?),
+            Event::CData(e) => text.push_str(&String::from_utf8_lossy(e)),
+            Event::GeneralRef(e) => push_entity(&mut text, e)?,
+            Event::End(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                path.pop();
+                let in_title_info = path.iter().any(|n| n == b"title-info");
+                let value = normalize_text(&text);
+
+                match name.as_slice() {
+                    b"binary" => {
+                        if let Some((id, content_type)) = binary.take() {
+                            let data: String =
+                                text.chars().filter(|c| !c.is_whitespace()).collect();
+                            book.binaries.insert(id, (content_type, data));
+                        }
+                        text.clear();
+                    }
+                    b"book-title" if in_title_info && !value.is_empty() => {
+                        book.title.get_or_insert(value);
+                    }
+                    b"lang" if in_title_info && !value.is_empty() => {
+                        book.language.get_or_insert(value);
+                    }
+                    b"first-name" | b"middle-name" | b"last-name" | b"nickname"
+                        if in_title_info =>
+                    {
+                        if let Some(parts) = author_parts.as_mut() {
+                            if !value.is_empty() {
+                                parts.push(value);
+                            }
+                        }
+                    }
+                    b"author" if in_title_info => {
+                        if let Some(parts) = author_parts.take() {
+                            if !parts.is_empty() {
+                                book.authors.push(parts.join(" "));
+                            }
+                        }
+                    }
+                    b"title" if title_lines.is_some() => {
+                        let heading = title_lines.take().unwrap_or_default().join(" ");
+                        if !heading.is_empty() {
+                            book.blocks.push(Fb2Block::Heading(heading));
+                        }
+                    }
+                    b"td" | b"th" if row_cells.is_some() => {
+                        if let Some(cells) = row_cells.as_mut() {
+                            cells.push(value);
+                        }
+                        text.clear();
+                    }
+                    b"tr" if row_cells.is_some() => {
+                        let cells = row_cells.take().unwrap_or_default();
+                        if cells.iter().any(|cell| !cell.is_empty()) {
+                            book.blocks.push(Fb2Block::Paragraph(cells.join(" | ")));
+                        }
+                    }
+                    _ if in_paragraph && PARAGRAPH_ELEMENTS.contains(&name.as_slice()) => {
+                        in_paragraph = false;
+                        if !value.is_empty() {
+                            match title_lines.as_mut() {
+                                Some(lines) => lines.push(value),
+                                None => book.blocks.push(Fb2Block::Paragraph(value)),
+                            }
+                        }
+                        text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(book)
+}
+
+fn push_image(blocks: &mut Vec<Fb2Block>, element: &quick_xml::events::BytesStart) {
+    if let Some(id) = attribute(element, b"href").and_then(|href| {
+        href.strip_prefix('#')
+            .map(str::to_string)
+            .filter(|id| !id.is_empty())
+    }) {
+        blocks.push(Fb2Block::Image(id));
+    }
+}
+
+/// Groups blocks into sections, one per titled `<section>`
+///
+/// The title stays in its section's body, like the Markdown importer does.
+/// Content before the first title goes into a leading "Content" section.
+fn build_sections(
+    blocks: &[Fb2Block],
+    mut image_marker: impl FnMut(&str) -> Option<String>,
+) -> Vec<(String, i32, String, Vec<String>)> {
+    let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+    let mut current_title = LEADING_SECTION_TITLE.to_string();
+    let mut current: Vec<String> = Vec::new();
+
+    let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
+                        title: &str,
+                        paragraphs: Vec<String>| {
+        let order = sections.len() as i32;
+        sections.push((
+            title.to_string(),
+            order,
+            format!("section{}", order + 1),
+            paragraphs,
+        ));
+    };
+
+    for block in blocks {
+        match block {
+            Fb2Block::Heading(text) => {
+                if !current.is_empty() {
+                    push_section(&mut sections, &current_title, std::mem::take(&mut current));
+                }
+                current_title = text.clone();
+                current.push(text.clone());
+            }
+            Fb2Block::Paragraph(text) => current.push(text.clone()),
+            Fb2Block::Image(id) => {
+                if let Some(marker) = image_marker(id) {
+                    current.push(marker);
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() || sections.is_empty() {
+        if current.is_empty() {
+            current.push(EMPTY_FB2_PLACEHOLDER.to_string());
+        }
+        push_section(&mut sections, &current_title, current);
+    }
+
+    sections
+}
+
+/// Decodes a `<binary>` image to the output directory and returns its marker line
+fn extract_image(
+    binaries: &HashMap<String, (String, String)>,
+    id: &str,
+    output_dir: &Path,
+) -> Option<String> {
+    let (content_type, data) = binaries.get(id)?;
+    let extension = match content_type.to_ascii_lowercase().as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        _ => {
+            tracing::debug!("Skipping unsupported FB2 image {} ({})", id, content_type);
+            return None;
+        }
+    };
+    let bytes = match base64::engine::general_purpose::STANDARD.decode(data) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Invalid base64 in FB2 image {}: {}", id, e);
+            return None;
+        }
+    };
+
+    let stem = Path::new(id)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+    fs::create_dir_all(output_dir).ok()?;
+    let image_path = output_dir.join(format!("{}.{}", sanitize_filename(stem), extension));
+    fs::write(&image_path, bytes).ok()?;
+
+    Some(format!(
+        "{prefix}{path}]]",
+        prefix = IMAGE_MARKER_PREFIX,
+        path = image_path.to_string_lossy()
+    ))
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_sections, decode_xml, parse_book};
+
+    #[test]
+    fn parses_sections_and_description() {
+        // "Глава" in Windows-1251
+        let mut bytes = br##"<?xml version="1.0" encoding="windows-1251"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">
+<description><title-info>
+  <author><first-name>Anna</first-name><last-name>Sever</last-name></author>
+  <author><nickname>Quill</nickname></author>
+  <book-title>Winter &amp; Frost</book-title>
+  <annotation><p>Not part of the text.</p></annotation>
+  <lang>ru</lang>
+</title-info></description>
+<body>
+  <title><p>Anna Sever</p><p>Winter &amp; Frost</p></title>
+  <epigraph><p>Cold is a teacher.</p></epigraph>
+  <section>
+    <title><p>"##
+            .to_vec();
+        bytes.extend_from_slice(&[0xC3, 0xEB, 0xE0, 0xE2, 0xE0]);
+        bytes.extend_from_slice(
+            br##" 1</p><p>Snow</p></title>
+    <p>It <emphasis>snowed</emphasis>
+       all night.</p>
+    <image l:href="#missing.png"/>
+    <empty-line/>
+    <poem><stanza><v>White roofs,</v><v>white roads.</v></stanza></poem>
+    <table><tr><th>Day</th><td>Mon</td></tr></table>
+  </section>
+</body>
+<binary id="unused.png" content-type="image/png">iVBORw0KGgo=</binary>
+</FictionBook>"##,
+        );
+
+        let book = parse_book(&decode_xml(&bytes)).unwrap();
+        assert_eq!(book.title.as_deref(), Some("Winter & Frost"));
+        assert_eq!(book.authors, vec!["Anna Sever", "Quill"]);
+        assert_eq!(book.language.as_deref(), Some("ru"));
+        assert!(book.binaries.contains_key("unused.png"));
+
+        let sections = build_sections(&book.blocks, |_| None);
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0, "Content");
+        assert_eq!(
+            sections[0].3,
+            vec!["Anna Sever", "Winter & Frost", "Cold is a teacher."]
+        );
+        assert_eq!(sections[1].0, "Глава 1 Snow");
+        assert_eq!(
+            sections[1].3,
+            vec![
+                "Глава 1 Snow",
+                "It snowed all night.",
+                "White roofs,",
+                "white roads.",
+                "Day | Mon"
+            ]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! RSS 2.0, RSS 1.0 (RDF) and Atom feeds
+//!
+//! Only what the feed importer needs is kept: the feed title and, per entry,
+//! a stable id, link, author, date and whatever HTML content the feed carries.
+
+use super::xml_text::{attribute, push_entity};
+use crate::error::{ReaderError, Result};
+use chrono::DateTime;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+#[derive(Debug, Clone, Default)]
+pub struct Feed {
+    pub title: Option<String>,
+    /// The website the feed belongs to
+    pub link: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeedEntry {
+    /// `<guid>` or `<id>`, falling back to the link or title
+    pub guid: String,
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    /// Unix timestamp of publication (or last update when that's all there is)
+    pub published_at: Option<i64>,
+    /// Full HTML content (`content:encoded` or Atom `<content>`)
+    pub content_html: Option<String>,
+    /// Short HTML or text summary (`<description>` or `<summary>`)
+    pub summary: Option<String>,
+}
+
+/// Parses an RSS or Atom document
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+    let feed = read_feed(xml).map_err(|e| ReaderError::FeedParse(e.to_string()))?;
+    if feed.title.is_none() && feed.entries.is_empty() {
+        return Err(ReaderError::FeedParse(
+            "No RSS or Atom content found".to_string(),
+        ));
+    }
+    Ok(feed)
+}
+
+fn read_feed(xml: &str) -> std::result::Result<Feed, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut feed = Feed::default();
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut entry: Option<FeedEntry> = None;
+    let mut text = String::new();
+    // Depth at which inline XHTML content started, if inside it
+    let mut xhtml_depth: Option<usize> = None;
+    let mut updated_at: Option<i64> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                if xhtml_depth.is_some() {
+                    text.push('<');
+                    text.push_str(&String::from_utf8_lossy(e));
+                    text.push('>');
+                    path.push(name);
+                    continue;
+                }
+                match name.as_slice() {
+                    b"item" | b"entry" => {
+                        entry = Some(FeedEntry::default());
+                        updated_at = None;
+                    }
+                    b"content" if attribute(e, b"type").as_deref() == Some("xhtml") => {
+                        xhtml_depth = Some(path.len() + 1);
+                    }
+                    b"link" => read_atom_link(e, entry.as_mut(), &mut feed),
+                    _ => {}
+                }
+                text.clear();
+                path.push(name);
+            }
+            Event::Empty(e) => {
+                if xhtml_depth.is_some() {
+                    text.push('<');
+                    text.push_str(&String::from_utf8_lossy(e));
+                    text.push_str("/>");
+                } else if e.local_name().as_ref() == b"link" {
+                    read_atom_link(e, entry.as_mut(), &mut feed);
+                }
+            }
+            Event::Text(e) => text.push_str(&e.decode()?),
+            Event::CData(e) => text.push_str(&String::from_utf8_lossy(e)),
+            Event::GeneralRef(e) => push_entity(&mut text, e)?,
+            Event::End(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                if let Some(depth) = xhtml_depth {
+                    if path.len() > depth {
+                        text.push_str("</");
+                        text.push_str(&String::from_utf8_lossy(e.as_ref()));
+                        text.push('>');
+                        path.pop();
+                        continue;
+                    }
+                    xhtml_depth = None;
+                }
+                path.pop();
+                let value = text.trim().to_string();
+                text.clear();
+                let parent = path.last().map(Vec::as_slice);
+
+                if let Some(current) = entry.as_mut() {
+                    match name.as_slice() {
+                        b"item" | b"entry" => {
+                            let mut finished = entry.take().unwrap_or_default();
+                            finished.published_at = finished.published_at.or(updated_at);
+                            if finished.guid.is_empty() {
+                                finished.guid = finished
+                                    .link
+                                    .clone()
+                                    .or_else(|| finished.title.clone())
+                                    .unwrap_or_default();
+                            }
+                            if !finished.guid.is_empty() {
+                                feed.entries.push(finished);
+                            }
+                        }
+                        _ if value.is_empty() => {}
+                        b"guid" | b"id" => current.guid = value,
+                        b"title" => current.title = Some(value),
+                        b"link" if current.link.is_none() => current.link = Some(value),
+                        // RSS <author> holds an e-mail address; Atom nests <name>
+                        b"creator" | b"author" => {
+                            current.author.get_or_insert(value);
+                        }
+                        b"name" if parent == Some(b"author".as_slice()) => {
+                            current.author = Some(value);
+                        }
+                        b"pubDate" | b"published" | b"date" | b"issued" => {
+                            current.published_at = parse_date(&value);
+                        }
+                        b"updated" | b"modified" => updated_at = parse_date(&value),
+                        b"encoded" | b"content" => current.content_html = Some(value),
+                        b"description" | b"summary" => current.summary = Some(value),
+                        _ => {}
+                    }
+                } else if !value.is_empty() && matches!(parent, Some(b"channel") | Some(b"feed")) {
+                    match name.as_slice() {
+                        b"title" if feed.title.is_none() => feed.title = Some(value),
+                        b"link" if feed.link.is_none() => feed.link = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(feed)
+}
+
+/// Atom links are attributes; the alternate (or unlabelled) one is the page
+fn read_atom_link(element: &BytesStart, entry: Option<&mut FeedEntry>, feed: &mut Feed) {
+    let Some(href) = attribute(element, b"href") else {
+        return;
+    };
+    let rel = attribute(element, b"rel");
+    if !matches!(rel.as_deref(), None | Some("alternate")) {
+        return;
+    }
+    match entry {
+        Some(entry) => {
+            entry.link.get_or_insert(href);
+        }
+        None => {
+            feed.link.get_or_insert(href);
+        }
+    }
+}
+
+/// RFC 2822 (RSS) or RFC 3339 (Atom, Dublin Core) dates
+fn parse_date(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|date| date.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_feed;
+
+    #[test]
+    fn parses_rss_and_atom_entries() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                 xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <channel>
+                <title>Example Blog</title>
+                <link>https://example.com/</link>
+                <item>
+                  <title>First &amp; Foremost</title>
+                  <link>https://example.com/first</link>
+                  <guid isPermaLink="false">post-1</guid>
+                  <dc:creator>Ada</dc:creator>
+                  <pubDate>Tue, 02 Jan 2024 10:00:00 +0000</pubDate>
+                  <description>&lt;p&gt;Short&lt;/p&gt;</description>
+                  <content:encoded><![CDATA[<p>Full <b>text</b></p>]]></content:encoded>
+                </item>
+                <item><title>No guid</title><link>https://example.com/second</link></item>
+              </channel>
+            </rss>"#;
+        let feed = parse_feed(rss).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        assert_eq!(feed.entries.len(), 2);
+        let first = &feed.entries[0];
+        assert_eq!(first.guid, "post-1");
+        assert_eq!(first.title.as_deref(), Some("First & Foremost"));
+        assert_eq!(first.author.as_deref(), Some("Ada"));
+        assert_eq!(first.published_at, Some(1704189600));
+        assert_eq!(first.summary.as_deref(), Some("<p>Short</p>"));
+        assert_eq!(
+            first.content_html.as_deref(),
+            Some("<p>Full <b>text</b></p>")
+        );
+        assert_eq!(feed.entries[1].guid, "https://example.com/second");
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Atom Site</title>
+              <link rel="self" href="https://atom.example/feed.xml"/>
+              <link href="https://atom.example/"/>
+              <entry>
+                <id>urn:uuid:1</id>
+                <title>Hello</title>
+                <link rel="alternate" href="https://atom.example/hello"/>
+                <author><name>Bob</name></author>
+                <updated>2024-03-01T12:00:00Z</updated>
+                <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml"><p>Hi<br/>there</p></div></content>
+              </entry>
+            </feed>"#;
+        let feed = parse_feed(atom).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Atom Site"));
+        assert_eq!(feed.link.as_deref(), Some("https://atom.example/"));
+        let entry = &feed.entries[0];
+        assert_eq!(entry.guid, "urn:uuid:1");
+        assert_eq!(entry.link.as_deref(), Some("https://atom.example/hello"));
+        assert_eq!(entry.author.as_deref(), Some("Bob"));
+        assert_eq!(entry.published_at, Some(1709294400));
+        assert_eq!(
+            entry.content_html.as_deref(),
+            Some(r#"<div xmlns="http://www.w3.org/1999/xhtml"><p>Hi<br/>there</p></div>"#)
+        );
+    }
+}
@@ -0,0 +1,779 @@
+//! Highlights exported by other reading apps
+//!
+//! Kindle appends every highlight, note and bookmark to `My Clippings.txt`;
+//! KOReader keeps them in a Lua sidecar (`book.sdr/metadata.epub.lua`). Both
+//! are read into [`ExternalHighlight`]s, with Kindle notes attached to the
+//! highlight they were written on.
+
+use crate::error::{ReaderError, Result};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+const CLIPPING_SEPARATOR: &str = "==========";
+const KINDLE_BOOKMARK_WORDS: [&str; 7] = [
+    "bookmark",
+    "书签",
+    "lesezeichen",
+    "signet",
+    "marcador",
+    "segnalibro",
+    "ブックマーク",
+];
+const KINDLE_HIGHLIGHT_WORDS: [&str; 8] = [
+    "highlight",
+    "标注",
+    "markierung",
+    "surlignement",
+    "subrayado",
+    "evidenziazione",
+    "destaque",
+    "ハイライト",
+];
+const KINDLE_LOCATION_WORDS: [&str; 6] = [
+    "location",
+    "位置",
+    "position",
+    "emplacement",
+    "posición",
+    "posizione",
+];
+const KINDLE_DATE_FORMATS: [&str; 3] = [
+    "%A, %B %d, %Y %I:%M:%S %p",
+    "%A, %d %B %Y %H:%M:%S",
+    "%A, %B %d, %Y %H:%M:%S",
+];
+
+/// A highlight (optionally with a note) made in another reading app
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalHighlight {
+    pub book_title: String,
+    pub author: Option<String>,
+    /// Highlighted text; empty for a note that isn't attached to any highlight
+    pub text: String,
+    pub note: Option<String>,
+    /// Position as the source app shows it, e.g. "Location 120-125" or "Page 12"
+    pub location: Option<String>,
+    pub chapter: Option<String>,
+    /// Unix timestamp when the highlight was made
+    pub created_at: Option<i64>,
+}
+
+/// Reads Kindle clippings or a KOReader sidecar (`.lua` file or `.sdr` folder)
+pub fn read_highlights(path: &str) -> Result<Vec<ExternalHighlight>> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Err(ReaderError::NotFound(path.display().to_string()));
+    }
+
+    let sidecar = if path.is_dir() {
+        let lua = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .find(|candidate| {
+                candidate
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("metadata.") && name.ends_with(".lua"))
+            })
+            .ok_or_else(|| {
+                ReaderError::HighlightParse(format!(
+                    "No KOReader metadata file in {}",
+                    path.display()
+                ))
+            })?;
+        Some(lua)
+    } else if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("lua"))
+    {
+        Some(path.to_path_buf())
+    } else {
+        None
+    };
+
+    match sidecar {
+        Some(lua) => {
+            // `Book Title.sdr/metadata.epub.lua` names the book after its folder
+            let fallback_title = lua
+                .parent()
+                .and_then(|dir| dir.file_stem())
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("Untitled")
+                .to_string();
+            parse_koreader_sidecar(&fs::read_to_string(&lua)?, &fallback_title)
+        }
+        None => {
+            let bytes = fs::read(path)?;
+            Ok(parse_kindle_clippings(&String::from_utf8_lossy(&bytes)))
+        }
+    }
+}
+
+/// A clipping before notes are attached to highlights
+struct KindleClipping {
+    highlight: ExternalHighlight,
+    /// Location range used to pair notes with highlights
+    range: Option<(u32, u32)>,
+    is_note: bool,
+}
+
+/// Parses a Kindle `My Clippings.txt` file; bookmarks are skipped
+pub fn parse_kindle_clippings(content: &str) -> Vec<ExternalHighlight> {
+    let content = content.replace("\r\n", "\n");
+    let title_re = Regex::new(r"^(.*?)\s*\(([^()]*)\)\s*$").unwrap();
+    let range_re = Regex::new(r"(\d+)(?:\s*-\s*(\d+))?").unwrap();
+
+    let mut clippings: Vec<KindleClipping> = Vec::new();
+    for entry in content.split(CLIPPING_SEPARATOR) {
+        let mut lines = entry
+            .lines()
+            .map(|line| line.trim_start_matches('\u{feff}').trim_end())
+            .skip_while(|line| line.trim().is_empty());
+        let (Some(title_line), Some(meta_line)) = (lines.next(), lines.next()) else {
+            continue;
+        };
+        let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+        let meta = meta_line.trim().trim_start_matches('-').trim();
+        let meta_lower = meta.to_lowercase();
+        if KINDLE_BOOKMARK_WORDS
+            .iter()
+            .any(|word| meta_lower.contains(word))
+        {
+            continue;
+        }
+        let is_note = !KINDLE_HIGHLIGHT_WORDS
+            .iter()
+            .any(|word| meta_lower.contains(word));
+        if body.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = meta.split('|').map(str::trim).collect();
+        let location_part = parts
+            .iter()
+            .find(|part| {
+                let lower = part.to_lowercase();
+                KINDLE_LOCATION_WORDS
+                    .iter()
+                    .any(|word| lower.contains(word))
+            })
+            .or_else(|| parts.first().filter(|_| parts.len() > 1));
+        let range = location_part.and_then(|part| {
+            let caps = range_re.captures(part)?;
+            let start: u32 = caps[1].parse().ok()?;
+            let end = caps
+                .get(2)
+                .and_then(|end| end.as_str().parse().ok())
+                .unwrap_or(start);
+            Some((start, end))
+        });
+        let location = location_part.map(|part| strip_kindle_verb(part));
+        let created_at = parts.last().and_then(|part| parse_kindle_date(part));
+
+        let (book_title, author) = match title_re.captures(title_line.trim()) {
+            Some(caps) if !caps[1].trim().is_empty() => (
+                caps[1].trim().to_string(),
+                Some(caps[2].trim().to_string()).filter(|author| !author.is_empty()),
+            ),
+            _ => (title_line.trim().to_string(), None),
+        };
+
+        let highlight = ExternalHighlight {
+            book_title,
+            author,
+            text: if is_note { String::new() } else { body.clone() },
+            note: is_note.then_some(body),
+            location,
+            chapter: None,
+            created_at,
+        };
+
+        // Extending a highlight on the device appends a new clipping for it
+        if !is_note {
+            if let Some(previous) = clippings.iter_mut().rev().find(|clipping| {
+                !clipping.is_note
+                    && clipping.highlight.book_title == highlight.book_title
+                    && ranges_overlap(clipping.range, range)
+                    && (clipping.highlight.text.contains(&highlight.text)
+                        || highlight.text.contains(&clipping.highlight.text))
+            }) {
+                if highlight.text.len() >= previous.highlight.text.len() {
+                    let note = previous.highlight.note.take();
+                    previous.highlight = ExternalHighlight { note, ..highlight };
+                    previous.range = range;
+                }
+                continue;
+            }
+        }
+
+        clippings.push(KindleClipping {
+            highlight,
+            range,
+            is_note,
+        });
+    }
+
+    // A note is stored at the end location of the highlight it belongs to
+    let mut highlights: Vec<ExternalHighlight> = Vec::new();
+    let mut highlight_ranges: Vec<Option<(u32, u32)>> = Vec::new();
+    for clipping in clippings {
+        if clipping.is_note {
+            let note = clipping.highlight.note.clone().unwrap_or_default();
+            let owner = clipping.range.and_then(|(position, _)| {
+                highlights
+                    .iter()
+                    .zip(&highlight_ranges)
+                    .rposition(|(h, r)| {
+                        h.book_title == clipping.highlight.book_title
+                            && !h.text.is_empty()
+                            && r.is_some_and(|(start, end)| start <= position && position <= end)
+                    })
+            });
+            if let Some(idx) = owner {
+                let existing = &mut highlights[idx].note;
+                *existing = Some(match existing.take() {
+                    Some(previous) => format!("{}\n\n{}", previous, note),
+                    None => note,
+                });
+                continue;
+            }
+        }
+        highlight_ranges.push(clipping.range);
+        highlights.push(clipping.highlight);
+    }
+    highlights
+}
+
+/// "Your Highlight on page 12" → "Page 12"
+fn strip_kindle_verb(part: &str) -> String {
+    let lower = part.to_lowercase();
+    for marker in [" on ", " at "] {
+        if let Some(pos) = lower.rfind(marker) {
+            if part.is_char_boundary(pos + marker.len()) {
+                return capitalize(part[pos + marker.len()..].trim());
+            }
+        }
+    }
+    part.to_string()
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn ranges_overlap(a: Option<(u32, u32)>, b: Option<(u32, u32)>) -> bool {
+    match (a, b) {
+        (Some((a_start, a_end)), Some((b_start, b_end))) => a_start <= b_end && b_start <= a_end,
+        _ => false,
+    }
+}
+
+fn parse_kindle_date(part: &str) -> Option<i64> {
+    let date = part.trim().strip_prefix("Added on").unwrap_or(part).trim();
+    KINDLE_DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|datetime| datetime.timestamp())
+}
+
+/// Parses a KOReader `metadata.*.lua` sidecar
+///
+/// Reads the `annotations` list of current KOReader versions, falling back to
+/// the older `highlight` table with notes kept in `bookmarks`.
+pub fn parse_koreader_sidecar(
+    content: &str,
+    fallback_title: &str,
+) -> Result<Vec<ExternalHighlight>> {
+    let root = LuaReader::new(content)
+        .read_document()
+        .ok_or_else(|| ReaderError::HighlightParse("Not a KOReader metadata file".to_string()))?;
+
+    let props = root.get("doc_props").or_else(|| root.get("stats"));
+    let book_title = props
+        .and_then(|props| props.get("title"))
+        .and_then(LuaValue::as_str)
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or(fallback_title)
+        .to_string();
+    let author = props
+        .and_then(|props| props.get("authors"))
+        .and_then(LuaValue::as_str)
+        .map(|authors| {
+            authors
+                .lines()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|authors| !authors.is_empty());
+
+    let make = |item: &LuaValue, note: Option<String>| -> Option<ExternalHighlight> {
+        let text = item
+            .get("text")
+            .and_then(LuaValue::as_str)?
+            .trim()
+            .to_string();
+        if text.is_empty() {
+            return None;
+        }
+        let page = item.get("pageno").or_else(|| item.get("page"));
+        Some(ExternalHighlight {
+            book_title: book_title.clone(),
+            author: author.clone(),
+            text,
+            note: note.filter(|note| !note.trim().is_empty()),
+            location: page
+                .and_then(LuaValue::as_f64)
+                .map(|page| format!("Page {}", page)),
+            chapter: item
+                .get("chapter")
+                .and_then(LuaValue::as_str)
+                .map(str::to_string),
+            created_at: item
+                .get("datetime")
+                .and_then(LuaValue::as_str)
+                .and_then(parse_koreader_date),
+        })
+    };
+
+    if let Some(annotations) = root.get("annotations") {
+        return Ok(annotations
+            .values()
+            .filter(|item| item.get("drawer").is_some() || item.get("pos0").is_some())
+            .filter_map(|item| {
+                let note = item
+                    .get("note")
+                    .and_then(LuaValue::as_str)
+                    .map(str::to_string);
+                make(item, note)
+            })
+            .collect());
+    }
+
+    let bookmarks: Vec<&LuaValue> = root
+        .get("bookmarks")
+        .map(|bookmarks| bookmarks.values().collect())
+        .unwrap_or_default();
+    let auto_text_re = Regex::new(r"(?s)^Page \d+ .* @ \d{4}-\d{2}-\d{2}").unwrap();
+    let mut highlights = Vec::new();
+    if let Some(pages) = root.get("highlight") {
+        for page_items in pages.values() {
+            for item in page_items.values() {
+                let text = item.get("text").and_then(LuaValue::as_str).unwrap_or("");
+                let datetime = item.get("datetime").and_then(LuaValue::as_str);
+                // The edited bookmark text is the note; untouched ones are generated
+                let note = bookmarks
+                    .iter()
+                    .find(|bookmark| {
+                        bookmark.get("notes").and_then(LuaValue::as_str) == Some(text)
+                            || (datetime.is_some()
+                                && bookmark.get("datetime").and_then(LuaValue::as_str) == datetime)
+                    })
+                    .and_then(|bookmark| bookmark.get("text").and_then(LuaValue::as_str))
+                    .filter(|note| *note != text && !auto_text_re.is_match(note))
+                    .map(str::to_string);
+                if let Some(highlight) = make(item, note) {
+                    highlights.push(highlight);
+                }
+            }
+        }
+    }
+    Ok(highlights)
+}
+
+fn parse_koreader_date(value: &str) -> Option<i64> {
+    let naive = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|datetime| datetime.timestamp())
+}
+
+/// A value of the Lua subset KOReader serializes
+#[derive(Debug, Clone, PartialEq)]
+enum LuaValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Table(Vec<(LuaValue, LuaValue)>),
+}
+
+impl LuaValue {
+    fn get(&self, key: &str) -> Option<&LuaValue> {
+        match self {
+            LuaValue::Table(entries) => entries
+                .iter()
+                .find(|(k, _)| matches!(k, LuaValue::String(name) if name == key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Table values, numeric keys in ascending order
+    fn values(&self) -> impl Iterator<Item = &LuaValue> {
+        let mut entries: Vec<&(LuaValue, LuaValue)> = match self {
+            LuaValue::Table(entries) => entries.iter().collect(),
+            _ => Vec::new(),
+        };
+        entries.sort_by(|(a, _), (b, _)| {
+            let key = |value: &LuaValue| value.as_f64().unwrap_or(f64::MAX);
+            key(a).total_cmp(&key(b))
+        });
+        entries.into_iter().map(|(_, value)| value)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            LuaValue::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            LuaValue::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// Reads `return { ... }` Lua data files
+struct LuaReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> LuaReader<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            data: content.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn read_document(&mut self) -> Option<LuaValue> {
+        self.skip_space();
+        if self.data[self.pos..].starts_with(b"return") {
+            self.pos += "return".len();
+        }
+        let value = self.read_value()?;
+        matches!(value, LuaValue::Table(_)).then_some(value)
+    }
+
+    fn skip_space(&mut self) {
+        loop {
+            while self.pos < self.data.len() && self.data[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if !self.data[self.pos..].starts_with(b"--") {
+                return;
+            }
+            self.pos += 2;
+            if let Some(level) = self.long_bracket_level() {
+                let _ = self.read_long_string(level);
+            } else {
+                while self.pos < self.data.len() && self.data[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn read_value(&mut self) -> Option<LuaValue> {
+        self.skip_space();
+        let byte = *self.data.get(self.pos)?;
+        match byte {
+            b'{' => self.read_table(),
+            b'"' | b'\'' => self.read_quoted().map(LuaValue::String),
+            b'[' => {
+                let level = self.long_bracket_level()?;
+                self.read_long_string(level).map(LuaValue::String)
+            }
+            b'-' | b'.' | b'0'..=b'9' => self.read_number().map(LuaValue::Number),
+            _ => match self.read_name()?.as_str() {
+                "true" => Some(LuaValue::Bool(true)),
+                "false" => Some(LuaValue::Bool(false)),
+                "nil" => Some(LuaValue::Nil),
+                _ => None,
+            },
+        }
+    }
+
+    fn read_table(&mut self) -> Option<LuaValue> {
+        self.pos += 1;
+        let mut entries = Vec::new();
+        let mut next_index = 1.0;
+        loop {
+            self.skip_space();
+            match *self.data.get(self.pos)? {
+                b'}' => {
+                    self.pos += 1;
+                    return Some(LuaValue::Table(entries));
+                }
+                b',' | b';' => {
+                    self.pos += 1;
+                    continue;
+                }
+                b'[' if self.long_bracket_level().is_none() => {
+                    self.pos += 1;
+                    let key = self.read_value()?;
+                    self.skip_space();
+                    self.expect(b']')?;
+                    self.skip_space();
+                    self.expect(b'=')?;
+                    let value = self.read_value()?;
+                    entries.push((key, value));
+                }
+                byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                    let start = self.pos;
+                    let name = self.read_name()?;
+                    self.skip_space();
+                    if self.data.get(self.pos) == Some(&b'=') {
+                        self.pos += 1;
+                        let value = self.read_value()?;
+                        entries.push((LuaValue::String(name), value));
+                    } else {
+                        self.pos = start;
+                        let value = self.read_value()?;
+                        entries.push((LuaValue::Number(next_index), value));
+                        next_index += 1.0;
+                    }
+                }
+                _ => {
+                    let value = self.read_value()?;
+                    entries.push((LuaValue::Number(next_index), value));
+                    next_index += 1.0;
+                }
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.data.get(self.pos) == Some(&byte)).then(|| self.pos += 1)
+    }
+
+    fn read_name(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            self.pos += 1;
+        }
+        (self.pos > start)
+            .then(|| String::from_utf8_lossy(&self.data[start..self.pos]).into_owned())
+    }
+
+    fn read_number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        if self.data.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        if self.data[self.pos..].starts_with(b"0x") || self.data[self.pos..].starts_with(b"0X") {
+            self.pos += 2;
+            let digits_start = self.pos;
+            while self.data.get(self.pos).is_some_and(u8::is_ascii_hexdigit) {
+                self.pos += 1;
+            }
+            let digits = std::str::from_utf8(&self.data[digits_start..self.pos]).ok()?;
+            let value = i64::from_str_radix(digits, 16).ok()? as f64;
+            return Some(if self.data[start] == b'-' {
+                -value
+            } else {
+                value
+            });
+        }
+        while let Some(&byte) = self.data.get(self.pos) {
+            let exponent_sign = matches!(byte, b'+' | b'-')
+                && matches!(self.data.get(self.pos - 1), Some(b'e' | b'E'));
+            if byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E') || exponent_sign {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn read_quoted(&mut self) -> Option<String> {
+        let quote = self.data[self.pos];
+        self.pos += 1;
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            if byte == quote {
+                return Some(String::from_utf8_lossy(&out).into_owned());
+            }
+            if byte != b'\\' {
+                out.push(byte);
+                continue;
+            }
+            let escaped = *self.data.get(self.pos)?;
+            self.pos += 1;
+            match escaped {
+                b'n' | b'\n' => out.push(b'\n'),
+                b't' => out.push(b'\t'),
+                b'r' => out.push(b'\r'),
+                b'a' => out.push(0x07),
+                b'b' => out.push(0x08),
+                b'f' => out.push(0x0C),
+                b'v' => out.push(0x0B),
+                b'x' => {
+                    let hex = std::str::from_utf8(self.data.get(self.pos..self.pos + 2)?).ok()?;
+                    out.push(u8::from_str_radix(hex, 16).ok()?);
+                    self.pos += 2;
+                }
+                b'z' => {
+                    while self.data.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+                        self.pos += 1;
+                    }
+                }
+                b'0'..=b'9' => {
+                    let start = self.pos - 1;
+                    while self.pos < start + 3
+                        && self.data.get(self.pos).is_some_and(u8::is_ascii_digit)
+                    {
+                        self.pos += 1;
+                    }
+                    let code: u32 = std::str::from_utf8(&self.data[start..self.pos])
+                        .ok()?
+                        .parse()
+                        .ok()?;
+                    out.push(u8::try_from(code).ok()?);
+                }
+                other => out.push(other),
+            }
+        }
+    }
+
+    /// Level of a `[==[` long bracket at the cursor, if there is one
+    fn long_bracket_level(&self) -> Option<usize> {
+        let rest = &self.data[self.pos..];
+        if rest.first() != Some(&b'[') {
+            return None;
+        }
+        let level = rest[1..].iter().take_while(|b| **b == b'=').count();
+        (rest.get(level + 1) == Some(&b'[')).then_some(level)
+    }
+
+    fn read_long_string(&mut self, level: usize) -> Option<String> {
+        self.pos += level + 2;
+        // A newline right after the opening bracket is skipped
+        if self.data.get(self.pos) == Some(&b'\n') {
+            self.pos += 1;
+        }
+        let closing = format!("]{}]", "=".repeat(level));
+        let end = self.data[self.pos..]
+            .windows(closing.len())
+            .position(|window| window == closing.as_bytes())?;
+        let text = String::from_utf8_lossy(&self.data[self.pos..self.pos + end]).into_owned();
+        self.pos += end + closing.len();
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_kindle_clippings, parse_koreader_sidecar};
+
+    #[test]
+    fn parses_kindle_clippings_with_notes() {
+        let clippings = "\u{feff}The Pragmatic Programmer (Hunt, Andrew)\r
+- Your Highlight on page 12 | Location 120-123 | Added on Monday, March 4, 2024 10:12:33 PM\r
+\r
+Care about your craft.\r
+==========\r
+The Pragmatic Programmer (Hunt, Andrew)\r
+- Your Highlight on page 12 | Location 120-125 | Added on Monday, March 4, 2024 10:13:00 PM\r
+\r
+Care about your craft. Think about your work.\r
+==========\r
+The Pragmatic Programmer (Hunt, Andrew)\r
+- Your Note on page 12 | Location 125 | Added on Monday, March 4, 2024 10:14:00 PM\r
+\r
+Tip 1 and 2\r
+==========\r
+The Pragmatic Programmer (Hunt, Andrew)\r
+- Your Bookmark on page 30 | Location 400 | Added on Monday, March 4, 2024 10:15:00 PM\r
+\r
+\r
+==========\r
+";
+        let highlights = parse_kindle_clippings(clippings);
+        assert_eq!(highlights.len(), 1);
+        let highlight = &highlights[0];
+        assert_eq!(highlight.book_title, "The Pragmatic Programmer");
+        assert_eq!(highlight.author.as_deref(), Some("Hunt, Andrew"));
+        assert_eq!(
+            highlight.text,
+            "Care about your craft. Think about your work."
+        );
+        assert_eq!(highlight.note.as_deref(), Some("Tip 1 and 2"));
+        assert_eq!(highlight.location.as_deref(), Some("Location 120-125"));
+        assert!(highlight.created_at.is_some());
+    }
+
+    #[test]
+    fn parses_koreader_sidecars() {
+        let sidecar = r#"-- we can read Lua syntax here!
+return {
+    ["annotations"] = {
+        [1] = {
+            ["chapter"] = "One",
+            ["datetime"] = "2024-03-04 22:12:33",
+            ["drawer"] = "lighten",
+            ["note"] = "Remember \"this\"",
+            ["pageno"] = 12,
+            ["pos0"] = "/body/DocFragment[2]/body/p[3]/text().0",
+            ["text"] = "It was a bright cold day",
+        },
+        [2] = {
+            ["datetime"] = "2024-03-04 22:20:00",
+            ["page"] = "/body/DocFragment[4]",
+            ["text"] = "in Chapter Two",
+        },
+    },
+    ["doc_props"] = {
+        ["authors"] = "George Orwell",
+        ["title"] = "1984",
+    },
+}"#;
+        let highlights = parse_koreader_sidecar(sidecar, "fallback").unwrap();
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(highlights[0].book_title, "1984");
+        assert_eq!(highlights[0].author.as_deref(), Some("George Orwell"));
+        assert_eq!(highlights[0].text, "It was a bright cold day");
+        assert_eq!(highlights[0].note.as_deref(), Some("Remember \"this\""));
+        assert_eq!(highlights[0].location.as_deref(), Some("Page 12"));
+        assert_eq!(highlights[0].chapter.as_deref(), Some("One"));
+
+        let legacy = r#"return {
+            ["bookmarks"] = {
+                [1] = { ["notes"] = "War is peace", ["text"] = "Slogan", ["datetime"] = "2024-01-01 10:00:00" },
+            },
+            ["highlight"] = {
+                [5] = { [1] = { ["text"] = "War is peace", ["datetime"] = "2024-01-01 10:00:00" } },
+            },
+        }"#;
+        let highlights = parse_koreader_sidecar(legacy, "Nineteen").unwrap();
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(highlights[0].book_title, "Nineteen");
+        assert_eq!(highlights[0].note.as_deref(), Some("Slogan"));
+    }
+}
@@ -0,0 +1,378 @@
+//! Saved web pages (`.html`, `.htm`, `.xhtml`) and MHTML archives
+//!
+//! The page is decoded using its BOM or declared charset, the main content is
+//! pulled out by the readability extractor and split into sections at
+//! `<h1>`–`<h3>`. Images are copied out of the archive, `data:` URIs or the
+//! page's folder next to the other parser image output.
+
+use super::mhtml::{looks_like_mhtml, MhtmlArchive};
+use super::readability::{extract_article, ArticleBlock};
+use super::{image_output_dir, IMAGE_MARKER_PREFIX};
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use base64::Engine;
+use encoding_rs::{Encoding, UTF_8};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+const EMPTY_HTML_PLACEHOLDER: &str = "No readable content extracted from HTML file.";
+const LEADING_SECTION_TITLE: &str = "Content";
+/// `<h1>` to `<h3>` start a new section
+const MAX_SECTION_HEADING_LEVEL: u8 = 3;
+const CHARSET_SNIFF_BYTES: usize = 4096;
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg"];
+
+pub struct HtmlParser {
+    file_path: String,
+}
+
+/// Where the page's images can be read from
+struct ImageSource<'a> {
+    /// URL relative image references are resolved against
+    base_url: Option<Url>,
+    archive: Option<&'a MhtmlArchive>,
+    output_dir: PathBuf,
+    extracted: usize,
+}
+
+impl HtmlParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let bytes = fs::read(&self.file_path)?;
+        let is_archive_extension = Path::new(&self.file_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "mht" | "mhtml"));
+
+        let archive =
+            if is_archive_extension || looks_like_mhtml(&bytes) {
+                Some(MhtmlArchive::parse(&bytes).ok_or_else(|| {
+                    ReaderError::HtmlParse("Not a valid MHTML archive".to_string())
+                })?)
+            } else {
+                None
+            };
+
+        let file_url = fs::canonicalize(&self.file_path)
+            .ok()
+            .and_then(|path| Url::from_file_path(path).ok());
+        let (html, base_url) = match &archive {
+            Some(archive) => {
+                let page = archive.main_html().ok_or_else(|| {
+                    ReaderError::HtmlParse("MHTML archive has no HTML part".to_string())
+                })?;
+                let base_url = page
+                    .location
+                    .as_deref()
+                    .and_then(|location| Url::parse(location).ok());
+                (decode_html(&page.body, page.charset.as_deref()), base_url)
+            }
+            None => (decode_html(&bytes, None), file_url),
+        };
+
+        let article = extract_article(&html);
+
+        let output_dir = image_output_dir(&self.file_path, "html");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut images = ImageSource {
+            base_url,
+            archive: archive.as_ref(),
+            output_dir,
+            extracted: 0,
+        };
+        let sections = build_sections(&article.blocks, &mut images);
+
+        let title = article.title.unwrap_or_else(|| {
+            Path::new(&self.file_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Untitled")
+                .to_string()
+        });
+
+        let metadata = NewDocument {
+            title,
+            author: article.metadata.author,
+            language: article.metadata.language,
+            file_path: self.file_path.clone(),
+            file_type: "html".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+}
+
+impl ImageSource<'_> {
+    /// Copies an image to the output directory and returns its marker line
+    fn extract(&mut self, src: &str) -> Option<String> {
+        let (bytes, extension) = if let Some(data) = src.strip_prefix("data:") {
+            decode_data_uri(data)?
+        } else {
+            let resolved = match &self.base_url {
+                Some(base) => base.join(src).ok()?,
+                None => Url::parse(src).ok()?,
+            };
+            match self.archive {
+                Some(archive) => {
+                    let part = archive
+                        .resource(resolved.as_str())
+                        .or_else(|| archive.resource(src))?;
+                    let extension = extension_for_mime(&part.content_type)
+                        .or_else(|| extension_for_path(resolved.path()))?;
+                    (part.body.clone(), extension)
+                }
+                None if resolved.scheme() == "file" => {
+                    let path = resolved.to_file_path().ok()?;
+                    let extension = extension_for_path(&path.to_string_lossy())?;
+                    (fs::read(&path).ok()?, extension)
+                }
+                None => {
+                    tracing::debug!("Skipping remote image in saved page: {}", resolved);
+                    return None;
+                }
+            }
+        };
+
+        fs::create_dir_all(&self.output_dir).ok()?;
+        self.extracted += 1;
+        let image_path = self
+            .output_dir
+            .join(format!("image_{:03}.{}", self.extracted, extension));
+        fs::write(&image_path, bytes).ok()?;
+
+        Some(format!(
+            "{prefix}{path}]]",
+            prefix = IMAGE_MARKER_PREFIX,
+            path = image_path.to_string_lossy()
+        ))
+    }
+}
+
+/// Decodes page bytes using the BOM, the declared charset or a `<meta>` charset
+///
+/// Falls back to UTF-8 with replacement characters.
+pub fn decode_html(bytes: &[u8], declared_charset: Option<&str>) -> String {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return text.into_owned();
+    }
+    let encoding = declared_charset
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .or_else(|| sniff_meta_charset(bytes))
+        .unwrap_or(UTF_8);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    text.into_owned()
+}
+
+/// Finds `<meta charset=...>` or the `http-equiv` form near the top of the page
+fn sniff_meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(CHARSET_SNIFF_BYTES)])
+        .to_ascii_lowercase();
+    let start = head.find("charset=")? + "charset=".len();
+    let label: String = head[start..]
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    Encoding::for_label(label.as_bytes())
+}
+
+/// Groups blocks into sections starting at `<h1>`–`<h3>`
+///
+/// The heading stays in its section's body, like the Markdown importer does.
+/// Content before the first heading goes into a leading "Content" section.
+fn build_sections(
+    blocks: &[ArticleBlock],
+    images: &mut ImageSource,
+) -> Vec<(String, i32, String, Vec<String>)> {
+    let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+    let mut current_title = LEADING_SECTION_TITLE.to_string();
+    let mut current: Vec<String> = Vec::new();
+
+    let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
+                        title: &str,
+                        paragraphs: Vec<String>| {
+        let order = sections.len() as i32;
+        sections.push((
+            title.to_string(),
+            order,
+            format!("section{}", order + 1),
+            paragraphs,
+        ));
+    };
+
+    for block in blocks {
+        match block {
+            ArticleBlock::Heading { level, text } => {
+                if *level <= MAX_SECTION_HEADING_LEVEL {
+                    if !current.is_empty() {
+                        push_section(&mut sections, &current_title, std::mem::take(&mut current));
+                    }
+                    current_title = text.clone();
+                }
+                current.push(text.clone());
+            }
+            ArticleBlock::Paragraph(text) | ArticleBlock::Code(text) => current.push(text.clone()),
+            ArticleBlock::ListItem(text) => current.push(format!("• {}", text)),
+            ArticleBlock::Image { src, .. } => {
+                if let Some(marker) = images.extract(src) {
+                    current.push(marker);
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() || sections.is_empty() {
+        if current.is_empty() {
+            current.push(EMPTY_HTML_PLACEHOLDER.to_string());
+        }
+        push_section(&mut sections, &current_title, current);
+    }
+
+    sections
+}
+
+/// Decodes the part of a `data:` URI after the scheme into bytes and an extension
+fn decode_data_uri(data: &str) -> Option<(Vec<u8>, &'static str)> {
+    let (header, payload) = data.split_once(',')?;
+    let mut params = header.split(';');
+    let extension = extension_for_mime(&params.next()?.trim().to_ascii_lowercase())?;
+    let bytes = if params.any(|param| param.trim().eq_ignore_ascii_case("base64")) {
+        let compact: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+        base64::engine::general_purpose::STANDARD
+            .decode(compact)
+            .ok()?
+    } else {
+        percent_decode(payload)
+    };
+    Some((bytes, extension))
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let decoded = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(value) => {
+                out.push(value);
+                idx += 3;
+            }
+            None => {
+                out.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    out
+}
+
+fn extension_for_mime(mime: &str) -> Option<&'static str> {
+    match mime {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/bmp" => Some("bmp"),
+        "image/webp" => Some("webp"),
+        "image/svg+xml" => Some("svg"),
+        _ => None,
+    }
+}
+
+fn extension_for_path(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())?
+        .to_ascii_lowercase();
+    IMAGE_EXTENSIONS
+        .iter()
+        .copied()
+        .find(|known| *known == extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlParser;
+    use crate::parsers::IMAGE_MARKER_PREFIX;
+    use std::fs;
+
+    // A 1x1 transparent PNG
+    const PIXEL_PNG_BASE64: &str =
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn parses_mhtml_archive_into_sections() {
+        let archive = format!(
+            "From: <Saved by Blink>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/related;\r\n\
+\ttype=\"text/html\";\r\n\
+\tboundary=\"----MultipartBoundary--abc\"\r\n\
+\r\n\
+------MultipartBoundary--abc\r\n\
+Content-Type: text/html; charset=\"windows-1252\"\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+Content-Location: https://example.com/notes/cafe.html\r\n\
+\r\n\
+<html lang=3D\"fr\"><head><title>Caf=E9 notes</title>\r\n\
+<meta name=3D\"author\" content=3D\"Ana\"></head><body><article>\r\n\
+<p>Notes from the caf=E9, written slowly, over several long mornings, with cof=\r\n\
+fee.</p>\r\n\
+<h2>Menu</h2><p>Bread, butter, jam, and more coffee for everyone who asks.</p>\r\n\
+<img src=3D\"img/pixel.png\" alt=3D\"dot\"><ul><li>Tea</li></ul>\r\n\
+</article></body></html>\r\n\
+------MultipartBoundary--abc\r\n\
+Content-Type: image/png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+Content-Location: https://example.com/notes/img/pixel.png\r\n\
+\r\n\
+{}\r\n\
+------MultipartBoundary--abc--\r\n",
+            PIXEL_PNG_BASE64
+        );
+        let path =
+            std::env::temp_dir().join(format!("reader_html_test_{}.mhtml", std::process::id()));
+        fs::write(&path, archive).unwrap();
+
+        let parser = HtmlParser::new(path.to_str().unwrap()).unwrap();
+        let (metadata, sections) = parser.parse_all().unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(metadata.title, "Café notes");
+        assert_eq!(metadata.author.as_deref(), Some("Ana"));
+        assert_eq!(metadata.language.as_deref(), Some("fr"));
+        assert_eq!(metadata.file_type, "html");
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0, "Content");
+        assert_eq!(
+            sections[0].3,
+            vec!["Notes from the café, written slowly, over several long mornings, with coffee."]
+        );
+        assert_eq!(sections[1].0, "Menu");
+        assert_eq!(sections[1].2, "section2");
+        assert_eq!(sections[1].3[0], "Menu");
+        assert!(sections[1].3[2].starts_with(IMAGE_MARKER_PREFIX));
+        assert_eq!(sections[1].3[3], "• Tea");
+
+        let image_path = sections[1].3[2]
+            .trim_start_matches(IMAGE_MARKER_PREFIX)
+            .trim_end_matches("]]");
+        assert!(fs::read(image_path).unwrap().starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,287 @@
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+// Private-use code points used to carry anchor/link positions through the
+// tag-stripping pass. They never appear in the returned paragraph text.
+const ANCHOR_START: char = '\u{E000}';
+const ANCHOR_END: char = '\u{E001}';
+const LINK_START: char = '\u{E002}';
+const LINK_NOTEREF: char = '\u{E003}';
+const LINK_HREF_END: char = '\u{E004}';
+const LINK_END: char = '\u{E005}';
+
+/// Paragraph text extracted from an (X)HTML document together with the
+/// anchors and hyperlinks found while stripping its markup.
+#[derive(Debug, Default)]
+pub struct HtmlParagraphs {
+    pub paragraphs: Vec<String>,
+    /// Element `id` attributes mapped to the paragraph they belong to.
+    pub anchors: HashMap<String, usize>,
+    pub links: Vec<HtmlLink>,
+}
+
+/// An `<a href>` found inside a paragraph
+#[derive(Debug, Clone)]
+pub struct HtmlLink {
+    pub paragraph_index: usize,
+    pub href: String,
+    pub label: String,
+    /// True for EPUB footnote references (`epub:type="noteref"` or `role="doc-noteref"`)
+    pub is_noteref: bool,
+}
+
+/// Splits an (X)HTML document into plain-text paragraphs
+///
+/// Paragraph boundaries follow `<p>`, `<div>` and `<br>` tags plus source
+/// line breaks; every other tag is stripped. Anchor ids and links are kept
+/// so that footnotes and cross references can be resolved afterwards.
+pub fn extract_paragraphs(html: &[u8]) -> HtmlParagraphs {
+    let html_str = String::from_utf8_lossy(html);
+
+    let text = html_str
+        .replace("<p>", "\n")
+        .replace("</p>", "\n")
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<div>", "\n")
+        .replace("</div>", "\n");
+
+    // Remove all other HTML tags, leaving markers behind for ids and links
+    let tag_re = Regex::new(r"<[^>]+>").unwrap();
+    let id_re = Regex::new(r#"(?:^|\s)id\s*=\s*["']([^"']+)["']"#).unwrap();
+    let href_re = Regex::new(r#"(?:^|\s)href\s*=\s*["']([^"']*)["']"#).unwrap();
+    let type_re = Regex::new(r#"(?:epub:type|role)\s*=\s*["']([^"']*)["']"#).unwrap();
+
+    let text = tag_re.replace_all(&text, |caps: &Captures| {
+        let tag = &caps[0];
+        if let Some(closing) = tag.strip_prefix("</") {
+            let name = closing.trim_end_matches('>').trim().to_ascii_lowercase();
+            return if name == "a" {
+                LINK_END.to_string()
+            } else {
+                String::new()
+            };
+        }
+        if tag.starts_with("<!") || tag.starts_with("<?") {
+            return String::new();
+        }
+
+        let mut out = String::new();
+        if let Some(id) = id_re.captures(tag) {
+            out.push(ANCHOR_START);
+            out.push_str(id[1].trim());
+            out.push(ANCHOR_END);
+        }
+
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if name == "a" && !tag.ends_with("/>") {
+            if let Some(href) = href_re.captures(tag) {
+                out.push(LINK_START);
+                let noteref = type_re
+                    .captures(tag)
+                    .is_some_and(|t| t[1].contains("noteref"));
+                if noteref {
+                    out.push(LINK_NOTEREF);
+                }
+                out.push_str(href[1].trim());
+                out.push(LINK_HREF_END);
+            }
+        }
+        out
+    });
+
+    let mut result = HtmlParagraphs::default();
+    let mut pending_anchors: Vec<String> = Vec::new();
+
+    for segment in text.split('\n') {
+        let parsed = parse_segment(segment);
+        pending_anchors.extend(parsed.anchors);
+
+        let trimmed = parsed.text.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let index = result.paragraphs.len();
+        let offset = parsed.text.len() - parsed.text.trim_start().len();
+        for anchor in pending_anchors.drain(..) {
+            result.anchors.entry(anchor).or_insert(index);
+        }
+        for (href, is_noteref, start, end) in parsed.links {
+            let start = start.saturating_sub(offset).min(trimmed.len());
+            let end = end.saturating_sub(offset).min(trimmed.len());
+            result.links.push(HtmlLink {
+                paragraph_index: index,
+                href,
+                label: trimmed.get(start..end).unwrap_or("").trim().to_string(),
+                is_noteref,
+            });
+        }
+        result.paragraphs.push(trimmed.to_string());
+    }
+
+    result
+}
+
+struct ParsedSegment {
+    text: String,
+    anchors: Vec<String>,
+    /// (href, is_noteref, label start byte, label end byte) within `text`
+    links: Vec<(String, bool, usize, usize)>,
+}
+
+fn parse_segment(segment: &str) -> ParsedSegment {
+    let mut text = String::with_capacity(segment.len());
+    let mut anchors = Vec::new();
+    let mut links = Vec::new();
+    let mut open_link: Option<(String, bool, usize)> = None;
+    let mut chars = segment.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ANCHOR_START => {
+                let id: String = chars.by_ref().take_while(|&c| c != ANCHOR_END).collect();
+                if !id.is_empty() {
+                    anchors.push(id);
+                }
+            }
+            LINK_START => {
+                let raw: String = chars.by_ref().take_while(|&c| c != LINK_HREF_END).collect();
+                let (href, is_noteref) = match raw.strip_prefix(LINK_NOTEREF) {
+                    Some(rest) => (rest.to_string(), true),
+                    None => (raw, false),
+                };
+                if let Some((href, is_noteref, start)) = open_link.take() {
+                    links.push((href, is_noteref, start, text.len()));
+                }
+                open_link = Some((href, is_noteref, text.len()));
+            }
+            LINK_END => {
+                if let Some((href, is_noteref, start)) = open_link.take() {
+                    links.push((href, is_noteref, start, text.len()));
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+
+    // A link left open at a line break ends with the segment
+    if let Some((href, is_noteref, start)) = open_link.take() {
+        links.push((href, is_noteref, start, text.len()));
+    }
+
+    ParsedSegment {
+        text,
+        anchors,
+        links,
+    }
+}
+
+/// Resolves a relative `href` against the path of the document it appears in
+///
+/// Returns the target document path (percent-decoded, `..` collapsed) and the
+/// optional fragment. External links (`http:`, `mailto:` …) yield `None`.
+pub fn resolve_relative_href(base_path: &str, href: &str) -> Option<(String, Option<String>)> {
+    let href = href.trim();
+    if href.is_empty() || href.contains("://") || href.starts_with("mailto:") {
+        return None;
+    }
+
+    let (path_part, fragment) = match href.split_once('#') {
+        Some((path, fragment)) => (path, Some(percent_decode(fragment))),
+        None => (href, None),
+    };
+    let path_part = path_part.split('?').next().unwrap_or(path_part);
+    let base_path = base_path.replace('\\', "/");
+
+    if path_part.is_empty() {
+        return Some((base_path, fragment.filter(|f| !f.is_empty())));
+    }
+
+    let mut segments: Vec<&str> = if path_part.starts_with('/') {
+        Vec::new()
+    } else {
+        let mut dir: Vec<&str> = base_path.split('/').collect();
+        dir.pop();
+        dir
+    };
+    for segment in path_part.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+
+    let joined = segments
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((percent_decode(&joined), fragment.filter(|f| !f.is_empty())))
+}
+
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = &input[i + 1..i + 3];
+            if let Ok(value) = u8::from_str_radix(hex, 16) {
+                out.push(value);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_paragraphs, resolve_relative_href};
+
+    #[test]
+    fn keeps_noteref_and_footnote_anchor() {
+        let html = br##"<html><body>
+<p>Body text<a epub:type="noteref" href="#fn1" id="ref1">1</a> continues.</p>
+<aside epub:type="footnote" id="fn1"><p>The footnote body.</p></aside>
+</body></html>"##;
+        let parsed = extract_paragraphs(html);
+        assert_eq!(
+            parsed.paragraphs,
+            vec!["Body text1 continues.", "The footnote body."]
+        );
+        assert_eq!(parsed.anchors.get("ref1"), Some(&0));
+        assert_eq!(parsed.anchors.get("fn1"), Some(&1));
+        assert_eq!(parsed.links.len(), 1);
+        assert_eq!(parsed.links[0].href, "#fn1");
+        assert_eq!(parsed.links[0].label, "1");
+        assert!(parsed.links[0].is_noteref);
+    }
+
+    #[test]
+    fn resolves_cross_chapter_href() {
+        assert_eq!(
+            resolve_relative_href("OEBPS/Text/ch1.xhtml", "../Text/ch3.xhtml#sec2"),
+            Some(("OEBPS/Text/ch3.xhtml".to_string(), Some("sec2".to_string())))
+        );
+        assert_eq!(
+            resolve_relative_href("OEBPS/ch1.xhtml", "#fn1"),
+            Some(("OEBPS/ch1.xhtml".to_string(), Some("fn1".to_string())))
+        );
+        assert_eq!(
+            resolve_relative_href("ch1.xhtml", "https://example.com"),
+            None
+        );
+    }
+}
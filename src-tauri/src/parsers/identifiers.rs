@@ -0,0 +1,185 @@
+//! ISBN and DOI detection in document metadata and front matter
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// An "ISBN", "ISBN-10" or "ISBN-13" label and the separator after it
+static ISBN_LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bISBN(?:-1[03])?[:\s]*").unwrap());
+/// An ISBN-13 at the start of the text, not followed by more digits
+static LEADING_ISBN_13: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^97[89](?:[\s-]?[0-9]){10}\b").unwrap());
+/// An ISBN-10 at the start of the text
+static LEADING_ISBN_10: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:[0-9][\s-]?){9}[0-9Xx]").unwrap());
+static BARE_ISBN_13: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b97[89](?:[\s-]?[0-9]){10}\b").unwrap());
+static DOI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\b10\.[0-9]{4,9}/[^\s"'<>]+"#).unwrap());
+
+/// Book and article identifiers found in a document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identifiers {
+    /// ISBN-13 or ISBN-10, digits only
+    pub isbn: Option<String>,
+    /// DOI, e.g. `10.1000/182`
+    pub doi: Option<String>,
+}
+
+impl Identifiers {
+    /// Finds the first valid ISBN and DOI in free text such as a copyright page
+    ///
+    /// Numbers labelled "ISBN" are preferred; an unlabelled 13-digit number is
+    /// only taken when it starts with 978/979 and its check digit is right.
+    pub fn scan(text: &str) -> Self {
+        // Both lengths are tried, so a year after an ISBN-10 isn't read as
+        // the rest of an ISBN-13
+        let isbn = ISBN_LABEL
+            .find_iter(text)
+            .find_map(|label| {
+                let rest = &text[label.end()..];
+                [&LEADING_ISBN_13, &LEADING_ISBN_10]
+                    .into_iter()
+                    .find_map(|re| normalize_isbn(re.find(rest)?.as_str()))
+            })
+            .or_else(|| {
+                BARE_ISBN_13
+                    .find_iter(text)
+                    .find_map(|m| normalize_isbn(m.as_str()))
+            });
+
+        let doi = DOI.find_iter(text).find_map(|m| normalize_doi(m.as_str()));
+
+        Self { isbn, doi }
+    }
+
+    /// Reads identifiers from metadata values such as `urn:isbn:…` or `doi:…`
+    pub fn from_values<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut found = Self::default();
+        for value in values {
+            let value = strip_scheme(value.trim());
+            if found.isbn.is_none() {
+                found.isbn = normalize_isbn(value);
+            }
+            if found.doi.is_none() {
+                found.doi = normalize_doi(value);
+            }
+        }
+        found
+    }
+}
+
+/// Removes `urn:isbn:`, `doi:` and similar prefixes, and DOI resolver URLs
+fn strip_scheme(value: &str) -> &str {
+    let lower = value.to_ascii_lowercase();
+    for prefix in [
+        "urn:isbn:",
+        "isbn:",
+        "urn:doi:",
+        "doi:",
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+    ] {
+        if lower.starts_with(prefix) {
+            return value[prefix.len()..].trim();
+        }
+    }
+    value
+}
+
+/// Digits of a valid ISBN-10 or ISBN-13, without separators
+pub fn normalize_isbn(value: &str) -> Option<String> {
+    let value = strip_scheme(value.trim());
+    if value
+        .chars()
+        .any(|c| !(c.is_ascii_digit() || matches!(c, '-' | ' ' | 'X' | 'x')))
+    {
+        return None;
+    }
+    let digits: String = value
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let valid = match digits.len() {
+        10 => {
+            let sum = digits.chars().enumerate().try_fold(0, |sum, (i, c)| {
+                let value = match c {
+                    'X' if i == 9 => 10,
+                    c => c.to_digit(10)?,
+                };
+                Some(sum + value * (10 - i as u32))
+            });
+            sum.is_some_and(|sum| sum % 11 == 0)
+        }
+        13 => {
+            let sum = digits.chars().enumerate().try_fold(0, |sum, (i, c)| {
+                let weight = if i % 2 == 0 { 1 } else { 3 };
+                Some(sum + c.to_digit(10)? * weight)
+            });
+            sum.is_some_and(|sum| sum % 10 == 0)
+        }
+        _ => false,
+    };
+    valid.then_some(digits)
+}
+
+/// A DOI without resolver prefix or trailing punctuation, lower-cased
+pub fn normalize_doi(value: &str) -> Option<String> {
+    let doi = strip_scheme(value.trim()).trim_end_matches(['.', ',', ';', ':', ')', ']']);
+    let (prefix, suffix) = doi.split_once('/')?;
+    let registrant = prefix.strip_prefix("10.")?;
+    let valid = (4..=9).contains(&registrant.len())
+        && registrant.chars().all(|c| c.is_ascii_digit())
+        && !suffix.is_empty()
+        && !suffix.chars().any(char::is_whitespace);
+    // DOIs are case-insensitive
+    valid.then(|| doi.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_doi, normalize_isbn, Identifiers};
+
+    #[test]
+    fn finds_identifiers_in_front_matter_and_metadata() {
+        let copyright = "Copyright © 2008 by Someone.\nISBN-13: 978-0-306-40615-7 (hardcover)\n\
+                         Printed in 2008. Published version: https://doi.org/10.1000/182.";
+        let found = Identifiers::scan(copyright);
+        assert_eq!(found.isbn.as_deref(), Some("9780306406157"));
+        assert_eq!(found.doi.as_deref(), Some("10.1000/182"));
+
+        // A wrong check digit is not an ISBN
+        assert_eq!(Identifiers::scan("ISBN 978-0-306-40615-8").isbn, None);
+        assert_eq!(
+            Identifiers::scan("order no. 9780306406157").isbn.as_deref(),
+            Some("9780306406157")
+        );
+        assert_eq!(Identifiers::scan("page 1234567890123").isbn, None);
+        // A year after an ISBN-10 is not part of it
+        assert_eq!(
+            Identifiers::scan("ISBN 0-306-40615-2 1998").isbn.as_deref(),
+            Some("0306406152")
+        );
+        assert_eq!(
+            Identifiers::scan("ISBN: 080442957X, 2nd ed.")
+                .isbn
+                .as_deref(),
+            Some("080442957X")
+        );
+
+        let epub = Identifiers::from_values(["urn:uuid:1234", "urn:isbn:0-306-40615-2"]);
+        assert_eq!(epub.isbn.as_deref(), Some("0306406152"));
+        assert_eq!(epub.doi, None);
+
+        assert_eq!(normalize_isbn("080442957X").as_deref(), Some("080442957X"));
+        assert_eq!(normalize_isbn("ISBN 0-8044-2957-X"), None);
+        assert_eq!(
+            normalize_doi("doi:10.1038/NPHYS1170").as_deref(),
+            Some("10.1038/nphys1170")
+        );
+        assert_eq!(normalize_doi("10.12/x"), None);
+    }
+}
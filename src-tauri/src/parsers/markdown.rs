@@ -34,77 +34,88 @@ impl MarkdownParser {
     }
 
     fn parse_markdown(&self, content: &str) -> (String, Vec<(String, i32, String, Vec<String>)>) {
-        let mut title = Path::new(&self.file_path)
+        let fallback_title = Path::new(&self.file_path)
             .file_stem()
             .and_then(|s| s.to_str())
-            .unwrap_or("Untitled")
-            .to_string();
-
-        let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
-        let mut current_section_title = "Content".to_string();
-        let mut current_buffer: Vec<String> = Vec::new();
-        let mut section_order = 0;
-        let mut in_code_block = false;
-
-        let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
-                            title: &str,
-                            order: i32,
-                            buffer: &[String]| {
-            let paragraphs = split_paragraphs(buffer);
-            sections.push((
-                title.to_string(),
-                order,
-                format!("section{}", order + 1),
-                paragraphs,
-            ));
-        };
+            .unwrap_or("Untitled");
+        build_markdown_sections(content, fallback_title)
+    }
+}
 
-        for line in content.lines() {
-            let trimmed = line.trim();
+/// Splits Markdown into sections at its headings
+///
+/// Returns the first `# ` heading (or `fallback_title`) and the sections. Also
+/// used by the importers that convert other markup to Markdown.
+pub(crate) fn build_markdown_sections(
+    content: &str,
+    fallback_title: &str,
+) -> (String, Vec<(String, i32, String, Vec<String>)>) {
+    let mut title = fallback_title.to_string();
+
+    let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+    let mut current_section_title = "Content".to_string();
+    let mut current_buffer: Vec<String> = Vec::new();
+    let mut section_order = 0;
+    let mut in_code_block = false;
+
+    let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
+                        title: &str,
+                        order: i32,
+                        buffer: &[String]| {
+        let paragraphs = split_paragraphs(buffer);
+        sections.push((
+            title.to_string(),
+            order,
+            format!("section{}", order + 1),
+            paragraphs,
+        ));
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
 
-            if trimmed.starts_with("```") {
-                in_code_block = !in_code_block;
-            }
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
 
-            if !in_code_block && trimmed.starts_with('#') {
-                let heading = trimmed.trim_start_matches('#').trim();
-                if !heading.is_empty() {
-                    if trimmed.starts_with("# ") {
-                        title = heading.to_string();
-                    }
-
-                    if has_meaningful_content(&current_buffer) {
-                        push_section(
-                            &mut sections,
-                            &current_section_title,
-                            section_order,
-                            &current_buffer,
-                        );
-                        current_buffer.clear();
-                        section_order += 1;
-                    }
-
-                    current_section_title = heading.to_string();
-                    // Keep heading markdown line in body so reader can render full markdown document.
-                    current_buffer.push(trimmed.to_string());
-                    continue;
+        if !in_code_block && trimmed.starts_with('#') {
+            let heading = trimmed.trim_start_matches('#').trim();
+            if !heading.is_empty() {
+                if trimmed.starts_with("# ") {
+                    title = heading.to_string();
                 }
-            }
 
-            current_buffer.push(line.to_string());
-        }
+                if has_meaningful_content(&current_buffer) {
+                    push_section(
+                        &mut sections,
+                        &current_section_title,
+                        section_order,
+                        &current_buffer,
+                    );
+                    current_buffer.clear();
+                    section_order += 1;
+                }
 
-        if has_meaningful_content(&current_buffer) || sections.is_empty() {
-            push_section(
-                &mut sections,
-                &current_section_title,
-                section_order,
-                &current_buffer,
-            );
+                current_section_title = heading.to_string();
+                // Keep heading markdown line in body so reader can render full markdown document.
+                current_buffer.push(trimmed.to_string());
+                continue;
+            }
         }
 
-        (title, sections)
+        current_buffer.push(line.to_string());
     }
+
+    if has_meaningful_content(&current_buffer) || sections.is_empty() {
+        push_section(
+            &mut sections,
+            &current_section_title,
+            section_order,
+            &current_buffer,
+        );
+    }
+
+    (title, sections)
 }
 
 fn split_paragraphs(lines: &[String]) -> Vec<String> {
@@ -0,0 +1,262 @@
+//! MHTML (`.mht` / `.mhtml`) web archives
+//!
+//! A MIME `multipart/related` message holding the page HTML and the resources
+//! it references. Parts are decoded from base64 or quoted-printable; resources
+//! are looked up by `Content-Location` or `Content-ID`.
+
+use base64::Engine;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct MhtmlPart {
+    /// Lower-cased MIME type without parameters, e.g. `text/html`
+    pub content_type: String,
+    pub charset: Option<String>,
+    pub location: Option<String>,
+    /// `Content-ID` without the surrounding angle brackets
+    pub content_id: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct MhtmlArchive {
+    pub parts: Vec<MhtmlPart>,
+}
+
+impl MhtmlArchive {
+    /// Parses an archive, returning None when the data is not a MIME multipart message
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let (headers, body) = split_headers(data)?;
+        let content_type = headers.get("content-type")?;
+        let (mime, params) = parse_content_type(content_type);
+        if !mime.starts_with("multipart/") {
+            // A single-part archive is just the encoded page
+            let part = decode_part(&headers, body);
+            return Some(Self { parts: vec![part] });
+        }
+        let boundary = params.get("boundary")?;
+
+        let delimiter = format!("--{}", boundary);
+        let mut parts = Vec::new();
+        for chunk in split_on_boundary(body, delimiter.as_bytes()) {
+            let Some((part_headers, part_body)) = split_headers(chunk) else {
+                continue;
+            };
+            parts.push(decode_part(&part_headers, part_body));
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        Some(Self { parts })
+    }
+
+    /// The page itself: the first `text/html` part
+    pub fn main_html(&self) -> Option<&MhtmlPart> {
+        self.parts
+            .iter()
+            .find(|part| part.content_type == "text/html")
+    }
+
+    /// Finds a resource by its URL or `cid:` reference
+    pub fn resource(&self, reference: &str) -> Option<&MhtmlPart> {
+        if let Some(cid) = reference.strip_prefix("cid:") {
+            return self
+                .parts
+                .iter()
+                .find(|part| part.content_id.as_deref() == Some(cid));
+        }
+        self.parts
+            .iter()
+            .find(|part| part.location.as_deref() == Some(reference))
+    }
+}
+
+/// Whether the data looks like a MIME message rather than plain HTML
+pub fn looks_like_mhtml(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(4096)])
+        .to_ascii_lowercase()
+        .replace("\r\n", "\n");
+    let header_block = head.split("\n\n").next().unwrap_or("");
+    header_block.contains("mime-version:") && header_block.contains("content-type: multipart/")
+}
+
+/// Splits a message into unfolded, lower-cased headers and the body
+fn split_headers(data: &[u8]) -> Option<(HashMap<String, String>, &[u8])> {
+    // Skip blank lines left over from the boundary line
+    let start = data
+        .iter()
+        .position(|b| *b != b'\r' && *b != b'\n')
+        .unwrap_or(data.len());
+    let data = &data[start..];
+
+    let (header_end, body_start) = find_blank_line(data)?;
+    let header_text = String::from_utf8_lossy(&data[..header_end]);
+
+    let mut headers = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in header_text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = current.as_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = current.take() {
+            headers.entry(name).or_insert(value);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            current = Some((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    if let Some((name, value)) = current {
+        headers.entry(name).or_insert(value);
+    }
+
+    Some((headers, &data[body_start..]))
+}
+
+/// Returns (end of headers, start of body) around the first empty line
+fn find_blank_line(data: &[u8]) -> Option<(usize, usize)> {
+    let mut idx = 0;
+    while idx < data.len() {
+        if data[idx] == b'\n' {
+            let rest = &data[idx + 1..];
+            if rest.starts_with(b"\r\n") {
+                return Some((idx, idx + 3));
+            }
+            if rest.starts_with(b"\n") {
+                return Some((idx, idx + 2));
+            }
+        }
+        idx += 1;
+    }
+    None
+}
+
+fn split_on_boundary<'a>(body: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut chunks = Vec::new();
+    let mut positions = Vec::new();
+    let mut idx = 0;
+    while idx + delimiter.len() <= body.len() {
+        let at_line_start = idx == 0 || body[idx - 1] == b'\n';
+        if at_line_start && body[idx..].starts_with(delimiter) {
+            positions.push(idx);
+            idx += delimiter.len();
+        } else {
+            idx += 1;
+        }
+    }
+
+    for (i, &pos) in positions.iter().enumerate() {
+        let content_start = pos + delimiter.len();
+        if body[content_start..].starts_with(b"--") {
+            break;
+        }
+        let content_end = positions.get(i + 1).copied().unwrap_or(body.len());
+        let mut chunk = &body[content_start..content_end];
+        // The line break before the next delimiter belongs to the delimiter
+        if chunk.ends_with(b"\r\n") {
+            chunk = &chunk[..chunk.len() - 2];
+        } else if chunk.ends_with(b"\n") {
+            chunk = &chunk[..chunk.len() - 1];
+        }
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+fn decode_part(headers: &HashMap<String, String>, body: &[u8]) -> MhtmlPart {
+    let (content_type, params) = headers
+        .get("content-type")
+        .map(|value| parse_content_type(value))
+        .unwrap_or_else(|| ("text/plain".to_string(), HashMap::new()));
+    let encoding = headers
+        .get("content-transfer-encoding")
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let body = match encoding.as_str() {
+        "base64" => {
+            let compact: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(&compact)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Invalid base64 MHTML part: {}", e);
+                    Vec::new()
+                })
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    };
+
+    MhtmlPart {
+        content_type,
+        charset: params.get("charset").cloned(),
+        location: headers.get("content-location").cloned(),
+        content_id: headers.get("content-id").map(|id| {
+            id.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        }),
+        body,
+    }
+}
+
+/// Splits `type/subtype; key=value` into the lower-cased type and its parameters
+fn parse_content_type(value: &str) -> (String, HashMap<String, String>) {
+    let mut pieces = value.split(';');
+    let mime = pieces.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = pieces
+        .filter_map(|piece| piece.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_ascii_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (mime, params)
+}
+
+fn decode_quoted_printable(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    let mut idx = 0;
+    while idx < body.len() {
+        let byte = body[idx];
+        if byte != b'=' {
+            out.push(byte);
+            idx += 1;
+            continue;
+        }
+        // Soft line break
+        if body[idx + 1..].starts_with(b"\r\n") {
+            idx += 3;
+            continue;
+        }
+        if body[idx + 1..].starts_with(b"\n") {
+            idx += 2;
+            continue;
+        }
+        let hex = body
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(value) => {
+                out.push(value);
+                idx += 3;
+            }
+            None => {
+                out.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    out
+}
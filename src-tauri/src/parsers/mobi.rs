@@ -0,0 +1,1081 @@
+//! Kindle MOBI / AZW3 (KF8) books without DRM
+//!
+//! The Palm database records are decompressed (PalmDOC or HUFF/CDIC) into
+//! the book's HTML, which then goes through the same HTML-to-text path as
+//! EPUB chapters. MOBI books are split at the entries of their `filepos` table
+//! of contents, or at `<mbp:pagebreak/>` when there is none; KF8 books are
+//! split per source file. Title, authors, language and cover come from EXTH.
+
+use super::html_text;
+use super::{image_output_dir, IMAGE_MARKER_PREFIX};
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use encoding_rs::{UTF_8, WINDOWS_1252};
+use regex::bytes::Regex as BytesRegex;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+const EMPTY_MOBI_PLACEHOLDER: &str = "No readable content extracted from MOBI file.";
+const LEADING_SECTION_TITLE: &str = "Content";
+const CONTENTS_SECTION_TITLE: &str = "Contents";
+const PDB_HEADER_LEN: usize = 78;
+const COMPRESSION_NONE: u16 = 1;
+const COMPRESSION_PALMDOC: u16 = 2;
+const COMPRESSION_HUFF_CDIC: u16 = 17480;
+const ENCODING_UTF8: u32 = 65001;
+/// Record index fields are unset when they hold this value
+const NO_RECORD: u32 = 0xFFFF_FFFF;
+const MAX_HUFF_NESTING: usize = 32;
+const MAX_SECTION_TITLE_CHARS: usize = 120;
+
+const EXTH_AUTHOR: u32 = 100;
+const EXTH_KF8_BOUNDARY: u32 = 121;
+const EXTH_COVER_OFFSET: u32 = 201;
+const EXTH_UPDATED_TITLE: u32 = 503;
+const EXTH_LANGUAGE: u32 = 524;
+
+static REFERENCE: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r"(?is)<reference\b[^>]*>").unwrap());
+static TYPE_TOC: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r#"(?i)\btype\s*=\s*["']?toc\b"#).unwrap());
+static FILEPOS: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r#"(?i)\bfilepos\s*=\s*["']?0*(\d+)"#).unwrap());
+/// A table of contents link: target offset and label
+static FILEPOS_LINK: LazyLock<BytesRegex> = LazyLock::new(|| {
+    BytesRegex::new(r#"(?is)<a\b[^>]*\bfilepos\s*=\s*["']?0*(\d+)[^>]*>(.*?)</a>"#).unwrap()
+});
+static PAGEBREAK: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r"(?i)<mbp:pagebreak\b[^>]*>").unwrap());
+/// Start of a KF8 source file within the flow text
+static HTML_START: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r"(?i)<html[\s>]").unwrap());
+static HEADING: LazyLock<BytesRegex> =
+    LazyLock::new(|| BytesRegex::new(r"(?is)<h[1-3]\b[^>]*>(.*?)</h[1-3]\s*>").unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").unwrap());
+static HEAD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<head\b.*?</head\s*>").unwrap());
+static IMG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<img\b[^>]*>").unwrap());
+static RECINDEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\brecindex\s*=\s*["']?0*(\d+)"#).unwrap());
+/// A KF8 image reference, numbered in base 32
+static KINDLE_EMBED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)kindle:embed:([0-9A-V]+)").unwrap());
+/// Tags that end a paragraph in MOBI markup
+static BLOCK_BOUNDARY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)</(?:h[1-6]|li|blockquote|tr|dt|dd|pre|table|center|section|figure|figcaption)\s*>|<(?:p|div|br|hr|h[1-6]|li|mbp:pagebreak)\b[^>]*>",
+    )
+    .unwrap()
+});
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);").unwrap());
+
+pub struct MobiParser {
+    file_path: String,
+}
+
+/// A Palm database: the whole file plus the start offset of every record
+struct PalmDatabase {
+    data: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+/// Fields of a record 0 (PalmDOC + MOBI + EXTH headers) that the import needs
+///
+/// Record indices are absolute, already shifted by the KF8 boundary.
+#[derive(Debug, Default)]
+struct MobiHeader {
+    compression: u16,
+    text_length: usize,
+    text_record_count: usize,
+    encryption: u16,
+    text_encoding: u32,
+    version: u32,
+    full_name: Option<String>,
+    locale: u32,
+    first_image: Option<usize>,
+    huff_record: usize,
+    huff_record_count: usize,
+    extra_flags: u16,
+    fdst_record: Option<usize>,
+    exth: HashMap<u32, Vec<Vec<u8>>>,
+}
+
+/// A chapter cut out of the book text
+struct RawChapter<'a> {
+    title: Option<String>,
+    html: &'a [u8],
+}
+
+impl PalmDatabase {
+    fn new(data: Vec<u8>) -> Result<Self> {
+        if data.len() < PDB_HEADER_LEN {
+            return Err(ReaderError::MobiParse("File is too small".to_string()));
+        }
+        let record_count = read_u16(&data, 76) as usize;
+        let mut offsets = Vec::with_capacity(record_count);
+        for idx in 0..record_count {
+            let entry = PDB_HEADER_LEN + idx * 8;
+            if entry + 8 > data.len() {
+                return Err(ReaderError::MobiParse("Truncated record list".to_string()));
+            }
+            offsets.push(read_u32(&data, entry) as usize);
+        }
+        Ok(Self { data, offsets })
+    }
+
+    fn type_and_creator(&self) -> &[u8] {
+        &self.data[60..68]
+    }
+
+    fn record(&self, idx: usize) -> Option<&[u8]> {
+        let start = *self.offsets.get(idx)?;
+        let end = self
+            .offsets
+            .get(idx + 1)
+            .copied()
+            .unwrap_or(self.data.len())
+            .min(self.data.len());
+        (start <= end).then(|| &self.data[start..end])
+    }
+}
+
+impl MobiHeader {
+    fn parse(record0: &[u8], section_start: usize) -> Result<Self> {
+        if record0.len() < 16 {
+            return Err(ReaderError::MobiParse("Record 0 is too small".to_string()));
+        }
+        let mut header = MobiHeader {
+            compression: read_u16(record0, 0),
+            text_length: read_u32(record0, 4) as usize,
+            text_record_count: read_u16(record0, 8) as usize,
+            encryption: read_u16(record0, 12),
+            text_encoding: 1252,
+            ..Default::default()
+        };
+        if record0.get(16..20) != Some(b"MOBI".as_slice()) {
+            // Plain PalmDOC e-text
+            return Ok(header);
+        }
+
+        let header_length = read_u32(record0, 20) as usize;
+        let field = |offset: usize| {
+            (offset + 4 <= 16 + header_length && offset + 4 <= record0.len())
+                .then(|| read_u32(record0, offset))
+        };
+        let record_index = |offset: usize| {
+            field(offset)
+                .filter(|value| *value != NO_RECORD)
+                .map(|value| value as usize + section_start)
+        };
+
+        header.text_encoding = field(28).unwrap_or(1252);
+        header.version = field(36).unwrap_or(0);
+        header.locale = field(92).unwrap_or(0);
+        header.first_image = record_index(108);
+        header.huff_record = record_index(112).unwrap_or(0);
+        header.huff_record_count = field(116).unwrap_or(0) as usize;
+        if header.version >= 8 {
+            header.fdst_record = record_index(192);
+        }
+        if header_length >= 0xE4 && record0.len() >= 0xF4 {
+            header.extra_flags = read_u16(record0, 0xF2);
+        }
+
+        if let (Some(offset), Some(length)) = (field(84), field(88)) {
+            let (offset, length) = (offset as usize, length as usize);
+            if let Some(name) = record0.get(offset..offset + length) {
+                header.full_name = Some(decode_bytes(name, header.text_encoding));
+            }
+        }
+
+        let exth_flags = field(128).unwrap_or(0);
+        if exth_flags & 0x40 != 0 {
+            header.exth = parse_exth(&record0[(16 + header_length).min(record0.len())..]);
+        }
+
+        Ok(header)
+    }
+
+    fn exth_string(&self, kind: u32) -> Option<String> {
+        self.exth
+            .get(&kind)?
+            .first()
+            .map(|value| decode_bytes(value, self.text_encoding).trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn exth_u32(&self, kind: u32) -> Option<u32> {
+        let value = self.exth.get(&kind)?.first()?;
+        (value.len() >= 4).then(|| read_u32(value, 0))
+    }
+}
+
+impl MobiParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let pdb = PalmDatabase::new(fs::read(&self.file_path)?)?;
+        match pdb.type_and_creator() {
+            b"BOOKMOBI" | b"TEXtREAd" => {}
+            b"TPZ0TPZ3" | b"TPZ3TPZ0" => {
+                return Err(ReaderError::MobiParse(
+                    "Topaz books are not supported".to_string(),
+                ))
+            }
+            other => {
+                return Err(ReaderError::MobiParse(format!(
+                    "Not a MOBI file (type {})",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        }
+
+        let record0 = pdb
+            .record(0)
+            .ok_or_else(|| ReaderError::MobiParse("Missing record 0".to_string()))?;
+        let mobi6 = MobiHeader::parse(record0, 0)?;
+        check_not_encrypted(&mobi6)?;
+
+        // Combined MOBI/KF8 files carry a second, better HTML copy after the boundary
+        let kf8_start = if mobi6.version >= 8 {
+            Some(0)
+        } else {
+            mobi6
+                .exth_u32(EXTH_KF8_BOUNDARY)
+                .filter(|boundary| *boundary != NO_RECORD)
+                .map(|boundary| boundary as usize)
+                .filter(|boundary| *boundary > 0 && *boundary < pdb.offsets.len())
+        };
+        let (header, section_start) = match kf8_start {
+            Some(0) | None => (mobi6, 0),
+            Some(start) => {
+                let record = pdb.record(start).unwrap_or_default();
+                let header = MobiHeader::parse(record, start)?;
+                check_not_encrypted(&header)?;
+                (header, start)
+            }
+        };
+        let is_kf8 = kf8_start.is_some();
+
+        let text = decompress_text(&pdb, &header, section_start)?;
+        let chapters = if is_kf8 {
+            split_kf8_parts(&text, &pdb, &header)
+        } else {
+            split_mobi_chapters(&text)
+        };
+
+        let image_output_dir = image_output_dir(&self.file_path, "mobi");
+        let _ = fs::remove_dir_all(&image_output_dir);
+        let mut images = ImageWriter {
+            pdb: &pdb,
+            first_image: header.first_image,
+            output_dir: image_output_dir,
+            written: HashMap::new(),
+        };
+
+        let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+        for chapter in &chapters {
+            let html = decode_bytes(chapter.html, header.text_encoding);
+            let paragraphs = chapter_paragraphs(&html, is_kf8, &mut images);
+            if paragraphs.is_empty() {
+                continue;
+            }
+            let order = sections.len() as i32;
+            let title = chapter
+                .title
+                .clone()
+                .unwrap_or_else(|| format!("Section {}", order + 1));
+            sections.push((title, order, format!("section{}", order + 1), paragraphs));
+        }
+
+        // Show the cover first unless the text already starts with it
+        if let Some(cover) = header
+            .exth_u32(EXTH_COVER_OFFSET)
+            .filter(|offset| *offset != NO_RECORD)
+            .and_then(|offset| images.marker(offset as usize + 1))
+        {
+            match sections.first_mut() {
+                Some(first) if first.3.first() != Some(&cover) => first.3.insert(0, cover),
+                Some(_) => {}
+                None => sections.push((
+                    LEADING_SECTION_TITLE.to_string(),
+                    0,
+                    "section1".to_string(),
+                    vec![cover],
+                )),
+            }
+        }
+        if sections.is_empty() {
+            sections.push((
+                LEADING_SECTION_TITLE.to_string(),
+                0,
+                "section1".to_string(),
+                vec![EMPTY_MOBI_PLACEHOLDER.to_string()],
+            ));
+        }
+
+        let title = header
+            .exth_string(EXTH_UPDATED_TITLE)
+            .or_else(|| {
+                header
+                    .full_name
+                    .clone()
+                    .filter(|name| !name.trim().is_empty())
+            })
+            .unwrap_or_else(|| {
+                Path::new(&self.file_path)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Untitled")
+                    .to_string()
+            });
+        let authors: Vec<String> = header
+            .exth
+            .get(&EXTH_AUTHOR)
+            .map(|values| {
+                values
+                    .iter()
+                    .map(|value| decode_bytes(value, header.text_encoding).trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metadata = NewDocument {
+            title,
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            language: header
+                .exth_string(EXTH_LANGUAGE)
+                .or_else(|| language_from_locale(header.locale).map(str::to_string)),
+            file_path: self.file_path.clone(),
+            file_type: "mobi".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+}
+
+fn check_not_encrypted(header: &MobiHeader) -> Result<()> {
+    if header.encryption != 0 {
+        return Err(ReaderError::DrmProtected(
+            "This Kindle book is DRM-protected and cannot be imported".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Decompresses the text records following record 0 of a section
+fn decompress_text(
+    pdb: &PalmDatabase,
+    header: &MobiHeader,
+    section_start: usize,
+) -> Result<Vec<u8>> {
+    let mut huff = if header.compression == COMPRESSION_HUFF_CDIC {
+        let mut records = Vec::new();
+        for idx in header.huff_record..header.huff_record + header.huff_record_count {
+            records.push(pdb.record(idx).ok_or_else(|| {
+                ReaderError::MobiParse(format!("Missing HUFF/CDIC record {}", idx))
+            })?);
+        }
+        Some(HuffDecoder::new(&records)?)
+    } else {
+        None
+    };
+
+    let mut text = Vec::with_capacity(header.text_length);
+    for idx in 1..=header.text_record_count {
+        let Some(record) = pdb.record(section_start + idx) else {
+            break;
+        };
+        let data = &record[..record.len() - trailing_entries_size(record, header.extra_flags)];
+        match header.compression {
+            COMPRESSION_NONE => text.extend_from_slice(data),
+            COMPRESSION_PALMDOC => text.extend(palmdoc_decompress(data)),
+            COMPRESSION_HUFF_CDIC => {
+                if let Some(huff) = huff.as_mut() {
+                    text.extend(huff.decode(data, 0)?);
+                }
+            }
+            other => {
+                return Err(ReaderError::MobiParse(format!(
+                    "Unknown compression type {}",
+                    other
+                )))
+            }
+        }
+    }
+    text.truncate(header.text_length);
+    Ok(text)
+}
+
+/// Size of the extra data appended to a text record, per the header's flags
+fn trailing_entries_size(record: &[u8], flags: u16) -> usize {
+    let mut size = 0;
+    let mut remaining = flags >> 1;
+    while remaining != 0 {
+        if remaining & 1 != 0 {
+            // Backward-encoded variable-width length at the end of the record
+            let end = record.len().saturating_sub(size);
+            let mut value = 0usize;
+            for byte in &record[end.saturating_sub(4)..end] {
+                if byte & 0x80 != 0 {
+                    value = 0;
+                }
+                value = (value << 7) | (byte & 0x7F) as usize;
+            }
+            size += value;
+        }
+        remaining >>= 1;
+    }
+    if flags & 1 != 0 {
+        // Multibyte character overlap
+        if let Some(byte) = record.len().checked_sub(size + 1).map(|idx| record[idx]) {
+            size += (byte & 0x3) as usize + 1;
+        }
+    }
+    size.min(record.len())
+}
+
+/// PalmDOC LZ77 decompression
+fn palmdoc_decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut idx = 0;
+    while idx < data.len() {
+        let byte = data[idx];
+        idx += 1;
+        match byte {
+            0x00 | 0x09..=0x7F => out.push(byte),
+            0x01..=0x08 => {
+                let end = (idx + byte as usize).min(data.len());
+                out.extend_from_slice(&data[idx..end]);
+                idx = end;
+            }
+            0x80..=0xBF => {
+                let Some(&next) = data.get(idx) else {
+                    break;
+                };
+                idx += 1;
+                let pair = ((byte as usize) << 8) | next as usize;
+                let distance = (pair >> 3) & 0x07FF;
+                let length = (pair & 0x07) + 3;
+                if distance == 0 || distance > out.len() {
+                    continue;
+                }
+                for _ in 0..length {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+            0xC0..=0xFF => {
+                out.push(b' ');
+                out.push(byte ^ 0x80);
+            }
+        }
+    }
+    out
+}
+
+/// HUFF/CDIC decompressor
+struct HuffDecoder {
+    /// (code length, terminal, max code) per leading byte
+    dict1: Vec<(u32, bool, u64)>,
+    min_codes: Vec<u64>,
+    max_codes: Vec<u64>,
+    /// Phrase bytes and whether they are already fully decoded
+    phrases: Vec<Option<(Vec<u8>, bool)>>,
+}
+
+impl HuffDecoder {
+    fn new(records: &[&[u8]]) -> Result<Self> {
+        let invalid = |what: &str| ReaderError::MobiParse(format!("Invalid {} record", what));
+        let (huff, cdics) = records.split_first().ok_or_else(|| invalid("HUFF"))?;
+        if huff.len() < 24 || !huff.starts_with(b"HUFF\x00\x00\x00\x18") {
+            return Err(invalid("HUFF"));
+        }
+        let dict1_offset = read_u32(huff, 8) as usize;
+        let dict2_offset = read_u32(huff, 12) as usize;
+        if dict1_offset + 256 * 4 > huff.len() || dict2_offset + 64 * 4 > huff.len() {
+            return Err(invalid("HUFF"));
+        }
+
+        let mut dict1 = Vec::with_capacity(256);
+        for idx in 0..256 {
+            let value = read_u32(huff, dict1_offset + idx * 4);
+            let code_len = value & 0x1F;
+            if code_len == 0 {
+                return Err(invalid("HUFF"));
+            }
+            let max_code = (((value >> 8) as u64 + 1) << (32 - code_len)) - 1;
+            dict1.push((code_len, value & 0x80 != 0, max_code));
+        }
+
+        let mut min_codes = vec![0u64];
+        let mut max_codes = vec![u32::MAX as u64];
+        for code_len in 1..=32u32 {
+            let pair = dict2_offset + (code_len as usize - 1) * 8;
+            let min = read_u32(huff, pair) as u64;
+            let max = read_u32(huff, pair + 4) as u64;
+            min_codes.push(min << (32 - code_len));
+            max_codes.push(((max + 1) << (32 - code_len)) - 1);
+        }
+
+        let mut phrases = Vec::new();
+        for cdic in cdics {
+            if cdic.len() < 16 || !cdic.starts_with(b"CDIC\x00\x00\x00\x10") {
+                return Err(invalid("CDIC"));
+            }
+            let phrase_count = read_u32(cdic, 8) as usize;
+            let bits = read_u32(cdic, 12).min(31);
+            let count = (1usize << bits).min(phrase_count.saturating_sub(phrases.len()));
+            for idx in 0..count {
+                let offset_pos = 16 + idx * 2;
+                if offset_pos + 2 > cdic.len() {
+                    break;
+                }
+                let offset = read_u16(cdic, offset_pos) as usize;
+                let Some(length_pos) = (16 + offset + 2 <= cdic.len()).then_some(16 + offset)
+                else {
+                    phrases.push(None);
+                    continue;
+                };
+                let length = read_u16(cdic, length_pos);
+                let start = length_pos + 2;
+                let end = (start + (length & 0x7FFF) as usize).min(cdic.len());
+                phrases.push(Some((cdic[start..end].to_vec(), length & 0x8000 != 0)));
+            }
+        }
+
+        Ok(Self {
+            dict1,
+            min_codes,
+            max_codes,
+            phrases,
+        })
+    }
+
+    fn decode(&mut self, data: &[u8], depth: usize) -> Result<Vec<u8>> {
+        if depth > MAX_HUFF_NESTING {
+            return Err(ReaderError::MobiParse(
+                "HUFF phrases nest too deeply".to_string(),
+            ));
+        }
+        let mut padded = data.to_vec();
+        padded.extend_from_slice(&[0; 8]);
+        let mut bits_left = data.len() as i64 * 8;
+        let mut pos = 0;
+        let mut window = read_u64(&padded, pos);
+        let mut shift: i32 = 32;
+        let mut out = Vec::new();
+
+        loop {
+            if shift <= 0 {
+                pos += 4;
+                if pos + 8 > padded.len() {
+                    break;
+                }
+                window = read_u64(&padded, pos);
+                shift += 32;
+            }
+            let code = (window >> shift) & 0xFFFF_FFFF;
+            let (mut code_len, terminal, mut max_code) = self.dict1[(code >> 24) as usize];
+            if !terminal {
+                while code_len < 32 && code < self.min_codes[code_len as usize] {
+                    code_len += 1;
+                }
+                max_code = self.max_codes[code_len as usize];
+            }
+            shift -= code_len as i32;
+            bits_left -= code_len as i64;
+            if bits_left < 0 {
+                break;
+            }
+
+            let index = (max_code
+                .checked_sub(code)
+                .ok_or_else(|| ReaderError::MobiParse("Invalid HUFF code".to_string()))?
+                >> (32 - code_len)) as usize;
+            let (phrase, decoded) = self
+                .phrases
+                .get_mut(index)
+                .and_then(Option::take)
+                .ok_or_else(|| ReaderError::MobiParse("Invalid HUFF phrase".to_string()))?;
+            let phrase = if decoded {
+                phrase
+            } else {
+                self.decode(&phrase, depth + 1)?
+            };
+            out.extend_from_slice(&phrase);
+            self.phrases[index] = Some((phrase, true));
+        }
+
+        Ok(out)
+    }
+}
+
+/// Splits MOBI text at its table of contents entries or page breaks
+fn split_mobi_chapters(text: &[u8]) -> Vec<RawChapter<'_>> {
+    let parse_pos =
+        |bytes: &[u8]| -> Option<usize> { std::str::from_utf8(bytes).ok()?.parse().ok() };
+
+    let toc_pos = REFERENCE
+        .find_iter(text)
+        .filter(|tag| TYPE_TOC.is_match(tag.as_bytes()))
+        .find_map(|tag| parse_pos(FILEPOS.captures(tag.as_bytes())?.get(1)?.as_bytes()))
+        .filter(|pos| *pos < text.len());
+
+    let mut entries: Vec<(usize, Option<String>)> = Vec::new();
+    if let Some(toc_pos) = toc_pos {
+        let toc_end = PAGEBREAK
+            .find_at(text, toc_pos + 1)
+            .map(|found| found.start())
+            .unwrap_or(text.len());
+        for caps in FILEPOS_LINK.captures_iter(&text[toc_pos..toc_end]) {
+            let Some(pos) = parse_pos(&caps[1]).filter(|pos| *pos < text.len()) else {
+                continue;
+            };
+            if entries.iter().any(|(existing, _)| *existing == pos) {
+                continue;
+            }
+            entries.push((pos, plain_title(&String::from_utf8_lossy(&caps[2]))));
+        }
+        entries.sort_by_key(|(pos, _)| *pos);
+        if entries.len() >= 2 && toc_pos > entries[0].0 {
+            // A table of contents at the back of the book gets its own section
+            if let Some(idx) = entries.iter().position(|(pos, _)| *pos > toc_pos) {
+                entries.insert(idx, (toc_pos, Some(CONTENTS_SECTION_TITLE.to_string())));
+            } else {
+                entries.push((toc_pos, Some(CONTENTS_SECTION_TITLE.to_string())));
+            }
+        }
+    }
+
+    if entries.len() < 2 {
+        let mut starts = vec![0];
+        starts.extend(PAGEBREAK.find_iter(text).map(|found| found.end()));
+        return chunks_at(text, &starts)
+            .into_iter()
+            .map(|html| RawChapter {
+                title: first_heading(html),
+                html,
+            })
+            .collect();
+    }
+
+    let mut chapters = Vec::new();
+    if entries[0].0 > 0 {
+        chapters.push(RawChapter {
+            title: Some(LEADING_SECTION_TITLE.to_string()),
+            html: &text[..entries[0].0],
+        });
+    }
+    for (idx, (start, title)) in entries.iter().enumerate() {
+        let end = entries
+            .get(idx + 1)
+            .map(|(pos, _)| *pos)
+            .unwrap_or(text.len());
+        let html = &text[*start..end];
+        chapters.push(RawChapter {
+            title: title.clone().or_else(|| first_heading(html)),
+            html,
+        });
+    }
+    chapters
+}
+
+/// Splits the HTML flow of a KF8 book into its source files
+///
+/// Each file's skeleton starts with `<html`; its fragments follow it in the
+/// raw text, so every file's content stays within its chunk.
+fn split_kf8_parts<'a>(
+    text: &'a [u8],
+    pdb: &PalmDatabase,
+    header: &MobiHeader,
+) -> Vec<RawChapter<'a>> {
+    // Flow 0 is the HTML; later flows hold CSS and SVG
+    let flow_end = header
+        .fdst_record
+        .and_then(|idx| pdb.record(idx))
+        .filter(|fdst| fdst.len() >= 20 && fdst.starts_with(b"FDST"))
+        .map(|fdst| {
+            let table = read_u32(fdst, 4) as usize;
+            if table + 8 <= fdst.len() {
+                read_u32(fdst, table + 4) as usize
+            } else {
+                text.len()
+            }
+        })
+        .unwrap_or(text.len())
+        .min(text.len());
+    let html = &text[..flow_end];
+
+    let mut starts = vec![0];
+    starts.extend(HTML_START.find_iter(html).map(|found| found.start()));
+    chunks_at(html, &starts)
+        .into_iter()
+        .map(|chunk| RawChapter {
+            title: first_heading(chunk),
+            html: chunk,
+        })
+        .collect()
+}
+
+/// Cuts `text` at the given start offsets, dropping whitespace-only pieces
+fn chunks_at<'a>(text: &'a [u8], starts: &[usize]) -> Vec<&'a [u8]> {
+    let mut starts = starts.to_vec();
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(idx, start)| {
+            let end = starts.get(idx + 1).copied().unwrap_or(text.len());
+            &text[*start..end]
+        })
+        .filter(|chunk| chunk.iter().any(|b| !b.is_ascii_whitespace()))
+        .collect()
+}
+
+fn first_heading(html: &[u8]) -> Option<String> {
+    HEADING
+        .captures_iter(html)
+        .find_map(|caps| plain_title(&String::from_utf8_lossy(&caps[1])))
+}
+
+/// Strips tags and entities from a TOC label or heading
+fn plain_title(html: &str) -> Option<String> {
+    let text = decode_entities(&TAG.replace_all(html, " "));
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty() && text.chars().count() <= MAX_SECTION_TITLE_CHARS).then_some(text)
+}
+
+/// Writes image records out once and hands back their marker lines
+struct ImageWriter<'a> {
+    pdb: &'a PalmDatabase,
+    first_image: Option<usize>,
+    output_dir: PathBuf,
+    written: HashMap<usize, Option<String>>,
+}
+
+impl ImageWriter<'_> {
+    /// Marker for the 1-based image `recindex` / `kindle:embed` number
+    fn marker(&mut self, image_number: usize) -> Option<String> {
+        let record_idx = self.first_image? + image_number.checked_sub(1)?;
+        if let Some(marker) = self.written.get(&record_idx) {
+            return marker.clone();
+        }
+        let marker = self.write(record_idx);
+        self.written.insert(record_idx, marker.clone());
+        marker
+    }
+
+    fn write(&self, record_idx: usize) -> Option<String> {
+        let data = self.pdb.record(record_idx)?;
+        let extension = if data.starts_with(&[0xFF, 0xD8]) {
+            "jpg"
+        } else if data.starts_with(b"\x89PNG") {
+            "png"
+        } else if data.starts_with(b"GIF8") {
+            "gif"
+        } else if data.starts_with(b"BM") {
+            "bmp"
+        } else {
+            tracing::debug!("Skipping non-image MOBI record {}", record_idx);
+            return None;
+        };
+
+        fs::create_dir_all(&self.output_dir).ok()?;
+        let image_path = self
+            .output_dir
+            .join(format!("image_{:05}.{}", record_idx, extension));
+        fs::write(&image_path, data).ok()?;
+        Some(format!(
+            "{prefix}{path}]]",
+            prefix = IMAGE_MARKER_PREFIX,
+            path = image_path.to_string_lossy()
+        ))
+    }
+}
+
+/// Runs a chapter's HTML through the EPUB text extraction
+///
+/// MOBI markup rarely has line breaks and puts attributes on `<p>`, so block
+/// boundaries are made explicit first; images become marker paragraphs.
+fn chapter_paragraphs(html: &str, is_kf8: bool, images: &mut ImageWriter) -> Vec<String> {
+    let html = HEAD.replace_all(html, "");
+    let html = IMG.replace_all(&html, |caps: &Captures| {
+        let tag = &caps[0];
+        let number = if is_kf8 {
+            KINDLE_EMBED
+                .captures(tag)
+                .and_then(|embed| usize::from_str_radix(&embed[1].to_ascii_uppercase(), 32).ok())
+        } else {
+            RECINDEX
+                .captures(tag)
+                .and_then(|recindex| recindex[1].parse().ok())
+        };
+        number
+            .and_then(|number| images.marker(number))
+            .map(|marker| format!("\n{}\n", marker))
+            .unwrap_or_default()
+    });
+    let html = BLOCK_BOUNDARY.replace_all(&html, "\n$0\n");
+
+    html_text::extract_paragraphs(html.as_bytes())
+        .paragraphs
+        .into_iter()
+        .map(|paragraph| decode_entities(&paragraph).trim().to_string())
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |caps: &Captures| {
+            let entity = &caps[1];
+            let decoded = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{00A0}'),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "lsquo" => Some('‘'),
+                    "rsquo" => Some('’'),
+                    "ldquo" => Some('“'),
+                    "rdquo" => Some('”'),
+                    _ => None,
+                }
+            };
+            decoded
+                .map(String::from)
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn parse_exth(data: &[u8]) -> HashMap<u32, Vec<Vec<u8>>> {
+    let mut records: HashMap<u32, Vec<Vec<u8>>> = HashMap::new();
+    if data.len() < 12 || !data.starts_with(b"EXTH") {
+        return records;
+    }
+    let count = read_u32(data, 8) as usize;
+    let mut pos = 12;
+    for _ in 0..count {
+        if pos + 8 > data.len() {
+            break;
+        }
+        let kind = read_u32(data, pos);
+        let length = read_u32(data, pos + 4) as usize;
+        if length < 8 || pos + length > data.len() {
+            break;
+        }
+        records
+            .entry(kind)
+            .or_default()
+            .push(data[pos + 8..pos + length].to_vec());
+        pos += length;
+    }
+    records
+}
+
+fn decode_bytes(bytes: &[u8], text_encoding: u32) -> String {
+    let encoding = if text_encoding == ENCODING_UTF8 {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    };
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    text.into_owned()
+}
+
+/// Language code for the primary language id of a MOBI locale
+fn language_from_locale(locale: u32) -> Option<&'static str> {
+    match locale & 0xFF {
+        4 => Some("zh"),
+        7 => Some("de"),
+        9 => Some("en"),
+        10 => Some("es"),
+        12 => Some("fr"),
+        16 => Some("it"),
+        17 => Some("ja"),
+        18 => Some("ko"),
+        19 => Some("nl"),
+        22 => Some("pt"),
+        25 => Some("ru"),
+        _ => None,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .unwrap_or(0)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .unwrap_or(0)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    data.get(offset..offset + 8)
+        .map(|bytes| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(bytes);
+            u64::from_be_bytes(buf)
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{palmdoc_decompress, trailing_entries_size, HuffDecoder, MobiParser};
+    use crate::error::ReaderError;
+    use std::fs;
+
+    /// Builds a single-text-record, uncompressed MOBI file
+    fn build_mobi(text: &[u8], encryption: u16) -> Vec<u8> {
+        let mut exth = b"EXTH".to_vec();
+        let records: [(u32, &[u8]); 3] = [(100, b"Ada Writer"), (503, b"Moby Test"), (524, b"en")];
+        let body: Vec<u8> = records
+            .iter()
+            .flat_map(|(kind, value)| {
+                let mut entry = kind.to_be_bytes().to_vec();
+                entry.extend((value.len() as u32 + 8).to_be_bytes());
+                entry.extend_from_slice(value);
+                entry
+            })
+            .collect();
+        exth.extend((12 + body.len() as u32).to_be_bytes());
+        exth.extend((records.len() as u32).to_be_bytes());
+        exth.extend(body);
+
+        let header_length = 0xE8u32;
+        let mut record0 = vec![0u8; 16 + header_length as usize];
+        record0[0..2].copy_from_slice(&1u16.to_be_bytes());
+        record0[4..8].copy_from_slice(&(text.len() as u32).to_be_bytes());
+        record0[8..10].copy_from_slice(&1u16.to_be_bytes());
+        record0[10..12].copy_from_slice(&4096u16.to_be_bytes());
+        record0[12..14].copy_from_slice(&encryption.to_be_bytes());
+        record0[16..20].copy_from_slice(b"MOBI");
+        record0[20..24].copy_from_slice(&header_length.to_be_bytes());
+        record0[28..32].copy_from_slice(&65001u32.to_be_bytes());
+        record0[36..40].copy_from_slice(&6u32.to_be_bytes());
+        record0[108..112].copy_from_slice(&u32::MAX.to_be_bytes());
+        record0[128..132].copy_from_slice(&0x40u32.to_be_bytes());
+        record0.extend(exth);
+
+        let mut file = vec![0u8; 78];
+        file[..9].copy_from_slice(b"Moby_Test");
+        file[60..68].copy_from_slice(b"BOOKMOBI");
+        file[76..78].copy_from_slice(&2u16.to_be_bytes());
+        let first = 78 + 2 * 8 + 2;
+        file.extend((first as u32).to_be_bytes());
+        file.extend([0, 0, 0, 0]);
+        file.extend(((first + record0.len()) as u32).to_be_bytes());
+        file.extend([0, 0, 0, 2]);
+        file.extend([0, 0]);
+        file.extend(record0);
+        file.extend_from_slice(text);
+        file
+    }
+
+    #[test]
+    fn decompresses_palmdoc_records() {
+        // Literal "abc", a 6-byte back reference, a space+char pair and a literal run
+        let data = [b'a', b'b', b'c', 0x80, 0x1B, 0xE1, 0x02, b'x', b'y'];
+        assert_eq!(palmdoc_decompress(&data), b"abcabcabc axy");
+        // One trailing entry of 3 bytes plus a multibyte overlap byte
+        assert_eq!(trailing_entries_size(b"text\x01\x00\x83", 0b11), 4);
+    }
+
+    #[test]
+    fn rejects_huff_codes_above_the_dictionary_maximum() {
+        // Every leading byte is a terminal 8-bit code whose max code is
+        // 0x00FF_FFFF, so any code starting with 0xFF lies above it
+        let mut huff = b"HUFF\x00\x00\x00\x18".to_vec();
+        huff.extend(24u32.to_be_bytes());
+        huff.extend((24 + 256 * 4u32).to_be_bytes());
+        huff.extend([0; 8]);
+        for _ in 0..256 {
+            huff.extend(0x88u32.to_be_bytes());
+        }
+        huff.extend([0; 64 * 4]);
+
+        let mut decoder = HuffDecoder::new(&[&huff]).unwrap();
+        let err = decoder.decode(&[0xFF, 0xFF, 0xFF, 0xFF], 0).unwrap_err();
+        assert!(matches!(err, ReaderError::MobiParse(message) if message == "Invalid HUFF code"));
+    }
+
+    #[test]
+    fn splits_at_filepos_toc_and_rejects_drm() {
+        let mut text = b"<html><head><guide><reference type=\"toc\" filepos=0000000000 /></guide></head><body>".to_vec();
+        let toc_start = text.len();
+        text.extend_from_slice(b"<p>Contents</p><p><a filepos=0000000000>Chapter &amp; One</a></p><p><a filepos=0000000000>Two</a></p><mbp:pagebreak/>");
+        let one = text.len();
+        text.extend_from_slice(
+            b"<h2>One</h2><p width=\"0\">It began.</p><p>Then more.</p><mbp:pagebreak/>",
+        );
+        let two = text.len();
+        text.extend_from_slice(b"<h2>Two</h2><p>The end.</p></body></html>");
+        let text = String::from_utf8(text)
+            .unwrap()
+            .replacen(
+                "filepos=0000000000 /",
+                &format!("filepos={:010} /", toc_start),
+                1,
+            )
+            .replacen(
+                "filepos=0000000000>Chapter",
+                &format!("filepos={:010}>Chapter", one),
+                1,
+            )
+            .replacen(
+                "filepos=0000000000>Two",
+                &format!("filepos={:010}>Two", two),
+                1,
+            );
+
+        let path =
+            std::env::temp_dir().join(format!("reader_mobi_test_{}.mobi", std::process::id()));
+        fs::write(&path, build_mobi(text.as_bytes(), 0)).unwrap();
+        let (metadata, sections) = MobiParser::new(path.to_str().unwrap())
+            .unwrap()
+            .parse_all()
+            .unwrap();
+
+        assert_eq!(metadata.title, "Moby Test");
+        assert_eq!(metadata.author.as_deref(), Some("Ada Writer"));
+        assert_eq!(metadata.language.as_deref(), Some("en"));
+        assert_eq!(metadata.file_type, "mobi");
+        let titles: Vec<&str> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(titles, vec!["Content", "Chapter & One", "Two"]);
+        assert_eq!(sections[1].3, vec!["One", "It began.", "Then more."]);
+        assert_eq!(sections[2].3, vec!["Two", "The end."]);
+
+        fs::write(&path, build_mobi(text.as_bytes(), 2)).unwrap();
+        let result = MobiParser::new(path.to_str().unwrap()).unwrap().parse_all();
+        let _ = fs::remove_file(&path);
+        assert!(matches!(result, Err(ReaderError::DrmProtected(_))));
+    }
+}
@@ -1,7 +1,131 @@
+mod asciidoc;
+mod calibre;
+mod detect;
+mod docx;
 mod epub;
+mod fb2;
+mod feed;
+mod highlights;
+mod html;
+mod html_text;
+mod identifiers;
 mod markdown;
+mod mhtml;
+mod mobi;
+mod notebook;
 mod pdf;
+mod pdf_layout;
+mod pdf_ocr;
+mod pdf_outline;
+mod pdf_text;
+mod readability;
+mod rst;
+mod text;
+mod xml_text;
 
+pub use asciidoc::AsciidocParser;
+pub use calibre::{CalibreBook, CalibreLibrary};
+pub use detect::detect_format;
+pub use docx::DocxParser;
 pub use epub::EpubParser;
+pub use fb2::Fb2Parser;
+pub use feed::{parse_feed, Feed, FeedEntry};
+pub use highlights::{read_highlights, ExternalHighlight};
+pub use html::{decode_html, HtmlParser};
+pub use identifiers::{normalize_doi, normalize_isbn, Identifiers};
 pub use markdown::MarkdownParser;
+pub use mobi::MobiParser;
+pub use notebook::NotebookParser;
 pub use pdf::PdfParser;
+pub use pdf_ocr::OcrOptions;
+pub use readability::{extract_article, Article, ArticleBlock};
+pub use rst::RstParser;
+pub use text::TextParser;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Paragraph text of an extracted image, `[[PDF_IMAGE:/path/to/file.png]]`;
+/// the reader renders such paragraphs as figures.
+pub const IMAGE_MARKER_PREFIX: &str = "[[PDF_IMAGE:";
+
+/// Temporary directory for the images extracted from the file at `path`,
+/// `{tmp}/reader_{kind}_images/{stem}_{path hash}`
+///
+/// Managed documents get their images copied into the library on import.
+pub(crate) fn image_output_dir(path: &str, kind: &str) -> PathBuf {
+    let stem = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or(kind);
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    std::env::temp_dir()
+        .join(format!("reader_{}_images", kind))
+        .join(format!(
+            "{}_{:016x}",
+            sanitize_filename(stem),
+            hasher.finish()
+        ))
+}
+
+/// Replaces everything but ASCII letters, digits, `-`, `_` and `.` with `_`
+pub(crate) fn sanitize_filename(name: &str) -> String {
+    let out: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.is_empty() {
+        "file".to_string()
+    } else {
+        out
+    }
+}
+
+pub const LINK_KIND_FOOTNOTE: &str = "footnote";
+pub const LINK_KIND_INTERNAL: &str = "internal";
+
+/// Paragraph read from the document's own text
+pub const PARAGRAPH_KIND_TEXT: &str = "text";
+/// Paragraph recognised by OCR from a page image
+pub const PARAGRAPH_KIND_OCR: &str = "ocr";
+
+/// A reference from one parsed paragraph to another within the same document
+///
+/// Positions are (chapter index, paragraph index) into the chapters returned
+/// by the parser's `parse_all`; they become paragraph ids at import time.
+#[derive(Debug, Clone)]
+pub struct InternalLink {
+    pub source: (usize, usize),
+    pub target: (usize, usize),
+    pub kind: String,
+    pub label: String,
+}
+
+/// Extra data a parser can hand to the import alongside its chapters
+#[derive(Debug, Default)]
+pub struct ParseExtras {
+    pub links: Vec<InternalLink>,
+    /// Paragraph `location` overrides keyed by (chapter index, paragraph index);
+    /// paragraphs without one get `{href}#p{order}`.
+    pub locations: HashMap<(usize, usize), String>,
+    /// Paragraph kinds keyed the same way; paragraphs without one are
+    /// [`PARAGRAPH_KIND_TEXT`].
+    pub kinds: HashMap<(usize, usize), String>,
+    /// ISBN and DOI read from the file's metadata or front matter
+    pub identifiers: Identifiers,
+    /// Tags from a catalogue the file was imported from, e.g. Calibre
+    pub tags: Vec<String>,
+    /// Series from such a catalogue and the document's position in it
+    pub series: Option<String>,
+    pub series_index: Option<f64>,
+}
@@ -0,0 +1,267 @@
+//! Jupyter notebooks (`.ipynb`)
+//!
+//! Cells are rendered to Markdown: markdown cells as written, code cells as
+//! fenced blocks in the kernel language, and their text outputs as `text`
+//! blocks right after. The result is sectioned like any Markdown file.
+
+use super::markdown::build_markdown_sections;
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Longer outputs are cut off so a noisy training loop doesn't swamp the notebook
+const MAX_OUTPUT_LINES: usize = 200;
+
+pub struct NotebookParser {
+    file_path: String,
+}
+
+impl NotebookParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let content = fs::read_to_string(&self.file_path)?;
+        let notebook: Value = serde_json::from_str(&content)
+            .map_err(|e| ReaderError::NotebookParse(format!("{}", e)))?;
+
+        let fallback_title = Path::new(&self.file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled");
+        let (heading_title, sections) =
+            build_markdown_sections(&notebook_to_markdown(&notebook), fallback_title);
+
+        let metadata = notebook.get("metadata");
+        let title = metadata
+            .and_then(|m| m.get("title"))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string)
+            .unwrap_or(heading_title);
+        let authors: Vec<&str> = metadata
+            .and_then(|m| m.get("authors"))
+            .and_then(Value::as_array)
+            .map(|authors| {
+                authors
+                    .iter()
+                    .filter_map(|author| author.get("name").and_then(Value::as_str))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metadata = NewDocument {
+            title,
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            language: None,
+            file_path: self.file_path.clone(),
+            file_type: "markdown".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+}
+
+fn notebook_to_markdown(notebook: &Value) -> String {
+    let language = notebook
+        .pointer("/metadata/language_info/name")
+        .or_else(|| notebook.pointer("/metadata/kernelspec/language"))
+        .and_then(Value::as_str)
+        .unwrap_or("python");
+
+    // nbformat 3 kept the cells in worksheets
+    let cells: Vec<&Value> = match notebook.get("cells").and_then(Value::as_array) {
+        Some(cells) => cells.iter().collect(),
+        None => notebook
+            .get("worksheets")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|sheet| sheet.get("cells").and_then(Value::as_array))
+            .flatten()
+            .collect(),
+    };
+
+    let mut blocks: Vec<String> = Vec::new();
+    for cell in cells {
+        let source = cell
+            .get("source")
+            .or_else(|| cell.get("input"))
+            .map(multiline_text)
+            .unwrap_or_default();
+        match cell.get("cell_type").and_then(Value::as_str) {
+            Some("markdown") => {
+                if !source.trim().is_empty() {
+                    blocks.push(source.trim().to_string());
+                }
+            }
+            Some("heading") => {
+                let level = cell.get("level").and_then(Value::as_u64).unwrap_or(1);
+                blocks.push(format!(
+                    "{} {}",
+                    "#".repeat(level.clamp(1, 6) as usize),
+                    source.trim()
+                ));
+            }
+            Some("code") => {
+                if !source.trim().is_empty() {
+                    blocks.push(fenced(language, source.trim_end()));
+                }
+                for output in cell
+                    .get("outputs")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    if let Some(text) = output_text(output) {
+                        blocks.push(fenced("text", &truncate_lines(&text)));
+                    }
+                }
+            }
+            _ => {
+                if !source.trim().is_empty() {
+                    blocks.push(fenced("text", source.trim_end()));
+                }
+            }
+        }
+    }
+
+    blocks.join("\n\n")
+}
+
+/// Text shown for one cell output, if it has any
+fn output_text(output: &Value) -> Option<String> {
+    let text = match output.get("output_type").and_then(Value::as_str)? {
+        "stream" => output.get("text").map(multiline_text)?,
+        "execute_result" | "display_data" | "pyout" => {
+            let data = output.get("data").unwrap_or(output);
+            data.get("text/plain")
+                .or_else(|| data.get("text"))
+                .or_else(|| data.get("text/markdown"))
+                .map(multiline_text)?
+        }
+        "error" | "pyerr" => {
+            let traceback = output
+                .get("traceback")
+                .map(multiline_lines)
+                .unwrap_or_default();
+            if traceback.is_empty() {
+                format!(
+                    "{}: {}",
+                    output
+                        .get("ename")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Error"),
+                    output.get("evalue").and_then(Value::as_str).unwrap_or("")
+                )
+            } else {
+                traceback.join("\n")
+            }
+        }
+        _ => return None,
+    };
+
+    let ansi_re = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap();
+    let text = ansi_re.replace_all(&text, "").trim_end().to_string();
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Notebook strings are stored either whole or as a list of lines
+fn multiline_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Like [`multiline_text`] for lists whose entries lack trailing newlines
+fn multiline_lines(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(lines) => lines
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        other => vec![multiline_text(other)],
+    }
+}
+
+fn truncate_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= MAX_OUTPUT_LINES {
+        return text.to_string();
+    }
+    format!(
+        "{}\n… ({} more lines)",
+        lines[..MAX_OUTPUT_LINES].join("\n"),
+        lines.len() - MAX_OUTPUT_LINES
+    )
+}
+
+/// Wraps code in a fence longer than any backtick run inside it
+fn fenced(language: &str, code: &str) -> String {
+    let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{code}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotebookParser;
+    use std::fs;
+
+    #[test]
+    fn renders_cells_and_outputs_as_markdown_sections() {
+        let notebook = r###"{
+            "metadata": {"kernelspec": {"language": "python"}},
+            "nbformat": 4,
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Data Tour\n", "\n", "Intro text."]},
+                {"cell_type": "code", "source": ["# load\n", "x = 1\n", "print(x)"],
+                 "outputs": [{"output_type": "stream", "name": "stdout", "text": ["1\n"]}]},
+                {"cell_type": "markdown", "source": "## Errors"},
+                {"cell_type": "code", "source": "1/0",
+                 "outputs": [{"output_type": "error", "ename": "ZeroDivisionError", "evalue": "division by zero",
+                              "traceback": ["\u001b[0;31mZeroDivisionError\u001b[0m: division by zero"]}]}
+            ]
+        }"###;
+        let path =
+            std::env::temp_dir().join(format!("reader_notebook_test_{}.ipynb", std::process::id()));
+        fs::write(&path, notebook).unwrap();
+        let (metadata, sections) = NotebookParser::new(path.to_str().unwrap())
+            .unwrap()
+            .parse_all()
+            .unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(metadata.title, "Data Tour");
+        assert_eq!(metadata.file_type, "markdown");
+        assert_eq!(sections.len(), 2);
+        assert_eq!(
+            sections[0].3,
+            vec![
+                "# Data Tour",
+                "Intro text.",
+                "```python\n# load\nx = 1\nprint(x)\n```",
+                "```text\n1\n```",
+            ]
+        );
+        assert_eq!(sections[1].0, "Errors");
+        assert_eq!(
+            sections[1].3[2],
+            "```text\nZeroDivisionError: division by zero\n```"
+        );
+    }
+}
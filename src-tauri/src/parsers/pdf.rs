@@ -1,23 +1,64 @@
+use super::pdf_layout::{self, PageLayout};
+use super::pdf_ocr::{self, OcrEngine, OcrOptions};
+use super::pdf_outline::{self, OutlineEntry};
+use super::pdf_text::{self, ImageHooks};
+use super::{
+    image_output_dir, sanitize_filename, Identifiers, ParseExtras, IMAGE_MARKER_PREFIX,
+    PARAGRAPH_KIND_OCR,
+};
 use crate::error::{ReaderError, Result};
 use crate::models::NewDocument;
 use image::codecs::png::PngEncoder;
 use image::{ColorType, ImageEncoder};
-use pdf::content::{Op, TextDrawAdjusted};
+use pdf::content::Op;
 use pdf::enc::StreamFilter;
 use pdf::file::FileOptions;
 use pdf::object::{ColorSpace, ImageXObject, Resolve, XObject};
-use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::path::PathBuf;
 use std::path::Path;
 use std::process::Command;
 
 pub struct PdfParser {
     file_path: String,
+    ocr: Option<OcrOptions>,
+    ocr_pages: Vec<usize>,
+    extras: ParseExtras,
+}
+const EMPTY_PDF_PLACEHOLDER: &str = "No readable content extracted from PDF.";
+const FRONT_MATTER_TITLE: &str = "Front Matter";
+const PARAGRAPH_END_CHARS: &[char] = &['.', '!', '?', ':', '"', '”', '…', '。', '！', '？'];
+const CONTINUATION_END_CHARS: &[char] = &['-', ',', ';', '，', '、'];
+/// Pages with fewer recognised characters than this are treated as scans
+const MIN_TEXT_LAYER_CHARS: usize = 16;
+/// Leading pages searched for an ISBN or DOI (title and copyright pages)
+const IDENTIFIER_PAGES: usize = 5;
+
+/// A paragraph of the document text stream and the (1-based) pages it spans
+#[derive(Debug, Clone, PartialEq)]
+struct PdfParagraph {
+    text: String,
+    first_page: usize,
+    last_page: usize,
+    /// Recognised by OCR rather than read from the text layer
+    ocr: bool,
+}
+
+impl PdfParagraph {
+    /// Location used by the reader to open the PDF at the paragraph's page
+    ///
+    /// Paragraphs continuing across a page break record both pages, e.g.
+    /// `page12-13#p4`; the first page is what "open at page" jumps to.
+    fn location(&self, order: usize) -> String {
+        if self.last_page > self.first_page {
+            format!("page{}-{}#p{}", self.first_page, self.last_page, order)
+        } else {
+            format!("page{}#p{}", self.first_page, order)
+        }
+    }
 }
-const PDF_IMAGE_MARKER_PREFIX: &str = "[[PDF_IMAGE:";
 
 impl PdfParser {
     pub fn new(file_path: &str) -> Result<Self> {
@@ -27,9 +68,18 @@ impl PdfParser {
         }
         Ok(Self {
             file_path: file_path.to_string(),
+            ocr: None,
+            ocr_pages: Vec::new(),
+            extras: ParseExtras::default(),
         })
     }
 
+    /// Enables OCR of pages that have no text layer
+    pub fn with_ocr(mut self, options: OcrOptions) -> Self {
+        self.ocr = Some(options);
+        self
+    }
+
     pub fn get_metadata(&self) -> Result<NewDocument> {
         let default_title = Path::new(&self.file_path)
             .file_stem()
@@ -67,31 +117,88 @@ impl PdfParser {
         })
     }
 
-    pub fn extract_text_by_page(&self) -> Result<Vec<(String, Vec<String>)>> {
-        let image_output_dir = build_pdf_image_output_dir(&self.file_path);
+    /// Extracts the paragraphs of every page, with page headers and footers removed
+    fn extract_page_paragraphs(&mut self) -> Result<Vec<Vec<String>>> {
+        let image_output_dir = image_output_dir(&self.file_path, "pdf");
         let _ = fs::remove_dir_all(&image_output_dir);
         let _ = fs::create_dir_all(&image_output_dir);
 
-        if let Some(raw_page_lines) = extract_with_system_tools(&self.file_path, &image_output_dir)
+        let mut raw_page_lines = match extract_with_system_tools(&self.file_path, &image_output_dir)
         {
-            let cleaned_page_lines = clean_page_lines(raw_page_lines);
-            let mut pages = Vec::new();
-
-            for (idx, lines) in cleaned_page_lines.into_iter().enumerate() {
-                let paragraphs = split_pdf_paragraphs(&lines);
-                pages.push((format!("Page {}", idx + 1), paragraphs));
+            Some(raw_page_lines) => raw_page_lines,
+            None => {
+                tracing::info!(
+                    "pdftotext unavailable, extracting text in-process: {}",
+                    self.file_path
+                );
+                self.extract_lines_in_process(&image_output_dir)?
             }
+        };
+        self.recognize_scanned_pages(&mut raw_page_lines, &image_output_dir);
+        Ok(page_paragraphs_from_raw_lines(raw_page_lines))
+    }
+
+    /// Replaces the lines of pages without a text layer with OCR output
+    ///
+    /// Pages that cannot be recognised (OCR disabled, tesseract missing or
+    /// failing) fall back to a rendered snapshot so they still show up.
+    fn recognize_scanned_pages(&mut self, raw_page_lines: &mut [Vec<String>], work_dir: &Path) {
+        self.ocr_pages.clear();
+        let scanned: Vec<usize> = raw_page_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, lines)| lacks_text_layer(lines))
+            .map(|(idx, _)| idx)
+            .collect();
+        if scanned.is_empty() {
+            return;
+        }
+
+        let engine = self.ocr.as_ref().and_then(OcrEngine::detect);
+        match (&self.ocr, &engine) {
+            (None, _) => tracing::info!(
+                "{} pages have no text layer; OCR is disabled",
+                scanned.len()
+            ),
+            (Some(_), None) => tracing::warn!(
+                "{} pages have no text layer but tesseract is not available, skipping OCR",
+                scanned.len()
+            ),
+            (Some(_), Some(engine)) => tracing::info!(
+                "Running OCR ({}) on {} pages without a text layer",
+                engine.languages(),
+                scanned.len()
+            ),
+        }
 
-            if pages.is_empty() {
-                pages.push((
-                    "Page 1".to_string(),
-                    vec!["No readable content extracted from PDF.".to_string()],
-                ));
+        for idx in scanned {
+            let page_number = idx + 1;
+            let recognized = engine
+                .as_ref()
+                .and_then(|engine| engine.recognize_page(&self.file_path, page_number, work_dir))
+                .filter(|lines| !lacks_text_layer(lines));
+            if let Some(lines) = recognized {
+                raw_page_lines[idx] = lines;
+                self.ocr_pages.push(page_number);
+                continue;
             }
 
-            return Ok(pages);
+            let lines = &mut raw_page_lines[idx];
+            if !lines.iter().any(|line| is_pdf_image_marker(line.trim())) {
+                if let Some(marker) =
+                    render_page_snapshot_marker(&self.file_path, page_number, work_dir)
+                {
+                    lines.push(marker);
+                }
+            }
         }
+    }
 
+    /// Extracts page lines with the pure-Rust content-stream walker
+    ///
+    /// Image XObjects and inline images are written to `image_output_dir` and
+    /// referenced by `[[PDF_IMAGE:...]]` marker lines at their drawing position.
+    fn extract_lines_in_process(&self, image_output_dir: &Path) -> Result<Vec<Vec<String>>> {
         let file = FileOptions::cached()
             .open(&self.file_path)
             .map_err(|e| ReaderError::PdfParse(format!("Failed to open PDF: {}", e)))?;
@@ -100,126 +207,278 @@ impl PdfParser {
         for (idx, page_result) in file.pages().enumerate() {
             let page = page_result
                 .map_err(|e| ReaderError::PdfParse(format!("Failed to read page: {}", e)))?;
-            let mut lines: Vec<String> = Vec::new();
-            let mut current_line = String::new();
 
-            if let Some(content) = page.contents.as_ref() {
-                let page_xobject_markers = collect_page_image_markers(
+            let page_xobject_markers =
+                collect_page_image_markers(&file, &page, page_idx_label(idx), image_output_dir);
+            let mut used_xobject_names: HashSet<String> = HashSet::new();
+            let mut inline_image_index = 0usize;
+
+            let mut on_xobject = |name: &str| -> Vec<String> {
+                match page_xobject_markers.get(name) {
+                    Some(markers) => {
+                        used_xobject_names.insert(name.to_string());
+                        markers.clone()
+                    }
+                    None => Vec::new(),
+                }
+            };
+            let mut on_inline_image = |image: &ImageXObject| -> Option<String> {
+                let marker = build_image_marker(
                     &file,
-                    &page,
+                    image,
                     page_idx_label(idx),
-                    &image_output_dir,
+                    &format!("inline{}", inline_image_index),
+                    image_output_dir,
                 );
-                let mut used_xobject_names: HashSet<String> = HashSet::new();
+                inline_image_index += 1;
+                marker
+            };
+            let mut hooks = ImageHooks {
+                xobject: &mut on_xobject,
+                inline_image: &mut on_inline_image,
+            };
 
-                let ops = content.operations(&file).map_err(|e| {
+            let runs =
+                pdf_text::extract_page_runs(&file, &page, Some(&mut hooks)).map_err(|e| {
                     ReaderError::PdfParse(format!(
                         "Failed to parse content stream on page {}: {}",
                         idx + 1,
                         e
                     ))
                 })?;
-
-                let mut inline_image_index = 0usize;
-                for op in ops {
-                    match op {
-                        Op::TextDraw { text } => append_text_fragment(&mut current_line, text.to_string_lossy()),
-                        Op::TextDrawAdjusted { array } => {
-                            for part in array {
-                                if let TextDrawAdjusted::Text(text) = part {
-                                    append_text_fragment(&mut current_line, text.to_string_lossy());
-                                }
-                            }
-                        }
-                        Op::TextNewline => flush_line(&mut lines, &mut current_line),
-                        Op::XObject { name } => {
-                            let key = name.to_string();
-                            if let Some(markers) = page_xobject_markers.get(&key) {
-                                flush_line(&mut lines, &mut current_line);
-                                lines.extend(markers.clone());
-                                used_xobject_names.insert(key);
-                            }
-                        }
-                        Op::InlineImage { image } => {
-                            if let Some(marker) = build_image_marker(
-                                &file,
-                                &image,
-                                page_idx_label(idx),
-                                &format!("inline{}", inline_image_index),
-                                &image_output_dir,
-                            ) {
-                                lines.push(marker);
-                            }
-                            inline_image_index += 1;
-                        }
-                        _ => {}
-                    }
-                }
-
-                // Append image resources that exist on page but are not explicitly referenced
-                // in parsed operators, as a fallback.
-                for (name, markers) in page_xobject_markers {
-                    if used_xobject_names.contains(&name) {
-                        continue;
-                    }
-                    lines.extend(markers);
+            let media_box = page
+                .media_box()
+                .ok()
+                .map(|rect| (rect.left, rect.bottom, rect.right, rect.top));
+            let layout = PageLayout::from_runs(&runs, media_box);
+            let mut lines = pdf_layout::reading_order_lines(&layout);
+
+            // Append image resources that exist on page but are not explicitly referenced
+            // in parsed operators, as a fallback.
+            for (name, markers) in page_xobject_markers {
+                if used_xobject_names.contains(&name) {
+                    continue;
                 }
+                lines.extend(markers);
             }
 
-            flush_line(&mut lines, &mut current_line);
             raw_page_lines.push(lines);
         }
 
-        let cleaned_page_lines = clean_page_lines(raw_page_lines);
-        let mut pages = Vec::new();
+        Ok(raw_page_lines)
+    }
 
-        for (idx, lines) in cleaned_page_lines.into_iter().enumerate() {
-            let paragraphs = split_pdf_paragraphs(&lines);
-            pages.push((format!("Page {}", idx + 1), paragraphs));
+    /// Reads the PDF bookmarks, returning an empty list when there are none
+    fn read_outline(&self) -> Vec<OutlineEntry> {
+        match FileOptions::cached().open(&self.file_path) {
+            Ok(file) => pdf_outline::read_outline(&file, file.get_root()),
+            Err(e) => {
+                tracing::warn!("Failed to open PDF for outline: {}", e);
+                Vec::new()
+            }
         }
+    }
 
-        if pages.is_empty() {
-            pages.push((
-                "Page 1".to_string(),
-                vec!["No readable content extracted from PDF.".to_string()],
-            ));
-        }
+    /// Page locations and OCR flags of the paragraphs returned by the last
+    /// `parse_all`
+    pub fn take_extras(&mut self) -> ParseExtras {
+        std::mem::take(&mut self.extras)
+    }
 
-        Ok(pages)
+    /// 1-based pages whose text came from OCR in the last `parse_all`
+    pub fn ocr_pages(&self) -> &[usize] {
+        &self.ocr_pages
     }
 
-    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+    /// Parses the PDF into chapters
+    ///
+    /// Chapters follow the document outline when it has at least two usable
+    /// bookmarks, and fall back to one chapter per page otherwise. Paragraphs
+    /// broken by a page break are merged back together in both cases.
+    pub fn parse_all(&mut self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
         let metadata = self.get_metadata()?;
-        let pages = self.extract_text_by_page()?;
+        let pages = self.extract_page_paragraphs()?;
+        let page_count = pages.len();
+        let paragraphs = merge_page_paragraphs(pages, &self.ocr_pages);
+
+        let outline = self.read_outline();
+        let sections = if paragraphs.is_empty() {
+            Vec::new()
+        } else {
+            sections_from_outline(&outline, &paragraphs)
+        };
 
-        let mut chapters = Vec::new();
+        let sections = if sections.len() >= 2 {
+            tracing::info!(
+                "Using PDF outline: {} bookmarks -> {} sections",
+                outline.len(),
+                sections.len()
+            );
+            sections
+        } else {
+            sections_by_page(page_count, &paragraphs)
+        };
 
-        for (order_index, (title, paragraphs)) in pages.into_iter().enumerate() {
-            let href = format!("page{}", order_index + 1);
-            chapters.push((title, order_index as i32, href, paragraphs));
+        let mut chapters = Vec::with_capacity(sections.len());
+        let front_matter = paragraphs
+            .iter()
+            .take_while(|p| p.first_page <= IDENTIFIER_PAGES)
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.extras = ParseExtras {
+            identifiers: Identifiers::scan(&front_matter),
+            ..Default::default()
+        };
+
+        for (order_index, (title, start_page, range)) in sections.into_iter().enumerate() {
+            let section_paragraphs = &paragraphs[range];
+            let texts = if section_paragraphs.is_empty() {
+                vec![EMPTY_PDF_PLACEHOLDER.to_string()]
+            } else {
+                for (para_order, paragraph) in section_paragraphs.iter().enumerate() {
+                    self.extras
+                        .locations
+                        .insert((order_index, para_order), paragraph.location(para_order));
+                    if paragraph.ocr {
+                        self.extras
+                            .kinds
+                            .insert((order_index, para_order), PARAGRAPH_KIND_OCR.to_string());
+                    }
+                }
+                section_paragraphs.iter().map(|p| p.text.clone()).collect()
+            };
+            let href = format!("page{}", start_page);
+            chapters.push((title, order_index as i32, href, texts));
         }
 
         Ok((metadata, chapters))
     }
 }
 
-fn append_text_fragment(line: &mut String, fragment: impl AsRef<str>) {
-    let fragment = fragment.as_ref().trim();
-    if fragment.is_empty() {
-        return;
+/// Splits the paragraph stream at the outline entries
+///
+/// Returns (title, first page, paragraph range) per section; text before the
+/// first bookmark becomes a "Front Matter" section and bookmarks that end up
+/// without paragraphs are dropped.
+fn sections_from_outline(
+    outline: &[OutlineEntry],
+    paragraphs: &[PdfParagraph],
+) -> Vec<(String, usize, Range<usize>)> {
+    let positions: Vec<(usize, &str)> = paragraphs
+        .iter()
+        .map(|p| (p.first_page - 1, p.text.as_str()))
+        .collect();
+    let mut starts = pdf_outline::section_starts(outline, &positions);
+    if starts.len() < 2 {
+        return Vec::new();
+    }
+    if starts[0].1 > 0 {
+        starts.insert(0, (FRONT_MATTER_TITLE.to_string(), 0));
     }
-    if !line.is_empty() && !line.ends_with(' ') {
-        line.push(' ');
+
+    let mut sections = Vec::with_capacity(starts.len());
+    for (idx, (title, start)) in starts.iter().enumerate() {
+        let end = starts
+            .get(idx + 1)
+            .map(|(_, next)| *next)
+            .unwrap_or(paragraphs.len());
+        if *start >= end {
+            continue;
+        }
+        sections.push((title.clone(), paragraphs[*start].first_page, *start..end));
+    }
+    sections
+}
+
+/// One section per page; a paragraph belongs to the page it starts on
+fn sections_by_page(
+    page_count: usize,
+    paragraphs: &[PdfParagraph],
+) -> Vec<(String, usize, Range<usize>)> {
+    let mut sections = Vec::with_capacity(page_count.max(1));
+    let mut start = 0usize;
+    for page in 1..=page_count.max(1) {
+        let end = start
+            + paragraphs[start..]
+                .iter()
+                .take_while(|p| p.first_page == page)
+                .count();
+        sections.push((format!("Page {}", page), page, start..end));
+        start = end;
+    }
+    sections
+}
+
+/// Flattens per-page paragraphs into one stream, joining paragraphs that a
+/// page break cut in two
+///
+/// `ocr_pages` lists the 1-based pages whose text came from OCR; paragraphs
+/// are never merged across a text-layer/OCR boundary.
+fn merge_page_paragraphs(pages: Vec<Vec<String>>, ocr_pages: &[usize]) -> Vec<PdfParagraph> {
+    let mut merged: Vec<PdfParagraph> = Vec::new();
+
+    for (page_idx, paragraphs) in pages.into_iter().enumerate() {
+        let page = page_idx + 1;
+        let ocr = ocr_pages.contains(&page);
+        for (idx, text) in paragraphs.into_iter().enumerate() {
+            if idx == 0 {
+                if let Some(previous) = merged.last_mut() {
+                    if previous.last_page + 1 == page
+                        && previous.ocr == ocr
+                        && continues_on_next_page(&previous.text, &text)
+                    {
+                        append_pdf_line_to_paragraph(&mut previous.text, &text);
+                        previous.last_page = page;
+                        continue;
+                    }
+                }
+            }
+            merged.push(PdfParagraph {
+                text,
+                first_page: page,
+                last_page: page,
+                ocr,
+            });
+        }
     }
-    line.push_str(fragment);
+
+    merged
 }
 
-fn flush_line(lines: &mut Vec<String>, current_line: &mut String) {
-    let normalized = normalize_whitespace(current_line);
-    if !normalized.is_empty() {
-        lines.push(normalized);
+/// Whether the last paragraph of a page runs on into the first one of the next
+fn continues_on_next_page(previous: &str, next: &str) -> bool {
+    let previous = previous.trim_end();
+    let next = next.trim_start();
+    if is_pdf_image_marker(previous)
+        || is_pdf_image_marker(next)
+        || previous.contains('\n')
+        || next.contains('\n')
+    {
+        return false;
+    }
+
+    let (Some(last), Some(first)) = (previous.chars().last(), next.chars().next()) else {
+        return false;
+    };
+    if PARAGRAPH_END_CHARS.contains(&last) {
+        return false;
     }
-    current_line.clear();
+
+    first.is_lowercase()
+        || CONTINUATION_END_CHARS.contains(&last)
+        || (is_cjk_char(last) && is_cjk_char(first))
+}
+
+fn is_cjk_char(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+fn page_paragraphs_from_raw_lines(raw_page_lines: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    clean_page_lines(raw_page_lines)
+        .into_iter()
+        .map(|lines| split_pdf_paragraphs(&lines))
+        .collect()
 }
 
 fn normalize_whitespace(input: &str) -> String {
@@ -326,6 +585,13 @@ fn split_pdf_paragraphs(lines: &[String]) -> Vec<String> {
             continue;
         }
 
+        // Figure and table captions always start a paragraph of their own
+        if looks_like_figure_or_table_caption(trimmed) && !current.is_empty() {
+            paragraphs.push(normalize_pdf_paragraph_text(&current));
+            current.clear();
+            current_is_table = false;
+        }
+
         let line_is_table = is_tabular_line(trimmed);
         let normalized_line = normalize_pdf_line_text(trimmed);
 
@@ -366,16 +632,41 @@ fn split_pdf_paragraphs(lines: &[String]) -> Vec<String> {
         paragraphs.push(normalize_pdf_paragraph_text(&current));
     }
 
-    if paragraphs.is_empty() {
-        return vec!["No readable content extracted from PDF.".to_string()];
-    }
-
     paragraphs
 }
 
 fn extract_with_system_tools(pdf_path: &str, output_dir: &Path) -> Option<Vec<Vec<String>>> {
     let _ = output_dir;
-    extract_lines_with_pdftotext(pdf_path)
+    extract_lines_with_pdftotext_bbox(pdf_path).or_else(|| extract_lines_with_pdftotext(pdf_path))
+}
+
+/// Runs `pdftotext -bbox` and rebuilds each page in reading order from the
+/// word boxes, so multi-column pages are not interleaved line by line.
+fn extract_lines_with_pdftotext_bbox(pdf_path: &str) -> Option<Vec<Vec<String>>> {
+    let mut output = None;
+    for cmd in ["/opt/homebrew/bin/pdftotext", "pdftotext"] {
+        match Command::new(cmd)
+            .args(["-bbox", "-enc", "UTF-8", pdf_path, "-"])
+            .output()
+        {
+            Ok(result) => {
+                output = Some(result);
+                break;
+            }
+            Err(_) => continue,
+        }
+    }
+    let output = output?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let html = String::from_utf8_lossy(&output.stdout);
+    let pages = pdf_layout::parse_pdftotext_bbox(&html);
+    if pages.iter().all(|page| page.boxes.is_empty()) {
+        return None;
+    }
+    Some(pages.iter().map(pdf_layout::reading_order_lines).collect())
 }
 
 fn extract_lines_with_pdftotext(pdf_path: &str) -> Option<Vec<Vec<String>>> {
@@ -398,8 +689,14 @@ fn extract_lines_with_pdftotext(pdf_path: &str) -> Option<Vec<Vec<String>>> {
     }
 
     let text = String::from_utf8_lossy(&output.stdout);
-    let mut pages = Vec::new();
-    for segment in text.split('\u{000C}') {
+    // Every page ends with a form feed; empty pages are kept so page numbers
+    // stay aligned with the PDF (scanned pages are picked up by OCR later).
+    let mut segments: Vec<&str> = text.split('\u{000C}').collect();
+    if segments.last().is_some_and(|s| s.trim().is_empty()) {
+        segments.pop();
+    }
+    let mut pages = Vec::with_capacity(segments.len());
+    for segment in segments {
         let mut lines = Vec::new();
         for raw in segment.lines() {
             let line = raw.trim_end_matches('\r').to_string();
@@ -409,9 +706,7 @@ fn extract_lines_with_pdftotext(pdf_path: &str) -> Option<Vec<Vec<String>>> {
                 lines.push(line);
             }
         }
-        if lines.iter().any(|line| !line.trim().is_empty()) {
-            pages.push(lines);
-        }
+        pages.push(lines);
     }
     Some(pages)
 }
@@ -702,7 +997,7 @@ fn extract_page_image_markers_with_pdfimages(
             };
             markers.push(format!(
                 "{prefix}{path}]]",
-                prefix = PDF_IMAGE_MARKER_PREFIX,
+                prefix = IMAGE_MARKER_PREFIX,
                 path = path.to_string_lossy()
             ));
         }
@@ -714,47 +1009,22 @@ fn extract_page_image_markers_with_pdfimages(
     Some(result)
 }
 
-fn render_page_snapshot_marker(pdf_path: &str, page_number: usize, output_dir: &Path) -> Option<String> {
-    let prefix = output_dir.join(format!("page_{:04}", page_number));
-    let prefix_string = prefix.to_string_lossy().to_string();
-    let page_number_string = page_number.to_string();
-
-    let mut rendered = false;
-    for cmd in ["/opt/homebrew/bin/pdftoppm", "pdftoppm"] {
-        let status = Command::new(cmd)
-            .args([
-                "-f",
-                page_number_string.as_str(),
-                "-l",
-                page_number_string.as_str(),
-                "-singlefile",
-                "-png",
-                "-r",
-                "144",
-                pdf_path,
-                prefix_string.as_str(),
-            ])
-            .status();
-        match status {
-            Ok(status) if status.success() => {
-                rendered = true;
-                break;
-            }
-            Ok(_) | Err(_) => continue,
-        }
-    }
-    if !rendered {
-        return None;
-    }
-
-    let png_path = prefix.with_extension("png");
-    if !png_path.exists() {
-        return None;
-    }
+fn render_page_snapshot_marker(
+    pdf_path: &str,
+    page_number: usize,
+    output_dir: &Path,
+) -> Option<String> {
+    let png_path = pdf_ocr::render_page_png(
+        pdf_path,
+        page_number,
+        output_dir,
+        &format!("page_{:04}", page_number),
+        144,
+    )?;
 
     Some(format!(
         "{prefix}{path}]]",
-        prefix = PDF_IMAGE_MARKER_PREFIX,
+        prefix = IMAGE_MARKER_PREFIX,
         path = png_path.to_string_lossy()
     ))
 }
@@ -866,6 +1136,17 @@ fn needs_page_visual_fallback(lines: &[String]) -> bool {
     has_caption || has_formula_noise
 }
 
+/// Whether a page has (next to) no extractable text, as with scanned pages
+fn lacks_text_layer(lines: &[String]) -> bool {
+    let text_chars: usize = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !is_pdf_image_marker(line))
+        .map(|line| line.chars().filter(|c| c.is_alphanumeric()).count())
+        .sum();
+    text_chars < MIN_TEXT_LAYER_CHARS
+}
+
 fn clean_page_lines(page_lines: Vec<Vec<String>>) -> Vec<Vec<String>> {
     if page_lines.is_empty() {
         return page_lines;
@@ -991,8 +1272,7 @@ fn is_repeated_edge_noise(
 }
 
 fn is_pdf_image_marker(line: &str) -> bool {
-    line.starts_with(PDF_IMAGE_MARKER_PREFIX) && line.ends_with("]]"
-    )
+    line.starts_with(IMAGE_MARKER_PREFIX) && line.ends_with("]]")
 }
 
 fn is_tabular_line(line: &str) -> bool {
@@ -1014,36 +1294,6 @@ fn is_tabular_line(line: &str) -> bool {
     alpha_tokens >= 2 && numeric_tokens >= 2 && line.contains(' ')
 }
 
-fn build_pdf_image_output_dir(pdf_path: &str) -> PathBuf {
-    let stem = Path::new(pdf_path)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("pdf");
-    let sanitized = sanitize_filename(stem);
-    let mut hasher = DefaultHasher::new();
-    pdf_path.hash(&mut hasher);
-    let suffix = hasher.finish();
-    std::env::temp_dir()
-        .join("reader_pdf_images")
-        .join(format!("{}_{:016x}", sanitized, suffix))
-}
-
-fn sanitize_filename(name: &str) -> String {
-    let mut out = String::with_capacity(name.len());
-    for ch in name.chars() {
-        if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
-            out.push(ch);
-        } else {
-            out.push('_');
-        }
-    }
-    if out.is_empty() {
-        "pdf".to_string()
-    } else {
-        out
-    }
-}
-
 fn collect_page_image_markers<R: Resolve>(
     file: &R,
     page: &pdf::object::Page,
@@ -1220,7 +1470,7 @@ fn build_image_marker<R: Resolve>(
 
     Some(format!(
         "{prefix}{path}]]",
-        prefix = PDF_IMAGE_MARKER_PREFIX,
+        prefix = IMAGE_MARKER_PREFIX,
         path = abs_path.to_string_lossy()
     ))
 }
@@ -1292,9 +1542,184 @@ fn expand_mono_bitmap(input: &[u8], target_pixels: usize) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use super::{
-        append_pdf_line_to_paragraph, collapse_spaced_uppercase_letters, normalize_pdf_paragraph_text,
-        normalize_whitespace, PdfParser,
+        append_pdf_line_to_paragraph, collapse_spaced_uppercase_letters,
+        extract_lines_with_pdftotext, merge_page_paragraphs, normalize_pdf_paragraph_text,
+        normalize_whitespace, sections_by_page, PdfParagraph, PdfParser,
     };
+    use std::path::{Path, PathBuf};
+
+    const TO_UNICODE_CMAP: &str = "/CIDInit /ProcSet findresource begin\n\
+12 dict begin\n\
+begincmap\n\
+/CMapName /Fixture-UCS def\n\
+/CMapType 2 def\n\
+1 begincodespacerange\n<00> <FF>\nendcodespacerange\n\
+8 beginbfchar\n\
+<01> <0048>\n<02> <0065>\n<03> <006C>\n<04> <006F>\n\
+<05> <0020>\n<06> <0057>\n<07> <0072>\n<08> <0064>\n\
+endbfchar\n\
+endcmap\n\
+CMapName currentdict /CMap defineresource pop\n\
+end\nend";
+
+    /// Writes a two-page fixture PDF: page 1 uses a WinAnsi base font with
+    /// `Td`/`TJ` positioning, page 2 a font whose codes only make sense
+    /// through its ToUnicode CMap.
+    fn write_fixture_pdf(path: &Path) {
+        let page1 = "BT /F1 12 Tf 72 720 Td (Reading order matters.) Tj \
+0 -14 Td [(Second) -250 (line here.)] TJ \
+0 -40 Td (A new paragraph starts.) Tj ET";
+        let page2 = "BT /F2 14 Tf 72 700 Td <0102030304050604070308> Tj ET";
+
+        let objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R 6 0 R] /Count 2 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] \
+/Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+            stream_object(page1),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] \
+/Resources << /Font << /F2 7 0 R >> >> /Contents 8 0 R >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /ToUnicode 9 0 R >>".to_string(),
+            stream_object(page2),
+            stream_object(TO_UNICODE_CMAP),
+        ];
+
+        let mut out = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (idx, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.push_str(&format!("{} 0 obj\n{}\nendobj\n", idx + 1, body));
+        }
+        let xref_offset = out.len();
+        out.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            out.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        out.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        ));
+
+        std::fs::write(path, out).expect("failed to write fixture PDF");
+    }
+
+    fn stream_object(content: &str) -> String {
+        format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len() + 1,
+            content
+        )
+    }
+
+    fn fixture_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("reader-pdf-fixture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create fixture dir");
+        let path = dir.join(name);
+        write_fixture_pdf(&path);
+        path
+    }
+
+    fn page_words(pages: &[Vec<String>]) -> Vec<Vec<String>> {
+        pages
+            .iter()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .flat_map(|line| line.split_whitespace())
+                    .map(|word| word.to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn in_process_extraction_decodes_fixture_pdf() {
+        let path = fixture_path("in-process.pdf");
+        let parser = PdfParser::new(path.to_str().unwrap()).expect("failed to create parser");
+        let output_dir = path.with_extension("images");
+        let pages = parser
+            .extract_lines_in_process(&output_dir)
+            .expect("in-process extraction failed");
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            pages[0],
+            vec![
+                "Reading order matters.",
+                "Second line here.",
+                "",
+                "A new paragraph starts."
+            ]
+        );
+        assert_eq!(pages[1], vec!["Hello World"]);
+    }
+
+    /// Checked-in PDFs with their `pdftotext` output: `kerning.pdf` sets
+    /// words with `TJ` kerning arrays, `type0-tounicode.pdf` uses a two-byte
+    /// Identity-H font that only decodes through its ToUnicode CMap.
+    const REFERENCE_FIXTURES: [&str; 2] = ["kerning", "type0-tounicode"];
+
+    fn reference_fixture(name: &str) -> (PathBuf, Vec<Vec<String>>) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pdf");
+        let text = std::fs::read_to_string(dir.join(format!("{}.txt", name)))
+            .expect("failed to read reference text");
+        // pdftotext ends every page with a form feed
+        let pages = text
+            .split('\u{c}')
+            .filter(|page| !page.trim().is_empty())
+            .map(|page| page.lines().map(|line| line.to_string()).collect())
+            .collect();
+        (dir.join(format!("{}.pdf", name)), pages)
+    }
+
+    fn extract_in_process(path: &Path) -> Vec<Vec<String>> {
+        let parser = PdfParser::new(path.to_str().unwrap()).expect("failed to create parser");
+        let output_dir = std::env::temp_dir().join(format!(
+            "reader-pdf-reference-{}-{}",
+            std::process::id(),
+            path.file_stem().unwrap().to_string_lossy()
+        ));
+        parser
+            .extract_lines_in_process(&output_dir)
+            .expect("in-process extraction failed")
+    }
+
+    #[test]
+    fn in_process_extraction_matches_reference_text() {
+        for name in REFERENCE_FIXTURES {
+            let (path, reference_pages) = reference_fixture(name);
+            assert_eq!(
+                page_words(&extract_in_process(&path)),
+                page_words(&reference_pages),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the pdftotext binary"]
+    fn in_process_extraction_matches_pdftotext_words() {
+        for name in REFERENCE_FIXTURES {
+            let (path, _) = reference_fixture(name);
+            let pdftotext_pages = extract_lines_with_pdftotext(path.to_str().unwrap())
+                .expect("pdftotext is not available");
+            assert_eq!(
+                page_words(&extract_in_process(&path)),
+                page_words(&pdftotext_pages),
+                "{}",
+                name
+            );
+        }
+    }
 
     #[test]
     fn parse_pdf_case_from_env_when_available() {
@@ -1303,7 +1728,7 @@ mod tests {
             Err(_) => return,
         };
 
-        let parser = PdfParser::new(&path).expect("failed to create parser");
+        let mut parser = PdfParser::new(&path).expect("failed to create parser");
         let (_, chapters) = parser.parse_all().expect("failed to parse PDF");
         assert!(!chapters.is_empty(), "expected non-empty chapters");
 
@@ -1329,6 +1754,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn merge_paragraph_split_by_page_break() {
+        let pages = vec![
+            vec![
+                "Chapter One".to_string(),
+                "The rain kept falling over the".to_string(),
+            ],
+            vec![
+                "harbour until dawn.".to_string(),
+                "Next paragraph.".to_string(),
+            ],
+            vec!["Scanned pages follow, and".to_string()],
+            vec!["this one was recognised by OCR.".to_string()],
+        ];
+        let merged = merge_page_paragraphs(pages, &[4]);
+        assert_eq!(
+            merged,
+            vec![
+                PdfParagraph {
+                    text: "Chapter One".to_string(),
+                    first_page: 1,
+                    last_page: 1,
+                    ocr: false,
+                },
+                PdfParagraph {
+                    text: "The rain kept falling over the harbour until dawn.".to_string(),
+                    first_page: 1,
+                    last_page: 2,
+                    ocr: false,
+                },
+                PdfParagraph {
+                    text: "Next paragraph.".to_string(),
+                    first_page: 2,
+                    last_page: 2,
+                    ocr: false,
+                },
+                PdfParagraph {
+                    text: "Scanned pages follow, and".to_string(),
+                    first_page: 3,
+                    last_page: 3,
+                    ocr: false,
+                },
+                PdfParagraph {
+                    text: "this one was recognised by OCR.".to_string(),
+                    first_page: 4,
+                    last_page: 4,
+                    ocr: true,
+                },
+            ]
+        );
+        assert_eq!(merged[1].location(1), "page1-2#p1");
+        assert_eq!(merged[2].location(0), "page2#p0");
+
+        let sections = sections_by_page(4, &merged);
+        let ranges: Vec<_> = sections.iter().map(|(_, _, range)| range.clone()).collect();
+        assert_eq!(ranges, vec![0..2, 2..3, 3..4, 4..5]);
+    }
+
     #[test]
     fn collapse_spaced_uppercase_heading_words() {
         let line = "Kimi K2 T E C H N I C A L R E P O R T";
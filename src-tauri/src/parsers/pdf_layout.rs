@@ -0,0 +1,649 @@
+//! Page layout analysis and reading-order reconstruction for PDFs
+//!
+//! Works on positioned text boxes: runs from the in-process content stream
+//! walker, or words from `pdftotext -bbox`. Boxes are grouped into row
+//! segments, running headers and footers are dropped, column gutters are
+//! found from the horizontal coverage of the segments, and the page is then
+//! emitted column by column between full-width blocks (titles, abstracts,
+//! wide figures and their captions). Narrow columns set in a smaller type
+//! are treated as sidebars and emitted after the main text.
+
+use super::pdf_text::TextRun;
+use regex::Regex;
+use std::collections::HashSet;
+
+/// Share of the page height at the top and bottom searched for headers/footers
+const HEADER_FOOTER_BAND: f32 = 0.08;
+const HEADER_FOOTER_MAX_WORDS: usize = 20;
+/// Horizontal gap, in font sizes, that splits a row into separate segments
+const SEGMENT_GAP_EM: f32 = 1.2;
+/// Horizontal gap, in font sizes, rendered as a space inside a segment
+const WORD_GAP_EM: f32 = 0.15;
+const GUTTER_BIN: f32 = 2.0;
+const MIN_GUTTER_WIDTH: f32 = 6.0;
+const MIN_COLUMN_SEGMENTS: usize = 3;
+const MIN_COLUMN_SHARE: f32 = 0.1;
+const COLUMN_TOLERANCE: f32 = 2.0;
+const SIDEBAR_MAX_SHARE: f32 = 0.3;
+const SIDEBAR_FONT_RATIO: f32 = 0.9;
+const PARAGRAPH_GAP_FACTOR: f32 = 1.6;
+
+/// A piece of text and its bounding box, in page coordinates with the origin
+/// at the top-left corner
+#[derive(Debug, Clone)]
+pub struct TextBox {
+    pub x0: f32,
+    pub y0: f32,
+    pub x1: f32,
+    pub y1: f32,
+    pub text: String,
+    /// Image markers and similar items that must stay on a line of their own
+    pub standalone: bool,
+}
+
+impl TextBox {
+    fn height(&self) -> f32 {
+        (self.y1 - self.y0).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PageLayout {
+    /// Page width and height; header/footer detection is skipped when unknown
+    pub size: Option<(f32, f32)>,
+    pub boxes: Vec<TextBox>,
+}
+
+impl PageLayout {
+    /// Builds a layout from content-stream runs (PDF user space, origin at the
+    /// bottom-left). `media_box` is `(left, bottom, right, top)`.
+    pub fn from_runs(runs: &[TextRun], media_box: Option<(f32, f32, f32, f32)>) -> Self {
+        let (left, top) = match media_box {
+            Some((left, _, _, top)) => (left, top),
+            None => (
+                0.0,
+                runs.iter()
+                    .map(|run| run.y + run.font_size)
+                    .fold(0.0, f32::max),
+            ),
+        };
+
+        let boxes = runs
+            .iter()
+            .filter(|run| !run.text.trim().is_empty())
+            .map(|run| TextBox {
+                x0: run.x - left,
+                y0: top - (run.y + run.font_size * 0.8),
+                x1: run.x - left + run.width.max(0.0),
+                y1: top - (run.y - run.font_size * 0.2),
+                text: run.text.clone(),
+                standalone: run.standalone,
+            })
+            .collect();
+
+        PageLayout {
+            size: media_box
+                .map(|(left, bottom, right, top)| (right - left, top - bottom))
+                .filter(|(width, height)| *width > 0.0 && *height > 0.0),
+            boxes,
+        }
+    }
+}
+
+/// Parses the XHTML written by `pdftotext -bbox` into one layout per page
+pub fn parse_pdftotext_bbox(html: &str) -> Vec<PageLayout> {
+    let page_re = Regex::new(r#"<page\s+width="([0-9.]+)"\s+height="([0-9.]+)"\s*>"#).unwrap();
+    let word_re = Regex::new(
+        r#"<word\s+xMin="([-0-9.]+)"\s+yMin="([-0-9.]+)"\s+xMax="([-0-9.]+)"\s+yMax="([-0-9.]+)"\s*>(.*?)</word>"#,
+    )
+    .unwrap();
+
+    let page_starts: Vec<(usize, Option<(f32, f32)>)> = page_re
+        .captures_iter(html)
+        .map(|caps| {
+            let width = caps[1].parse::<f32>().ok();
+            let height = caps[2].parse::<f32>().ok();
+            (caps.get(0).unwrap().end(), width.zip(height))
+        })
+        .collect();
+
+    let mut pages = Vec::with_capacity(page_starts.len());
+    for (idx, (start, size)) in page_starts.iter().enumerate() {
+        let end = page_starts
+            .get(idx + 1)
+            .map(|(next, _)| *next)
+            .unwrap_or(html.len());
+        let boxes = word_re
+            .captures_iter(&html[*start..end])
+            .filter_map(|caps| {
+                Some(TextBox {
+                    x0: caps[1].parse().ok()?,
+                    y0: caps[2].parse().ok()?,
+                    x1: caps[3].parse().ok()?,
+                    y1: caps[4].parse().ok()?,
+                    text: unescape_xml(&caps[5]),
+                    standalone: false,
+                })
+            })
+            .collect();
+        pages.push(PageLayout { size: *size, boxes });
+    }
+    pages
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    font_size: f32,
+    text: String,
+    standalone: bool,
+    row: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+    /// A main text column inside the band between two full-width blocks
+    Column(usize),
+    Spanning,
+    Sidebar(usize),
+}
+
+struct Line {
+    text: String,
+    y1: f32,
+    font_size: f32,
+    flow: Flow,
+    standalone: bool,
+}
+
+/// Returns the page text as lines in reading order
+///
+/// An empty line marks a paragraph gap, as in `pdftotext` output.
+pub fn reading_order_lines(page: &PageLayout) -> Vec<String> {
+    let mut segments = build_segments(&page.boxes);
+    if let Some((_, height)) = page.size {
+        drop_headers_and_footers(&mut segments, height);
+    }
+    if segments.is_empty() {
+        return Vec::new();
+    }
+
+    let columns = find_columns(&segments);
+    let column_of = |segment: &Segment| -> Option<usize> {
+        columns.iter().position(|(left, right)| {
+            if segment.standalone {
+                segment.x0 >= left - COLUMN_TOLERANCE && segment.x0 <= right + COLUMN_TOLERANCE
+            } else {
+                segment.x0 >= left - COLUMN_TOLERANCE && segment.x1 <= right + COLUMN_TOLERANCE
+            }
+        })
+    };
+    let sidebars = find_sidebars(&segments, &columns, &column_of);
+
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&segments[a], &segments[b]);
+        a.y0.total_cmp(&b.y0).then(a.x0.total_cmp(&b.x0))
+    });
+
+    let mut lines: Vec<Line> = Vec::with_capacity(segments.len());
+    let mut band: Vec<(usize, usize)> = Vec::new();
+    let mut sidebar_segments: Vec<(usize, usize)> = Vec::new();
+
+    for idx in order {
+        match column_of(&segments[idx]) {
+            Some(column) if sidebars.contains(&column) => sidebar_segments.push((column, idx)),
+            Some(column) => band.push((column, idx)),
+            None => {
+                flush_band(&segments, &mut band, &mut lines, Flow::Column);
+                push_line(&mut lines, &segments[idx], Flow::Spanning);
+            }
+        }
+    }
+    flush_band(&segments, &mut band, &mut lines, Flow::Column);
+    flush_band(&segments, &mut sidebar_segments, &mut lines, Flow::Sidebar);
+
+    with_paragraph_gaps(lines)
+}
+
+/// Groups boxes into rows sharing a baseline, then splits each row where the
+/// horizontal gap is wide enough to be a column gutter
+fn build_segments(boxes: &[TextBox]) -> Vec<Segment> {
+    let mut text_boxes: Vec<&TextBox> = boxes.iter().filter(|b| !b.standalone).collect();
+    text_boxes.sort_by(|a, b| {
+        (a.y0 + a.y1)
+            .total_cmp(&(b.y0 + b.y1))
+            .then(a.x0.total_cmp(&b.x0))
+    });
+
+    let mut rows: Vec<(f32, f32, Vec<&TextBox>)> = Vec::new();
+    for text_box in text_boxes {
+        if let Some((top, bottom, members)) = rows.last_mut() {
+            let overlap = text_box.y1.min(*bottom) - text_box.y0.max(*top);
+            let smaller = text_box.height().min(*bottom - *top).max(1.0);
+            if overlap >= smaller * 0.5 {
+                *top = top.min(text_box.y0);
+                *bottom = bottom.max(text_box.y1);
+                members.push(text_box);
+                continue;
+            }
+        }
+        rows.push((text_box.y0, text_box.y1, vec![text_box]));
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for (row_idx, (_, _, mut members)) in rows.into_iter().enumerate() {
+        members.sort_by(|a, b| a.x0.total_cmp(&b.x0));
+        let mut current: Option<Segment> = None;
+        for text_box in members {
+            let size = text_box.height();
+            if let Some(segment) = current.as_mut() {
+                let gap = text_box.x0 - segment.x1;
+                let em = segment.font_size.max(size).max(1.0);
+                if gap <= em * SEGMENT_GAP_EM {
+                    if gap > em * WORD_GAP_EM
+                        && !segment.text.ends_with(' ')
+                        && !text_box.text.starts_with(' ')
+                    {
+                        segment.text.push(' ');
+                    }
+                    segment.text.push_str(&text_box.text);
+                    segment.x1 = segment.x1.max(text_box.x1);
+                    segment.y0 = segment.y0.min(text_box.y0);
+                    segment.y1 = segment.y1.max(text_box.y1);
+                    segment.font_size = segment.font_size.max(size);
+                    continue;
+                }
+                segments.push(current.take().unwrap());
+            }
+            current = Some(Segment {
+                x0: text_box.x0,
+                y0: text_box.y0,
+                x1: text_box.x1,
+                y1: text_box.y1,
+                font_size: size,
+                text: text_box.text.clone(),
+                standalone: false,
+                row: row_idx,
+            });
+        }
+        segments.extend(current);
+    }
+
+    let next_row = segments.last().map(|s| s.row + 1).unwrap_or(0);
+    for (offset, text_box) in boxes.iter().filter(|b| b.standalone).enumerate() {
+        segments.push(Segment {
+            x0: text_box.x0,
+            y0: text_box.y0,
+            x1: text_box.x1,
+            y1: text_box.y1,
+            font_size: 0.0,
+            text: text_box.text.clone(),
+            standalone: true,
+            row: next_row + offset,
+        });
+    }
+
+    segments
+}
+
+/// Removes short rows in the top and bottom bands of the page that are set
+/// apart from the body text (running heads, page numbers, journal lines)
+fn drop_headers_and_footers(segments: &mut Vec<Segment>, page_height: f32) {
+    // (row id, top, bottom, word count) per text row, ordered top to bottom
+    let mut rows: Vec<(usize, f32, f32, usize)> = Vec::new();
+    for segment in segments.iter().filter(|s| !s.standalone) {
+        let words = segment.text.split_whitespace().count();
+        match rows.iter_mut().find(|(row, ..)| *row == segment.row) {
+            Some((_, top, bottom, count)) => {
+                *top = top.min(segment.y0);
+                *bottom = bottom.max(segment.y1);
+                *count += words;
+            }
+            None => rows.push((segment.row, segment.y0, segment.y1, words)),
+        }
+    }
+    rows.sort_by(|a, b| a.1.total_cmp(&b.1));
+    if rows.len() < 3 {
+        return;
+    }
+
+    let mut dropped: HashSet<usize> = HashSet::new();
+    let band = page_height * HEADER_FOOTER_BAND;
+
+    let header_rows = rows.iter().take_while(|row| row.2 <= band).count();
+    if header_rows > 0 && header_rows < rows.len() {
+        let last = &rows[header_rows - 1];
+        let gap = rows[header_rows].1 - last.2;
+        let words: usize = rows[..header_rows].iter().map(|row| row.3).sum();
+        if gap > (last.2 - last.1).max(1.0) && words <= HEADER_FOOTER_MAX_WORDS {
+            dropped.extend(rows[..header_rows].iter().map(|row| row.0));
+        }
+    }
+
+    let footer_rows = rows
+        .iter()
+        .rev()
+        .take_while(|row| row.1 >= page_height - band)
+        .count();
+    if footer_rows > 0 && footer_rows < rows.len() {
+        let first = &rows[rows.len() - footer_rows];
+        let gap = first.1 - rows[rows.len() - footer_rows - 1].2;
+        let words: usize = rows[rows.len() - footer_rows..]
+            .iter()
+            .map(|row| row.3)
+            .sum();
+        if gap > (first.2 - first.1).max(1.0) && words <= HEADER_FOOTER_MAX_WORDS {
+            dropped.extend(rows[rows.len() - footer_rows..].iter().map(|row| row.0));
+        }
+    }
+
+    if !dropped.is_empty() {
+        segments.retain(|segment| segment.standalone || !dropped.contains(&segment.row));
+    }
+}
+
+/// Finds text columns as the x ranges between vertical gutters
+///
+/// A gutter is a run of x positions crossed by at most a tenth of the
+/// segments (full-width titles and figures may cross it) with enough text
+/// on both sides. Without gutters the whole text width is one column.
+fn find_columns(segments: &[Segment]) -> Vec<(f32, f32)> {
+    let body: Vec<&Segment> = segments.iter().filter(|s| !s.standalone).collect();
+    let Some(left) = body.iter().map(|s| s.x0).reduce(f32::min) else {
+        return vec![(f32::MIN, f32::MAX)];
+    };
+    let right = body.iter().map(|s| s.x1).fold(left, f32::max);
+    let text_width = right - left;
+    if body.len() < MIN_COLUMN_SEGMENTS * 2 || text_width <= MIN_GUTTER_WIDTH {
+        return vec![(left, right)];
+    }
+
+    let bins = (text_width / GUTTER_BIN).ceil() as usize;
+    let mut coverage = vec![0usize; bins];
+    for segment in &body {
+        for (bin, count) in coverage.iter_mut().enumerate() {
+            let center = left + (bin as f32 + 0.5) * GUTTER_BIN;
+            if segment.x0 <= center && center <= segment.x1 {
+                *count += 1;
+            }
+        }
+    }
+
+    let allowed = (body.len() / 10).max(1);
+    let mut gutters: Vec<(f32, f32)> = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for bin in 0..=bins {
+        let empty = coverage.get(bin).is_some_and(|count| *count <= allowed);
+        match (empty, run_start) {
+            (true, None) => run_start = Some(bin),
+            (false, Some(start)) => {
+                run_start = None;
+                let width = (bin - start) as f32 * GUTTER_BIN;
+                if start > 0 && bin < bins && width >= MIN_GUTTER_WIDTH {
+                    gutters.push((
+                        left + start as f32 * GUTTER_BIN,
+                        left + bin as f32 * GUTTER_BIN,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let inside = |from: f32, to: f32| {
+        body.iter()
+            .filter(|s| s.x0 >= from - COLUMN_TOLERANCE && s.x1 <= to + COLUMN_TOLERANCE)
+            .count()
+    };
+    let min_width = text_width * MIN_COLUMN_SHARE;
+    let mut columns = Vec::new();
+    let mut column_left = left;
+    for (gutter_left, gutter_right) in gutters {
+        if gutter_left - column_left >= min_width
+            && right - gutter_right >= min_width
+            && inside(column_left, gutter_left) >= MIN_COLUMN_SEGMENTS
+            && inside(gutter_right, right) >= MIN_COLUMN_SEGMENTS
+        {
+            columns.push((column_left, gutter_left));
+            column_left = gutter_right;
+        }
+    }
+    columns.push((column_left, right));
+    columns
+}
+
+/// Narrow columns set noticeably smaller than the body text
+fn find_sidebars(
+    segments: &[Segment],
+    columns: &[(f32, f32)],
+    column_of: &dyn Fn(&Segment) -> Option<usize>,
+) -> HashSet<usize> {
+    let mut sidebars = HashSet::new();
+    if columns.len() < 2 {
+        return sidebars;
+    }
+
+    let body_font = median(
+        segments
+            .iter()
+            .filter(|s| !s.standalone)
+            .map(|s| s.font_size),
+    );
+    let text_width = columns[columns.len() - 1].1 - columns[0].0;
+    for (idx, (left, right)) in columns.iter().enumerate() {
+        let column_font = median(
+            segments
+                .iter()
+                .filter(|s| !s.standalone && column_of(s) == Some(idx))
+                .map(|s| s.font_size),
+        );
+        if let (Some(body_font), Some(column_font)) = (body_font, column_font) {
+            if right - left < text_width * SIDEBAR_MAX_SHARE
+                && column_font < body_font * SIDEBAR_FONT_RATIO
+            {
+                sidebars.insert(idx);
+            }
+        }
+    }
+    if sidebars.len() == columns.len() {
+        sidebars.clear();
+    }
+    sidebars
+}
+
+fn median(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let mut values: Vec<f32> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    Some(values[values.len() / 2])
+}
+
+/// Emits the collected segments column by column, joining segments that
+/// share a row within a column (table cells) with a double space
+fn flush_band(
+    segments: &[Segment],
+    pending: &mut Vec<(usize, usize)>,
+    lines: &mut Vec<Line>,
+    flow: fn(usize) -> Flow,
+) {
+    pending.sort_by_key(|(column, _)| *column);
+    let mut previous: Option<(usize, usize)> = None;
+    for &(column, idx) in pending.iter() {
+        let segment = &segments[idx];
+        if let (Some((prev_column, prev_idx)), Some(line)) = (previous, lines.last_mut()) {
+            let prev = &segments[prev_idx];
+            if prev_column == column
+                && prev.row == segment.row
+                && !prev.standalone
+                && !segment.standalone
+            {
+                line.text.push_str("  ");
+                line.text.push_str(segment.text.trim());
+                previous = Some((column, idx));
+                continue;
+            }
+        }
+        push_line(lines, segment, flow(column));
+        previous = Some((column, idx));
+    }
+    pending.clear();
+}
+
+fn push_line(lines: &mut Vec<Line>, segment: &Segment, flow: Flow) {
+    lines.push(Line {
+        text: segment.text.trim().to_string(),
+        y1: segment.y1,
+        font_size: segment.font_size,
+        flow,
+        standalone: segment.standalone,
+    });
+}
+
+/// Inserts an empty line between blocks and where the line spacing or type
+/// size suggests a new paragraph
+fn with_paragraph_gaps(lines: Vec<Line>) -> Vec<String> {
+    let typical_spacing = {
+        let mut spacings: Vec<f32> = lines
+            .windows(2)
+            .filter(|pair| pair[0].flow == pair[1].flow)
+            .filter(|pair| !pair[0].standalone && !pair[1].standalone)
+            .map(|pair| pair[1].y1 - pair[0].y1)
+            .filter(|gap| *gap > 0.0)
+            .collect();
+        spacings.sort_by(|a, b| a.total_cmp(b));
+        spacings.get(spacings.len().saturating_sub(1) / 2).copied()
+    };
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    for (idx, line) in lines.iter().enumerate() {
+        if idx > 0 && !line.standalone && !lines[idx - 1].standalone {
+            let previous = &lines[idx - 1];
+            let new_block = match (previous.flow, line.flow) {
+                (Flow::Column(_), Flow::Column(_)) => false,
+                (a, b) => a != b,
+            };
+            let gap = line.y1 - previous.y1;
+            let paragraph_gap = previous.flow == line.flow
+                && match typical_spacing {
+                    Some(spacing) => gap > spacing * PARAGRAPH_GAP_FACTOR,
+                    None => gap > line.font_size.max(previous.font_size) * 2.0,
+                };
+            let size_change = previous.font_size > 0.0
+                && line.font_size > 0.0
+                && (previous.font_size - line.font_size).abs() > previous.font_size * 0.2;
+            if (new_block || paragraph_gap || size_change)
+                && out.last().is_some_and(|last| !last.is_empty())
+            {
+                out.push(String::new());
+            }
+        }
+        if !line.text.is_empty() {
+            out.push(line.text.clone());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pdftotext_bbox, reading_order_lines, PageLayout, TextBox};
+    use crate::parsers::pdf_text::TextRun;
+
+    fn word(x0: f32, y0: f32, text: &str) -> TextBox {
+        TextBox {
+            x0,
+            y0,
+            x1: x0 + text.chars().count() as f32 * 5.0,
+            y1: y0 + 10.0,
+            text: text.to_string(),
+            standalone: false,
+        }
+    }
+
+    #[test]
+    fn joins_runs_on_one_baseline_and_breaks_paragraphs() {
+        let run = |x: f32, y: f32, text: &str| TextRun {
+            x,
+            y,
+            width: text.chars().count() as f32 * 5.0,
+            font_size: 10.0,
+            text: text.to_string(),
+            standalone: false,
+        };
+        let runs = vec![
+            run(72.0, 700.0, "Hello"),
+            run(102.0, 700.0, "world"),
+            run(72.0, 688.0, "second line"),
+            run(72.0, 650.0, "New paragraph"),
+        ];
+        assert_eq!(
+            reading_order_lines(&PageLayout::from_runs(&runs, None)),
+            vec!["Hello world", "second line", "", "New paragraph"]
+        );
+    }
+
+    #[test]
+    fn reads_two_columns_between_full_width_blocks() {
+        let mut boxes = vec![
+            word(200.0, 20.0, "Journal of Tests"),
+            word(100.0, 80.0, "A Study of Reading Order In Two Column Papers"),
+        ];
+        for (idx, y) in [120.0, 132.0, 144.0, 156.0].iter().enumerate() {
+            boxes.push(word(50.0, *y, &format!("left column line {}", idx + 1)));
+            boxes.push(word(320.0, *y, &format!("right column line {}", idx + 1)));
+        }
+        boxes.push(word(300.0, 770.0, "7"));
+
+        let page = PageLayout {
+            size: Some((612.0, 792.0)),
+            boxes,
+        };
+        assert_eq!(
+            reading_order_lines(&page),
+            vec![
+                "A Study of Reading Order In Two Column Papers",
+                "",
+                "left column line 1",
+                "left column line 2",
+                "left column line 3",
+                "left column line 4",
+                "right column line 1",
+                "right column line 2",
+                "right column line 3",
+                "right column line 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_pdftotext_bbox_words() {
+        let html = r#"<doc>
+  <page width="612.000000" height="792.000000">
+    <word xMin="72.000000" yMin="60.000000" xMax="100.000000" yMax="72.000000">Tom&amp;Jerry</word>
+    <word xMin="104.000000" yMin="60.000000" xMax="130.000000" yMax="72.000000">run</word>
+  </page>
+  <page width="612.000000" height="792.000000">
+  </page>
+</doc>"#;
+        let pages = parse_pdftotext_bbox(html);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].size, Some((612.0, 792.0)));
+        assert_eq!(reading_order_lines(&pages[0]), vec!["Tom&Jerry run"]);
+        assert!(pages[1].boxes.is_empty());
+    }
+}
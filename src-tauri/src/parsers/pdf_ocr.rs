@@ -0,0 +1,303 @@
+//! OCR for scanned PDF pages
+//!
+//! Pages without a usable text layer are rendered with `pdftoppm` and passed
+//! to a locally installed `tesseract`. Both tools are optional: when either is
+//! missing the page keeps whatever the text extractor produced.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const DEFAULT_OCR_LANGUAGES: &str = "eng";
+const OCR_RENDER_DPI: u32 = 300;
+const TESSERACT_CANDIDATES: &[&str] = &[
+    "/opt/homebrew/bin/tesseract",
+    "/usr/local/bin/tesseract",
+    "tesseract",
+];
+const PDFTOPPM_CANDIDATES: &[&str] = &["/opt/homebrew/bin/pdftoppm", "pdftoppm"];
+
+/// User-facing OCR settings
+#[derive(Debug, Clone)]
+pub struct OcrOptions {
+    /// Tesseract language codes, e.g. `eng+chi_sim`
+    pub languages: String,
+    /// Explicit tesseract binary, searched on the usual paths when unset
+    pub tesseract_path: Option<String>,
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        Self {
+            languages: DEFAULT_OCR_LANGUAGES.to_string(),
+            tesseract_path: None,
+        }
+    }
+}
+
+/// A tesseract binary together with the installed subset of the requested languages
+#[derive(Debug, Clone)]
+pub struct OcrEngine {
+    tesseract: String,
+    languages: String,
+}
+
+impl OcrEngine {
+    /// Locates tesseract and checks which requested languages are installed
+    ///
+    /// Returns None when tesseract cannot be run or none of the requested
+    /// languages is available.
+    pub fn detect(options: &OcrOptions) -> Option<Self> {
+        let configured = options
+            .tesseract_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty());
+        let candidates = configured
+            .into_iter()
+            .chain(TESSERACT_CANDIDATES.iter().copied());
+
+        for tesseract in candidates {
+            let Ok(output) = Command::new(tesseract).arg("--list-langs").output() else {
+                continue;
+            };
+            if !output.status.success() {
+                continue;
+            }
+            // Older releases print the list on stderr
+            let mut listing = String::from_utf8_lossy(&output.stdout).to_string();
+            listing.push('\n');
+            listing.push_str(&String::from_utf8_lossy(&output.stderr));
+            let installed = parse_language_list(&listing);
+
+            let requested = normalize_languages(&options.languages);
+            let languages = if installed.is_empty() {
+                requested
+            } else {
+                let available: Vec<&str> = requested
+                    .split('+')
+                    .filter(|lang| installed.iter().any(|i| i == lang))
+                    .collect();
+                if available.is_empty() {
+                    tracing::warn!(
+                        "None of the OCR languages '{}' is installed for {} (have: {})",
+                        requested,
+                        tesseract,
+                        installed.join(", ")
+                    );
+                    return None;
+                }
+                available.join("+")
+            };
+
+            return Some(Self {
+                tesseract: tesseract.to_string(),
+                languages,
+            });
+        }
+
+        None
+    }
+
+    pub fn languages(&self) -> &str {
+        &self.languages
+    }
+
+    /// Renders one page (1-based) and returns the recognised text as lines
+    ///
+    /// Blank lines separate the paragraphs tesseract detected. Returns None
+    /// when the page could not be rendered or recognised.
+    pub fn recognize_page(
+        &self,
+        pdf_path: &str,
+        page_number: usize,
+        work_dir: &Path,
+    ) -> Option<Vec<String>> {
+        let image = render_page_png(
+            pdf_path,
+            page_number,
+            work_dir,
+            &format!("ocr_page_{:04}", page_number),
+            OCR_RENDER_DPI,
+        )?;
+        let dpi = OCR_RENDER_DPI.to_string();
+        let output = Command::new(&self.tesseract)
+            .arg(&image)
+            .args([
+                "stdout",
+                "-l",
+                self.languages.as_str(),
+                "--dpi",
+                dpi.as_str(),
+            ])
+            .output();
+        let _ = fs::remove_file(&image);
+
+        match output {
+            Ok(output) if output.status.success() => {
+                Some(ocr_text_to_lines(&String::from_utf8_lossy(&output.stdout)))
+            }
+            Ok(output) => {
+                tracing::warn!(
+                    "tesseract failed on page {}: {}",
+                    page_number,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Failed to run tesseract on page {}: {}", page_number, e);
+                None
+            }
+        }
+    }
+}
+
+/// Renders a single page (1-based) to `{output_dir}/{file_stem}.png` with pdftoppm
+pub fn render_page_png(
+    pdf_path: &str,
+    page_number: usize,
+    output_dir: &Path,
+    file_stem: &str,
+    dpi: u32,
+) -> Option<PathBuf> {
+    let prefix = output_dir.join(file_stem);
+    let prefix_string = prefix.to_string_lossy().to_string();
+    let page_number_string = page_number.to_string();
+    let dpi_string = dpi.to_string();
+
+    let rendered = PDFTOPPM_CANDIDATES.iter().any(|cmd| {
+        Command::new(cmd)
+            .args([
+                "-f",
+                page_number_string.as_str(),
+                "-l",
+                page_number_string.as_str(),
+                "-singlefile",
+                "-png",
+                "-r",
+                dpi_string.as_str(),
+                pdf_path,
+                prefix_string.as_str(),
+            ])
+            .status()
+            .is_ok_and(|status| status.success())
+    });
+    if !rendered {
+        return None;
+    }
+
+    let png_path = prefix.with_extension("png");
+    png_path.exists().then_some(png_path)
+}
+
+/// Turns a free-form language setting into tesseract's `a+b` syntax
+///
+/// Accepts `+`, comma or whitespace separated codes and drops anything that
+/// is not a plausible traineddata name.
+pub fn normalize_languages(raw: &str) -> String {
+    let mut languages: Vec<&str> = Vec::new();
+    for lang in raw.split(|c: char| c == '+' || c == ',' || c.is_whitespace()) {
+        let valid = !lang.is_empty()
+            && !lang.starts_with('-')
+            && lang
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if valid && !languages.contains(&lang) {
+            languages.push(lang);
+        }
+    }
+    if languages.is_empty() {
+        DEFAULT_OCR_LANGUAGES.to_string()
+    } else {
+        languages.join("+")
+    }
+}
+
+/// Parses `tesseract --list-langs` output into language codes
+fn parse_language_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.contains(' ') && !line.ends_with(':') && line != &"osd"
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits tesseract output into lines, keeping blank lines as paragraph breaks
+///
+/// Tesseract separates CJK glyphs with spaces; those are removed again.
+fn ocr_text_to_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split(['\n', '\u{000C}']) {
+        let line = join_cjk_glyphs(raw.trim());
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+fn join_cjk_glyphs(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    for (idx, &c) in chars.iter().enumerate() {
+        if c == ' ' && idx > 0 {
+            let prev = chars[idx - 1];
+            let next = chars.get(idx + 1).copied().unwrap_or(' ');
+            if is_cjk(prev) && is_cjk(next) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3000}'..='\u{303F}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_languages, ocr_text_to_lines, parse_language_list};
+
+    #[test]
+    fn normalizes_language_settings() {
+        assert_eq!(normalize_languages("eng, chi_sim"), "eng+chi_sim");
+        assert_eq!(normalize_languages(" deu+eng eng "), "deu+eng");
+        assert_eq!(normalize_languages("eng; rm -rf"), "rm");
+        assert_eq!(normalize_languages(""), "eng");
+    }
+
+    #[test]
+    fn parses_language_listing() {
+        let listing =
+            "List of available languages in \"/usr/share/tesseract-ocr/5/tessdata/\" (3):\n\
+chi_sim\neng\nosd\n";
+        assert_eq!(parse_language_list(listing), vec!["chi_sim", "eng"]);
+    }
+
+    #[test]
+    fn splits_ocr_text_into_paragraph_lines() {
+        let text = "\nThe first line\nwraps here.\n\n\n中 文 识 别 结 果\n\n\u{000C}";
+        assert_eq!(
+            ocr_text_to_lines(text),
+            vec!["The first line", "wraps here.", "", "中文识别结果"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! PDF document outline (bookmarks) support
+//!
+//! Flattens the `/Outlines` tree into entries with a resolved page index and
+//! decides where each outline entry starts within the document's paragraph
+//! stream, so that a PDF can be split into chapters instead of pages.
+
+use pdf::object::{
+    Action, Catalog, MaybeNamedDest, OutlineItem, PagesNode, PlainRef, Ref, Resolve,
+};
+use pdf::primitive::Primitive;
+use std::collections::{HashMap, HashSet};
+
+/// Outline levels deeper than this are folded into their parent section
+pub const MAX_SECTION_DEPTH: usize = 2;
+const MAX_OUTLINE_ITEMS: usize = 10_000;
+const MAX_DEST_INDIRECTION: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// Nesting level, 0 for top-level bookmarks
+    pub depth: usize,
+    /// Zero-based page index the bookmark points at
+    pub page_index: usize,
+}
+
+/// Reads the document outline in reading order
+///
+/// Bookmarks whose destination cannot be resolved to a page are skipped.
+/// Returns an empty list when the PDF has no outline.
+pub fn read_outline<R: Resolve>(file: &R, catalog: &Catalog) -> Vec<OutlineEntry> {
+    let Some(outlines) = catalog.outlines.as_ref() else {
+        return Vec::new();
+    };
+    let Some(first) = outlines.first else {
+        return Vec::new();
+    };
+
+    let mut page_refs = Vec::new();
+    collect_page_refs(
+        file,
+        &catalog.pages.kids,
+        &mut page_refs,
+        &mut HashSet::new(),
+    );
+    let page_index: HashMap<PlainRef, usize> = page_refs
+        .into_iter()
+        .enumerate()
+        .map(|(idx, page_ref)| (page_ref, idx))
+        .collect();
+    let named = named_destinations(file, catalog, &page_index);
+
+    let mut entries = Vec::new();
+    let mut visited: HashSet<PlainRef> = HashSet::new();
+    // Depth-first walk; siblings are pushed in reverse so they pop in order
+    let mut stack: Vec<(Ref<OutlineItem>, usize)> = vec![(first, 0)];
+
+    while let Some((item_ref, depth)) = stack.pop() {
+        if entries.len() >= MAX_OUTLINE_ITEMS || !visited.insert(item_ref.get_inner()) {
+            continue;
+        }
+        let Ok(item) = file.get(item_ref) else {
+            continue;
+        };
+
+        if let Some(next) = item.next {
+            stack.push((next, depth));
+        }
+        if let Some(child) = item.first {
+            stack.push((child, depth + 1));
+        }
+
+        let title = item
+            .title
+            .as_ref()
+            .map(|t| t.to_string_lossy())
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if title.is_empty() {
+            continue;
+        }
+
+        let page = item
+            .dest
+            .as_ref()
+            .and_then(|dest| dest_page(file, dest, &page_index, &named, 0))
+            .or_else(|| match item.action.as_ref() {
+                Some(Action::Goto(MaybeNamedDest::Direct(dest))) => dest
+                    .page
+                    .and_then(|page| page_index.get(&page.get_inner()).copied()),
+                Some(Action::Goto(MaybeNamedDest::Named(name))) => {
+                    named.get(&name.to_string_lossy()).copied()
+                }
+                _ => None,
+            });
+
+        if let Some(page_index) = page {
+            entries.push(OutlineEntry {
+                title,
+                depth,
+                page_index,
+            });
+        }
+    }
+
+    entries
+}
+
+fn collect_page_refs<R: Resolve>(
+    file: &R,
+    kids: &[Ref<PagesNode>],
+    out: &mut Vec<PlainRef>,
+    visited: &mut HashSet<PlainRef>,
+) {
+    for kid in kids {
+        if !visited.insert(kid.get_inner()) {
+            continue;
+        }
+        match file.get(*kid).as_deref() {
+            Ok(PagesNode::Tree(tree)) => collect_page_refs(file, &tree.kids, out, visited),
+            Ok(PagesNode::Leaf(_)) => out.push(kid.get_inner()),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Named destinations from both the `/Dests` dictionary and the `/Names` tree
+fn named_destinations<R: Resolve>(
+    file: &R,
+    catalog: &Catalog,
+    page_index: &HashMap<PlainRef, usize>,
+) -> HashMap<String, usize> {
+    let mut named = HashMap::new();
+
+    if let Some(dests) = catalog.dests.as_ref() {
+        for (name, dest) in dests.iter() {
+            if let Some(page) = dest_page(file, dest, page_index, &HashMap::new(), 0) {
+                named.insert(name.to_string(), page);
+            }
+        }
+    }
+
+    if let Some(tree) = catalog
+        .names
+        .as_ref()
+        .and_then(|names| names.dests.as_ref())
+    {
+        let _ = tree.walk(file, &mut |name, dest| {
+            let page = dest
+                .as_ref()
+                .and_then(|dest| dest.page)
+                .and_then(|page| page_index.get(&page.get_inner()).copied());
+            if let Some(page) = page {
+                named.entry(name.to_string_lossy()).or_insert(page);
+            }
+        });
+    }
+
+    named
+}
+
+/// Resolves an explicit or named destination to a page index
+///
+/// Explicit destinations are arrays whose first element is the page object
+/// (or a page number for remote targets); named ones may map to such an
+/// array directly or to a dictionary holding it under `/D`.
+fn dest_page<R: Resolve>(
+    file: &R,
+    dest: &Primitive,
+    page_index: &HashMap<PlainRef, usize>,
+    named: &HashMap<String, usize>,
+    depth: usize,
+) -> Option<usize> {
+    if depth > MAX_DEST_INDIRECTION {
+        return None;
+    }
+    match dest {
+        Primitive::Array(items) => match items.first()? {
+            Primitive::Reference(page) => page_index.get(page).copied(),
+            Primitive::Integer(page) if *page >= 0 => Some(*page as usize),
+            _ => None,
+        },
+        Primitive::Name(name) => named.get(&name.to_string()).copied(),
+        Primitive::String(name) => named.get(&name.to_string_lossy()).copied(),
+        Primitive::Dictionary(dict) => {
+            dest_page(file, dict.get("D")?, page_index, named, depth + 1)
+        }
+        Primitive::Reference(r) => {
+            let resolved = file.resolve(*r).ok()?;
+            dest_page(file, &resolved, page_index, named, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Chooses the paragraph at which each outline entry starts
+///
+/// `paragraphs` lists the zero-based start page and text of every paragraph
+/// in reading order. An entry starts at the first paragraph on its page that
+/// begins with the bookmark title, or at the first paragraph of that page
+/// otherwise. Entries deeper than [`MAX_SECTION_DEPTH`] are ignored and
+/// bookmarks pointing backwards are clamped so sections stay in order.
+pub fn section_starts(
+    entries: &[OutlineEntry],
+    paragraphs: &[(usize, &str)],
+) -> Vec<(String, usize)> {
+    let mut starts: Vec<(String, usize)> = Vec::new();
+    let mut cursor = 0usize;
+
+    for entry in entries.iter().filter(|e| e.depth < MAX_SECTION_DEPTH) {
+        let first_on_page = paragraphs[cursor..]
+            .iter()
+            .position(|(page, _)| *page >= entry.page_index)
+            .map(|offset| cursor + offset)
+            .unwrap_or(paragraphs.len());
+
+        // Skip the paragraph the previous entry already claimed
+        let search_from = if starts.is_empty() {
+            first_on_page
+        } else {
+            first_on_page.max(cursor + 1)
+        };
+        let wanted = normalize_title(&entry.title);
+        let heading = paragraphs
+            .get(search_from..)
+            .unwrap_or_default()
+            .iter()
+            .take_while(|(page, _)| *page <= entry.page_index)
+            .position(|(_, text)| !wanted.is_empty() && normalize_title(text).starts_with(&wanted))
+            .map(|offset| search_from + offset);
+
+        let start = heading.unwrap_or(first_on_page);
+        starts.push((entry.title.clone(), start));
+        cursor = start;
+    }
+
+    starts
+}
+
+fn normalize_title(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{section_starts, OutlineEntry};
+
+    fn entry(title: &str, depth: usize, page_index: usize) -> OutlineEntry {
+        OutlineEntry {
+            title: title.to_string(),
+            depth,
+            page_index,
+        }
+    }
+
+    #[test]
+    fn starts_sections_at_matching_headings() {
+        let paragraphs = vec![
+            (0, "A Book Title"),
+            (1, "Contents"),
+            (2, "Chapter 1 Beginnings"),
+            (2, "It was a dark night."),
+            (2, "1.1 The Storm"),
+            (3, "Rain fell."),
+            (4, "Chapter 2 Endings"),
+        ];
+        let entries = vec![
+            entry("Chapter 1: Beginnings", 0, 2),
+            entry("1.1 The Storm", 1, 2),
+            entry("1.1.1 Thunder", 2, 3),
+            entry("Chapter 2 — Endings", 0, 4),
+        ];
+
+        assert_eq!(
+            section_starts(&entries, &paragraphs),
+            vec![
+                ("Chapter 1: Beginnings".to_string(), 2),
+                ("1.1 The Storm".to_string(), 4),
+                ("Chapter 2 — Endings".to_string(), 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_first_paragraph_of_page() {
+        let paragraphs = vec![(0, "Intro text."), (1, "Body one."), (1, "Body two.")];
+        let entries = vec![entry("Preface", 0, 0), entry("Part One", 0, 1)];
+
+        assert_eq!(
+            section_starts(&entries, &paragraphs),
+            vec![("Preface".to_string(), 0), ("Part One".to_string(), 1)]
+        );
+    }
+}
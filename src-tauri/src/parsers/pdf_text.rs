@@ -0,0 +1,591 @@
+//! In-process PDF text extraction built on the `pdf` crate
+//!
+//! Walks page content streams, tracks the graphics/text state needed to place
+//! each text-showing operator on the page, decodes string bytes through the
+//! font's ToUnicode map (or its simple encoding). The positioned runs are put
+//! back into reading order by `pdf_layout`. Used when poppler's `pdftotext`
+//! is not installed.
+
+use pdf::content::{Op, TextDrawAdjusted};
+use pdf::font::{Font, ToUnicodeMap, Widths};
+use pdf::object::{ImageXObject, MaybeRef, Page, Resolve, Resources, XObject};
+use std::collections::HashMap;
+
+const MAX_FORM_DEPTH: usize = 6;
+const DEFAULT_GLYPH_WIDTH: f32 = 500.0;
+
+/// A piece of text placed on the page, in PDF user space (origin bottom-left)
+#[derive(Debug, Clone)]
+pub struct TextRun {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub font_size: f32,
+    pub text: String,
+    /// Image markers and similar items that must stay on a line of their own
+    pub standalone: bool,
+}
+
+/// Hooks used to turn image operators into marker runs while walking a page
+pub struct ImageHooks<'a> {
+    pub xobject: &'a mut dyn FnMut(&str) -> Vec<String>,
+    pub inline_image: &'a mut dyn FnMut(&ImageXObject) -> Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Affine {
+    const IDENTITY: Affine = Affine {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    fn from_pdf(m: &pdf::content::Matrix) -> Self {
+        Affine {
+            a: m.a,
+            b: m.b,
+            c: m.c,
+            d: m.d,
+            e: m.e,
+            f: m.f,
+        }
+    }
+
+    /// `self × other` using PDF's row-vector convention
+    fn then(&self, other: &Affine) -> Affine {
+        Affine {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+
+    fn translate(tx: f32, ty: f32) -> Affine {
+        Affine {
+            e: tx,
+            f: ty,
+            ..Affine::IDENTITY
+        }
+    }
+
+    fn vertical_scale(&self) -> f32 {
+        (self.c * self.c + self.d * self.d).sqrt()
+    }
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Affine,
+    font: Option<String>,
+    font_size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horiz_scale: f32,
+    leading: f32,
+    rise: f32,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: Affine::IDENTITY,
+            font: None,
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horiz_scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+        }
+    }
+}
+
+/// Decoding data for one font resource
+struct FontDecoder {
+    to_unicode: Option<ToUnicodeMap>,
+    two_byte: bool,
+    widths: Option<Widths>,
+    differences: HashMap<u32, String>,
+}
+
+impl FontDecoder {
+    fn load<R: Resolve>(file: &R, font: &Font) -> Self {
+        let to_unicode = font.to_unicode(file).and_then(|map| map.ok());
+        let widths = font.widths(file).ok().flatten();
+        let differences = font
+            .encoding()
+            .map(|encoding| {
+                encoding
+                    .differences
+                    .iter()
+                    .map(|(code, name)| (*code, name.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            to_unicode,
+            two_byte: font.is_cid(),
+            widths,
+            differences,
+        }
+    }
+
+    /// Splits string bytes into character codes and decodes each one
+    ///
+    /// Returns (code, unicode text) pairs; codes that cannot be mapped decode
+    /// to an empty string but still advance the pen.
+    fn decode(&self, bytes: &[u8]) -> Vec<(u32, String)> {
+        let codes: Vec<u32> = if self.two_byte {
+            bytes
+                .chunks(2)
+                .map(|pair| {
+                    if pair.len() == 2 {
+                        (u32::from(pair[0]) << 8) | u32::from(pair[1])
+                    } else {
+                        u32::from(pair[0])
+                    }
+                })
+                .collect()
+        } else {
+            bytes.iter().map(|&b| u32::from(b)).collect()
+        };
+
+        codes
+            .into_iter()
+            .map(|code| (code, self.decode_code(code)))
+            .collect()
+    }
+
+    fn decode_code(&self, code: u32) -> String {
+        if let Some(map) = &self.to_unicode {
+            if let Some(text) = u16::try_from(code).ok().and_then(|gid| map.get(gid)) {
+                return text.to_string();
+            }
+        }
+        if let Some(name) = self.differences.get(&code) {
+            if let Some(text) = glyph_name_to_unicode(name) {
+                return text;
+            }
+        }
+        if self.two_byte {
+            return String::new();
+        }
+        u8::try_from(code)
+            .ok()
+            .and_then(win_ansi_char)
+            .map(|c| c.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Glyph advance in thousandths of text space units
+    fn glyph_width(&self, code: u32) -> f32 {
+        match &self.widths {
+            Some(widths) => {
+                let width = widths.get(code as usize);
+                if width > 0.0 {
+                    width
+                } else {
+                    DEFAULT_GLYPH_WIDTH
+                }
+            }
+            None => DEFAULT_GLYPH_WIDTH,
+        }
+    }
+}
+
+struct PageWalker<'a, 'h, R: Resolve> {
+    file: &'a R,
+    fonts: HashMap<String, Option<FontDecoder>>,
+    /// Counter giving each direct (unreferenced) resource dictionary its own scope
+    direct_scopes: usize,
+    runs: Vec<TextRun>,
+    hooks: Option<&'a mut ImageHooks<'h>>,
+}
+
+/// Extracts positioned text runs from a page, in content-stream order
+pub fn extract_page_runs<R: Resolve>(
+    file: &R,
+    page: &Page,
+    hooks: Option<&mut ImageHooks<'_>>,
+) -> pdf::error::Result<Vec<TextRun>> {
+    let Some(content) = page.contents.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let ops = content.operations(file)?;
+    let resources = page.resources().ok();
+
+    let mut walker = PageWalker {
+        file,
+        fonts: HashMap::new(),
+        direct_scopes: 0,
+        runs: Vec::new(),
+        hooks,
+    };
+    walker.walk(&ops, resources, GraphicsState::default(), 0);
+    Ok(walker.runs)
+}
+
+impl<'a, 'h, R: Resolve> PageWalker<'a, 'h, R> {
+    fn walk(
+        &mut self,
+        ops: &[Op],
+        resources: Option<&MaybeRef<Resources>>,
+        initial: GraphicsState,
+        depth: usize,
+    ) {
+        let mut state = initial;
+        let mut stack: Vec<GraphicsState> = Vec::new();
+        let mut text_matrix = Affine::IDENTITY;
+        let mut line_matrix = Affine::IDENTITY;
+        // Font names are only unique within one resource dictionary. Shared
+        // dictionaries are keyed by object number; direct ones get a scope of
+        // their own for this walk
+        let scope = match resources.map(|res| res.as_ref()) {
+            Some(Some(obj_ref)) => {
+                let inner = obj_ref.get_inner();
+                format!("{} {}:", inner.id, inner.gen)
+            }
+            Some(None) => {
+                self.direct_scopes += 1;
+                format!("direct {}:", self.direct_scopes)
+            }
+            None => String::new(),
+        };
+
+        for op in ops {
+            match op {
+                Op::Save => stack.push(state.clone()),
+                Op::Restore => {
+                    if let Some(saved) = stack.pop() {
+                        state = saved;
+                    }
+                }
+                Op::Transform { matrix } => {
+                    state.ctm = Affine::from_pdf(matrix).then(&state.ctm);
+                }
+                Op::BeginText => {
+                    text_matrix = Affine::IDENTITY;
+                    line_matrix = Affine::IDENTITY;
+                }
+                Op::EndText => {}
+                Op::CharSpacing { char_space } => state.char_spacing = *char_space,
+                Op::WordSpacing { word_space } => state.word_spacing = *word_space,
+                Op::TextScaling { horiz_scale } => state.horiz_scale = *horiz_scale / 100.0,
+                Op::Leading { leading } => state.leading = *leading,
+                Op::TextRise { rise } => state.rise = *rise,
+                Op::TextFont { name, size } => {
+                    let key = format!("{}{}", scope, name);
+                    if !self.fonts.contains_key(&key) {
+                        let decoder = resources
+                            .and_then(|res| res.fonts.get(name))
+                            .map(|font| FontDecoder::load(self.file, font));
+                        self.fonts.insert(key.clone(), decoder);
+                    }
+                    state.font = Some(key);
+                    state.font_size = *size;
+                }
+                Op::SetTextMatrix { matrix } => {
+                    text_matrix = Affine::from_pdf(matrix);
+                    line_matrix = text_matrix;
+                }
+                Op::MoveTextPosition { translation } => {
+                    line_matrix =
+                        Affine::translate(translation.x, translation.y).then(&line_matrix);
+                    text_matrix = line_matrix;
+                }
+                Op::TextNewline => {
+                    line_matrix = Affine::translate(0.0, -state.leading).then(&line_matrix);
+                    text_matrix = line_matrix;
+                }
+                Op::TextDraw { text } => {
+                    self.show_text(text.as_bytes(), &state, &mut text_matrix);
+                }
+                Op::TextDrawAdjusted { array } => {
+                    for part in array {
+                        match part {
+                            TextDrawAdjusted::Text(text) => {
+                                self.show_text(text.as_bytes(), &state, &mut text_matrix);
+                            }
+                            TextDrawAdjusted::Spacing(adjust) => {
+                                let tx = -adjust / 1000.0 * state.font_size * state.horiz_scale;
+                                text_matrix = Affine::translate(tx, 0.0).then(&text_matrix);
+                                // Large negative kerning is how many producers encode word gaps
+                                if *adjust < -200.0 {
+                                    if let Some(last) = self.runs.last_mut() {
+                                        if !last.standalone && !last.text.ends_with(' ') {
+                                            last.text.push(' ');
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Op::XObject { name } => {
+                    let origin = state.ctm;
+                    let name_str: &str = name;
+                    if let Some(hooks) = self.hooks.as_mut() {
+                        for marker in (hooks.xobject)(name_str) {
+                            self.push_standalone(marker, &origin);
+                        }
+                    }
+                    if depth + 1 >= MAX_FORM_DEPTH {
+                        continue;
+                    }
+                    let Some(obj_ref) = resources.and_then(|res| res.xobjects.get(name)) else {
+                        continue;
+                    };
+                    let Ok(xobject) = self.file.get(*obj_ref) else {
+                        continue;
+                    };
+                    if let XObject::Form(form) = &*xobject {
+                        let Ok(form_ops) = form.operations(self.file) else {
+                            continue;
+                        };
+                        let form_resources = form.dict().resources.as_ref();
+                        // Form text is decoded without image hooks: markers were emitted above
+                        let hooks = self.hooks.take();
+                        self.walk(
+                            &form_ops,
+                            form_resources.or(resources),
+                            state.clone(),
+                            depth + 1,
+                        );
+                        self.hooks = hooks;
+                    }
+                }
+                Op::InlineImage { image } => {
+                    let origin = state.ctm;
+                    let marker = self
+                        .hooks
+                        .as_mut()
+                        .and_then(|hooks| (hooks.inline_image)(image));
+                    if let Some(marker) = marker {
+                        self.push_standalone(marker, &origin);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn show_text(&mut self, bytes: &[u8], state: &GraphicsState, text_matrix: &mut Affine) {
+        let decoder = state
+            .font
+            .as_ref()
+            .and_then(|key| self.fonts.get(key))
+            .and_then(|decoder| decoder.as_ref());
+
+        let glyphs = match decoder {
+            Some(decoder) => decoder.decode(bytes),
+            None => bytes
+                .iter()
+                .map(|&b| {
+                    (
+                        u32::from(b),
+                        win_ansi_char(b).map(|c| c.to_string()).unwrap_or_default(),
+                    )
+                })
+                .collect(),
+        };
+        if glyphs.is_empty() {
+            return;
+        }
+
+        let start = Affine::translate(0.0, state.rise)
+            .then(text_matrix)
+            .then(&state.ctm);
+        let mut text = String::new();
+        let mut advance = 0.0f32;
+
+        for (code, unicode) in glyphs {
+            let glyph_width = decoder
+                .map(|decoder| decoder.glyph_width(code))
+                .unwrap_or(DEFAULT_GLYPH_WIDTH);
+            let is_space = unicode == " " || (code == 32 && !decoder.is_some_and(|d| d.two_byte));
+            let mut tx = glyph_width / 1000.0 * state.font_size + state.char_spacing;
+            if is_space {
+                tx += state.word_spacing;
+            }
+            advance += tx * state.horiz_scale;
+            text.push_str(&unicode);
+        }
+
+        let end = Affine::translate(advance, 0.0).then(text_matrix);
+        *text_matrix = end;
+
+        let text = text
+            .chars()
+            .filter(|c| !c.is_control() || *c == '\t')
+            .collect::<String>();
+        if text.is_empty() {
+            return;
+        }
+
+        let rendered = end.then(&state.ctm);
+        let font_size = (state.font_size * start.vertical_scale()).abs().max(1.0);
+        self.runs.push(TextRun {
+            x: start.e,
+            y: start.f,
+            width: (rendered.e - start.e).abs(),
+            font_size,
+            text,
+            standalone: false,
+        });
+    }
+
+    fn push_standalone(&mut self, text: String, origin: &Affine) {
+        self.runs.push(TextRun {
+            x: origin.e,
+            // Images are drawn upwards from their origin; use the top edge
+            y: origin.f + origin.vertical_scale(),
+            width: 0.0,
+            font_size: 0.0,
+            text,
+            standalone: true,
+        });
+    }
+}
+
+fn win_ansi_char(byte: u8) -> Option<char> {
+    let c = match byte {
+        0x80 => '€',
+        0x82 => '‚',
+        0x83 => 'ƒ',
+        0x84 => '„',
+        0x85 => '…',
+        0x86 => '†',
+        0x87 => '‡',
+        0x88 => 'ˆ',
+        0x89 => '‰',
+        0x8A => 'Š',
+        0x8B => '‹',
+        0x8C => 'Œ',
+        0x8E => 'Ž',
+        0x91 => '\u{2018}',
+        0x92 => '\u{2019}',
+        0x93 => '\u{201C}',
+        0x94 => '\u{201D}',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x98 => '˜',
+        0x99 => '™',
+        0x9A => 'š',
+        0x9B => '›',
+        0x9C => 'œ',
+        0x9E => 'ž',
+        0x9F => 'Ÿ',
+        0x00..=0x1F | 0x7F..=0x9F => return None,
+        other => other as char,
+    };
+    Some(c)
+}
+
+/// Maps an Adobe glyph name (from an encoding /Differences array) to text
+fn glyph_name_to_unicode(name: &str) -> Option<String> {
+    if let Some(hex) = name.strip_prefix("uni") {
+        if hex.len() >= 4 && hex.len() % 4 == 0 {
+            let decoded: Option<String> = hex
+                .as_bytes()
+                .chunks(4)
+                .map(|chunk| {
+                    std::str::from_utf8(chunk)
+                        .ok()
+                        .and_then(|h| u32::from_str_radix(h, 16).ok())
+                        .and_then(char::from_u32)
+                })
+                .collect();
+            if decoded.is_some() {
+                return decoded;
+            }
+        }
+    }
+
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphabetic() {
+            return Some(c.to_string());
+        }
+    }
+
+    let text = match name {
+        "space" | "nbspace" => " ",
+        "zero" => "0",
+        "one" => "1",
+        "two" => "2",
+        "three" => "3",
+        "four" => "4",
+        "five" => "5",
+        "six" => "6",
+        "seven" => "7",
+        "eight" => "8",
+        "nine" => "9",
+        "period" => ".",
+        "comma" => ",",
+        "colon" => ":",
+        "semicolon" => ";",
+        "hyphen" | "minus" => "-",
+        "endash" => "–",
+        "emdash" => "—",
+        "exclam" => "!",
+        "question" => "?",
+        "parenleft" => "(",
+        "parenright" => ")",
+        "bracketleft" => "[",
+        "bracketright" => "]",
+        "slash" => "/",
+        "quotesingle" => "'",
+        "quotedbl" => "\"",
+        "quoteleft" => "\u{2018}",
+        "quoteright" => "\u{2019}",
+        "quotedblleft" => "\u{201C}",
+        "quotedblright" => "\u{201D}",
+        "ellipsis" => "…",
+        "bullet" => "•",
+        "fi" => "fi",
+        "fl" => "fl",
+        "ff" => "ff",
+        "ffi" => "ffi",
+        "ffl" => "ffl",
+        _ => return None,
+    };
+    Some(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glyph_name_to_unicode, Affine};
+
+    #[test]
+    fn composes_text_and_page_matrices() {
+        let ctm = Affine::translate(10.0, 20.0);
+        let tm = Affine::translate(5.0, 5.0);
+        let combined = tm.then(&ctm);
+        assert_eq!((combined.e, combined.f), (15.0, 25.0));
+    }
+
+    #[test]
+    fn maps_glyph_names() {
+        assert_eq!(glyph_name_to_unicode("fi").as_deref(), Some("fi"));
+        assert_eq!(glyph_name_to_unicode("uni00E9").as_deref(), Some("é"));
+        assert_eq!(glyph_name_to_unicode("A").as_deref(), Some("A"));
+        assert_eq!(glyph_name_to_unicode("unknownglyph"), None);
+    }
+}
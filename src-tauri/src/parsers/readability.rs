@@ -0,0 +1,740 @@
+//! Readability-style main content extraction for web pages
+//!
+//! Paragraph-bearing elements are scored the way Mozilla's Readability does
+//! (text length, commas, class/id hints, link density); the best container
+//! and its related siblings are kept and flattened into blocks. Page metadata
+//! comes from `<title>`, `<meta>` and OpenGraph tags.
+
+use kuchikiki::traits::*;
+use kuchikiki::{Node, NodeRef};
+use std::collections::HashMap;
+
+const MIN_SCORED_TEXT_CHARS: usize = 25;
+const MAX_SCORED_ANCESTORS: usize = 5;
+/// Containers with more link text than this share are navigation, not content
+const MAX_CONTAINER_LINK_DENSITY: f64 = 0.5;
+
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "iframe", "svg", "canvas", "form", "button",
+    "input", "select", "textarea", "object", "embed", "nav", "aside", "footer", "dialog",
+];
+const REMOVED_ROLES: &[&str] = &[
+    "navigation",
+    "complementary",
+    "dialog",
+    "alertdialog",
+    "menu",
+    "menubar",
+    "search",
+];
+const UNLIKELY_HINTS: &[&str] = &[
+    "-ad-",
+    "ad-break",
+    "agegate",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "community",
+    "cookie",
+    "cover-wrap",
+    "disqus",
+    "extra",
+    "gdpr",
+    "legends",
+    "menu",
+    "newsletter",
+    "pager",
+    "pagination",
+    "popup",
+    "related",
+    "remark",
+    "replies",
+    "rss",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "social",
+    "sponsor",
+    "subscribe",
+    "supplemental",
+    "yom-remote",
+];
+const MAYBE_CANDIDATE_HINTS: &[&str] = &[
+    "and", "article", "body", "column", "content", "main", "shadow",
+];
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "h-entry", "main", "page", "post",
+    "story", "text",
+];
+const NEGATIVE_HINTS: &[&str] = &[
+    "-ad-",
+    "banner",
+    "byline",
+    "combx",
+    "comment",
+    "contact",
+    "foot",
+    "footer",
+    "footnote",
+    "gdpr",
+    "masthead",
+    "media",
+    "meta",
+    "outbrain",
+    "promo",
+    "related",
+    "scroll",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "sponsor",
+    "shopping",
+    "tags",
+    "tool",
+    "widget",
+];
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+const TITLE_SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " :: ", " / ", " · "];
+
+/// Metadata declared by the page itself
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArticleMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    pub published_time: Option<String>,
+    pub language: Option<String>,
+    pub canonical_url: Option<String>,
+    pub image: Option<String>,
+}
+
+/// A piece of the extracted main content, in reading order
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArticleBlock {
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph(String),
+    ListItem(String),
+    /// Preformatted text with its line breaks kept
+    Code(String),
+    /// An image with its `src` exactly as written in the page
+    Image {
+        src: String,
+        alt: String,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Article {
+    /// Page title with the site name stripped, or the first heading
+    pub title: Option<String>,
+    pub metadata: ArticleMetadata,
+    pub blocks: Vec<ArticleBlock>,
+}
+
+impl Article {
+    /// Characters of readable text, used to judge whether extraction worked
+    pub fn text_len(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                ArticleBlock::Heading { text, .. }
+                | ArticleBlock::Paragraph(text)
+                | ArticleBlock::ListItem(text)
+                | ArticleBlock::Code(text) => text.chars().count(),
+                ArticleBlock::Image { .. } => 0,
+            })
+            .sum()
+    }
+}
+
+/// Extracts the main content and metadata of an HTML page
+pub fn extract_article(html: &str) -> Article {
+    let document = kuchikiki::parse_html().one(html).document_node;
+    let metadata = read_metadata(&document);
+
+    remove_non_content(&document);
+    let body = document
+        .select_first("body")
+        .map(|body| body.as_node().clone())
+        .unwrap_or_else(|_| document.clone());
+
+    let content_nodes = select_content(&body);
+    let mut emitter = BlockEmitter::default();
+    for node in &content_nodes {
+        emitter.walk(node);
+    }
+    emitter.flush();
+    let blocks = emitter.blocks;
+
+    let title = metadata
+        .title
+        .as_deref()
+        .map(|title| clean_document_title(title, metadata.site_name.as_deref()))
+        .filter(|title| !title.is_empty())
+        .or_else(|| {
+            blocks.iter().find_map(|block| match block {
+                ArticleBlock::Heading { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+        });
+
+    Article {
+        title,
+        metadata,
+        blocks,
+    }
+}
+
+fn read_metadata(document: &NodeRef) -> ArticleMetadata {
+    let mut values: HashMap<String, String> = HashMap::new();
+    if let Ok(metas) = document.select("meta") {
+        for meta in metas {
+            let attributes = meta.attributes.borrow();
+            let Some(content) = attributes.get("content").map(normalize_text) else {
+                continue;
+            };
+            if content.is_empty() {
+                continue;
+            }
+            for key_attr in ["property", "name", "itemprop", "http-equiv"] {
+                if let Some(keys) = attributes.get(key_attr) {
+                    // `property` may hold several space separated names
+                    for key in keys.split_whitespace() {
+                        values
+                            .entry(key.to_ascii_lowercase())
+                            .or_insert_with(|| content.clone());
+                    }
+                }
+            }
+        }
+    }
+    let pick = |keys: &[&str]| keys.iter().find_map(|key| values.get(*key).cloned());
+
+    let document_title = document
+        .select_first("title")
+        .map(|title| normalize_text(&title.text_contents()))
+        .ok()
+        .filter(|title| !title.is_empty());
+    let html_lang = document.select_first("html").ok().and_then(|html| {
+        html.attributes
+            .borrow()
+            .get("lang")
+            .map(|lang| lang.trim().to_string())
+            .filter(|lang| !lang.is_empty())
+    });
+    let canonical = document
+        .select_first("link[rel~=canonical]")
+        .ok()
+        .and_then(|link| link.attributes.borrow().get("href").map(str::to_string));
+    let rel_author = document
+        .select_first("[rel~=author], [itemprop~=author]")
+        .ok()
+        .map(|author| normalize_text(&author.text_contents()))
+        .filter(|author| !author.is_empty() && author.chars().count() < 100);
+    let time = document
+        .select_first("time[datetime]")
+        .ok()
+        .and_then(|time| time.attributes.borrow().get("datetime").map(str::to_string));
+
+    // `article:author` is often a profile URL rather than a name
+    let author = pick(&["author", "dc.creator", "parsely-author", "twitter:creator"])
+        .or_else(|| pick(&["article:author"]).filter(|a| !a.starts_with("http")))
+        .or(rel_author);
+
+    ArticleMetadata {
+        title: pick(&["og:title", "twitter:title", "dc.title"]).or(document_title),
+        author,
+        description: pick(&["description", "og:description", "twitter:description"]),
+        site_name: pick(&["og:site_name", "application-name"]),
+        published_time: pick(&[
+            "article:published_time",
+            "datepublished",
+            "dc.date",
+            "date",
+            "pubdate",
+            "og:published_time",
+        ])
+        .or(time),
+        language: html_lang.or_else(|| pick(&["content-language", "og:locale"])),
+        canonical_url: canonical.or_else(|| pick(&["og:url"])),
+        image: pick(&["og:image", "twitter:image"]),
+    }
+}
+
+/// Strips a leading or trailing site name from a `<title>`
+fn clean_document_title(title: &str, site_name: Option<&str>) -> String {
+    let title = normalize_text(title);
+    if let Some(site) = site_name.map(normalize_text).filter(|s| !s.is_empty()) {
+        for separator in TITLE_SEPARATORS {
+            if let Some(rest) = title.strip_suffix(&format!("{}{}", separator, site)) {
+                return rest.trim().to_string();
+            }
+            if let Some(rest) = title.strip_prefix(&format!("{}{}", site, separator)) {
+                return rest.trim().to_string();
+            }
+        }
+    }
+    for separator in TITLE_SEPARATORS {
+        if let Some((head, _)) = title.rsplit_once(separator) {
+            if head.split_whitespace().count() >= 3 {
+                return head.trim().to_string();
+            }
+        }
+    }
+    title
+}
+
+/// Detaches scripts, navigation, hidden elements and unlikely candidates
+fn remove_non_content(document: &NodeRef) {
+    let mut doomed = Vec::new();
+    for node in document.descendants() {
+        let Some(element) = node.as_element() else {
+            if node.as_comment().is_some() {
+                doomed.push(node.clone());
+            }
+            continue;
+        };
+        let tag = element.name.local.as_ref();
+        if REMOVED_TAGS.contains(&tag) {
+            doomed.push(node.clone());
+            continue;
+        }
+
+        let attributes = element.attributes.borrow();
+        let hidden = attributes.contains("hidden")
+            || attributes.get("aria-hidden") == Some("true")
+            || attributes.get("style").is_some_and(|style| {
+                let style = style.replace(' ', "").to_ascii_lowercase();
+                style.contains("display:none") || style.contains("visibility:hidden")
+            });
+        let role_removed = attributes
+            .get("role")
+            .is_some_and(|role| REMOVED_ROLES.contains(&role.trim()));
+        if hidden || role_removed {
+            doomed.push(node.clone());
+            continue;
+        }
+
+        if matches!(tag, "html" | "body" | "a" | "article" | "main") {
+            continue;
+        }
+        let hints = class_and_id(&attributes);
+        if has_hint(&hints, UNLIKELY_HINTS)
+            && !has_hint(&hints, MAYBE_CANDIDATE_HINTS)
+            && !contains_tag(&node, "h1")
+        {
+            doomed.push(node.clone());
+        }
+    }
+    for node in doomed {
+        node.detach();
+    }
+}
+
+/// Picks the nodes holding the main content, in document order
+fn select_content(body: &NodeRef) -> Vec<NodeRef> {
+    let mut scores: HashMap<*const Node, (NodeRef, f64)> = HashMap::new();
+
+    for node in body.descendants() {
+        let Some(element) = node.as_element() else {
+            continue;
+        };
+        let tag = element.name.local.as_ref();
+        let scorable = matches!(tag, "p" | "pre" | "td")
+            || (matches!(tag, "div" | "section") && !has_block_children(&node));
+        if !scorable {
+            continue;
+        }
+        let text = normalize_text(&node.text_contents());
+        let text_len = text.chars().count();
+        if text_len < MIN_SCORED_TEXT_CHARS {
+            continue;
+        }
+
+        let commas = text.matches([',', '，', '、']).count();
+        let score = 1.0 + commas as f64 + (text_len as f64 / 100.0).min(3.0);
+
+        for (level, ancestor) in node.ancestors().take(MAX_SCORED_ANCESTORS).enumerate() {
+            if ancestor.as_element().is_none() {
+                break;
+            }
+            let divider = match level {
+                0 => 1.0,
+                1 => 2.0,
+                _ => level as f64 * 3.0,
+            };
+            let entry = scores
+                .entry(node_key(&ancestor))
+                .or_insert_with(|| (ancestor.clone(), initial_score(&ancestor)));
+            entry.1 += score / divider;
+        }
+    }
+
+    let top = scores
+        .values()
+        .map(|(node, score)| (node.clone(), score * (1.0 - link_density(node))))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    let Some((top, top_score)) = top else {
+        return vec![body.clone()];
+    };
+
+    let Some(parent) = top.parent().filter(|parent| parent.as_element().is_some()) else {
+        return vec![top];
+    };
+
+    // Siblings scoring close to the top candidate are part of the article too
+    let threshold = (top_score * 0.2).max(10.0);
+    let top_class = class_attribute(&top);
+    let mut content = Vec::new();
+    for sibling in parent.children() {
+        if sibling == top {
+            content.push(sibling);
+            continue;
+        }
+        let Some(element) = sibling.as_element() else {
+            continue;
+        };
+        let mut score = scores
+            .get(&node_key(&sibling))
+            .map(|(_, score)| *score * (1.0 - link_density(&sibling)))
+            .unwrap_or(0.0);
+        if !top_class.is_empty() && class_attribute(&sibling) == top_class {
+            score += top_score * 0.2;
+        }
+        let keep = score >= threshold
+            || (element.name.local.as_ref() == "p" && {
+                let text = normalize_text(&sibling.text_contents());
+                let len = text.chars().count();
+                let density = link_density(&sibling);
+                (len > 80 && density < 0.25) || (len > 0 && density == 0.0 && ends_sentence(&text))
+            });
+        if keep {
+            content.push(sibling);
+        }
+    }
+    content
+}
+
+fn initial_score(node: &NodeRef) -> f64 {
+    let Some(element) = node.as_element() else {
+        return 0.0;
+    };
+    let base = match element.name.local.as_ref() {
+        "div" | "article" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let hints = class_and_id(&element.attributes.borrow());
+    let mut weight = 0.0;
+    if has_hint(&hints, POSITIVE_HINTS) {
+        weight += 25.0;
+    }
+    if has_hint(&hints, NEGATIVE_HINTS) {
+        weight -= 25.0;
+    }
+    base + weight
+}
+
+/// Flattens content nodes into blocks
+#[derive(Default)]
+struct BlockEmitter {
+    blocks: Vec<ArticleBlock>,
+    inline: String,
+    pending_images: Vec<ArticleBlock>,
+}
+
+impl BlockEmitter {
+    fn walk(&mut self, node: &NodeRef) {
+        if let Some(text) = node.as_text() {
+            self.inline.push_str(&text.borrow());
+            return;
+        }
+        let Some(element) = node.as_element() else {
+            for child in node.children() {
+                self.walk(&child);
+            }
+            return;
+        };
+
+        let tag = element.name.local.as_ref();
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let text = normalize_text(&node.text_contents());
+                if !text.is_empty() {
+                    let level = tag[1..].parse().unwrap_or(6);
+                    self.blocks.push(ArticleBlock::Heading { level, text });
+                }
+            }
+            "br" => self.inline.push('\n'),
+            "img" => {
+                let attributes = element.attributes.borrow();
+                let src = ["src", "data-src", "data-original"]
+                    .iter()
+                    .find_map(|attr| attributes.get(*attr))
+                    .map(str::trim)
+                    .filter(|src| !src.is_empty());
+                if let Some(src) = src {
+                    self.pending_images.push(ArticleBlock::Image {
+                        src: src.to_string(),
+                        alt: normalize_text(attributes.get("alt").unwrap_or("")),
+                    });
+                }
+            }
+            "pre" => {
+                self.flush();
+                let text = node.text_contents();
+                let text = text.trim_matches('\n').trim_end();
+                if !text.trim().is_empty() {
+                    self.blocks.push(ArticleBlock::Code(text.to_string()));
+                }
+            }
+            "li" if !has_block_children(node) => {
+                self.flush();
+                self.walk_children(node);
+                let text = self.take_inline();
+                if !text.is_empty() {
+                    self.blocks.push(ArticleBlock::ListItem(text));
+                }
+                self.flush();
+            }
+            "tr" => {
+                self.flush();
+                let cells: Vec<String> = node
+                    .children()
+                    .filter(|cell| {
+                        cell.as_element()
+                            .is_some_and(|e| matches!(e.name.local.as_ref(), "td" | "th"))
+                    })
+                    .map(|cell| normalize_text(&cell.text_contents()))
+                    .collect();
+                if cells.iter().any(|cell| !cell.is_empty()) {
+                    self.blocks.push(ArticleBlock::Paragraph(cells.join(" | ")));
+                }
+                for image in node.descendants().filter(|n| is_tag(n, "img")) {
+                    self.walk(&image);
+                }
+                self.flush();
+            }
+            _ if BLOCK_TAGS.contains(&tag) => {
+                let is_container = matches!(tag, "div" | "section" | "ul" | "ol" | "table");
+                if is_container && link_density(node) > MAX_CONTAINER_LINK_DENSITY {
+                    return;
+                }
+                self.flush();
+                self.walk_children(node);
+                self.flush();
+            }
+            _ => self.walk_children(node),
+        }
+    }
+
+    fn walk_children(&mut self, node: &NodeRef) {
+        for child in node.children() {
+            self.walk(&child);
+        }
+    }
+
+    fn take_inline(&mut self) -> String {
+        let text = std::mem::take(&mut self.inline)
+            .split('\n')
+            .map(normalize_text)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        text
+    }
+
+    fn flush(&mut self) {
+        let text = self.take_inline();
+        if !text.is_empty() {
+            self.blocks.push(ArticleBlock::Paragraph(text));
+        }
+        self.blocks.append(&mut self.pending_images);
+    }
+}
+
+fn node_key(node: &NodeRef) -> *const Node {
+    &**node as *const Node
+}
+
+fn is_tag(node: &NodeRef, tag: &str) -> bool {
+    node.as_element()
+        .is_some_and(|element| element.name.local.as_ref() == tag)
+}
+
+fn contains_tag(node: &NodeRef, tag: &str) -> bool {
+    node.descendants().any(|n| is_tag(&n, tag))
+}
+
+fn has_block_children(node: &NodeRef) -> bool {
+    node.children().any(|child| {
+        child
+            .as_element()
+            .is_some_and(|element| BLOCK_TAGS.contains(&element.name.local.as_ref()))
+    })
+}
+
+/// Share of the text that sits inside links
+fn link_density(node: &NodeRef) -> f64 {
+    let total = normalize_text(&node.text_contents()).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = node
+        .descendants()
+        .filter(|n| is_tag(n, "a"))
+        .map(|a| normalize_text(&a.text_contents()).chars().count())
+        .sum();
+    (linked as f64 / total as f64).min(1.0)
+}
+
+fn class_attribute(node: &NodeRef) -> String {
+    node.as_element()
+        .and_then(|element| element.attributes.borrow().get("class").map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn class_and_id(attributes: &kuchikiki::Attributes) -> String {
+    format!(
+        "{} {}",
+        attributes.get("class").unwrap_or(""),
+        attributes.get("id").unwrap_or("")
+    )
+    .to_ascii_lowercase()
+}
+
+fn has_hint(hints: &str, patterns: &[&str]) -> bool {
+    hints
+        .split(|c: char| c.is_whitespace() || c == '_')
+        .any(|word| {
+            !word.is_empty()
+                && patterns.iter().any(|pattern| {
+                    if pattern.starts_with('-') {
+                        format!("-{}-", word).contains(pattern)
+                    } else {
+                        word.contains(pattern)
+                    }
+                })
+        })
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .ends_with(['.', '!', '?', '。', '！', '？', '"', '”'])
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_article, ArticleBlock};
+
+    const PAGE: &str = r#"<!doctype html>
+<html lang="en"><head>
+<title>Why Tides Turn | Ocean Weekly</title>
+<meta property="og:site_name" content="Ocean Weekly">
+<meta name="author" content="Mara Quint">
+<meta property="article:published_time" content="2024-05-02T08:00:00Z">
+<meta name="description" content="A short tour of tidal forces.">
+<script>var tracking = "Lots, of, commas, here, to, confuse, scoring";</script>
+</head><body>
+<nav><a href="/">Home</a> <a href="/news">News</a></nav>
+<div class="sidebar"><p>Subscribe now, today, for more stories, offers, and news.</p></div>
+<article class="post-content">
+  <h1>Why Tides Turn</h1>
+  <p>The Moon pulls on the oceans, and the Earth turns beneath that bulge, which is why most coasts see two tides a day.</p>
+  <h2>Springs and neaps</h2>
+  <p>When the Sun and Moon line up, their pulls add together, giving the large spring tides; at right angles, they partly cancel.</p>
+  <figure><img src="images/tide.png" alt="Tide chart"><figcaption>Tide heights over a month.</figcaption></figure>
+  <ul><li>High water</li><li>Low water</li></ul>
+  <div class="share"><a href="/s1">Share on social</a> <a href="/s2">Email this</a></div>
+</article>
+<footer><p>Copyright, all rights, reserved, by the publisher, forever.</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn extracts_article_blocks_and_metadata() {
+        let article = extract_article(PAGE);
+
+        assert_eq!(article.title.as_deref(), Some("Why Tides Turn"));
+        assert_eq!(article.metadata.author.as_deref(), Some("Mara Quint"));
+        assert_eq!(
+            article.metadata.published_time.as_deref(),
+            Some("2024-05-02T08:00:00Z")
+        );
+        assert_eq!(article.metadata.language.as_deref(), Some("en"));
+        assert_eq!(article.metadata.site_name.as_deref(), Some("Ocean Weekly"));
+
+        assert_eq!(
+            article.blocks,
+            vec![
+                ArticleBlock::Heading {
+                    level: 1,
+                    text: "Why Tides Turn".to_string()
+                },
+                ArticleBlock::Paragraph(
+                    "The Moon pulls on the oceans, and the Earth turns beneath that bulge, which is why most coasts see two tides a day.".to_string()
+                ),
+                ArticleBlock::Heading {
+                    level: 2,
+                    text: "Springs and neaps".to_string()
+                },
+                ArticleBlock::Paragraph(
+                    "When the Sun and Moon line up, their pulls add together, giving the large spring tides; at right angles, they partly cancel.".to_string()
+                ),
+                ArticleBlock::Image {
+                    src: "images/tide.png".to_string(),
+                    alt: "Tide chart".to_string()
+                },
+                ArticleBlock::Paragraph("Tide heights over a month.".to_string()),
+                ArticleBlock::ListItem("High water".to_string()),
+                ArticleBlock::ListItem("Low water".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! reStructuredText (`.rst`) documents
+//!
+//! The source is converted to Markdown and sectioned like any Markdown file.
+//! Section levels follow the order in which adornment styles first appear;
+//! literal blocks and code directives become fenced blocks, admonitions
+//! become quotes, and Sphinx-only directives (toctree, index, ...) are dropped.
+
+use super::markdown::build_markdown_sections;
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+const ADORNMENT_CHARS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+/// Placeholders for inline code while the rest of a paragraph is rewritten
+const PROTECTED_START: char = '\u{E010}';
+const PROTECTED_END: char = '\u{E011}';
+
+/// A `.. _name: url` hyperlink target
+static HYPERLINK_TARGET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*\.\.\s+_([^:]+):\s*(\S+)\s*$").unwrap());
+/// A `.. |name| replace:: text` substitution definition
+static SUBSTITUTION_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*\.\.\s+\|([^|]+)\|\s+replace::\s*(.+)$").unwrap());
+static DIRECTIVE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\.\.\s+([A-Za-z0-9_:+-]+)::\s*(.*)$").unwrap());
+static FOOTNOTE_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\.\.\s+\[([^\]]+)\]\s*(.*)$").unwrap());
+static FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^:([^:]+):\s*(.*)$").unwrap());
+static BULLET_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[-*+•‣⁃]\s+(.*)$").unwrap());
+static ENUMERATED_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:\d+|#)[.)]\s+(.*)$|^\((?:\d+|#)\)\s+(.*)$").unwrap());
+static SIMPLE_TABLE_BORDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^=+(?:\s+=+)+\s*$").unwrap());
+static GRID_TABLE_BORDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\+[-=+]+\+\s*$").unwrap());
+static DIRECTIVE_OPTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:([\w-]+):\s*(.*)$").unwrap());
+static INLINE_LITERAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"``(.+?)``").unwrap());
+/// Interpreted text with an explicit role, such as :code:`x`
+static ROLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r":([A-Za-z0-9_.+-]+(?::[A-Za-z0-9_.+-]+)?):`([^`]+)`").unwrap());
+/// An inline hyperlink with an embedded `<uri>`
+static EMBEDDED_URI: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`([^`<]*?)\s*<([^<>`]+)>`__?").unwrap());
+static NAMED_REFERENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`__?").unwrap());
+static DEFAULT_ROLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`").unwrap());
+static FOOTNOTE_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(#?[\w-]*|\*)\]_").unwrap());
+static SUBSTITUTION_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\|([^|\s][^|]*)\|(?:__?)?").unwrap());
+/// A protected span left by `inline`
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("\u{E010}(\\d+)\u{E011}").unwrap());
+
+pub struct RstParser {
+    file_path: String,
+}
+
+impl RstParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let content = fs::read_to_string(&self.file_path)?;
+        let (markdown, author) = rst_to_markdown(&content);
+
+        let fallback_title = Path::new(&self.file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled");
+        let (title, sections) = build_markdown_sections(&markdown, fallback_title);
+
+        let metadata = NewDocument {
+            title,
+            author,
+            language: None,
+            file_path: self.file_path.clone(),
+            file_type: "markdown".to_string(),
+        };
+
+        Ok((metadata, sections))
+    }
+}
+
+/// Converts reStructuredText to Markdown, also returning the `:Author:` field
+fn rst_to_markdown(content: &str) -> (String, Option<String>) {
+    let content = content.replace("\r\n", "\n").replace('\t', "    ");
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut converter = RstConverter::default();
+    for line in &lines {
+        if let Some(caps) = HYPERLINK_TARGET.captures(line) {
+            converter
+                .targets
+                .insert(caps[1].trim().to_lowercase(), caps[2].to_string());
+        } else if let Some(caps) = SUBSTITUTION_DEFINITION.captures(line) {
+            converter
+                .substitutions
+                .insert(caps[1].to_string(), caps[2].trim().to_string());
+        }
+    }
+
+    let blocks = converter.convert(&lines);
+    (blocks.join("\n\n"), converter.author)
+}
+
+#[derive(Default)]
+struct RstConverter {
+    /// Named hyperlink targets, keyed by lower-cased name
+    targets: HashMap<String, String>,
+    substitutions: HashMap<String, String>,
+    /// Adornment (character, has overline) per section level, in order of appearance
+    heading_styles: Vec<(char, bool)>,
+    author: Option<String>,
+}
+
+impl RstConverter {
+    /// Converts a run of (already dedented) lines to Markdown blocks
+    fn convert(&mut self, lines: &[&str]) -> Vec<String> {
+        let mut blocks: Vec<String> = Vec::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut list: Vec<String> = Vec::new();
+        let mut literal_next = false;
+
+        let mut idx = 0;
+        while idx < lines.len() {
+            let line = lines[idx];
+            let trimmed = line.trim();
+            let next = lines.get(idx + 1).copied();
+
+            if trimmed.is_empty() {
+                literal_next |= self.flush_paragraph(&mut paragraph, &mut blocks);
+                idx += 1;
+                continue;
+            }
+
+            let indented = line.starts_with(' ');
+            if indented && !paragraph.is_empty() {
+                paragraph.push(trimmed);
+                idx += 1;
+                continue;
+            }
+            if indented {
+                let (block, end) = indented_block(lines, idx);
+                if literal_next {
+                    flush_list(&mut list, &mut blocks);
+                    blocks.push(fenced("", &block.join("\n")));
+                } else if !list.is_empty() {
+                    // Nested list or item continuation
+                    for inner in self.convert(&block) {
+                        list.extend(inner.lines().map(|l| format!("  {}", l)));
+                    }
+                } else {
+                    let inner = self.convert(&block);
+                    if !inner.is_empty() {
+                        blocks.push(quote(&inner));
+                    }
+                }
+                literal_next = false;
+                idx = end;
+                continue;
+            }
+            literal_next = false;
+
+            if paragraph.is_empty() {
+                // Section title with overline and underline
+                if is_adornment(line) {
+                    if let (Some(title), Some(under)) = (next, lines.get(idx + 2)) {
+                        if !title.trim().is_empty() && under.trim_end() == line.trim_end() {
+                            flush_list(&mut list, &mut blocks);
+                            let style = (trimmed.chars().next().unwrap_or('='), true);
+                            blocks.push(self.heading(style, title.trim()));
+                            idx += 3;
+                            continue;
+                        }
+                    }
+                    let prev_blank = idx == 0 || lines[idx - 1].trim().is_empty();
+                    let next_blank = next.map(|l| l.trim().is_empty()).unwrap_or(true);
+                    if trimmed.len() >= 4 && prev_blank && next_blank {
+                        flush_list(&mut list, &mut blocks);
+                        blocks.push("---".to_string());
+                        idx += 1;
+                        continue;
+                    }
+                }
+
+                // Section title with underline only
+                if let Some(under) = next {
+                    if is_adornment(under)
+                        && !is_adornment(line)
+                        && under.trim().chars().count() >= trimmed.chars().count().min(3)
+                    {
+                        flush_list(&mut list, &mut blocks);
+                        let style = (under.trim().chars().next().unwrap_or('='), false);
+                        blocks.push(self.heading(style, trimmed));
+                        idx += 2;
+                        continue;
+                    }
+                }
+
+                if trimmed == ".." || trimmed.starts_with(".. ") {
+                    flush_list(&mut list, &mut blocks);
+                    let (body, end) = indented_block(lines, idx + 1);
+                    if let Some(caps) = DIRECTIVE.captures(trimmed) {
+                        let name = caps[1].to_ascii_lowercase();
+                        let argument = caps[2].trim().to_string();
+                        blocks.extend(self.directive(&name, &argument, &body));
+                    } else if let Some(caps) = FOOTNOTE_DEFINITION.captures(trimmed) {
+                        let mut text = caps[2].to_string();
+                        for line in &body {
+                            text.push(' ');
+                            text.push_str(line.trim());
+                        }
+                        blocks.push(format!("[{}] {}", &caps[1], self.inline(text.trim())));
+                    }
+                    // Anything else is a comment, target or substitution definition
+                    idx = end;
+                    continue;
+                }
+
+                if let Some(caps) = FIELD.captures(trimmed) {
+                    let name = caps[1].trim();
+                    let mut value = caps[2].trim().to_string();
+                    let (body, end) = indented_block(lines, idx + 1);
+                    for line in &body {
+                        value.push(' ');
+                        value.push_str(line.trim());
+                    }
+                    if matches!(name.to_ascii_lowercase().as_str(), "author" | "authors")
+                        && self.author.is_none()
+                        && !value.trim().is_empty()
+                    {
+                        self.author = Some(value.trim().to_string());
+                    }
+                    list.push(format!("- **{}:** {}", name, self.inline(value.trim())));
+                    idx = end;
+                    continue;
+                }
+
+                if SIMPLE_TABLE_BORDER.is_match(line) || GRID_TABLE_BORDER.is_match(line) {
+                    flush_list(&mut list, &mut blocks);
+                    let end = lines[idx..]
+                        .iter()
+                        .position(|l| l.trim().is_empty())
+                        .map(|offset| idx + offset)
+                        .unwrap_or(lines.len());
+                    blocks.push(fenced("text", &lines[idx..end].join("\n")));
+                    idx = end;
+                    continue;
+                }
+
+                let item = BULLET_ITEM
+                    .captures(trimmed)
+                    .map(|caps| ("-", caps[1].to_string()))
+                    .or_else(|| {
+                        ENUMERATED_ITEM.captures(trimmed).map(|caps| {
+                            let text = caps.get(1).or(caps.get(2)).map_or("", |m| m.as_str());
+                            ("1.", text.to_string())
+                        })
+                    });
+                if let Some((marker, mut text)) = item {
+                    idx += 1;
+                    // Continuation lines are indented to the item text
+                    while let Some(more) = lines.get(idx) {
+                        if more.trim().is_empty() || !more.starts_with(' ') {
+                            break;
+                        }
+                        text.push(' ');
+                        text.push_str(more.trim());
+                        idx += 1;
+                    }
+                    list.push(format!("{} {}", marker, self.inline(&text)));
+                    continue;
+                }
+
+                // Definition list term
+                if let Some(definition) = next {
+                    if definition.starts_with(' ') && !definition.trim().is_empty() {
+                        flush_list(&mut list, &mut blocks);
+                        blocks.push(format!("**{}**", self.inline(trimmed)));
+                        let (body, end) = indented_block(lines, idx + 1);
+                        blocks.extend(self.convert(&body));
+                        idx = end;
+                        continue;
+                    }
+                }
+            }
+
+            flush_list(&mut list, &mut blocks);
+            paragraph.push(trimmed);
+            idx += 1;
+        }
+
+        self.flush_paragraph(&mut paragraph, &mut blocks);
+        flush_list(&mut list, &mut blocks);
+        blocks
+    }
+
+    fn heading(&mut self, style: (char, bool), title: &str) -> String {
+        let level = match self.heading_styles.iter().position(|known| *known == style) {
+            Some(position) => position + 1,
+            None => {
+                self.heading_styles.push(style);
+                self.heading_styles.len()
+            }
+        };
+        format!("{} {}", "#".repeat(level.min(6)), self.inline(title))
+    }
+
+    /// Emits a finished paragraph, returning whether it introduces a literal block
+    fn flush_paragraph(&self, paragraph: &mut Vec<&str>, blocks: &mut Vec<String>) -> bool {
+        if paragraph.is_empty() {
+            return false;
+        }
+        let text = paragraph.join("\n");
+        paragraph.clear();
+
+        let introduces_literal = text.ends_with("::");
+        let text = if !introduces_literal {
+            text.as_str()
+        } else if text.trim() == "::" {
+            ""
+        } else if let Some(stripped) = text.strip_suffix(" ::") {
+            stripped
+        } else {
+            &text[..text.len() - 1]
+        };
+        if !text.trim().is_empty() {
+            blocks.push(self.inline(text));
+        }
+        introduces_literal
+    }
+
+    fn directive(&mut self, name: &str, argument: &str, body: &[&str]) -> Vec<String> {
+        let option_count = body
+            .iter()
+            .take_while(|line| DIRECTIVE_OPTION.is_match(line.trim()))
+            .count();
+        let options: HashMap<String, String> = body[..option_count]
+            .iter()
+            .filter_map(|line| DIRECTIVE_OPTION.captures(line.trim()))
+            .map(|caps| (caps[1].to_ascii_lowercase(), caps[2].trim().to_string()))
+            .collect();
+        let content = &body[option_count..];
+        let text = content.join("\n").trim_matches('\n').to_string();
+
+        match name {
+            "code" | "code-block" | "sourcecode" | "ipython" => {
+                let language = argument.split_whitespace().next().unwrap_or("");
+                vec![fenced(language, &text)]
+            }
+            "math" => vec![format!(
+                "$$\n{}\n$$",
+                if text.is_empty() { argument } else { &text }
+            )],
+            "image" | "figure" => {
+                let alt = options.get("alt").map(String::as_str).unwrap_or("");
+                let mut blocks = vec![format!("![{}]({})", alt, argument)];
+                blocks.extend(self.convert(content));
+                blocks
+            }
+            "note" | "warning" | "tip" | "important" | "caution" | "attention" | "hint"
+            | "danger" | "error" | "seealso" | "admonition" | "versionadded" | "versionchanged"
+            | "deprecated" => {
+                let label = match name {
+                    "admonition" => argument.to_string(),
+                    "seealso" => "See also".to_string(),
+                    "versionadded" => format!("New in version {}", argument),
+                    "versionchanged" => format!("Changed in version {}", argument),
+                    "deprecated" => format!("Deprecated since version {}", argument),
+                    other => capitalize(other),
+                };
+                let mut inner = self.convert(content);
+                // Text on the directive line is the start of the body
+                if !matches!(
+                    name,
+                    "admonition" | "versionadded" | "versionchanged" | "deprecated"
+                ) && !argument.is_empty()
+                {
+                    inner.insert(0, self.inline(argument));
+                }
+                match inner.first_mut() {
+                    Some(first) => *first = format!("**{}:** {}", label, first),
+                    None => inner.push(format!("**{}**", label)),
+                }
+                vec![quote(&inner)]
+            }
+            "topic" | "sidebar" | "rubric" | "container" | "epigraph" | "highlights"
+            | "pull-quote" | "compound" => {
+                let mut blocks = Vec::new();
+                if !argument.is_empty() && name != "container" {
+                    blocks.push(format!("**{}**", self.inline(argument)));
+                }
+                blocks.extend(self.convert(content));
+                blocks
+            }
+            "csv-table" | "list-table" | "table" => {
+                let mut blocks = Vec::new();
+                if !argument.is_empty() {
+                    blocks.push(format!("**{}**", self.inline(argument)));
+                }
+                if name == "table" {
+                    blocks.extend(self.convert(content));
+                } else if !text.is_empty() {
+                    blocks.push(fenced("text", &text));
+                }
+                blocks
+            }
+            // Build instructions, navigation and raw output have no reading text
+            "toctree" | "contents" | "index" | "meta" | "raw" | "include" | "literalinclude"
+            | "highlight" | "sectnum" | "autosummary" | "only" | "tabularcolumns"
+            | "default-role" | "role" | "currentmodule" | "module" | "title" | "header"
+            | "footer" | "replace" | "unicode" | "date" | "target-notes" => Vec::new(),
+            _ => {
+                let mut blocks = Vec::new();
+                if !argument.is_empty() {
+                    blocks.push(format!("`{}`", argument));
+                }
+                blocks.extend(self.convert(content));
+                blocks
+            }
+        }
+    }
+
+    /// Rewrites inline markup: literals, roles, hyperlinks and substitutions
+    fn inline(&self, text: &str) -> String {
+        let mut protected: Vec<String> = Vec::new();
+        let mut protect = |code: String| {
+            protected.push(code);
+            format!(
+                "{}{}{}",
+                PROTECTED_START,
+                protected.len() - 1,
+                PROTECTED_END
+            )
+        };
+
+        let text = INLINE_LITERAL
+            .replace_all(text, |caps: &Captures| protect(format!("`{}`", &caps[1])))
+            .into_owned();
+        let text = ROLE
+            .replace_all(&text, |caps: &Captures| {
+                let role = caps[1]
+                    .rsplit(':')
+                    .next()
+                    .unwrap_or("")
+                    .to_ascii_lowercase();
+                let content = &caps[2];
+                // `Label <target>` shows the label; `~a.b.c` shows the last part
+                let label = match content.rfind(" <") {
+                    Some(pos) if content.ends_with('>') => content[..pos].trim().to_string(),
+                    _ if content.starts_with('~') => content[1..]
+                        .rsplit('.')
+                        .next()
+                        .unwrap_or(content)
+                        .to_string(),
+                    _ => content.to_string(),
+                };
+                match role.as_str() {
+                    "ref" | "doc" | "term" | "numref" | "abbr" | "title-reference" | "pep"
+                    | "rfc" | "download" => label,
+                    "emphasis" => format!("*{}*", label),
+                    "strong" => format!("**{}**", label),
+                    "math" => format!("${}$", label),
+                    _ => protect(format!("`{}`", label)),
+                }
+            })
+            .into_owned();
+        let text = EMBEDDED_URI
+            .replace_all(&text, |caps: &Captures| {
+                let label = caps[1].trim();
+                let target = caps[2].trim();
+                let url = match target.strip_suffix('_') {
+                    Some(name) => self.targets.get(&name.to_lowercase()).cloned(),
+                    None => Some(target.to_string()),
+                };
+                match url {
+                    Some(url) if label.is_empty() => format!("<{}>", url),
+                    Some(url) => format!("[{}]({})", label, url),
+                    None => label.to_string(),
+                }
+            })
+            .into_owned();
+        let text = NAMED_REFERENCE
+            .replace_all(&text, |caps: &Captures| {
+                let label = &caps[1];
+                match self.targets.get(&label.to_lowercase()) {
+                    Some(url) => format!("[{}]({})", label, url),
+                    None => label.to_string(),
+                }
+            })
+            .into_owned();
+        let text = DEFAULT_ROLE.replace_all(&text, "*$1*");
+        let text = FOOTNOTE_REFERENCE.replace_all(&text, "[$1]");
+        let text = SUBSTITUTION_REFERENCE.replace_all(&text, |caps: &Captures| {
+            self.substitutions
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        });
+
+        PLACEHOLDER
+            .replace_all(&text, |caps: &Captures| {
+                caps[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|idx| protected.get(idx).cloned())
+                    .unwrap_or_default()
+            })
+            .into_owned()
+    }
+}
+
+/// A section title adornment: one punctuation character repeated
+fn is_adornment(line: &str) -> bool {
+    let line = line.trim_end();
+    let mut chars = line.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    line.len() >= 2 && ADORNMENT_CHARS.contains(first) && chars.all(|c| c == first)
+}
+
+/// Collects the indented lines starting at `start`, dedented, and the index after them
+fn indented_block<'a>(lines: &[&'a str], start: usize) -> (Vec<&'a str>, usize) {
+    let mut end = start;
+    while end < lines.len() && (lines[end].trim().is_empty() || lines[end].starts_with(' ')) {
+        end += 1;
+    }
+    let mut block = &lines[start..end];
+    while block.last().is_some_and(|line| line.trim().is_empty()) {
+        block = &block[..block.len() - 1];
+    }
+    let indent = block
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let dedented = block
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    (dedented, end)
+}
+
+fn flush_list(list: &mut Vec<String>, blocks: &mut Vec<String>) {
+    if !list.is_empty() {
+        blocks.push(list.join("\n"));
+        list.clear();
+    }
+}
+
+fn quote(blocks: &[String]) -> String {
+    blocks
+        .iter()
+        .map(|block| {
+            block
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n>\n")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Wraps code in a fence longer than any backtick run inside it
+fn fenced(language: &str, code: &str) -> String {
+    let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{code}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rst_to_markdown;
+    use crate::parsers::markdown::build_markdown_sections;
+
+    #[test]
+    fn converts_sections_code_and_inline_markup() {
+        let rst = "\
+=========
+ Howto
+=========
+
+:Author: Jane Doe
+
+Install it with ``pip`` (see `the docs <https://example.com/docs>`_)::
+
+    pip install thing
+
+Usage
+=====
+
+Call :func:`~thing.run` first.
+
+.. code-block:: python
+   :linenos:
+
+   import thing
+   thing.run()
+
+.. note:: Runs are cached.
+
+.. toctree::
+
+   api
+
+Details
+-------
+
+* first item
+* second
+  item
+";
+        let (markdown, author) = rst_to_markdown(rst);
+        assert_eq!(author.as_deref(), Some("Jane Doe"));
+
+        let (title, sections) = build_markdown_sections(&markdown, "fallback");
+        assert_eq!(title, "Howto");
+        let titles: Vec<&str> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(titles, vec!["Howto", "Usage", "Details"]);
+        assert_eq!(
+            sections[0].3,
+            vec![
+                "# Howto",
+                "- **Author:** Jane Doe",
+                "Install it with `pip` (see [the docs](https://example.com/docs)):",
+                "```\npip install thing\n```",
+            ]
+        );
+        assert_eq!(
+            sections[1].3,
+            vec![
+                "## Usage",
+                "Call `run` first.",
+                "```python\nimport thing\nthing.run()\n```",
+                "> **Note:** Runs are cached.",
+            ]
+        );
+        assert_eq!(
+            sections[2].3,
+            vec!["### Details", "- first item\n- second item"]
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Plain text (`.txt`) books
+//!
+//! The encoding is taken from a BOM or a Project Gutenberg header, otherwise
+//! guessed between UTF-8, UTF-16, GBK and Windows-1252. Gutenberg license
+//! boilerplate is stripped and the text is split into sections at chapter
+//! headings ("CHAPTER IV", "Book One", "第三章", "PROLOGUE").
+
+use crate::error::{ReaderError, Result};
+use crate::models::NewDocument;
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+const EMPTY_TEXT_PLACEHOLDER: &str = "No readable content extracted from text file.";
+const LEADING_SECTION_TITLE: &str = "Content";
+/// Title of the section holding a table of contents found before chapter one
+const CONTENTS_SECTION_TITLE: &str = "Contents";
+const ENCODING_SNIFF_BYTES: usize = 4096;
+const MAX_HEADING_CHARS: usize = 80;
+/// A heading block may carry the chapter name on a second line of at most this length
+const MAX_HEADING_SUBTITLE_CHARS: usize = 60;
+/// Below this share of blank lines, every line is its own paragraph
+const MIN_BLANK_LINE_RATIO: f64 = 0.05;
+
+pub struct TextParser {
+    file_path: String,
+}
+
+/// Fields read from a Project Gutenberg header
+#[derive(Debug, Default)]
+struct GutenbergHeader {
+    title: Option<String>,
+    author: Option<String>,
+    language: Option<String>,
+}
+
+impl TextParser {
+    pub fn new(file_path: &str) -> Result<Self> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ReaderError::NotFound(file_path.to_string()));
+        }
+        Ok(Self {
+            file_path: file_path.to_string(),
+        })
+    }
+
+    pub fn parse_all(&self) -> Result<(NewDocument, Vec<(String, i32, String, Vec<String>)>)> {
+        let bytes = fs::read(&self.file_path)?;
+        let text = decode_text(&bytes)
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let (header, body) = strip_gutenberg_boilerplate(&text);
+
+        let title = header.title.unwrap_or_else(|| {
+            Path::new(&self.file_path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Untitled")
+                .to_string()
+        });
+
+        let metadata = NewDocument {
+            title,
+            author: header.author,
+            language: header.language,
+            file_path: self.file_path.clone(),
+            file_type: "text".to_string(),
+        };
+
+        Ok((metadata, build_sections(body)))
+    }
+}
+
+/// Decodes a text file, detecting its encoding
+///
+/// Order: BOM, BOM-less UTF-16, valid UTF-8, a Gutenberg `Character set
+/// encoding:` line, GBK (as GB18030) when the text decodes cleanly into
+/// mostly CJK characters, and finally Windows-1252.
+pub fn decode_text(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return text.into_owned();
+    }
+    if let Some(encoding) = detect_utf16(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return text.into_owned();
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    if let Some(encoding) = declared_gutenberg_encoding(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return text.into_owned();
+    }
+
+    let (text, had_errors) = GB18030.decode_without_bom_handling(bytes);
+    if !had_errors && mostly_cjk(&text) {
+        return text.into_owned();
+    }
+    let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+    text.into_owned()
+}
+
+/// Guesses BOM-less UTF-16 from NUL bytes in ASCII-heavy text
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(ENCODING_SNIFF_BYTES) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+    if odd_zeros * 10 > pairs * 4 && even_zeros * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 > pairs * 4 && odd_zeros * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+fn declared_gutenberg_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(ENCODING_SNIFF_BYTES)]);
+    head.lines().find_map(|line| {
+        let label = line.trim().strip_prefix("Character set encoding:")?;
+        Encoding::for_label(label.trim().as_bytes())
+    })
+}
+
+fn mostly_cjk(text: &str) -> bool {
+    let non_ascii: Vec<char> = text.chars().filter(|c| !c.is_ascii()).collect();
+    !non_ascii.is_empty()
+        && non_ascii.iter().filter(|c| is_cjk(**c)).count() * 5 >= non_ascii.len() * 4
+}
+
+/// Splits off the Project Gutenberg header and license, returning the header
+/// fields and the book text in between
+fn strip_gutenberg_boilerplate(text: &str) -> (GutenbergHeader, &str) {
+    let mut header = GutenbergHeader::default();
+    let mut body_start = 0;
+    let mut body_end = text.len();
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let upper = trimmed.to_ascii_uppercase();
+        let is_marker = upper.starts_with("***") || upper.starts_with("*END*");
+        if body_start == 0 && is_marker && upper.contains("START OF") && upper.contains("GUTENBERG")
+        {
+            body_start = offset + line.len();
+        } else if body_start > 0
+            && ((is_marker && upper.contains("END OF") && upper.contains("GUTENBERG"))
+                || upper.starts_with("END OF THE PROJECT GUTENBERG")
+                || upper.starts_with("END OF PROJECT GUTENBERG"))
+        {
+            body_end = offset;
+            break;
+        } else if body_start == 0 {
+            let field = |name: &str| {
+                trimmed
+                    .strip_prefix(name)
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            if let Some(title) = field("Title:") {
+                header.title.get_or_insert(title);
+            } else if let Some(author) = field("Author:") {
+                header.author.get_or_insert(author);
+            } else if let Some(language) = field("Language:") {
+                header.language.get_or_insert(language);
+            }
+        }
+        offset += line.len();
+    }
+
+    if body_start == 0 {
+        // Not a Gutenberg file; header-looking lines belong to the text
+        return (GutenbergHeader::default(), text);
+    }
+
+    let mut body = &text[body_start..body_end];
+    // Transcriber credits directly after the start marker
+    let credits = body.trim_start();
+    if credits.starts_with("Produced by") || credits.starts_with("E-text prepared by") {
+        body = credits
+            .split_once("\n\n")
+            .map(|(_, rest)| rest)
+            .unwrap_or("");
+    }
+    (header, body)
+}
+
+/// Splits text into sections at chapter headings
+///
+/// Blank lines separate paragraphs and hard-wrapped lines are joined. A run
+/// of headings without text in between (a table of contents) becomes its own
+/// "Contents" section instead of a series of empty chapters.
+fn build_sections(text: &str) -> Vec<(String, i32, String, Vec<String>)> {
+    let patterns = heading_patterns();
+    let lines: Vec<&str> = text.lines().collect();
+    let blank_lines = lines.iter().filter(|line| line.trim().is_empty()).count();
+    let line_per_paragraph =
+        !lines.is_empty() && (blank_lines as f64 / lines.len() as f64) < MIN_BLANK_LINE_RATIO;
+
+    let blocks: Vec<Vec<&str>> = if line_per_paragraph {
+        lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| vec![line])
+            .collect()
+    } else {
+        lines
+            .split(|line| line.trim().is_empty())
+            .filter(|block| !block.is_empty())
+            .map(|block| block.iter().map(|line| line.trim()).collect())
+            .collect()
+    };
+
+    let mut sections: Vec<(String, i32, String, Vec<String>)> = Vec::new();
+    let mut current_title = LEADING_SECTION_TITLE.to_string();
+    let mut current: Vec<String> = Vec::new();
+    let mut has_body = false;
+
+    let push_section = |sections: &mut Vec<(String, i32, String, Vec<String>)>,
+                        title: &str,
+                        paragraphs: Vec<String>| {
+        let order = sections.len() as i32;
+        sections.push((
+            title.to_string(),
+            order,
+            format!("section{}", order + 1),
+            paragraphs,
+        ));
+    };
+
+    for block in blocks {
+        let heading_lines = block
+            .iter()
+            .filter(|line| is_heading(line, &patterns))
+            .count();
+        let starts_with_heading = is_heading(block[0], &patterns);
+
+        // Several headings in one block is a table of contents
+        if !starts_with_heading || heading_lines > 1 {
+            let paragraphs = if heading_lines > 1 {
+                block.iter().map(|line| line.to_string()).collect()
+            } else {
+                vec![join_wrapped_lines(&block)]
+            };
+            if !has_body && current.len() > 1 {
+                let chapter = current.pop().unwrap_or_default();
+                push_section(
+                    &mut sections,
+                    CONTENTS_SECTION_TITLE,
+                    std::mem::take(&mut current),
+                );
+                current.push(chapter.clone());
+                current_title = chapter;
+            }
+            current.extend(paragraphs);
+            has_body = true;
+            continue;
+        }
+
+        let (heading, rest) = match block.as_slice() {
+            [first, second]
+                if second.chars().count() <= MAX_HEADING_SUBTITLE_CHARS
+                    && !is_heading(second, &patterns) =>
+            {
+                (format!("{} {}", first, second), &block[2..])
+            }
+            [first, rest @ ..] => (first.to_string(), rest),
+            [] => continue,
+        };
+
+        if has_body {
+            push_section(&mut sections, &current_title, std::mem::take(&mut current));
+            has_body = false;
+        }
+        current_title = heading.clone();
+        current.push(heading);
+        if !rest.is_empty() {
+            current.push(join_wrapped_lines(rest));
+            has_body = true;
+        }
+    }
+
+    if !current.is_empty() || sections.is_empty() {
+        if current.is_empty() {
+            current.push(EMPTY_TEXT_PLACEHOLDER.to_string());
+        }
+        push_section(&mut sections, &current_title, current);
+    }
+
+    sections
+}
+
+fn heading_patterns() -> Vec<Regex> {
+    [
+        r"(?i)^(chapter|book|part|volume|canto|letter)\s+([0-9]+|[ivxlcdm]+|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve|thirteen|fourteen|fifteen|sixteen|seventeen|eighteen|nineteen|twenty|first|second|third|fourth|fifth|sixth|seventh|eighth|ninth|tenth|last)\b",
+        r"^第[0-9０-９零〇一二三四五六七八九十百千万两]+[章回节卷部篇集]",
+        r"(?i)^(prologue|epilogue|preface|introduction|foreword|afterword|appendix|conclusion)[.:]?$",
+    ]
+    .iter()
+    .filter_map(|pattern| Regex::new(pattern).ok())
+    .collect()
+}
+
+fn is_heading(line: &str, patterns: &[Regex]) -> bool {
+    line.chars().count() <= MAX_HEADING_CHARS && patterns.iter().any(|p| p.is_match(line))
+}
+
+/// Joins hard-wrapped lines, without a space between CJK characters
+fn join_wrapped_lines(lines: &[&str]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let needs_space = match (out.chars().last(), line.chars().next()) {
+            (Some(prev), Some(next)) => !(is_cjk(prev) && is_cjk(next)),
+            _ => false,
+        };
+        if needs_space {
+            out.push(' ');
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3000}'..='\u{303F}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_sections, decode_text, strip_gutenberg_boilerplate};
+
+    #[test]
+    fn strips_gutenberg_boilerplate_and_splits_chapters() {
+        let text = "The Project Gutenberg eBook of Tiny Tales\n\n\
+Title: Tiny Tales\n\
+Author: A. Writer\n\
+Language: English\n\n\
+*** START OF THE PROJECT GUTENBERG EBOOK TINY TALES ***\n\n\
+Produced by Volunteers\n\n\
+CONTENTS\n\n\
+CHAPTER I. The Start\n\n\
+CHAPTER II. The End\n\n\
+CHAPTER I.\n\
+The Start\n\n\
+It began on a\n\
+quiet morning.\n\n\
+CHAPTER II.\n\
+The End\n\n\
+And so it ended.\n\n\
+*** END OF THE PROJECT GUTENBERG EBOOK TINY TALES ***\n\
+License text.\n";
+
+        let (header, body) = strip_gutenberg_boilerplate(text);
+        assert_eq!(header.title.as_deref(), Some("Tiny Tales"));
+        assert_eq!(header.author.as_deref(), Some("A. Writer"));
+        assert_eq!(header.language.as_deref(), Some("English"));
+
+        let sections = build_sections(body);
+        let titles: Vec<&str> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Content",
+                "Contents",
+                "CHAPTER I. The Start",
+                "CHAPTER II. The End"
+            ]
+        );
+        assert_eq!(
+            sections[1].3,
+            vec!["CHAPTER I. The Start", "CHAPTER II. The End"]
+        );
+        assert_eq!(
+            sections[2].3,
+            vec!["CHAPTER I. The Start", "It began on a quiet morning."]
+        );
+        assert_eq!(sections[3].2, "section4");
+    }
+
+    #[test]
+    fn detects_gbk_and_utf16_text() {
+        // "第一章 开始" in GBK
+        let gbk = [
+            0xB5, 0xDA, 0xD2, 0xBB, 0xD5, 0xC2, 0x20, 0xBF, 0xAA, 0xCA, 0xBC,
+        ];
+        assert_eq!(decode_text(&gbk), "第一章 开始");
+
+        let utf16: Vec<u8> = "Hello, world"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        assert_eq!(decode_text(&utf16), "Hello, world");
+    }
+}
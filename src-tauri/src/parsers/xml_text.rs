@@ -0,0 +1,27 @@
+//! Helpers shared by the quick-xml based parsers
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesRef, BytesStart};
+
+/// Value of the attribute with the given local name, ignoring its namespace
+pub fn attribute(element: &BytesStart, local_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == local_name)
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+/// Appends the text of a character or predefined entity reference
+pub fn push_entity(
+    out: &mut String,
+    entity: &BytesRef,
+) -> std::result::Result<(), quick_xml::Error> {
+    if let Some(ch) = entity.resolve_char_ref()? {
+        out.push(ch);
+    } else if let Some(text) = resolve_predefined_entity(&entity.decode()?) {
+        out.push_str(text);
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Text-quote anchors that tie an annotation to one occurrence of its text
+//!
+//! An anchor records the character range of the selection together with a
+//! little of the text around it. When a paragraph changes, the selection is
+//! looked up again and the occurrence with the most similar surroundings wins,
+//! so a phrase that appears twice keeps pointing at the one the user marked.
+
+use crate::models::TextAnchor;
+
+/// Characters of context stored on each side of the selection
+const CONTEXT_CHARS: usize = 32;
+
+/// Anchors the characters `start..end` of `text`
+///
+/// Returns None for an empty or out-of-range selection.
+pub fn anchor_at(text: &str, start: usize, end: usize) -> Option<TextAnchor> {
+    let chars: Vec<char> = text.chars().collect();
+    if start >= end || end > chars.len() {
+        return None;
+    }
+    Some(TextAnchor {
+        start,
+        end,
+        prefix: chars[start.saturating_sub(CONTEXT_CHARS)..start]
+            .iter()
+            .collect(),
+        suffix: chars[end..(end + CONTEXT_CHARS).min(chars.len())]
+            .iter()
+            .collect(),
+    })
+}
+
+/// Anchors the byte range `start..end` of `text`, e.g. a [`super::TextMatch`]
+pub fn anchor_at_bytes(text: &str, start: usize, end: usize) -> Option<TextAnchor> {
+    let start_char = text.get(..start)?.chars().count();
+    let end_char = start_char + text.get(start..end)?.chars().count();
+    anchor_at(text, start_char, end_char)
+}
+
+/// Finds `exact` in `text` again, preferring the occurrence `previous` pointed at
+///
+/// The stored range is kept when it still holds `exact`. Otherwise every
+/// occurrence is scored by how much of the stored prefix and suffix surrounds
+/// it, and the nearest to the old position breaks ties. Returns None when
+/// `exact` no longer appears in `text`.
+pub fn resolve_anchor(
+    text: &str,
+    exact: &str,
+    previous: Option<&TextAnchor>,
+) -> Option<TextAnchor> {
+    if exact.is_empty() {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let exact_len = exact.chars().count();
+    if let Some(previous) = previous {
+        let unchanged = previous.end == previous.start + exact_len
+            && previous.end <= chars.len()
+            && chars[previous.start..previous.end]
+                .iter()
+                .copied()
+                .eq(exact.chars());
+        if unchanged {
+            return anchor_at(text, previous.start, previous.end);
+        }
+    }
+
+    let starts = text
+        .match_indices(exact)
+        .map(|(byte, _)| text[..byte].chars().count());
+    let best = match previous {
+        None => starts.min(),
+        Some(previous) => {
+            let prefix: Vec<char> = previous.prefix.chars().collect();
+            let suffix: Vec<char> = previous.suffix.chars().collect();
+            starts.max_by_key(|&start| {
+                let end = start + exact_len;
+                let before = chars[..start]
+                    .iter()
+                    .rev()
+                    .zip(prefix.iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let after = chars[end..]
+                    .iter()
+                    .zip(&suffix)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                // Closer to the old position is better on equal context
+                (
+                    before + after,
+                    std::cmp::Reverse(start.abs_diff(previous.start)),
+                )
+            })
+        }
+    }?;
+    anchor_at(text, best, best + exact_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{anchor_at, anchor_at_bytes, resolve_anchor};
+
+    #[test]
+    fn resolves_repeated_phrases_after_edits() {
+        let text = "The cat sat. Later the cat ran away, and the dog followed.";
+        let second = anchor_at(text, 23, 26).unwrap();
+        assert_eq!(second.prefix, "The cat sat. Later the ");
+        assert_eq!(second.suffix, " ran away, and the dog followed.");
+        assert_eq!(anchor_at(text, 5, 5), None);
+        assert_eq!(anchor_at(text, 50, 80), None);
+
+        // Unchanged text keeps the stored range
+        assert_eq!(
+            resolve_anchor(text, "cat", Some(&second)),
+            Some(second.clone())
+        );
+
+        // Text inserted before both occurrences: context picks the second one
+        let edited = "Once upon a time the cat sat. Later the cat ran away, and the dog followed.";
+        let moved = resolve_anchor(edited, "cat", Some(&second)).unwrap();
+        assert_eq!((moved.start, moved.end), (40, 43));
+        assert_eq!(moved.suffix, " ran away, and the dog followed.");
+
+        // Without an anchor the first occurrence is taken
+        let first = resolve_anchor(edited, "cat", None).unwrap();
+        assert_eq!(first.start, 21);
+        assert_eq!(resolve_anchor(edited, "mouse", Some(&second)), None);
+
+        // Offsets count characters, not bytes
+        let accented = "Café — déjà vu, déjà vu";
+        let byte_start = accented.rfind("déjà").unwrap();
+        let anchor = anchor_at_bytes(accented, byte_start, byte_start + "déjà".len()).unwrap();
+        assert_eq!((anchor.start, anchor.end), (16, 20));
+        assert_eq!(anchor.prefix, "Café — déjà vu, ");
+    }
+}
@@ -0,0 +1,466 @@
+//! Approximate text matching for highlights brought in from other apps and
+//! for lining up two versions of a document
+//!
+//! Highlights exported by e-readers rarely match our paragraphs byte for byte:
+//! quotes and dashes differ, hyphenation or OCR noise creeps in, and a
+//! highlight may run across a paragraph break. Text is compared on a folded
+//! form (lower case, plain punctuation, collapsed whitespace), first as an
+//! exact substring and then by shared word pairs.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Share of the highlight's word pairs a paragraph must contain
+const MIN_PAIR_COVERAGE: f32 = 0.6;
+/// For a highlight crossing paragraphs: matched pairs needed at a paragraph edge
+const MIN_EDGE_PAIRS: usize = 4;
+/// Unmatched word pairs tolerated inside a matched run
+const MAX_PAIR_GAP: usize = 3;
+/// Word-pair similarity needed to pair an edited paragraph with its old version
+const MIN_PARAGRAPH_SIMILARITY: f32 = 0.6;
+/// New paragraphs searched on either side of an edited paragraph's expected position
+const ALIGN_WINDOW: usize = 200;
+
+/// Where a highlight sits in the paragraph list
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatch {
+    pub paragraph_index: usize,
+    /// Byte range of the matched text in the original paragraph
+    pub start: usize,
+    pub end: usize,
+    /// 1.0 for an exact (folded) match
+    pub score: f32,
+}
+
+/// Folded text with a map back to byte offsets in the original
+struct FoldedText {
+    text: String,
+    /// Original (start, end) byte range for every byte of `text`
+    origins: Vec<(usize, usize)>,
+}
+
+struct Word {
+    start: usize,
+    end: usize,
+    hash: u64,
+}
+
+struct IndexedParagraph {
+    folded: FoldedText,
+    words: Vec<Word>,
+    pairs: HashSet<u64>,
+}
+
+/// Paragraphs of one document, prepared for repeated lookups
+pub struct ParagraphMatcher {
+    paragraphs: Vec<IndexedParagraph>,
+}
+
+impl ParagraphMatcher {
+    pub fn new<S: AsRef<str>>(paragraphs: &[S]) -> Self {
+        let paragraphs = paragraphs
+            .iter()
+            .map(|text| {
+                let folded = fold(text.as_ref());
+                let words = words(&folded);
+                let pairs = word_pairs(&words).collect();
+                IndexedParagraph {
+                    folded,
+                    words,
+                    pairs,
+                }
+            })
+            .collect();
+        Self { paragraphs }
+    }
+
+    /// Finds the paragraph holding `text`, or the one holding most of it
+    pub fn locate(&self, text: &str) -> Option<TextMatch> {
+        let needle = fold(text);
+        let needle_text = needle.text.trim();
+        if needle_text.is_empty() {
+            return None;
+        }
+
+        for (paragraph_index, paragraph) in self.paragraphs.iter().enumerate() {
+            if let Some(pos) = paragraph.folded.text.find(needle_text) {
+                let origins = &paragraph.folded.origins;
+                return Some(TextMatch {
+                    paragraph_index,
+                    start: origins[pos].0,
+                    end: origins[pos + needle_text.len() - 1].1,
+                    score: 1.0,
+                });
+            }
+        }
+
+        let needle_words = words(&needle);
+        let needle_pairs: HashSet<u64> = word_pairs(&needle_words).collect();
+        if needle_pairs.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<TextMatch> = None;
+        for (paragraph_index, paragraph) in self.paragraphs.iter().enumerate() {
+            let shared = needle_pairs.intersection(&paragraph.pairs).count();
+            if shared == 0
+                || best
+                    .as_ref()
+                    .is_some_and(|b| b.score * needle_pairs.len() as f32 >= shared as f32)
+            {
+                continue;
+            }
+            let Some((first, last, matched)) = densest_run(paragraph, &needle_pairs) else {
+                continue;
+            };
+            let coverage = matched as f32 / needle_pairs.len() as f32;
+            let pair_count = paragraph.words.len().saturating_sub(1);
+            let at_edge = first == 0 || last + 1 >= pair_count;
+            let run_density = matched as f32 / (last - first + 1) as f32;
+            let accepted = coverage >= MIN_PAIR_COVERAGE
+                || (at_edge && matched >= MIN_EDGE_PAIRS && run_density >= 0.8);
+            if !accepted {
+                continue;
+            }
+            let start_word = &paragraph.words[first];
+            let end_word = &paragraph.words[last + 1];
+            best = Some(TextMatch {
+                paragraph_index,
+                start: paragraph.folded.origins[start_word.start].0,
+                end: paragraph.folded.origins[end_word.end - 1].1,
+                score: coverage.min(0.99),
+            });
+        }
+        best
+    }
+}
+
+/// Pairs the paragraphs of an old version of a document with those of a new one
+///
+/// Identical (folded) paragraphs are paired first, in document order where a
+/// text repeats. Each remaining old paragraph is then paired with the most
+/// similar unpaired new paragraph between its paired neighbours, if any is
+/// similar enough. Returns, per old paragraph, the new index and the
+/// similarity (1.0 when identical).
+pub fn align_paragraphs<S: AsRef<str>, T: AsRef<str>>(
+    old: &[S],
+    new: &[T],
+) -> Vec<Option<(usize, f32)>> {
+    let old_folded: Vec<FoldedText> = old.iter().map(|text| fold(text.as_ref())).collect();
+    let new_folded: Vec<FoldedText> = new.iter().map(|text| fold(text.as_ref())).collect();
+
+    let mut by_text: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, folded) in new_folded.iter().enumerate() {
+        by_text.entry(folded.text.as_str()).or_default().push(idx);
+    }
+
+    let mut used = vec![false; new.len()];
+    let mut aligned: Vec<Option<(usize, f32)>> = vec![None; old.len()];
+    let mut cursor = 0;
+    for (old_idx, folded) in old_folded.iter().enumerate() {
+        let Some(candidates) = by_text.get(folded.text.as_str()) else {
+            continue;
+        };
+        let pick = candidates
+            .iter()
+            .copied()
+            .find(|&idx| idx >= cursor && !used[idx])
+            .or_else(|| candidates.iter().copied().find(|&idx| !used[idx]));
+        if let Some(new_idx) = pick {
+            used[new_idx] = true;
+            aligned[old_idx] = Some((new_idx, 1.0));
+            cursor = new_idx + 1;
+        }
+    }
+
+    // Upper bound for each old paragraph: the next identical pairing after it
+    let mut next_paired = vec![new.len(); old.len()];
+    let mut upper = new.len();
+    for old_idx in (0..old.len()).rev() {
+        next_paired[old_idx] = upper;
+        if let Some((new_idx, _)) = aligned[old_idx] {
+            upper = new_idx;
+        }
+    }
+
+    let mut new_pairs: Vec<Option<HashSet<u64>>> = vec![None; new.len()];
+    let mut lower = 0;
+    for old_idx in 0..old.len() {
+        if let Some((new_idx, _)) = aligned[old_idx] {
+            lower = new_idx + 1;
+            continue;
+        }
+        let old_pairs: HashSet<u64> = word_pairs(&words(&old_folded[old_idx])).collect();
+        if old_pairs.is_empty() {
+            continue;
+        }
+        let expected = old_idx * new.len() / old.len().max(1);
+        let start = lower.max(expected.saturating_sub(ALIGN_WINDOW));
+        let end = next_paired[old_idx].min(expected + ALIGN_WINDOW);
+
+        let mut best: Option<(usize, f32)> = None;
+        for new_idx in start..end {
+            if used[new_idx] {
+                continue;
+            }
+            let pairs = new_pairs[new_idx]
+                .get_or_insert_with(|| word_pairs(&words(&new_folded[new_idx])).collect());
+            if pairs.is_empty() {
+                continue;
+            }
+            let shared = old_pairs.intersection(pairs).count();
+            let similarity = 2.0 * shared as f32 / (old_pairs.len() + pairs.len()) as f32;
+            if similarity >= MIN_PARAGRAPH_SIMILARITY
+                && best.is_none_or(|(_, score)| similarity > score)
+            {
+                best = Some((new_idx, similarity));
+            }
+        }
+        if let Some((new_idx, similarity)) = best {
+            used[new_idx] = true;
+            aligned[old_idx] = Some((new_idx, similarity.min(0.99)));
+            lower = new_idx + 1;
+        }
+    }
+    aligned
+}
+
+/// Similarity of two book titles between 0 and 1
+///
+/// Subtitles and bracketed parts ("(Penguin Classics)") are ignored when
+/// that gives a better score.
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let variants = |title: &str| -> Vec<HashSet<String>> {
+        let full = title_tokens(title);
+        let main = title
+            .split([':', '(', '[', '（', '：'])
+            .next()
+            .map(title_tokens)
+            .unwrap_or_default();
+        vec![full, main]
+    };
+    let mut best: f32 = 0.0;
+    for a_tokens in variants(a) {
+        for b_tokens in variants(b) {
+            let larger = a_tokens.len().max(b_tokens.len());
+            if larger == 0 {
+                continue;
+            }
+            let shared = a_tokens.intersection(&b_tokens).count();
+            best = best.max(shared as f32 / larger as f32);
+        }
+    }
+    best
+}
+
+/// Whether two author strings name at least one common person
+pub fn authors_overlap(a: &str, b: &str) -> bool {
+    let a_tokens = title_tokens(a);
+    let b_tokens = title_tokens(b);
+    a_tokens
+        .iter()
+        .any(|token| token.chars().count() > 1 && b_tokens.contains(token))
+}
+
+fn title_tokens(title: &str) -> HashSet<String> {
+    fold(title)
+        .text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty() && !matches!(*token, "the" | "a" | "an"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The stretch of a paragraph's word pairs richest in pairs from the highlight
+///
+/// Returns (first pair index, last pair index, matched pairs).
+fn densest_run(
+    paragraph: &IndexedParagraph,
+    needle_pairs: &HashSet<u64>,
+) -> Option<(usize, usize, usize)> {
+    let hits: Vec<usize> = paragraph
+        .words
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| needle_pairs.contains(&pair_hash(&pair[0], &pair[1])))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut run_start = 0;
+    for idx in 0..hits.len() {
+        if idx > 0 && hits[idx] - hits[idx - 1] > MAX_PAIR_GAP + 1 {
+            run_start = idx;
+        }
+        let matched = idx - run_start + 1;
+        if best.is_none_or(|(_, _, count)| matched > count) {
+            best = Some((hits[run_start], hits[idx], matched));
+        }
+    }
+    best
+}
+
+fn fold(text: &str) -> FoldedText {
+    let mut folded = FoldedText {
+        text: String::with_capacity(text.len()),
+        origins: Vec::with_capacity(text.len()),
+    };
+    let mut pending_space: Option<(usize, usize)> = None;
+    for (start, ch) in text.char_indices() {
+        let end = start + ch.len_utf8();
+        if ch.is_whitespace() {
+            if !folded.text.is_empty() {
+                pending_space.get_or_insert((start, end));
+            }
+            continue;
+        }
+        let replacement: &str = match ch {
+            '\u{00AD}' | '\u{200B}' | '\u{FEFF}' => continue,
+            '‘' | '’' | '‛' | '′' | '`' => "'",
+            '“' | '”' | '„' | '″' | '«' | '»' => "\"",
+            '‐' | '‑' | '‒' | '–' | '—' | '―' => "-",
+            '…' => "...",
+            _ => "",
+        };
+        if let Some(space) = pending_space.take() {
+            folded.text.push(' ');
+            folded.origins.push(space);
+        }
+        if replacement.is_empty() {
+            for lower in ch.to_lowercase() {
+                let before = folded.text.len();
+                folded.text.push(lower);
+                folded.origins.extend(std::iter::repeat_n(
+                    (start, end),
+                    folded.text.len() - before,
+                ));
+            }
+        } else {
+            folded.text.push_str(replacement);
+            folded
+                .origins
+                .extend(std::iter::repeat_n((start, end), replacement.len()));
+        }
+    }
+    folded
+}
+
+/// Alphanumeric runs of folded text, each CJK character counting as a word
+fn words(folded: &FoldedText) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut current: Option<usize> = None;
+    let push = |start: usize, end: usize, words: &mut Vec<Word>| {
+        let mut hasher = DefaultHasher::new();
+        folded.text[start..end].hash(&mut hasher);
+        words.push(Word {
+            start,
+            end,
+            hash: hasher.finish(),
+        });
+    };
+    for (idx, ch) in folded.text.char_indices() {
+        let cjk = is_cjk(ch);
+        if ch.is_alphanumeric() && !cjk {
+            current.get_or_insert(idx);
+            continue;
+        }
+        if let Some(start) = current.take() {
+            push(start, idx, &mut words);
+        }
+        if cjk {
+            push(idx, idx + ch.len_utf8(), &mut words);
+        }
+    }
+    if let Some(start) = current {
+        push(start, folded.text.len(), &mut words);
+    }
+    words
+}
+
+fn word_pairs(words: &[Word]) -> impl Iterator<Item = u64> + '_ {
+    words.windows(2).map(|pair| pair_hash(&pair[0], &pair[1]))
+}
+
+fn pair_hash(first: &Word, second: &Word) -> u64 {
+    first.hash.rotate_left(17) ^ second.hash
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{align_paragraphs, title_similarity, ParagraphMatcher};
+
+    #[test]
+    fn locates_highlights_despite_typography_and_breaks() {
+        let paragraphs = [
+            "Chapter 1",
+            "It was a bright cold day in April, and the clocks were striking thirteen.",
+            "Winston Smith, his chin nuzzled into his breast in an effort to escape the vile wind, slipped quickly through the glass doors of Victory Mansions.",
+        ];
+        let matcher = ParagraphMatcher::new(&paragraphs);
+
+        let exact = matcher
+            .locate("the  Clocks were striking thirteen")
+            .unwrap();
+        assert_eq!(exact.paragraph_index, 1);
+        assert_eq!(
+            &paragraphs[1][exact.start..exact.end],
+            "the clocks were striking thirteen"
+        );
+
+        // Curly quotes and a highlight that runs into the next paragraph
+        let crossing = matcher
+            .locate("and the clocks were striking thirteen. Winston Smith, his chin")
+            .unwrap();
+        assert_eq!(crossing.paragraph_index, 1);
+        assert_eq!(
+            &paragraphs[1][crossing.start..crossing.end],
+            "and the clocks were striking thirteen"
+        );
+
+        assert!(matcher.locate("Completely unrelated words here").is_none());
+        assert!(
+            title_similarity(
+                "Nineteen Eighty-Four (Penguin Modern Classics)",
+                "Nineteen Eighty-Four"
+            ) > 0.99
+        );
+        assert!(title_similarity("It", "It Ends with Us") < 0.5);
+    }
+
+    #[test]
+    fn aligns_edited_and_repeated_paragraphs() {
+        let old = [
+            "Chapter 1",
+            "* * *",
+            "The quick brown fox jumps over the lazy dog near the river bank.",
+            "A paragraph that the new edition removed entirely.",
+            "* * *",
+            "Closing words of the chapter.",
+        ];
+        let new = [
+            "Chapter 1",
+            "A new foreword paragraph.",
+            "* * *",
+            "The quick brown fox leaps over the lazy dog near the river bank.",
+            "* * *",
+            "Closing words of the chapter.",
+        ];
+        let aligned = align_paragraphs(&old, &new);
+
+        assert_eq!(aligned[0], Some((0, 1.0)));
+        // Repeated separators keep their order
+        assert_eq!(aligned[1], Some((2, 1.0)));
+        assert_eq!(aligned[4], Some((4, 1.0)));
+        let (edited, similarity) = aligned[2].unwrap();
+        assert_eq!(edited, 3);
+        assert!(similarity > 0.6 && similarity < 1.0);
+        assert_eq!(aligned[3], None);
+        assert_eq!(aligned[5], Some((5, 1.0)));
+    }
+}
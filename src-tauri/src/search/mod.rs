@@ -1,6 +1,13 @@
-use crate::database::{embeddings, get_connection, paragraphs};
+mod anchor;
+mod fuzzy;
+
+pub use anchor::{anchor_at, anchor_at_bytes, resolve_anchor};
+pub use fuzzy::{align_paragraphs, authors_overlap, title_similarity, ParagraphMatcher, TextMatch};
+
+use crate::database::{self, embeddings, get_connection, paragraphs};
 use crate::error::{ReaderError, Result};
 use crate::llm::AiClient;
+use crate::models::DocumentFilter;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -59,6 +66,14 @@ pub struct SearchOptions {
     #[serde(default)]
     pub doc_id: Option<String>,
 
+    /// Optional collection to restrict search to; ignored when `doc_id` is set
+    #[serde(default)]
+    pub collection_id: Option<String>,
+
+    /// Optional tag name to restrict search to; ignored when `doc_id` is set
+    #[serde(default)]
+    pub tag: Option<String>,
+
     /// Force keyword-only search path (skip semantic embedding generation)
     #[serde(default)]
     pub force_keyword: bool,
@@ -68,6 +83,63 @@ fn default_top_k() -> usize {
     10
 }
 
+impl SearchOptions {
+    /// Ids of the documents the search is restricted to, or None for the whole library
+    pub fn scope_doc_ids(&self, conn: &Connection) -> Result<Option<Vec<String>>> {
+        resolve_scope(
+            conn,
+            self.doc_id.as_deref(),
+            self.collection_id.as_deref(),
+            self.tag.as_deref(),
+        )
+    }
+}
+
+/// Resolves a search scope to document ids; a document id wins over a collection or tag
+///
+/// Returns None when nothing restricts the search.
+pub fn resolve_scope(
+    conn: &Connection,
+    doc_id: Option<&str>,
+    collection_id: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Option<Vec<String>>> {
+    if let Some(doc_id) = doc_id {
+        return Ok(Some(vec![doc_id.to_string()]));
+    }
+    if collection_id.is_none() && tag.is_none() {
+        return Ok(None);
+    }
+    let filter = DocumentFilter {
+        tag: tag.map(str::to_string),
+        collection_id: collection_id.map(str::to_string),
+        series: None,
+    };
+    Ok(Some(
+        database::list_documents_filtered(conn, &filter)?
+            .into_iter()
+            .map(|doc| doc.id)
+            .collect(),
+    ))
+}
+
+/// Stored embeddings for the documents in `scope`, or for the whole library
+pub fn scoped_embeddings(
+    conn: &Connection,
+    scope: Option<&[String]>,
+) -> Result<Vec<embeddings::Embedding>> {
+    match scope {
+        Some(doc_ids) => {
+            let mut all = Vec::new();
+            for doc_id in doc_ids {
+                all.extend(embeddings::list_by_document(conn, doc_id)?);
+            }
+            Ok(all)
+        }
+        None => Ok(embeddings::list_all_vectors(conn)?),
+    }
+}
+
 /// Performs semantic search using embeddings
 ///
 /// 1. Generates an embedding for the query text
@@ -82,42 +154,24 @@ pub async fn semantic_search(
     // Generate embedding for the query
     let query_embedding = llm_client.generate_embedding(&options.query).await?;
 
-    // Get all embeddings (optionally filtered by document)
-    let embeddings: Vec<(String, Vec<f32>)> = if let Some(doc_id) = &options.doc_id {
-        embeddings::list_by_document(conn, doc_id)?
-            .into_iter()
-            .filter_map(|emb| {
-                if emb.vector.len() == query_embedding.len() {
-                    Some((emb.paragraph_id, emb.vector))
-                } else {
-                    tracing::warn!(
-                        "Embedding dimension mismatch for paragraph {}: expected {}, got {}",
-                        emb.paragraph_id,
-                        query_embedding.len(),
-                        emb.vector.len()
-                    );
-                    None
-                }
-            })
-            .collect()
-    } else {
-        embeddings::list_all_vectors(conn)?
-            .into_iter()
-            .filter_map(|emb| {
-                if emb.vector.len() == query_embedding.len() {
-                    Some((emb.paragraph_id, emb.vector))
-                } else {
-                    tracing::warn!(
-                        "Embedding dimension mismatch for paragraph {}: expected {}, got {}",
-                        emb.paragraph_id,
-                        query_embedding.len(),
-                        emb.vector.len()
-                    );
-                    None
-                }
-            })
-            .collect()
-    };
+    // Get all embeddings (optionally restricted to a document, collection or tag)
+    let scope = options.scope_doc_ids(conn)?;
+    let embeddings: Vec<(String, Vec<f32>)> = scoped_embeddings(conn, scope.as_deref())?
+        .into_iter()
+        .filter_map(|emb| {
+            if emb.vector.len() == query_embedding.len() {
+                Some((emb.paragraph_id, emb.vector))
+            } else {
+                tracing::warn!(
+                    "Embedding dimension mismatch for paragraph {}: expected {}, got {}",
+                    emb.paragraph_id,
+                    query_embedding.len(),
+                    emb.vector.len()
+                );
+                None
+            }
+        })
+        .collect();
 
     if embeddings.is_empty() {
         return Ok(Vec::new());
@@ -244,4 +298,33 @@ mod tests {
         let b: Vec<f32> = vec![];
         assert!(cosine_similarity(&a, &b).is_err());
     }
+
+    #[test]
+    fn test_resolve_scope_to_collection_or_tag() {
+        let conn = database::open_in_memory();
+        let dune = database::insert_test_document(&conn, "dune");
+        let emma = database::insert_test_document(&conn, "emma");
+        database::insert_test_document(&conn, "ubik");
+        database::add_document_tags(&conn, &emma, &["Classics".to_string()]).unwrap();
+        let shelf = database::insert_collection(&conn, "Shelf", None).unwrap();
+        database::add_to_collection(&conn, &shelf.id, std::slice::from_ref(&dune)).unwrap();
+
+        assert_eq!(resolve_scope(&conn, None, None, None).unwrap(), None);
+        assert_eq!(
+            resolve_scope(&conn, None, Some(&shelf.id), None).unwrap(),
+            Some(vec![dune.clone()])
+        );
+        assert_eq!(
+            resolve_scope(&conn, None, None, Some("classics")).unwrap(),
+            Some(vec![emma.clone()])
+        );
+        assert_eq!(
+            resolve_scope(&conn, None, Some(&shelf.id), Some("Classics")).unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(
+            resolve_scope(&conn, Some(&emma), Some(&shelf.id), None).unwrap(),
+            Some(vec![emma])
+        );
+    }
 }
@@ -4,9 +4,11 @@ import { Settings } from './components/Settings';
 import { useStore } from './store/useStore';
 import { useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { getEmbeddingStatus, indexDocumentWithLocalEmbedding, type EmbeddingProfile } from './services/embeddingIndex';
 import { matchesAnyShortcut } from './utils/shortcuts';
+import type { DeepLinkTarget } from './types';
 
 const MIN_FONT_SIZE = 14;
 const MAX_FONT_SIZE = 28;
@@ -143,6 +145,61 @@ function App() {
     void runAutoIndexForCurrentDocument();
   }, [selectedDocumentId]);
 
+  useEffect(() => {
+    // com.mac.reader://open links from exported notes
+    const openTarget = async ({ doc_id, section_id, paragraph_id }: DeepLinkTarget) => {
+      const store = useStore.getState();
+      if (!store.documents.some((doc) => doc.id === doc_id)) {
+        await store.loadDocuments();
+      }
+      const targetDoc = useStore.getState().documents.find((doc) => doc.id === doc_id);
+      if (!targetDoc) {
+        alert('The linked document is no longer in the library.');
+        return;
+      }
+      const markdownTarget = targetDoc.file_type === 'markdown';
+      setShowSettings(false);
+      if (useStore.getState().selectedDocumentId !== doc_id) {
+        store.selectDocument(doc_id);
+        await store.loadSections(doc_id);
+        if (markdownTarget) {
+          await store.loadDocumentParagraphs(doc_id);
+        }
+      }
+      const sectionId = section_id ?? useStore.getState().sections[0]?.id;
+      if (!sectionId) return;
+      store.selectSection(sectionId);
+      if (!markdownTarget) {
+        await store.loadParagraphs(sectionId);
+      }
+      if (paragraph_id) {
+        store.setFocusedParagraphId(paragraph_id);
+      }
+    };
+
+    let unlisten: (() => void) | null = null;
+    let disposed = false;
+    const open = (target: DeepLinkTarget) => {
+      openTarget(target).catch((error) => console.error('Failed to open deep link:', error));
+    };
+    void listen<DeepLinkTarget>('deep-link-open', (event) => open(event.payload)).then(async (stop) => {
+      if (disposed) {
+        stop();
+        return;
+      }
+      unlisten = stop;
+      // A link that launched the app arrived before this listener
+      const launchTarget = await invoke<DeepLinkTarget | null>('take_launch_deep_link');
+      if (launchTarget && !disposed) {
+        open(launchTarget);
+      }
+    }).catch((error) => console.error('Failed to read launch deep link:', error));
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, []);
+
   useEffect(() => {
     const onKeyDown = async (event: KeyboardEvent) => {
       if (isEditableTarget(event.target)) return;
@@ -1,18 +1,32 @@
 import { useEffect, useMemo, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useStore } from '../store/useStore';
-import type { Annotation, AnnotationStyle } from '../types';
+import { NotesExportDialog } from './NotesExportDialog';
+import type { Annotation, AnnotationColor, AnnotationStyle, NotesImportResult } from '../types';
+import {
+  ANNOTATION_COLORS,
+  ANNOTATION_STYLES,
+  annotationStyleLabel,
+  annotationSwatchClass,
+  parseTags,
+} from './annotationStyles';
 
-const annotationStyleLabel: Record<AnnotationStyle, string> = {
-  single_underline: 'Single Underline',
-  double_underline: 'Double Underline',
-  wavy_strikethrough: 'Wavy Strikethrough',
+type AnnotationEdit = {
+  id: string;
+  style: AnnotationStyle;
+  color: AnnotationColor | null;
+  note: string;
+  tags: string;
 };
 
 export function AnnotationPanel() {
-  const { paragraphs, setFocusedParagraphId } = useStore();
+  const { paragraphs, setFocusedParagraphId, selectedDocumentId, documents } = useStore();
   const [annotations, setAnnotations] = useState<Annotation[]>([]);
   const [isLoading, setIsLoading] = useState(false);
+  const [editing, setEditing] = useState<AnnotationEdit | null>(null);
+  const [tagFilter, setTagFilter] = useState('');
+  const [showExport, setShowExport] = useState(false);
+  const [copiedId, setCopiedId] = useState<string | null>(null);
 
   const paragraphIds = useMemo(() => paragraphs.map((item) => item.id), [paragraphs]);
   const paragraphIdsKey = useMemo(() => paragraphIds.join('|'), [paragraphIds]);
@@ -56,6 +70,78 @@ export function AnnotationPanel() {
     }
   };
 
+  const startEdit = (item: Annotation) =>
+    setEditing({
+      id: item.id,
+      style: item.style,
+      color: item.color ?? null,
+      note: item.note ?? '',
+      tags: item.tags.join(', '),
+    });
+
+  const handleSaveEdit = async () => {
+    if (!editing) return;
+    try {
+      const updated = await invoke<Annotation>('update_annotation', {
+        id: editing.id,
+        style: editing.style,
+        color: editing.color ?? '',
+        note: editing.note,
+        tags: parseTags(editing.tags),
+      });
+      setAnnotations((prev) => prev.map((item) => (item.id === updated.id ? updated : item)));
+      setEditing(null);
+      window.dispatchEvent(new CustomEvent('reader:annotations-changed'));
+    } catch (error) {
+      console.error('Failed to update annotation:', error);
+    }
+  };
+
+  const handleCopyWebAnnotation = async (id: string) => {
+    try {
+      const annotation = await invoke<unknown>('get_web_annotation', { id });
+      await navigator.clipboard.writeText(JSON.stringify(annotation, null, 2));
+      setCopiedId(id);
+      window.setTimeout(() => setCopiedId((current) => (current === id ? null : current)), 1500);
+    } catch (error) {
+      console.error('Failed to copy Web Annotation:', error);
+    }
+  };
+
+  // W3C Web Annotations copied from another tool, e.g. Hypothesis
+  const handlePasteWebAnnotations = async () => {
+    if (!selectedDocumentId) return;
+    try {
+      const content = await navigator.clipboard.readText();
+      const result = await invoke<NotesImportResult>('import_web_annotations', {
+        content,
+        docId: selectedDocumentId,
+      });
+      const lines = [`Imported ${result.annotations} annotation(s).`];
+      if (result.skipped_duplicates > 0) {
+        lines.push(`Skipped ${result.skipped_duplicates} already here.`);
+      }
+      if (result.unplaced.length > 0) {
+        lines.push(`${result.unplaced.length} could not be found in this document.`);
+      }
+      alert(lines.join('\n'));
+      await loadAnnotations();
+      window.dispatchEvent(new CustomEvent('reader:annotations-changed'));
+    } catch (error) {
+      console.error('Failed to import Web Annotations:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      alert(`Failed to import Web Annotations: ${message}`);
+    }
+  };
+
+  const allTags = useMemo(
+    () => Array.from(new Set(annotations.flatMap((item) => item.tags))).sort((a, b) => a.localeCompare(b)),
+    [annotations]
+  );
+  const visibleAnnotations = tagFilter
+    ? annotations.filter((item) => item.tags.some((tag) => tag.toLowerCase() === tagFilter.toLowerCase()))
+    : annotations;
+
   if (isLoading) {
     return (
       <div className="p-4 text-sm text-gray-500">Loading annotations...</div>
@@ -64,18 +150,55 @@ export function AnnotationPanel() {
 
   return (
     <div className="p-4">
-      <div className="mb-3 flex items-center justify-between">
+      {showExport && (
+        <NotesExportDialog
+          docId={selectedDocumentId}
+          docTitle={documents.find((doc) => doc.id === selectedDocumentId)?.title}
+          onClose={() => setShowExport(false)}
+        />
+      )}
+      <div className="mb-3 flex items-center gap-2">
         <h3 className="text-sm font-semibold text-gray-800">Annotations & Highlights</h3>
-        <span className="rounded bg-gray-100 px-2 py-0.5 text-xs text-gray-600">{annotations.length}</span>
+        <span className="rounded bg-gray-100 px-2 py-0.5 text-xs text-gray-600">{visibleAnnotations.length}</span>
+        <button
+          onClick={() => setShowExport(true)}
+          className="ml-auto text-xs text-blue-600 underline-offset-2 hover:underline"
+          title="Export annotations and bookmarks to Markdown, Obsidian, JSON or W3C Web Annotations"
+        >
+          Export
+        </button>
+        <button
+          onClick={() => void handlePasteWebAnnotations()}
+          disabled={!selectedDocumentId}
+          className="text-xs text-blue-600 underline-offset-2 hover:underline disabled:text-gray-400"
+          title="Import W3C Web Annotations (JSON-LD) from the clipboard into this document"
+        >
+          Paste W3C
+        </button>
       </div>
+      {allTags.length > 0 && (
+        <select
+          value={tagFilter}
+          onChange={(e) => setTagFilter(e.target.value)}
+          className="mb-3 w-full rounded border border-gray-300 px-2 py-1 text-xs text-gray-700"
+        >
+          <option value="">All tags</option>
+          {allTags.map((tag) => (
+            <option key={tag} value={tag}>
+              #{tag}
+            </option>
+          ))}
+        </select>
+      )}
 
       {annotations.length === 0 ? (
         <p className="text-sm text-gray-500">No annotations yet. Select text to create one.</p>
       ) : (
         <div className="space-y-2">
-          {annotations.map((item) => (
+          {visibleAnnotations.map((item) => (
             <div key={item.id} className="rounded border border-gray-200 bg-gray-50 px-3 py-2">
               <div className="mb-1 flex items-center gap-2">
+                {item.color && <span className={`h-3 w-3 rounded-full ${annotationSwatchClass(item.color)}`} title={item.color} />}
                 <span className="rounded bg-white px-2 py-0.5 text-xs text-gray-700">
                   {annotationStyleLabel[item.style]}
                 </span>
@@ -85,16 +208,96 @@ export function AnnotationPanel() {
                 >
                   Go to Location
                 </button>
+                <button
+                  onClick={() => void handleCopyWebAnnotation(item.id)}
+                  className="ml-auto text-xs text-gray-600 underline-offset-2 hover:underline"
+                  title="Copy as a W3C Web Annotation (JSON-LD)"
+                >
+                  {copiedId === item.id ? 'Copied' : 'Copy W3C'}
+                </button>
+                <button
+                  onClick={() => startEdit(item)}
+                  className="text-xs text-gray-600 underline-offset-2 hover:underline"
+                >
+                  Edit
+                </button>
                 <button
                   onClick={() => void handleDelete(item.id)}
-                  className="ml-auto text-xs text-rose-600 underline-offset-2 hover:underline"
+                  className="text-xs text-rose-600 underline-offset-2 hover:underline"
                 >
                   Delete
                 </button>
               </div>
               <p className="text-sm text-gray-800">"{item.selected_text}"</p>
-              {item.note && item.note.trim().length > 0 && (
-                <p className="mt-1 text-xs text-amber-800">Note: {item.note}</p>
+              {editing?.id === item.id ? (
+                <div className="mt-2 space-y-2">
+                  <select
+                    value={editing.style}
+                    onChange={(e) => setEditing({ ...editing, style: e.target.value as AnnotationStyle })}
+                    className="w-full rounded border border-gray-300 px-2 py-1 text-xs"
+                  >
+                    {ANNOTATION_STYLES.map((style) => (
+                      <option key={style} value={style}>
+                        {annotationStyleLabel[style]}
+                      </option>
+                    ))}
+                  </select>
+                  <div className="flex items-center gap-1.5">
+                    {ANNOTATION_COLORS.map((color) => (
+                      <button
+                        key={color}
+                        onClick={() => setEditing({ ...editing, color: editing.color === color ? null : color })}
+                        className={`h-5 w-5 rounded-full ${annotationSwatchClass(color)} ${
+                          editing.color === color ? 'ring-2 ring-blue-500 ring-offset-1' : ''
+                        }`}
+                        aria-label={`Colour ${color}`}
+                        title={color}
+                      />
+                    ))}
+                  </div>
+                  <textarea
+                    value={editing.note}
+                    onChange={(e) => setEditing({ ...editing, note: e.target.value })}
+                    placeholder="Note"
+                    rows={2}
+                    className="w-full resize-none rounded border border-gray-300 px-2 py-1 text-xs focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                  <input
+                    value={editing.tags}
+                    onChange={(e) => setEditing({ ...editing, tags: e.target.value })}
+                    placeholder="Tags, comma separated"
+                    className="w-full rounded border border-gray-300 px-2 py-1 text-xs focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                  <div className="flex justify-end gap-2">
+                    <button
+                      onClick={() => setEditing(null)}
+                      className="rounded border border-gray-300 px-2 py-1 text-xs text-gray-700"
+                    >
+                      Cancel
+                    </button>
+                    <button
+                      onClick={() => void handleSaveEdit()}
+                      className="rounded bg-blue-600 px-2 py-1 text-xs text-white hover:bg-blue-700"
+                    >
+                      Save
+                    </button>
+                  </div>
+                </div>
+              ) : (
+                <>
+                  {item.note && item.note.trim().length > 0 && (
+                    <p className="mt-1 text-xs text-amber-800">Note: {item.note}</p>
+                  )}
+                  {item.tags.length > 0 && (
+                    <div className="mt-1 flex flex-wrap gap-1">
+                      {item.tags.map((tag) => (
+                        <span key={tag} className="rounded bg-slate-100 px-1 py-0.5 text-[10px] text-slate-600">
+                          #{tag}
+                        </span>
+                      ))}
+                    </div>
+                  )}
+                </>
               )}
             </div>
           ))}
@@ -0,0 +1,204 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useStore } from '../store/useStore';
+import type { Bookmark, BookmarkColor, ReadingPosition } from '../types';
+
+const BOOKMARK_COLORS: Record<BookmarkColor, string> = {
+  red: 'bg-red-400',
+  orange: 'bg-orange-400',
+  yellow: 'bg-yellow-300',
+  green: 'bg-green-400',
+  blue: 'bg-blue-400',
+  purple: 'bg-purple-400',
+};
+
+export function BookmarksPanel() {
+  const {
+    documents,
+    selectedDocumentId,
+    paragraphs,
+    selectDocument,
+    loadSections,
+    loadDocumentParagraphs,
+    selectSection,
+    loadParagraphs,
+    setFocusedParagraphId,
+  } = useStore();
+  const [bookmarks, setBookmarks] = useState<Bookmark[]>([]);
+  const [showAll, setShowAll] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadBookmarks = async () => {
+    if (!showAll && !selectedDocumentId) {
+      setBookmarks([]);
+      return;
+    }
+    try {
+      setBookmarks(
+        await invoke<Bookmark[]>('list_bookmarks', { docId: showAll ? null : selectedDocumentId })
+      );
+    } catch (err) {
+      console.error('Failed to load bookmarks:', err);
+    }
+  };
+
+  useEffect(() => {
+    void loadBookmarks();
+  }, [selectedDocumentId, showAll]);
+
+  useEffect(() => {
+    const onChanged = () => void loadBookmarks();
+    window.addEventListener('reader:bookmarks-changed', onChanged as EventListener);
+    return () => window.removeEventListener('reader:bookmarks-changed', onChanged as EventListener);
+  }, [selectedDocumentId, showAll]);
+
+  const notifyChanged = () => window.dispatchEvent(new CustomEvent('reader:bookmarks-changed'));
+
+  const handleAdd = async () => {
+    if (!selectedDocumentId) return;
+    setError(null);
+    try {
+      // Bookmark the paragraph at the top of the view, as last saved
+      const position = await invoke<ReadingPosition | null>('get_reading_position', {
+        docId: selectedDocumentId,
+      });
+      const paragraphId =
+        paragraphs.find((item) => item.id === position?.paragraph_id)?.id ?? paragraphs[0]?.id;
+      if (!paragraphId) return;
+      await invoke<Bookmark>('create_bookmark', { paragraphId });
+      notifyChanged();
+    } catch (err) {
+      setError(`Failed to add bookmark: ${err instanceof Error ? err.message : String(err)}`);
+    }
+  };
+
+  const handleUpdate = async (
+    bookmark: Bookmark,
+    changes: { title?: string; note?: string; color?: BookmarkColor | '' }
+  ) => {
+    setError(null);
+    try {
+      const updated = await invoke<Bookmark>('update_bookmark', { id: bookmark.id, ...changes });
+      setBookmarks((prev) => prev.map((item) => (item.id === updated.id ? updated : item)));
+    } catch (err) {
+      setError(`Failed to update bookmark: ${err instanceof Error ? err.message : String(err)}`);
+    }
+  };
+
+  const handleDelete = async (id: string) => {
+    try {
+      await invoke('delete_bookmark', { id });
+      setBookmarks((prev) => prev.filter((item) => item.id !== id));
+      notifyChanged();
+    } catch (err) {
+      console.error('Failed to delete bookmark:', err);
+    }
+  };
+
+  const handleOpen = async (bookmark: Bookmark) => {
+    try {
+      const targetDocType = documents.find((doc) => doc.id === bookmark.doc_id)?.file_type;
+      const markdownTarget = targetDocType === 'markdown';
+      if (selectedDocumentId !== bookmark.doc_id) {
+        selectDocument(bookmark.doc_id);
+        await loadSections(bookmark.doc_id);
+        if (markdownTarget) {
+          await loadDocumentParagraphs(bookmark.doc_id);
+        }
+      }
+      selectSection(bookmark.section_id);
+      if (!markdownTarget) {
+        await loadParagraphs(bookmark.section_id);
+      }
+      setFocusedParagraphId(bookmark.paragraph_id);
+    } catch (err) {
+      console.error('Failed to open bookmark:', err);
+    }
+  };
+
+  return (
+    <div className="p-4">
+      <div className="mb-3 flex items-center gap-2">
+        <h3 className="text-sm font-semibold text-gray-800">Bookmarks</h3>
+        <span className="rounded bg-gray-100 px-2 py-0.5 text-xs text-gray-600">{bookmarks.length}</span>
+        <label className="ml-auto flex items-center gap-1 text-xs text-gray-600">
+          <input type="checkbox" checked={showAll} onChange={(e) => setShowAll(e.target.checked)} />
+          All documents
+        </label>
+        <button
+          onClick={() => void handleAdd()}
+          disabled={!selectedDocumentId || paragraphs.length === 0}
+          className="px-2 py-1 text-xs border border-gray-300 rounded hover:bg-gray-50 disabled:bg-gray-100 disabled:text-gray-400"
+        >
+          Add here
+        </button>
+      </div>
+      {error && <p className="mb-2 text-xs text-red-600">{error}</p>}
+
+      {bookmarks.length === 0 ? (
+        <p className="text-sm text-gray-500">No bookmarks yet.</p>
+      ) : (
+        <div className="space-y-2">
+          {bookmarks.map((item) => (
+            <div key={item.id} className="rounded border border-gray-200 bg-gray-50 px-3 py-2">
+              <div className="flex items-center gap-2">
+                <span
+                  className={`h-2.5 w-2.5 shrink-0 rounded-full ${item.color ? BOOKMARK_COLORS[item.color] : 'bg-gray-300'}`}
+                />
+                <input
+                  key={`${item.id}-${item.updated_at}`}
+                  defaultValue={item.title}
+                  onBlur={(e) => {
+                    const title = e.target.value.trim();
+                    if (title && title !== item.title) void handleUpdate(item, { title });
+                  }}
+                  className="min-w-0 flex-1 bg-transparent text-sm font-medium text-gray-900 focus:outline-none focus:ring-1 focus:ring-blue-500 rounded px-1"
+                />
+              </div>
+              <div className="mt-0.5 text-[11px] text-gray-500 truncate">
+                {showAll ? `${item.document_title} · ` : ''}
+                {item.section_title}
+              </div>
+              <textarea
+                key={`${item.id}-note-${item.updated_at}`}
+                rows={2}
+                defaultValue={item.note ?? ''}
+                onBlur={(e) => {
+                  if (e.target.value.trim() !== (item.note ?? '')) {
+                    void handleUpdate(item, { note: e.target.value });
+                  }
+                }}
+                placeholder="Add a note..."
+                className="mt-1 w-full resize-none border border-gray-200 rounded px-2 py-1 text-xs focus:outline-none focus:ring-1 focus:ring-blue-500"
+              />
+              <div className="mt-1 flex items-center gap-1.5">
+                {(Object.keys(BOOKMARK_COLORS) as BookmarkColor[]).map((color) => (
+                  <button
+                    key={color}
+                    onClick={() => void handleUpdate(item, { color: item.color === color ? '' : color })}
+                    className={`h-3.5 w-3.5 rounded-full ${BOOKMARK_COLORS[color]} ${
+                      item.color === color ? 'ring-2 ring-offset-1 ring-gray-500' : ''
+                    }`}
+                    title={color}
+                  />
+                ))}
+                <button
+                  onClick={() => void handleOpen(item)}
+                  className="ml-auto text-xs text-blue-600 underline-offset-2 hover:underline"
+                >
+                  Go to Location
+                </button>
+                <button
+                  onClick={() => void handleDelete(item.id)}
+                  className="text-xs text-rose-600 underline-offset-2 hover:underline"
+                >
+                  Delete
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,58 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { Document, RecentlyRead } from '../types';
+
+interface ContinueReadingProps {
+  documents: Document[];
+  onOpen: (docId: string) => void;
+}
+
+const RECENT_LIMIT = 6;
+
+const formatReadAt = (timestamp: number) => {
+  const minutes = Math.floor((Date.now() / 1000 - timestamp) / 60);
+  if (minutes < 1) return 'Just now';
+  if (minutes < 60) return `${minutes} min ago`;
+  if (minutes < 60 * 24) return `${Math.floor(minutes / 60)} h ago`;
+  return new Date(timestamp * 1000).toLocaleDateString();
+};
+
+export const ContinueReading: React.FC<ContinueReadingProps> = ({ documents, onOpen }) => {
+  const [recent, setRecent] = useState<RecentlyRead[]>([]);
+
+  // Reload when the library changes so deleted documents drop out
+  useEffect(() => {
+    let cancelled = false;
+    invoke<RecentlyRead[]>('list_recently_read', { limit: RECENT_LIMIT })
+      .then((items) => {
+        if (!cancelled) setRecent(items);
+      })
+      .catch((error) => console.error('Failed to load recently read documents:', error));
+    return () => {
+      cancelled = true;
+    };
+  }, [documents]);
+
+  if (recent.length === 0) return null;
+
+  return (
+    <div className="px-6 pt-4">
+      <h3 className="mb-2 text-sm font-semibold text-gray-800">Continue reading</h3>
+      <div className="flex gap-3 overflow-x-auto pb-1">
+        {recent.map(({ document, position, section_title }) => (
+          <button
+            key={document.id}
+            onClick={() => onOpen(document.id)}
+            className="w-56 shrink-0 rounded-md border border-gray-200 bg-white px-3 py-2 text-left hover:border-blue-300 hover:bg-blue-50 transition-colors"
+          >
+            <div className="text-sm font-medium text-gray-900 truncate" title={document.title}>
+              {document.title}
+            </div>
+            <div className="text-xs text-gray-500 truncate">{section_title || document.author || '\u00a0'}</div>
+            <div className="mt-1 text-[11px] text-gray-400">{formatReadAt(position.updated_at)}</div>
+          </button>
+        ))}
+      </div>
+    </div>
+  );
+};
@@ -5,6 +5,8 @@ interface DocumentCardProps {
   document: Document;
   onClick: () => void;
   onDelete: () => void;
+  onReimport?: () => void;
+  onOrganize?: () => void;
   variant?: 'grid' | 'list' | 'compact';
   category?: string;
   tags?: string[];
@@ -14,6 +16,8 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
   document,
   onClick,
   onDelete,
+  onReimport,
+  onOrganize,
   variant = 'grid',
   category,
   tags = [],
@@ -21,15 +25,64 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
   const getFileTypeIcon = () => {
     if (document.file_type === 'epub') return '📚';
     if (document.file_type === 'markdown') return '📝';
+    if (document.file_type === 'docx') return '📃';
+    if (document.file_type === 'html') return '🌐';
+    if (document.file_type === 'text') return '🗒️';
+    if (document.file_type === 'fb2') return '📖';
+    if (document.file_type === 'mobi') return '📙';
     return '📄';
   };
 
   const getFileTypeLabel = () => {
     if (document.file_type === 'epub') return 'EPUB';
     if (document.file_type === 'markdown') return 'Markdown';
+    if (document.file_type === 'docx') return 'Word';
+    if (document.file_type === 'html') return 'Web Page';
+    if (document.file_type === 'text') return 'Text';
+    if (document.file_type === 'fb2') return 'FB2';
+    if (document.file_type === 'mobi') return 'Kindle';
     return 'PDF';
   };
 
+  const renderReimportButton = (iconClass: string) =>
+    onReimport && (
+      <button
+        onClick={(e) => {
+          e.stopPropagation();
+          onReimport();
+        }}
+        className="text-gray-400 hover:text-blue-500 transition-colors p-1"
+        aria-label="Re-import document"
+        title="Re-import from file, keeping annotations"
+      >
+        <svg xmlns="http://www.w3.org/2000/svg" className={iconClass} fill="none" viewBox="0 0 24 24" stroke="currentColor">
+          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 4v5h.582m15.356 2A8.001 8.001 0 004.582 9m0 0H9m11 11v-5h-.581m0 0a8.003 8.003 0 01-15.357-2m15.357 2H15" />
+        </svg>
+      </button>
+    );
+
+  const renderOrganizeButton = (iconClass: string) =>
+    onOrganize && (
+      <button
+        onClick={(e) => {
+          e.stopPropagation();
+          onOrganize();
+        }}
+        className="text-gray-400 hover:text-blue-500 transition-colors p-1"
+        aria-label="Organize document"
+        title="Details, tags, series and collections"
+      >
+        <svg xmlns="http://www.w3.org/2000/svg" className={iconClass} fill="none" viewBox="0 0 24 24" stroke="currentColor">
+          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M7 7h.01M7 3h5c.512 0 1.024.195 1.414.586l7 7a2 2 0 010 2.828l-7 7a2 2 0 01-2.828 0l-7-7A1.994 1.994 0 013 12V7a4 4 0 014-4z" />
+        </svg>
+      </button>
+    );
+
+  const ratingLabel = document.rating ? '★'.repeat(document.rating) : '';
+  const seriesLabel = document.series
+    ? `${document.series}${document.series_index != null ? ` #${document.series_index}` : ''}`
+    : null;
+
   const formatDate = (timestamp: number) => {
     return new Date(timestamp * 1000).toLocaleDateString();
   };
@@ -45,6 +98,8 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
           <p className="flex-1 min-w-0 text-xs font-medium text-gray-900 truncate">{document.title}</p>
           {category && <span className="text-[11px] text-blue-700 bg-blue-50 rounded px-1.5 py-0.5">{category}</span>}
           <span className="text-[11px] text-gray-500">{getFileTypeLabel()}</span>
+          {renderOrganizeButton('h-4 w-4')}
+          {renderReimportButton('h-4 w-4')}
           <button
             onClick={(e) => {
               e.stopPropagation();
@@ -77,7 +132,9 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
               <span className="text-[11px] text-gray-600 bg-gray-100 rounded px-2 py-0.5">{getFileTypeLabel()}</span>
             </div>
             <p className="text-[11px] text-gray-500 mt-0.5">
-              {document.author ? `${document.author} · ` : ''}Added {formatDate(document.created_at)}
+              {document.author ? `${document.author} · ` : ''}
+              {seriesLabel ? `${seriesLabel} · ` : ''}
+              {ratingLabel ? `${ratingLabel} · ` : ''}Added {formatDate(document.created_at)}
             </p>
             {tags.length > 0 && (
               <div className="mt-1 flex flex-wrap gap-1">
@@ -89,6 +146,8 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
               </div>
             )}
           </div>
+          {renderOrganizeButton('h-5 w-5')}
+        {renderReimportButton('h-5 w-5')}
           <button
             onClick={(e) => {
               e.stopPropagation();
@@ -120,7 +179,9 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
             {document.author && (
               <p className="text-xs text-gray-600 truncate">{document.author}</p>
             )}
+            {seriesLabel && <p className="text-xs text-gray-500 truncate">{seriesLabel}</p>}
             <p className="text-[11px] text-gray-500 mt-0.5">
+              {ratingLabel && <span className="text-amber-500">{ratingLabel} </span>}
               Added {formatDate(document.created_at)}
             </p>
             {tags.length > 0 && (
@@ -134,6 +195,8 @@ export const DocumentCard: React.FC<DocumentCardProps> = ({
             )}
           </div>
         </div>
+        {renderOrganizeButton('h-5 w-5')}
+        {renderReimportButton('h-5 w-5')}
         <button
           onClick={(e) => {
             e.stopPropagation();
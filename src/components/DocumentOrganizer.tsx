@@ -0,0 +1,304 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { Collection, Document, DocumentUpdate } from '../types';
+
+interface DocumentOrganizerProps {
+  document: Document;
+  collections: Collection[];
+  onClose: () => void;
+  onChanged: () => void | Promise<void>;
+}
+
+const errorText = (error: unknown) => (error instanceof Error ? error.message : String(error));
+
+type DetailField = Exclude<keyof DocumentUpdate, 'rating'>;
+
+const DETAIL_FIELDS: DetailField[] = [
+  'title',
+  'subtitle',
+  'author',
+  'language',
+  'publisher',
+  'published_date',
+  'isbn',
+  'doi',
+  'description',
+];
+
+const inputClass =
+  'mt-1 w-full px-2.5 py-1.5 text-sm border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500';
+
+export const DocumentOrganizer: React.FC<DocumentOrganizerProps> = ({
+  document,
+  collections,
+  onClose,
+  onChanged,
+}) => {
+  const [details, setDetails] = useState<Record<DetailField, string>>(() =>
+    Object.fromEntries(DETAIL_FIELDS.map((field) => [field, document[field] ?? ''])) as Record<
+      DetailField,
+      string
+    >
+  );
+  const [rating, setRating] = useState(document.rating ?? 0);
+  const [tagsInput, setTagsInput] = useState((document.tags ?? []).join(', '));
+  const [series, setSeries] = useState(document.series ?? '');
+  const [seriesIndex, setSeriesIndex] = useState(
+    document.series_index != null ? String(document.series_index) : ''
+  );
+  const [memberOf, setMemberOf] = useState<string[]>(document.collection_ids ?? []);
+  const [newCollection, setNewCollection] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const setDetail = (field: DetailField) => (
+    e: React.ChangeEvent<HTMLInputElement | HTMLTextAreaElement>
+  ) => setDetails((prev) => ({ ...prev, [field]: e.target.value }));
+
+  // Only changed fields are sent so untouched ones keep their stored form
+  const detailChanges = (): DocumentUpdate => {
+    const changes: DocumentUpdate = {};
+    for (const field of DETAIL_FIELDS) {
+      if (details[field].trim() !== (document[field] ?? '')) {
+        changes[field] = details[field].trim();
+      }
+    }
+    if (rating !== (document.rating ?? 0)) {
+      changes.rating = rating;
+    }
+    return changes;
+  };
+
+  const toggleCollection = async (collectionId: string, checked: boolean) => {
+    setStatus(null);
+    try {
+      await invoke(checked ? 'add_to_collection' : 'remove_from_collection', {
+        collectionId,
+        docIds: [document.id],
+      });
+      setMemberOf((prev) =>
+        checked ? [...prev, collectionId] : prev.filter((id) => id !== collectionId)
+      );
+      await onChanged();
+    } catch (error) {
+      setStatus(`Failed to update collection: ${errorText(error)}`);
+    }
+  };
+
+  const handleCreateCollection = async () => {
+    const name = newCollection.trim();
+    if (!name) return;
+    setStatus(null);
+    try {
+      const collection = await invoke<Collection>('create_collection', { name });
+      await invoke('add_to_collection', { collectionId: collection.id, docIds: [document.id] });
+      setMemberOf((prev) => [...prev, collection.id]);
+      setNewCollection('');
+      await onChanged();
+    } catch (error) {
+      setStatus(`Failed to create collection: ${errorText(error)}`);
+    }
+  };
+
+  const handleSave = async () => {
+    if (!details.title.trim()) {
+      setStatus('Title cannot be empty');
+      return;
+    }
+    const index = seriesIndex.trim() ? Number(seriesIndex) : null;
+    if (index !== null && (!Number.isFinite(index) || index < 0)) {
+      setStatus('Series number must be a non-negative number');
+      return;
+    }
+    setBusy(true);
+    setStatus(null);
+    try {
+      const changes = detailChanges();
+      if (Object.keys(changes).length > 0) {
+        await invoke<Document>('update_document', { id: document.id, changes });
+      }
+      const tags = tagsInput
+        .split(',')
+        .map((tag) => tag.trim())
+        .filter(Boolean);
+      await invoke<Document>('set_document_tags', { docId: document.id, tags });
+      await invoke<Document>('set_document_series', {
+        docId: document.id,
+        series: series.trim() || null,
+        seriesIndex: index,
+      });
+      await onChanged();
+      onClose();
+    } catch (error) {
+      setStatus(`Failed to save: ${errorText(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 bg-black bg-opacity-40 flex items-center justify-center">
+      <div className="bg-white rounded-lg shadow-xl w-full max-w-md mx-4 p-5 max-h-[90vh] overflow-y-auto">
+        <div className="flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900 truncate">Organize: {document.title}</h3>
+          <button onClick={onClose} className="text-sm text-gray-500 hover:text-gray-800" aria-label="Close">
+            ✕
+          </button>
+        </div>
+
+        <label className="mt-4 block text-xs font-medium text-gray-700">
+          Title
+          <input value={details.title} onChange={setDetail('title')} className={inputClass} />
+        </label>
+        <label className="mt-3 block text-xs font-medium text-gray-700">
+          Subtitle
+          <input value={details.subtitle} onChange={setDetail('subtitle')} className={inputClass} />
+        </label>
+        <div className="mt-3 flex gap-2">
+          <label className="flex-1 block text-xs font-medium text-gray-700">
+            Author
+            <input value={details.author} onChange={setDetail('author')} className={inputClass} />
+          </label>
+          <label className="w-24 block text-xs font-medium text-gray-700">
+            Language
+            <input value={details.language} onChange={setDetail('language')} className={inputClass} />
+          </label>
+        </div>
+        <div className="mt-3 flex gap-2">
+          <label className="flex-1 block text-xs font-medium text-gray-700">
+            Publisher
+            <input value={details.publisher} onChange={setDetail('publisher')} className={inputClass} />
+          </label>
+          <label className="w-28 block text-xs font-medium text-gray-700">
+            Published
+            <input
+              value={details.published_date}
+              onChange={setDetail('published_date')}
+              placeholder="2019-03-01"
+              className={inputClass}
+            />
+          </label>
+        </div>
+        <div className="mt-3 flex gap-2">
+          <label className="flex-1 block text-xs font-medium text-gray-700">
+            ISBN
+            <input value={details.isbn} onChange={setDetail('isbn')} className={inputClass} />
+          </label>
+          <label className="flex-1 block text-xs font-medium text-gray-700">
+            DOI
+            <input value={details.doi} onChange={setDetail('doi')} placeholder="10.1000/182" className={inputClass} />
+          </label>
+        </div>
+        <label className="mt-3 block text-xs font-medium text-gray-700">
+          Description
+          <textarea value={details.description} onChange={setDetail('description')} rows={3} className={inputClass} />
+        </label>
+        <div className="mt-3 flex items-center gap-1 text-xs font-medium text-gray-700">
+          <span className="mr-1">Rating</span>
+          {[1, 2, 3, 4, 5].map((star) => (
+            <button
+              key={star}
+              type="button"
+              onClick={() => setRating(star === rating ? 0 : star)}
+              className={`text-lg leading-none ${star <= rating ? 'text-amber-500' : 'text-gray-300'} hover:text-amber-400`}
+              aria-label={`${star} star${star > 1 ? 's' : ''}`}
+            >
+              ★
+            </button>
+          ))}
+        </div>
+
+        <label className="mt-4 block text-xs font-medium text-gray-700">
+          Tags
+          <input
+            value={tagsInput}
+            onChange={(e) => setTagsInput(e.target.value)}
+            placeholder="fiction, to-read, ..."
+            className="mt-1 w-full px-2.5 py-1.5 text-sm border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+          />
+        </label>
+
+        <div className="mt-3 flex gap-2">
+          <label className="flex-1 block text-xs font-medium text-gray-700">
+            Series
+            <input
+              value={series}
+              onChange={(e) => setSeries(e.target.value)}
+              className="mt-1 w-full px-2.5 py-1.5 text-sm border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+          </label>
+          <label className="w-20 block text-xs font-medium text-gray-700">
+            Number
+            <input
+              value={seriesIndex}
+              onChange={(e) => setSeriesIndex(e.target.value)}
+              inputMode="decimal"
+              disabled={!series.trim()}
+              className="mt-1 w-full px-2.5 py-1.5 text-sm border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500 disabled:bg-gray-100"
+            />
+          </label>
+        </div>
+
+        <div className="mt-4">
+          <p className="text-xs font-medium text-gray-700">Collections</p>
+          <ul className="mt-1 max-h-40 overflow-y-auto space-y-1">
+            {collections.length === 0 && <li className="text-xs text-gray-500">No collections yet.</li>}
+            {collections.map((collection) => (
+              <li key={collection.id}>
+                <label className="flex items-center gap-2 text-sm text-gray-800">
+                  <input
+                    type="checkbox"
+                    checked={memberOf.includes(collection.id)}
+                    onChange={(e) => void toggleCollection(collection.id, e.target.checked)}
+                  />
+                  <span className="truncate">{collection.name}</span>
+                  <span className="ml-auto text-[11px] text-gray-500">{collection.document_count}</span>
+                </label>
+              </li>
+            ))}
+          </ul>
+          <div className="mt-2 flex gap-2">
+            <input
+              value={newCollection}
+              onChange={(e) => setNewCollection(e.target.value)}
+              onKeyDown={(e) => {
+                if (e.key === 'Enter') {
+                  e.preventDefault();
+                  void handleCreateCollection();
+                }
+              }}
+              placeholder="New collection..."
+              className="flex-1 px-2.5 py-1.5 text-xs border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+            <button
+              onClick={() => void handleCreateCollection()}
+              disabled={!newCollection.trim()}
+              className="px-2 py-1 text-xs text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+            >
+              Add
+            </button>
+          </div>
+        </div>
+
+        <div className="mt-4 flex items-center justify-between gap-3">
+          <span className="text-xs text-red-600">{status}</span>
+          <div className="flex shrink-0 gap-2">
+            <button
+              onClick={onClose}
+              className="px-3 py-2 text-sm text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200"
+            >
+              Cancel
+            </button>
+            <button
+              onClick={() => void handleSave()}
+              disabled={busy}
+              className="px-3 py-2 text-sm text-white bg-blue-600 rounded-md hover:bg-blue-700 disabled:bg-gray-400"
+            >
+              {busy ? 'Saving...' : 'Save'}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+};
@@ -0,0 +1,173 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { Feed, FeedRefreshResult } from '../types';
+
+interface FeedManagerProps {
+  onClose: () => void;
+  onImported: () => void | Promise<void>;
+}
+
+const POLL_OPTIONS: { label: string; value: number | null }[] = [
+  { label: 'Manual only', value: null },
+  { label: 'Every hour', value: 60 },
+  { label: 'Every 6 hours', value: 360 },
+  { label: 'Daily', value: 1440 },
+];
+
+const formatPolled = (timestamp?: number | null) =>
+  timestamp ? new Date(timestamp * 1000).toLocaleString() : 'Never';
+
+export const FeedManager: React.FC<FeedManagerProps> = ({ onClose, onImported }) => {
+  const [feeds, setFeeds] = useState<Feed[]>([]);
+  const [url, setUrl] = useState('');
+  const [pollInterval, setPollInterval] = useState<number | null>(360);
+  const [busy, setBusy] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const loadFeeds = async () => {
+    try {
+      setFeeds(await invoke<Feed[]>('list_feeds'));
+    } catch (error) {
+      console.error('Failed to load feeds:', error);
+    }
+  };
+
+  useEffect(() => {
+    void loadFeeds();
+  }, []);
+
+  const runRefresh = async (feedId?: string) => {
+    setBusy(true);
+    setStatus(null);
+    try {
+      const results = await invoke<FeedRefreshResult[]>('refresh_feeds', { feedId: feedId ?? null });
+      const imported = results.reduce((sum, item) => sum + item.imported.length, 0);
+      const failed = results.reduce((sum, item) => sum + item.failed.length, 0);
+      const broken = results.filter((item) => item.error).map((item) => item.title);
+      const parts = [`Imported ${imported} new article(s)`];
+      if (failed > 0) parts.push(`${failed} failed`);
+      if (broken.length > 0) parts.push(`could not fetch: ${broken.join(', ')}`);
+      setStatus(parts.join('; '));
+      if (imported > 0) await onImported();
+      await loadFeeds();
+    } catch (error) {
+      setStatus(`Refresh failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleAdd = async () => {
+    if (!url.trim()) return;
+    setBusy(true);
+    setStatus(null);
+    try {
+      const feed = await invoke<Feed>('add_feed', { url: url.trim(), pollIntervalMinutes: pollInterval });
+      setUrl('');
+      await loadFeeds();
+      setBusy(false);
+      await runRefresh(feed.id);
+    } catch (error) {
+      setStatus(`Failed to add feed: ${error instanceof Error ? error.message : String(error)}`);
+      setBusy(false);
+    }
+  };
+
+  const handleDelete = async (id: string) => {
+    try {
+      await invoke('delete_feed', { id });
+      await loadFeeds();
+    } catch (error) {
+      setStatus(`Failed to remove feed: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 bg-black bg-opacity-40 flex items-center justify-center">
+      <div className="bg-white rounded-lg shadow-xl w-full max-w-xl mx-4 p-5">
+        <div className="flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900">Feeds</h3>
+          <button onClick={onClose} className="text-sm text-gray-500 hover:text-gray-800" aria-label="Close">
+            ✕
+          </button>
+        </div>
+
+        <div className="mt-3 flex items-center gap-2">
+          <input
+            value={url}
+            onChange={(e) => setUrl(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === 'Enter') {
+                e.preventDefault();
+                void handleAdd();
+              }
+            }}
+            placeholder="RSS or Atom feed URL..."
+            className="flex-1 px-2.5 py-1.5 text-xs border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+          />
+          <select
+            value={pollInterval ?? ''}
+            onChange={(e) => setPollInterval(e.target.value ? Number(e.target.value) : null)}
+            className="h-8 px-2 text-xs border border-gray-300 rounded-md bg-white"
+          >
+            {POLL_OPTIONS.map((option) => (
+              <option key={option.label} value={option.value ?? ''}>
+                {option.label}
+              </option>
+            ))}
+          </select>
+          <button
+            onClick={() => void handleAdd()}
+            disabled={busy || !url.trim()}
+            className="h-8 px-3 text-xs bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:bg-gray-400 transition-colors"
+          >
+            Subscribe
+          </button>
+        </div>
+
+        <ul className="mt-4 max-h-72 overflow-y-auto divide-y divide-gray-100">
+          {feeds.length === 0 && <li className="py-3 text-sm text-gray-500">No feeds yet.</li>}
+          {feeds.map((feed) => (
+            <li key={feed.id} className="py-2 flex items-start justify-between gap-3">
+              <div className="min-w-0">
+                <div className="text-sm font-medium text-gray-900 truncate">{feed.title}</div>
+                <div className="text-xs text-gray-500 truncate">{feed.url}</div>
+                <div className="text-xs text-gray-400">
+                  Last checked: {formatPolled(feed.last_polled_at)}
+                  {feed.last_error && <span className="text-red-500"> · {feed.last_error}</span>}
+                </div>
+              </div>
+              <div className="flex shrink-0 gap-1">
+                <button
+                  onClick={() => void runRefresh(feed.id)}
+                  disabled={busy}
+                  className="px-2 py-1 text-xs text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+                >
+                  Refresh
+                </button>
+                <button
+                  onClick={() => void handleDelete(feed.id)}
+                  disabled={busy}
+                  className="px-2 py-1 text-xs text-red-600 bg-gray-100 rounded-md hover:bg-red-50 disabled:text-gray-400"
+                >
+                  Remove
+                </button>
+              </div>
+            </li>
+          ))}
+        </ul>
+
+        <div className="mt-4 flex items-center justify-between gap-3">
+          <span className="text-xs text-gray-600">{busy ? 'Working...' : status}</span>
+          <button
+            onClick={() => void runRefresh()}
+            disabled={busy || feeds.length === 0}
+            className="px-3 py-2 text-sm text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+          >
+            Refresh all
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
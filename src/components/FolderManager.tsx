@@ -0,0 +1,217 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import type { FolderImportResult, RepairResult, WatchedFolder } from '../types';
+
+interface FolderManagerProps {
+  onClose: () => void;
+  onImported: () => void | Promise<void>;
+}
+
+const formatScanned = (timestamp?: number | null) =>
+  timestamp ? new Date(timestamp * 1000).toLocaleString() : 'Never';
+
+const summarize = (results: FolderImportResult[]) => {
+  const imported = results.reduce((sum, item) => sum + item.imported.length, 0);
+  const updated = results.reduce((sum, item) => sum + item.updated.length, 0);
+  const unsupported = results.reduce((sum, item) => sum + item.unsupported, 0);
+  const failed = results.flatMap((item) => item.failed);
+  const parts = [`Imported ${imported} new document(s)`];
+  if (updated > 0) parts.push(`${updated} updated`);
+  if (unsupported > 0) parts.push(`${unsupported} unsupported`);
+  if (failed.length > 0) {
+    const names = failed.slice(0, 3).map((item) => item.path.split(/[\\/]/).pop());
+    parts.push(`${failed.length} failed (${names.join(', ')}${failed.length > 3 ? ', ...' : ''})`);
+  }
+  return { text: parts.join('; '), changed: imported + updated > 0 };
+};
+
+export const FolderManager: React.FC<FolderManagerProps> = ({ onClose, onImported }) => {
+  const [folders, setFolders] = useState<WatchedFolder[]>([]);
+  const [recursive, setRecursive] = useState(true);
+  const [busy, setBusy] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const loadFolders = async () => {
+    try {
+      setFolders(await invoke<WatchedFolder[]>('list_watched_folders'));
+    } catch (error) {
+      console.error('Failed to load watched folders:', error);
+    }
+  };
+
+  useEffect(() => {
+    void loadFolders();
+  }, []);
+
+  const report = async (results: FolderImportResult[]) => {
+    const { text, changed } = summarize(results);
+    setStatus(text);
+    if (changed) await onImported();
+  };
+
+  const pickFolder = async () => {
+    const selected = await open({ directory: true, multiple: false });
+    return selected && typeof selected === 'string' ? selected : null;
+  };
+
+  const handleImportOnce = async () => {
+    const folderPath = await pickFolder();
+    if (!folderPath) return;
+    setBusy(true);
+    setStatus(null);
+    try {
+      const result = await invoke<FolderImportResult>('import_folder', { folderPath, recursive });
+      await report([result]);
+    } catch (error) {
+      setStatus(`Folder import failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const runScan = async (folderId?: string) => {
+    setBusy(true);
+    setStatus(null);
+    try {
+      const results = await invoke<FolderImportResult[]>('scan_watched_folders', { folderId: folderId ?? null });
+      await report(results);
+      await loadFolders();
+    } catch (error) {
+      setStatus(`Scan failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleWatch = async () => {
+    const path = await pickFolder();
+    if (!path) return;
+    try {
+      const folder = await invoke<WatchedFolder>('add_watched_folder', { path, recursive });
+      await loadFolders();
+      await runScan(folder.id);
+    } catch (error) {
+      setStatus(`Failed to watch folder: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  };
+
+  const handleRepair = async () => {
+    // Without a folder only managed copies are restored from their originals
+    const folderPath = await pickFolder();
+    setBusy(true);
+    setStatus(null);
+    try {
+      const result = await invoke<RepairResult>('repair_missing_files', { folderPath });
+      const parts = [`Found ${result.relocated.length} missing file(s)`];
+      if (result.still_missing.length > 0) {
+        const names = result.still_missing.slice(0, 3);
+        parts.push(`still missing: ${names.join(', ')}${result.still_missing.length > 3 ? ', ...' : ''}`);
+      }
+      setStatus(parts.join('; '));
+      if (result.relocated.length > 0) await onImported();
+    } catch (error) {
+      setStatus(`Repair failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRemove = async (id: string) => {
+    try {
+      await invoke('remove_watched_folder', { id });
+      await loadFolders();
+    } catch (error) {
+      setStatus(`Failed to remove folder: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 bg-black bg-opacity-40 flex items-center justify-center">
+      <div className="bg-white rounded-lg shadow-xl w-full max-w-xl mx-4 p-5">
+        <div className="flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900">Folders</h3>
+          <button onClick={onClose} className="text-sm text-gray-500 hover:text-gray-800" aria-label="Close">
+            ✕
+          </button>
+        </div>
+
+        <div className="mt-3 flex items-center gap-2">
+          <button
+            onClick={() => void handleImportOnce()}
+            disabled={busy}
+            className="h-8 px-3 text-xs bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:bg-gray-400 transition-colors"
+          >
+            Import folder...
+          </button>
+          <button
+            onClick={() => void handleWatch()}
+            disabled={busy}
+            className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 disabled:text-gray-400 transition-colors"
+            title="Import new and changed files from this folder automatically"
+          >
+            Watch folder...
+          </button>
+          <label className="ml-auto flex items-center gap-1.5 text-xs text-gray-600">
+            <input type="checkbox" checked={recursive} onChange={(e) => setRecursive(e.target.checked)} />
+            Include subfolders
+          </label>
+        </div>
+
+        <ul className="mt-4 max-h-72 overflow-y-auto divide-y divide-gray-100">
+          {folders.length === 0 && <li className="py-3 text-sm text-gray-500">No watched folders.</li>}
+          {folders.map((folder) => (
+            <li key={folder.id} className="py-2 flex items-start justify-between gap-3">
+              <div className="min-w-0">
+                <div className="text-sm font-medium text-gray-900 truncate" title={folder.path}>
+                  {folder.path}
+                </div>
+                <div className="text-xs text-gray-400">
+                  {folder.recursive ? 'With subfolders' : 'Top level only'} · Last scanned:{' '}
+                  {formatScanned(folder.last_scanned_at)}
+                </div>
+              </div>
+              <div className="flex shrink-0 gap-1">
+                <button
+                  onClick={() => void runScan(folder.id)}
+                  disabled={busy}
+                  className="px-2 py-1 text-xs text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+                >
+                  Scan
+                </button>
+                <button
+                  onClick={() => void handleRemove(folder.id)}
+                  disabled={busy}
+                  className="px-2 py-1 text-xs text-red-600 bg-gray-100 rounded-md hover:bg-red-50 disabled:text-gray-400"
+                >
+                  Remove
+                </button>
+              </div>
+            </li>
+          ))}
+        </ul>
+
+        <div className="mt-4 flex items-center justify-between gap-3">
+          <span className="text-xs text-gray-600">{busy ? 'Working...' : status}</span>
+          <div className="flex shrink-0 gap-2">
+            <button
+              onClick={() => void handleRepair()}
+              disabled={busy}
+              className="px-3 py-2 text-sm text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+              title="Look for moved or deleted documents' files in a folder"
+            >
+              Find missing files...
+            </button>
+            <button
+              onClick={() => void runScan()}
+              disabled={busy || folders.length === 0}
+              className="px-3 py-2 text-sm text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200 disabled:text-gray-400"
+            >
+              Scan all
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+};
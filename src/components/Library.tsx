@@ -6,7 +6,21 @@ import TurndownService from 'turndown';
 import { gfm } from 'turndown-plugin-gfm';
 import { useStore } from '../store/useStore';
 import { DocumentCard } from './DocumentCard';
-import type { Document as ReaderDocument } from '../types';
+import { FeedManager } from './FeedManager';
+import { FolderManager } from './FolderManager';
+import { ContinueReading } from './ContinueReading';
+import { DocumentOrganizer } from './DocumentOrganizer';
+import { NotesExportDialog } from './NotesExportDialog';
+import type {
+  CalibreImportResult,
+  Collection,
+  Document as ReaderDocument,
+  HighlightImportResult,
+  NotesImportResult,
+  ReimportResult,
+  SeriesInfo,
+  Tag,
+} from '../types';
 
 type LibraryProps = {
   onOpenSettings?: () => void;
@@ -23,10 +37,13 @@ type DocumentInsight = {
   tags: string[];
 };
 
-const normalizeFileType = (fileType: string): 'epub' | 'pdf' | 'markdown' => {
+const normalizeFileType = (fileType: string): 'epub' | 'pdf' | 'markdown' | 'docx' | 'html' | 'text' | 'fb2' | 'mobi' => {
   const normalized = fileType.trim().toLowerCase();
   if (normalized === 'md') return 'markdown';
-  if (normalized === 'epub' || normalized === 'pdf' || normalized === 'markdown') {
+  if (normalized === 'htm' || normalized === 'mhtml') return 'html';
+  if (normalized === 'txt') return 'text';
+  if (normalized === 'azw3' || normalized === 'azw') return 'mobi';
+  if (normalized === 'epub' || normalized === 'pdf' || normalized === 'markdown' || normalized === 'docx' || normalized === 'html' || normalized === 'text' || normalized === 'fb2' || normalized === 'mobi') {
     return normalized;
   }
   return 'markdown';
@@ -87,15 +104,28 @@ const inferDocumentInsight = (doc: ReaderDocument, preview: string): DocumentIns
 export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) => {
   const DEFAULT_CATEGORY_VISIBLE_COUNT = 8;
   const DEFAULT_EXPANDED_CATEGORY_COUNT = 2;
-  const { documents, isLoading, loadDocuments, importEpub, importPdf, importMarkdown, deleteDocument, selectDocument } = useStore();
+  const { documents, isLoading, loadDocuments, importEpub, importPdf, importMarkdown, importDocx, importHtml, importText, importFb2, importMobi, importNotebook, importRst, importAsciidoc, deleteDocument, selectDocument } = useStore();
   const [viewMode, setViewMode] = useState<'grid' | 'list' | 'compact'>('grid');
-  const [typeFilter, setTypeFilter] = useState<'all' | 'epub' | 'pdf' | 'markdown'>('all');
+  const [typeFilter, setTypeFilter] = useState<'all' | 'epub' | 'pdf' | 'markdown' | 'docx' | 'html' | 'text' | 'fb2' | 'mobi'>('all');
   const [sortBy, setSortBy] = useState<'recent' | 'title' | 'type'>('recent');
   const [searchText, setSearchText] = useState('');
   const [categoryFilter, setCategoryFilter] = useState<string>('all');
+  const [tagFilter, setTagFilter] = useState<string>('all');
+  const [collectionFilter, setCollectionFilter] = useState<string>('all');
+  const [seriesFilter, setSeriesFilter] = useState<string>('all');
+  const [collections, setCollections] = useState<Collection[]>([]);
+  const [tagList, setTagList] = useState<Tag[]>([]);
+  const [seriesList, setSeriesList] = useState<SeriesInfo[]>([]);
+  const [organizing, setOrganizing] = useState<ReaderDocument | null>(null);
   const [groupByCategory, setGroupByCategory] = useState(true);
   const [urlInput, setUrlInput] = useState('');
   const [isImportingUrl, setIsImportingUrl] = useState(false);
+  const [isImportingHighlights, setIsImportingHighlights] = useState(false);
+  const [isImportingNotes, setIsImportingNotes] = useState(false);
+  const [showNotesExport, setShowNotesExport] = useState(false);
+  const [isImportingCalibre, setIsImportingCalibre] = useState(false);
+  const [showFeeds, setShowFeeds] = useState(false);
+  const [showFolders, setShowFolders] = useState(false);
   const [isAutoClassifying, setIsAutoClassifying] = useState(false);
   const [documentInsights, setDocumentInsights] = useState<Record<string, DocumentInsight>>({});
   const [collapsedCategories, setCollapsedCategories] = useState<Record<string, boolean>>({});
@@ -245,6 +275,67 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
     loadDocuments();
   }, [loadDocuments]);
 
+  const loadOrganization = async () => {
+    try {
+      const [nextCollections, nextTags, nextSeries] = await Promise.all([
+        invoke<Collection[]>('list_collections'),
+        invoke<Tag[]>('list_tags'),
+        invoke<SeriesInfo[]>('list_series'),
+      ]);
+      setCollections(nextCollections);
+      setTagList(nextTags);
+      setSeriesList(nextSeries);
+    } catch (error) {
+      console.error('Failed to load collections and tags:', error);
+    }
+  };
+
+  useEffect(() => {
+    void loadOrganization();
+  }, [documents]);
+
+  // Drop filters whose tag, collection or series no longer exists
+  useEffect(() => {
+    if (tagFilter !== 'all' && !tagList.some((tag) => tag.name === tagFilter)) setTagFilter('all');
+    if (collectionFilter !== 'all' && !collections.some((item) => item.id === collectionFilter)) {
+      setCollectionFilter('all');
+    }
+    if (seriesFilter !== 'all' && !seriesList.some((item) => item.name === seriesFilter)) setSeriesFilter('all');
+  }, [collections, tagList, seriesList]);
+
+  const handleOrganized = async () => {
+    await loadDocuments();
+    await loadOrganization();
+  };
+
+  const handleDeleteCollection = async (collection: Collection) => {
+    if (!confirm(`Delete collection "${collection.name}"? Its documents stay in the library.`)) return;
+    try {
+      await invoke('delete_collection', { id: collection.id });
+      await handleOrganized();
+    } catch (error) {
+      console.error('Failed to delete collection:', error);
+      alert(`Failed to delete collection: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  };
+
+  const matchesOrganization = (doc: ReaderDocument) => {
+    if (tagFilter !== 'all' && !(doc.tags ?? []).some((tag) => tag.toLowerCase() === tagFilter.toLowerCase())) {
+      return false;
+    }
+    if (collectionFilter !== 'all' && !(doc.collection_ids ?? []).includes(collectionFilter)) return false;
+    if (seriesFilter !== 'all' && (doc.series ?? '').toLowerCase() !== seriesFilter.toLowerCase()) return false;
+    return true;
+  };
+
+  const cardTags = (doc: ReaderDocument) => {
+    const own = doc.tags ?? [];
+    const auto = (documentInsights[doc.id]?.tags || []).filter(
+      (tag) => !own.some((item) => item.toLowerCase() === tag.toLowerCase())
+    );
+    return [...own, ...auto];
+  };
+
   useEffect(() => {
     if (documents.length === 0) {
       setDocumentInsights({});
@@ -296,7 +387,7 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
         filters: [
           {
             name: 'Documents',
-            extensions: ['epub', 'pdf', 'md']
+            extensions: ['epub', 'pdf', 'md', 'docx', 'html', 'htm', 'mhtml', 'mht', 'txt', 'fb2', 'zip', 'mobi', 'azw3', 'azw', 'ipynb', 'rst', 'adoc', 'asciidoc']
           }
         ]
       });
@@ -306,9 +397,28 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
         if (ext === 'epub') {
           await importEpub(selected);
         } else if (ext === 'pdf') {
-          await importPdf(selected);
+          const { ocr_pages: ocrPages } = await importPdf(selected);
+          if (ocrPages.length > 0) {
+            alert(`Recognized text with OCR on ${ocrPages.length} scanned page(s): ${ocrPages.join(', ')}`);
+          }
         } else if (ext === 'md') {
           await importMarkdown(selected);
+        } else if (ext === 'ipynb') {
+          await importNotebook(selected);
+        } else if (ext === 'rst') {
+          await importRst(selected);
+        } else if (ext === 'adoc' || ext === 'asciidoc') {
+          await importAsciidoc(selected);
+        } else if (ext === 'docx') {
+          await importDocx(selected);
+        } else if (ext === 'html' || ext === 'htm' || ext === 'mhtml' || ext === 'mht') {
+          await importHtml(selected);
+        } else if (ext === 'txt') {
+          await importText(selected);
+        } else if (ext === 'fb2' || selected.toLowerCase().endsWith('.fb2.zip')) {
+          await importFb2(selected);
+        } else if (ext === 'mobi' || ext === 'azw3' || ext === 'azw') {
+          await importMobi(selected);
         }
       }
     } catch (error) {
@@ -333,6 +443,140 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
     }
   };
 
+  const handleReimport = async (id: string, title: string) => {
+    try {
+      const result = await invoke<ReimportResult>('reimport_document', { id });
+      await loadDocuments();
+      const lines = [
+        `Re-imported "${title}": ${result.paragraphs_matched} of ${result.paragraphs} paragraph(s) matched the previous version.`,
+        `${result.annotations_kept} annotation(s) and ${result.bookmarks_kept} bookmark(s) kept.`,
+      ];
+      if (result.annotations_dropped.length > 0) {
+        lines.push(`${result.annotations_dropped.length} annotation(s) no longer found:`);
+        result.annotations_dropped.slice(0, 5).forEach((text) => lines.push(`• ${text.slice(0, 80)}`));
+      }
+      alert(lines.join('\n'));
+    } catch (error) {
+      console.error('Re-import failed:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      alert(`Failed to re-import document: ${message}`);
+    }
+  };
+
+  const handleImportHighlights = async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [
+          {
+            name: 'Kindle clippings / KOReader highlights',
+            extensions: ['txt', 'lua']
+          }
+        ]
+      });
+      if (!selected || typeof selected !== 'string') return;
+
+      setIsImportingHighlights(true);
+      const result = await invoke<HighlightImportResult>('import_highlights', { filePath: selected });
+      const lines = [
+        `Imported ${result.imported} of ${result.total} highlight(s)` +
+          (result.documents.length > 0 ? ` into: ${result.documents.join(', ')}` : ''),
+      ];
+      if (result.skipped_duplicates > 0) {
+        lines.push(`Skipped ${result.skipped_duplicates} already imported.`);
+      }
+      if (result.unmatched.length > 0) {
+        lines.push(`${result.unmatched.length} could not be matched:`);
+        result.unmatched.slice(0, 5).forEach((item) => {
+          const excerpt = item.text.length > 60 ? `${item.text.slice(0, 60)}…` : item.text;
+          lines.push(`• ${item.book_title}: "${excerpt}" (${item.reason})`);
+        });
+        if (result.unmatched.length > 5) {
+          lines.push(`…and ${result.unmatched.length - 5} more`);
+        }
+      }
+      alert(lines.join('\n'));
+    } catch (error) {
+      console.error('Import highlights failed:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      alert(`Failed to import highlights: ${message}`);
+    } finally {
+      setIsImportingHighlights(false);
+    }
+  };
+
+  const handleImportNotes = async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [{ name: 'Notes archive or W3C Web Annotations', extensions: ['json', 'jsonld'] }]
+      });
+      if (!selected || typeof selected !== 'string') return;
+
+      setIsImportingNotes(true);
+      const result = await invoke<NotesImportResult>('import_notes', { filePath: selected });
+      const lines = [
+        `Imported ${result.annotations} annotation(s) and ${result.bookmarks} bookmark(s)` +
+          (result.documents.length > 0 ? ` into: ${result.documents.join(', ')}` : ''),
+      ];
+      if (result.skipped_duplicates > 0) {
+        lines.push(`Skipped ${result.skipped_duplicates} already in the library.`);
+      }
+      if (result.unmatched_documents.length > 0) {
+        lines.push(`No matching document for: ${result.unmatched_documents.join(', ')}`);
+      }
+      if (result.unplaced.length > 0) {
+        lines.push(`${result.unplaced.length} note(s) could not be placed:`);
+        result.unplaced.slice(0, 5).forEach((text) => {
+          lines.push(`• "${text.length > 60 ? `${text.slice(0, 60)}…` : text}"`);
+        });
+        if (result.unplaced.length > 5) {
+          lines.push(`…and ${result.unplaced.length - 5} more`);
+        }
+      }
+      window.dispatchEvent(new CustomEvent('reader:annotations-changed'));
+      window.dispatchEvent(new CustomEvent('reader:bookmarks-changed'));
+      alert(lines.join('\n'));
+    } catch (error) {
+      console.error('Import notes failed:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      alert(`Failed to import notes: ${message}`);
+    } finally {
+      setIsImportingNotes(false);
+    }
+  };
+
+  const handleImportCalibre = async () => {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+      if (!selected || typeof selected !== 'string') return;
+
+      setIsImportingCalibre(true);
+      const result = await invoke<CalibreImportResult>('import_calibre_library', { libraryPath: selected });
+      await loadDocuments();
+      const lines = [
+        `Imported ${result.imported.length} of ${result.total} book(s) from Calibre.`,
+      ];
+      if (result.skipped_existing > 0) {
+        lines.push(`${result.skipped_existing} already in the library.`);
+      }
+      if (result.skipped_unsupported.length > 0) {
+        lines.push(`${result.skipped_unsupported.length} without a supported format.`);
+      }
+      if (result.failed.length > 0) {
+        lines.push(`${result.failed.length} failed:`);
+        result.failed.slice(0, 5).forEach((item) => lines.push(`• ${item.title}: ${item.error}`));
+      }
+      alert(lines.join('\n'));
+    } catch (error) {
+      console.error('Import Calibre library failed:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      alert(`Failed to import Calibre library: ${message}`);
+    } finally {
+      setIsImportingCalibre(false);
+    }
+  };
+
   const handleImportUrl = async () => {
     const url = normalizeUrl(urlInput);
     if (!url) return;
@@ -389,6 +633,7 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
       if (categoryFilter !== 'all' && (documentInsights[doc.id]?.category || '其他') !== categoryFilter) {
         return false;
       }
+      if (!matchesOrganization(doc)) return false;
       if (!q) return true;
       const title = doc.title.toLowerCase();
       const author = (doc.author || '').toLowerCase();
@@ -404,8 +649,12 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
     } else {
       sorted.sort((a, b) => normalizeFileType(a.file_type).localeCompare(normalizeFileType(b.file_type)) || a.title.localeCompare(b.title));
     }
+    if (seriesFilter !== 'all') {
+      // Reading order within a series; unnumbered volumes last
+      sorted.sort((a, b) => (a.series_index ?? Infinity) - (b.series_index ?? Infinity));
+    }
     return sorted;
-  }, [categoryFilter, documentInsights, documents, searchText, sortBy, typeFilter]);
+  }, [categoryFilter, collectionFilter, documentInsights, documents, searchText, seriesFilter, sortBy, tagFilter, typeFilter]);
 
   const searchableDocuments = useMemo(() => {
     const q = searchText.trim().toLowerCase();
@@ -413,13 +662,14 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
       if (categoryFilter !== 'all' && (documentInsights[doc.id]?.category || '其他') !== categoryFilter) {
         return false;
       }
+      if (!matchesOrganization(doc)) return false;
       if (!q) return true;
       const title = doc.title.toLowerCase();
       const author = (doc.author || '').toLowerCase();
       const filePath = doc.file_path.toLowerCase();
       return title.includes(q) || author.includes(q) || filePath.includes(q);
     });
-  }, [categoryFilter, documentInsights, documents, searchText]);
+  }, [categoryFilter, collectionFilter, documentInsights, documents, searchText, seriesFilter, tagFilter]);
 
   const categoryOptions = useMemo(() => {
     const categories = new Set<string>();
@@ -442,11 +692,21 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
     const markdownCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'markdown').length;
     const pdfCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'pdf').length;
     const epubCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'epub').length;
+    const docxCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'docx').length;
+    const htmlCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'html').length;
+    const textCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'text').length;
+    const fb2Count = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'fb2').length;
+    const mobiCount = searchableDocuments.filter((doc) => normalizeFileType(doc.file_type) === 'mobi').length;
     return [
       { key: 'all' as const, label: 'All', count: searchableDocuments.length, hint: 'All formats' },
       { key: 'markdown' as const, label: 'Markdown', count: markdownCount, hint: 'Notes & articles' },
       { key: 'pdf' as const, label: 'PDF', count: pdfCount, hint: 'Documents' },
       { key: 'epub' as const, label: 'EPUB', count: epubCount, hint: 'Books' },
+      { key: 'docx' as const, label: 'Word', count: docxCount, hint: 'DOCX files' },
+      { key: 'html' as const, label: 'Web', count: htmlCount, hint: 'Saved pages' },
+      { key: 'text' as const, label: 'Text', count: textCount, hint: 'Plain text books' },
+      { key: 'fb2' as const, label: 'FB2', count: fb2Count, hint: 'FictionBook' },
+      { key: 'mobi' as const, label: 'Kindle', count: mobiCount, hint: 'MOBI & AZW3' },
     ];
   }, [searchableDocuments]);
 
@@ -477,6 +737,18 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
 
   return (
     <>
+      {showFeeds && <FeedManager onClose={() => setShowFeeds(false)} onImported={loadDocuments} />}
+      {showFolders && <FolderManager onClose={() => setShowFolders(false)} onImported={loadDocuments} />}
+      {showNotesExport && <NotesExportDialog onClose={() => setShowNotesExport(false)} />}
+      {organizing && (
+        <DocumentOrganizer
+          document={organizing}
+          collections={collections}
+          onClose={() => setOrganizing(null)}
+          onChanged={handleOrganized}
+        />
+      )}
+
       {pendingDelete && (
         <div className="fixed inset-0 z-50 bg-black bg-opacity-40 flex items-center justify-center">
           <div className="bg-white rounded-lg shadow-xl w-full max-w-md mx-4 p-5">
@@ -562,6 +834,43 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                 </div>
               </div>
 
+              {collections.length > 0 && (
+                <div className="mt-4">
+                  <h3 className="mb-1.5 px-1 text-[11px] font-semibold uppercase tracking-wide text-gray-500">Collections</h3>
+                  <div className="space-y-1">
+                    {collections.map((collection) => {
+                      const active = collectionFilter === collection.id;
+                      return (
+                        <div
+                          key={collection.id}
+                          className={`group flex items-center rounded-lg px-2.5 py-1.5 transition-colors ${
+                            active ? 'bg-gray-900 text-white' : 'text-gray-700 hover:bg-gray-200/70'
+                          }`}
+                        >
+                          <button
+                            onClick={() => setCollectionFilter(active ? 'all' : collection.id)}
+                            className="min-w-0 flex-1 truncate text-left text-xs font-medium"
+                            title={collection.description || collection.name}
+                          >
+                            {collection.name}
+                          </button>
+                          <span className={`ml-1 text-[11px] ${active ? 'text-gray-200' : 'text-gray-500'}`}>
+                            {collection.document_count}
+                          </span>
+                          <button
+                            onClick={() => void handleDeleteCollection(collection)}
+                            className="ml-1 hidden text-[11px] text-gray-400 hover:text-red-500 group-hover:inline"
+                            aria-label={`Delete collection ${collection.name}`}
+                          >
+                            ✕
+                          </button>
+                        </div>
+                      );
+                    })}
+                  </div>
+                </div>
+              )}
+
               <div className="mt-4 rounded-lg border border-gray-200 bg-white px-2.5 py-2">
                 <p className="text-[11px] uppercase tracking-wide text-gray-500">Summary</p>
                 <p className="mt-0.5 text-xl font-semibold text-gray-900">{documents.length}</p>
@@ -621,6 +930,51 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                 >
                   {isLoading ? 'Importing...' : 'Import Document'}
                 </button>
+                <button
+                  onClick={() => void handleImportHighlights()}
+                  disabled={isImportingHighlights}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 disabled:text-gray-400 transition-colors"
+                  title="Import Kindle My Clippings.txt or KOReader highlights"
+                >
+                  {isImportingHighlights ? 'Importing...' : 'Import Highlights'}
+                </button>
+                <button
+                  onClick={() => setShowNotesExport(true)}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 transition-colors"
+                  title="Export annotations and bookmarks to Markdown, Obsidian or JSON"
+                >
+                  Export Notes
+                </button>
+                <button
+                  onClick={() => void handleImportNotes()}
+                  disabled={isImportingNotes}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 disabled:text-gray-400 transition-colors"
+                  title="Import annotations and bookmarks from a JSON notes archive or W3C Web Annotations"
+                >
+                  {isImportingNotes ? 'Importing...' : 'Import Notes'}
+                </button>
+                <button
+                  onClick={() => void handleImportCalibre()}
+                  disabled={isImportingCalibre}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 disabled:text-gray-400 transition-colors"
+                  title="Import all books from a Calibre library folder"
+                >
+                  {isImportingCalibre ? 'Importing...' : 'Import Calibre'}
+                </button>
+                <button
+                  onClick={() => setShowFolders(true)}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 transition-colors"
+                  title="Import a folder of documents or watch folders for new files"
+                >
+                  Folders
+                </button>
+                <button
+                  onClick={() => setShowFeeds(true)}
+                  className="h-8 px-3 text-xs bg-white text-gray-700 border border-gray-300 rounded-md hover:bg-gray-50 transition-colors"
+                  title="Manage RSS/Atom feed subscriptions"
+                >
+                  Feeds
+                </button>
               </div>
               <button
                 onClick={onOpenSettings}
@@ -658,6 +1012,36 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
               ))}
             </select>
 
+            {tagList.length > 0 && (
+              <select
+                value={tagFilter}
+                onChange={(e) => setTagFilter(e.target.value)}
+                className="px-2 py-1 text-xs border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              >
+                <option value="all">Tag: All</option>
+                {tagList.map((tag) => (
+                  <option key={tag.id} value={tag.name}>
+                    #{tag.name} ({tag.document_count})
+                  </option>
+                ))}
+              </select>
+            )}
+
+            {seriesList.length > 0 && (
+              <select
+                value={seriesFilter}
+                onChange={(e) => setSeriesFilter(e.target.value)}
+                className="px-2 py-1 text-xs border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+              >
+                <option value="all">Series: All</option>
+                {seriesList.map((item) => (
+                  <option key={item.name} value={item.name}>
+                    {item.name} ({item.document_count})
+                  </option>
+                ))}
+              </select>
+            )}
+
             <button
               onClick={() => setGroupByCategory((prev) => !prev)}
               className={`px-2 py-1 text-xs rounded-md border ${
@@ -685,6 +1069,8 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
           </div>
         </div>
 
+      <ContinueReading documents={documents} onOpen={selectDocument} />
+
       {/* Documents Grid */}
       <div className="flex-1 overflow-y-auto p-6">
         {documents.length === 0 ? (
@@ -729,9 +1115,11 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                         document={doc}
                         variant="grid"
                         category={documentInsights[doc.id]?.category}
-                        tags={documentInsights[doc.id]?.tags || []}
+                        tags={cardTags(doc)}
                         onClick={() => selectDocument(doc.id)}
+                        onOrganize={() => setOrganizing(doc)}
                         onDelete={() => handleDeleteRequest(doc.id, doc.title)}
+                        onReimport={() => void handleReimport(doc.id, doc.title)}
                       />
                     ))}
                   </div>
@@ -743,9 +1131,11 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                         document={doc}
                         variant={viewMode}
                         category={documentInsights[doc.id]?.category}
-                        tags={documentInsights[doc.id]?.tags || []}
+                        tags={cardTags(doc)}
                         onClick={() => selectDocument(doc.id)}
+                        onOrganize={() => setOrganizing(doc)}
                         onDelete={() => handleDeleteRequest(doc.id, doc.title)}
+                        onReimport={() => void handleReimport(doc.id, doc.title)}
                       />
                     ))}
                   </div>
@@ -772,9 +1162,11 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                 document={doc}
                 variant="grid"
                 category={documentInsights[doc.id]?.category}
-                tags={documentInsights[doc.id]?.tags || []}
+                tags={cardTags(doc)}
                 onClick={() => selectDocument(doc.id)}
+                onOrganize={() => setOrganizing(doc)}
                 onDelete={() => handleDeleteRequest(doc.id, doc.title)}
+                onReimport={() => void handleReimport(doc.id, doc.title)}
               />
             ))}
           </div>
@@ -786,9 +1178,11 @@ export const Library: React.FC<LibraryProps> = ({ onOpenSettings, statusBar }) =
                 document={doc}
                 variant={viewMode}
                 category={documentInsights[doc.id]?.category}
-                tags={documentInsights[doc.id]?.tags || []}
+                tags={cardTags(doc)}
                 onClick={() => selectDocument(doc.id)}
+                onOrganize={() => setOrganizing(doc)}
                 onDelete={() => handleDeleteRequest(doc.id, doc.title)}
+                onReimport={() => void handleReimport(doc.id, doc.title)}
               />
             ))}
           </div>
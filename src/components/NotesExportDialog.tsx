@@ -0,0 +1,142 @@
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import type { NotesExportFormat, NotesExportResult } from '../types';
+
+interface NotesExportDialogProps {
+  /** Document to export by default; the whole library when unset */
+  docId?: string | null;
+  docTitle?: string;
+  onClose: () => void;
+}
+
+const FORMATS: { value: NotesExportFormat; label: string; hint: string }[] = [
+  { value: 'markdown', label: 'Markdown', hint: 'One file, grouped by book and section, with links back into the reader.' },
+  {
+    value: 'obsidian',
+    label: 'Obsidian vault',
+    hint: 'One note per book with YAML front matter. Notes exported before are updated in place.',
+  },
+  { value: 'json', label: 'JSON archive', hint: 'Everything, losslessly. Use Import Notes to bring it back.' },
+  {
+    value: 'w3c',
+    label: 'W3C Web Annotations',
+    hint: 'JSON-LD AnnotationPage of the annotations, for Hypothesis and other annotation tools.',
+  },
+];
+
+const FILE_TYPES: Record<Exclude<NotesExportFormat, 'obsidian'>, { name: string; extension: string }> = {
+  markdown: { name: 'Markdown', extension: 'md' },
+  json: { name: 'JSON', extension: 'json' },
+  w3c: { name: 'JSON-LD', extension: 'jsonld' },
+};
+
+const safeFileName = (title: string) => title.replace(/[\\/:*?"<>|]+/g, ' ').trim() || 'notes';
+
+export const NotesExportDialog: React.FC<NotesExportDialogProps> = ({ docId, docTitle, onClose }) => {
+  const [format, setFormat] = useState<NotesExportFormat>('markdown');
+  const [scope, setScope] = useState<'document' | 'library'>(docId ? 'document' : 'library');
+  const [busy, setBusy] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const pickPath = async () => {
+    if (format === 'obsidian') {
+      const selected = await open({ directory: true, multiple: false, title: 'Choose the vault folder for book notes' });
+      return selected && typeof selected === 'string' ? selected : null;
+    }
+    const { name, extension } = FILE_TYPES[format];
+    const baseName = scope === 'document' && docTitle ? safeFileName(docTitle) : 'Reader notes';
+    const selected = await save({
+      defaultPath: `${baseName}.${extension}`,
+      filters: [{ name, extensions: [extension] }],
+    });
+    return selected || null;
+  };
+
+  const handleExport = async () => {
+    const path = await pickPath();
+    if (!path) return;
+    setBusy(true);
+    setStatus(null);
+    try {
+      const result = await invoke<NotesExportResult>('export_notes', {
+        format,
+        path,
+        docId: scope === 'document' ? docId : null,
+      });
+      const files = format === 'obsidian' ? ` into ${result.files.length} note(s)` : '';
+      setStatus(
+        `Exported ${result.annotations} annotation(s) and ${result.bookmarks} bookmark(s) ` +
+          `from ${result.documents} document(s)${files}.`
+      );
+    } catch (error) {
+      console.error('Failed to export notes:', error);
+      const message = error instanceof Error ? error.message : String(error);
+      setStatus(`Export failed: ${message}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 bg-black bg-opacity-40 flex items-center justify-center">
+      <div className="bg-white rounded-lg shadow-xl w-full max-w-md mx-4 p-5">
+        <div className="flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900">Export Notes</h3>
+          <button onClick={onClose} className="text-sm text-gray-500 hover:text-gray-800" aria-label="Close">
+            ✕
+          </button>
+        </div>
+
+        <div className="mt-3 space-y-2">
+          {FORMATS.map((item) => (
+            <label key={item.value} className="flex items-start gap-2 text-sm text-gray-800">
+              <input
+                type="radio"
+                name="notes-format"
+                className="mt-1"
+                checked={format === item.value}
+                onChange={() => setFormat(item.value)}
+              />
+              <span>
+                <span className="font-medium">{item.label}</span>
+                <span className="block text-xs text-gray-500">{item.hint}</span>
+              </span>
+            </label>
+          ))}
+        </div>
+
+        {docId && (
+          <div className="mt-3 flex items-center gap-3 text-xs text-gray-700">
+            <label className="flex items-center gap-1">
+              <input type="radio" checked={scope === 'document'} onChange={() => setScope('document')} />
+              {docTitle ? `“${docTitle}”` : 'This document'}
+            </label>
+            <label className="flex items-center gap-1">
+              <input type="radio" checked={scope === 'library'} onChange={() => setScope('library')} />
+              Whole library
+            </label>
+          </div>
+        )}
+
+        {status && <p className="mt-3 text-xs text-gray-600">{status}</p>}
+
+        <div className="mt-4 flex justify-end gap-2">
+          <button
+            onClick={onClose}
+            className="px-3 py-2 text-sm text-gray-700 bg-gray-100 rounded-md hover:bg-gray-200"
+          >
+            Close
+          </button>
+          <button
+            onClick={() => void handleExport()}
+            disabled={busy}
+            className="px-3 py-2 text-sm text-white bg-blue-600 rounded-md hover:bg-blue-700 disabled:bg-gray-400"
+          >
+            {busy ? 'Exporting...' : format === 'obsidian' ? 'Choose vault...' : 'Save as...'}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
@@ -1,5 +1,7 @@
 import React, { useEffect, useMemo, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useStore } from '../store/useStore';
+import type { Bookmark } from '../types';
 
 type NoteRequest = {
   id: number;
@@ -92,9 +94,19 @@ export const NotesPanel: React.FC<NotesPanelProps> = ({ request }) => {
     setNotes((prev) => prev.filter((n) => n.id !== id));
   };
 
-  const exportJson = () => {
+  const loadDocumentBookmarks = async () => {
+    if (!selectedDocumentId) return [];
+    try {
+      return await invoke<Bookmark[]>('list_bookmarks', { docId: selectedDocumentId });
+    } catch (err) {
+      console.warn('Failed to load bookmarks for export:', err);
+      return [];
+    }
+  };
+
+  const exportJson = async () => {
     if (!selectedDocumentId) return;
-    const data = currentDocNotes;
+    const data = { notes: currentDocNotes, bookmarks: await loadDocumentBookmarks() };
     const blob = new Blob([JSON.stringify(data, null, 2)], { type: 'application/json' });
     const url = URL.createObjectURL(blob);
     const a = document.createElement('a');
@@ -112,7 +124,14 @@ export const NotesPanel: React.FC<NotesPanelProps> = ({ request }) => {
         return `## ${idx + 1}. Note\n\n- Time: ${formatTime(n.createdAt)}\n- Paragraph: ${n.paragraphId || 'N/A'}\n\n### Selected Text\n\n> ${n.selectedText}\n\n### My Note\n\n${note}`;
       })
       .join('\n\n---\n\n');
-    const content = `# Notes for ${currentDocTitle}\n\n${body || '_No notes_'}`;
+    const bookmarks = (await loadDocumentBookmarks())
+      .map((b) => {
+        const note = b.note?.trim() ? `\n\n  ${b.note.trim()}` : '';
+        return `- **${b.title}** (${b.section_title})${note}`;
+      })
+      .join('\n');
+    const bookmarkBlock = bookmarks ? `\n\n---\n\n## Bookmarks\n\n${bookmarks}` : '';
+    const content = `# Notes for ${currentDocTitle}\n\n${body || '_No notes_'}${bookmarkBlock}`;
     try {
       await navigator.clipboard.writeText(content);
     } catch (err) {
@@ -123,9 +142,11 @@ export const NotesPanel: React.FC<NotesPanelProps> = ({ request }) => {
   const importFromJson = () => {
     setImportError(null);
     try {
-      const parsed = JSON.parse(importText) as NoteItem[];
+      // Exports wrap notes together with the document's bookmarks
+      const raw = JSON.parse(importText) as NoteItem[] | { notes?: NoteItem[] };
+      const parsed = Array.isArray(raw) ? raw : raw?.notes;
       if (!Array.isArray(parsed)) {
-        throw new Error('JSON must be an array');
+        throw new Error('JSON must be an array of notes or an export with a "notes" array');
       }
       const normalized: NoteItem[] = parsed
         .filter((n) => n && typeof n.selectedText === 'string' && typeof n.docId === 'string')
@@ -157,15 +178,15 @@ export const NotesPanel: React.FC<NotesPanelProps> = ({ request }) => {
         </div>
         <div className="flex items-center gap-2">
           <button
-            onClick={exportJson}
-            disabled={!selectedDocumentId || currentDocNotes.length === 0}
+            onClick={() => void exportJson()}
+            disabled={!selectedDocumentId}
             className="px-2 py-1 text-xs border border-gray-300 rounded hover:bg-gray-50 disabled:bg-gray-100 disabled:text-gray-400"
           >
             Export JSON
           </button>
           <button
             onClick={() => void exportMarkdown()}
-            disabled={!selectedDocumentId || currentDocNotes.length === 0}
+            disabled={!selectedDocumentId}
             className="px-2 py-1 text-xs border border-gray-300 rounded hover:bg-gray-50 disabled:bg-gray-100 disabled:text-gray-400"
           >
             Copy Markdown
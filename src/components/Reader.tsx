@@ -1,4 +1,5 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { useStore } from '../store/useStore';
 import { matchesAnyShortcut } from '../utils/shortcuts';
@@ -7,6 +8,7 @@ import { ReaderContent } from './ReaderContent';
 import { ToolPanel } from './ToolPanel';
 import { FloatingAudiobookControl } from './FloatingAudiobookControl';
 import { loadReaderViewSettings } from './readerTheme';
+import type { ReadingPosition } from '../types';
 
 type ReaderProps = {
   onOpenSettings?: () => void;
@@ -95,13 +97,46 @@ export function Reader({ onOpenSettings }: ReaderProps) {
   );
 
   useEffect(() => {
-    if (selectedDocumentId) {
-      void loadSections(selectedDocumentId);
-      if (currentDocumentType === 'markdown') {
-        void loadDocumentParagraphs(selectedDocumentId);
+    if (!selectedDocumentId) return;
+    let cancelled = false;
+    const isMarkdown = currentDocumentType === 'markdown';
+
+    // Reopen the section and paragraph where reading last stopped
+    const openDocument = async () => {
+      const [, position] = await Promise.all([
+        loadSections(selectedDocumentId),
+        invoke<ReadingPosition | null>('get_reading_position', { docId: selectedDocumentId }),
+        isMarkdown ? loadDocumentParagraphs(selectedDocumentId) : Promise.resolve(),
+      ]);
+      const sectionId = position?.section_id;
+      if (cancelled || !sectionId) return;
+      const state = useStore.getState();
+      // Leave it alone if a search result or bookmark was opened meanwhile
+      if (state.sections.some((section) => section.id === state.currentSectionId)) return;
+      if (!state.sections.some((section) => section.id === sectionId)) return;
+      selectSection(sectionId);
+      if (!isMarkdown) {
+        await loadParagraphs(sectionId);
+      }
+      if (!cancelled && position?.paragraph_id) {
+        setFocusedParagraphId(position.paragraph_id);
       }
-    }
-  }, [selectedDocumentId, currentDocumentType, loadSections, loadDocumentParagraphs]);
+    };
+    openDocument().catch((error) => {
+      console.error('Failed to open document:', error);
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, [
+    selectedDocumentId,
+    currentDocumentType,
+    loadSections,
+    loadDocumentParagraphs,
+    loadParagraphs,
+    selectSection,
+    setFocusedParagraphId,
+  ]);
 
   const handleFlipPage = useCallback(
     (direction: 'prev' | 'next') => {
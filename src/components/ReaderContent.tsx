@@ -4,7 +4,16 @@ import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
 import { parseSentenceKey, splitIntoSentences, toSpeakableText } from '../utils/sentences';
-import type { Annotation, AnnotationStyle } from '../types';
+import type { Annotation, AnnotationColor, AnnotationStyle } from '../types';
+import {
+  ANNOTATION_COLORS,
+  ANNOTATION_STYLES,
+  annotationClassName,
+  annotationStyleLabel,
+  annotationSwatchClass,
+  matchesAnchorContext,
+  parseTags,
+} from './annotationStyles';
 import {
   READER_THEMES,
   VIEW_SETTINGS_KEY,
@@ -14,17 +23,15 @@ import {
 
 const markdownTranslationKey = (paragraphId: string) => `${paragraphId}__md`;
 const PDF_IMAGE_MARKER_RE = /^\[\[PDF_IMAGE:(.+)\]\]$/;
-const annotationStyleOrder: AnnotationStyle[] = ['single_underline', 'double_underline', 'wavy_strikethrough'];
-const annotationStyleLabel: Record<AnnotationStyle, string> = {
-  single_underline: 'Single Underline',
-  double_underline: 'Double Underline',
-  wavy_strikethrough: 'Wavy Strikethrough',
-};
 
 type SelectionDraft = {
   paragraphId: string;
   selectedText: string;
+  /** Character offset of the selection in the paragraph's rendered text */
+  startOffset: number | null;
   style: AnnotationStyle;
+  color: AnnotationColor | null;
+  tags: string;
   note: string;
 };
 
@@ -54,6 +61,7 @@ const selectionActionIcon: Record<SelectionAction, string> = {
 };
 const DEFAULT_SELECTION_POPOVER_WIDTH = 540;
 const DOUBLE_COLUMN_PAGE_SIZE = 12;
+const READING_POSITION_SAVE_DELAY_MS = 800;
 
 type CodeRule = {
   regex: RegExp;
@@ -222,28 +230,25 @@ const renderTextWithHighlight = (text: string, query: string): ReactNode => {
   });
 };
 
-const annotationClassName = (style: AnnotationStyle) => {
-  if (style === 'double_underline') {
-    return 'decoration-2 underline decoration-double decoration-emerald-600 underline-offset-2';
-  }
-  if (style === 'wavy_strikethrough') {
-    return 'line-through decoration-rose-500 decoration-wavy decoration-2';
-  }
-  return 'underline decoration-2 decoration-sky-600 underline-offset-2';
-};
-
 const renderTextWithAnnotation = (text: string, annotation: Annotation, keyPrefix: string): ReactNode => {
   const target = annotation.selected_text.trim();
   if (!target) return text;
   const regex = new RegExp(`(${escapeRegExp(target)})`, 'ig');
   const parts = text.split(regex);
   if (parts.length <= 1) return text;
+  let consumed = 0;
   return parts.map((part, idx) => {
-    if (part.toLowerCase() === target.toLowerCase()) {
+    const start = consumed;
+    consumed += part.length;
+    // With an anchor only the occurrence the user selected is marked
+    const isMatch =
+      part.toLowerCase() === target.toLowerCase()
+      && (!annotation.anchor || matchesAnchorContext(text.slice(0, start), text.slice(consumed), annotation.anchor));
+    if (isMatch) {
       return (
         <span
           key={`${keyPrefix}-a-${annotation.id}-${idx}`}
-          className={`${annotationClassName(annotation.style)} rounded-sm px-0.5`}
+          className={`${annotationClassName(annotation.style, annotation.color)} rounded-sm px-0.5`}
           title={annotation.note || annotationStyleLabel[annotation.style]}
         >
           {part}
@@ -926,16 +931,26 @@ export function ReaderContent() {
       : origin.parentElement;
     const paragraphEl = baseElement?.closest('[data-paragraph-id]');
     const paragraphId = paragraphEl?.getAttribute('data-paragraph-id');
-    if (!paragraphId) {
+    if (!paragraphEl || !paragraphId) {
       setSelectionDraft(null);
       setSelectionAnchor(null);
       return;
     }
 
+    // Offset of the selection within the paragraph as rendered, in characters
+    const before = document.createRange();
+    before.selectNodeContents(paragraphEl);
+    before.setEnd(range.startContainer, range.startOffset);
+    const leadingSpace = selection.toString().length - selection.toString().trimStart().length;
+    const startOffset = Array.from(before.toString()).length + leadingSpace;
+
     setSelectionDraft({
       paragraphId,
       selectedText: selectedText.slice(0, 300),
-      style: 'single_underline',
+      startOffset,
+      style: 'highlight',
+      color: 'yellow',
+      tags: '',
       note: '',
     });
     setSelectionQuestion('');
@@ -955,11 +970,18 @@ export function ReaderContent() {
   const handleCreateHighlightOnly = async () => {
     if (!selectionDraft) return;
     try {
+      const { startOffset, selectedText } = selectionDraft;
       const created = await invoke<Annotation>('create_annotation', {
-        paragraphId: selectionDraft.paragraphId,
-        selectedText: selectionDraft.selectedText,
-        style: selectionDraft.style,
-        note: '',
+        request: {
+          paragraph_id: selectionDraft.paragraphId,
+          selected_text: selectedText,
+          style: selectionDraft.style,
+          color: selectionDraft.color,
+          note: '',
+          tags: parseTags(selectionDraft.tags),
+          start_offset: startOffset,
+          end_offset: startOffset === null ? null : startOffset + Array.from(selectedText).length,
+        },
       });
       setAnnotationsByParagraph((prev) => {
         const list = prev[created.paragraph_id] || [];
@@ -1230,6 +1252,40 @@ export function ReaderContent() {
     return () => window.clearTimeout(timer);
   }, [columnPageIndex, focusedParagraphId, setFocusedParagraphId]);
 
+  useEffect(() => {
+    const container = contentRef.current;
+    if (!container || !selectedDocumentId || paragraphs.length === 0) return;
+    let timer: number | null = null;
+
+    // Saves the first paragraph whose bottom is below the top of the view
+    const savePosition = () => {
+      timer = null;
+      const top = container.getBoundingClientRect().top;
+      const visible = paragraphs.find((paragraph) => {
+        const el = paragraphRefs.current[paragraph.id];
+        return el ? el.getBoundingClientRect().bottom > top : false;
+      });
+      const scrollable = container.scrollHeight - container.clientHeight;
+      void invoke('save_reading_position', {
+        docId: selectedDocumentId,
+        sectionId: currentSectionId ?? visible?.section_id ?? null,
+        paragraphId: visible?.id ?? null,
+        location: visible?.location ?? null,
+        scrollFraction: scrollable > 0 ? container.scrollTop / scrollable : 0,
+      }).catch((error) => console.warn('Failed to save reading position:', error));
+    };
+    const onScroll = () => {
+      if (timer !== null) window.clearTimeout(timer);
+      timer = window.setTimeout(savePosition, READING_POSITION_SAVE_DELAY_MS);
+    };
+
+    container.addEventListener('scroll', onScroll, { passive: true });
+    return () => {
+      container.removeEventListener('scroll', onScroll);
+      if (timer !== null) window.clearTimeout(timer);
+    };
+  }, [currentSectionId, isLoading, paragraphs, selectedDocumentId]);
+
   if (isLoading) {
     return (
       <div className="flex-1 flex items-center justify-center" style={{ backgroundColor: currentTheme.background }}>
@@ -1662,7 +1718,7 @@ export function ReaderContent() {
           {selectionActionMode === 'highlight' && (
             <>
               <div className="mb-2 flex items-center gap-2 flex-wrap">
-                {annotationStyleOrder.map((style) => (
+                {ANNOTATION_STYLES.map((style) => (
                   <button
                     key={style}
                     onClick={() => setSelectionDraft((prev) => (prev ? { ...prev, style } : prev))}
@@ -1676,6 +1732,25 @@ export function ReaderContent() {
                   </button>
                 ))}
               </div>
+              <div className="mb-2 flex items-center gap-1.5">
+                {ANNOTATION_COLORS.map((color) => (
+                  <button
+                    key={color}
+                    onClick={() => setSelectionDraft((prev) => (prev ? { ...prev, color } : prev))}
+                    className={`h-5 w-5 rounded-full ${annotationSwatchClass(color)} ${
+                      selectionDraft.color === color ? 'ring-2 ring-blue-500 ring-offset-1' : ''
+                    }`}
+                    aria-label={`Colour ${color}`}
+                    title={color}
+                  />
+                ))}
+              </div>
+              <input
+                value={selectionDraft.tags}
+                onChange={(e) => setSelectionDraft((prev) => (prev ? { ...prev, tags: e.target.value } : prev))}
+                placeholder="Tags, comma separated"
+                className="mb-2 w-full rounded border border-gray-300 px-2 py-1 text-xs focus:outline-none focus:ring-2 focus:ring-blue-500"
+              />
               <div className="mb-2 flex justify-end gap-2">
                 <button
                   onClick={() => setSelectionActionMode(null)}
@@ -9,6 +9,7 @@ import {
   type EmbeddingStatus,
 } from '../services/embeddingIndex';
 import { localEmbeddingEngine } from '../services/localEmbedding';
+import type { Collection, Tag } from '../types';
 
 interface SearchResult {
   paragraph_id: string;
@@ -91,6 +92,9 @@ export const SearchPanel: React.FC = () => {
     cannotLocateParagraph: isZh ? '无法在数据库中定位该段落。' : 'Unable to locate this paragraph in database.',
     searchPlaceholder: isZh ? '输入你的查询内容...' : 'Enter your search query...',
     resultsLabel: isZh ? '结果数:' : 'Results:',
+    scopeLabel: isZh ? '范围:' : 'Scope:',
+    scopeDocument: isZh ? '当前文档' : 'This document',
+    scopeLibrary: isZh ? '整个书库' : 'Whole library',
   };
   const {
     documents,
@@ -116,6 +120,10 @@ export const SearchPanel: React.FC = () => {
   const [indexProgress, setIndexProgress] = useState<{ phase: string; done: number; total: number } | null>(null);
   const [showModelDownloadHint, setShowModelDownloadHint] = useState(false);
   const [isDownloadingModel, setIsDownloadingModel] = useState(false);
+  // 'document', 'library', 'collection:<id>' or 'tag:<name>'
+  const [scope, setScope] = useState('document');
+  const [collections, setCollections] = useState<Collection[]>([]);
+  const [tags, setTags] = useState<Tag[]>([]);
   const queryInputRef = useRef<HTMLTextAreaElement | null>(null);
 
   const abortRef = useRef<AbortController | null>(null);
@@ -221,6 +229,24 @@ export const SearchPanel: React.FC = () => {
     return () => window.removeEventListener('reader:focus-search', onFocusSearch as EventListener);
   }, []);
 
+  useEffect(() => {
+    Promise.all([invoke<Collection[]>('list_collections'), invoke<Tag[]>('list_tags')])
+      .then(([nextCollections, nextTags]) => {
+        setCollections(nextCollections);
+        setTags(nextTags);
+      })
+      .catch((e) => console.warn('Failed to load search scopes:', e));
+  }, [documents]);
+
+  const effectiveScope = scope === 'document' && !selectedDocumentId ? 'library' : scope;
+
+  const scopeArgs = () => {
+    if (effectiveScope.startsWith('collection:')) return { collection_id: effectiveScope.slice('collection:'.length) };
+    if (effectiveScope.startsWith('tag:')) return { tag: effectiveScope.slice('tag:'.length) };
+    if (effectiveScope === 'document') return { doc_id: selectedDocumentId };
+    return {};
+  };
+
   const runKeywordFallbackSearch = async () => {
     setSearchMode('keyword-fallback');
     const fallbackResults = await withTimeout(
@@ -228,7 +254,7 @@ export const SearchPanel: React.FC = () => {
         options: {
           query,
           top_k: topK,
-          doc_id: selectedDocumentId || undefined,
+          ...scopeArgs(),
           force_keyword: true,
         },
       }),
@@ -264,7 +290,7 @@ export const SearchPanel: React.FC = () => {
             request: {
               query_vector: vector,
               top_k: topK,
-              doc_id: selectedDocumentId || undefined,
+              ...scopeArgs(),
               query_text: query,
             },
           }),
@@ -431,6 +457,28 @@ export const SearchPanel: React.FC = () => {
             </select>
           </label>
 
+          <label className="flex items-center gap-2 text-sm text-gray-700">
+            <span>{t.scopeLabel}</span>
+            <select
+              value={effectiveScope}
+              onChange={(e) => setScope(e.target.value)}
+              className="max-w-[10rem] border border-gray-300 rounded px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500"
+            >
+              {selectedDocumentId && <option value="document">{t.scopeDocument}</option>}
+              <option value="library">{t.scopeLibrary}</option>
+              {collections.map((collection) => (
+                <option key={collection.id} value={`collection:${collection.id}`}>
+                  {collection.name}
+                </option>
+              ))}
+              {tags.map((tag) => (
+                <option key={tag.id} value={`tag:${tag.name}`}>
+                  #{tag.name}
+                </option>
+              ))}
+            </select>
+          </label>
+
           {selectedDocumentId && (
             <button
               onClick={() => void handleIndexDocument()}
@@ -53,6 +53,10 @@ interface Config {
   reader_background_color: string;
   reader_font_size: number;
   keymap: Keymap;
+  ocr_enabled: boolean;
+  ocr_languages: string;
+  ocr_tesseract_path?: string;
+  managed_library: boolean;
 }
 
 interface SettingsProps {
@@ -137,6 +141,10 @@ export const Settings: React.FC<SettingsProps> = ({ onClose, initialSection = 'r
     reader_background_color: '#F4F8EE',
     reader_font_size: 18,
     keymap: normalizeKeymap(undefined),
+    ocr_enabled: false,
+    ocr_languages: 'eng',
+    ocr_tesseract_path: '',
+    managed_library: false,
   });
   const [readerViewSettings, setReaderViewSettings] = useState<ReaderViewSettings>(() =>
     loadReaderViewSettings(18)
@@ -468,6 +476,28 @@ export const Settings: React.FC<SettingsProps> = ({ onClose, initialSection = 'r
                   description="Automatically expand all details blocks"
                   right={<ToggleSwitch checked={readerViewSettings.expandDetails} onChange={(next) => setReaderViewSettings((prev) => ({ ...prev, expandDetails: next }))} />}
                 />
+                <SettingsDivider />
+                <SettingRow
+                  title="OCR Scanned PDFs"
+                  description="Recognize text on image-only PDF pages with a local tesseract install"
+                  right={<ToggleSwitch checked={config.ocr_enabled} onChange={(next) => setConfig((prev) => ({ ...prev, ocr_enabled: next }))} />}
+                />
+                <SettingRow
+                  title="OCR Languages"
+                  description="Tesseract language codes, e.g. eng+chi_sim"
+                  right={<input className={`${compactControlClass} w-[260px]`} disabled={!config.ocr_enabled} value={config.ocr_languages} onChange={(e) => setConfig((prev) => ({ ...prev, ocr_languages: e.target.value }))} />}
+                />
+                <SettingRow
+                  title="Tesseract Path"
+                  description="Leave empty to find tesseract on the system PATH"
+                  right={<input className={`${compactControlClass} w-[260px]`} disabled={!config.ocr_enabled} value={config.ocr_tesseract_path || ''} onChange={(e) => setConfig((prev) => ({ ...prev, ocr_tesseract_path: e.target.value }))} />}
+                />
+                <SettingsDivider />
+                <SettingRow
+                  title="Managed Library"
+                  description="Copy imported files into the app's library folder so moving or deleting the originals doesn't break documents"
+                  right={<ToggleSwitch checked={config.managed_library} onChange={(next) => setConfig((prev) => ({ ...prev, managed_library: next }))} />}
+                />
               </SettingsCard>
             )}
 
@@ -6,10 +6,11 @@ import { AudiobookPanel } from './AudiobookPanel';
 import { DeepAnalysisPanel } from './DeepAnalysisPanel';
 import { ChatPanel } from './ChatPanel';
 import { NotesPanel } from './NotesPanel';
+import { BookmarksPanel } from './BookmarksPanel';
 import { AnnotationPanel } from './AnnotationPanel';
 import { DictPanel } from './DictPanel';
 
-type Tab = 'search' | 'summary' | 'translate' | 'deep' | 'chat' | 'notes' | 'annotations' | 'dict' | 'audiobook';
+type Tab = 'search' | 'summary' | 'translate' | 'deep' | 'chat' | 'notes' | 'annotations' | 'bookmarks' | 'dict' | 'audiobook';
 
 type ExplainEventDetail = {
   selectedText?: string;
@@ -87,6 +88,7 @@ export const ToolPanel: React.FC<ToolPanelProps> = ({
     { key: 'chat', label: 'Chat', icon: '💬' },
     { key: 'notes', label: 'Notes', icon: '📒' },
     { key: 'annotations', label: 'Marks', icon: '🖍️' },
+    { key: 'bookmarks', label: 'Bookmarks', icon: '🔖' },
     { key: 'audiobook', label: 'Audio', icon: '🎧' },
   ];
 
@@ -329,6 +331,7 @@ export const ToolPanel: React.FC<ToolPanelProps> = ({
             <div className={activeTab === 'chat' ? '' : 'hidden'}><ChatPanel request={chatRequest} /></div>
             <div className={activeTab === 'notes' ? '' : 'hidden'}><NotesPanel request={noteRequest} /></div>
             <div className={activeTab === 'annotations' ? '' : 'hidden'}><AnnotationPanel /></div>
+            <div className={activeTab === 'bookmarks' ? '' : 'hidden'}><BookmarksPanel /></div>
             <div className={activeTab === 'dict' ? '' : 'hidden'}><DictPanel request={dictRequest} /></div>
           </div>
         </>
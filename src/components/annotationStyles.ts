@@ -0,0 +1,65 @@
+import type { AnnotationColor, AnnotationStyle, TextAnchor } from '../types';
+
+export const ANNOTATION_STYLES: AnnotationStyle[] = [
+  'highlight',
+  'single_underline',
+  'double_underline',
+  'wavy_strikethrough',
+];
+
+export const annotationStyleLabel: Record<AnnotationStyle, string> = {
+  highlight: 'Highlight',
+  single_underline: 'Single Underline',
+  double_underline: 'Double Underline',
+  wavy_strikethrough: 'Wavy Strikethrough',
+};
+
+export const ANNOTATION_COLORS: AnnotationColor[] = ['yellow', 'green', 'blue', 'pink', 'purple', 'orange'];
+
+// Full class names so Tailwind keeps them
+const colorClasses: Record<AnnotationColor, { fill: string; decoration: string; swatch: string }> = {
+  yellow: { fill: 'bg-yellow-200', decoration: 'decoration-yellow-500', swatch: 'bg-yellow-300' },
+  green: { fill: 'bg-green-200', decoration: 'decoration-green-600', swatch: 'bg-green-400' },
+  blue: { fill: 'bg-sky-200', decoration: 'decoration-sky-600', swatch: 'bg-sky-400' },
+  pink: { fill: 'bg-pink-200', decoration: 'decoration-pink-500', swatch: 'bg-pink-400' },
+  purple: { fill: 'bg-violet-200', decoration: 'decoration-violet-600', swatch: 'bg-violet-400' },
+  orange: { fill: 'bg-orange-200', decoration: 'decoration-orange-500', swatch: 'bg-orange-400' },
+};
+
+export const annotationSwatchClass = (color: AnnotationColor) => colorClasses[color].swatch;
+
+export const annotationClassName = (style: AnnotationStyle, color?: AnnotationColor | null) => {
+  if (style === 'highlight') {
+    return `${colorClasses[color ?? 'yellow'].fill} text-inherit`;
+  }
+  if (style === 'double_underline') {
+    return `decoration-2 underline decoration-double ${color ? colorClasses[color].decoration : 'decoration-emerald-600'} underline-offset-2`;
+  }
+  if (style === 'wavy_strikethrough') {
+    return `line-through ${color ? colorClasses[color].decoration : 'decoration-rose-500'} decoration-wavy decoration-2`;
+  }
+  return `underline decoration-2 ${color ? colorClasses[color].decoration : 'decoration-sky-600'} underline-offset-2`;
+};
+
+/** Characters of stored context compared when telling repeated phrases apart */
+const ANCHOR_CONTEXT_CHARS = 12;
+
+/**
+ * Whether the text around one occurrence of an annotation's phrase agrees with
+ * its anchor. `before` and `after` may be cut short at sentence or markup
+ * boundaries, so only the overlapping part is compared.
+ */
+export const matchesAnchorContext = (before: string, after: string, anchor: TextAnchor) => {
+  const prefix = anchor.prefix.slice(-ANCHOR_CONTEXT_CHARS);
+  const suffix = anchor.suffix.slice(0, ANCHOR_CONTEXT_CHARS);
+  const beforeOk = before.length >= prefix.length ? before.endsWith(prefix) : prefix.endsWith(before);
+  const afterOk = after.length >= suffix.length ? after.startsWith(suffix) : suffix.startsWith(after);
+  return beforeOk && afterOk;
+};
+
+/** Parses comma-separated tags as typed by the user */
+export const parseTags = (input: string) =>
+  input
+    .split(',')
+    .map((tag) => tag.trim())
+    .filter(Boolean);
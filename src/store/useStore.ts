@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { Document, Section, Paragraph } from '../types';
+import type { Document, Section, Paragraph, PdfImportResult } from '../types';
 import { defaultKeymap, normalizeKeymap, type Keymap } from '../utils/shortcuts';
 
 export type TranslationMode = 'off' | 'en-zh' | 'zh-en';
@@ -35,7 +35,7 @@ const normalizeTranslationMode = (mode?: string): TranslationMode => {
 interface ReaderState {
   documents: Document[];
   selectedDocumentId: string | null;
-  currentDocumentType: 'epub' | 'pdf' | 'markdown' | null;
+  currentDocumentType: 'epub' | 'pdf' | 'markdown' | 'docx' | 'html' | 'text' | 'fb2' | 'mobi' | null;
   isLoading: boolean;
 
   // Reader state
@@ -62,8 +62,16 @@ interface ReaderState {
   loadDocuments: () => Promise<void>;
   selectDocument: (id: string) => void;
   importEpub: (filePath: string) => Promise<string>;
-  importPdf: (filePath: string) => Promise<string>;
+  importPdf: (filePath: string) => Promise<PdfImportResult>;
   importMarkdown: (filePath: string) => Promise<string>;
+  importDocx: (filePath: string) => Promise<string>;
+  importHtml: (filePath: string) => Promise<string>;
+  importText: (filePath: string) => Promise<string>;
+  importFb2: (filePath: string) => Promise<string>;
+  importMobi: (filePath: string) => Promise<string>;
+  importNotebook: (filePath: string) => Promise<string>;
+  importRst: (filePath: string) => Promise<string>;
+  importAsciidoc: (filePath: string) => Promise<string>;
   deleteDocument: (id: string) => Promise<void>;
 
   // Reader actions
@@ -173,10 +181,10 @@ export const useStore = create<ReaderState>((set, get) => ({
   importPdf: async (filePath: string) => {
     set({ isLoading: true });
     try {
-      const docId = await invoke<string>('import_pdf', { filePath });
+      const result = await invoke<PdfImportResult>('import_pdf', { filePath });
       await get().loadDocuments();
       set({ isLoading: false });
-      return docId;
+      return result;
     } catch (error) {
       console.error('Failed to import PDF:', error);
       set({ isLoading: false });
@@ -198,6 +206,118 @@ export const useStore = create<ReaderState>((set, get) => ({
     }
   },
 
+  importDocx: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_docx', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import DOCX:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importHtml: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_html', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import HTML:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importText: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_text', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import text file:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importFb2: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_fb2', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import FB2:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importMobi: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_mobi', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import MOBI:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importNotebook: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_notebook', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import notebook:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importRst: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_rst', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import reStructuredText:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
+  importAsciidoc: async (filePath: string) => {
+    set({ isLoading: true });
+    try {
+      const docId = await invoke<string>('import_asciidoc', { filePath });
+      await get().loadDocuments();
+      set({ isLoading: false });
+      return docId;
+    } catch (error) {
+      console.error('Failed to import AsciiDoc:', error);
+      set({ isLoading: false });
+      throw error;
+    }
+  },
+
   deleteDocument: async (id: string) => {
     set({ isLoading: true });
     try {
@@ -4,11 +4,105 @@ export interface Document {
   author?: string;
   language?: string;
   file_path: string;
-  file_type: 'epub' | 'pdf' | 'markdown';
+  file_type: 'epub' | 'pdf' | 'markdown' | 'docx' | 'html' | 'text' | 'fb2' | 'mobi';
+  content_hash?: string | null;
+  original_path?: string | null;
+  series?: string | null;
+  series_index?: number | null;
+  tags?: string[];
+  collection_ids?: string[];
+  subtitle?: string | null;
+  publisher?: string | null;
+  published_date?: string | null;
+  isbn?: string | null;
+  doi?: string | null;
+  description?: string | null;
+  rating?: number | null;
   created_at: number;
   updated_at: number;
 }
 
+/** Fields accepted by `update_document`; omitted fields are kept, empty strings clear them */
+export interface DocumentUpdate {
+  title?: string;
+  author?: string;
+  language?: string;
+  subtitle?: string;
+  publisher?: string;
+  published_date?: string;
+  isbn?: string;
+  doi?: string;
+  description?: string;
+  rating?: number;
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  document_count: number;
+  created_at: number;
+}
+
+export interface Collection {
+  id: string;
+  name: string;
+  description?: string | null;
+  document_count: number;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface SeriesInfo {
+  name: string;
+  document_count: number;
+}
+
+export interface DocumentFilter {
+  tag?: string;
+  collection_id?: string;
+  series?: string;
+}
+
+export interface UnmatchedHighlight {
+  book_title: string;
+  text: string;
+  location?: string | null;
+  reason: string;
+}
+
+export interface HighlightImportResult {
+  total: number;
+  imported: number;
+  skipped_duplicates: number;
+  documents: string[];
+  unmatched: UnmatchedHighlight[];
+}
+
+export type NotesExportFormat = 'markdown' | 'obsidian' | 'json' | 'w3c';
+
+export interface NotesExportResult {
+  documents: number;
+  annotations: number;
+  bookmarks: number;
+  files: string[];
+}
+
+export interface NotesImportResult {
+  documents: string[];
+  annotations: number;
+  bookmarks: number;
+  skipped_duplicates: number;
+  unmatched_documents: string[];
+  unplaced: string[];
+}
+
+/** Target of a `com.mac.reader://open` link from exported notes */
+export interface DeepLinkTarget {
+  doc_id: string;
+  section_id: string | null;
+  paragraph_id: string | null;
+}
+
 export interface Section {
   id: string;
   doc_id: string;
@@ -24,23 +118,138 @@ export interface Paragraph {
   order_index: number;
   text: string;
   location: string;
+  kind?: 'text' | 'ocr';
 }
 
 export interface ImportResult {
   docId: string;
 }
 
+export interface PdfImportResult {
+  doc_id: string;
+  ocr_pages: number[];
+}
+
 export type AnnotationStyle =
+  | 'highlight'
   | 'single_underline'
   | 'double_underline'
   | 'wavy_strikethrough';
 
+export type AnnotationColor = 'yellow' | 'green' | 'blue' | 'pink' | 'purple' | 'orange';
+
+/** Where an annotation sits in its paragraph; offsets count Unicode characters */
+export interface TextAnchor {
+  start: number;
+  end: number;
+  prefix: string;
+  suffix: string;
+}
+
 export interface Annotation {
   id: string;
   paragraph_id: string;
   selected_text: string;
   style: AnnotationStyle;
+  color?: AnnotationColor | null;
+  note?: string | null;
+  tags: string[];
+  anchor?: TextAnchor | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface CalibreImportResult {
+  total: number;
+  imported: string[];
+  skipped_existing: number;
+  skipped_unsupported: string[];
+  failed: { title: string; error: string }[];
+}
+
+export interface ReimportResult {
+  doc_id: string;
+  paragraphs: number;
+  paragraphs_matched: number;
+  annotations_kept: number;
+  annotations_dropped: string[];
+  bookmarks_kept: number;
+  translations_kept: number;
+  embeddings_kept: number;
+}
+
+export interface FolderImportResult {
+  folder: string;
+  scanned: number;
+  imported: string[];
+  updated: string[];
+  skipped_existing: number;
+  unsupported: number;
+  failed: { path: string; error: string }[];
+}
+
+export interface RepairResult {
+  relocated: string[];
+  still_missing: string[];
+}
+
+export type BookmarkColor = 'red' | 'orange' | 'yellow' | 'green' | 'blue' | 'purple';
+
+export interface Bookmark {
+  id: string;
+  doc_id: string;
+  document_title: string;
+  section_id: string;
+  section_title: string;
+  paragraph_id: string;
+  location: string;
+  title: string;
   note?: string | null;
+  color?: BookmarkColor | null;
   created_at: number;
   updated_at: number;
 }
+
+export interface ReadingPosition {
+  doc_id: string;
+  section_id?: string | null;
+  paragraph_id?: string | null;
+  location?: string | null;
+  scroll_fraction: number;
+  updated_at: number;
+}
+
+export interface RecentlyRead {
+  document: Document;
+  position: ReadingPosition;
+  section_title?: string | null;
+}
+
+export interface WatchedFolder {
+  id: string;
+  path: string;
+  recursive: boolean;
+  last_scanned_at?: number | null;
+  created_at: number;
+}
+
+export interface Feed {
+  id: string;
+  url: string;
+  title: string;
+  site_url?: string | null;
+  poll_interval_minutes?: number | null;
+  last_polled_at?: number | null;
+  last_error?: string | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface FeedRefreshResult {
+  feed_id: string;
+  title: string;
+  imported: string[];
+  existing: string[];
+  failed: { title: string; link?: string | null; error: string }[];
+  error?: string | null;
+}